@@ -0,0 +1,174 @@
+//! AES-CTR keystream computed in a garbled circuit VM.
+
+use mpz_circuits::circuits::AES128;
+use mpz_garble::{value::ValueRef, DecodePrivate, Execute, Memory};
+
+use crate::AeadError;
+
+/// The size of an AES block in bytes.
+pub const BLOCK_LEN: usize = 16;
+
+/// Computes AES encryptions of public blocks under a key held in a VM.
+///
+/// The key is a `[u8; 16]` value in the memory of the VM, so how it is provided is up to the
+/// caller, eg. as one party's private input or as the output of a circuit which combines shares
+/// of the key.
+///
+/// Both parties must call the same methods in the same order.
+#[derive(Debug)]
+pub struct AesCtr<T> {
+    id: String,
+    thread: T,
+    key: ValueRef,
+    counter: usize,
+}
+
+impl<T> AesCtr<T> {
+    /// Creates a new instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - A unique id used to name the values of this instance in the VM.
+    /// * `thread` - The VM thread.
+    /// * `key` - The key, a `[u8; 16]` value in the memory of `thread`.
+    pub fn new(id: &str, thread: T, key: ValueRef) -> Self {
+        Self {
+            id: id.to_string(),
+            thread,
+            key,
+            counter: 0,
+        }
+    }
+
+    /// Returns a mutable reference to the VM thread.
+    pub fn thread_mut(&mut self) -> &mut T {
+        &mut self.thread
+    }
+
+    /// Returns the VM thread.
+    pub fn into_inner(self) -> T {
+        self.thread
+    }
+}
+
+impl<T> AesCtr<T>
+where
+    T: Memory + Execute + DecodePrivate + Send,
+{
+    /// Encrypts public blocks, returning this party's XOR shares of the ciphertexts.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - The blocks to encrypt.
+    pub async fn encrypt_public_shared(
+        &mut self,
+        blocks: &[[u8; BLOCK_LEN]],
+    ) -> Result<Vec<[u8; BLOCK_LEN]>, AeadError> {
+        let mut outputs = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let id = format!("{}/{}", self.id, self.counter);
+            self.counter += 1;
+
+            let msg = self
+                .thread
+                .new_public_input::<[u8; BLOCK_LEN]>(&format!("{id}/msg"))?;
+            let output = self
+                .thread
+                .new_output::<[u8; BLOCK_LEN]>(&format!("{id}/output"))?;
+
+            self.thread.assign(&msg, *block)?;
+            self.thread
+                .execute(
+                    AES128.clone(),
+                    &[self.key.clone(), msg],
+                    std::slice::from_ref(&output),
+                )
+                .await?;
+
+            outputs.push(output);
+        }
+
+        self.thread
+            .decode_shared(&outputs)
+            .await?
+            .into_iter()
+            .map(|value| <[u8; BLOCK_LEN]>::try_from(value).map_err(AeadError::from))
+            .collect()
+    }
+
+    /// Computes keystream blocks, returning this party's XOR shares.
+    ///
+    /// Block `i` of the keystream is the encryption of `nonce || start_ctr + i`, with the
+    /// counter encoded as a big-endian `u32`.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - The nonce.
+    /// * `start_ctr` - The counter of the first block.
+    /// * `count` - The number of blocks.
+    pub async fn keystream_shared(
+        &mut self,
+        nonce: [u8; 12],
+        start_ctr: u32,
+        count: usize,
+    ) -> Result<Vec<[u8; BLOCK_LEN]>, AeadError> {
+        let blocks = (0..count as u32)
+            .map(|i| counter_block(nonce, start_ctr.wrapping_add(i)))
+            .collect::<Vec<_>>();
+
+        self.encrypt_public_shared(&blocks).await
+    }
+}
+
+/// Returns the counter block `nonce || ctr`.
+pub(crate) fn counter_block(nonce: [u8; 12], ctr: u32) -> [u8; BLOCK_LEN] {
+    let mut block = [0u8; BLOCK_LEN];
+    block[..12].copy_from_slice(&nonce);
+    block[12..].copy_from_slice(&ctr.to_be_bytes());
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aes::{
+        cipher::{BlockEncrypt, KeyInit},
+        Aes128,
+    };
+    use mpz_garble::protocol::deap::mock::create_mock_deap_vm;
+
+    #[tokio::test]
+    async fn test_keystream_shared() {
+        let key = [42u8; 16];
+        let nonce = [7u8; 12];
+
+        let (leader, follower) = create_mock_deap_vm();
+
+        let leader_key = leader.new_private_input::<[u8; 16]>("key").unwrap();
+        leader.assign(&leader_key, key).unwrap();
+        let follower_key = follower.new_blind_input::<[u8; 16]>("key").unwrap();
+
+        let mut leader = AesCtr::new("aes", leader, leader_key);
+        let mut follower = AesCtr::new("aes", follower, follower_key);
+
+        let (leader_shares, follower_shares) = futures::join!(
+            leader.keystream_shared(nonce, 2, 3),
+            follower.keystream_shared(nonce, 2, 3)
+        );
+
+        let aes = Aes128::new(&key.into());
+        for (i, (a, b)) in leader_shares
+            .unwrap()
+            .into_iter()
+            .zip(follower_shares.unwrap())
+            .enumerate()
+        {
+            let mut expected = counter_block(nonce, 2 + i as u32).into();
+            aes.encrypt_block(&mut expected);
+
+            let actual: Vec<u8> = a.iter().zip(b).map(|(a, b)| a ^ b).collect();
+            assert_eq!(actual, expected.to_vec());
+        }
+    }
+}
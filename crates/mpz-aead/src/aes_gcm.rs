@@ -0,0 +1,297 @@
+//! AES-GCM with a key held in a garbled circuit VM.
+//!
+//! The hash key and the tag masks are computed in the VM and returned XOR-shared, and the GHASH
+//! of a message is computed with [`Ghash`]. The parties either keep their shares of a tag or
+//! exchange them to reveal the tag to both parties.
+
+use mpz_common::Context;
+use mpz_fields::gf2_128::Gf2_128;
+use mpz_garble::{DecodePrivate, Execute, Memory};
+use mpz_share_conversion::ShareConvert;
+use serio::{stream::IoStreamExt, SinkExt as _};
+
+use crate::{
+    aes_ctr::{counter_block, AesCtr, BLOCK_LEN},
+    ghash::Ghash,
+    AeadError,
+};
+
+/// The size of a nonce in bytes.
+pub const NONCE_LEN: usize = 12;
+
+/// The size of a tag in bytes.
+pub const TAG_LEN: usize = 16;
+
+/// AES-GCM with a key held in a garbled circuit VM.
+///
+/// Both parties must call the same methods in the same order.
+#[derive(Debug)]
+pub struct AesGcm<T, C> {
+    ctr: AesCtr<T>,
+    ghash: Ghash<C>,
+}
+
+impl<T, C> AesGcm<T, C>
+where
+    T: Memory + Execute + DecodePrivate + Send,
+{
+    /// Creates a new instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctr` - AES-CTR over the key.
+    /// * `converter` - The share converter for GF(2^128).
+    pub fn new(ctr: AesCtr<T>, converter: C) -> Self {
+        Self {
+            ctr,
+            ghash: Ghash::new(converter),
+        }
+    }
+
+    /// Computes the shares of the hash key and sets up GHASH.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context used for share conversion.
+    /// * `max_len` - The maximum combined length in bytes of the AAD and ciphertext of a message.
+    pub async fn setup<Ctx>(&mut self, ctx: &mut Ctx, max_len: usize) -> Result<(), AeadError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, Gf2_128> + Send,
+    {
+        let [h] = self
+            .ctr
+            .encrypt_public_shared(&[[0u8; BLOCK_LEN]])
+            .await?
+            .try_into()
+            .expect("one block was encrypted");
+
+        // Padding of the AAD and the ciphertext, and the length block.
+        let max_blocks = max_len.div_ceil(BLOCK_LEN) + 2;
+
+        self.ghash.setup(ctx, h, max_blocks).await
+    }
+
+    /// Computes this party's XOR share of the keystream for a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - The nonce.
+    /// * `len` - The length of the message in bytes.
+    pub async fn keystream_shared(
+        &mut self,
+        nonce: [u8; NONCE_LEN],
+        len: usize,
+    ) -> Result<Vec<u8>, AeadError> {
+        let mut keystream = self
+            .ctr
+            .keystream_shared(nonce, 2, len.div_ceil(BLOCK_LEN))
+            .await?
+            .concat();
+        keystream.truncate(len);
+
+        Ok(keystream)
+    }
+
+    /// Computes this party's XOR share of the tag of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - The nonce.
+    /// * `aad` - The additional authenticated data.
+    /// * `ciphertext` - The ciphertext.
+    pub async fn tag_share(
+        &mut self,
+        nonce: [u8; NONCE_LEN],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<[u8; TAG_LEN], AeadError> {
+        let ghash = self.ghash.compute_share(aad, ciphertext)?;
+        let [mask] = self
+            .ctr
+            .encrypt_public_shared(&[counter_block(nonce, 1)])
+            .await?
+            .try_into()
+            .expect("one block was encrypted");
+
+        Ok(std::array::from_fn(|i| ghash[i] ^ mask[i]))
+    }
+
+    /// Computes the tag of a message, revealing it to both parties.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context used to exchange the tag shares.
+    /// * `nonce` - The nonce.
+    /// * `aad` - The additional authenticated data.
+    /// * `ciphertext` - The ciphertext.
+    pub async fn compute_tag<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        nonce: [u8; NONCE_LEN],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<[u8; TAG_LEN], AeadError> {
+        let share = self.tag_share(nonce, aad, ciphertext).await?;
+
+        ctx.io_mut().send(share).await?;
+        let peer_share: [u8; TAG_LEN] = ctx.io_mut().expect_next().await?;
+
+        Ok(std::array::from_fn(|i| share[i] ^ peer_share[i]))
+    }
+
+    /// Verifies the tag of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context used to exchange the tag shares.
+    /// * `nonce` - The nonce.
+    /// * `aad` - The additional authenticated data.
+    /// * `ciphertext` - The ciphertext.
+    /// * `tag` - The purported tag.
+    pub async fn verify_tag<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        nonce: [u8; NONCE_LEN],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: [u8; TAG_LEN],
+    ) -> Result<(), AeadError> {
+        let expected = self.compute_tag(ctx, nonce, aad, ciphertext).await?;
+
+        if expected == tag {
+            Ok(())
+        } else {
+            Err(AeadError::InvalidTag)
+        }
+    }
+
+    /// Returns the VM thread.
+    pub fn into_inner(self) -> T {
+        self.ctr.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aes::{
+        cipher::{BlockEncrypt, KeyInit},
+        Aes128,
+    };
+    use ghash_rc::{
+        universal_hash::{NewUniversalHash, UniversalHash},
+        GHash,
+    };
+    use mpz_common::executor::test_st_executor;
+    use mpz_garble::protocol::deap::mock::create_mock_deap_vm;
+    use mpz_share_conversion::ideal::ideal_share_converter;
+
+    /// Encrypts a message with AES-GCM, returning the ciphertext and the tag.
+    fn reference_aes_gcm(
+        key: [u8; 16],
+        nonce: [u8; NONCE_LEN],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> (Vec<u8>, [u8; TAG_LEN]) {
+        let aes = Aes128::new(&key.into());
+        let encrypt = |block: [u8; 16]| {
+            let mut block = block.into();
+            aes.encrypt_block(&mut block);
+            <[u8; 16]>::from(block)
+        };
+
+        let ciphertext = msg
+            .chunks(BLOCK_LEN)
+            .enumerate()
+            .flat_map(|(i, chunk)| {
+                let keystream = encrypt(counter_block(nonce, 2 + i as u32));
+                chunk
+                    .iter()
+                    .zip(keystream)
+                    .map(|(a, b)| a ^ b)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ghash = GHash::new(&encrypt([0; 16]).into());
+        ghash.update_padded(aad);
+        ghash.update_padded(&ciphertext);
+        let mut lengths = [0u8; 16];
+        lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+        lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+        ghash.update(&lengths.into());
+        let ghash: [u8; 16] = ghash.finalize().into_bytes().into();
+
+        let mask = encrypt(counter_block(nonce, 1));
+        let tag = std::array::from_fn(|i| ghash[i] ^ mask[i]);
+
+        (ciphertext, tag)
+    }
+
+    #[tokio::test]
+    async fn test_aes_gcm() {
+        let key = [42u8; 16];
+        let nonce = [7u8; NONCE_LEN];
+        let aad = b"header";
+        let msg = b"a message which spans more than one block";
+
+        let (expected_ciphertext, expected_tag) = reference_aes_gcm(key, nonce, aad, msg);
+
+        let (leader, follower) = create_mock_deap_vm();
+        let leader_key = leader.new_private_input::<[u8; 16]>("key").unwrap();
+        leader.assign(&leader_key, key).unwrap();
+        let follower_key = follower.new_blind_input::<[u8; 16]>("key").unwrap();
+
+        let (converter_leader, converter_follower) = ideal_share_converter();
+        let mut leader = AesGcm::new(AesCtr::new("gcm", leader, leader_key), converter_leader);
+        let mut follower = AesGcm::new(
+            AesCtr::new("gcm", follower, follower_key),
+            converter_follower,
+        );
+
+        let (mut ctx_leader, mut ctx_follower) = test_st_executor(8);
+
+        tokio::try_join!(
+            leader.setup(&mut ctx_leader, 128),
+            follower.setup(&mut ctx_follower, 128)
+        )
+        .unwrap();
+
+        let (leader_keystream, follower_keystream) = tokio::try_join!(
+            leader.keystream_shared(nonce, msg.len()),
+            follower.keystream_shared(nonce, msg.len())
+        )
+        .unwrap();
+
+        let ciphertext = msg
+            .iter()
+            .zip(leader_keystream)
+            .zip(follower_keystream)
+            .map(|((m, a), b)| m ^ a ^ b)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ciphertext, expected_ciphertext);
+
+        let (leader_tag, follower_tag) = tokio::try_join!(
+            leader.compute_tag(&mut ctx_leader, nonce, aad, &ciphertext),
+            follower.compute_tag(&mut ctx_follower, nonce, aad, &ciphertext)
+        )
+        .unwrap();
+
+        assert_eq!(leader_tag, expected_tag);
+        assert_eq!(follower_tag, expected_tag);
+
+        let mut invalid_tag = expected_tag;
+        invalid_tag[0] ^= 1;
+
+        let (leader_result, follower_result) = futures::join!(
+            leader.verify_tag(&mut ctx_leader, nonce, aad, &ciphertext, invalid_tag),
+            follower.verify_tag(&mut ctx_follower, nonce, aad, &ciphertext, expected_tag)
+        );
+
+        assert!(matches!(leader_result, Err(AeadError::InvalidTag)));
+        follower_result.unwrap();
+    }
+}
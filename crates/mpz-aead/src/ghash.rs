@@ -0,0 +1,190 @@
+//! GHASH computed from shares of the hash key.
+//!
+//! The parties hold XOR shares of the hash key `H`. During setup the shares are converted into
+//! multiplicative shares, from which each party locally computes multiplicative shares of the
+//! powers of `H`, and these are converted back into additive shares. As GHASH is linear in the
+//! powers of `H`, each party can then compute a share of the GHASH of any public message without
+//! further interaction.
+//!
+//! The number of powers computed during setup bounds the length of the messages which can be
+//! hashed.
+
+use mpz_common::Context;
+use mpz_core::Block;
+use mpz_fields::{gf2_128::Gf2_128, Field};
+use mpz_share_conversion::ShareConvert;
+
+use crate::{aes_ctr::BLOCK_LEN, AeadError};
+
+/// GHASH over shares of the hash key.
+#[derive(Debug)]
+pub struct Ghash<C> {
+    converter: C,
+    /// Additive shares of `H^1, ..., H^n`.
+    powers: Option<Vec<Gf2_128>>,
+}
+
+impl<C> Ghash<C> {
+    /// Creates a new instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `converter` - The share converter for GF(2^128).
+    pub fn new(converter: C) -> Self {
+        Self {
+            converter,
+            powers: None,
+        }
+    }
+
+    /// Returns the maximum number of blocks which can be hashed, including the length block.
+    pub fn max_blocks(&self) -> usize {
+        self.powers.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Sets up GHASH with this party's share of the hash key.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `key_share` - This party's XOR share of the hash key.
+    /// * `max_blocks` - The maximum number of blocks to support, including the length block.
+    pub async fn setup<Ctx>(
+        &mut self,
+        ctx: &mut Ctx,
+        key_share: [u8; BLOCK_LEN],
+        max_blocks: usize,
+    ) -> Result<(), AeadError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, Gf2_128> + Send,
+    {
+        let h = to_field(key_share);
+        let h = self.converter.to_multiplicative(ctx, vec![h]).await?[0];
+
+        let mut powers = Vec::with_capacity(max_blocks);
+        let mut power = h;
+        for _ in 0..max_blocks {
+            powers.push(power);
+            power = power * h;
+        }
+
+        self.powers = Some(self.converter.to_additive(ctx, powers).await?);
+
+        Ok(())
+    }
+
+    /// Computes this party's share of the GHASH of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `aad` - The additional authenticated data.
+    /// * `ciphertext` - The ciphertext.
+    pub fn compute_share(
+        &self,
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<[u8; BLOCK_LEN], AeadError> {
+        let powers = self.powers.as_ref().ok_or(AeadError::NotSetup)?;
+        let blocks = ghash_blocks(aad, ciphertext);
+
+        if blocks.len() > powers.len() {
+            return Err(AeadError::MessageTooLong {
+                max: powers.len(),
+                actual: blocks.len(),
+            });
+        }
+
+        // GHASH(X_1, ..., X_m) = X_1 * H^m + ... + X_m * H
+        let share = blocks
+            .iter()
+            .zip(powers[..blocks.len()].iter().rev())
+            .fold(Gf2_128::zero(), |acc, (block, power)| {
+                acc + to_field(*block) * *power
+            });
+
+        Ok(from_field(share))
+    }
+}
+
+/// Returns the blocks hashed by GCM: the padded AAD, the padded ciphertext, and the lengths.
+fn ghash_blocks(aad: &[u8], ciphertext: &[u8]) -> Vec<[u8; BLOCK_LEN]> {
+    let padded = |data: &[u8]| {
+        data.chunks(BLOCK_LEN)
+            .map(|chunk| {
+                let mut block = [0u8; BLOCK_LEN];
+                block[..chunk.len()].copy_from_slice(chunk);
+                block
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut lengths = [0u8; BLOCK_LEN];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+
+    let mut blocks = padded(aad);
+    blocks.extend(padded(ciphertext));
+    blocks.push(lengths);
+    blocks
+}
+
+/// GHASH reflects the bits of the blocks relative to the polynomial representation of the field.
+fn to_field(block: [u8; BLOCK_LEN]) -> Gf2_128 {
+    Block::new(block).reverse_bits().into()
+}
+
+fn from_field(value: Gf2_128) -> [u8; BLOCK_LEN] {
+    Block::from(value).reverse_bits().to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ghash_rc::{
+        universal_hash::{NewUniversalHash, UniversalHash},
+        GHash,
+    };
+    use mpz_common::executor::test_st_executor;
+    use mpz_share_conversion::ideal::ideal_share_converter;
+
+    fn reference_ghash(h: [u8; 16], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+        let mut ghash = GHash::new(&h.into());
+        for block in ghash_blocks(aad, ciphertext) {
+            ghash.update(&block.into());
+        }
+        ghash.finalize().into_bytes().into()
+    }
+
+    #[tokio::test]
+    async fn test_ghash() {
+        let h = [3u8; 16];
+        let h_sender = [17u8; 16];
+        let h_receiver: [u8; 16] = std::array::from_fn(|i| h[i] ^ h_sender[i]);
+
+        let (converter_sender, converter_receiver) = ideal_share_converter();
+        let mut sender = Ghash::new(converter_sender);
+        let mut receiver = Ghash::new(converter_receiver);
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        tokio::try_join!(
+            sender.setup(&mut ctx_sender, h_sender, 8),
+            receiver.setup(&mut ctx_receiver, h_receiver, 8)
+        )
+        .unwrap();
+
+        let aad = b"additional data";
+        let ciphertext = b"some ciphertext which spans multiple blocks";
+
+        let a = sender.compute_share(aad, ciphertext).unwrap();
+        let b = receiver.compute_share(aad, ciphertext).unwrap();
+        let actual: [u8; 16] = std::array::from_fn(|i| a[i] ^ b[i]);
+
+        assert_eq!(actual, reference_ghash(h, aad, ciphertext));
+        assert!(matches!(
+            sender.compute_share(&[0; 80], ciphertext),
+            Err(AeadError::MessageTooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! Secret-shared AEAD.
+//!
+//! This crate provides protocols for two parties to encrypt and authenticate messages with a key
+//! which neither party knows. It is built from the garbling and share conversion protocols in
+//! this workspace:
+//!
+//! - [`aes_ctr`] computes AES-CTR keystream blocks by evaluating AES in a garbled circuit VM, and
+//!   returns them XOR-shared between the parties.
+//! - [`ghash`] computes shares of GHASH tags from shares of the hash key using share conversion
+//!   in GF(2^128). After a one-time setup, computing a tag requires no interaction.
+//! - [`aes_gcm`] combines the two into AES-GCM.
+//!
+//! Values which are shared between the parties are XOR shares, ie. additive shares in GF(2^128).
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+pub mod aes_ctr;
+pub mod aes_gcm;
+pub mod ghash;
+
+use mpz_circuits::types::TypeError;
+use mpz_garble::{DecodeError, ExecutionError, MemoryError};
+use mpz_share_conversion::ShareConversionError;
+
+/// An AEAD error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum AeadError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    ShareConversion(#[from] ShareConversionError),
+    #[error(transparent)]
+    Type(#[from] TypeError),
+    #[error("GHASH is not set up")]
+    NotSetup,
+    #[error("message of {actual} blocks exceeds the maximum of {max} blocks")]
+    MessageTooLong { max: usize, actual: usize },
+    #[error("invalid tag")]
+    InvalidTag,
+}
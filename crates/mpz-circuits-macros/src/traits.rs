@@ -34,6 +34,11 @@ impl IsPrimitiveType for Type {
                     || path == "u32"
                     || path == "u64"
                     || path == "u128"
+                    || path == "i8"
+                    || path == "i16"
+                    || path == "i32"
+                    || path == "i64"
+                    || path == "i128"
                     || path == "bool"
             }
             _ => false,
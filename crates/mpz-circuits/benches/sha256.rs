@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use mpz_circuits::{circuits::build_sha256, types::Value};
+use mpz_circuits::{circuits::build_sha256, types::Value, Circuit};
 
 fn criterion_benchmark(c: &mut Criterion) {
     let length = 512;
@@ -21,6 +21,17 @@ fn criterion_benchmark(c: &mut Criterion) {
             )
         })
     });
+
+    let batch = vec![
+        vec![
+            Value::Array(vec![Value::U32(0); 8]),
+            Value::Array(vec![Value::U8(0); length]),
+        ];
+        Circuit::BATCH_LANES
+    ];
+    c.bench_function("compute_sha256_batch", |bench| {
+        bench.iter(|| black_box(sha256.evaluate_batch(&batch).unwrap()))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,110 @@
+//! Static analysis of circuits.
+
+use crate::{components::Gate, Circuit};
+
+/// Statistics of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircuitStats {
+    /// Number of AND gates.
+    pub and_count: usize,
+    /// Number of XOR gates.
+    pub xor_count: usize,
+    /// Number of INV gates.
+    pub inv_count: usize,
+    /// Number of input bits.
+    pub input_bits: usize,
+    /// Number of output bits.
+    pub output_bits: usize,
+    /// Length of the longest path from an input to an output, counting all gates.
+    pub depth: usize,
+    /// Largest number of AND gates on any path from an input to an output.
+    pub and_depth: usize,
+}
+
+impl Circuit {
+    /// Computes statistics of the circuit.
+    pub fn stats(&self) -> CircuitStats {
+        // (depth, and depth) of every feed.
+        let mut depths = vec![(0usize, 0usize); self.feed_count];
+        let mut inv_count = 0;
+
+        for gate in &self.gates {
+            let (depth, and_depth) = match gate {
+                Gate::Xor { x, y, .. } => {
+                    let (x, y) = (depths[x.id], depths[y.id]);
+                    (x.0.max(y.0) + 1, x.1.max(y.1))
+                }
+                Gate::And { x, y, .. } => {
+                    let (x, y) = (depths[x.id], depths[y.id]);
+                    (x.0.max(y.0) + 1, x.1.max(y.1) + 1)
+                }
+                Gate::Inv { x, .. } => {
+                    inv_count += 1;
+                    let x = depths[x.id];
+                    (x.0 + 1, x.1)
+                }
+            };
+
+            depths[gate.z().id] = (depth, and_depth);
+        }
+
+        let (depth, and_depth) = self
+            .outputs
+            .iter()
+            .flat_map(|output| output.iter())
+            .map(|node| depths[node.id])
+            .fold((0, 0), |acc, node| (acc.0.max(node.0), acc.1.max(node.1)));
+
+        CircuitStats {
+            and_count: self.and_count,
+            xor_count: self.xor_count,
+            inv_count,
+            input_bits: self.inputs.iter().map(|input| input.len()).sum(),
+            output_bits: self.outputs.iter().map(|output| output.len()).sum(),
+            depth,
+            and_depth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ops::WrappingAdd, CircuitBuilder};
+
+    #[test]
+    fn test_stats() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        let c = builder.add_input::<u8>();
+        builder.add_output(a ^ b);
+        builder.add_output((a & b) & c);
+        let circ = builder.build().unwrap();
+
+        let stats = circ.stats();
+
+        assert_eq!(stats.and_count, 16);
+        assert_eq!(stats.xor_count, 8);
+        assert_eq!(stats.inv_count, 0);
+        assert_eq!(stats.input_bits, 24);
+        assert_eq!(stats.output_bits, 16);
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.and_depth, 2);
+    }
+
+    #[test]
+    fn test_stats_adder() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        let b = builder.add_input::<u32>();
+        builder.add_output(a.wrapping_add(b));
+        let circ = builder.build().unwrap();
+
+        let stats = circ.stats();
+
+        assert_eq!(stats.and_count, circ.and_count());
+        // Ripple carry adder has a carry chain through every bit.
+        assert_eq!(stats.and_depth, 31);
+    }
+}
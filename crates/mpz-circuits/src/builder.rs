@@ -1,8 +1,8 @@
-use itybity::{BitIterable, IntoBits};
+use itybity::IntoBits;
 
 use crate::{
     components::{Feed, Gate, Node},
-    types::{BinaryLength, BinaryRepr, ToBinaryRepr, ValueType},
+    types::{BinaryLength, BinaryRepr, ToBinaryRepr, Value, ValueType},
     Circuit, Tracer,
 };
 use std::{cell::RefCell, collections::HashMap, mem::discriminant};
@@ -15,6 +15,43 @@ pub enum BuilderError {
     MissingWire(usize),
     #[error("error appending circuit: {0}")]
     AppendError(String),
+    #[error("constant outputs require the circuit to have an input")]
+    ConstantOutput,
+}
+
+/// Architecture of the adders used by builder arithmetic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AdderKind {
+    /// Ripple-carry adder, requiring `n` AND gates with a depth of `n`.
+    #[default]
+    RippleCarry,
+    /// Sklansky parallel-prefix adder, requiring `O(n log n)` AND gates with a depth of
+    /// `O(log n)`.
+    ParallelPrefix,
+}
+
+/// Architecture of the multipliers used by builder arithmetic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MultiplierKind {
+    /// Schoolbook shift-and-add multiplier.
+    #[default]
+    Schoolbook,
+    /// Karatsuba multiplier, requiring fewer AND gates than schoolbook multiplication
+    /// for wide operands at the cost of a deeper circuit.
+    Karatsuba,
+}
+
+/// Configuration of a [`CircuitBuilder`].
+///
+/// The default configuration minimizes the number of AND gates, which is the primary cost
+/// when garbling. Protocols which are sensitive to the depth of a circuit may prefer a
+/// parallel-prefix adder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuilderConfig {
+    /// Adder architecture.
+    pub adder: AdderKind,
+    /// Multiplier architecture.
+    pub multiplier: MultiplierKind,
 }
 
 /// A circuit builder.
@@ -57,6 +94,16 @@ impl CircuitBuilder {
         }
     }
 
+    /// Creates a new circuit builder with the provided configuration.
+    pub fn new_with_config(config: BuilderConfig) -> Self {
+        Self {
+            state: RefCell::new(BuilderState {
+                config,
+                ..Default::default()
+            }),
+        }
+    }
+
     /// Returns a reference to the internal state of the builder
     pub fn state(&self) -> &RefCell<BuilderState> {
         &self.state
@@ -148,7 +195,7 @@ impl CircuitBuilder {
     }
 
     /// Returns a tracer for a constant value
-    pub fn get_constant<T: ToBinaryRepr + BitIterable>(&self, value: T) -> Tracer<'_, T::Repr> {
+    pub fn get_constant<T: ToBinaryRepr + Into<Value>>(&self, value: T) -> Tracer<'_, T::Repr> {
         let mut state = self.state.borrow_mut();
 
         let value = state.get_constant(value);
@@ -182,6 +229,7 @@ impl CircuitBuilder {
 /// The internal state of the [`CircuitBuilder`]
 #[derive(Debug)]
 pub struct BuilderState {
+    config: BuilderConfig,
     feed_id: usize,
     inputs: Vec<BinaryRepr>,
     outputs: Vec<BinaryRepr>,
@@ -194,6 +242,7 @@ pub struct BuilderState {
 impl Default for BuilderState {
     fn default() -> Self {
         Self {
+            config: BuilderConfig::default(),
             // ids 0 and 1 are reserved for constant zero and one
             feed_id: 2,
             inputs: vec![],
@@ -206,6 +255,11 @@ impl Default for BuilderState {
 }
 
 impl BuilderState {
+    /// Returns the configuration of the builder.
+    pub fn config(&self) -> &BuilderConfig {
+        &self.config
+    }
+
     /// Returns constant zero node.
     pub(crate) fn get_const_zero(&self) -> Node<Feed> {
         Node::<Feed>::new(0)
@@ -221,11 +275,12 @@ impl BuilderState {
     /// # Arguments
     ///
     /// * `value` - The value to encode.
-    pub fn get_constant<T: ToBinaryRepr + BitIterable>(&mut self, value: T) -> T::Repr {
+    pub fn get_constant<T: ToBinaryRepr + Into<Value>>(&mut self, value: T) -> T::Repr {
         let zero = self.get_const_zero();
         let one = self.get_const_one();
 
         let nodes: Vec<_> = value
+            .into()
             .into_iter_lsb0()
             .map(|bit| if bit { one } else { zero })
             .collect();
@@ -429,8 +484,57 @@ impl BuilderState {
         Ok(outputs)
     }
 
+    /// Replaces the constant nodes in the outputs with wires computing the constants.
+    ///
+    /// Constants are folded into the gates which consume them, but an output can still be a
+    /// constant, eg. the high bits of a zero-extended value. The constant nodes are not wires of
+    /// the circuit, so a zero is computed as the XOR of an input with itself, and a one as its
+    /// inverse.
+    fn materialize_constant_outputs(&mut self) -> Result<(), BuilderError> {
+        let (zero, one) = (self.get_const_zero(), self.get_const_one());
+
+        let mut outputs = std::mem::take(&mut self.outputs);
+        let mut consts: Option<(Node<Feed>, Node<Feed>)> = None;
+        for node in outputs.iter_mut().flat_map(|output| output.iter_mut()) {
+            if *node != zero && *node != one {
+                continue;
+            }
+
+            let (const_zero, const_one) = match consts {
+                Some(consts) => consts,
+                None => {
+                    let input = *self
+                        .inputs
+                        .iter()
+                        .flat_map(|input| input.iter())
+                        .next()
+                        .ok_or(BuilderError::ConstantOutput)?;
+
+                    let const_zero = self.add_feed();
+                    self.gates.push(Gate::Xor {
+                        x: input.into(),
+                        y: input.into(),
+                        z: const_zero,
+                    });
+                    self.xor_count += 1;
+
+                    let const_one = self.add_inv_gate(const_zero);
+
+                    *consts.insert((const_zero, const_one))
+                }
+            };
+
+            *node = if *node == zero { const_zero } else { const_one };
+        }
+        self.outputs = outputs;
+
+        Ok(())
+    }
+
     /// Builds the circuit.
     pub(crate) fn build(mut self) -> Result<Circuit, BuilderError> {
+        self.materialize_constant_outputs()?;
+
         // Shift all the node ids to the left by 2 to eliminate
         // the reserved constant nodes (which should be factored out during building)
         self.inputs.iter_mut().for_each(|input| input.shift_left(2));
@@ -484,6 +588,26 @@ mod test {
         assert_eq!(output, c);
     }
 
+    #[test]
+    fn test_build_constant_output() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        builder.add_output(a);
+        builder.add_output(builder.get_constant(0b1010_0101u8));
+
+        let circ = builder.build().unwrap();
+
+        let output: (u8, u8) = evaluate!(circ, fn(42u8) -> (u8, u8)).unwrap();
+
+        assert_eq!(output, (42, 0b1010_0101));
+
+        let builder = CircuitBuilder::new();
+        builder.add_output(builder.get_constant(1u8));
+
+        assert!(matches!(builder.build(), Err(BuilderError::ConstantOutput)));
+    }
+
     #[test]
     fn test_append() {
         let circ = build_adder();
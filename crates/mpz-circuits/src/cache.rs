@@ -0,0 +1,285 @@
+//! Content-addressed cache of built circuits.
+//!
+//! Building large circuits such as SHA-256 can take a significant amount of time. The cache
+//! allows circuits to be built once per process, or once per machine when backed by a
+//! directory, and shared between sessions.
+//!
+//! Circuits are stored by their [`CircuitHash`], and looked up using a caller provided key
+//! which identifies how the circuit was built, eg. `"sha256_compress"`. Identical circuits
+//! built under different keys are only stored once.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{BuilderError, Circuit, CircuitHash, DecodeError};
+
+static GLOBAL: Lazy<CircuitCache> = Lazy::new(CircuitCache::new);
+
+/// An error that can occur when using a [`CircuitCache`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum CacheError {
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    #[error(transparent)]
+    DecodeError(#[from] DecodeError),
+    #[error(transparent)]
+    BuilderError(#[from] BuilderError),
+}
+
+/// A cache of built circuits.
+#[derive(Debug, Default)]
+pub struct CircuitCache {
+    circuits: Mutex<HashMap<CircuitHash, Arc<Circuit>>>,
+    keys: Mutex<HashMap<String, CircuitHash>>,
+    dir: Option<PathBuf>,
+}
+
+impl CircuitCache {
+    /// Creates a new in-memory cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new cache which is persisted to the provided directory.
+    ///
+    /// The directory is created if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to store circuits in.
+    pub fn new_with_dir(dir: impl Into<PathBuf>) -> Result<Self, CacheError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir: Some(dir),
+            ..Default::default()
+        })
+    }
+
+    /// Returns the process-wide in-memory cache.
+    pub fn global() -> &'static CircuitCache {
+        &GLOBAL
+    }
+
+    /// Returns the circuit with the provided hash, if it is cached.
+    pub fn get(&self, hash: &CircuitHash) -> Option<Arc<Circuit>> {
+        if let Some(circ) = self.circuits.lock().unwrap().get(hash) {
+            return Some(circ.clone());
+        }
+
+        // A corrupted or unreadable file is treated as a cache miss.
+        let circ = self.load(hash).ok().flatten()?;
+
+        Some(self.insert_arc(*hash, circ))
+    }
+
+    /// Returns the circuit stored under the provided key, if it is cached.
+    pub fn get_by_key(&self, key: &str) -> Option<Arc<Circuit>> {
+        let hash = match self.keys.lock().unwrap().get(key) {
+            Some(hash) => *hash,
+            None => self.load_key(key).ok().flatten()?,
+        };
+
+        let circ = self.get(&hash)?;
+        self.keys.lock().unwrap().insert(key.to_string(), hash);
+
+        Some(circ)
+    }
+
+    /// Inserts a circuit into the cache, returning its hash.
+    ///
+    /// If an identical circuit is already cached, the cached instance is retained.
+    pub fn insert(&self, circ: Circuit) -> Result<(CircuitHash, Arc<Circuit>), CacheError> {
+        let hash = circ.hash();
+
+        if let Some(dir) = &self.dir {
+            write_atomic(&circuit_path(dir, &hash), &circ.to_bytes())?;
+        }
+
+        Ok((hash, self.insert_arc(hash, Arc::new(circ))))
+    }
+
+    /// Returns the circuit stored under the provided key, building and caching it if it is
+    /// not cached.
+    ///
+    /// The cache is not locked while building, so concurrent calls with the same key may
+    /// each build the circuit, after which they all return the same instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Identifies how the circuit is built.
+    /// * `build` - Builds the circuit.
+    pub fn get_or_build<F>(&self, key: &str, build: F) -> Result<Arc<Circuit>, CacheError>
+    where
+        F: FnOnce() -> Result<Circuit, BuilderError>,
+    {
+        if let Some(circ) = self.get_by_key(key) {
+            return Ok(circ);
+        }
+
+        let (hash, circ) = self.insert(build()?)?;
+
+        if let Some(dir) = &self.dir {
+            write_atomic(&key_path(dir, key), hash.as_bytes())?;
+        }
+        self.keys.lock().unwrap().insert(key.to_string(), hash);
+
+        Ok(circ)
+    }
+
+    fn insert_arc(&self, hash: CircuitHash, circ: Arc<Circuit>) -> Arc<Circuit> {
+        self.circuits
+            .lock()
+            .unwrap()
+            .entry(hash)
+            .or_insert(circ)
+            .clone()
+    }
+
+    fn load(&self, hash: &CircuitHash) -> Result<Option<Arc<Circuit>>, CacheError> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+
+        let path = circuit_path(dir, hash);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let circ = Circuit::from_bytes(&fs::read(path)?)?;
+
+        // Make sure the file was not corrupted or tampered with.
+        if circ.hash() != *hash {
+            return Ok(None);
+        }
+
+        Ok(Some(Arc::new(circ)))
+    }
+
+    fn load_key(&self, key: &str) -> Result<Option<CircuitHash>, CacheError> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+
+        let path = key_path(dir, key);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let bytes: [u8; 32] = match fs::read(path)?.try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(None),
+        };
+
+        Ok(Some(bytes.into()))
+    }
+}
+
+fn circuit_path(dir: &Path, hash: &CircuitHash) -> PathBuf {
+    dir.join(format!("{hash}.circ"))
+}
+
+fn key_path(dir: &Path, key: &str) -> PathBuf {
+    // Keys are hashed so that they are always valid file names.
+    let key_hash = CircuitHash::from(*blake3::hash(key.as_bytes()).as_bytes());
+    dir.join(format!("{key_hash}.key"))
+}
+
+/// Writes a file such that readers never observe a partially written file.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), CacheError> {
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ops::WrappingAdd, CircuitBuilder};
+
+    fn build_adder() -> Result<Circuit, BuilderError> {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        builder.add_output(a.wrapping_add(b));
+        builder.build()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("mpz-circuit-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_in_memory() {
+        let cache = CircuitCache::new();
+
+        let a = cache.get_or_build("adder", build_adder).unwrap();
+        let b = cache
+            .get_or_build("adder", || panic!("circuit should be cached"))
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+
+        // Identical circuits built under a different key are deduplicated.
+        let c = cache.get_or_build("adder_2", build_adder).unwrap();
+        assert!(Arc::ptr_eq(&a, &c));
+
+        assert!(Arc::ptr_eq(&a, &cache.get(&a.hash()).unwrap()));
+    }
+
+    #[test]
+    fn test_cache_on_disk() {
+        let dir = temp_dir("disk");
+
+        let hash = {
+            let cache = CircuitCache::new_with_dir(&dir).unwrap();
+            cache.get_or_build("adder", build_adder).unwrap().hash()
+        };
+
+        let cache = CircuitCache::new_with_dir(&dir).unwrap();
+        let circ = cache
+            .get_or_build("adder", || panic!("circuit should be cached on disk"))
+            .unwrap();
+
+        assert_eq!(circ.hash(), hash);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_on_disk_corrupted() {
+        let dir = temp_dir("corrupted");
+
+        let hash = {
+            let cache = CircuitCache::new_with_dir(&dir).unwrap();
+            cache.get_or_build("adder", build_adder).unwrap().hash()
+        };
+
+        let path = circuit_path(&dir, &hash);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let cache = CircuitCache::new_with_dir(&dir).unwrap();
+        assert!(cache.get(&hash).is_none());
+
+        let circ = cache.get_or_build("adder", build_adder).unwrap();
+        assert_eq!(circ.hash(), hash);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -5,11 +5,50 @@ use std::cell::RefCell;
 use itybity::IntoBits;
 
 use crate::{
-    ops::binary::{switch_nbit, wrapping_add_nbit, wrapping_sub_nbit},
+    ops::binary::{mul_nbit, switch_nbit, wrapping_add_nbit, wrapping_sub_nbit},
     types::U8,
-    BuilderState, Node, Tracer,
+    BuilderState, Feed, Node, Tracer,
 };
 
+/// A 256-bit unsigned integer, encoded as an array of bytes in big-endian order.
+pub type U256<'a> = [Tracer<'a, U8>; 32];
+
+/// Returns the nodes of a big-endian byte array in little-endian bit order.
+fn to_lsb0_nodes(value: &[Tracer<'_, U8>]) -> Vec<Node<Feed>> {
+    value
+        .iter()
+        .rev()
+        .flat_map(|byte| byte.to_inner().nodes().into_iter())
+        .collect()
+}
+
+/// Returns constant nodes for a big-endian byte array in little-endian bit order.
+fn const_to_lsb0_nodes(value: &[u8]) -> Vec<Node<Feed>> {
+    value
+        .iter()
+        .rev()
+        .flat_map(|byte| (*byte).into_iter_lsb0())
+        .map(|bit| Node::new(bit as usize))
+        .collect()
+}
+
+/// Collects little-endian bit ordered nodes into a big-endian byte array.
+fn from_lsb0_nodes<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    nodes: &[Node<Feed>],
+) -> [Tracer<'a, U8>; N] {
+    let mut bytes: [U8; N] = nodes
+        .chunks(8)
+        .map(|chunk| U8::new(chunk.try_into().unwrap()))
+        .collect::<Vec<_>>()
+        .try_into()
+        .expect("node count should be a multiple of 8");
+
+    bytes.reverse();
+
+    bytes.map(|v| Tracer::new(state, v))
+}
+
 /// Add two numbers modulo a constant modulus.
 ///
 /// This circuit assumes that the summands are in the range [0, modulus).
@@ -79,14 +118,196 @@ pub fn nbyte_add_mod_trace<'a, const N: usize>(
     sum_reduced.map(|v| Tracer::new(state, v))
 }
 
+/// Subtract two numbers modulo a constant modulus.
+///
+/// This circuit assumes that the operands are in the range [0, modulus).
+///
+/// # Arguments
+///
+/// * `state` - The builder state to append the circuit to.
+/// * `a` - The minuend encoded as an array of bytes in big-endian order.
+/// * `b` - The subtrahend encoded as an array of bytes in big-endian order.
+/// * `modulus` - The modulus encoded as an array of bytes in big-endian order.
+///
+/// # Returns
+///
+/// (a - b) % modulus
+pub fn nbyte_sub_mod_trace<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    a: [Tracer<'a, U8>; N],
+    b: [Tracer<'a, U8>; N],
+    modulus: [u8; N],
+) -> [Tracer<'a, U8>; N] {
+    let a_bits = to_lsb0_nodes(&a);
+    let b_bits = to_lsb0_nodes(&b);
+    let modulus_bits = const_to_lsb0_nodes(&modulus);
+
+    let mut builder_state = state.borrow_mut();
+
+    let (diff, underflow) = wrapping_sub_nbit(&mut builder_state, &a_bits, &b_bits);
+
+    let diff_wrapped = wrapping_add_nbit(&mut builder_state, &diff, &modulus_bits);
+
+    // if a >= b { a - b } else { a - b + modulus }
+    let diff_reduced = switch_nbit(&mut builder_state, &diff, &diff_wrapped, underflow);
+
+    drop(builder_state);
+
+    from_lsb0_nodes(state, &diff_reduced)
+}
+
+/// Reduces a number of arbitrary length modulo a constant modulus.
+///
+/// # Arguments
+///
+/// * `state` - The builder state to append the circuit to.
+/// * `value` - The number encoded as a slice of bytes in big-endian order.
+/// * `modulus` - The modulus encoded as an array of bytes in big-endian order.
+///
+/// # Returns
+///
+/// value % modulus
+pub fn nbyte_reduce_trace<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    value: &[Tracer<'a, U8>],
+    modulus: [u8; N],
+) -> [Tracer<'a, U8>; N] {
+    assert!(
+        modulus.iter().any(|byte| *byte != 0),
+        "modulus must be non-zero"
+    );
+
+    let mut modulus_bits = const_to_lsb0_nodes(&modulus);
+    // Tack on an extra bit to absorb the shifted remainder
+    modulus_bits.push(Node::new(0));
+
+    let mut builder_state = state.borrow_mut();
+
+    // Restoring reduction, processing the bits of the value from most to least significant.
+    //
+    // The remainder is always less than the modulus, so after shifting in the next bit it
+    // is less than twice the modulus and at most one subtraction is required.
+    let mut rem = vec![Node::new(0); N * 8 + 1];
+    for bit in to_lsb0_nodes(value).into_iter().rev() {
+        // rem = (rem << 1) | bit
+        rem.rotate_right(1);
+        rem[0] = bit;
+
+        let (diff, underflow) = wrapping_sub_nbit(&mut builder_state, &rem, &modulus_bits);
+
+        // if rem < modulus { rem } else { rem - modulus }
+        rem = switch_nbit(&mut builder_state, &diff, &rem, underflow);
+    }
+
+    drop(builder_state);
+
+    // Pop off the extra bit
+    rem.pop();
+
+    from_lsb0_nodes(state, &rem)
+}
+
+/// Multiply two numbers modulo a constant modulus.
+///
+/// # Arguments
+///
+/// * `state` - The builder state to append the circuit to.
+/// * `a` - The first number encoded as an array of bytes in big-endian order.
+/// * `b` - The second number encoded as an array of bytes in big-endian order.
+/// * `modulus` - The modulus encoded as an array of bytes in big-endian order.
+///
+/// # Returns
+///
+/// (a * b) % modulus
+pub fn nbyte_mul_mod_trace<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    a: [Tracer<'a, U8>; N],
+    b: [Tracer<'a, U8>; N],
+    modulus: [u8; N],
+) -> [Tracer<'a, U8>; N] {
+    let product = nbyte_mul_trace(state, a, b);
+
+    nbyte_reduce_trace(state, &product, modulus)
+}
+
+/// Add two numbers, wrapping on overflow.
+///
+/// # Arguments
+///
+/// * `state` - The builder state to append the circuit to.
+/// * `a` - The first number encoded as an array of bytes in big-endian order.
+/// * `b` - The second number encoded as an array of bytes in big-endian order.
+///
+/// # Returns
+///
+/// (a + b) % 2^(8N)
+pub fn nbyte_wrapping_add_trace<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    a: [Tracer<'a, U8>; N],
+    b: [Tracer<'a, U8>; N],
+) -> [Tracer<'a, U8>; N] {
+    let sum = wrapping_add_nbit(
+        &mut state.borrow_mut(),
+        &to_lsb0_nodes(&a),
+        &to_lsb0_nodes(&b),
+    );
+
+    from_lsb0_nodes(state, &sum)
+}
+
+/// Multiply two numbers, returning the full product.
+///
+/// # Arguments
+///
+/// * `state` - The builder state to append the circuit to.
+/// * `a` - The first number encoded as an array of bytes in big-endian order.
+/// * `b` - The second number encoded as an array of bytes in big-endian order.
+///
+/// # Returns
+///
+/// a * b, encoded as 2N bytes in big-endian order.
+pub fn nbyte_mul_trace<'a, const N: usize>(
+    state: &'a RefCell<BuilderState>,
+    a: [Tracer<'a, U8>; N],
+    b: [Tracer<'a, U8>; N],
+) -> Vec<Tracer<'a, U8>> {
+    let product = mul_nbit(
+        &mut state.borrow_mut(),
+        &to_lsb0_nodes(&a),
+        &to_lsb0_nodes(&b),
+    );
+
+    product
+        .chunks(8)
+        .rev()
+        .map(|chunk| Tracer::new(state, U8::new(chunk.try_into().unwrap())))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use mpz_circuits_macros::evaluate;
+    use num_bigint::BigUint;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use crate::CircuitBuilder;
 
     use super::*;
 
+    /// The P-256 base field prime.
+    const P256_P: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ];
+
+    fn to_be_bytes_32(value: &BigUint) -> [u8; 32] {
+        let bytes = value.to_bytes_be();
+        let mut out = [0u8; 32];
+        out[32 - bytes.len()..].copy_from_slice(&bytes);
+        out
+    }
+
     #[test]
     fn test_nbyte_add_mod() {
         let builder = CircuitBuilder::new();
@@ -112,4 +333,88 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_nbyte_sub_mod() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_array_input::<u8, 2>();
+        let b = builder.add_array_input::<u8, 2>();
+        let modulus = [0u8, 239u8];
+
+        let diff = nbyte_sub_mod_trace(builder.state(), a, b, modulus);
+
+        builder.add_output(diff);
+
+        let circ = builder.build().unwrap();
+
+        for a in 0u8..modulus[1] {
+            for b in 0u8..modulus[1] {
+                let expected_diff = ((a as u16 + 239 - b as u16) % 239) as u8;
+
+                let diff: [u8; 2] = evaluate!(circ, fn([0u8, a], [0u8, b]) -> [u8; 2]).unwrap();
+
+                assert_eq!(u16::from_be_bytes(diff) as u8, expected_diff);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nbyte_mul_mod() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_array_input::<u8, 2>();
+        let b = builder.add_array_input::<u8, 2>();
+        let modulus = 65521u16;
+
+        let product = nbyte_mul_mod_trace(builder.state(), a, b, modulus.to_be_bytes());
+
+        builder.add_output(product);
+
+        let circ = builder.build().unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let a: u16 = rng.gen_range(0..modulus);
+            let b: u16 = rng.gen_range(0..modulus);
+            let expected = ((a as u32 * b as u32) % modulus as u32) as u16;
+
+            let product: [u8; 2] =
+                evaluate!(circ, fn(a.to_be_bytes(), b.to_be_bytes()) -> [u8; 2]).unwrap();
+
+            assert_eq!(u16::from_be_bytes(product), expected);
+        }
+    }
+
+    #[test]
+    fn test_u256_mod_arith() {
+        let builder = CircuitBuilder::new();
+
+        let a: U256 = builder.add_array_input::<u8, 32>();
+        let b: U256 = builder.add_array_input::<u8, 32>();
+
+        let sum = nbyte_add_mod_trace(builder.state(), a, b, P256_P);
+        let product = nbyte_mul_mod_trace(builder.state(), a, b, P256_P);
+
+        builder.add_output(sum);
+        builder.add_output(product);
+
+        let circ = builder.build().unwrap();
+
+        let p = BigUint::from_bytes_be(&P256_P);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..4 {
+            let a = BigUint::from_bytes_be(&rng.gen::<[u8; 32]>()) % &p;
+            let b = BigUint::from_bytes_be(&rng.gen::<[u8; 32]>()) % &p;
+
+            let (sum, product): ([u8; 32], [u8; 32]) = evaluate!(
+                circ,
+                fn(to_be_bytes_32(&a), to_be_bytes_32(&b)) -> ([u8; 32], [u8; 32])
+            )
+            .unwrap();
+
+            assert_eq!(sum, to_be_bytes_32(&((&a + &b) % &p)));
+            assert_eq!(product, to_be_bytes_32(&((&a * &b) % &p)));
+        }
+    }
 }
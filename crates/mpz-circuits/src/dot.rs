@@ -0,0 +1,180 @@
+//! Export of circuits to the DOT graph description language.
+//!
+//! The output can be rendered using graphviz, eg. `dot -Tsvg circuit.dot > circuit.svg`.
+//! Gates are rendered as nodes labeled with the gate type, and wires as edges labeled with
+//! the wire id. Circuit inputs and outputs are labeled with their index and bit position.
+
+use std::fmt::Write;
+
+use crate::{components::Gate, Circuit};
+
+impl Circuit {
+    /// Returns the indices of the gates which the provided wires depend on, in topological
+    /// order.
+    ///
+    /// # Arguments
+    ///
+    /// * `wires` - The ids of the wires, eg. output wires.
+    pub fn cone_of_influence(&self, wires: &[usize]) -> Vec<usize> {
+        let producers = self.producers();
+
+        let mut visited = vec![false; self.gates.len()];
+        let mut stack: Vec<usize> = wires.iter().filter_map(|id| producers[*id]).collect();
+        while let Some(idx) = stack.pop() {
+            if visited[idx] {
+                continue;
+            }
+            visited[idx] = true;
+
+            let gate = &self.gates[idx];
+            stack.extend(
+                std::iter::once(gate.x())
+                    .chain(gate.y())
+                    .filter_map(|node| producers[node.id]),
+            );
+        }
+
+        // Gates are stored in topological order.
+        visited
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, visited)| visited.then_some(idx))
+            .collect()
+    }
+
+    /// Renders the circuit in DOT format.
+    pub fn to_dot(&self) -> String {
+        self.render_dot(&(0..self.gates.len()).collect::<Vec<_>>())
+    }
+
+    /// Renders the cone of influence of the provided wires in DOT format.
+    ///
+    /// # Arguments
+    ///
+    /// * `wires` - The ids of the wires, eg. output wires.
+    pub fn cone_to_dot(&self, wires: &[usize]) -> String {
+        self.render_dot(&self.cone_of_influence(wires))
+    }
+
+    /// Returns the index of the gate which produces each wire, if any.
+    fn producers(&self) -> Vec<Option<usize>> {
+        let mut producers = vec![None; self.feed_count];
+        for (idx, gate) in self.gates.iter().enumerate() {
+            producers[gate.z().id] = Some(idx);
+        }
+        producers
+    }
+
+    fn render_dot(&self, gates: &[usize]) -> String {
+        let producers = self.producers();
+        let mut included = vec![false; self.gates.len()];
+        gates.iter().for_each(|idx| included[*idx] = true);
+
+        // Wires which are used by the rendered gates but not produced by them.
+        let mut sources = vec![false; self.feed_count];
+
+        let mut dot = String::from("digraph circuit {\n    rankdir=LR;\n");
+
+        for idx in gates {
+            let gate = &self.gates[*idx];
+            let label = match gate {
+                Gate::Xor { .. } => "XOR",
+                Gate::And { .. } => "AND",
+                Gate::Inv { .. } => "INV",
+            };
+            writeln!(dot, "    g{idx} [shape=box, label=\"{label}\"];").unwrap();
+
+            for node in std::iter::once(gate.x()).chain(gate.y()) {
+                let from = match producers[node.id] {
+                    Some(producer) if included[producer] => format!("g{producer}"),
+                    _ => {
+                        sources[node.id] = true;
+                        format!("w{}", node.id)
+                    }
+                };
+                writeln!(dot, "    {from} -> g{idx} [label=\"{}\"];", node.id).unwrap();
+            }
+        }
+
+        // Label source wires which are circuit inputs.
+        let mut labels: Vec<Option<String>> = vec![None; self.feed_count];
+        for (i, input) in self.inputs.iter().enumerate() {
+            for (bit, node) in input.iter().enumerate() {
+                labels[node.id] = Some(format!("in{i}[{bit}]"));
+            }
+        }
+
+        for (id, _) in sources.iter().enumerate().filter(|(_, source)| **source) {
+            let label = labels[id].take().unwrap_or_else(|| format!("w{id}"));
+            writeln!(dot, "    w{id} [shape=plaintext, label=\"{label}\"];").unwrap();
+        }
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            for (bit, node) in output.iter().enumerate() {
+                let Some(producer) = producers[node.id].filter(|producer| included[*producer])
+                else {
+                    continue;
+                };
+                writeln!(
+                    dot,
+                    "    o{i}_{bit} [shape=plaintext, label=\"out{i}[{bit}]\"];\n    \
+                     g{producer} -> o{i}_{bit} [label=\"{}\"];",
+                    node.id
+                )
+                .unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{types::BinaryRepr, CircuitBuilder};
+
+    #[test]
+    fn test_to_dot() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<bool>();
+        let b = builder.add_input::<bool>();
+        let c = builder.add_input::<bool>();
+        builder.add_output((a & b) ^ c);
+        let circ = builder.build().unwrap();
+
+        let dot = circ.to_dot();
+
+        assert!(dot.starts_with("digraph circuit {"));
+        assert!(dot.contains("label=\"AND\""));
+        assert!(dot.contains("label=\"XOR\""));
+        assert!(dot.contains("label=\"in0[0]\""));
+        assert!(dot.contains("label=\"out0[0]\""));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_cone_of_influence() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        builder.add_output(a & b);
+        builder.add_output(a ^ b);
+        let circ = builder.build().unwrap();
+
+        let BinaryRepr::U8(and) = &circ.outputs()[0] else {
+            panic!("output should be u8");
+        };
+        let wire = and.nodes()[3].id();
+
+        let cone = circ.cone_of_influence(&[wire]);
+
+        assert_eq!(cone.len(), 1);
+        assert_eq!(circ.gates()[cone[0]].z().id(), wire);
+
+        let dot = circ.cone_to_dot(&[wire]);
+        assert!(dot.contains("label=\"AND\""));
+        assert!(!dot.contains("label=\"XOR\""));
+        assert!(dot.contains("label=\"out0[3]\""));
+    }
+}
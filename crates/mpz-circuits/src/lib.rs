@@ -3,23 +3,31 @@
 
 extern crate self as mpz_circuits;
 
+mod analysis;
 mod builder;
+mod cache;
 mod circuit;
 pub mod circuits;
 pub(crate) mod components;
+mod dot;
 pub mod ops;
 #[cfg(feature = "parse")]
 mod parse;
+mod serialize;
+pub mod testing;
 mod tracer;
 pub mod types;
 
+pub use analysis::CircuitStats;
 #[doc(hidden)]
 pub use builder::BuilderState;
-pub use builder::{BuilderError, CircuitBuilder};
+pub use builder::{AdderKind, BuilderConfig, BuilderError, CircuitBuilder, MultiplierKind};
+pub use cache::{CacheError, CircuitCache};
 pub use circuit::{Circuit, CircuitError};
 #[doc(hidden)]
 pub use components::{Feed, Node, Sink};
 pub use components::{Gate, GateType};
+pub use serialize::{CircuitHash, DecodeError, FORMAT_VERSION};
 pub use tracer::Tracer;
 
 pub use once_cell;
@@ -3,9 +3,13 @@ use std::ops::{BitAnd, BitOr, BitXor, Not};
 use crate::{
     components::{Feed, Node},
     types::Bit,
-    BuilderState, Tracer,
+    AdderKind, BuilderState, MultiplierKind, Tracer,
 };
 
+/// Operand width up to which the Karatsuba multiplier falls back to schoolbook
+/// multiplication.
+const KARATSUBA_THRESHOLD: usize = 16;
+
 /// Binary full-adder.
 fn full_adder(
     state: &mut BuilderState,
@@ -42,6 +46,12 @@ pub(crate) fn const_wrapping_add_nbit<const N: usize>(
     a: [Node<Feed>; N],
     b: [Node<Feed>; N],
 ) -> [Node<Feed>; N] {
+    if state.config().adder == AdderKind::ParallelPrefix {
+        let zero = state.get_const_zero();
+        let (sum, _) = prefix_add_nbit(state, &a, &b, zero);
+        return sum.try_into().expect("length should match");
+    }
+
     let mut c_out = Node::new(0);
     std::array::from_fn(|n| {
         if n == 0 {
@@ -68,6 +78,11 @@ pub(crate) fn wrapping_add_nbit(
 ) -> Vec<Node<Feed>> {
     assert_eq!(a.len(), b.len());
 
+    if state.config().adder == AdderKind::ParallelPrefix {
+        let zero = state.get_const_zero();
+        return prefix_add_nbit(state, a, b, zero).0;
+    }
+
     let len = a.len();
     let mut c_out = Node::new(0);
     a.iter()
@@ -91,6 +106,99 @@ pub(crate) fn wrapping_add_nbit(
         .collect()
 }
 
+/// Add two nbit values together using a Sklansky parallel-prefix adder.
+///
+/// Returns the sum and the carry out.
+fn prefix_add_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+    carry_in: Node<Feed>,
+) -> (Vec<Node<Feed>>, Node<Feed>) {
+    assert_eq!(a.len(), b.len());
+
+    let len = a.len();
+    if len == 0 {
+        return (vec![], carry_in);
+    }
+
+    let propagate: Vec<_> = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| state.add_xor_gate(*a, *b))
+        .collect();
+    let mut generate: Vec<_> = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| state.add_and_gate(*a, *b))
+        .collect();
+
+    // Fold the carry in into the first generate bit. Generate and propagate are
+    // mutually exclusive, so OR can be replaced with XOR here and below.
+    let carry_in_0 = state.add_and_gate(propagate[0], carry_in);
+    generate[0] = state.add_xor_gate(generate[0], carry_in_0);
+
+    // After the prefix computation `group_g[i]` is the carry out of bits `0..=i`.
+    let mut group_g = generate;
+    let mut group_p = propagate.clone();
+    let mut span = 1;
+    while span < len {
+        for i in (0..len).filter(|i| i & span != 0) {
+            // Last index of the lower half of the block containing `i`.
+            let j = (i & !(2 * span - 1)) + span - 1;
+
+            let carry = state.add_and_gate(group_p[i], group_g[j]);
+            group_g[i] = state.add_xor_gate(group_g[i], carry);
+
+            // The group propagate is only needed if `i` is in an upper half at a later level.
+            if i >= 2 * span {
+                group_p[i] = state.add_and_gate(group_p[i], group_p[j]);
+            }
+        }
+        span *= 2;
+    }
+
+    let sum = propagate
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let carry = if i == 0 { carry_in } else { group_g[i - 1] };
+            state.add_xor_gate(*p, carry)
+        })
+        .collect();
+
+    (sum, group_g[len - 1])
+}
+
+/// Add two nbit values together.
+///
+/// Returns the sum and the carry out.
+fn add_with_carry_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> (Vec<Node<Feed>>, Node<Feed>) {
+    assert_eq!(a.len(), b.len());
+
+    let zero = state.get_const_zero();
+    if state.config().adder == AdderKind::ParallelPrefix {
+        return prefix_add_nbit(state, a, b, zero);
+    }
+
+    let mut c_out = zero;
+    let sum = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| {
+            let (sum_n, c_out_n) = full_adder(state, *a, *b, c_out);
+            c_out = c_out_n;
+            sum_n
+        })
+        .collect();
+
+    (sum, c_out)
+}
+
 /// Subtract two nbit values, wrapping on underflow.
 ///
 /// Returns the result and the bit indicating whether underflow occurred.
@@ -102,6 +210,14 @@ pub(crate) fn const_wrapping_sub_nbit<const N: usize>(
     // invert b
     let b_inv = b.map(|b| state.add_inv_gate(b));
 
+    if state.config().adder == AdderKind::ParallelPrefix {
+        let one = state.get_const_one();
+        let (diff, b_out) = prefix_add_nbit(state, &a, &b_inv, one);
+        let underflow = state.add_inv_gate(b_out);
+
+        return (diff.try_into().expect("length should match"), underflow);
+    }
+
     // Set first b_in to 1, which adds 1 to b_inv.
     let mut b_out = Node::new(1);
     let diff = std::array::from_fn(|n| {
@@ -129,6 +245,14 @@ pub(crate) fn wrapping_sub_nbit(
     // invert b
     let b_inv = b.iter().map(|b| state.add_inv_gate(*b)).collect::<Vec<_>>();
 
+    if state.config().adder == AdderKind::ParallelPrefix {
+        let one = state.get_const_one();
+        let (diff, b_out) = prefix_add_nbit(state, a, &b_inv, one);
+        let underflow = state.add_inv_gate(b_out);
+
+        return (diff, underflow);
+    }
+
     // Set first b_in to 1, which adds 1 to b_inv.
     let mut b_out = Node::new(1);
 
@@ -171,6 +295,301 @@ pub(crate) fn switch_nbit(
         .collect()
 }
 
+/// Multiply two nbit values, wrapping on overflow.
+pub(crate) fn wrapping_mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    assert_eq!(a.len(), b.len());
+
+    match state.config().multiplier {
+        MultiplierKind::Schoolbook => schoolbook_wrapping_mul_nbit(state, a, b),
+        MultiplierKind::Karatsuba => karatsuba_wrapping_mul_nbit(state, a, b),
+    }
+}
+
+/// Multiply two nbit values.
+///
+/// Returns the full product with twice the length of the operands.
+pub(crate) fn mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    assert_eq!(a.len(), b.len());
+
+    match state.config().multiplier {
+        MultiplierKind::Schoolbook => schoolbook_mul_nbit(state, a, b),
+        MultiplierKind::Karatsuba => karatsuba_mul_nbit(state, a, b),
+    }
+}
+
+/// Multiply two nbit values using schoolbook multiplication, wrapping on overflow.
+fn schoolbook_wrapping_mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    let len = a.len();
+    let zero = state.get_const_zero();
+    let mut product = vec![zero; len];
+    for (i, b_i) in b.iter().enumerate() {
+        // Skip partial products which are known to be zero.
+        if b_i.id() == 0 {
+            continue;
+        }
+
+        // Partial product of `a` and the i-th bit of `b`, shifted left by `i`.
+        // The lower `i` bits of the product are unaffected, so we only add the upper bits.
+        let partial = a[..len - i]
+            .iter()
+            .map(|a_j| state.add_and_gate(*a_j, *b_i))
+            .collect::<Vec<_>>();
+
+        let sum = wrapping_add_nbit(state, &product[i..], &partial);
+        product[i..].copy_from_slice(&sum);
+    }
+
+    product
+}
+
+/// Multiply two nbit values using schoolbook multiplication.
+///
+/// Returns the full product with twice the length of the operands.
+fn schoolbook_mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    assert_eq!(a.len(), b.len());
+
+    let len = a.len();
+    let zero = state.get_const_zero();
+    let mut product = vec![zero; 2 * len];
+    for (i, b_i) in b.iter().enumerate() {
+        if b_i.id() == 0 {
+            continue;
+        }
+
+        let partial = a
+            .iter()
+            .map(|a_j| state.add_and_gate(*a_j, *b_i))
+            .collect::<Vec<_>>();
+
+        let (sum, carry) = add_with_carry_nbit(state, &product[i..i + len], &partial);
+        product[i..i + len].copy_from_slice(&sum);
+        product[i + len] = carry;
+    }
+
+    product
+}
+
+/// Multiply two nbit values using Karatsuba multiplication.
+///
+/// Returns the full product with twice the length of the operands.
+fn karatsuba_mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    assert_eq!(a.len(), b.len());
+
+    let len = a.len();
+    if len <= KARATSUBA_THRESHOLD {
+        return schoolbook_mul_nbit(state, a, b);
+    }
+
+    let zero = state.get_const_zero();
+
+    // a = a_1 * 2^h + a_0, where a_1 is at least as wide as a_0.
+    let h = len / 2;
+    let m = len - h;
+    let (a_0, a_1) = a.split_at(h);
+    let (b_0, b_1) = b.split_at(h);
+
+    let z_0 = karatsuba_mul_nbit(state, a_0, b_0);
+    let z_2 = karatsuba_mul_nbit(state, a_1, b_1);
+
+    // (a_0 + a_1) and (b_0 + b_1) with the carry as the most significant bit.
+    let mut sum = |lo: &[Node<Feed>], hi: &[Node<Feed>]| {
+        let mut lo = lo.to_vec();
+        lo.resize(m, zero);
+        let (mut sum, carry) = add_with_carry_nbit(state, &lo, hi);
+        sum.push(carry);
+        sum
+    };
+    let a_sum = sum(a_0, a_1);
+    let b_sum = sum(b_0, b_1);
+
+    let mut z_1 = karatsuba_mul_nbit(state, &a_sum, &b_sum);
+
+    // z_1 - z_0 - z_2 = a_0 * b_1 + a_1 * b_0, which fits in 2m + 1 bits.
+    let width = 2 * m + 1;
+    z_1.truncate(width);
+    let mut z_0_ext = z_0.clone();
+    z_0_ext.resize(width, zero);
+    let mut z_2_ext = z_2.clone();
+    z_2_ext.resize(width, zero);
+
+    let (mid, _) = wrapping_sub_nbit(state, &z_1, &z_0_ext);
+    let (mut mid, _) = wrapping_sub_nbit(state, &mid, &z_2_ext);
+
+    // z_0 and z_2 * 2^2h do not overlap, so they can be concatenated.
+    let mut product = z_0;
+    product.extend(z_2);
+
+    mid.resize(2 * len - h, zero);
+    let upper = wrapping_add_nbit(state, &product[h..], &mid);
+    product[h..].copy_from_slice(&upper);
+
+    product
+}
+
+/// Multiply two nbit values using Karatsuba multiplication, wrapping on overflow.
+fn karatsuba_wrapping_mul_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Vec<Node<Feed>> {
+    let len = a.len();
+    if len <= KARATSUBA_THRESHOLD {
+        return schoolbook_wrapping_mul_nbit(state, a, b);
+    }
+
+    let zero = state.get_const_zero();
+
+    // a * b mod 2^n = a_0 * b_0 + ((a_1 * b_0 + a_0 * b_1) mod 2^m) * 2^h
+    //
+    // Only the product of the lower halves is needed in full, the cross terms are
+    // truncated products of half the width.
+    let h = len / 2;
+    let m = len - h;
+    let (a_0, a_1) = a.split_at(h);
+    let (b_0, b_1) = b.split_at(h);
+
+    let mut product = karatsuba_mul_nbit(state, a_0, b_0);
+    product.resize(len, zero);
+
+    let extend = |value: &[Node<Feed>]| {
+        let mut value = value.to_vec();
+        value.resize(m, zero);
+        value
+    };
+
+    let cross_0 = karatsuba_wrapping_mul_nbit(state, a_1, &extend(b_0));
+    let cross_1 = karatsuba_wrapping_mul_nbit(state, &extend(a_0), b_1);
+    let cross = wrapping_add_nbit(state, &cross_0, &cross_1);
+
+    let upper = wrapping_add_nbit(state, &product[h..], &cross);
+    product[h..].copy_from_slice(&upper);
+
+    product
+}
+
+/// Divide two unsigned values using a restoring divider.
+///
+/// The divisor may be narrower than the dividend, which reduces the size of the circuit
+/// when the divisor is known to be small, eg. a constant.
+///
+/// Returns the quotient and the remainder, both with the same length as the dividend.
+/// Division by zero results in a quotient with all bits set and a remainder equal to
+/// the dividend.
+pub(crate) fn div_rem_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> (Vec<Node<Feed>>, Vec<Node<Feed>>) {
+    assert!(b.len() <= a.len());
+
+    let zero = state.get_const_zero();
+
+    // The remainder is always less than the divisor, so after shifting in the next bit
+    // it requires at most one more bit than the divisor.
+    let mut divisor = b.to_vec();
+    divisor.push(zero);
+
+    let mut quotient = vec![zero; a.len()];
+    let mut rem = vec![zero; divisor.len()];
+    for (i, a_i) in a.iter().enumerate().rev() {
+        // rem = (rem << 1) | a_i
+        rem.rotate_right(1);
+        rem[0] = *a_i;
+
+        let (diff, underflow) = wrapping_sub_nbit(state, &rem, &divisor);
+
+        quotient[i] = state.add_inv_gate(underflow);
+        // if rem < divisor { rem } else { rem - divisor }
+        rem = switch_nbit(state, &diff, &rem, underflow);
+    }
+
+    // The remainder fits in the width of the divisor, extend it to the width of the dividend.
+    rem.resize(a.len(), zero);
+
+    (quotient, rem)
+}
+
+/// Bitwise OR of two bits.
+fn or_bit(state: &mut BuilderState, a: Node<Feed>, b: Node<Feed>) -> Node<Feed> {
+    // OR = (A ⊕ B) ⊕ (A ^ B)
+    let a_xor_b = state.add_xor_gate(a, b);
+    let a_and_b = state.add_and_gate(a, b);
+
+    state.add_xor_gate(a_xor_b, a_and_b)
+}
+
+/// Returns a bit indicating whether two nbit values are equal.
+pub(crate) fn eq_nbit(state: &mut BuilderState, a: &[Node<Feed>], b: &[Node<Feed>]) -> Node<Feed> {
+    assert_eq!(a.len(), b.len());
+
+    let mut any_diff = state.get_const_zero();
+    for (a, b) in a.iter().zip(b) {
+        let diff = state.add_xor_gate(*a, *b);
+        any_diff = or_bit(state, any_diff, diff);
+    }
+
+    state.add_inv_gate(any_diff)
+}
+
+/// Returns a bit indicating whether `a < b`, where both are unsigned nbit values.
+pub(crate) fn lt_nbit(state: &mut BuilderState, a: &[Node<Feed>], b: &[Node<Feed>]) -> Node<Feed> {
+    assert_eq!(a.len(), b.len());
+
+    // Only the borrow chain of `a - b` is computed, the difference itself is not needed.
+    let mut b_out = Node::new(1);
+    for (a, b) in a.iter().zip(b) {
+        let b_inv = state.add_inv_gate(*b);
+
+        // C_OUT = C_IN ⊕ ((A ⊕ C_IN) ^ (B ⊕ C_IN))
+        let a_c_in = state.add_xor_gate(*a, b_out);
+        let b_c_in = state.add_xor_gate(b_inv, b_out);
+        let and = state.add_and_gate(a_c_in, b_c_in);
+        b_out = state.add_xor_gate(and, b_out);
+    }
+
+    // a < b if the subtraction underflows
+    state.add_inv_gate(b_out)
+}
+
+/// Returns a bit indicating whether `a < b`, where both are two's complement nbit values.
+pub(crate) fn lt_signed_nbit(
+    state: &mut BuilderState,
+    a: &[Node<Feed>],
+    b: &[Node<Feed>],
+) -> Node<Feed> {
+    assert_eq!(a.len(), b.len());
+
+    // Flipping the sign bits maps the signed range onto the unsigned range
+    // while preserving order.
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    let msb = a.len() - 1;
+    a[msb] = state.add_inv_gate(a[msb]);
+    b[msb] = state.add_inv_gate(b[msb]);
+
+    lt_nbit(state, &a, &b)
+}
+
 /// Bitwise XOR of two nbit values.
 pub(crate) fn xor_nbit<const N: usize>(
     state: &mut BuilderState,
@@ -260,9 +679,16 @@ impl<'a> BitOr for Tracer<'a, Bit> {
 mod tests {
     use mpz_circuits_macros::evaluate;
 
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
 
-    use crate::{types::U8, CircuitBuilder};
+    use crate::{
+        ops::{WrappingAdd, WrappingMul, WrappingSub},
+        testing::{check_exhaustive, check_random},
+        types::{U64, U8},
+        BuilderConfig, CircuitBuilder,
+    };
 
     #[test]
     fn test_wrapping_add() {
@@ -323,6 +749,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wrapping_mul() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>().to_inner();
+        let b = builder.add_input::<u8>().to_inner();
+
+        let product = wrapping_mul_nbit(
+            &mut builder.state().borrow_mut(),
+            a.nodes().as_slice(),
+            b.nodes().as_slice(),
+        );
+
+        builder.add_output(U8::new(product.try_into().unwrap()));
+
+        let circ = builder.build().unwrap();
+
+        for a in 0u8..=255 {
+            for b in [0u8, 1, 2, 3, 7, 100, 255] {
+                let product: u8 = evaluate!(circ, fn(a, b) -> u8).unwrap();
+
+                assert_eq!(product, a.wrapping_mul(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_nbit() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>().to_inner();
+        let b = builder.add_input::<u8>().to_inner();
+
+        let mut state = builder.state().borrow_mut();
+        let eq = eq_nbit(&mut state, a.nodes().as_slice(), b.nodes().as_slice());
+        let lt = lt_nbit(&mut state, a.nodes().as_slice(), b.nodes().as_slice());
+        let lt_signed = lt_signed_nbit(&mut state, a.nodes().as_slice(), b.nodes().as_slice());
+        drop(state);
+
+        builder.add_output(Bit::new([eq]));
+        builder.add_output(Bit::new([lt]));
+        builder.add_output(Bit::new([lt_signed]));
+
+        let circ = builder.build().unwrap();
+
+        for a in 0u8..=255 {
+            for b in 0u8..=255 {
+                let (eq, lt, lt_signed): (bool, bool, bool) =
+                    evaluate!(circ, fn(a, b) -> (bool, bool, bool)).unwrap();
+
+                assert_eq!(eq, a == b);
+                assert_eq!(lt, a < b);
+                assert_eq!(lt_signed, (a as i8) < (b as i8));
+            }
+        }
+    }
+
     #[test]
     fn test_switch_nbit() {
         let builder = CircuitBuilder::new();
@@ -355,4 +838,108 @@ mod tests {
         let out: u8 = evaluate!(circ, fn(a, b, true) -> u8).unwrap();
         assert_eq!(out, b);
     }
+
+    macro_rules! build_arithmetic {
+        ($ty:ty, $config:expr) => {{
+            let builder = CircuitBuilder::new_with_config($config);
+            let a = builder.add_input::<$ty>();
+            let b = builder.add_input::<$ty>();
+            builder.add_output(a.wrapping_add(b));
+            builder.add_output(a.wrapping_sub(b));
+            builder.add_output(a.wrapping_mul(b));
+            builder.build().unwrap()
+        }};
+    }
+
+    #[test]
+    fn test_arithmetic_architectures() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for adder in [AdderKind::RippleCarry, AdderKind::ParallelPrefix] {
+            for multiplier in [MultiplierKind::Schoolbook, MultiplierKind::Karatsuba] {
+                let config = BuilderConfig { adder, multiplier };
+
+                check_exhaustive(&build_arithmetic!(u8, config), |inputs| {
+                    let a: u8 = inputs[0].clone().try_into().unwrap();
+                    let b: u8 = inputs[1].clone().try_into().unwrap();
+                    vec![
+                        a.wrapping_add(b).into(),
+                        a.wrapping_sub(b).into(),
+                        a.wrapping_mul(b).into(),
+                    ]
+                })
+                .unwrap();
+
+                check_random(&build_arithmetic!(u64, config), &mut rng, 256, |inputs| {
+                    let a: u64 = inputs[0].clone().try_into().unwrap();
+                    let b: u64 = inputs[1].clone().try_into().unwrap();
+                    vec![
+                        a.wrapping_add(b).into(),
+                        a.wrapping_sub(b).into(),
+                        a.wrapping_mul(b).into(),
+                    ]
+                })
+                .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_architecture_tradeoffs() {
+        let build = |config: BuilderConfig| {
+            let builder = CircuitBuilder::new_with_config(config);
+            let a = builder.add_input::<u64>();
+            let b = builder.add_input::<u64>();
+            builder.add_output(a.wrapping_add(b));
+            builder.build().unwrap().stats()
+        };
+
+        let ripple = build(BuilderConfig::default());
+        let prefix = build(BuilderConfig {
+            adder: AdderKind::ParallelPrefix,
+            ..Default::default()
+        });
+
+        assert!(prefix.and_depth < ripple.and_depth);
+        assert!(prefix.and_count > ripple.and_count);
+
+        let build = |config: BuilderConfig| {
+            let builder = CircuitBuilder::new_with_config(config);
+            let a = builder.add_input::<u128>();
+            let b = builder.add_input::<u128>();
+            builder.add_output(a.wrapping_mul(b));
+            builder.build().unwrap().stats()
+        };
+
+        let schoolbook = build(BuilderConfig::default());
+        let karatsuba = build(BuilderConfig {
+            multiplier: MultiplierKind::Karatsuba,
+            ..Default::default()
+        });
+
+        assert!(karatsuba.and_count < schoolbook.and_count);
+    }
+
+    #[test]
+    fn test_karatsuba_full_product() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>().to_inner();
+        let b = builder.add_input::<u32>().to_inner();
+
+        let product = karatsuba_mul_nbit(&mut builder.state().borrow_mut(), &a.nodes(), &b.nodes());
+        let product = U64::new(product.try_into().unwrap());
+
+        builder.add_output(product);
+        let circ = builder.build().unwrap();
+
+        for (a, b) in [
+            (0u32, 0u32),
+            (1, u32::MAX),
+            (u32::MAX, u32::MAX),
+            (123456, 654321),
+        ] {
+            let product: u64 = evaluate!(circ, fn(a, b) -> u64).unwrap();
+            assert_eq!(product, a as u64 * b as u64);
+        }
+    }
 }
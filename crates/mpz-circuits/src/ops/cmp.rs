@@ -0,0 +1,147 @@
+use crate::{
+    types::{Bit, I128, I16, I32, I64, I8, U128, U16, U32, U64, U8},
+    Tracer,
+};
+
+use super::{binary, Compare};
+
+macro_rules! impl_compare {
+    ($ty:ident, $const_ty:ident, $lt:path) => {
+        impl<'a> Compare<Tracer<'a, $ty>> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, Bit>;
+
+            fn eq(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                let node = binary::eq_nbit(
+                    &mut self.state.borrow_mut(),
+                    &self.to_inner().nodes(),
+                    &rhs.to_inner().nodes(),
+                );
+
+                Tracer::new(self.state, Bit::new([node]))
+            }
+
+            fn ne(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                !Compare::eq(self, rhs)
+            }
+
+            fn lt(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                let node = $lt(
+                    &mut self.state.borrow_mut(),
+                    &self.to_inner().nodes(),
+                    &rhs.to_inner().nodes(),
+                );
+
+                Tracer::new(self.state, Bit::new([node]))
+            }
+
+            fn le(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                !Compare::lt(rhs, self)
+            }
+
+            fn gt(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                Compare::lt(rhs, self)
+            }
+
+            fn ge(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                !Compare::lt(self, rhs)
+            }
+        }
+
+        impl<'a> Compare<$const_ty> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, Bit>;
+
+            fn eq(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::eq(self, rhs)
+            }
+
+            fn ne(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::ne(self, rhs)
+            }
+
+            fn lt(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::lt(self, rhs)
+            }
+
+            fn le(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::le(self, rhs)
+            }
+
+            fn gt(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::gt(self, rhs)
+            }
+
+            fn ge(self, rhs: $const_ty) -> Self::Output {
+                let rhs = Tracer::new(self.state, self.state.borrow_mut().get_constant(rhs));
+                Compare::ge(self, rhs)
+            }
+        }
+    };
+}
+
+impl_compare!(U8, u8, binary::lt_nbit);
+impl_compare!(U16, u16, binary::lt_nbit);
+impl_compare!(U32, u32, binary::lt_nbit);
+impl_compare!(U64, u64, binary::lt_nbit);
+impl_compare!(U128, u128, binary::lt_nbit);
+impl_compare!(I8, i8, binary::lt_signed_nbit);
+impl_compare!(I16, i16, binary::lt_signed_nbit);
+impl_compare!(I32, i32, binary::lt_signed_nbit);
+impl_compare!(I64, i64, binary::lt_signed_nbit);
+impl_compare!(I128, i128, binary::lt_signed_nbit);
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits_macros::evaluate;
+
+    use super::*;
+    use crate::CircuitBuilder;
+
+    #[test]
+    fn test_compare_i8() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<i8>();
+        let b = builder.add_input::<i8>();
+
+        builder.add_output(Compare::eq(a, b));
+        builder.add_output(Compare::ne(a, b));
+        builder.add_output(Compare::lt(a, b));
+        builder.add_output(Compare::le(a, b));
+        builder.add_output(Compare::gt(a, b));
+        builder.add_output(Compare::ge(a, b));
+
+        let circ = builder.build().unwrap();
+
+        for a in i8::MIN..=i8::MAX {
+            for b in [i8::MIN, -1, 0, 1, 42, i8::MAX] {
+                let output: (bool, bool, bool, bool, bool, bool) =
+                    evaluate!(circ, fn(a, b) -> (bool, bool, bool, bool, bool, bool)).unwrap();
+
+                assert_eq!(output, (a == b, a != b, a < b, a <= b, a > b, a >= b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_const_u16() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u16>();
+
+        builder.add_output(Compare::lt(a, 1000u16));
+        builder.add_output(Compare::ge(a, 1000u16));
+
+        let circ = builder.build().unwrap();
+
+        for a in [0u16, 999, 1000, 1001, u16::MAX] {
+            let output: (bool, bool) = evaluate!(circ, fn(a) -> (bool, bool)).unwrap();
+
+            assert_eq!(output, (a < 1000, a >= 1000));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::ops::{Div, Rem};
+
+use crate::{
+    types::{U128, U16, U32, U64, U8},
+    Tracer,
+};
+
+use super::binary;
+
+// Division by a dynamic divisor uses a restoring divider over the full bit width.
+//
+// Division by a constant is specialized: powers of two reduce to shifting and masking
+// which require no gates, otherwise the divider only needs to be as wide as the divisor.
+//
+// Division by a dynamic zero divisor results in a quotient with all bits set and a
+// remainder equal to the dividend, whereas division by a constant zero panics.
+
+macro_rules! impl_div_rem_uint {
+    ($ty:ident, $const_ty:ident, $len:expr) => {
+        impl<'a> Tracer<'a, $ty> {
+            /// Computes the quotient and the remainder of a division.
+            pub fn div_rem(self, rhs: Tracer<'a, $ty>) -> (Tracer<'a, $ty>, Tracer<'a, $ty>) {
+                let mut state = self.state.borrow_mut();
+
+                let (quotient, rem) = binary::div_rem_nbit(
+                    &mut state,
+                    &self.to_inner().nodes(),
+                    &rhs.to_inner().nodes(),
+                );
+
+                drop(state);
+
+                (
+                    Tracer::new(self.state, <$ty>::new(quotient.try_into().unwrap())),
+                    Tracer::new(self.state, <$ty>::new(rem.try_into().unwrap())),
+                )
+            }
+
+            /// Computes the quotient and the remainder of a division by a constant.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `rhs` is zero.
+            pub fn div_rem_const(self, rhs: $const_ty) -> (Tracer<'a, $ty>, Tracer<'a, $ty>) {
+                assert!(rhs != 0, "attempt to divide by zero");
+
+                let mut state = self.state.borrow_mut();
+
+                let zero = state.get_const_zero();
+                let nodes = self.to_inner().nodes();
+
+                let (quotient, rem) = if rhs.is_power_of_two() {
+                    let shift = rhs.trailing_zeros() as usize;
+
+                    let quotient: [_; $len] = std::array::from_fn(|i| {
+                        if i + shift < $len {
+                            nodes[i + shift]
+                        } else {
+                            zero
+                        }
+                    });
+                    let rem: [_; $len] =
+                        std::array::from_fn(|i| if i < shift { nodes[i] } else { zero });
+
+                    (quotient, rem)
+                } else {
+                    // Only the significant bits of the divisor are needed.
+                    let divisor_len = ($len - rhs.leading_zeros()) as usize;
+                    let divisor = state.get_constant::<$const_ty>(rhs).nodes();
+
+                    let (quotient, rem) =
+                        binary::div_rem_nbit(&mut state, &nodes, &divisor[..divisor_len]);
+
+                    (quotient.try_into().unwrap(), rem.try_into().unwrap())
+                };
+
+                drop(state);
+
+                (
+                    Tracer::new(self.state, <$ty>::new(quotient)),
+                    Tracer::new(self.state, <$ty>::new(rem)),
+                )
+            }
+        }
+
+        impl<'a> Div<Tracer<'a, $ty>> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn div(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                self.div_rem(rhs).0
+            }
+        }
+
+        impl<'a> Div<$const_ty> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn div(self, rhs: $const_ty) -> Self::Output {
+                self.div_rem_const(rhs).0
+            }
+        }
+
+        impl<'a> Rem<Tracer<'a, $ty>> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn rem(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                self.div_rem(rhs).1
+            }
+        }
+
+        impl<'a> Rem<$const_ty> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn rem(self, rhs: $const_ty) -> Self::Output {
+                self.div_rem_const(rhs).1
+            }
+        }
+    };
+}
+
+impl_div_rem_uint!(U8, u8, 8);
+impl_div_rem_uint!(U16, u16, 16);
+impl_div_rem_uint!(U32, u32, 32);
+impl_div_rem_uint!(U64, u64, 64);
+impl_div_rem_uint!(U128, u128, 128);
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits_macros::evaluate;
+
+    use crate::CircuitBuilder;
+
+    #[test]
+    fn test_div_rem() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        let (q, r) = a.div_rem(b);
+        builder.add_output(q);
+        builder.add_output(r);
+        let circ = builder.build().unwrap();
+
+        for a in 0u8..=255 {
+            for b in 1u8..=255 {
+                let output: (u8, u8) = evaluate!(circ, fn(a, b) -> (u8, u8)).unwrap();
+                assert_eq!(output, (a / b, a % b));
+            }
+        }
+
+        // Division by zero
+        let output: (u8, u8) = evaluate!(circ, fn(42u8, 0u8) -> (u8, u8)).unwrap();
+        assert_eq!(output, (u8::MAX, 42));
+    }
+
+    #[test]
+    fn test_div_rem_const() {
+        for divisor in [1u16, 2, 3, 10, 64, 1000, u16::MAX] {
+            let builder = CircuitBuilder::new();
+            let a = builder.add_input::<u16>();
+            builder.add_output(a / divisor);
+            builder.add_output(a % divisor);
+            let circ = builder.build().unwrap();
+
+            for a in [0u16, 1, 9, 10, 11, 999, 1000, 12345, u16::MAX] {
+                let output: (u16, u16) = evaluate!(circ, fn(a) -> (u16, u16)).unwrap();
+                assert_eq!(output, (a / divisor, a % divisor));
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_const_is_smaller() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        let b = builder.add_input::<u32>();
+        builder.add_output(a / b);
+        let dynamic = builder.build().unwrap();
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        builder.add_output(a / 10u32);
+        let constant = builder.build().unwrap();
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        builder.add_output(a / 16u32);
+        let pow2 = builder.build().unwrap();
+
+        assert!(constant.and_count() < dynamic.and_count());
+        assert_eq!(pow2.and_count(), 0);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    types::{BinaryRepr, Bit, I128, I16, I32, I64, I8},
+    Tracer,
+};
+
+use super::{binary, Compare, WrappingAdd, WrappingMul, WrappingSub};
+
+/// A signed fixed-point number with `FRAC` fractional bits.
+///
+/// The number is represented by a two's complement integer `T`, where the raw integer `x`
+/// represents the real number `x / 2^FRAC`. Arithmetic wraps on overflow, and multiplication
+/// truncates the extra fractional bits towards negative infinity.
+///
+/// # Example
+///
+/// ```
+/// use mpz_circuits::{evaluate, ops::{Fixed, WrappingMul}, types::I32, CircuitBuilder};
+///
+/// type Q16 = Fixed<'static, I32, 16>;
+///
+/// let builder = CircuitBuilder::new();
+/// let a = Fixed::<I32, 16>::from_raw(builder.add_input::<i32>());
+/// let b = Fixed::<I32, 16>::from_raw(builder.add_input::<i32>());
+/// builder.add_output(a.wrapping_mul(b));
+/// let circ = builder.build().unwrap();
+///
+/// let a = Q16::encode(1.5);
+/// let b = Q16::encode(-2.25);
+/// let c: i32 = evaluate!(circ, fn(a, b) -> i32).unwrap();
+///
+/// assert_eq!(Q16::decode(c), -3.375);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Fixed<'a, T, const FRAC: usize> {
+    raw: Tracer<'a, T>,
+}
+
+impl<'a, T, const FRAC: usize> From<Fixed<'a, T, FRAC>> for BinaryRepr
+where
+    T: Into<BinaryRepr>,
+{
+    fn from(value: Fixed<'a, T, FRAC>) -> Self {
+        value.raw.into()
+    }
+}
+
+macro_rules! impl_fixed {
+    ($ty:ident, $prim:ident, $len:expr) => {
+        impl<'a, const FRAC: usize> Fixed<'a, $ty, FRAC> {
+            /// Creates a fixed-point number from its raw integer representation.
+            pub fn from_raw(raw: Tracer<'a, $ty>) -> Self {
+                assert!(
+                    FRAC < $len,
+                    "fractional bits must be less than the bit length"
+                );
+
+                Self { raw }
+            }
+
+            /// Returns the raw integer representation.
+            pub fn into_raw(self) -> Tracer<'a, $ty> {
+                self.raw
+            }
+
+            /// Converts an integer into a fixed-point number, wrapping on overflow.
+            pub fn from_int(value: Tracer<'a, $ty>) -> Self {
+                Self::from_raw(value << FRAC)
+            }
+
+            /// Converts the number into an integer, rounding towards negative infinity.
+            pub fn to_int(self) -> Tracer<'a, $ty> {
+                self.raw >> FRAC
+            }
+
+            /// Encodes a real number into the raw integer representation, rounding to the nearest
+            /// representable value.
+            pub fn encode(value: f64) -> $prim {
+                (value * (1u128 << FRAC) as f64).round() as $prim
+            }
+
+            /// Decodes a raw integer representation into a real number.
+            pub fn decode(raw: $prim) -> f64 {
+                raw as f64 / (1u128 << FRAC) as f64
+            }
+        }
+
+        impl<'a, const FRAC: usize> WrappingAdd<Fixed<'a, $ty, FRAC>> for Fixed<'a, $ty, FRAC> {
+            type Output = Fixed<'a, $ty, FRAC>;
+
+            fn wrapping_add(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Self::from_raw(self.raw.wrapping_add(rhs.raw))
+            }
+        }
+
+        impl<'a, const FRAC: usize> WrappingSub<Fixed<'a, $ty, FRAC>> for Fixed<'a, $ty, FRAC> {
+            type Output = Fixed<'a, $ty, FRAC>;
+
+            fn wrapping_sub(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Self::from_raw(self.raw.wrapping_sub(rhs.raw))
+            }
+        }
+
+        impl<'a, const FRAC: usize> WrappingMul<Fixed<'a, $ty, FRAC>> for Fixed<'a, $ty, FRAC> {
+            type Output = Fixed<'a, $ty, FRAC>;
+
+            fn wrapping_mul(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                let state = self.raw.state;
+
+                // Sign-extend both operands to twice the width so the product does not overflow
+                // before rescaling.
+                let sign_extend = |nodes: [_; $len]| {
+                    nodes
+                        .into_iter()
+                        .chain(std::iter::repeat(nodes[$len - 1]).take($len))
+                        .collect::<Vec<_>>()
+                };
+
+                let a = sign_extend(self.raw.to_inner().nodes());
+                let b = sign_extend(rhs.raw.to_inner().nodes());
+
+                let product = binary::wrapping_mul_nbit(&mut state.borrow_mut(), &a, &b);
+
+                let value = <$ty>::new(
+                    product[FRAC..FRAC + $len]
+                        .try_into()
+                        .expect("product should be wide enough"),
+                );
+
+                Self::from_raw(Tracer::new(state, value))
+            }
+        }
+
+        impl<'a, const FRAC: usize> Compare<Fixed<'a, $ty, FRAC>> for Fixed<'a, $ty, FRAC> {
+            type Output = Tracer<'a, Bit>;
+
+            fn eq(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::eq(self.raw, rhs.raw)
+            }
+
+            fn ne(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::ne(self.raw, rhs.raw)
+            }
+
+            fn lt(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::lt(self.raw, rhs.raw)
+            }
+
+            fn le(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::le(self.raw, rhs.raw)
+            }
+
+            fn gt(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::gt(self.raw, rhs.raw)
+            }
+
+            fn ge(self, rhs: Fixed<'a, $ty, FRAC>) -> Self::Output {
+                Compare::ge(self.raw, rhs.raw)
+            }
+        }
+    };
+}
+
+impl_fixed!(I8, i8, 8);
+impl_fixed!(I16, i16, 16);
+impl_fixed!(I32, i32, 32);
+impl_fixed!(I64, i64, 64);
+impl_fixed!(I128, i128, 128);
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits_macros::evaluate;
+
+    use super::*;
+    use crate::CircuitBuilder;
+
+    type Q8 = Fixed<'static, I16, 8>;
+
+    #[test]
+    fn test_fixed_arith() {
+        let builder = CircuitBuilder::new();
+        let a = Fixed::<I16, 8>::from_raw(builder.add_input::<i16>());
+        let b = Fixed::<I16, 8>::from_raw(builder.add_input::<i16>());
+
+        builder.add_output(a.wrapping_add(b));
+        builder.add_output(a.wrapping_sub(b));
+        builder.add_output(a.wrapping_mul(b));
+        builder.add_output(Compare::lt(a, b));
+        builder.add_output(a.to_int());
+
+        let circ = builder.build().unwrap();
+
+        for (a, b) in [(1.5, -2.25), (-0.5, -0.5), (3.75, 2.0), (-7.0, 0.125)] {
+            let (a, b) = (Q8::encode(a), Q8::encode(b));
+
+            let (sum, diff, product, lt, int): (i16, i16, i16, bool, i16) =
+                evaluate!(circ, fn(a, b) -> (i16, i16, i16, bool, i16)).unwrap();
+
+            assert_eq!(Q8::decode(sum), Q8::decode(a) + Q8::decode(b));
+            assert_eq!(Q8::decode(diff), Q8::decode(a) - Q8::decode(b));
+            assert_eq!(Q8::decode(product), Q8::decode(a) * Q8::decode(b));
+            assert_eq!(lt, a < b);
+            assert_eq!(int, Q8::decode(a).floor() as i16);
+        }
+    }
+}
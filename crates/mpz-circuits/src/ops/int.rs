@@ -0,0 +1,197 @@
+use std::ops::Shr;
+
+use crate::{
+    types::{I128, I16, I32, I64, I8, U128, U16, U32, U64, U8},
+    BuilderState, Feed, Node, Tracer,
+};
+
+use super::{binary, Cast, WrappingNeg};
+
+macro_rules! impl_wrapping_neg_int {
+    ($ty:ident, $len:expr) => {
+        impl<'a> WrappingNeg for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn wrapping_neg(self) -> Self::Output {
+                let mut state = self.state.borrow_mut();
+
+                let zero = [state.get_const_zero(); $len];
+                let (nodes, _) = binary::const_wrapping_sub_nbit::<$len>(
+                    &mut state,
+                    zero,
+                    self.to_inner().nodes(),
+                );
+
+                let value = <$ty>::new(nodes);
+
+                drop(state);
+
+                Tracer::new(self.state, value)
+            }
+        }
+    };
+}
+
+impl_wrapping_neg_int!(I8, 8);
+impl_wrapping_neg_int!(I16, 16);
+impl_wrapping_neg_int!(I32, 32);
+impl_wrapping_neg_int!(I64, 64);
+impl_wrapping_neg_int!(I128, 128);
+
+macro_rules! impl_shr_int {
+    ($ty:ident, $len:expr) => {
+        impl<'a> Shr<usize> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            /// Arithmetic shift right, the sign bit is shifted in.
+            fn shr(self, rhs: usize) -> Self::Output {
+                assert!(rhs <= $len);
+
+                let mut nodes = self.to_inner().nodes();
+                let sign = nodes[$len - 1];
+                // Bits are LSB0, so we rotate left
+                nodes.rotate_left(rhs);
+                // Replace the msbs with the sign bit
+                nodes[$len - rhs..].iter_mut().for_each(|node| *node = sign);
+
+                let value = <$ty>::new(nodes);
+
+                Tracer::new(self.state, value)
+            }
+        }
+    };
+}
+
+impl_shr_int!(I8, 8);
+impl_shr_int!(I16, 16);
+impl_shr_int!(I32, 32);
+impl_shr_int!(I64, 64);
+impl_shr_int!(I128, 128);
+
+/// Returns the node used to extend an unsigned value.
+fn zero_extend(state: &BuilderState, _nodes: &[Node<Feed>]) -> Node<Feed> {
+    state.get_const_zero()
+}
+
+/// Returns the node used to extend a signed value.
+fn sign_extend(_state: &BuilderState, nodes: &[Node<Feed>]) -> Node<Feed> {
+    nodes[nodes.len() - 1]
+}
+
+macro_rules! impl_cast {
+    ($from:ident, $from_len:expr, $extend:ident => $($to:ident, $to_len:expr);+) => {
+        $(
+            impl<'a> Cast<Tracer<'a, $to>> for Tracer<'a, $from> {
+                fn cast(self) -> Tracer<'a, $to> {
+                    let nodes = self.to_inner().nodes();
+                    let fill = $extend(&self.state.borrow(), &nodes);
+
+                    let value = <$to>::new(std::array::from_fn(|i| {
+                        if i < $from_len {
+                            nodes[i]
+                        } else {
+                            fill
+                        }
+                    }));
+
+                    Tracer::new(self.state, value)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_cast_all {
+    ($($from:ident, $from_len:expr, $extend:ident);+) => {
+        $(
+            impl_cast!($from, $from_len, $extend =>
+                U8, 8; U16, 16; U32, 32; U64, 64; U128, 128;
+                I8, 8; I16, 16; I32, 32; I64, 64; I128, 128
+            );
+        )+
+    };
+}
+
+impl_cast_all!(
+    U8, 8, zero_extend;
+    U16, 16, zero_extend;
+    U32, 32, zero_extend;
+    U64, 64, zero_extend;
+    U128, 128, zero_extend;
+    I8, 8, sign_extend;
+    I16, 16, sign_extend;
+    I32, 32, sign_extend;
+    I64, 64, sign_extend;
+    I128, 128, sign_extend
+);
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits_macros::{evaluate, test_circ, trace};
+
+    use super::*;
+    use crate::{
+        ops::{WrappingAdd, WrappingMul, WrappingSub},
+        CircuitBuilder,
+    };
+
+    #[trace]
+    fn signed_arith(a: i16, b: i16) -> i16 {
+        a.wrapping_mul(b).wrapping_sub(a).wrapping_add(b)
+    }
+
+    #[test]
+    fn test_signed_arith() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<i16>();
+        let b = builder.add_input::<i16>();
+        let c = signed_arith_trace(builder.state(), a, b);
+        builder.add_output(c);
+        let circ = builder.build().unwrap();
+
+        for (a, b) in [(-5i16, 7i16), (i16::MIN, -1), (300, -300), (0, i16::MAX)] {
+            test_circ!(circ, signed_arith, fn(a, b) -> i16);
+        }
+    }
+
+    #[test]
+    fn test_wrapping_neg_and_shr() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<i8>();
+        builder.add_output(a.wrapping_neg());
+        builder.add_output(a >> 3);
+        let circ = builder.build().unwrap();
+
+        for a in i8::MIN..=i8::MAX {
+            let (neg, shr): (i8, i8) = evaluate!(circ, fn(a) -> (i8, i8)).unwrap();
+
+            assert_eq!(neg, a.wrapping_neg());
+            assert_eq!(shr, a >> 3);
+        }
+    }
+
+    #[test]
+    fn test_cast() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<i8>();
+        let b = builder.add_input::<u16>();
+
+        let a_i32: Tracer<'_, I32> = a.cast();
+        let a_u32: Tracer<'_, U32> = a.cast();
+        let b_i8: Tracer<'_, I8> = b.cast();
+        let b_i64: Tracer<'_, I64> = b.cast();
+
+        builder.add_output(a_i32);
+        builder.add_output(a_u32);
+        builder.add_output(b_i8);
+        builder.add_output(b_i64);
+        let circ = builder.build().unwrap();
+
+        for (a, b) in [(-1i8, 0xffffu16), (127, 0x1280), (-128, 0x00ff)] {
+            let output: (i32, u32, i8, i64) =
+                evaluate!(circ, fn(a, b) -> (i32, u32, i8, i64)).unwrap();
+
+            assert_eq!(output, (a as i32, a as u32, b as i8, b as i64));
+        }
+    }
+}
@@ -1,8 +1,18 @@
 //! Operations on binary encoded types.
 
 pub(crate) mod binary;
+mod cmp;
+mod div;
+mod fixed;
+mod int;
+mod mux;
+mod network;
 mod uint;
 
+pub use fixed::Fixed;
+pub use mux::{assign_if, bounded_loop, mux_n};
+pub use network::{permutation_control_bits, permutation_switch_count, permute, sort, sort_by};
+
 /// Addition of two integers using so called "wrapping addition", which
 /// allows bit overflow.
 pub trait WrappingAdd<Rhs> {
@@ -34,3 +44,77 @@ pub trait WrappingSub<Rhs> {
     /// ```
     fn wrapping_sub(self, rhs: Rhs) -> Self::Output;
 }
+
+/// Multiplication of two integers using so called "wrapping multiplication", which
+/// allows bit overflow.
+pub trait WrappingMul<Rhs> {
+    /// The result type after wrapping multiplication.
+    type Output;
+
+    /// Multiplies two integers with wrapping multiplication.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(16u8.wrapping_mul(17u8), 16u8);
+    /// ```
+    fn wrapping_mul(self, rhs: Rhs) -> Self::Output;
+}
+
+/// Negation of a two's complement integer using so called "wrapping negation", which
+/// allows bit overflow.
+pub trait WrappingNeg {
+    /// The result type after wrapping negation.
+    type Output;
+
+    /// Negates an integer with wrapping negation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(i8::MIN.wrapping_neg(), i8::MIN);
+    /// ```
+    fn wrapping_neg(self) -> Self::Output;
+}
+
+/// Comparison of two integers, producing a single bit.
+///
+/// Unsigned integers are compared by magnitude, signed integers are compared
+/// using their two's complement interpretation.
+pub trait Compare<Rhs> {
+    /// The result type of the comparison.
+    type Output;
+
+    /// Returns whether `self == rhs`.
+    fn eq(self, rhs: Rhs) -> Self::Output;
+
+    /// Returns whether `self != rhs`.
+    fn ne(self, rhs: Rhs) -> Self::Output;
+
+    /// Returns whether `self < rhs`.
+    fn lt(self, rhs: Rhs) -> Self::Output;
+
+    /// Returns whether `self <= rhs`.
+    fn le(self, rhs: Rhs) -> Self::Output;
+
+    /// Returns whether `self > rhs`.
+    fn gt(self, rhs: Rhs) -> Self::Output;
+
+    /// Returns whether `self >= rhs`.
+    fn ge(self, rhs: Rhs) -> Self::Output;
+}
+
+/// Conversion between integer types with the same semantics as Rust's `as` operator.
+///
+/// Widening a signed integer sign-extends it, widening an unsigned integer zero-extends it,
+/// and narrowing truncates the most significant bits.
+pub trait Cast<T> {
+    /// Converts the value into `T`.
+    fn cast(self) -> T;
+}
+
+/// Conditional selection between two values, controlled by a bit.
+pub trait Mux<T> {
+    /// Returns `if_true` if the bit is set, otherwise returns `if_false`.
+    fn mux(self, if_true: T, if_false: T) -> T;
+}
@@ -0,0 +1,280 @@
+use crate::{
+    types::{Bit, I128, I16, I32, I64, I8, U128, U16, U32, U64, U8},
+    Tracer,
+};
+
+use super::{binary, Mux};
+
+macro_rules! impl_mux {
+    ($ty:ident) => {
+        impl<'a> Mux<Tracer<'a, $ty>> for Tracer<'a, Bit> {
+            fn mux(self, if_true: Tracer<'a, $ty>, if_false: Tracer<'a, $ty>) -> Tracer<'a, $ty> {
+                let mut state = self.state.borrow_mut();
+
+                let nodes = binary::switch_nbit(
+                    &mut state,
+                    &if_false.to_inner().nodes(),
+                    &if_true.to_inner().nodes(),
+                    self.node(),
+                );
+
+                let value = <$ty>::new(nodes.try_into().expect("length should match"));
+
+                drop(state);
+
+                Tracer::new(self.state, value)
+            }
+        }
+    };
+}
+
+impl_mux!(Bit);
+impl_mux!(U8);
+impl_mux!(U16);
+impl_mux!(U32);
+impl_mux!(U64);
+impl_mux!(U128);
+impl_mux!(I8);
+impl_mux!(I16);
+impl_mux!(I32);
+impl_mux!(I64);
+impl_mux!(I128);
+
+impl<'a, T, const N: usize> Mux<[T; N]> for Tracer<'a, Bit>
+where
+    Tracer<'a, Bit>: Mux<T>,
+{
+    fn mux(self, if_true: [T; N], if_false: [T; N]) -> [T; N] {
+        let mut if_false = if_false.into_iter();
+        if_true.map(|if_true| self.mux(if_true, if_false.next().unwrap()))
+    }
+}
+
+impl<'a, T> Mux<Vec<T>> for Tracer<'a, Bit>
+where
+    Tracer<'a, Bit>: Mux<T>,
+{
+    fn mux(self, if_true: Vec<T>, if_false: Vec<T>) -> Vec<T> {
+        assert_eq!(if_true.len(), if_false.len());
+
+        if_true
+            .into_iter()
+            .zip(if_false)
+            .map(|(if_true, if_false)| self.mux(if_true, if_false))
+            .collect()
+    }
+}
+
+impl<'a, A, B> Mux<(A, B)> for Tracer<'a, Bit>
+where
+    Tracer<'a, Bit>: Mux<A> + Mux<B>,
+{
+    fn mux(self, if_true: (A, B), if_false: (A, B)) -> (A, B) {
+        (
+            self.mux(if_true.0, if_false.0),
+            self.mux(if_true.1, if_false.1),
+        )
+    }
+}
+
+/// Selects one of the provided options using an index.
+///
+/// The circuit is a tree of two-way multiplexers, which requires `options.len() - 1`
+/// multiplexers. Options may be constants, which are folded into the multiplexers.
+///
+/// # Arguments
+///
+/// * `index` - The bits of the index, least significant bit first.
+/// * `options` - The options to select from.
+///
+/// # Returns
+///
+/// `options[index]`. If the index is out of range, one of the options is returned but
+/// which one is unspecified.
+///
+/// # Panics
+///
+/// Panics if `options` is empty or has more than `2^index.len()` elements.
+pub fn mux_n<'a, T>(index: &[Tracer<'a, Bit>], options: &[T]) -> T
+where
+    T: Clone,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    assert!(!options.is_empty(), "options must not be empty");
+    assert!(
+        index.len() >= usize::BITS as usize || options.len() <= 1 << index.len(),
+        "index is too narrow to select from {} options",
+        options.len()
+    );
+
+    let mut layer = options.to_vec();
+    for bit in index {
+        if layer.len() == 1 {
+            break;
+        }
+
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [if_false, if_true] => bit.mux(if_true.clone(), if_false.clone()),
+                [odd] => odd.clone(),
+                _ => unreachable!("chunks have at most 2 elements"),
+            })
+            .collect();
+    }
+
+    layer.pop().expect("layer should not be empty")
+}
+
+/// Assigns `value` to `target` if `cond` is set, otherwise leaves `target` unchanged.
+///
+/// This is the circuit equivalent of `if cond { *target = value }`.
+pub fn assign_if<'a, T>(cond: Tracer<'a, Bit>, target: &mut T, value: T)
+where
+    T: Clone,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    *target = cond.mux(value, target.clone());
+}
+
+/// Unrolls a loop with a data-dependent exit condition, bounded by a maximum number
+/// of iterations.
+///
+/// The loop has do-while semantics. The body is called with the iteration index and the
+/// current state, and returns the next state along with a bit indicating whether the loop
+/// should continue. Once the body returns a cleared bit, the state of that iteration is
+/// retained and all remaining iterations have no effect.
+///
+/// The body is always traced `max_iterations` times, so the size of the circuit scales
+/// with the bound rather than the number of iterations which are actually taken.
+///
+/// # Arguments
+///
+/// * `max_iterations` - The maximum number of iterations.
+/// * `init` - The initial state.
+/// * `body` - The loop body.
+///
+/// # Example
+///
+/// ```
+/// use mpz_circuits::{evaluate, ops::{bounded_loop, Compare, Mux, WrappingSub}, CircuitBuilder};
+///
+/// let builder = CircuitBuilder::new();
+/// let a = builder.add_input::<u8>();
+/// let b = builder.add_input::<u8>();
+///
+/// // Computes `a % b` by repeated subtraction.
+/// let rem = bounded_loop(255, a, |_, a| {
+///     let next = Compare::ge(a, b).mux(a.wrapping_sub(b), a);
+///     (next, Compare::ge(next, b))
+/// });
+///
+/// builder.add_output(rem);
+/// let circ = builder.build().unwrap();
+///
+/// let rem: u8 = evaluate!(circ, fn(200u8, 7u8) -> u8).unwrap();
+/// assert_eq!(rem, 200 % 7);
+/// ```
+pub fn bounded_loop<'a, S, F>(max_iterations: usize, init: S, mut body: F) -> S
+where
+    S: Clone,
+    F: FnMut(usize, S) -> (S, Tracer<'a, Bit>),
+    Tracer<'a, Bit>: Mux<S>,
+{
+    let mut state = init;
+    let mut active: Option<Tracer<'a, Bit>> = None;
+    for i in 0..max_iterations {
+        let (next, cont) = body(i, state.clone());
+
+        (state, active) = match active {
+            // The first iteration is always taken.
+            None => (next, Some(cont)),
+            Some(active) => (active.mux(next, state), Some(active & cont)),
+        };
+    }
+
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits_macros::evaluate;
+
+    use super::*;
+    use crate::{
+        ops::{Compare, WrappingAdd},
+        CircuitBuilder,
+    };
+
+    #[test]
+    fn test_mux() {
+        let builder = CircuitBuilder::new();
+        let cond = builder.add_input::<bool>();
+        let a = builder.add_array_input::<u16, 2>();
+        let b = builder.add_array_input::<u16, 2>();
+        builder.add_output(cond.mux(a, b));
+        let circ = builder.build().unwrap();
+
+        let a = [1u16, 2u16];
+        let b = [3u16, 4u16];
+
+        let out: [u16; 2] = evaluate!(circ, fn(true, a, b) -> [u16; 2]).unwrap();
+        assert_eq!(out, a);
+
+        let out: [u16; 2] = evaluate!(circ, fn(false, a, b) -> [u16; 2]).unwrap();
+        assert_eq!(out, b);
+    }
+
+    #[test]
+    fn test_mux_n() {
+        let options = [10u8, 20, 30, 40, 50];
+
+        let builder = CircuitBuilder::new();
+        let index = builder.add_input::<u8>();
+        let options_traced = options.map(|option| builder.get_constant(option));
+        let index_bits = index.to_lsb0_bits();
+        builder.add_output(mux_n(&index_bits[..3], &options_traced));
+        let circ = builder.build().unwrap();
+
+        for (i, expected) in options.iter().enumerate() {
+            let out: u8 = evaluate!(circ, fn(i as u8) -> u8).unwrap();
+            assert_eq!(out, *expected);
+        }
+    }
+
+    #[test]
+    fn test_assign_if() {
+        let builder = CircuitBuilder::new();
+        let cond = builder.add_input::<bool>();
+        let mut a = builder.add_input::<u32>();
+        let b = a.wrapping_add(1u32);
+        assign_if(cond, &mut a, b);
+        builder.add_output(a);
+        let circ = builder.build().unwrap();
+
+        let out: u32 = evaluate!(circ, fn(true, 41u32) -> u32).unwrap();
+        assert_eq!(out, 42);
+
+        let out: u32 = evaluate!(circ, fn(false, 41u32) -> u32).unwrap();
+        assert_eq!(out, 41);
+    }
+
+    #[test]
+    fn test_bounded_loop() {
+        let builder = CircuitBuilder::new();
+        let n = builder.add_input::<u8>();
+
+        // Counts the number of iterations until the counter reaches `n`, capped at 8.
+        let count = bounded_loop(8, builder.get_constant(0u8), |_, count| {
+            let next = count.wrapping_add(1u8);
+            (next, Compare::lt(next, n))
+        });
+        builder.add_output(count);
+        let circ = builder.build().unwrap();
+
+        for n in 0u8..16 {
+            let count: u8 = evaluate!(circ, fn(n) -> u8).unwrap();
+            assert_eq!(count, n.clamp(1, 8));
+        }
+    }
+}
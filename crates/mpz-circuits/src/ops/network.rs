@@ -0,0 +1,451 @@
+//! Permutation and sorting networks.
+//!
+//! These are data-oblivious, the gates of the circuit only depend on the number of values, so
+//! they can be used to shuffle and sort values which are garbled or secret-shared.
+//!
+//! To shuffle values such that neither party learns the permutation, each party samples a
+//! random permutation and computes its control bits with [`permutation_control_bits`], which it
+//! provides as a private input. The values are then permuted once with each party's control bits.
+
+use crate::{types::Bit, Tracer};
+
+use super::{Compare, Mux};
+
+/// Returns the number of switches in a permutation network over `n` values.
+///
+/// This is `n log2(n) - n / 2` when `n` is a power of two, and is roughly the same for other
+/// values of `n`.
+pub fn permutation_switch_count(n: usize) -> usize {
+    match n {
+        0 | 1 => 0,
+        2 => 1,
+        _ => {
+            let half = n / 2;
+            2 * half + permutation_switch_count(half) + permutation_switch_count(n - half)
+        }
+    }
+}
+
+/// Computes the control bits which program a permutation network to apply a permutation.
+///
+/// The control bits can be provided to [`permute`] with values of the same length.
+///
+/// # Arguments
+///
+/// * `permutation` - The permutation, where output `i` of the network is input `permutation[i]`.
+///
+/// # Panics
+///
+/// Panics if `permutation` is not a permutation of `0..permutation.len()`.
+pub fn permutation_control_bits(permutation: &[usize]) -> Vec<bool> {
+    let n = permutation.len();
+    let mut seen = vec![false; n];
+    for &i in permutation {
+        assert!(i < n && !seen[i], "invalid permutation");
+        seen[i] = true;
+    }
+
+    let mut bits = Vec::with_capacity(permutation_switch_count(n));
+    route(permutation, &mut bits);
+
+    bits
+}
+
+/// Permutes values with a permutation network.
+///
+/// The network is a recursive Beneš network which supports any number of values. Each switch
+/// swaps a pair of values if its control bit is set.
+///
+/// # Arguments
+///
+/// * `values` - The values to permute.
+/// * `control_bits` - The control bits of the switches, see [`permutation_control_bits`].
+///
+/// # Panics
+///
+/// Panics if the number of control bits does not match [`permutation_switch_count`].
+pub fn permute<'a, T>(values: &[T], control_bits: &[Tracer<'a, Bit>]) -> Vec<T>
+where
+    T: Clone,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    assert_eq!(
+        control_bits.len(),
+        permutation_switch_count(values.len()),
+        "control bit count does not match the number of values"
+    );
+
+    permute_inner(values, &mut control_bits.iter().copied())
+}
+
+/// Sorts values in ascending order with a sorting network.
+///
+/// The sort is not stable.
+///
+/// # Arguments
+///
+/// * `values` - The values to sort.
+pub fn sort<'a, T>(values: &mut [T])
+where
+    T: Clone + Compare<T, Output = Tracer<'a, Bit>>,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    sort_by(values, |a, b| Compare::lt(a.clone(), b.clone()))
+}
+
+/// Sorts values with a sorting network, using a comparison function.
+///
+/// The network is Batcher's odd-even merge sort, which requires `O(n log^2(n))` comparisons. The
+/// sort is not stable.
+///
+/// # Arguments
+///
+/// * `values` - The values to sort.
+/// * `lt` - Returns whether the first value must be ordered before the second.
+///
+/// # Example
+///
+/// Values can carry a payload, and be sorted in descending order to select the top-k values.
+///
+/// ```
+/// use mpz_circuits::{evaluate, ops::{sort_by, Compare}, CircuitBuilder};
+///
+/// let builder = CircuitBuilder::new();
+/// let keys = builder.add_array_input::<u8, 4>();
+/// let payloads = builder.add_array_input::<u8, 4>();
+///
+/// let mut values: Vec<_> = keys.into_iter().zip(payloads).collect();
+/// sort_by(&mut values, |(a, _), (b, _)| Compare::gt(*a, *b));
+///
+/// let (_, top): (Vec<_>, Vec<_>) = values.into_iter().take(2).unzip();
+/// builder.add_output(top);
+/// let circ = builder.build().unwrap();
+///
+/// let top: Vec<u8> = evaluate!(circ, fn([3u8, 9, 1, 7], [30u8, 90, 10, 70]) -> Vec<u8>).unwrap();
+/// assert_eq!(top, vec![90, 70]);
+/// ```
+pub fn sort_by<'a, T, F>(values: &mut [T], mut lt: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Tracer<'a, Bit>,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    for (i, j) in sorting_network(values.len()) {
+        let swap = lt(&values[j], &values[i]);
+        let (a, b) = cswap(swap, values[i].clone(), values[j].clone());
+        values[i] = a;
+        values[j] = b;
+    }
+}
+
+/// Swaps two values if the bit is set.
+fn cswap<'a, T>(bit: Tracer<'a, Bit>, a: T, b: T) -> (T, T)
+where
+    T: Clone,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    (bit.mux(b.clone(), a.clone()), bit.mux(a, b))
+}
+
+fn permute_inner<'a, T>(values: &[T], bits: &mut impl Iterator<Item = Tracer<'a, Bit>>) -> Vec<T>
+where
+    T: Clone,
+    Tracer<'a, Bit>: Mux<T>,
+{
+    let n = values.len();
+    let mut next_bit = || bits.next().expect("control bit count was checked");
+
+    match n {
+        0 | 1 => return values.to_vec(),
+        2 => {
+            let (a, b) = cswap(next_bit(), values[0].clone(), values[1].clone());
+            return vec![a, b];
+        }
+        _ => {}
+    }
+
+    let half = n / 2;
+    let mut upper = Vec::with_capacity(half);
+    let mut lower = Vec::with_capacity(n - half);
+    for pair in values.chunks_exact(2) {
+        let (a, b) = cswap(next_bit(), pair[0].clone(), pair[1].clone());
+        upper.push(a);
+        lower.push(b);
+    }
+
+    // The last value of an odd number of values is routed through the lower subnetwork.
+    if n % 2 == 1 {
+        lower.push(values[n - 1].clone());
+    }
+
+    let upper = permute_inner(&upper, bits);
+    let lower = permute_inner(&lower, bits);
+
+    let mut outputs = Vec::with_capacity(n);
+    for (a, b) in upper.into_iter().zip(lower.iter().cloned()) {
+        let (a, b) = cswap(bits.next().expect("control bit count was checked"), a, b);
+        outputs.push(a);
+        outputs.push(b);
+    }
+
+    if n % 2 == 1 {
+        outputs.push(lower[n - half - 1].clone());
+    }
+
+    outputs
+}
+
+/// Computes the control bits of a permutation network, in the order they are consumed by
+/// [`permute_inner`].
+fn route(permutation: &[usize], bits: &mut Vec<bool>) {
+    let n = permutation.len();
+    match n {
+        0 | 1 => return,
+        2 => {
+            bits.push(permutation[0] == 1);
+            return;
+        }
+        _ => {}
+    }
+
+    let half = n / 2;
+    let odd = n % 2 == 1;
+
+    let mut dest = vec![0; n];
+    for (i, &input) in permutation.iter().enumerate() {
+        dest[input] = i;
+    }
+
+    // Inputs which share a switch must be routed through different subnetworks, as must the
+    // inputs of outputs which share a switch. These constraints form paths and even cycles, so
+    // the inputs can always be 2-colored. The unpaired input and output of an odd network are
+    // fixed to the lower subnetwork.
+    let partners = |input: usize| {
+        let input_partner = (!(odd && input == n - 1)).then_some(input ^ 1);
+        let output_partner = (!(odd && dest[input] == n - 1)).then(|| permutation[dest[input] ^ 1]);
+        [input_partner, output_partner]
+    };
+
+    // Whether each input is routed through the lower subnetwork.
+    let mut lower: Vec<Option<bool>> = vec![None; n];
+    let mut color = |start: usize, is_lower: bool| {
+        let mut stack = vec![(start, is_lower)];
+        while let Some((input, is_lower)) = stack.pop() {
+            if lower[input].is_some() {
+                continue;
+            }
+
+            lower[input] = Some(is_lower);
+            stack.extend(
+                partners(input)
+                    .into_iter()
+                    .flatten()
+                    .map(|p| (p, !is_lower)),
+            );
+        }
+    };
+
+    if odd {
+        color(n - 1, true);
+        color(permutation[n - 1], true);
+    }
+
+    for input in 0..n {
+        color(input, false);
+    }
+
+    let lower: Vec<bool> = lower
+        .into_iter()
+        .map(|is_lower| is_lower.expect("all inputs are colored"))
+        .collect();
+
+    let mut upper_permutation = vec![0; half];
+    let mut lower_permutation = vec![0; n - half];
+    for input in 0..n {
+        if lower[input] {
+            lower_permutation[dest[input] / 2] = input / 2;
+        } else {
+            upper_permutation[dest[input] / 2] = input / 2;
+        }
+    }
+
+    bits.extend((0..half).map(|i| lower[2 * i]));
+    route(&upper_permutation, bits);
+    route(&lower_permutation, bits);
+    bits.extend((0..half).map(|i| lower[permutation[2 * i]]));
+}
+
+/// Returns the comparators of Batcher's odd-even merge sort over `n` values.
+///
+/// Comparators which involve values beyond `n` are omitted, which is equivalent to padding the
+/// values to a power of two with values which are greater than all others.
+fn sorting_network(n: usize) -> Vec<(usize, usize)> {
+    let mut comparators = Vec::new();
+
+    let mut p = 1;
+    while p < n {
+        let mut k = p;
+        while k >= 1 {
+            let mut j = k % p;
+            while j + k < n {
+                for i in 0..k.min(n - j - k) {
+                    if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                        comparators.push((i + j, i + j + k));
+                    }
+                }
+                j += 2 * k;
+            }
+            k /= 2;
+        }
+        p *= 2;
+    }
+
+    comparators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    use crate::{types::Value, CircuitBuilder};
+
+    #[test]
+    fn test_permutation_control_bits() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in (0..20).chain([33, 64, 100]) {
+            for _ in 0..10 {
+                let mut permutation: Vec<usize> = (0..n).collect();
+                permutation.shuffle(&mut rng);
+
+                let bits = permutation_control_bits(&permutation);
+                assert_eq!(bits.len(), permutation_switch_count(n));
+
+                // Route plaintext values through the network.
+                let mut bits = bits.into_iter();
+                let outputs = route_plaintext(&(0..n).collect::<Vec<_>>(), &mut bits);
+
+                assert_eq!(outputs, permutation);
+            }
+        }
+    }
+
+    /// Mirrors `permute_inner` on plaintext values.
+    fn route_plaintext(values: &[usize], bits: &mut impl Iterator<Item = bool>) -> Vec<usize> {
+        let n = values.len();
+        let mut swap = |a: usize, b: usize| if bits.next().unwrap() { (b, a) } else { (a, b) };
+
+        match n {
+            0 | 1 => return values.to_vec(),
+            2 => {
+                let (a, b) = swap(values[0], values[1]);
+                return vec![a, b];
+            }
+            _ => {}
+        }
+
+        let (mut upper, mut lower): (Vec<_>, Vec<_>) = values
+            .chunks_exact(2)
+            .map(|pair| swap(pair[0], pair[1]))
+            .unzip();
+        if n % 2 == 1 {
+            lower.push(values[n - 1]);
+        }
+
+        upper = route_plaintext(&upper, bits);
+        lower = route_plaintext(&lower, bits);
+
+        let mut outputs: Vec<usize> = upper
+            .iter()
+            .zip(&lower)
+            .flat_map(|(&a, &b)| {
+                let (a, b) = if bits.next().unwrap() { (b, a) } else { (a, b) };
+                [a, b]
+            })
+            .collect();
+        if n % 2 == 1 {
+            outputs.push(*lower.last().unwrap());
+        }
+
+        outputs
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_permutation_control_bits_invalid() {
+        permutation_control_bits(&[0, 0, 1]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [2, 5, 8, 13] {
+            let builder = CircuitBuilder::new();
+            let values = builder.add_vec_input::<u32>(n);
+            let bits = builder.add_vec_input::<bool>(permutation_switch_count(n));
+
+            builder.add_output(permute(&values, &bits));
+            let circ = builder.build().unwrap();
+
+            let mut permutation: Vec<usize> = (0..n).collect();
+            permutation.shuffle(&mut rng);
+            let values: Vec<u32> = (0..n).map(|_| rng.gen()).collect();
+
+            let output = circ
+                .evaluate(&[
+                    Value::from(values.clone()),
+                    Value::from(permutation_control_bits(&permutation)),
+                ])
+                .unwrap()
+                .pop()
+                .unwrap();
+            let output: Vec<u32> = output.try_into().unwrap();
+
+            let expected: Vec<u32> = permutation.iter().map(|&i| values[i]).collect();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for n in [1, 2, 3, 7, 16, 21] {
+            let builder = CircuitBuilder::new();
+            let mut values = builder.add_vec_input::<u16>(n);
+
+            sort(&mut values);
+            builder.add_output(values);
+            let circ = builder.build().unwrap();
+
+            // Include duplicates.
+            let mut values: Vec<u16> = (0..n).map(|_| rng.gen_range(0..8)).collect();
+            let output = circ
+                .evaluate(&[Value::from(values.clone())])
+                .unwrap()
+                .pop()
+                .unwrap();
+            let output: Vec<u16> = output.try_into().unwrap();
+
+            values.sort();
+            assert_eq!(output, values);
+        }
+    }
+
+    #[test]
+    fn test_sorting_network() {
+        // By the 0-1 principle, a network sorts all inputs if it sorts all binary inputs.
+        for n in 0..=12 {
+            let comparators = sorting_network(n);
+            for bits in 0..1u32 << n {
+                let mut values: Vec<u32> = (0..n).map(|i| (bits >> i) & 1).collect();
+                for &(i, j) in &comparators {
+                    if values[j] < values[i] {
+                        values.swap(i, j);
+                    }
+                }
+                assert!(values.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+}
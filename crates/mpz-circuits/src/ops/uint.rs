@@ -1,11 +1,15 @@
 use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
 
 use crate::{
-    types::{BinaryRepr, U128, U16, U32, U64, U8},
+    types::{BinaryRepr, Bit, I128, I16, I32, I64, I8, U128, U16, U32, U64, U8},
     Tracer,
 };
 
-use super::{binary, WrappingAdd, WrappingSub};
+use super::{binary, WrappingAdd, WrappingMul, WrappingSub};
+
+// Two's complement addition, subtraction, multiplication and the bitwise operations
+// are identical for signed and unsigned integers, so the implementations below are
+// shared between both.
 
 macro_rules! impl_wrapping_add_uint {
     ($ty:ident, $const_ty:ident, $len:expr) => {
@@ -56,6 +60,11 @@ impl_wrapping_add_uint!(U16, u16, 16);
 impl_wrapping_add_uint!(U32, u32, 32);
 impl_wrapping_add_uint!(U64, u64, 64);
 impl_wrapping_add_uint!(U128, u128, 128);
+impl_wrapping_add_uint!(I8, i8, 8);
+impl_wrapping_add_uint!(I16, i16, 16);
+impl_wrapping_add_uint!(I32, i32, 32);
+impl_wrapping_add_uint!(I64, i64, 64);
+impl_wrapping_add_uint!(I128, i128, 128);
 
 macro_rules! impl_wrapping_sub_uint {
     ($ty:ident, $const_ty:ident, $len:expr) => {
@@ -108,6 +117,65 @@ impl_wrapping_sub_uint!(U16, u16, 16);
 impl_wrapping_sub_uint!(U32, u32, 32);
 impl_wrapping_sub_uint!(U64, u64, 64);
 impl_wrapping_sub_uint!(U128, u128, 128);
+impl_wrapping_sub_uint!(I8, i8, 8);
+impl_wrapping_sub_uint!(I16, i16, 16);
+impl_wrapping_sub_uint!(I32, i32, 32);
+impl_wrapping_sub_uint!(I64, i64, 64);
+impl_wrapping_sub_uint!(I128, i128, 128);
+
+macro_rules! impl_wrapping_mul_uint {
+    ($ty:ident, $const_ty:ident, $len:expr) => {
+        impl<'a> WrappingMul<Tracer<'a, $ty>> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn wrapping_mul(self, rhs: Tracer<'a, $ty>) -> Self::Output {
+                let mut state = self.state.borrow_mut();
+
+                let nodes = binary::wrapping_mul_nbit(
+                    &mut state,
+                    &self.to_inner().nodes(),
+                    &rhs.to_inner().nodes(),
+                );
+
+                let value = <$ty>::new(nodes.try_into().expect("product should have same length"));
+
+                drop(state);
+
+                Tracer::new(self.state, value)
+            }
+        }
+
+        impl<'a> WrappingMul<$const_ty> for Tracer<'a, $ty> {
+            type Output = Tracer<'a, $ty>;
+
+            fn wrapping_mul(self, rhs: $const_ty) -> Self::Output {
+                let mut state = self.state.borrow_mut();
+
+                let rhs = state.get_constant::<$const_ty>(rhs);
+
+                let nodes =
+                    binary::wrapping_mul_nbit(&mut state, &self.to_inner().nodes(), &rhs.nodes());
+
+                let value = <$ty>::new(nodes.try_into().expect("product should have same length"));
+
+                drop(state);
+
+                Tracer::new(self.state, value)
+            }
+        }
+    };
+}
+
+impl_wrapping_mul_uint!(U8, u8, 8);
+impl_wrapping_mul_uint!(U16, u16, 16);
+impl_wrapping_mul_uint!(U32, u32, 32);
+impl_wrapping_mul_uint!(U64, u64, 64);
+impl_wrapping_mul_uint!(U128, u128, 128);
+impl_wrapping_mul_uint!(I8, i8, 8);
+impl_wrapping_mul_uint!(I16, i16, 16);
+impl_wrapping_mul_uint!(I32, i32, 32);
+impl_wrapping_mul_uint!(I64, i64, 64);
+impl_wrapping_mul_uint!(I128, i128, 128);
 
 impl<'a> BitXor for Tracer<'a, BinaryRepr> {
     type Output = Tracer<'a, BinaryRepr>;
@@ -138,6 +206,26 @@ impl<'a> BitXor for Tracer<'a, BinaryRepr> {
                 let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
                 Tracer::new(self.state, c.into())
             }
+            (BinaryRepr::I8(a), BinaryRepr::I8(b)) => {
+                let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I16(a), BinaryRepr::I16(b)) => {
+                let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I32(a), BinaryRepr::I32(b)) => {
+                let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I64(a), BinaryRepr::I64(b)) => {
+                let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I128(a), BinaryRepr::I128(b)) => {
+                let c = Tracer::new(self.state, a) ^ Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
             (BinaryRepr::Array(a), BinaryRepr::Array(b)) => Tracer::new(
                 self.state,
                 BinaryRepr::Array(
@@ -155,6 +243,72 @@ impl<'a> BitXor for Tracer<'a, BinaryRepr> {
     }
 }
 
+impl<'a> BitAnd for Tracer<'a, BinaryRepr> {
+    type Output = Tracer<'a, BinaryRepr>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        match (self.value, rhs.value) {
+            (BinaryRepr::Bit(a), BinaryRepr::Bit(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::U8(a), BinaryRepr::U8(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::U16(a), BinaryRepr::U16(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::U32(a), BinaryRepr::U32(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::U64(a), BinaryRepr::U64(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::U128(a), BinaryRepr::U128(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I8(a), BinaryRepr::I8(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I16(a), BinaryRepr::I16(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I32(a), BinaryRepr::I32(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I64(a), BinaryRepr::I64(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::I128(a), BinaryRepr::I128(b)) => {
+                let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                Tracer::new(self.state, c.into())
+            }
+            (BinaryRepr::Array(a), BinaryRepr::Array(b)) => Tracer::new(
+                self.state,
+                BinaryRepr::Array(
+                    a.into_iter()
+                        .zip(b)
+                        .map(|(a, b)| {
+                            let c = Tracer::new(self.state, a) & Tracer::new(self.state, b);
+                            c.value
+                        })
+                        .collect(),
+                ),
+            ),
+            (a, b) => panic!("types {:?} and {:?} are not compatible", a, b),
+        }
+    }
+}
+
 macro_rules! impl_bitxor_uint {
     ($ty:ident, $const_ty:ident, $len:expr) => {
         impl<'a> BitXor<Tracer<'a, $ty>> for Tracer<'a, $ty> {
@@ -202,6 +356,11 @@ impl_bitxor_uint!(U16, u16, 16);
 impl_bitxor_uint!(U32, u32, 32);
 impl_bitxor_uint!(U64, u64, 64);
 impl_bitxor_uint!(U128, u128, 128);
+impl_bitxor_uint!(I8, i8, 8);
+impl_bitxor_uint!(I16, i16, 16);
+impl_bitxor_uint!(I32, i32, 32);
+impl_bitxor_uint!(I64, i64, 64);
+impl_bitxor_uint!(I128, i128, 128);
 
 macro_rules! impl_bit_and_uint {
     ($ty:ident, $const_ty:ident, $len:expr) => {
@@ -250,6 +409,11 @@ impl_bit_and_uint!(U16, u16, 16);
 impl_bit_and_uint!(U32, u32, 32);
 impl_bit_and_uint!(U64, u64, 64);
 impl_bit_and_uint!(U128, u128, 128);
+impl_bit_and_uint!(I8, i8, 8);
+impl_bit_and_uint!(I16, i16, 16);
+impl_bit_and_uint!(I32, i32, 32);
+impl_bit_and_uint!(I64, i64, 64);
+impl_bit_and_uint!(I128, i128, 128);
 
 macro_rules! impl_bit_or_uint {
     ($ty:ident, $const_ty:ident, $len:expr) => {
@@ -298,6 +462,11 @@ impl_bit_or_uint!(U16, u16, 16);
 impl_bit_or_uint!(U32, u32, 32);
 impl_bit_or_uint!(U64, u64, 64);
 impl_bit_or_uint!(U128, u128, 128);
+impl_bit_or_uint!(I8, i8, 8);
+impl_bit_or_uint!(I16, i16, 16);
+impl_bit_or_uint!(I32, i32, 32);
+impl_bit_or_uint!(I64, i64, 64);
+impl_bit_or_uint!(I128, i128, 128);
 
 macro_rules! impl_shl_uint {
     ($ty:ident, $len:expr) => {
@@ -330,6 +499,11 @@ impl_shl_uint!(U16, 16);
 impl_shl_uint!(U32, 32);
 impl_shl_uint!(U64, 64);
 impl_shl_uint!(U128, 128);
+impl_shl_uint!(I8, 8);
+impl_shl_uint!(I16, 16);
+impl_shl_uint!(I32, 32);
+impl_shl_uint!(I64, 64);
+impl_shl_uint!(I128, 128);
 
 macro_rules! impl_shr_uint {
     ($ty:ident, $len:expr) => {
@@ -347,7 +521,7 @@ macro_rules! impl_shr_uint {
                 // Bits are LSB0, so we rotate left
                 nodes.rotate_left(rhs);
                 // Replace the msbs with 0s
-                nodes[$len - rhs - 1..]
+                nodes[$len - rhs..]
                     .iter_mut()
                     .for_each(|node| *node = const_zero);
 
@@ -386,6 +560,11 @@ impl_neg_uint!(U16);
 impl_neg_uint!(U32);
 impl_neg_uint!(U64);
 impl_neg_uint!(U128);
+impl_neg_uint!(I8);
+impl_neg_uint!(I16);
+impl_neg_uint!(I32);
+impl_neg_uint!(I64);
+impl_neg_uint!(I128);
 
 macro_rules! impl_convert_bytes {
     ($ty:ident, $len:expr) => {
@@ -415,6 +594,13 @@ macro_rules! impl_convert_bytes {
                     .to_le_bytes()
                     .map(|value| Tracer::new(self.state, value))
             }
+
+            /// Returns the bits of this value, least significant bit first.
+            pub fn to_lsb0_bits(self) -> [Tracer<'a, Bit>; $len * 8] {
+                self.to_inner()
+                    .nodes()
+                    .map(|node| Tracer::new(self.state, Bit::new([node])))
+            }
         }
     };
 }
@@ -424,3 +610,8 @@ impl_convert_bytes!(U16, 2);
 impl_convert_bytes!(U32, 4);
 impl_convert_bytes!(U64, 8);
 impl_convert_bytes!(U128, 16);
+impl_convert_bytes!(I8, 1);
+impl_convert_bytes!(I16, 2);
+impl_convert_bytes!(I32, 4);
+impl_convert_bytes!(I64, 8);
+impl_convert_bytes!(I128, 16);
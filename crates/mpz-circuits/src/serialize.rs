@@ -0,0 +1,447 @@
+//! Stable binary encoding of circuits.
+//!
+//! The encoding is independent of any serialization framework so that it can be relied upon
+//! for persisting circuits, exchanging them between parties and content addressing.
+//!
+//! # Format
+//!
+//! All integers are encoded as unsigned LEB128 unless noted otherwise.
+//!
+//! ```text
+//! circuit := magic "MPZC" | version (u16 le) | feed_count | inputs | outputs | gates
+//! inputs  := count | repr*
+//! outputs := count | repr*
+//! repr    := tag (u8) | node*            primitive types, node count given by the type
+//!          | tag (u8) | len | repr*      arrays
+//! gates   := count | gate*
+//! gate    := 0x00 | x | y | z            XOR
+//!          | 0x01 | x | y | z            AND
+//!          | 0x02 | x | z                INV
+//! ```
+
+use crate::{
+    components::{Feed, Gate, Node},
+    types::{BinaryRepr, ValueType},
+    Circuit,
+};
+
+/// The current version of the binary encoding.
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: [u8; 4] = *b"MPZC";
+const HASH_CONTEXT: &str = "mpz-circuits 2023 circuit hash";
+
+const TAG_ARRAY: u8 = 6;
+
+const GATE_XOR: u8 = 0;
+const GATE_AND: u8 = 1;
+const GATE_INV: u8 = 2;
+
+/// An error that can occur when decoding a circuit.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum DecodeError {
+    #[error("invalid magic bytes")]
+    InvalidMagic,
+    #[error("unsupported format version: {0}, expected: {FORMAT_VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+    #[error("integer overflow while decoding")]
+    Overflow,
+    #[error("invalid type tag: {0}")]
+    InvalidTypeTag(u8),
+    #[error("invalid gate tag: {0}")]
+    InvalidGateTag(u8),
+    #[error("node id {id} out of bounds, feed count: {feed_count}")]
+    InvalidNode { id: usize, feed_count: usize },
+    #[error("{0} trailing bytes after circuit")]
+    TrailingBytes(usize),
+}
+
+/// A hash of a circuit.
+///
+/// The hash commits to the inputs, outputs and gates of the circuit and can be compared by
+/// parties to make sure they are about to execute the same circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircuitHash([u8; 32]);
+
+impl CircuitHash {
+    /// Returns the hash as a byte array.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for CircuitHash {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl std::fmt::Display for CircuitHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Circuit {
+    /// Encodes the circuit using the stable binary encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.gates.len() * 8);
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        write_uint(&mut bytes, self.feed_count);
+
+        for reprs in [&self.inputs, &self.outputs] {
+            write_uint(&mut bytes, reprs.len());
+            for repr in reprs.iter() {
+                write_repr(&mut bytes, repr);
+            }
+        }
+
+        write_uint(&mut bytes, self.gates.len());
+        for gate in &self.gates {
+            match gate {
+                Gate::Xor { x, y, z } => {
+                    bytes.push(GATE_XOR);
+                    write_uint(&mut bytes, x.id);
+                    write_uint(&mut bytes, y.id);
+                    write_uint(&mut bytes, z.id);
+                }
+                Gate::And { x, y, z } => {
+                    bytes.push(GATE_AND);
+                    write_uint(&mut bytes, x.id);
+                    write_uint(&mut bytes, y.id);
+                    write_uint(&mut bytes, z.id);
+                }
+                Gate::Inv { x, z } => {
+                    bytes.push(GATE_INV);
+                    write_uint(&mut bytes, x.id);
+                    write_uint(&mut bytes, z.id);
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes a circuit from the stable binary encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded circuit.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::InvalidMagic);
+        }
+
+        let version = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let feed_count = reader.read_uint()?;
+
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for reprs in [&mut inputs, &mut outputs] {
+            let count = reader.read_uint()?;
+            for _ in 0..count {
+                reprs.push(reader.read_repr(feed_count)?);
+            }
+        }
+
+        let gate_count = reader.read_uint()?;
+        let mut gates = Vec::with_capacity(gate_count.min(reader.bytes.len()));
+        let mut and_count = 0;
+        let mut xor_count = 0;
+        for _ in 0..gate_count {
+            let gate = match reader.read_u8()? {
+                GATE_XOR => {
+                    xor_count += 1;
+                    Gate::Xor {
+                        x: reader.read_node(feed_count)?.into(),
+                        y: reader.read_node(feed_count)?.into(),
+                        z: reader.read_node(feed_count)?,
+                    }
+                }
+                GATE_AND => {
+                    and_count += 1;
+                    Gate::And {
+                        x: reader.read_node(feed_count)?.into(),
+                        y: reader.read_node(feed_count)?.into(),
+                        z: reader.read_node(feed_count)?,
+                    }
+                }
+                GATE_INV => Gate::Inv {
+                    x: reader.read_node(feed_count)?.into(),
+                    z: reader.read_node(feed_count)?,
+                },
+                tag => return Err(DecodeError::InvalidGateTag(tag)),
+            };
+            gates.push(gate);
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes(reader.bytes.len()));
+        }
+
+        Ok(Circuit {
+            inputs,
+            outputs,
+            gates,
+            feed_count,
+            and_count,
+            xor_count,
+        })
+    }
+
+    /// Returns a hash of the circuit, computed over its stable binary encoding.
+    pub fn hash(&self) -> CircuitHash {
+        let mut hasher = blake3::Hasher::new_derive_key(HASH_CONTEXT);
+        hasher.update(&self.to_bytes());
+        CircuitHash(hasher.finalize().into())
+    }
+}
+
+fn write_uint(bytes: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn type_tag(ty: &ValueType) -> u8 {
+    match ty {
+        ValueType::Bit => 0,
+        ValueType::U8 => 1,
+        ValueType::U16 => 2,
+        ValueType::U32 => 3,
+        ValueType::U64 => 4,
+        ValueType::U128 => 5,
+        ValueType::Array(..) => TAG_ARRAY,
+        ValueType::I8 => 7,
+        ValueType::I16 => 8,
+        ValueType::I32 => 9,
+        ValueType::I64 => 10,
+        ValueType::I128 => 11,
+    }
+}
+
+fn tag_type(tag: u8) -> Result<ValueType, DecodeError> {
+    Ok(match tag {
+        0 => ValueType::Bit,
+        1 => ValueType::U8,
+        2 => ValueType::U16,
+        3 => ValueType::U32,
+        4 => ValueType::U64,
+        5 => ValueType::U128,
+        7 => ValueType::I8,
+        8 => ValueType::I16,
+        9 => ValueType::I32,
+        10 => ValueType::I64,
+        11 => ValueType::I128,
+        tag => return Err(DecodeError::InvalidTypeTag(tag)),
+    })
+}
+
+fn write_repr(bytes: &mut Vec<u8>, repr: &BinaryRepr) {
+    match repr {
+        BinaryRepr::Array(elems) => {
+            bytes.push(TAG_ARRAY);
+            write_uint(bytes, elems.len());
+            for elem in elems {
+                write_repr(bytes, elem);
+            }
+        }
+        repr => {
+            bytes.push(type_tag(&repr.value_type()));
+            for node in repr.iter() {
+                write_uint(bytes, node.id);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEof);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_uint(&mut self) -> Result<usize, DecodeError> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            let bits = (byte & 0x7f) as usize;
+
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                return Err(DecodeError::Overflow);
+            }
+
+            value |= bits << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn read_node(&mut self, feed_count: usize) -> Result<Node<Feed>, DecodeError> {
+        let id = self.read_uint()?;
+        if id >= feed_count {
+            return Err(DecodeError::InvalidNode { id, feed_count });
+        }
+
+        Ok(Node::new(id))
+    }
+
+    fn read_repr(&mut self, feed_count: usize) -> Result<BinaryRepr, DecodeError> {
+        let tag = self.read_u8()?;
+        if tag == TAG_ARRAY {
+            let len = self.read_uint()?;
+            let elems = (0..len)
+                .map(|_| self.read_repr(feed_count))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(BinaryRepr::Array(elems));
+        }
+
+        let ty = tag_type(tag)?;
+        let nodes = (0..ty.len())
+            .map(|_| self.read_node(feed_count))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ty
+            .to_bin_repr(&nodes)
+            .expect("node count should match the type"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ops::WrappingAdd, CircuitBuilder};
+
+    fn build_adder() -> Circuit {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_array_input::<u8, 4>();
+        let b = builder.add_input::<i32>();
+
+        let c = a.map(|a| a.wrapping_add(1u8));
+
+        builder.add_output(c);
+        builder.add_output(b.wrapping_add(b));
+
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let circ = build_adder();
+
+        let bytes = circ.to_bytes();
+        let decoded = Circuit::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.hash(), circ.hash());
+        assert_eq!(decoded.and_count(), circ.and_count());
+        assert_eq!(decoded.xor_count(), circ.xor_count());
+
+        let inputs = [[1u8, 2, 3, 255].into(), (-5i32).into()];
+        assert_eq!(
+            decoded.evaluate(&inputs).unwrap(),
+            circ.evaluate(&inputs).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "aes")]
+    fn test_roundtrip_aes() {
+        use crate::circuits::AES128;
+
+        let bytes = AES128.to_bytes();
+        let decoded = Circuit::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.hash(), AES128.hash());
+        assert_eq!(decoded.and_count(), AES128.and_count());
+    }
+
+    #[test]
+    fn test_hash_differs() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        builder.add_output(a ^ b);
+        let xor = builder.build().unwrap();
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        builder.add_output(a & b);
+        let and = builder.build().unwrap();
+
+        assert_ne!(xor.hash(), and.hash());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = build_adder().to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 1;
+        assert!(matches!(
+            Circuit::from_bytes(&bad_magic),
+            Err(DecodeError::InvalidMagic)
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 0xff;
+        assert!(matches!(
+            Circuit::from_bytes(&bad_version),
+            Err(DecodeError::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            Circuit::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEof)
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Circuit::from_bytes(&trailing),
+            Err(DecodeError::TrailingBytes(1))
+        ));
+    }
+}
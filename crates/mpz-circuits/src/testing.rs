@@ -0,0 +1,207 @@
+//! Utilities for testing circuits against reference implementations.
+//!
+//! Circuits are evaluated in batches of [`Circuit::BATCH_LANES`] input vectors at once, using
+//! [`Circuit::evaluate_batch`].
+//!
+//! # Example
+//!
+//! ```
+//! use mpz_circuits::{ops::WrappingAdd, testing::check_exhaustive, types::Value, CircuitBuilder};
+//!
+//! let builder = CircuitBuilder::new();
+//! let a = builder.add_input::<u8>();
+//! let b = builder.add_input::<u8>();
+//! builder.add_output(a.wrapping_add(b));
+//! let circ = builder.build().unwrap();
+//!
+//! check_exhaustive(&circ, |inputs| {
+//!     let a: u8 = inputs[0].clone().try_into().unwrap();
+//!     let b: u8 = inputs[1].clone().try_into().unwrap();
+//!     vec![Value::from(a.wrapping_add(b))]
+//! })
+//! .unwrap();
+//! ```
+
+use rand::Rng;
+
+use crate::{types::Value, Circuit, CircuitError};
+
+/// Maximum number of input bits for which a circuit can be checked exhaustively.
+pub const MAX_EXHAUSTIVE_BITS: usize = 24;
+
+/// An error that can occur when checking a circuit.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum EquivalenceError {
+    #[error(
+        "circuit has {0} input bits, at most {MAX_EXHAUSTIVE_BITS} can be checked exhaustively"
+    )]
+    TooManyInputBits(usize),
+    #[error(transparent)]
+    CircuitError(#[from] CircuitError),
+    #[error("output mismatch for inputs {inputs:?}: expected {expected:?}, got {actual:?}")]
+    Mismatch {
+        inputs: Vec<Value>,
+        expected: Vec<Value>,
+        actual: Vec<Value>,
+    },
+}
+
+/// Checks a circuit against a reference function for every possible input.
+///
+/// # Arguments
+///
+/// * `circ` - The circuit to check.
+/// * `f` - The reference function, which is called with the inputs of the circuit and
+///   returns the expected outputs.
+pub fn check_exhaustive<F>(circ: &Circuit, mut f: F) -> Result<(), EquivalenceError>
+where
+    F: FnMut(&[Value]) -> Vec<Value>,
+{
+    let input_bits: usize = circ.inputs().iter().map(|input| input.len()).sum();
+    if input_bits > MAX_EXHAUSTIVE_BITS {
+        return Err(EquivalenceError::TooManyInputBits(input_bits));
+    }
+
+    let count = 1u64 << input_bits;
+    let mut start = 0;
+    while start < count {
+        let end = (start + Circuit::BATCH_LANES as u64).min(count);
+        let batch = (start..end)
+            .map(|counter| {
+                let bits: Vec<bool> = (0..input_bits).map(|i| (counter >> i) & 1 == 1).collect();
+                let mut bits = bits.as_slice();
+                circ.inputs()
+                    .iter()
+                    .map(|input| {
+                        let (value, rest) = bits.split_at(input.len());
+                        bits = rest;
+                        input
+                            .from_bin_repr(value)
+                            .expect("bit length should match input")
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        check_batch(circ, batch, &mut f)?;
+        start = end;
+    }
+
+    Ok(())
+}
+
+/// Checks a circuit against a reference function for randomly sampled inputs.
+///
+/// # Arguments
+///
+/// * `circ` - The circuit to check.
+/// * `rng` - The rng used to sample inputs.
+/// * `count` - The number of input vectors to check.
+/// * `f` - The reference function, which is called with the inputs of the circuit and
+///   returns the expected outputs.
+pub fn check_random<R, F>(
+    circ: &Circuit,
+    rng: &mut R,
+    count: usize,
+    mut f: F,
+) -> Result<(), EquivalenceError>
+where
+    R: Rng,
+    F: FnMut(&[Value]) -> Vec<Value>,
+{
+    let mut remaining = count;
+    while remaining > 0 {
+        let batch_len = remaining.min(Circuit::BATCH_LANES);
+        let batch = (0..batch_len)
+            .map(|_| {
+                circ.inputs()
+                    .iter()
+                    .map(|input| Value::random(rng, &input.value_type()))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        check_batch(circ, batch, &mut f)?;
+        remaining -= batch_len;
+    }
+
+    Ok(())
+}
+
+fn check_batch<F>(circ: &Circuit, batch: Vec<Vec<Value>>, f: &mut F) -> Result<(), EquivalenceError>
+where
+    F: FnMut(&[Value]) -> Vec<Value>,
+{
+    let outputs = circ.evaluate_batch(&batch)?;
+
+    for (inputs, actual) in batch.into_iter().zip(outputs) {
+        let expected = f(&inputs);
+        if expected != actual {
+            return Err(EquivalenceError::Mismatch {
+                inputs,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{ops::WrappingAdd, CircuitBuilder};
+
+    fn build_adder() -> Circuit {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        builder.add_output(a.wrapping_add(b));
+        builder.build().unwrap()
+    }
+
+    fn add(inputs: &[Value]) -> Vec<Value> {
+        let a: u8 = inputs[0].clone().try_into().unwrap();
+        let b: u8 = inputs[1].clone().try_into().unwrap();
+        vec![a.wrapping_add(b).into()]
+    }
+
+    #[test]
+    fn test_check_exhaustive() {
+        check_exhaustive(&build_adder(), add).unwrap();
+    }
+
+    #[test]
+    fn test_check_random() {
+        let mut rng = StdRng::seed_from_u64(0);
+        check_random(&build_adder(), &mut rng, 1000, add).unwrap();
+    }
+
+    #[test]
+    fn test_check_detects_mismatch() {
+        let err = check_exhaustive(&build_adder(), |inputs| {
+            let a: u8 = inputs[0].clone().try_into().unwrap();
+            let b: u8 = inputs[1].clone().try_into().unwrap();
+            vec![a.saturating_add(b).into()]
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EquivalenceError::Mismatch { .. }));
+    }
+
+    #[test]
+    fn test_check_exhaustive_too_many_bits() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        builder.add_output(a);
+        let circ = builder.build().unwrap();
+
+        let err = check_exhaustive(&circ, |inputs| inputs.to_vec()).unwrap_err();
+
+        assert!(matches!(err, EquivalenceError::TooManyInputBits(32)));
+    }
+}
@@ -65,6 +65,11 @@ pub enum BinaryRepr {
     U64(U64),
     U128(U128),
     Array(Vec<BinaryRepr>),
+    I8(I8),
+    I16(I16),
+    I32(I32),
+    I64(I64),
+    I128(I128),
 }
 
 impl BinaryRepr {
@@ -78,6 +83,11 @@ impl BinaryRepr {
             BinaryRepr::U64(_) => ValueType::U64,
             BinaryRepr::U128(_) => ValueType::U128,
             BinaryRepr::Array(v) => ValueType::Array(Box::new(v[0].value_type()), v.len()),
+            BinaryRepr::I8(_) => ValueType::I8,
+            BinaryRepr::I16(_) => ValueType::I16,
+            BinaryRepr::I32(_) => ValueType::I32,
+            BinaryRepr::I64(_) => ValueType::I64,
+            BinaryRepr::I128(_) => ValueType::I128,
         }
     }
 
@@ -92,6 +102,11 @@ impl BinaryRepr {
             BinaryRepr::U64(U64 { .. }) => 64,
             BinaryRepr::U128(U128 { .. }) => 128,
             BinaryRepr::Array(v) => v.iter().map(|v| v.len()).sum(),
+            BinaryRepr::I8(I8 { .. }) => 8,
+            BinaryRepr::I16(I16 { .. }) => 16,
+            BinaryRepr::I32(I32 { .. }) => 32,
+            BinaryRepr::I64(I64 { .. }) => 64,
+            BinaryRepr::I128(I128 { .. }) => 128,
         }
     }
 
@@ -105,6 +120,11 @@ impl BinaryRepr {
             BinaryRepr::U64(v) => Box::new(v.0.iter()),
             BinaryRepr::U128(v) => Box::new(v.0.iter()),
             BinaryRepr::Array(v) => Box::new(v.iter().flat_map(|v| v.iter())),
+            BinaryRepr::I8(v) => Box::new(v.0.iter()),
+            BinaryRepr::I16(v) => Box::new(v.0.iter()),
+            BinaryRepr::I32(v) => Box::new(v.0.iter()),
+            BinaryRepr::I64(v) => Box::new(v.0.iter()),
+            BinaryRepr::I128(v) => Box::new(v.0.iter()),
         }
     }
 
@@ -118,6 +138,11 @@ impl BinaryRepr {
             BinaryRepr::U64(v) => Box::new(v.0.iter_mut()),
             BinaryRepr::U128(v) => Box::new(v.0.iter_mut()),
             BinaryRepr::Array(v) => Box::new(v.iter_mut().flat_map(|v| v.iter_mut())),
+            BinaryRepr::I8(v) => Box::new(v.0.iter_mut()),
+            BinaryRepr::I16(v) => Box::new(v.0.iter_mut()),
+            BinaryRepr::I32(v) => Box::new(v.0.iter_mut()),
+            BinaryRepr::I64(v) => Box::new(v.0.iter_mut()),
+            BinaryRepr::I128(v) => Box::new(v.0.iter_mut()),
         }
     }
 
@@ -131,6 +156,11 @@ impl BinaryRepr {
             BinaryRepr::U64(v) => v.shift_left(offset),
             BinaryRepr::U128(v) => v.shift_left(offset),
             BinaryRepr::Array(v) => v.iter_mut().for_each(|v| v.shift_left(offset)),
+            BinaryRepr::I8(v) => v.shift_left(offset),
+            BinaryRepr::I16(v) => v.shift_left(offset),
+            BinaryRepr::I32(v) => v.shift_left(offset),
+            BinaryRepr::I64(v) => v.shift_left(offset),
+            BinaryRepr::I128(v) => v.shift_left(offset),
         }
     }
 
@@ -163,6 +193,13 @@ impl BinaryRepr {
                     .map(|(v, bits)| v.from_bin_repr(bits).unwrap())
                     .collect(),
             )),
+            BinaryRepr::I8(_) => Ok(Value::I8(u8::from_lsb0_iter(bits.iter().copied()) as i8)),
+            BinaryRepr::I16(_) => Ok(Value::I16(u16::from_lsb0_iter(bits.iter().copied()) as i16)),
+            BinaryRepr::I32(_) => Ok(Value::I32(u32::from_lsb0_iter(bits.iter().copied()) as i32)),
+            BinaryRepr::I64(_) => Ok(Value::I64(u64::from_lsb0_iter(bits.iter().copied()) as i64)),
+            BinaryRepr::I128(_) => Ok(Value::I128(
+                u128::from_lsb0_iter(bits.iter().copied()) as i128
+            )),
         }
     }
 }
@@ -177,6 +214,11 @@ impl Display for BinaryRepr {
             BinaryRepr::U64(v) => write!(f, "U64({:?})", v.0),
             BinaryRepr::U128(v) => write!(f, "U128({:?})", v.0),
             BinaryRepr::Array(v) => write!(f, "Array({:?})", v),
+            BinaryRepr::I8(v) => write!(f, "I8({:?})", v.0),
+            BinaryRepr::I16(v) => write!(f, "I16({:?})", v.0),
+            BinaryRepr::I32(v) => write!(f, "I32({:?})", v.0),
+            BinaryRepr::I64(v) => write!(f, "I64({:?})", v.0),
+            BinaryRepr::I128(v) => write!(f, "I128({:?})", v.0),
         }
     }
 }
@@ -426,6 +468,11 @@ define_binary_value!(u16, U16, 16);
 define_binary_value!(u32, U32, 32);
 define_binary_value!(u64, U64, 64);
 define_binary_value!(u128, U128, 128);
+define_binary_value!(i8, I8, 8);
+define_binary_value!(i16, I16, 16);
+define_binary_value!(i32, I32, 32);
+define_binary_value!(i64, I64, 64);
+define_binary_value!(i128, I128, 128);
 
 /// A value type that can be encoded into a binary representation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -439,6 +486,11 @@ pub enum ValueType {
     U64,
     U128,
     Array(Box<ValueType>, usize),
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
 }
 
 impl ValueType {
@@ -463,6 +515,11 @@ impl ValueType {
             ValueType::U64 => 64,
             ValueType::U128 => 128,
             ValueType::Array(ty, len) => ty.len() * len,
+            ValueType::I8 => 8,
+            ValueType::I16 => 16,
+            ValueType::I32 => 32,
+            ValueType::I64 => 64,
+            ValueType::I128 => 128,
         }
     }
 
@@ -471,6 +528,26 @@ impl ValueType {
         matches!(self, ValueType::Array(..))
     }
 
+    /// Returns whether the value type is a signed integer.
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            ValueType::I8 | ValueType::I16 | ValueType::I32 | ValueType::I64 | ValueType::I128
+        )
+    }
+
+    /// Decodes a value of this type from its bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The bits of the value, in LSB0 order.
+    pub fn from_bin_repr(&self, bits: &[bool]) -> Result<Value, TypeError> {
+        // Only the layout of the binary representation is used, so the nodes are placeholders.
+        let nodes: Vec<_> = (0..self.len()).map(Node::new).collect();
+
+        self.to_bin_repr(&nodes)?.from_bin_repr(bits)
+    }
+
     pub(crate) fn to_bin_repr(&self, nodes: &[Node<Feed>]) -> Result<BinaryRepr, TypeError> {
         if nodes.len() != self.len() {
             return Err(TypeError::InvalidLength {
@@ -491,6 +568,11 @@ impl ValueType {
                     .map(|nodes| ty.to_bin_repr(nodes).unwrap())
                     .collect(),
             ),
+            ValueType::I8 => BinaryRepr::I8(I8::new(nodes.try_into().unwrap())),
+            ValueType::I16 => BinaryRepr::I16(I16::new(nodes.try_into().unwrap())),
+            ValueType::I32 => BinaryRepr::I32(I32::new(nodes.try_into().unwrap())),
+            ValueType::I64 => BinaryRepr::I64(I64::new(nodes.try_into().unwrap())),
+            ValueType::I128 => BinaryRepr::I128(I128::new(nodes.try_into().unwrap())),
         };
 
         Ok(encoded)
@@ -507,6 +589,11 @@ impl Display for ValueType {
             ValueType::U64 => write!(f, "U64"),
             ValueType::U128 => write!(f, "U128"),
             ValueType::Array(ty, len) => write!(f, "Array<{}, {}>", ty, len),
+            ValueType::I8 => write!(f, "I8"),
+            ValueType::I16 => write!(f, "I16"),
+            ValueType::I32 => write!(f, "I32"),
+            ValueType::I64 => write!(f, "I64"),
+            ValueType::I128 => write!(f, "I128"),
         }
     }
 }
@@ -533,6 +620,11 @@ impl_value_type!(u16, U16);
 impl_value_type!(u32, U32);
 impl_value_type!(u64, U64);
 impl_value_type!(u128, U128);
+impl_value_type!(i8, I8);
+impl_value_type!(i16, I16);
+impl_value_type!(i32, I32);
+impl_value_type!(i64, I64);
+impl_value_type!(i128, I128);
 
 /// A value that can be encoded into a binary representation.
 #[derive(Debug, Clone, PartialEq)]
@@ -546,6 +638,11 @@ pub enum Value {
     U64(u64),
     U128(u128),
     Array(Vec<Value>),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
 }
 
 impl Value {
@@ -563,6 +660,11 @@ impl Value {
                     .map(|_| Value::random(rng, ty))
                     .collect::<Vec<_>>(),
             ),
+            ValueType::I8 => Value::I8(rng.gen()),
+            ValueType::I16 => Value::I16(rng.gen()),
+            ValueType::I32 => Value::I32(rng.gen()),
+            ValueType::I64 => Value::I64(rng.gen()),
+            ValueType::I128 => Value::I128(rng.gen()),
         }
     }
 
@@ -576,6 +678,11 @@ impl Value {
             Value::U64(_) => ValueType::U64,
             Value::U128(_) => ValueType::U128,
             Value::Array(v) => ValueType::Array(Box::new(v[0].value_type()), v.len()),
+            Value::I8(_) => ValueType::I8,
+            Value::I16(_) => ValueType::I16,
+            Value::I32(_) => ValueType::I32,
+            Value::I64(_) => ValueType::I64,
+            Value::I128(_) => ValueType::I128,
         }
     }
 }
@@ -593,6 +700,11 @@ impl IntoBits for Value {
             Value::U64(v) => v.into_lsb0_vec(),
             Value::U128(v) => v.into_lsb0_vec(),
             Value::Array(v) => v.into_iter().flat_map(|v| v.into_iter_lsb0()).collect(),
+            Value::I8(v) => (v as u8).into_lsb0_vec(),
+            Value::I16(v) => (v as u16).into_lsb0_vec(),
+            Value::I32(v) => (v as u32).into_lsb0_vec(),
+            Value::I64(v) => (v as u64).into_lsb0_vec(),
+            Value::I128(v) => (v as u128).into_lsb0_vec(),
         }
         .into_iter()
     }
@@ -606,6 +718,11 @@ impl IntoBits for Value {
             Value::U64(v) => v.into_msb0_vec(),
             Value::U128(v) => v.into_msb0_vec(),
             Value::Array(v) => v.into_iter().flat_map(|v| v.into_iter_msb0()).collect(),
+            Value::I8(v) => (v as u8).into_msb0_vec(),
+            Value::I16(v) => (v as u16).into_msb0_vec(),
+            Value::I32(v) => (v as u32).into_msb0_vec(),
+            Value::I64(v) => (v as u64).into_msb0_vec(),
+            Value::I128(v) => (v as u128).into_msb0_vec(),
         }
         .into_iter()
     }
@@ -621,6 +738,11 @@ impl Display for Value {
             Value::U64(v) => write!(f, "U64({})", v),
             Value::U128(v) => write!(f, "U128({})", v),
             Value::Array(v) => write!(f, "Array({:?})", v),
+            Value::I8(v) => write!(f, "I8({})", v),
+            Value::I16(v) => write!(f, "I16({})", v),
+            Value::I32(v) => write!(f, "I32({})", v),
+            Value::I64(v) => write!(f, "I64({})", v),
+            Value::I128(v) => write!(f, "I128({})", v),
         }
     }
 }
@@ -636,6 +758,11 @@ impl BitXor for Value {
             (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
             (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
             (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
+            (Value::I8(a), Value::I8(b)) => Value::I8(a ^ b),
+            (Value::I16(a), Value::I16(b)) => Value::I16(a ^ b),
+            (Value::I32(a), Value::I32(b)) => Value::I32(a ^ b),
+            (Value::I64(a), Value::I64(b)) => Value::I64(a ^ b),
+            (Value::I128(a), Value::I128(b)) => Value::I128(a ^ b),
             (Value::Array(a), Value::Array(b)) => Value::Array(
                 a.iter()
                     .zip(b.iter())
@@ -663,6 +790,11 @@ impl BitXor<&Value> for &Value {
             (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
             (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
             (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
+            (Value::I8(a), Value::I8(b)) => Value::I8(a ^ b),
+            (Value::I16(a), Value::I16(b)) => Value::I16(a ^ b),
+            (Value::I32(a), Value::I32(b)) => Value::I32(a ^ b),
+            (Value::I64(a), Value::I64(b)) => Value::I64(a ^ b),
+            (Value::I128(a), Value::I128(b)) => Value::I128(a ^ b),
             (Value::Array(a), Value::Array(b)) => Value::Array(
                 a.iter()
                     .zip(b.iter())
@@ -690,6 +822,11 @@ impl BitXor<&Value> for Value {
             (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
             (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
             (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
+            (Value::I8(a), Value::I8(b)) => Value::I8(a ^ b),
+            (Value::I16(a), Value::I16(b)) => Value::I16(a ^ b),
+            (Value::I32(a), Value::I32(b)) => Value::I32(a ^ b),
+            (Value::I64(a), Value::I64(b)) => Value::I64(a ^ b),
+            (Value::I128(a), Value::I128(b)) => Value::I128(a ^ b),
             (Value::Array(a), Value::Array(b)) => Value::Array(
                 a.iter()
                     .zip(b.iter())
@@ -717,6 +854,11 @@ impl BitXor<Value> for &Value {
             (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
             (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
             (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
+            (Value::I8(a), Value::I8(b)) => Value::I8(a ^ b),
+            (Value::I16(a), Value::I16(b)) => Value::I16(a ^ b),
+            (Value::I32(a), Value::I32(b)) => Value::I32(a ^ b),
+            (Value::I64(a), Value::I64(b)) => Value::I64(a ^ b),
+            (Value::I128(a), Value::I128(b)) => Value::I128(a ^ b),
             (Value::Array(a), Value::Array(b)) => Value::Array(
                 a.iter()
                     .zip(b.iter())
@@ -764,11 +906,18 @@ impl_convert_bytes!(U16, 2);
 impl_convert_bytes!(U32, 4);
 impl_convert_bytes!(U64, 8);
 impl_convert_bytes!(U128, 16);
+impl_convert_bytes!(I8, 1);
+impl_convert_bytes!(I16, 2);
+impl_convert_bytes!(I32, 4);
+impl_convert_bytes!(I64, 8);
+impl_convert_bytes!(I128, 16);
 
 #[cfg(test)]
 mod tests {
+    use itybity::IntoBits;
     use mpz_circuits_macros::{test_circ, trace};
 
+    use super::*;
     use crate::CircuitBuilder;
 
     #[trace]
@@ -799,4 +948,18 @@ mod tests {
 
         test_circ!(circ, to_le_bytes, fn(69u128) -> [u8; 16]);
     }
+
+    #[test]
+    fn test_value_type_from_bin_repr() {
+        for value in [
+            Value::I8(-42),
+            Value::I128(i128::MIN),
+            Value::U32(0xdeadbeef),
+            Value::from([-1i16, 2, -3]),
+        ] {
+            let bits = value.clone().into_lsb0_vec();
+
+            assert_eq!(value.value_type().from_bin_repr(&bits).unwrap(), value);
+        }
+    }
 }
@@ -41,6 +41,9 @@ mod sender;
 pub use receiver::{receiver_state, Receiver};
 pub use sender::{sender_state, Sender};
 
+/// Domain of the sender's seed commitment.
+pub(crate) const COMMITMENT_DOMAIN: &str = "mpz-cointoss 2023 seed commitment";
+
 /// A coin-toss error.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
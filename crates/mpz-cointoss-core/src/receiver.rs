@@ -2,7 +2,7 @@ use mpz_core::{hash::Hash, Block};
 
 use crate::{
     msgs::{ReceiverPayload, SenderCommitment, SenderPayload},
-    CointossError,
+    CointossError, COMMITMENT_DOMAIN,
 };
 
 /// A coin-toss receiver.
@@ -52,7 +52,7 @@ impl Receiver<receiver_state::Received> {
             });
         }
 
-        decommitment.verify(&commitment)?;
+        decommitment.verify_with_domain(&commitment, COMMITMENT_DOMAIN)?;
 
         Ok(decommitment
             .into_inner()
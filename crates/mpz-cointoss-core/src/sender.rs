@@ -2,7 +2,7 @@ use mpz_core::{commit::HashCommit, Block};
 
 use crate::{
     msgs::{ReceiverPayload, SenderCommitment, SenderPayload},
-    CointossError,
+    CointossError, COMMITMENT_DOMAIN,
 };
 
 /// A coin-toss sender.
@@ -23,7 +23,7 @@ impl Sender {
     pub fn send(self) -> (Sender<sender_state::Committed>, SenderCommitment) {
         let sender_state::Initialized { seeds } = self.state;
 
-        let (decommitment, commitment) = seeds.clone().hash_commit();
+        let (decommitment, commitment) = seeds.clone().hash_commit_with_domain(COMMITMENT_DOMAIN);
 
         (
             Sender {
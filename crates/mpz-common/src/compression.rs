@@ -0,0 +1,269 @@
+//! Message compression.
+//!
+//! [`CompressedIo`] compresses the messages sent over an I/O channel. Garbled circuits and
+//! MPCOT payloads compress modestly, which is worthwhile over WAN links.
+//!
+//! The algorithm is negotiated at setup with [`negotiate`], which picks the most preferred
+//! algorithm supported by both parties. Algorithms are enabled with the `lz4` and `zstd`
+//! features.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context as StdContext, Poll},
+};
+
+use serio::{stream::IoStreamExt, Deserialize, IoDuplex, Serialize, Sink, SinkExt, Stream};
+
+/// Messages shorter than this are sent uncompressed.
+pub const MIN_COMPRESS_LEN: usize = 64;
+
+/// The zstd compression level.
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// A compression algorithm.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum Compression {
+    /// No compression.
+    None,
+    /// LZ4 compression, which is fast but compresses less.
+    Lz4,
+    /// Zstandard compression.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the algorithms which are enabled, in order of preference.
+    pub fn supported() -> Vec<Self> {
+        let mut supported = Vec::new();
+        if cfg!(feature = "zstd") {
+            supported.push(Compression::Zstd);
+        }
+        if cfg!(feature = "lz4") {
+            supported.push(Compression::Lz4);
+        }
+        supported.push(Compression::None);
+        supported
+    }
+
+    fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL),
+            #[allow(unreachable_patterns)]
+            _ => Err(unsupported(*self)),
+        }
+    }
+
+    fn decompress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(data)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::decode_all(data),
+            #[allow(unreachable_patterns)]
+            _ => Err(unsupported(*self)),
+        }
+    }
+}
+
+fn unsupported(compression: Compression) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("compression algorithm is not enabled: {compression:?}"),
+    )
+}
+
+/// Negotiates a compression algorithm with the peer.
+///
+/// Both parties send the algorithms they support, and pick the first of the
+/// [supported](Compression::supported) algorithms which is also supported by the peer. The
+/// order of preference is fixed, so both parties pick the same algorithm.
+///
+/// # Arguments
+///
+/// * `io` - The I/O channel.
+/// * `supported` - The algorithms this party supports.
+pub async fn negotiate<Io: IoDuplex + Unpin>(
+    io: &mut Io,
+    supported: &[Compression],
+) -> io::Result<Compression> {
+    io.send(supported.to_vec()).await?;
+    let peer_supported: Vec<Compression> = io.expect_next().await?;
+
+    Ok(Compression::supported()
+        .into_iter()
+        .find(|compression| supported.contains(compression) && peer_supported.contains(compression))
+        .unwrap_or(Compression::None))
+}
+
+/// A compressed frame.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Frame {
+    compressed: bool,
+    payload: Vec<u8>,
+}
+
+pin_project_lite::pin_project! {
+    /// An I/O channel which compresses messages.
+    #[derive(Debug)]
+    pub struct CompressedIo<Io> {
+        #[pin]
+        io: Io,
+        compression: Compression,
+    }
+}
+
+impl<Io> CompressedIo<Io> {
+    /// Creates a new compressed I/O channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The I/O channel.
+    /// * `compression` - The negotiated compression algorithm.
+    pub fn new(io: Io, compression: Compression) -> Self {
+        Self { io, compression }
+    }
+
+    /// Returns the compression algorithm.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Returns the inner I/O channel.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+}
+
+impl<Io: Sink<Error = io::Error>> Sink for CompressedIo<Io> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_ready(cx)
+    }
+
+    fn start_send<Item: Serialize>(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        let this = self.project();
+
+        let data =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let frame = if *this.compression == Compression::None || data.len() < MIN_COMPRESS_LEN {
+            Frame {
+                compressed: false,
+                payload: data,
+            }
+        } else {
+            Frame {
+                compressed: true,
+                payload: this.compression.compress(&data)?,
+            }
+        };
+
+        this.io.start_send(frame)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_close(cx)
+    }
+}
+
+impl<Io: Stream<Error = io::Error>> Stream for CompressedIo<Io> {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = self.project();
+
+        let Some(frame) = ready!(this.io.poll_next::<Frame>(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        let item = frame.and_then(|frame| {
+            let data = if frame.compressed {
+                this.compression.decompress(&frame.payload)?
+            } else {
+                frame.payload
+            };
+
+            bincode::deserialize(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+
+        Poll::Ready(Some(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serio::channel::duplex;
+
+    use super::*;
+
+    fn roundtrip(compression: Compression) {
+        let (io_0, io_1) = duplex(8);
+        let mut io_0 = CompressedIo::new(io_0, compression);
+        let mut io_1 = CompressedIo::new(io_1, compression);
+
+        let small = vec![1u8; 8];
+        let large = vec![42u8; 4096];
+
+        block_on(async {
+            io_0.send(small.clone()).await.unwrap();
+            io_0.send(large.clone()).await.unwrap();
+
+            assert_eq!(io_1.expect_next::<Vec<u8>>().await.unwrap(), small);
+            assert_eq!(io_1.expect_next::<Vec<u8>>().await.unwrap(), large);
+        });
+    }
+
+    #[test]
+    fn test_compressed_io() {
+        for compression in Compression::supported() {
+            roundtrip(compression);
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let (mut io_0, mut io_1) = duplex(8);
+        let supported = Compression::supported();
+
+        let (compression_0, compression_1) = block_on(async {
+            futures::try_join!(
+                negotiate(&mut io_0, &supported),
+                negotiate(&mut io_1, &[Compression::None])
+            )
+            .unwrap()
+        });
+
+        assert_eq!(compression_0, Compression::None);
+        assert_eq!(compression_1, Compression::None);
+
+        let (compression_0, compression_1) = block_on(async {
+            futures::try_join!(
+                negotiate(&mut io_0, &supported),
+                negotiate(&mut io_1, &supported)
+            )
+            .unwrap()
+        });
+
+        assert_eq!(compression_0, compression_1);
+        assert_eq!(compression_0, supported[0]);
+    }
+}
@@ -32,6 +32,7 @@ impl ContextError {
 pub(crate) enum ErrorKind {
     Mux,
     Thread,
+    Channel,
 }
 
 impl fmt::Display for ErrorKind {
@@ -39,6 +40,7 @@ impl fmt::Display for ErrorKind {
         match self {
             ErrorKind::Mux => write!(f, "multiplexer error"),
             ErrorKind::Thread => write!(f, "thread error"),
+            ErrorKind::Channel => write!(f, "channel error"),
         }
     }
 }
@@ -58,6 +60,36 @@ pub trait Context: Send + Sync {
     /// Returns a mutable reference to the thread's I/O channel.
     fn io_mut(&mut self) -> &mut Self::Io;
 
+    /// Opens an independent logical channel with the provided label.
+    ///
+    /// The channel is identified by the thread ID and the label, so both parties must open it
+    /// from the same thread with the same label. Messages on the channel never interleave with
+    /// messages on the thread's I/O channel, or on other channels, so subprotocols can use
+    /// their own channels concurrently.
+    ///
+    /// Each label can only be opened once per thread. Implementations which can not multiplex
+    /// return an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the channel, eg. the name of the subprotocol.
+    async fn open_channel(&mut self, label: &str) -> Result<Self::Io, ContextError>;
+
+    /// Forks the thread into `n` child contexts.
+    ///
+    /// Child contexts are derived deterministically, so both parties obtain matching children
+    /// as long as they fork in the same order. Each child has its own I/O channel and can be
+    /// moved to another task, eg. to run data-parallel work.
+    ///
+    /// Implementations which can not fork return an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of child contexts.
+    async fn fork(&mut self, n: usize) -> Result<Vec<Self>, ContextError>
+    where
+        Self: Sized;
+
     /// Executes a task that may block the thread.
     ///
     /// If CPU multi-threading is available, the task is executed on a separate thread. Otherwise,
@@ -1,18 +1,122 @@
 //! CPU backend shim.
+//!
+//! Compute-heavy work, eg. OT extension and garbling, is offloaded to the [`CpuBackend`]. With the
+//! `rayon` feature, the work runs on the global rayon pool by default. Applications which share
+//! the host with other work can instead configure a dedicated pool with [`configure_pool`], so
+//! that MPC work does not starve the application's other threads. Parallel iterators executed by
+//! the offloaded work run on the same pool.
+
+use std::{fmt, sync::Arc};
 
 use cfg_if::cfg_if;
 
 cfg_if! {
     if #[cfg(feature = "force-st")] {
-        pub use st::SingleThreadedBackend as CpuBackend;
-    } else if #[cfg(feature = "rayon")] {
-        pub use rayon_backend::RayonBackend as CpuBackend;
+        pub use st::{install, SingleThreadedBackend as CpuBackend};
+    } else if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
+        pub use rayon_backend::{install, set_pool, RayonBackend as CpuBackend};
     } else {
-        pub use st::SingleThreadedBackend as CpuBackend;
+        pub use st::{install, SingleThreadedBackend as CpuBackend};
+    }
+}
+
+/// Error for [`configure_pool`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum CpuPoolError {
+    #[error("CPU worker pool is already configured")]
+    AlreadyConfigured,
+    #[error("failed to build CPU worker pool: {0}")]
+    Build(String),
+}
+
+type StartHandler = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// CPU worker pool configuration.
+#[derive(Clone, Default)]
+pub struct CpuPoolConfig {
+    num_threads: Option<usize>,
+    thread_name: Option<String>,
+    start_handler: Option<StartHandler>,
+}
+
+impl fmt::Debug for CpuPoolConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CpuPoolConfig")
+            .field("num_threads", &self.num_threads)
+            .field("thread_name", &self.thread_name)
+            .field("start_handler", &self.start_handler.is_some())
+            .finish()
+    }
+}
+
+impl CpuPoolConfig {
+    /// Creates a new configuration with the defaults of rayon.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of worker threads.
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Sets the name prefix of the worker threads, the index of the thread is appended to it.
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = Some(name.into());
+        self
+    }
+
+    /// Sets a handler which is called with the index of each worker thread when it starts.
+    ///
+    /// This can be used to pin the worker threads to cores, or to adjust their priority.
+    pub fn start_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.start_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Returns the number of worker threads, if set.
+    pub fn get_num_threads(&self) -> Option<usize> {
+        self.num_threads
+    }
+
+    /// Returns the name prefix of the worker threads, if set.
+    pub fn get_thread_name(&self) -> Option<&str> {
+        self.thread_name.as_deref()
     }
 }
 
-#[cfg(any(feature = "force-st", not(feature = "rayon")))]
+/// Configures the CPU worker pool used by the [`CpuBackend`].
+///
+/// The pool can only be configured once, and must be configured before any work is offloaded for
+/// it to apply to all of the work. Without the `rayon` feature the work runs on the calling
+/// thread, and the configuration is ignored.
+///
+/// # Arguments
+///
+/// * `config` - The pool configuration.
+pub fn configure_pool(config: CpuPoolConfig) -> Result<(), CpuPoolError> {
+    #[cfg(all(
+        feature = "rayon",
+        not(feature = "force-st"),
+        not(target_arch = "wasm32")
+    ))]
+    {
+        rayon_backend::configure_pool(config)
+    }
+
+    #[cfg(any(feature = "force-st", not(feature = "rayon"), target_arch = "wasm32"))]
+    {
+        _ = config;
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "force-st", not(feature = "rayon"), target_arch = "wasm32"))]
 mod st {
     use futures::Future;
 
@@ -42,6 +146,16 @@ mod st {
         }
     }
 
+    /// Executes a closure on the CPU worker pool.
+    #[inline]
+    pub fn install<F, R>(f: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        f()
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -61,10 +175,88 @@ mod st {
     }
 }
 
-#[cfg(all(feature = "rayon", not(feature = "force-st")))]
+#[cfg(all(
+    feature = "rayon",
+    not(feature = "force-st"),
+    not(target_arch = "wasm32")
+))]
 mod rayon_backend {
+    use std::sync::OnceLock;
+
     use futures::{channel::oneshot, Future};
     use pollster::block_on;
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+
+    use super::{CpuPoolConfig, CpuPoolError};
+
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+    pub(super) fn configure_pool(config: CpuPoolConfig) -> Result<(), CpuPoolError> {
+        // Avoid spawning the threads of a pool which would be discarded.
+        if POOL.get().is_some() {
+            return Err(CpuPoolError::AlreadyConfigured);
+        }
+
+        let CpuPoolConfig {
+            num_threads,
+            thread_name,
+            start_handler,
+        } = config;
+
+        let mut builder = ThreadPoolBuilder::new();
+        if let Some(num_threads) = num_threads {
+            builder = builder.num_threads(num_threads);
+        }
+        if let Some(name) = thread_name {
+            builder = builder.thread_name(move |idx| format!("{name}-{idx}"));
+        }
+        if let Some(handler) = start_handler {
+            builder = builder.start_handler(move |idx| handler(idx));
+        }
+
+        let pool = builder
+            .build()
+            .map_err(|err| CpuPoolError::Build(err.to_string()))?;
+
+        set_pool(pool)
+    }
+
+    /// Sets the CPU worker pool used by the [`CpuBackend`](super::CpuBackend).
+    ///
+    /// This is an alternative to [`configure_pool`](super::configure_pool) for applications which
+    /// build the pool themselves.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The thread pool.
+    pub fn set_pool(pool: ThreadPool) -> Result<(), CpuPoolError> {
+        POOL.set(pool).map_err(|_| CpuPoolError::AlreadyConfigured)
+    }
+
+    /// Executes a closure on the CPU worker pool, blocking the calling thread until it completes.
+    ///
+    /// Parallel iterators executed by the closure run on the configured pool, or on the global
+    /// pool if none is configured.
+    pub fn install<F, R>(f: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match POOL.get() {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    fn spawn<F>(f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match POOL.get() {
+            Some(pool) => pool.spawn(f),
+            None => rayon::spawn(f),
+        }
+    }
 
     /// A Rayon CPU backend.
     #[derive(Debug)]
@@ -72,19 +264,17 @@ mod rayon_backend {
 
     impl RayonBackend {
         /// Executes a future on the CPU backend.
-        pub fn blocking_async<F>(fut: F) -> impl Future<Output = F::Output> + Send
+        pub async fn blocking_async<F>(fut: F) -> F::Output
         where
             F: Future + Send + 'static,
             F::Output: Send,
         {
-            async move {
-                let (sender, receiver) = oneshot::channel();
-                rayon::spawn(move || {
-                    let output = block_on(fut);
-                    _ = sender.send(output);
-                });
-                receiver.await.expect("worker thread does not drop channel")
-            }
+            let (sender, receiver) = oneshot::channel();
+            spawn(move || {
+                let output = block_on(fut);
+                _ = sender.send(output);
+            });
+            receiver.await.expect("worker thread does not drop channel")
         }
 
         /// Executes a closure on the CPU backend.
@@ -94,7 +284,7 @@ mod rayon_backend {
             R: Send + 'static,
         {
             let (sender, receiver) = oneshot::channel();
-            rayon::spawn(move || {
+            spawn(move || {
                 _ = sender.send(f());
             });
             receiver.await.expect("worker thread does not drop channel")
@@ -116,5 +306,25 @@ mod rayon_backend {
             let output = block_on(RayonBackend::blocking_async(async { 42 }));
             assert_eq!(output, 42);
         }
+
+        #[test]
+        fn test_rayon_backend_configured_pool() {
+            configure_pool(CpuPoolConfig::new().num_threads(2).thread_name("mpz-cpu")).unwrap();
+
+            assert!(matches!(
+                configure_pool(CpuPoolConfig::new()),
+                Err(CpuPoolError::AlreadyConfigured)
+            ));
+
+            let name = block_on(RayonBackend::blocking(|| {
+                std::thread::current().name().map(String::from)
+            }));
+            assert!(name.unwrap().starts_with("mpz-cpu-"));
+
+            // Parallel iterators of offloaded work run on the configured pool.
+            let num_threads = block_on(RayonBackend::blocking(rayon::current_num_threads));
+            assert_eq!(num_threads, 2);
+            assert_eq!(install(rayon::current_num_threads), 2);
+        }
     }
 }
@@ -0,0 +1,166 @@
+//! Deterministic single-threaded executor.
+//!
+//! [`run_deterministic`] runs both parties of a protocol on the calling thread. Whenever both
+//! parties are able to make progress, the party which is polled next is sampled from a seeded
+//! RNG, and each party is given its own seeded RNG. A failing test can therefore be reproduced
+//! bit-for-bit from its seed, and different seeds exercise different interleavings.
+
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context as StdContext, Poll},
+};
+
+use futures::{
+    task::{waker, ArcWake},
+    Future,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{test_st_executor, TestSTExecutor};
+
+/// The size of the I/O buffer between the parties.
+const IO_BUFFER: usize = 8;
+
+struct Flag(AtomicBool);
+
+impl ArcWake for Flag {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Runs two parties of a protocol deterministically on the calling thread.
+///
+/// Each party is provided a single-threaded context connected to the other party, and an RNG
+/// derived from `seed`.
+///
+/// # Panics
+///
+/// Panics if neither party can make progress, eg. because both are waiting to receive a
+/// message.
+///
+/// # Arguments
+///
+/// * `seed` - The seed of the interleaving and of the parties' RNGs.
+/// * `a` - The first party.
+/// * `b` - The second party.
+pub fn run_deterministic<A, B, FA, FB>(seed: u64, a: A, b: B) -> (FA::Output, FB::Output)
+where
+    A: FnOnce(TestSTExecutor, StdRng) -> FA,
+    B: FnOnce(TestSTExecutor, StdRng) -> FB,
+    FA: Future,
+    FB: Future,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let rng_a = StdRng::seed_from_u64(rng.gen());
+    let rng_b = StdRng::seed_from_u64(rng.gen());
+
+    let (ctx_a, ctx_b) = test_st_executor(IO_BUFFER);
+    let mut fut_a = pin!(a(ctx_a, rng_a));
+    let mut fut_b = pin!(b(ctx_b, rng_b));
+
+    let flag_a = Arc::new(Flag(AtomicBool::new(true)));
+    let flag_b = Arc::new(Flag(AtomicBool::new(true)));
+    let waker_a = waker(flag_a.clone());
+    let waker_b = waker(flag_b.clone());
+
+    let mut output_a = None;
+    let mut output_b = None;
+    while output_a.is_none() || output_b.is_none() {
+        let ready_a = output_a.is_none() && flag_a.0.load(Ordering::SeqCst);
+        let ready_b = output_b.is_none() && flag_b.0.load(Ordering::SeqCst);
+
+        let poll_a = match (ready_a, ready_b) {
+            (true, true) => rng.gen_bool(0.5),
+            (true, false) => true,
+            (false, true) => false,
+            (false, false) => panic!("deterministic executor deadlocked, seed: {seed}"),
+        };
+
+        if poll_a {
+            flag_a.0.store(false, Ordering::SeqCst);
+            if let Poll::Ready(output) = fut_a.as_mut().poll(&mut StdContext::from_waker(&waker_a))
+            {
+                output_a = Some(output);
+            }
+        } else {
+            flag_b.0.store(false, Ordering::SeqCst);
+            if let Poll::Ready(output) = fut_b.as_mut().poll(&mut StdContext::from_waker(&waker_b))
+            {
+                output_b = Some(output);
+            }
+        }
+    }
+
+    (output_a.unwrap(), output_b.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Mutex};
+
+    use serio::{stream::IoStreamExt, SinkExt};
+
+    use super::*;
+    use crate::Context;
+
+    /// Runs a two-way protocol, returning the order in which the parties made progress and the
+    /// values sampled by the parties.
+    fn run(seed: u64) -> (Vec<&'static str>, u64, u64) {
+        let log = Mutex::new(Vec::new());
+
+        let (rand_a, rand_b) = run_deterministic(
+            seed,
+            |mut ctx, mut rng| {
+                let log = &log;
+                async move {
+                    for i in 0..4u32 {
+                        log.lock().unwrap().push("a");
+                        ctx.io_mut().send(i).await.unwrap();
+                        let received: u32 = ctx.io_mut().expect_next().await.unwrap();
+                        assert_eq!(received, i);
+                    }
+                    rng.gen::<u64>()
+                }
+            },
+            |mut ctx, mut rng| {
+                let log = &log;
+                async move {
+                    for i in 0..4u32 {
+                        log.lock().unwrap().push("b");
+                        ctx.io_mut().send(i).await.unwrap();
+                        let received: u32 = ctx.io_mut().expect_next().await.unwrap();
+                        assert_eq!(received, i);
+                    }
+                    rng.gen::<u64>()
+                }
+            },
+        );
+
+        (log.into_inner().unwrap(), rand_a, rand_b)
+    }
+
+    #[test]
+    fn test_run_deterministic() {
+        for seed in 0..8 {
+            assert_eq!(run(seed), run(seed));
+        }
+
+        let interleavings = (0..16).map(|seed| run(seed).0).collect::<HashSet<_>>();
+        assert!(interleavings.len() > 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "deadlocked")]
+    fn test_run_deterministic_deadlock() {
+        let _ = run_deterministic(
+            0,
+            |mut ctx, _| async move { ctx.io_mut().expect_next::<u8>().await },
+            |mut ctx, _| async move { ctx.io_mut().expect_next::<u8>().await },
+        );
+    }
+}
@@ -3,7 +3,11 @@ use async_trait::async_trait;
 use scoped_futures::ScopedBoxFuture;
 use serio::{Sink, Stream};
 
-use crate::{context::Context, cpu::CpuBackend, ContextError, ThreadId};
+use crate::{
+    context::{Context, ErrorKind},
+    cpu::CpuBackend,
+    ContextError, ThreadId,
+};
 
 /// A dummy executor.
 #[derive(Debug, Default)]
@@ -75,6 +79,24 @@ impl Context for DummyExecutor {
         &mut self.io
     }
 
+    async fn open_channel(&mut self, _label: &str) -> Result<Self::Io, ContextError> {
+        Ok(DummyIo)
+    }
+
+    async fn fork(&mut self, n: usize) -> Result<Vec<Self>, ContextError> {
+        let mut id = self.id.fork();
+
+        (0..n)
+            .map(|_| {
+                let id = id.increment_in_place().ok_or_else(|| {
+                    ContextError::new(ErrorKind::Thread, "exceeded maximum number of threads")
+                })?;
+
+                Ok(Self { id, io: DummyIo })
+            })
+            .collect()
+    }
+
     async fn blocking<F, R>(&mut self, f: F) -> Result<R, ContextError>
     where
         F: for<'a> FnOnce(&'a mut Self) -> ScopedBoxFuture<'static, 'a, R> + Send + 'static,
@@ -1,12 +1,24 @@
 //! Executors.
 
+#[cfg(any(test, feature = "test-utils"))]
+mod det;
 mod dummy;
 mod mt;
+#[cfg(any(test, feature = "test-utils"))]
+mod sim;
 mod st;
+#[cfg(any(test, feature = "test-utils"))]
+mod tamper;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub use det::run_deterministic;
 pub use dummy::{DummyExecutor, DummyIo};
 pub use mt::{MTContext, MTExecutor};
+#[cfg(any(test, feature = "test-utils"))]
+pub use sim::{sim_link, test_sim_executor, LinkConfig, LinkStats, SimIo};
 pub use st::STExecutor;
+#[cfg(any(test, feature = "test-utils"))]
+pub use tamper::{tamper_link, test_tamper_executor, Deviation, TamperIo};
 
 #[cfg(any(test, feature = "test-utils"))]
 mod test_utils {
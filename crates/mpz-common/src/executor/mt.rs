@@ -1,4 +1,4 @@
-use std::pin::Pin;
+use std::{collections::HashSet, pin::Pin};
 
 use async_trait::async_trait;
 use futures::{stream::FuturesOrdered, Future, StreamExt};
@@ -104,6 +104,8 @@ struct Inner<M, Io> {
     io: Io,
     // Child threads are created lazily, and are cached for reuse.
     children: Children<M, Io>,
+    // Labels of the channels opened by this thread.
+    channels: HashSet<String>,
 }
 
 impl<M, Io> MTContext<M, Io> {
@@ -116,6 +118,7 @@ impl<M, Io> MTContext<M, Io> {
             inner: Some(Inner {
                 io,
                 children: Children::new(child_id, max_concurrency),
+                channels: HashSet::new(),
             }),
             max_concurrency,
         }
@@ -157,6 +160,31 @@ where
         &mut self.inner_mut().io
     }
 
+    async fn open_channel(&mut self, label: &str) -> Result<Self::Io, ContextError> {
+        if !self.inner_mut().channels.insert(label.to_string()) {
+            return Err(ContextError::new(
+                ErrorKind::Channel,
+                format!("channel was already opened: {label}"),
+            ));
+        }
+
+        self.mux
+            .open_framed(&self.id.channel(label.as_bytes()))
+            .await
+            .map_err(|e| ContextError::new(ErrorKind::Mux, e))
+    }
+
+    async fn fork(&mut self, n: usize) -> Result<Vec<Self>, ContextError> {
+        let mux = self.mux.clone();
+        let children = &mut self.inner_mut().children;
+
+        // Children which are cached for `join` are handed out first, the remaining ones are
+        // created with the next ids.
+        children.alloc(&mux, n).await?;
+
+        Ok(children.slots.drain(..n).collect())
+    }
+
     async fn blocking<F, R>(&mut self, f: F) -> Result<R, ContextError>
     where
         F: for<'a> FnOnce(&'a mut Self) -> ScopedBoxFuture<'static, 'a, R> + Send + 'static,
@@ -288,10 +316,12 @@ where
             let count = count - self.slots.len();
             let mut futs = FuturesOrdered::new();
             for _ in 0..count {
-                let id = self
-                    .id
-                    .increment_in_place()
-                    .expect("number of threads were checked");
+                let id = self.id.increment_in_place().ok_or_else(|| {
+                    ContextError::new(
+                        ErrorKind::Thread,
+                        "exceeded maximum number of threads (255)",
+                    )
+                })?;
 
                 futs.push_back(async {
                     let io = mux
@@ -388,6 +418,62 @@ mod tests {
         assert!(ctx_b.inner.is_some());
     }
 
+    #[tokio::test]
+    async fn test_mt_executor_fork() {
+        let (mut exec_a, mut exec_b) = test_mt_executor(8);
+
+        let (mut ctx_a, mut ctx_b) =
+            futures::try_join!(exec_a.new_thread(), exec_b.new_thread()).unwrap();
+
+        let (children_a, children_b) = futures::try_join!(ctx_a.fork(4), ctx_b.fork(4)).unwrap();
+
+        assert_eq!(children_a.len(), 4);
+        let tasks = children_a
+            .into_iter()
+            .zip(children_b)
+            .enumerate()
+            .map(|(i, (mut child_a, mut child_b))| {
+                assert_eq!(child_a.id(), child_b.id());
+                tokio::spawn(async move {
+                    child_a.io_mut().send(i as u8).await.unwrap();
+                    child_b.io_mut().expect_next::<u8>().await.unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap(), i as u8);
+        }
+
+        // Forking again yields new children.
+        let (children_a, children_b) = futures::try_join!(ctx_a.fork(2), ctx_b.fork(2)).unwrap();
+        assert_ne!(children_a[0].id(), ctx_a.id());
+        assert_eq!(children_a[1].id(), children_b[1].id());
+    }
+
+    #[tokio::test]
+    async fn test_mt_executor_open_channel() {
+        let (mut exec_a, mut exec_b) = test_mt_executor(8);
+
+        let (mut ctx_a, mut ctx_b) =
+            futures::try_join!(exec_a.new_thread(), exec_b.new_thread()).unwrap();
+
+        let (mut ot_a, mut ot_b) =
+            futures::try_join!(ctx_a.open_channel("ot"), ctx_b.open_channel("ot")).unwrap();
+        let (mut garble_a, mut garble_b) =
+            futures::try_join!(ctx_a.open_channel("garble"), ctx_b.open_channel("garble")).unwrap();
+
+        ot_a.send(1u8).await.unwrap();
+        garble_a.send(2u8).await.unwrap();
+        ctx_a.io_mut().send(3u8).await.unwrap();
+
+        assert_eq!(ctx_b.io_mut().expect_next::<u8>().await.unwrap(), 3);
+        assert_eq!(garble_b.expect_next::<u8>().await.unwrap(), 2);
+        assert_eq!(ot_b.expect_next::<u8>().await.unwrap(), 1);
+
+        assert!(ctx_a.open_channel("ot").await.is_err());
+    }
+
     #[tokio::test]
     // Tests that the mt executor polls futures concurrently.
     async fn test_mt_executor_concurrency() {
@@ -0,0 +1,322 @@
+//! Simulated network link.
+
+use std::{
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context as StdContext, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    Future, Stream as _,
+};
+use futures_timer::Delay;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serio::{Deserialize, Serialize, Sink, Stream};
+
+use super::STExecutor;
+
+/// Configuration of a simulated link.
+#[derive(Debug, Clone, Copy)]
+pub struct LinkConfig {
+    /// One-way latency.
+    pub latency: Duration,
+    /// Bandwidth in bits per second, or `None` for unlimited bandwidth.
+    pub bandwidth: Option<u64>,
+    /// Maximum additional latency, sampled uniformly for every message.
+    pub jitter: Duration,
+    /// Seed used to sample the jitter.
+    pub seed: u64,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            bandwidth: None,
+            jitter: Duration::ZERO,
+            seed: 0,
+        }
+    }
+}
+
+impl LinkConfig {
+    /// Returns the time it takes to transmit the provided number of bytes.
+    fn transmit_time(&self, len: usize) -> Duration {
+        match self.bandwidth {
+            Some(bandwidth) => Duration::from_secs_f64((len as f64 * 8.0) / bandwidth as f64),
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// Statistics of one end of a simulated link.
+#[derive(Debug, Default, Clone)]
+pub struct LinkStats(Arc<LinkCounters>);
+
+#[derive(Debug, Default)]
+struct LinkCounters {
+    bytes_sent: AtomicU64,
+    messages_sent: AtomicU64,
+    rounds: AtomicU64,
+    // Whether a message was sent since the last message was received.
+    sent: AtomicBool,
+}
+
+impl LinkStats {
+    /// Returns the number of bytes sent.
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages sent.
+    pub fn messages_sent(&self) -> u64 {
+        self.0.messages_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of rounds, ie the number of times a message was received after
+    /// sending a message.
+    pub fn rounds(&self) -> u64 {
+        self.0.rounds.load(Ordering::Relaxed)
+    }
+}
+
+/// A message in flight.
+#[derive(Debug)]
+struct Frame {
+    deliver_at: Instant,
+    payload: Vec<u8>,
+}
+
+/// One end of a simulated link.
+#[derive(Debug)]
+pub struct SimIo {
+    config: LinkConfig,
+    rng: StdRng,
+    tx: UnboundedSender<Frame>,
+    rx: UnboundedReceiver<Frame>,
+    // Instant at which the link is free to transmit the next message.
+    link_free_at: Instant,
+    // Delivery instant of the last message, messages are delivered in order.
+    last_delivery: Instant,
+    pending: VecDeque<Frame>,
+    timer: Option<Delay>,
+    stats: LinkStats,
+}
+
+impl SimIo {
+    /// Returns the statistics of this end of the link.
+    pub fn stats(&self) -> &LinkStats {
+        &self.stats
+    }
+}
+
+/// Creates a simulated link.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the link, which applies to both directions.
+pub fn sim_link(config: LinkConfig) -> (SimIo, SimIo) {
+    let (tx_0, rx_1) = unbounded();
+    let (tx_1, rx_0) = unbounded();
+    let now = Instant::now();
+
+    let new = |tx, rx, seed| SimIo {
+        config,
+        rng: StdRng::seed_from_u64(seed),
+        tx,
+        rx,
+        link_free_at: now,
+        last_delivery: now,
+        pending: VecDeque::new(),
+        timer: None,
+        stats: LinkStats::default(),
+    };
+
+    (
+        new(tx_0, rx_0, config.seed),
+        new(tx_1, rx_1, config.seed.wrapping_add(1)),
+    )
+}
+
+/// Creates a pair of single-threaded executors connected by a simulated link.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the link.
+pub fn test_sim_executor(config: LinkConfig) -> (STExecutor<SimIo>, STExecutor<SimIo>) {
+    let (io_0, io_1) = sim_link(config);
+
+    (STExecutor::new(io_0), STExecutor::new(io_1))
+}
+
+impl Sink for SimIo {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send<Item: Serialize>(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        let payload =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let now = Instant::now();
+        let start = self.link_free_at.max(now);
+        self.link_free_at = start + self.config.transmit_time(payload.len());
+
+        let jitter = if self.config.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            let jitter = self.config.jitter;
+            self.rng.gen_range(Duration::ZERO..=jitter)
+        };
+
+        let deliver_at = (self.link_free_at + self.config.latency + jitter).max(self.last_delivery);
+        self.last_delivery = deliver_at;
+
+        let counters = &self.stats.0;
+        counters
+            .bytes_sent
+            .fetch_add(payload.len() as u64, Ordering::Relaxed);
+        counters.messages_sent.fetch_add(1, Ordering::Relaxed);
+        counters.sent.store(true, Ordering::Relaxed);
+
+        self.tx
+            .unbounded_send(Frame {
+                deliver_at,
+                payload,
+            })
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.tx.close_channel();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for SimIo {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = &mut *self;
+
+        // Receive all frames which are in flight.
+        loop {
+            match Pin::new(&mut this.rx).poll_next(cx) {
+                Poll::Ready(Some(frame)) => this.pending.push_back(frame),
+                Poll::Ready(None) if this.pending.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+
+        let Some(deliver_at) = this.pending.front().map(|frame| frame.deliver_at) else {
+            return Poll::Pending;
+        };
+
+        let now = Instant::now();
+        if deliver_at > now {
+            let timer = this
+                .timer
+                .get_or_insert_with(|| Delay::new(deliver_at - now));
+            ready!(Pin::new(timer).poll(cx));
+        }
+        this.timer = None;
+
+        let frame = this.pending.pop_front().expect("frame is pending");
+
+        let counters = &this.stats.0;
+        if counters.sent.swap(false, Ordering::Relaxed) {
+            counters.rounds.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Poll::Ready(Some(
+            bincode::deserialize(&frame.payload)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serio::{stream::IoStreamExt, SinkExt};
+
+    use crate::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_sim_link_latency() {
+        let latency = Duration::from_millis(20);
+        let (mut ctx_0, mut ctx_1) = test_sim_executor(LinkConfig {
+            latency,
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        block_on(async {
+            ctx_0.io_mut().send(1u8).await.unwrap();
+            assert_eq!(ctx_1.io_mut().expect_next::<u8>().await.unwrap(), 1);
+            ctx_1.io_mut().send(2u8).await.unwrap();
+            assert_eq!(ctx_0.io_mut().expect_next::<u8>().await.unwrap(), 2);
+        });
+
+        assert!(start.elapsed() >= 2 * latency);
+        assert_eq!(ctx_0.io_mut().stats().rounds(), 1);
+        assert_eq!(ctx_1.io_mut().stats().rounds(), 0);
+    }
+
+    #[test]
+    fn test_sim_link_bandwidth() {
+        // 1 Mbps, so 12.5kB takes 100ms to transmit.
+        let (mut io_0, mut io_1) = sim_link(LinkConfig {
+            bandwidth: Some(1_000_000),
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        block_on(async {
+            io_0.send(vec![0u8; 12_500]).await.unwrap();
+            io_1.expect_next::<Vec<u8>>().await.unwrap();
+        });
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert!(io_0.stats().bytes_sent() >= 12_500);
+        assert_eq!(io_0.stats().messages_sent(), 1);
+    }
+
+    #[test]
+    fn test_sim_link_jitter_preserves_order() {
+        let (mut io_0, mut io_1) = sim_link(LinkConfig {
+            jitter: Duration::from_millis(5),
+            ..Default::default()
+        });
+
+        block_on(async {
+            for i in 0..16u8 {
+                io_0.send(i).await.unwrap();
+            }
+            for i in 0..16u8 {
+                assert_eq!(io_1.expect_next::<u8>().await.unwrap(), i);
+            }
+        });
+    }
+}
@@ -4,7 +4,7 @@ use scoped_futures::ScopedBoxFuture;
 use serio::{IoSink, IoStream};
 
 use crate::{
-    context::{Context, ContextError},
+    context::{Context, ContextError, ErrorKind},
     cpu::CpuBackend,
     ThreadId,
 };
@@ -67,6 +67,20 @@ where
         &mut self.inner().io
     }
 
+    async fn open_channel(&mut self, label: &str) -> Result<Self::Io, ContextError> {
+        Err(ContextError::new(
+            ErrorKind::Channel,
+            format!("single-threaded executor can not open channel: {label}"),
+        ))
+    }
+
+    async fn fork(&mut self, _n: usize) -> Result<Vec<Self>, ContextError> {
+        Err(ContextError::new(
+            ErrorKind::Thread,
+            "single-threaded executor can not fork",
+        ))
+    }
+
     async fn blocking<F, R>(&mut self, f: F) -> Result<R, ContextError>
     where
         F: for<'a> FnOnce(&'a mut Self) -> ScopedBoxFuture<'static, 'a, R> + Send + 'static,
@@ -0,0 +1,211 @@
+//! Link which injects deviations from a protocol.
+//!
+//! Malicious security claims are tested by letting one party deviate from the protocol and
+//! asserting that the honest party rejects. A [`Deviation`] modifies a message of a given type
+//! sent by the deviating party, identified by how many messages of that type were sent before it.
+//! This allows negative tests to be written against the unmodified protocol implementation.
+
+use std::{
+    any::type_name,
+    collections::HashMap,
+    fmt, io,
+    pin::Pin,
+    task::{Context as StdContext, Poll},
+};
+
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    Stream as _,
+};
+use serio::{Deserialize, Serialize, Sink, Stream};
+
+use super::STExecutor;
+
+type Mutation = Box<dyn FnMut(&[u8]) -> io::Result<Vec<u8>> + Send + Sync>;
+
+/// A deviation from a protocol.
+pub struct Deviation {
+    type_name: &'static str,
+    occurrence: usize,
+    mutation: Mutation,
+}
+
+impl fmt::Debug for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deviation")
+            .field("type_name", &self.type_name)
+            .field("occurrence", &self.occurrence)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deviation {
+    /// Creates a deviation which modifies a message of type `T`.
+    ///
+    /// # Arguments
+    ///
+    /// * `occurrence` - The number of messages of type `T` sent before the modified message.
+    /// * `modify` - Modifies the message.
+    pub fn modify<T, F>(occurrence: usize, mut modify: F) -> Self
+    where
+        T: Serialize + Deserialize,
+        F: FnMut(&mut T) + Send + Sync + 'static,
+    {
+        Self {
+            type_name: type_name::<T>(),
+            occurrence,
+            mutation: Box::new(move |payload| {
+                let mut msg: T = bincode::deserialize(payload)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                modify(&mut msg);
+                bincode::serialize(&msg).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }),
+        }
+    }
+}
+
+/// One end of a link which injects deviations into the messages it sends.
+#[derive(Debug)]
+pub struct TamperIo {
+    tx: UnboundedSender<Vec<u8>>,
+    rx: UnboundedReceiver<Vec<u8>>,
+    deviations: Vec<Deviation>,
+    // The number of messages of each type sent so far.
+    sent: HashMap<&'static str, usize>,
+    applied: usize,
+}
+
+impl TamperIo {
+    /// Returns the number of deviations which have been applied.
+    ///
+    /// Negative tests should assert that the deviation was applied, otherwise the honest party
+    /// passes trivially.
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+}
+
+/// Creates a link in which the first end applies the provided deviations.
+///
+/// # Arguments
+///
+/// * `deviations` - The deviations of the first end.
+pub fn tamper_link(deviations: Vec<Deviation>) -> (TamperIo, TamperIo) {
+    let (tx_0, rx_1) = unbounded();
+    let (tx_1, rx_0) = unbounded();
+
+    let new = |tx, rx, deviations| TamperIo {
+        tx,
+        rx,
+        deviations,
+        sent: HashMap::new(),
+        applied: 0,
+    };
+
+    (new(tx_0, rx_0, deviations), new(tx_1, rx_1, Vec::new()))
+}
+
+/// Creates a pair of single-threaded executors in which the first deviates from the protocol.
+///
+/// # Arguments
+///
+/// * `deviations` - The deviations of the first executor.
+pub fn test_tamper_executor(
+    deviations: Vec<Deviation>,
+) -> (STExecutor<TamperIo>, STExecutor<TamperIo>) {
+    let (io_0, io_1) = tamper_link(deviations);
+
+    (STExecutor::new(io_0), STExecutor::new(io_1))
+}
+
+impl Sink for TamperIo {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send<Item: Serialize>(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        let this = &mut *self;
+
+        let mut payload =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let name = type_name::<Item>();
+        let sent = this.sent.entry(name).or_default();
+        let occurrence = *sent;
+        *sent += 1;
+
+        for deviation in this
+            .deviations
+            .iter_mut()
+            .filter(|deviation| deviation.type_name == name && deviation.occurrence == occurrence)
+        {
+            payload = (deviation.mutation)(&payload)?;
+            this.applied += 1;
+        }
+
+        this.tx
+            .unbounded_send(payload)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.tx.close_channel();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for TamperIo {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        Pin::new(&mut self.rx).poll_next(cx).map(|payload| {
+            payload.map(|payload| {
+                bincode::deserialize(&payload)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serio::{stream::IoStreamExt, SinkExt};
+
+    use super::*;
+
+    #[test]
+    fn test_tamper_link() {
+        let (mut io_0, mut io_1) =
+            tamper_link(vec![Deviation::modify::<u32, _>(1, |msg| *msg += 1)]);
+
+        block_on(async {
+            io_0.send(0u32).await.unwrap();
+            io_0.send(0u8).await.unwrap();
+            io_0.send(0u32).await.unwrap();
+            io_0.send(0u32).await.unwrap();
+            io_1.send(0u32).await.unwrap();
+
+            assert_eq!(io_1.expect_next::<u32>().await.unwrap(), 0);
+            assert_eq!(io_1.expect_next::<u8>().await.unwrap(), 0);
+            assert_eq!(io_1.expect_next::<u32>().await.unwrap(), 1);
+            assert_eq!(io_1.expect_next::<u32>().await.unwrap(), 0);
+            assert_eq!(io_0.expect_next::<u32>().await.unwrap(), 0);
+        });
+
+        assert_eq!(io_0.applied(), 1);
+        assert_eq!(io_1.applied(), 0);
+    }
+}
@@ -0,0 +1,229 @@
+//! Protocol version negotiation.
+//!
+//! Parties running mismatched builds otherwise fail with inscrutable deserialization errors
+//! part way through a protocol. [`handshake`] exchanges a [`ProtocolInfo`] when a context is
+//! established, and fails fast with a descriptive error if the parties are incompatible.
+//!
+//! # Example
+//!
+//! ```
+//! use mpz_common::handshake::ProtocolInfo;
+//!
+//! let info = ProtocolInfo::new()
+//!     .with_feature("garbling", "half-gates")
+//!     .with_feature("ot", "kos15")
+//!     .with_feature("security_parameter", 40);
+//!
+//! assert_eq!(info.feature("ot"), Some("kos15"));
+//! ```
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serio::{stream::IoStreamExt, SinkExt};
+
+use crate::Context;
+
+/// The current protocol version.
+///
+/// This is incremented whenever a change breaks compatibility with previous builds.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A handshake error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum HandshakeError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("protocol version mismatch: local version {local}, peer version {peer}")]
+    Version { local: u32, peer: u32 },
+    #[error(
+        "feature mismatch for \"{feature}\": local {}, peer {}",
+        display_feature(.local),
+        display_feature(.peer)
+    )]
+    Feature {
+        feature: String,
+        local: Option<String>,
+        peer: Option<String>,
+    },
+}
+
+fn display_feature(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("\"{value}\""),
+        None => "not enabled".to_string(),
+    }
+}
+
+/// The protocol version and enabled features of a party.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    version: u32,
+    features: BTreeMap<String, String>,
+}
+
+impl Default for ProtocolInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProtocolInfo {
+    /// Creates a new protocol info with the current [protocol version](PROTOCOL_VERSION) and no
+    /// features.
+    pub fn new() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            features: BTreeMap::new(),
+        }
+    }
+
+    /// Sets a feature, eg. the garbling scheme, OT flavor or security parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the feature.
+    /// * `value` - The value of the feature.
+    pub fn with_feature(mut self, name: impl Into<String>, value: impl Display) -> Self {
+        self.features.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Returns the protocol version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the value of a feature, if it is set.
+    pub fn feature(&self, name: &str) -> Option<&str> {
+        self.features.get(name).map(String::as_str)
+    }
+
+    /// Returns an iterator over the features.
+    pub fn features(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.features
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Checks that the peer is compatible.
+    ///
+    /// Parties are compatible if they have the same protocol version and the same features.
+    pub fn check(&self, peer: &ProtocolInfo) -> Result<(), HandshakeError> {
+        if self.version != peer.version {
+            return Err(HandshakeError::Version {
+                local: self.version,
+                peer: peer.version,
+            });
+        }
+
+        let names = self.features.keys().chain(peer.features.keys());
+        for name in names {
+            let local = self.features.get(name);
+            let peer = peer.features.get(name);
+            if local != peer {
+                return Err(HandshakeError::Feature {
+                    feature: name.clone(),
+                    local: local.cloned(),
+                    peer: peer.cloned(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Performs the handshake with the peer.
+///
+/// Both parties send their protocol info and check that the peer is compatible. This should be
+/// done once when the context is established, before running any protocols.
+///
+/// Returns the protocol info of the peer.
+///
+/// # Arguments
+///
+/// * `ctx` - The thread context.
+/// * `info` - The protocol info of this party.
+pub async fn handshake<Ctx: Context>(
+    ctx: &mut Ctx,
+    info: &ProtocolInfo,
+) -> Result<ProtocolInfo, HandshakeError> {
+    let io = ctx.io_mut();
+    io.send(info.clone()).await?;
+    let peer: ProtocolInfo = io.expect_next().await?;
+
+    info.check(&peer)?;
+
+    Ok(peer)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::executor::test_st_executor;
+
+    fn info() -> ProtocolInfo {
+        ProtocolInfo::new()
+            .with_feature("garbling", "half-gates")
+            .with_feature("ot", "kos15")
+            .with_feature("security_parameter", 40)
+    }
+
+    #[test]
+    fn test_handshake() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let local = info();
+
+        let (peer_a, peer_b) = block_on(async {
+            futures::try_join!(handshake(&mut ctx_a, &local), handshake(&mut ctx_b, &local))
+                .unwrap()
+        });
+
+        assert_eq!(peer_a, local);
+        assert_eq!(peer_b, local);
+    }
+
+    #[test]
+    fn test_handshake_version_mismatch() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+        let local = info();
+        let mut old = info();
+        old.version = PROTOCOL_VERSION - 1;
+
+        let (res_a, res_b) = block_on(async {
+            futures::join!(handshake(&mut ctx_a, &local), handshake(&mut ctx_b, &old))
+        });
+
+        assert!(matches!(res_a, Err(HandshakeError::Version { .. })));
+        assert!(matches!(res_b, Err(HandshakeError::Version { .. })));
+    }
+
+    #[test]
+    fn test_handshake_feature_mismatch() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+        let local = info();
+        let other = info().with_feature("ot", "ferret");
+        let missing = ProtocolInfo::new().with_feature("garbling", "half-gates");
+
+        let (res_a, res_b) = block_on(async {
+            futures::join!(handshake(&mut ctx_a, &local), handshake(&mut ctx_b, &other))
+        });
+
+        let err = res_a.unwrap_err();
+        assert!(matches!(err, HandshakeError::Feature { ref feature, .. } if feature == "ot"));
+        assert_eq!(
+            err.to_string(),
+            "feature mismatch for \"ot\": local \"kos15\", peer \"ferret\""
+        );
+        assert!(res_b.is_err());
+
+        let err = info().check(&missing).unwrap_err();
+        assert!(err.to_string().contains("peer not enabled"));
+    }
+}
@@ -55,8 +55,29 @@ impl ThreadId {
 
         Self(id.into())
     }
+
+    /// Returns the ID of a logical channel of the thread with the provided label.
+    ///
+    /// Thread IDs issued by the executors never contain the byte `0xFF`, which is used to
+    /// separate the thread ID from the label. Hence channel IDs are distinct from thread IDs, and
+    /// from channel IDs of other threads.
+    #[inline]
+    pub fn channel(&self, label: &[u8]) -> Self {
+        let mut id = Vec::with_capacity(self.0.len() + 1 + label.len());
+        id.extend_from_slice(&self.0);
+        id.push(CHANNEL_SEPARATOR);
+        id.extend_from_slice(label);
+
+        Self(id.into())
+    }
 }
 
+/// Separates a thread ID from the label of a channel.
+///
+/// Thread IDs are incremented with a checked add and the last ID is never issued, so no thread
+/// ID contains this byte.
+const CHANNEL_SEPARATOR: u8 = u8::MAX;
+
 impl AsRef<[u8]> for ThreadId {
     #[inline]
     fn as_ref(&self) -> &[u8] {
@@ -116,4 +137,13 @@ mod tests {
         assert_eq!(id.increment().unwrap().as_bytes(), &[2]);
         assert_eq!(id.fork().as_bytes(), &[1, 0]);
     }
+
+    #[test]
+    fn test_channel_id() {
+        let id = ThreadId::new(1);
+
+        assert_eq!(id.channel(b"ot").as_bytes(), &[1, 0xFF, b'o', b't']);
+        assert_ne!(id.channel(b"ot"), id.channel(b"garble"));
+        assert_ne!(id.channel(&[]), id.fork());
+    }
 }
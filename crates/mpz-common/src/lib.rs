@@ -14,14 +14,24 @@
     clippy::all
 )]
 
+pub mod compression;
 mod context;
 pub mod cpu;
 pub mod executor;
+pub mod handshake;
 mod id;
 #[cfg(any(test, feature = "ideal"))]
 pub mod ideal;
+pub mod metrics;
+pub mod recovery;
+pub mod replay;
+#[cfg(feature = "secure")]
+pub mod secure;
 #[cfg(feature = "sync")]
 pub mod sync;
+pub mod timeout;
+pub mod transcript;
+pub mod transport;
 
 use async_trait::async_trait;
 pub use context::{Context, ContextError};
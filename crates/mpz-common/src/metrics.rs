@@ -0,0 +1,425 @@
+//! I/O accounting.
+//!
+//! [`MeteredIo`] counts the messages sent and received on an I/O channel, and [`MeteredStream`]
+//! counts the bytes written to and read from a byte stream. Both record to a shared [`IoStats`],
+//! which can be sampled before and after a protocol phase to compute its communication cost.
+//!
+//! [`MeteredMux`] meters every channel opened by a multiplexer, recording the stats of each
+//! channel by id in an [`IoMetrics`] registry. It meters bytes when wrapping a [`UidMux`], and
+//! messages when wrapping a [`FramedUidMux`].
+
+use std::{
+    collections::BTreeMap,
+    io,
+    ops::{Add, Sub},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context as StdContext, Poll},
+};
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite};
+use serio::{Deserialize, Serialize, Sink, Stream};
+use uid_mux::{FramedUidMux, UidMux};
+
+/// I/O counts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IoCounts {
+    /// Number of bytes sent.
+    pub bytes_sent: u64,
+    /// Number of bytes received.
+    pub bytes_received: u64,
+    /// Number of messages sent.
+    pub messages_sent: u64,
+    /// Number of messages received.
+    pub messages_received: u64,
+}
+
+impl Add for IoCounts {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            bytes_sent: self.bytes_sent + rhs.bytes_sent,
+            bytes_received: self.bytes_received + rhs.bytes_received,
+            messages_sent: self.messages_sent + rhs.messages_sent,
+            messages_received: self.messages_received + rhs.messages_received,
+        }
+    }
+}
+
+impl Sub for IoCounts {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            bytes_sent: self.bytes_sent - rhs.bytes_sent,
+            bytes_received: self.bytes_received - rhs.bytes_received,
+            messages_sent: self.messages_sent - rhs.messages_sent,
+            messages_received: self.messages_received - rhs.messages_received,
+        }
+    }
+}
+
+/// Shared I/O counters.
+#[derive(Debug, Default, Clone)]
+pub struct IoStats(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+}
+
+impl IoStats {
+    /// Creates new counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current counts.
+    pub fn counts(&self) -> IoCounts {
+        IoCounts {
+            bytes_sent: self.0.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.0.bytes_received.load(Ordering::Relaxed),
+            messages_sent: self.0.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.0.messages_received.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_bytes_sent(&self, n: usize) {
+        self.0.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn record_bytes_received(&self, n: usize) {
+        self.0.bytes_received.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn record_message_sent(&self) {
+        self.0.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_message_received(&self) {
+        self.0.messages_received.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// An I/O channel which counts messages.
+    #[derive(Debug)]
+    pub struct MeteredIo<Io> {
+        #[pin]
+        io: Io,
+        stats: IoStats,
+    }
+}
+
+impl<Io> MeteredIo<Io> {
+    /// Creates a new metered I/O channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The I/O channel.
+    /// * `stats` - The counters to record to.
+    pub fn new(io: Io, stats: IoStats) -> Self {
+        Self { io, stats }
+    }
+
+    /// Returns the counters.
+    pub fn stats(&self) -> &IoStats {
+        &self.stats
+    }
+
+    /// Returns the inner I/O channel.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+}
+
+impl<Io: Sink> Sink for MeteredIo<Io> {
+    type Error = Io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_ready(cx)
+    }
+
+    fn start_send<Item: Serialize>(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        let this = self.project();
+        this.io.start_send(item)?;
+        this.stats.record_message_sent();
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_close(cx)
+    }
+}
+
+impl<Io: Stream> Stream for MeteredIo<Io> {
+    type Error = Io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = self.project();
+        let item = this.io.poll_next::<Item>(cx);
+        if let Poll::Ready(Some(Ok(_))) = &item {
+            this.stats.record_message_received();
+        }
+        item
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A byte stream which counts bytes.
+    #[derive(Debug)]
+    pub struct MeteredStream<S> {
+        #[pin]
+        stream: S,
+        stats: IoStats,
+    }
+}
+
+impl<S> MeteredStream<S> {
+    /// Creates a new metered byte stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The byte stream.
+    /// * `stats` - The counters to record to.
+    pub fn new(stream: S, stats: IoStats) -> Self {
+        Self { stream, stats }
+    }
+
+    /// Returns the counters.
+    pub fn stats(&self) -> &IoStats {
+        &self.stats
+    }
+
+    /// Returns the inner byte stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for MeteredStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let read = this.stream.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &read {
+            this.stats.record_bytes_received(*n);
+        }
+        read
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for MeteredStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let written = this.stream.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &written {
+            this.stats.record_bytes_sent(*n);
+        }
+        written
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        self.project().stream.poll_close(cx)
+    }
+}
+
+/// A registry of I/O counters, by channel id.
+#[derive(Debug, Default, Clone)]
+pub struct IoMetrics {
+    channels: Arc<Mutex<BTreeMap<Vec<u8>, IoStats>>>,
+}
+
+impl IoMetrics {
+    /// Creates a new registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the counters of a channel, registering it if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the channel, eg. a [`ThreadId`](crate::ThreadId).
+    pub fn channel(&self, id: &[u8]) -> IoStats {
+        self.channels
+            .lock()
+            .unwrap()
+            .entry(id.to_vec())
+            .or_default()
+            .clone()
+    }
+
+    /// Returns the counts of a channel, if it is registered.
+    pub fn counts(&self, id: &[u8]) -> Option<IoCounts> {
+        self.channels.lock().unwrap().get(id).map(IoStats::counts)
+    }
+
+    /// Returns the counts of all channels, ordered by id.
+    pub fn all_counts(&self) -> Vec<(Vec<u8>, IoCounts)> {
+        self.channels
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, stats)| (id.clone(), stats.counts()))
+            .collect()
+    }
+
+    /// Returns the sum of the counts of all channels.
+    pub fn total(&self) -> IoCounts {
+        self.channels
+            .lock()
+            .unwrap()
+            .values()
+            .fold(IoCounts::default(), |total, stats| total + stats.counts())
+    }
+}
+
+/// A multiplexer which meters every channel it opens.
+#[derive(Debug, Clone)]
+pub struct MeteredMux<M> {
+    mux: M,
+    metrics: IoMetrics,
+}
+
+impl<M> MeteredMux<M> {
+    /// Creates a new metered multiplexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux` - The multiplexer.
+    /// * `metrics` - The registry to record to.
+    pub fn new(mux: M, metrics: IoMetrics) -> Self {
+        Self { mux, metrics }
+    }
+
+    /// Returns the registry.
+    pub fn metrics(&self) -> &IoMetrics {
+        &self.metrics
+    }
+}
+
+#[async_trait]
+impl<Id, M> UidMux<Id> for MeteredMux<M>
+where
+    Id: AsRef<[u8]> + Sync,
+    M: UidMux<Id> + Sync,
+{
+    type Stream = MeteredStream<M::Stream>;
+    type Error = M::Error;
+
+    async fn open(&self, id: &Id) -> Result<Self::Stream, Self::Error> {
+        let stream = self.mux.open(id).await?;
+
+        Ok(MeteredStream::new(
+            stream,
+            self.metrics.channel(id.as_ref()),
+        ))
+    }
+}
+
+#[async_trait]
+impl<Id, M> FramedUidMux<Id> for MeteredMux<M>
+where
+    Id: AsRef<[u8]> + Sync,
+    M: FramedUidMux<Id> + Sync,
+{
+    type Framed = MeteredIo<M::Framed>;
+    type Error = M::Error;
+
+    async fn open_framed(&self, id: &Id) -> Result<Self::Framed, Self::Error> {
+        let io = self.mux.open_framed(id).await?;
+
+        Ok(MeteredIo::new(io, self.metrics.channel(id.as_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, io::Cursor, AsyncReadExt, AsyncWriteExt};
+    use serio::{channel::duplex, stream::IoStreamExt, SinkExt};
+
+    use super::*;
+
+    #[test]
+    fn test_metered_io() {
+        let (io_0, io_1) = duplex(8);
+        let mut io_0 = MeteredIo::new(io_0, IoStats::new());
+        let mut io_1 = MeteredIo::new(io_1, IoStats::new());
+
+        block_on(async {
+            io_0.send(1u8).await.unwrap();
+            io_0.send(2u8).await.unwrap();
+            io_1.expect_next::<u8>().await.unwrap();
+        });
+
+        assert_eq!(io_0.stats().counts().messages_sent, 2);
+        assert_eq!(io_1.stats().counts().messages_received, 1);
+
+        // Sample before and after a phase.
+        let before = io_1.stats().counts();
+        block_on(io_1.expect_next::<u8>()).unwrap();
+        let phase = io_1.stats().counts() - before;
+
+        assert_eq!(
+            phase,
+            IoCounts {
+                messages_received: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_metered_stream() {
+        let metrics = IoMetrics::new();
+        let mut stream = MeteredStream::new(Cursor::new(Vec::new()), metrics.channel(b"ot"));
+
+        block_on(stream.write_all(&[0u8; 42])).unwrap();
+
+        let mut stream = MeteredStream::new(
+            Cursor::new(stream.into_inner().into_inner()),
+            metrics.channel(b"garble"),
+        );
+        let mut buf = Vec::new();
+        block_on(stream.read_to_end(&mut buf)).unwrap();
+
+        assert_eq!(metrics.counts(b"ot").unwrap().bytes_sent, 42);
+        assert_eq!(metrics.counts(b"garble").unwrap().bytes_received, 42);
+        assert_eq!(
+            metrics.total(),
+            IoCounts {
+                bytes_sent: 42,
+                bytes_received: 42,
+                ..Default::default()
+            }
+        );
+        assert_eq!(metrics.all_counts().len(), 2);
+    }
+}
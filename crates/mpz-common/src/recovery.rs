@@ -0,0 +1,503 @@
+//! Connection recovery.
+//!
+//! [`ResumableIo`] wraps an I/O channel and transparently reconnects after a transient
+//! disconnect, so that long running phases, eg. preprocessing, survive flaky networks.
+//!
+//! Every message is assigned a sequence number and kept until the peer acknowledges it.
+//! Acknowledgements are piggybacked on the messages sent by the peer. After reconnecting, both
+//! parties exchange the sequence number of the next message they expect, and replay the
+//! messages the peer has not received.
+//!
+//! Both parties must wrap their channel in a [`ResumableIo`].
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{ready, Context as StdContext, Poll},
+    time::Duration,
+};
+
+use futures_timer::Delay;
+use serio::{stream::IoStreamExt, Deserialize, IoDuplex, Serialize, Sink, SinkExt, Stream};
+
+/// A future which resolves to a new connection.
+pub type ConnectFuture<Io> = Pin<Box<dyn Future<Output = io::Result<Io>> + Send + Sync>>;
+
+type ReconnectFuture<Io> = Pin<Box<dyn Future<Output = io::Result<(Io, u64)>> + Send + Sync>>;
+
+/// A type which can establish a new connection to the peer.
+pub trait Connector: Send + Sync + Unpin + 'static {
+    /// The I/O channel of a connection.
+    type Io: IoDuplex + Send + Sync + Unpin + 'static;
+
+    /// Returns a future which resolves to a new connection.
+    fn connect(&mut self) -> ConnectFuture<Self::Io>;
+}
+
+impl<F, Fut, Io> Connector for F
+where
+    F: FnMut() -> Fut + Send + Sync + Unpin + 'static,
+    Fut: Future<Output = io::Result<Io>> + Send + Sync + 'static,
+    Io: IoDuplex + Send + Sync + Unpin + 'static,
+{
+    type Io = Io;
+
+    fn connect(&mut self) -> ConnectFuture<Self::Io> {
+        Box::pin(self())
+    }
+}
+
+/// Configuration of [`ResumableIo`].
+#[derive(Debug, Clone, Copy)]
+pub struct RecoveryConfig {
+    /// The maximum number of consecutive reconnection attempts.
+    pub max_retries: usize,
+    /// The delay before each reconnection attempt after the first.
+    pub backoff: Duration,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// A packet sent over the underlying connection.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+enum Packet {
+    Data {
+        seq: u64,
+        ack: u64,
+        payload: Vec<u8>,
+    },
+    Resume {
+        next: u64,
+    },
+}
+
+enum State<Io> {
+    Connected(Io),
+    Reconnecting(ReconnectFuture<Io>),
+    Failed,
+}
+
+/// An I/O channel which recovers from disconnects.
+pub struct ResumableIo<C: Connector> {
+    connector: C,
+    config: RecoveryConfig,
+    state: State<C::Io>,
+    attempts: usize,
+    /// Sequence number of the next message to send.
+    send_seq: u64,
+    /// Sequence number of the next message expected from the peer.
+    recv_seq: u64,
+    /// Sent messages which the peer has not acknowledged.
+    unacked: VecDeque<(u64, Vec<u8>)>,
+    /// Sequence number of the next message to replay after reconnecting.
+    replay_from: Option<u64>,
+}
+
+impl<C: Connector> std::fmt::Debug for ResumableIo<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResumableIo")
+            .field("config", &self.config)
+            .field("send_seq", &self.send_seq)
+            .field("recv_seq", &self.recv_seq)
+            .field("unacked", &self.unacked.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: Connector> ResumableIo<C> {
+    /// Creates a new resumable I/O channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The I/O channel of the current connection.
+    /// * `connector` - Establishes a new connection after a disconnect.
+    /// * `config` - The recovery configuration.
+    pub fn new(io: C::Io, connector: C, config: RecoveryConfig) -> Self {
+        Self {
+            connector,
+            config,
+            state: State::Connected(io),
+            attempts: 0,
+            send_seq: 0,
+            recv_seq: 0,
+            unacked: VecDeque::new(),
+            replay_from: None,
+        }
+    }
+
+    /// Returns the number of sent messages which the peer has not acknowledged.
+    pub fn unacked(&self) -> usize {
+        self.unacked.len()
+    }
+
+    /// Drops the messages which the peer has received.
+    fn acknowledge(&mut self, next: u64) {
+        while self.unacked.front().is_some_and(|(seq, _)| *seq < next) {
+            self.unacked.pop_front();
+        }
+    }
+
+    /// Starts reconnecting, or fails if the number of retries is exhausted.
+    fn reconnect(&mut self, err: io::Error) -> io::Result<()> {
+        if self.attempts >= self.config.max_retries {
+            self.state = State::Failed;
+            return Err(err);
+        }
+
+        let delay = (self.attempts > 0).then_some(self.config.backoff);
+        let connect = self.connector.connect();
+        let next = self.recv_seq;
+
+        self.attempts += 1;
+        self.state = State::Reconnecting(Box::pin(async move {
+            if let Some(delay) = delay {
+                Delay::new(delay).await;
+            }
+
+            let mut io = connect.await?;
+            io.send(Packet::Resume { next }).await?;
+
+            match io.expect_next::<Packet>().await? {
+                Packet::Resume { next } => Ok((io, next)),
+                Packet::Data { .. } => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "expected resume packet",
+                )),
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Polls until connected and all unacknowledged messages are replayed.
+    fn poll_connected(&mut self, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                State::Connected(_) => {}
+                State::Reconnecting(fut) => match ready!(fut.as_mut().poll(cx)) {
+                    Ok((io, next)) => {
+                        self.state = State::Connected(io);
+                        self.acknowledge(next);
+                        self.replay_from = self.unacked.front().map(|(seq, _)| *seq);
+                    }
+                    Err(err) => {
+                        self.reconnect(err)?;
+                        continue;
+                    }
+                },
+                State::Failed => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::NotConnected,
+                        "connection could not be recovered",
+                    )))
+                }
+            }
+
+            match ready!(self.poll_replay(cx)) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(err) => self.reconnect(err)?,
+            }
+        }
+    }
+
+    /// Replays the messages which were not received by the peer.
+    fn poll_replay(&mut self, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        let State::Connected(io) = &mut self.state else {
+            unreachable!("replay is only performed when connected");
+        };
+
+        while let Some(from) = self.replay_from {
+            let Some(idx) = self.unacked.iter().position(|(seq, _)| *seq >= from) else {
+                self.replay_from = None;
+                break;
+            };
+
+            ready!(Pin::new(&mut *io).poll_ready(cx))?;
+
+            let (seq, payload) = &self.unacked[idx];
+            Pin::new(&mut *io).start_send(Packet::Data {
+                seq: *seq,
+                ack: self.recv_seq,
+                payload: payload.clone(),
+            })?;
+            self.replay_from = Some(seq + 1);
+        }
+
+        self.attempts = 0;
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Polls an operation on the connection, reconnecting if it fails.
+    fn poll_op<T>(
+        &mut self,
+        cx: &mut StdContext<'_>,
+        mut op: impl FnMut(Pin<&mut C::Io>, &mut StdContext<'_>) -> Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        loop {
+            ready!(self.poll_connected(cx))?;
+
+            let State::Connected(io) = &mut self.state else {
+                unreachable!("connection was established");
+            };
+
+            match ready!(op(Pin::new(io), cx)) {
+                Ok(output) => return Poll::Ready(Ok(output)),
+                Err(err) => self.reconnect(err)?,
+            }
+        }
+    }
+}
+
+impl<C: Connector> Sink for ResumableIo<C> {
+    type Error = io::Error;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_ready(cx))
+    }
+
+    fn start_send<Item: Serialize>(
+        mut self: Pin<&mut Self>,
+        item: Item,
+    ) -> Result<(), Self::Error> {
+        let payload =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let seq = self.send_seq;
+        self.send_seq += 1;
+        self.unacked.push_back((seq, payload.clone()));
+
+        let ack = self.recv_seq;
+        let State::Connected(io) = &mut self.state else {
+            // The message is replayed after reconnecting.
+            return Ok(());
+        };
+
+        if let Err(err) = Pin::new(io).start_send(Packet::Data { seq, ack, payload }) {
+            self.reconnect(err)?;
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_flush(cx))
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_close(cx))
+    }
+}
+
+impl<C: Connector> Stream for ResumableIo<C> {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = &mut *self;
+
+        loop {
+            let packet = this.poll_op(cx, |io, cx| match ready!(io.poll_next::<Packet>(cx)) {
+                Some(packet) => Poll::Ready(packet),
+                None => Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into())),
+            });
+
+            let (seq, ack, payload) = match ready!(packet) {
+                Ok(Packet::Data { seq, ack, payload }) => (seq, ack, payload),
+                Ok(Packet::Resume { .. }) => {
+                    return Poll::Ready(Some(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected resume packet",
+                    ))))
+                }
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            };
+
+            this.acknowledge(ack);
+
+            if seq < this.recv_seq {
+                // Duplicate of a message which was already received.
+                continue;
+            } else if seq > this.recv_seq {
+                return Poll::Ready(Some(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected message {}, got {seq}", this.recv_seq),
+                ))));
+            }
+
+            this.recv_seq += 1;
+
+            return Poll::Ready(Some(
+                bincode::deserialize(&payload)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use futures::{channel::mpsc, executor::block_on, StreamExt};
+    use serio::channel::{duplex, MemoryDuplex};
+
+    use super::*;
+
+    /// A connection which can be broken.
+    struct Flaky {
+        io: MemoryDuplex,
+        broken: Arc<AtomicBool>,
+    }
+
+    impl Flaky {
+        fn check(&self) -> io::Result<()> {
+            if self.broken.load(Ordering::Relaxed) {
+                Err(io::ErrorKind::ConnectionReset.into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Sink for Flaky {
+        type Error = io::Error;
+
+        fn poll_ready(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.check()?;
+            Pin::new(&mut self.io).poll_ready(cx)
+        }
+
+        fn start_send<Item: Serialize>(
+            mut self: Pin<&mut Self>,
+            item: Item,
+        ) -> Result<(), Self::Error> {
+            self.check()?;
+            Pin::new(&mut self.io).start_send(item)
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.check()?;
+            Pin::new(&mut self.io).poll_flush(cx)
+        }
+
+        fn poll_close(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Pin::new(&mut self.io).poll_close(cx)
+        }
+    }
+
+    impl Stream for Flaky {
+        type Error = io::Error;
+
+        fn poll_next<Item: Deserialize>(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Option<Result<Item, Self::Error>>> {
+            self.check()?;
+            Pin::new(&mut self.io).poll_next(cx)
+        }
+    }
+
+    fn flaky_pair(broken: &Arc<AtomicBool>) -> (Flaky, Flaky) {
+        let (io_0, io_1) = duplex(8);
+
+        (
+            Flaky {
+                io: io_0,
+                broken: broken.clone(),
+            },
+            Flaky {
+                io: io_1,
+                broken: broken.clone(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_resumable_io() {
+        let broken = Arc::new(AtomicBool::new(false));
+        let (io_0, io_1) = flaky_pair(&broken);
+
+        // New connections are handed from the connector of party 0 to party 1.
+        let (conn_tx, conn_rx) = mpsc::unbounded();
+        let conn_rx = Arc::new(std::sync::Mutex::new(conn_rx));
+
+        let connector_0 = move || {
+            let (io_0, io_1) = flaky_pair(&Arc::new(AtomicBool::new(false)));
+            conn_tx.unbounded_send(io_1).unwrap();
+            async move { Ok::<_, io::Error>(io_0) }
+        };
+        let connector_1 = move || {
+            let conn_rx = conn_rx.clone();
+            futures::future::poll_fn(move |cx| {
+                conn_rx
+                    .lock()
+                    .unwrap()
+                    .poll_next_unpin(cx)
+                    .map(|io| io.ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected)))
+            })
+        };
+
+        let mut io_0 = ResumableIo::new(io_0, connector_0, RecoveryConfig::default());
+        let mut io_1 = ResumableIo::new(io_1, connector_1, RecoveryConfig::default());
+
+        block_on(async {
+            io_0.send(0u8).await.unwrap();
+            assert_eq!(io_1.expect_next::<u8>().await.unwrap(), 0);
+
+            // The second message is lost when the connection breaks.
+            io_0.send(1u8).await.unwrap();
+            broken.store(true, Ordering::Relaxed);
+
+            let ((), received) = futures::join!(
+                async {
+                    io_0.send(2u8).await.unwrap();
+                },
+                async {
+                    (
+                        io_1.expect_next::<u8>().await.unwrap(),
+                        io_1.expect_next::<u8>().await.unwrap(),
+                    )
+                }
+            );
+            assert_eq!(received, (1, 2));
+
+            // Acknowledgements are piggybacked on messages from the peer.
+            io_1.send(3u8).await.unwrap();
+            assert_eq!(io_0.expect_next::<u8>().await.unwrap(), 3);
+            assert_eq!(io_0.unacked(), 0);
+        });
+    }
+}
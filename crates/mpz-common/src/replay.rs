@@ -0,0 +1,448 @@
+//! Deterministic replay of recorded sessions.
+//!
+//! A [`Recorder`] captures everything one party needs to rerun a session offline: the seed of its
+//! randomness, and the bytes it read from and wrote to each channel. Protocols running under a
+//! recorder draw their randomness from [`Recorder::prg`], and their byte streams are wrapped with
+//! [`Recorder::record`], or with a [`RecordingMux`] when using a multiplexer. Recording happens
+//! below the framing layer, so the peer does not need to cooperate.
+//!
+//! A [`Recording`] can be serialized and replayed later against the unmodified protocol
+//! implementation. [`Recording::replay`] serves the recorded inbound bytes of a channel in order
+//! and checks the bytes written against the recorded outbound bytes. A replay which is not
+//! faithful to the recording, eg. because a bug was fixed, fails with an error at the first
+//! diverging byte. This makes it possible to bisect a protocol bug observed in production.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as StdContext, Poll},
+};
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite};
+use mpz_core::{prg::Prg, Block};
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+use uid_mux::UidMux;
+
+/// The bytes read from and written to a channel.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct Channel {
+    inbound: Vec<u8>,
+    outbound: Vec<u8>,
+}
+
+/// The channels of a session, keyed by id.
+type Channels = BTreeMap<Vec<u8>, Arc<Mutex<Channel>>>;
+
+/// A recorded session of one party.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    seed: Block,
+    channels: BTreeMap<Vec<u8>, Channel>,
+}
+
+impl Recording {
+    /// Returns the seed of the party's randomness.
+    pub fn seed(&self) -> Block {
+        self.seed
+    }
+
+    /// Returns a PRG which outputs the same randomness as [`Recorder::prg`] did.
+    pub fn prg(&self) -> Prg {
+        Prg::from_seed(self.seed)
+    }
+
+    /// Returns the ids of the recorded channels.
+    pub fn channels(&self) -> impl Iterator<Item = &[u8]> {
+        self.channels.keys().map(|id| id.as_slice())
+    }
+
+    /// Returns a byte stream which replays a channel.
+    ///
+    /// A channel which was not recorded is replayed as a channel which was closed immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the channel.
+    pub fn replay(&self, id: &[u8]) -> ReplayStream {
+        let channel = self.channels.get(id).cloned().unwrap_or_default();
+
+        ReplayStream {
+            channel,
+            read: 0,
+            written: 0,
+        }
+    }
+
+    /// Serializes the recording.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("recording is serializable")
+    }
+
+    /// Deserializes a recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized recording.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Records the session of one party.
+///
+/// Cloning the recorder returns a handle to the same recording.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    seed: Block,
+    channels: Arc<Mutex<Channels>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// Creates a new recorder with a random seed.
+    pub fn new() -> Self {
+        Self::new_with_seed(Prg::new().random_block())
+    }
+
+    /// Creates a new recorder with the provided seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the party's randomness.
+    pub fn new_with_seed(seed: Block) -> Self {
+        Self {
+            seed,
+            channels: Default::default(),
+        }
+    }
+
+    /// Returns the seed of the party's randomness.
+    pub fn seed(&self) -> Block {
+        self.seed
+    }
+
+    /// Returns a PRG seeded with the recorded seed.
+    ///
+    /// The party must draw all of its randomness from this PRG for the session to be replayed
+    /// deterministically.
+    pub fn prg(&self) -> Prg {
+        Prg::from_seed(self.seed)
+    }
+
+    /// Wraps a byte stream, recording it as the channel with the provided id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the channel, which must be unique.
+    /// * `stream` - The byte stream.
+    pub fn record<S>(&self, id: &[u8], stream: S) -> RecordingStream<S> {
+        let channel = self
+            .channels
+            .lock()
+            .unwrap()
+            .entry(id.to_vec())
+            .or_default()
+            .clone();
+
+        RecordingStream { stream, channel }
+    }
+
+    /// Returns a snapshot of the recording.
+    ///
+    /// This can be called at any time, eg. after the session failed.
+    pub fn recording(&self) -> Recording {
+        let channels = self
+            .channels
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, channel)| (id.clone(), channel.lock().unwrap().clone()))
+            .collect();
+
+        Recording {
+            seed: self.seed,
+            channels,
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A byte stream which is recorded.
+    #[derive(Debug)]
+    pub struct RecordingStream<S> {
+        #[pin]
+        stream: S,
+        channel: Arc<Mutex<Channel>>,
+    }
+}
+
+impl<S> RecordingStream<S> {
+    /// Returns the inner byte stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for RecordingStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let read = this.stream.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &read {
+            this.channel
+                .lock()
+                .unwrap()
+                .inbound
+                .extend_from_slice(&buf[..*n]);
+        }
+        read
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for RecordingStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let written = this.stream.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &written {
+            this.channel
+                .lock()
+                .unwrap()
+                .outbound
+                .extend_from_slice(&buf[..*n]);
+        }
+        written
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        self.project().stream.poll_close(cx)
+    }
+}
+
+/// A byte stream which replays a recorded channel.
+///
+/// Reads return the recorded inbound bytes, followed by the end of the stream. Writes are checked
+/// against the recorded outbound bytes, and fail if they diverge from them.
+#[derive(Debug)]
+pub struct ReplayStream {
+    channel: Channel,
+    read: usize,
+    written: usize,
+}
+
+impl ReplayStream {
+    /// Returns `true` if all recorded inbound bytes have been read and all recorded outbound bytes
+    /// have been written.
+    pub fn is_finished(&self) -> bool {
+        self.read == self.channel.inbound.len() && self.written == self.channel.outbound.len()
+    }
+}
+
+impl AsyncRead for ReplayStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut StdContext<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let remaining = &this.channel.inbound[this.read..];
+        let n = remaining.len().min(buf.len());
+        buf[..n].copy_from_slice(&remaining[..n]);
+        this.read += n;
+
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncWrite for ReplayStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut StdContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let expected = &this.channel.outbound[this.written..];
+
+        if let Some(offset) = buf
+            .iter()
+            .zip(expected)
+            .position(|(actual, expected)| actual != expected)
+            .or_else(|| (buf.len() > expected.len()).then_some(expected.len()))
+        {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "replay diverged from the recording at outbound byte {}",
+                    this.written + offset
+                ),
+            )));
+        }
+
+        this.written += buf.len();
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A multiplexer which records every channel it opens.
+#[derive(Debug, Clone)]
+pub struct RecordingMux<M> {
+    mux: M,
+    recorder: Recorder,
+}
+
+impl<M> RecordingMux<M> {
+    /// Creates a new recording multiplexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux` - The multiplexer.
+    /// * `recorder` - The recorder.
+    pub fn new(mux: M, recorder: Recorder) -> Self {
+        Self { mux, recorder }
+    }
+
+    /// Returns the recorder.
+    pub fn recorder(&self) -> &Recorder {
+        &self.recorder
+    }
+}
+
+#[async_trait]
+impl<Id, M> UidMux<Id> for RecordingMux<M>
+where
+    Id: AsRef<[u8]> + Sync,
+    M: UidMux<Id> + Sync,
+{
+    type Stream = RecordingStream<M::Stream>;
+    type Error = M::Error;
+
+    async fn open(&self, id: &Id) -> Result<Self::Stream, Self::Error> {
+        let stream = self.mux.open(id).await?;
+
+        Ok(self.recorder.record(id.as_ref(), stream))
+    }
+}
+
+/// A multiplexer which replays the channels of a recording.
+#[derive(Debug, Clone)]
+pub struct ReplayMux {
+    recording: Arc<Recording>,
+}
+
+impl ReplayMux {
+    /// Creates a new replay multiplexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `recording` - The recording.
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording: Arc::new(recording),
+        }
+    }
+
+    /// Returns the recording.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+}
+
+#[async_trait]
+impl<Id> UidMux<Id> for ReplayMux
+where
+    Id: AsRef<[u8]> + Sync,
+{
+    type Stream = ReplayStream;
+    type Error = io::Error;
+
+    async fn open(&self, id: &Id) -> Result<Self::Stream, Self::Error> {
+        Ok(self.recording.replay(id.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{AsyncReadExt, AsyncWriteExt};
+    use tokio_util::compat::TokioAsyncReadCompatExt;
+
+    use super::*;
+
+    /// A party which sends a random nonce and returns the XOR of its nonce and the peer's.
+    async fn party<S: AsyncRead + AsyncWrite + Unpin>(
+        mut prg: Prg,
+        stream: &mut S,
+    ) -> io::Result<[u8; 16]> {
+        let nonce = prg.random_block().to_bytes();
+        stream.write_all(&nonce).await?;
+
+        let mut peer_nonce = [0u8; 16];
+        stream.read_exact(&mut peer_nonce).await?;
+
+        Ok(std::array::from_fn(|i| nonce[i] ^ peer_nonce[i]))
+    }
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        let (io_0, io_1) = tokio::io::duplex(64);
+        let recorder = Recorder::new();
+        let mut io_0 = recorder.record(b"main", io_0.compat());
+        let mut io_1 = io_1.compat();
+
+        let (output, _) = futures::try_join!(
+            party(recorder.prg(), &mut io_0),
+            party(Prg::new(), &mut io_1)
+        )
+        .unwrap();
+
+        let recording = Recording::from_bytes(&recorder.recording().to_bytes()).unwrap();
+        assert_eq!(recording, recorder.recording());
+        assert_eq!(
+            recording.channels().collect::<Vec<_>>(),
+            vec![b"main".as_slice()]
+        );
+
+        // The replay reproduces the output offline.
+        let mut replay = recording.replay(b"main");
+        assert_eq!(party(recording.prg(), &mut replay).await.unwrap(), output);
+        assert!(replay.is_finished());
+
+        // A party which deviates from the recording is detected.
+        let err = party(Prg::new(), &mut recording.replay(b"main"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Reading past the recording ends the stream.
+        let mut replay = recording.replay(b"unknown");
+        let err = replay.read_exact(&mut [0u8; 1]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
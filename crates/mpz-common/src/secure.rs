@@ -0,0 +1,325 @@
+//! Authenticated encryption of protocol messages.
+//!
+//! Protocol messages are sent in the clear unless the transport provides confidentiality, eg.
+//! TLS. [`handshake`] performs a Noise-style key exchange over an I/O channel and returns a
+//! [`SecureIo`], which encrypts every message with ChaCha20-Poly1305. The secure channel is
+//! enabled with the `secure` feature.
+//!
+//! # Handshake
+//!
+//! Both parties send an ephemeral Ristretto public key, and derive a key for each direction from
+//! the Diffie-Hellman secret, both public keys and an optional pre-shared key. The parties then
+//! exchange an encrypted confirmation message, so that the handshake fails if the keys differ.
+//!
+//! Without a pre-shared key the ephemeral keys are not authenticated, so the channel only protects
+//! against passive attackers. With a pre-shared key, an active attacker can not derive the keys
+//! without knowing the pre-shared key.
+//!
+//! # Framing
+//!
+//! Every message is serialized and encrypted into one frame. The nonce of a frame is the number
+//! of frames which were sent before it in the same direction, so frames which are dropped,
+//! reordered or replayed fail to decrypt.
+//!
+//! # Example
+//!
+//! ```
+//! # futures::executor::block_on(async {
+//! use mpz_common::{executor::STExecutor, secure::handshake};
+//! use serio::channel::duplex;
+//!
+//! let (io_0, io_1) = duplex(8);
+//! let (io_0, io_1) = futures::try_join!(handshake(io_0, None), handshake(io_1, None)).unwrap();
+//!
+//! let ctx_0 = STExecutor::new(io_0);
+//! let ctx_1 = STExecutor::new(io_1);
+//! # });
+//! ```
+
+use std::{
+    fmt, io,
+    pin::Pin,
+    task::{ready, Context as StdContext, Poll},
+};
+
+use chacha20poly1305::{aead::Aead, ChaCha20Poly1305, Key, KeyInit, Nonce};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_TABLE, ristretto::CompressedRistretto, scalar::Scalar,
+    traits::IsIdentity,
+};
+use rand::thread_rng;
+use serio::{stream::IoStreamExt, Deserialize, IoDuplex, Serialize, Sink, SinkExt, Stream};
+
+/// The context of the key derivation.
+const KDF_CONTEXT: &str = "mpz-common secure channel v1 key derivation";
+/// The message which is exchanged to confirm the keys.
+const CONFIRMATION: &[u8] = b"mpz-common secure channel confirmation";
+
+/// A secure channel error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum SecureChannelError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid public key received from the peer")]
+    InvalidPublicKey,
+    #[error("key confirmation failed, the parties may be using different pre-shared keys")]
+    KeyConfirmation,
+}
+
+/// Performs a handshake with the peer, returning a secure channel.
+///
+/// # Arguments
+///
+/// * `io` - The I/O channel.
+/// * `psk` - The pre-shared key, which must be the same for both parties.
+pub async fn handshake<Io: IoDuplex + Unpin>(
+    mut io: Io,
+    psk: Option<[u8; 32]>,
+) -> Result<SecureIo<Io>, SecureChannelError> {
+    let secret = Scalar::random(&mut thread_rng());
+    let public = (&secret * RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+
+    io.send(public).await?;
+    let peer_public: [u8; 32] = io.expect_next().await?;
+
+    // A peer which reflects our public key would receive the keys of our direction.
+    if peer_public == public {
+        return Err(SecureChannelError::InvalidPublicKey);
+    }
+
+    let peer_point = CompressedRistretto(peer_public)
+        .decompress()
+        .filter(|point| !point.is_identity())
+        .ok_or(SecureChannelError::InvalidPublicKey)?;
+    let shared = (secret * peer_point).compress().to_bytes();
+
+    // The public keys are ordered, so that both parties derive the same keys.
+    let is_first = public < peer_public;
+    let (first, second) = if is_first {
+        (public, peer_public)
+    } else {
+        (peer_public, public)
+    };
+
+    let mut kdf = blake3::Hasher::new_derive_key(KDF_CONTEXT);
+    kdf.update(&first);
+    kdf.update(&second);
+    kdf.update(&shared);
+    kdf.update(&[psk.is_some() as u8]);
+    kdf.update(&psk.unwrap_or_default());
+    let master: [u8; 32] = kdf.finalize().into();
+
+    let first_key: [u8; 32] = blake3::keyed_hash(&master, b"first").into();
+    let second_key: [u8; 32] = blake3::keyed_hash(&master, b"second").into();
+    let (send_key, recv_key) = if is_first {
+        (first_key, second_key)
+    } else {
+        (second_key, first_key)
+    };
+
+    let mut io = SecureIo::new(io, send_key, recv_key);
+
+    io.send(CONFIRMATION.to_vec()).await?;
+    let confirmation: Vec<u8> = io.expect_next().await.map_err(|err| {
+        if err.kind() == io::ErrorKind::InvalidData {
+            SecureChannelError::KeyConfirmation
+        } else {
+            SecureChannelError::Io(err)
+        }
+    })?;
+
+    if confirmation != CONFIRMATION {
+        return Err(SecureChannelError::KeyConfirmation);
+    }
+
+    Ok(io)
+}
+
+/// The cipher of one direction of a secure channel.
+struct Cipher {
+    aead: ChaCha20Poly1305,
+    counter: u64,
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cipher")
+            .field("counter", &self.counter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Cipher {
+    fn new(key: [u8; 32]) -> Self {
+        Self {
+            aead: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            counter: 0,
+        }
+    }
+
+    /// Returns the nonce of the next frame.
+    fn next_nonce(&mut self) -> io::Result<Nonce> {
+        let counter = self.counter;
+        self.counter = counter
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("secure channel nonces are exhausted"))?;
+
+        let mut nonce = Nonce::default();
+        nonce[4..].copy_from_slice(&counter.to_le_bytes());
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let nonce = self.next_nonce()?;
+        self.aead.encrypt(&nonce, plaintext).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "message could not be encrypted",
+            )
+        })
+    }
+
+    fn open(&mut self, ciphertext: &[u8]) -> io::Result<Vec<u8>> {
+        let nonce = self.next_nonce()?;
+        self.aead.decrypt(&nonce, ciphertext).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "message authentication failed")
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// An I/O channel which encrypts and authenticates messages.
+    ///
+    /// A secure channel is established with [`handshake`].
+    #[derive(Debug)]
+    pub struct SecureIo<Io> {
+        #[pin]
+        io: Io,
+        send: Cipher,
+        recv: Cipher,
+    }
+}
+
+impl<Io> SecureIo<Io> {
+    fn new(io: Io, send_key: [u8; 32], recv_key: [u8; 32]) -> Self {
+        Self {
+            io,
+            send: Cipher::new(send_key),
+            recv: Cipher::new(recv_key),
+        }
+    }
+}
+
+impl<Io: Sink<Error = io::Error>> Sink for SecureIo<Io> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_ready(cx)
+    }
+
+    fn start_send<Item: Serialize>(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        let this = self.project();
+
+        let data =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        this.io.start_send(this.send.seal(&data)?)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_close(cx)
+    }
+}
+
+impl<Io: Stream<Error = io::Error>> Stream for SecureIo<Io> {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = self.project();
+
+        let Some(frame) = ready!(this.io.poll_next::<Vec<u8>>(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        let item = frame.and_then(|frame| {
+            let data = this.recv.open(&frame)?;
+            bincode::deserialize(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+
+        Poll::Ready(Some(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serio::channel::duplex;
+
+    use super::*;
+
+    #[test]
+    fn test_secure_io() {
+        for psk in [None, Some([42u8; 32])] {
+            let (io_0, io_1) = duplex(8);
+
+            block_on(async {
+                let (mut io_0, mut io_1) =
+                    futures::try_join!(handshake(io_0, psk), handshake(io_1, psk)).unwrap();
+
+                io_0.send(vec![1u8; 64]).await.unwrap();
+                io_1.send(7u32).await.unwrap();
+
+                assert_eq!(io_1.expect_next::<Vec<u8>>().await.unwrap(), vec![1u8; 64]);
+                assert_eq!(io_0.expect_next::<u32>().await.unwrap(), 7);
+            });
+        }
+    }
+
+    #[test]
+    fn test_secure_io_psk_mismatch() {
+        let (io_0, io_1) = duplex(8);
+
+        let (result_0, result_1) = block_on(async {
+            futures::join!(
+                handshake(io_0, Some([1u8; 32])),
+                handshake(io_1, Some([2u8; 32]))
+            )
+        });
+
+        assert!(matches!(
+            result_0.unwrap_err(),
+            SecureChannelError::KeyConfirmation
+        ));
+        assert!(matches!(
+            result_1.unwrap_err(),
+            SecureChannelError::KeyConfirmation
+        ));
+    }
+
+    #[test]
+    fn test_cipher_rejects_tampering_and_replay() {
+        let mut sender = Cipher::new([3u8; 32]);
+        let mut receiver = Cipher::new([3u8; 32]);
+
+        let frame = sender.seal(b"message").unwrap();
+
+        let mut tampered = frame.clone();
+        tampered[0] ^= 1;
+        assert!(receiver.open(&tampered).is_err());
+
+        // The nonce of the receiver advanced, so the original frame is rejected as well.
+        assert!(receiver.open(&frame).is_err());
+
+        let mut receiver = Cipher::new([3u8; 32]);
+        assert_eq!(receiver.open(&frame).unwrap(), b"message");
+        assert!(receiver.open(&frame).is_err());
+    }
+}
@@ -3,10 +3,12 @@
 mod async_mutex;
 mod async_syncer;
 mod mutex;
+mod ordered_mutex;
 
 pub use async_mutex::AsyncMutex;
 pub use async_syncer::AsyncSyncer;
 pub use mutex::{Mutex, MutexError};
+pub use ordered_mutex::{OrderedMutex, OrderedMutexGuard, Reservation};
 
 use std::{
     collections::HashMap,
@@ -0,0 +1,242 @@
+//! Ticket ordered async mutex.
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::poll_fn,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex as StdMutex},
+    task::{Poll, Waker},
+};
+
+use tokio::sync::{Mutex as TokioMutex, MutexGuard};
+
+use crate::sync::Ticket;
+
+/// A mutex which grants the lock in the order in which tickets were reserved.
+///
+/// Unlike [`AsyncMutex`](crate::sync::AsyncMutex), this mutex does not communicate with the other
+/// parties. Instead, a [`Reservation`] is taken for every lock before the tasks which use it are
+/// spawned, and the lock is granted in the order of the reservations regardless of the order in
+/// which the tasks are polled.
+///
+/// As long as every party reserves the tickets in the same order, e.g. in the same sequential
+/// code, all parties acquire the protected resource in the same order. This prevents tasks which
+/// race for a shared protocol object, e.g. a shared OT sender, from performing their transfers
+/// in a different order than the other party.
+///
+/// A reservation which is dropped without being used is skipped.
+#[derive(Debug)]
+pub struct OrderedMutex<T> {
+    inner: TokioMutex<T>,
+    turns: Arc<StdMutex<Turns>>,
+}
+
+impl<T> OrderedMutex<T> {
+    /// Creates a new mutex.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value protected by the mutex.
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: TokioMutex::new(value),
+            turns: Arc::new(StdMutex::new(Turns::default())),
+        }
+    }
+
+    /// Reserves the next ticket for a lock on the mutex.
+    pub fn reserve(&self) -> Reservation {
+        let ticket = self.turns.lock().unwrap().next.increment_in_place();
+
+        Reservation {
+            ticket,
+            turns: self.turns.clone(),
+            used: false,
+        }
+    }
+
+    /// Returns a lock on the mutex once it is the turn of the reservation.
+    ///
+    /// # Arguments
+    ///
+    /// * `reservation` - The reservation of the lock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the reservation was made with a different mutex.
+    pub async fn lock(&self, mut reservation: Reservation) -> OrderedMutexGuard<'_, T> {
+        assert!(
+            Arc::ptr_eq(&self.turns, &reservation.turns),
+            "reservation was made with a different mutex"
+        );
+
+        poll_fn(|cx| {
+            let mut turns = self.turns.lock().unwrap();
+            if turns.turn == reservation.ticket {
+                Poll::Ready(())
+            } else {
+                turns.waiting.insert(reservation.ticket, cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await;
+
+        // The previous guard may still be releasing the inner lock.
+        let guard = self.inner.lock().await;
+        reservation.used = true;
+
+        OrderedMutexGuard {
+            guard,
+            turns: &self.turns,
+        }
+    }
+
+    /// Returns the inner value, consuming the mutex.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+/// A reservation of a lock on an [`OrderedMutex`].
+#[derive(Debug)]
+#[must_use = "a reservation which is dropped is skipped"]
+pub struct Reservation {
+    ticket: Ticket,
+    turns: Arc<StdMutex<Turns>>,
+    used: bool,
+}
+
+impl Reservation {
+    /// Returns the ticket of the reservation.
+    pub fn ticket(&self) -> Ticket {
+        self.ticket
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if !self.used {
+            self.turns.lock().unwrap().release(self.ticket);
+        }
+    }
+}
+
+/// A lock on an [`OrderedMutex`], which passes the turn to the next reservation when dropped.
+#[derive(Debug)]
+pub struct OrderedMutexGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    turns: &'a StdMutex<Turns>,
+}
+
+impl<T> Deref for OrderedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OrderedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for OrderedMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.turns.lock().unwrap().advance();
+    }
+}
+
+#[derive(Debug, Default)]
+struct Turns {
+    // The next ticket to reserve.
+    next: Ticket,
+    // The ticket whose turn it is.
+    turn: Ticket,
+    // Tickets which were released before their turn.
+    skipped: HashSet<Ticket>,
+    // Tasks waiting for their turn.
+    waiting: HashMap<Ticket, Waker>,
+}
+
+impl Turns {
+    // Releases a ticket which was not used.
+    fn release(&mut self, ticket: Ticket) {
+        self.waiting.remove(&ticket);
+        if ticket == self.turn {
+            self.advance();
+        } else {
+            self.skipped.insert(ticket);
+        }
+    }
+
+    // Passes the turn to the next ticket which was not skipped, and wakes its task.
+    fn advance(&mut self) {
+        self.turn.increment_in_place();
+        while self.skipped.remove(&self.turn) {
+            self.turn.increment_in_place();
+        }
+
+        if let Some(waker) = self.waiting.remove(&self.turn) {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, poll};
+
+    use super::*;
+
+    #[test]
+    fn test_ordered_mutex() {
+        let mutex = OrderedMutex::new(Vec::new());
+
+        let reservations: Vec<_> = (0..3).map(|_| mutex.reserve()).collect();
+        let mut futs: Vec<_> = reservations
+            .into_iter()
+            .enumerate()
+            .map(|(i, reservation)| {
+                let mutex = &mutex;
+                Box::pin(async move { mutex.lock(reservation).await.push(i) })
+            })
+            .collect();
+
+        block_on(async {
+            // Poll out of order.
+            assert!(poll!(&mut futs[2]).is_pending());
+            assert!(poll!(&mut futs[1]).is_pending());
+            assert!(poll!(&mut futs[0]).is_ready());
+            assert!(poll!(&mut futs[2]).is_pending());
+            assert!(poll!(&mut futs[1]).is_ready());
+            assert!(poll!(&mut futs[2]).is_ready());
+        });
+        drop(futs);
+
+        assert_eq!(mutex.into_inner(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_ordered_mutex_skip_dropped() {
+        let mutex = OrderedMutex::new(Vec::new());
+
+        let a = mutex.reserve();
+        let b = mutex.reserve();
+        let c = mutex.reserve();
+
+        let mut fut_c = Box::pin(async { mutex.lock(c).await.push(2) });
+
+        block_on(async {
+            assert!(poll!(&mut fut_c).is_pending());
+            drop(b);
+            assert!(poll!(&mut fut_c).is_pending());
+            mutex.lock(a).await.push(0);
+            assert!(poll!(&mut fut_c).is_ready());
+        });
+        drop(fut_c);
+
+        assert_eq!(mutex.into_inner(), vec![0, 2]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Deadlines and timeouts.
+//!
+//! A stalled peer would otherwise cause protocol calls to hang forever inside an `.await`.
+//! [`TimeoutIo`] wraps an I/O channel and fails pending operations with a [`TimeoutError`] when
+//! either the per-operation timeout or the session deadline is exceeded. [`TimeoutMux`] applies
+//! the same to every channel opened by a multiplexer, so that it can be used with the
+//! [`MTExecutor`](crate::executor::MTExecutor).
+//!
+//! The error is surfaced as an [`std::io::Error`] of kind [`TimedOut`](std::io::ErrorKind::TimedOut)
+//! by the protocols, and can be recovered with [`TimeoutError::from_io`].
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context as StdContext, Poll},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use futures::future::{select, Either};
+use futures_timer::Delay;
+use serio::{Deserialize, IoSink, IoStream, Serialize, Sink, Stream};
+use uid_mux::FramedUidMux;
+
+/// An error which occurs when a deadline is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum TimeoutError {
+    #[error("operation timed out after {0:?}")]
+    Operation(Duration),
+    #[error("session deadline exceeded")]
+    Session,
+}
+
+impl TimeoutError {
+    /// Returns the timeout error which caused the provided I/O error, if any.
+    pub fn from_io(err: &io::Error) -> Option<Self> {
+        err.get_ref()
+            .and_then(|err| err.downcast_ref::<TimeoutError>())
+            .copied()
+    }
+}
+
+impl From<TimeoutError> for io::Error {
+    fn from(err: TimeoutError) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
+
+/// Timeout configuration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeoutConfig {
+    operation: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl TimeoutConfig {
+    /// Creates a new configuration without any timeouts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum duration a single I/O operation can be pending.
+    pub fn operation_timeout(mut self, timeout: Duration) -> Self {
+        self.operation = Some(timeout);
+        self
+    }
+
+    /// Sets the instant after which all I/O operations fail.
+    pub fn session_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the duration, starting now, after which all I/O operations fail.
+    pub fn session_timeout(self, timeout: Duration) -> Self {
+        self.session_deadline(Instant::now() + timeout)
+    }
+
+    /// Returns the operation timeout.
+    pub fn get_operation_timeout(&self) -> Option<Duration> {
+        self.operation
+    }
+
+    /// Returns the session deadline.
+    pub fn get_session_deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+/// Executes a future, failing if it does not complete within the provided duration.
+///
+/// # Arguments
+///
+/// * `duration` - The maximum duration of the operation.
+/// * `fut` - The future to execute.
+pub async fn timeout<F>(duration: Duration, fut: F) -> Result<F::Output, TimeoutError>
+where
+    F: Future,
+{
+    futures::pin_mut!(fut);
+
+    match select(fut, Delay::new(duration)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(TimeoutError::Operation(duration)),
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// An I/O channel with deadlines.
+    #[derive(Debug)]
+    pub struct TimeoutIo<Io> {
+        #[pin]
+        io: Io,
+        operation: Option<Duration>,
+        // Armed while an operation is pending.
+        timer: Option<Delay>,
+        deadline: Option<Instant>,
+        // Wakes the task when the session deadline is exceeded.
+        session: Option<Delay>,
+    }
+}
+
+impl<Io> TimeoutIo<Io> {
+    /// Creates a new I/O channel with deadlines.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The I/O channel.
+    /// * `config` - The timeout configuration.
+    pub fn new(io: Io, config: TimeoutConfig) -> Self {
+        Self {
+            io,
+            operation: config.operation,
+            timer: None,
+            deadline: config.deadline,
+            session: config
+                .deadline
+                .map(|deadline| Delay::new(deadline.saturating_duration_since(Instant::now()))),
+        }
+    }
+
+    /// Returns the inner I/O channel.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+
+    /// Polls an operation on the inner I/O channel, failing if a deadline is exceeded.
+    fn poll_op<T>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        op: impl FnOnce(Pin<&mut Io>, &mut StdContext<'_>) -> Poll<Result<T, io::Error>>,
+    ) -> Poll<Result<T, io::Error>> {
+        let this = self.project();
+
+        if let Some(session) = this.session.as_mut() {
+            let expired = this
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline);
+            if expired || Pin::new(session).poll(cx).is_ready() {
+                return Poll::Ready(Err(TimeoutError::Session.into()));
+            }
+        }
+
+        if let Poll::Ready(output) = op(this.io, cx) {
+            *this.timer = None;
+            return Poll::Ready(output);
+        }
+
+        if let Some(duration) = *this.operation {
+            let timer = this.timer.get_or_insert_with(|| Delay::new(duration));
+            if Pin::new(timer).poll(cx).is_ready() {
+                *this.timer = None;
+                return Poll::Ready(Err(TimeoutError::Operation(duration).into()));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Io: IoSink> Sink for TimeoutIo<Io> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_ready(cx))
+    }
+
+    fn start_send<Item: Serialize>(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        self.project().io.start_send(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_flush(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_op(cx, |io, cx| io.poll_close(cx))
+    }
+}
+
+impl<Io: IoStream> Stream for TimeoutIo<Io> {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        self.poll_op(cx, |io, cx| match io.poll_next::<Item>(cx) {
+            Poll::Ready(Some(item)) => Poll::Ready(item.map(Some)),
+            Poll::Ready(None) => Poll::Ready(Ok(None)),
+            Poll::Pending => Poll::Pending,
+        })
+        .map(Result::transpose)
+    }
+}
+
+/// A multiplexer which applies deadlines to every channel it opens.
+///
+/// All channels share the same session deadline.
+#[derive(Debug, Clone)]
+pub struct TimeoutMux<M> {
+    mux: M,
+    config: TimeoutConfig,
+}
+
+impl<M> TimeoutMux<M> {
+    /// Creates a new multiplexer with deadlines.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux` - The multiplexer.
+    /// * `config` - The timeout configuration.
+    pub fn new(mux: M, config: TimeoutConfig) -> Self {
+        Self { mux, config }
+    }
+
+    /// Returns the timeout configuration.
+    pub fn config(&self) -> &TimeoutConfig {
+        &self.config
+    }
+}
+
+#[async_trait]
+impl<Id, M> FramedUidMux<Id> for TimeoutMux<M>
+where
+    Id: Sync,
+    M: FramedUidMux<Id> + Sync,
+{
+    type Framed = TimeoutIo<M::Framed>;
+    type Error = M::Error;
+
+    async fn open_framed(&self, id: &Id) -> Result<Self::Framed, Self::Error> {
+        let io = self.mux.open_framed(id).await?;
+
+        Ok(TimeoutIo::new(io, self.config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, future::pending};
+    use serio::{channel::duplex, stream::IoStreamExt, SinkExt};
+
+    use super::*;
+
+    #[test]
+    fn test_timeout() {
+        let duration = Duration::from_millis(10);
+
+        assert_eq!(block_on(timeout(duration, async { 1u8 })), Ok(1));
+        assert_eq!(
+            block_on(timeout(duration, pending::<()>())),
+            Err(TimeoutError::Operation(duration))
+        );
+    }
+
+    #[test]
+    fn test_timeout_io_operation() {
+        let duration = Duration::from_millis(10);
+        let (io_0, io_1) = duplex(1);
+        let mut io_0 = TimeoutIo::new(io_0, TimeoutConfig::new().operation_timeout(duration));
+        let mut io_1 = TimeoutIo::new(io_1, TimeoutConfig::new().operation_timeout(duration));
+
+        block_on(async {
+            io_0.send(1u8).await.unwrap();
+            assert_eq!(io_1.expect_next::<u8>().await.unwrap(), 1);
+
+            // The peer is stalled.
+            let err = io_1.expect_next::<u8>().await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+            assert_eq!(
+                TimeoutError::from_io(&err),
+                Some(TimeoutError::Operation(duration))
+            );
+
+            // The timer is reset after an operation completes.
+            io_0.send(2u8).await.unwrap();
+            assert_eq!(io_1.expect_next::<u8>().await.unwrap(), 2);
+        });
+    }
+
+    #[test]
+    fn test_timeout_io_session() {
+        let (io_0, _io_1) = duplex(1);
+        let mut io_0 = TimeoutIo::new(io_0, TimeoutConfig::new().session_deadline(Instant::now()));
+
+        let err = block_on(io_0.send(1u8)).unwrap_err();
+        assert_eq!(TimeoutError::from_io(&err), Some(TimeoutError::Session));
+    }
+}
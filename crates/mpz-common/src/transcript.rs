@@ -0,0 +1,339 @@
+//! Session transcripts.
+//!
+//! A [`Transcript`] keeps a running hash of every message sent and received on each channel of a
+//! session, eg. during base OT, OT extension, garbling and decoding. Channels are wrapped with
+//! [`TranscriptIo`], or with a [`TranscriptMux`] when using a multiplexer, in which case the
+//! channels are identified by their [`ThreadId`](crate::ThreadId).
+//!
+//! At the end of the session, [`finalize`] compares the transcripts of both parties. Every
+//! channel is hashed separately, so the comparison does not depend on how the channels were
+//! scheduled, but a message which was dropped, reordered, or substituted with a message of
+//! another channel is detected.
+//!
+//! Both parties must wrap their channels, as messages are hashed in their serialized form, and
+//! must record the same set of channels.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context as StdContext, Poll},
+};
+
+use async_trait::async_trait;
+use blake3::Hasher;
+use mpz_core::hash::Hash;
+use serio::{stream::IoStreamExt, Deserialize, Serialize, Sink, SinkExt, Stream};
+use uid_mux::FramedUidMux;
+
+use crate::Context;
+
+/// A transcript error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum TranscriptError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("transcript mismatch, the parties did not observe the same messages")]
+    Mismatch,
+}
+
+/// The running hashes of the messages of a channel.
+#[derive(Debug, Default)]
+struct ChannelHashes {
+    sent: Hasher,
+    received: Hasher,
+}
+
+/// The transcript of a channel.
+#[derive(Debug, Default, Clone)]
+pub struct ChannelTranscript(Arc<Mutex<ChannelHashes>>);
+
+impl ChannelTranscript {
+    /// Creates a new channel transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record_sent(&self, message: &[u8]) {
+        append(&mut self.0.lock().unwrap().sent, message);
+    }
+
+    fn record_received(&self, message: &[u8]) {
+        append(&mut self.0.lock().unwrap().received, message);
+    }
+
+    /// Returns the hashes of the sent and received messages.
+    fn hashes(&self) -> ([u8; 32], [u8; 32]) {
+        let hashes = self.0.lock().unwrap();
+        (
+            hashes.sent.finalize().into(),
+            hashes.received.finalize().into(),
+        )
+    }
+}
+
+/// Appends a message to a running hash.
+///
+/// Messages are prefixed with their length, so that the boundaries of messages are hashed.
+fn append(hasher: &mut Hasher, message: &[u8]) {
+    hasher.update(&(message.len() as u64).to_le_bytes());
+    hasher.update(message);
+}
+
+/// The transcript of a session, by channel id.
+#[derive(Debug, Default, Clone)]
+pub struct Transcript {
+    channels: Arc<Mutex<BTreeMap<Vec<u8>, ChannelTranscript>>>,
+}
+
+impl Transcript {
+    /// Creates a new transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the transcript of a channel, registering it if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the channel, eg. a [`ThreadId`](crate::ThreadId).
+    pub fn channel(&self, id: &[u8]) -> ChannelTranscript {
+        self.channels
+            .lock()
+            .unwrap()
+            .entry(id.to_vec())
+            .or_default()
+            .clone()
+    }
+
+    /// Returns the digest of the transcript.
+    ///
+    /// If `swap` is true, the sent and received messages are swapped, which returns the digest
+    /// the peer computes if both parties observed the same messages.
+    fn digest(&self, swap: bool) -> Hash {
+        let mut hasher = Hasher::new();
+        for (id, channel) in self.channels.lock().unwrap().iter() {
+            let (sent, received) = channel.hashes();
+            let (first, second) = if swap {
+                (received, sent)
+            } else {
+                (sent, received)
+            };
+
+            append(&mut hasher, id);
+            hasher.update(&first);
+            hasher.update(&second);
+        }
+
+        Hash::from(<[u8; 32]>::from(hasher.finalize()))
+    }
+}
+
+/// Compares the transcripts of both parties.
+///
+/// This should be called at the end of a session, once all channels are idle. The messages of
+/// the comparison are sent over the context, and must not be recorded in the transcript.
+///
+/// # Arguments
+///
+/// * `ctx` - The context.
+/// * `transcript` - The transcript of the session.
+pub async fn finalize<Ctx: Context>(
+    ctx: &mut Ctx,
+    transcript: &Transcript,
+) -> Result<(), TranscriptError> {
+    let digest = transcript.digest(false);
+    let expected = transcript.digest(true);
+
+    let io = ctx.io_mut();
+    io.send(digest).await?;
+    let peer_digest: Hash = io.expect_next().await?;
+
+    if peer_digest != expected {
+        return Err(TranscriptError::Mismatch);
+    }
+
+    Ok(())
+}
+
+pin_project_lite::pin_project! {
+    /// An I/O channel which records its messages in a transcript.
+    #[derive(Debug)]
+    pub struct TranscriptIo<Io> {
+        #[pin]
+        io: Io,
+        transcript: ChannelTranscript,
+    }
+}
+
+impl<Io> TranscriptIo<Io> {
+    /// Creates a new I/O channel which records its messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The I/O channel.
+    /// * `transcript` - The transcript of the channel.
+    pub fn new(io: Io, transcript: ChannelTranscript) -> Self {
+        Self { io, transcript }
+    }
+
+    /// Returns the inner I/O channel.
+    pub fn into_inner(self) -> Io {
+        self.io
+    }
+}
+
+impl<Io: Sink<Error = io::Error>> Sink for TranscriptIo<Io> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_ready(cx)
+    }
+
+    fn start_send<Item: Serialize>(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+        let this = self.project();
+
+        let data =
+            bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        this.transcript.record_sent(&data);
+        this.io.start_send(data)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().io.poll_close(cx)
+    }
+}
+
+impl<Io: Stream<Error = io::Error>> Stream for TranscriptIo<Io> {
+    type Error = io::Error;
+
+    fn poll_next<Item: Deserialize>(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Option<Result<Item, Self::Error>>> {
+        let this = self.project();
+
+        let Some(data) = ready!(this.io.poll_next::<Vec<u8>>(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        let item = data.and_then(|data| {
+            this.transcript.record_received(&data);
+            bincode::deserialize(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+
+        Poll::Ready(Some(item))
+    }
+}
+
+/// A multiplexer which records the messages of every channel it opens.
+#[derive(Debug, Clone)]
+pub struct TranscriptMux<M> {
+    mux: M,
+    transcript: Transcript,
+}
+
+impl<M> TranscriptMux<M> {
+    /// Creates a new multiplexer which records its channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux` - The multiplexer.
+    /// * `transcript` - The transcript to record to.
+    pub fn new(mux: M, transcript: Transcript) -> Self {
+        Self { mux, transcript }
+    }
+
+    /// Returns the transcript.
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+}
+
+#[async_trait]
+impl<Id, M> FramedUidMux<Id> for TranscriptMux<M>
+where
+    Id: AsRef<[u8]> + Sync,
+    M: FramedUidMux<Id> + Sync,
+{
+    type Framed = TranscriptIo<M::Framed>;
+    type Error = M::Error;
+
+    async fn open_framed(&self, id: &Id) -> Result<Self::Framed, Self::Error> {
+        let io = self.mux.open_framed(id).await?;
+
+        Ok(TranscriptIo::new(io, self.transcript.channel(id.as_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serio::channel::duplex;
+
+    use super::*;
+    use crate::executor::test_st_executor;
+
+    #[test]
+    fn test_transcript() {
+        let (transcript_0, transcript_1) = (Transcript::new(), Transcript::new());
+
+        let (io_0, io_1) = duplex(8);
+        let mut io_0 = TranscriptIo::new(io_0, transcript_0.channel(b"ot"));
+        let mut io_1 = TranscriptIo::new(io_1, transcript_1.channel(b"ot"));
+
+        let (mut ctx_0, mut ctx_1) = test_st_executor(8);
+
+        block_on(async {
+            io_0.send(1u8).await.unwrap();
+            io_1.send(vec![2u8; 16]).await.unwrap();
+            assert_eq!(io_1.expect_next::<u8>().await.unwrap(), 1);
+            assert_eq!(io_0.expect_next::<Vec<u8>>().await.unwrap(), vec![2u8; 16]);
+
+            futures::try_join!(
+                finalize(&mut ctx_0, &transcript_0),
+                finalize(&mut ctx_1, &transcript_1)
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn test_transcript_substitution() {
+        let (transcript_0, transcript_1) = (Transcript::new(), Transcript::new());
+
+        let (ot_io_0, ot_io_1) = duplex(8);
+        let (garble_io_0, garble_io_1) = duplex(8);
+        let mut ot_0 = TranscriptIo::new(ot_io_0, transcript_0.channel(b"ot"));
+        let mut garble_0 = TranscriptIo::new(garble_io_0, transcript_0.channel(b"garble"));
+
+        // The channels of the second party are swapped, so the messages are delivered on the
+        // wrong channels.
+        let mut ot_1 = TranscriptIo::new(garble_io_1, transcript_1.channel(b"ot"));
+        let mut garble_1 = TranscriptIo::new(ot_io_1, transcript_1.channel(b"garble"));
+
+        let (mut ctx_0, mut ctx_1) = test_st_executor(8);
+
+        let (result_0, result_1) = block_on(async {
+            ot_0.send(1u8).await.unwrap();
+            garble_0.send(2u8).await.unwrap();
+            assert_eq!(ot_1.expect_next::<u8>().await.unwrap(), 2);
+            assert_eq!(garble_1.expect_next::<u8>().await.unwrap(), 1);
+
+            futures::join!(
+                finalize(&mut ctx_0, &transcript_0),
+                finalize(&mut ctx_1, &transcript_1)
+            )
+        });
+
+        assert!(matches!(result_0.unwrap_err(), TranscriptError::Mismatch));
+        assert!(matches!(result_1.unwrap_err(), TranscriptError::Mismatch));
+    }
+}
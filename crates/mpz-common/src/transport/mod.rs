@@ -0,0 +1,8 @@
+//! Network transports.
+//!
+//! Transports provide multiplexers which can be used with the executors in [`executor`](crate::executor),
+//! eg. the [`MTExecutor`](crate::executor::MTExecutor).
+
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod websocket;
@@ -0,0 +1,351 @@
+//! QUIC transport.
+//!
+//! [`QuicMux`] maps every stream id, eg. a [`ThreadId`](crate::ThreadId), to its own bidirectional
+//! QUIC stream. Streams are flow controlled independently, so a slow subprotocol does not block
+//! the others, and all traffic is encrypted by the TLS session of the connection.
+//!
+//! Streams are always opened by the [`Role::Client`], which writes the stream id as a header. The
+//! [`Role::Server`] accepts incoming streams and routes them to the caller waiting for that id.
+//!
+//! [`QuicMux`] implements [`UidMux`], so it can be wrapped in a [`FramedMux`](uid_mux::FramedMux)
+//! with a codec and used with the [`MTExecutor`](crate::executor::MTExecutor).
+
+use std::{
+    collections::HashMap,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as StdContext, Poll},
+};
+
+use async_trait::async_trait;
+use futures::{
+    future::{select, Either},
+    pin_mut, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+use quinn::{Connection, RecvStream, SendStream};
+use tokio::sync::Notify;
+use uid_mux::UidMux;
+
+/// The maximum length of a stream id.
+pub const MAX_ID_LEN: usize = u16::MAX as usize;
+
+/// An error for [`QuicMux`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum QuicError {
+    #[error(transparent)]
+    Connection(#[from] quinn::ConnectionError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("stream id is too long: {0} bytes")]
+    IdTooLong(usize),
+    #[error("stream with id {0:?} was opened more than once")]
+    DuplicateId(Vec<u8>),
+}
+
+/// The role of a party in a QUIC connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The party which opens streams.
+    Client,
+    /// The party which accepts streams.
+    Server,
+}
+
+/// A multiplexer which maps stream ids to QUIC streams.
+#[derive(Debug, Clone)]
+pub struct QuicMux {
+    role: Role,
+    conn: Connection,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    /// Streams which were accepted but have not been requested yet.
+    streams: Mutex<HashMap<Vec<u8>, QuicStream>>,
+    /// Notifies callers when a stream is added to `streams`.
+    notify: Notify,
+}
+
+impl QuicMux {
+    /// Creates a new QUIC multiplexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - The QUIC connection to the peer.
+    /// * `role` - The role of this party, which must be different from the role of the peer.
+    pub fn new(conn: Connection, role: Role) -> Self {
+        Self {
+            role,
+            conn,
+            shared: Arc::new(Shared::default()),
+        }
+    }
+
+    /// Returns the role of this party.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Returns the underlying connection.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Opens a new stream with the provided id, sending the id to the peer.
+    async fn open_stream(&self, id: &[u8]) -> Result<QuicStream, QuicError> {
+        let (send, recv) = self.conn.open_bi().await?;
+        let mut stream = QuicStream { send, recv };
+
+        // QUIC streams are not visible to the peer until data is sent, so the header also
+        // announces the stream.
+        write_id(&mut stream, id).await?;
+
+        Ok(stream)
+    }
+
+    /// Accepts streams until one with the provided id is received.
+    ///
+    /// Streams with other ids are stashed for the callers waiting for them.
+    async fn accept_stream(&self, id: &[u8]) -> Result<QuicStream, QuicError> {
+        loop {
+            // The notification must be created before checking for the stream so that a stream
+            // which is stashed concurrently is not missed.
+            let notified = self.shared.notify.notified();
+
+            if let Some(stream) = self.shared.streams.lock().unwrap().remove(id) {
+                return Ok(stream);
+            }
+
+            let accept = self.conn.accept_bi();
+            pin_mut!(notified, accept);
+
+            let (send, recv) = match select(notified, accept).await {
+                Either::Left(_) => continue,
+                Either::Right((stream, _)) => stream?,
+            };
+
+            let mut stream = QuicStream { send, recv };
+            let stream_id = read_id(&mut stream).await?;
+
+            if stream_id == id {
+                return Ok(stream);
+            }
+
+            {
+                let mut streams = self.shared.streams.lock().unwrap();
+                if streams.contains_key(&stream_id) {
+                    return Err(QuicError::DuplicateId(stream_id));
+                }
+                streams.insert(stream_id, stream);
+            }
+
+            self.shared.notify.notify_waiters();
+        }
+    }
+}
+
+#[async_trait]
+impl<Id> UidMux<Id> for QuicMux
+where
+    Id: AsRef<[u8]> + Sync,
+{
+    type Stream = QuicStream;
+    type Error = QuicError;
+
+    async fn open(&self, id: &Id) -> Result<Self::Stream, Self::Error> {
+        let id = id.as_ref();
+        if id.len() > MAX_ID_LEN {
+            return Err(QuicError::IdTooLong(id.len()));
+        }
+
+        match self.role {
+            Role::Client => self.open_stream(id).await,
+            Role::Server => self.accept_stream(id).await,
+        }
+    }
+}
+
+/// A bidirectional QUIC stream.
+#[derive(Debug)]
+pub struct QuicStream {
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncRead::poll_read(Pin::new(&mut self.recv), cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.send), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.send), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_close(Pin::new(&mut self.send), cx)
+    }
+}
+
+/// Writes a length-prefixed stream id.
+async fn write_id<S: AsyncWrite + Unpin>(io: &mut S, id: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(id.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "stream id is too long"))?;
+
+    io.write_all(&len.to_le_bytes()).await?;
+    io.write_all(id).await?;
+    io.flush().await
+}
+
+/// Reads a length-prefixed stream id.
+async fn read_id<S: AsyncRead + Unpin>(io: &mut S) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 2];
+    io.read_exact(&mut len).await?;
+
+    let mut id = vec![0u8; u16::from_le_bytes(len) as usize];
+    io.read_exact(&mut id).await?;
+
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use futures::{executor::block_on, io::Cursor};
+    use quinn::{
+        rustls::{
+            pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            RootCertStore,
+        },
+        ClientConfig, Endpoint, ServerConfig,
+    };
+
+    use crate::ThreadId;
+
+    use super::*;
+
+    /// A self-signed certificate for `localhost`, and its key.
+    const CERT: &[u8] = include_bytes!("testdata/cert.der");
+    const KEY: &[u8] = include_bytes!("testdata/key.der");
+
+    /// Creates a pair of multiplexers over a loopback QUIC connection.
+    ///
+    /// The endpoints are returned as well, as the connection is closed when they are dropped.
+    async fn test_quic_pair() -> ((QuicMux, Endpoint), (QuicMux, Endpoint)) {
+        let cert = CertificateDer::from(CERT.to_vec());
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(KEY.to_vec()));
+        let localhost = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+
+        let server_config = ServerConfig::with_single_cert(vec![cert.clone()], key).unwrap();
+        let server = Endpoint::server(server_config, localhost).unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert).unwrap();
+        let mut client = Endpoint::client(localhost).unwrap();
+        client.set_default_client_config(
+            ClientConfig::with_root_certificates(Arc::new(roots)).unwrap(),
+        );
+
+        let (client_conn, server_conn) = futures::join!(
+            async {
+                client
+                    .connect(server.local_addr().unwrap(), "localhost")
+                    .unwrap()
+                    .await
+                    .unwrap()
+            },
+            async { server.accept().await.unwrap().await.unwrap() }
+        );
+
+        (
+            (QuicMux::new(client_conn, Role::Client), client),
+            (QuicMux::new(server_conn, Role::Server), server),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_quic_mux() {
+        let ((client, _client_endpoint), (server, _server_endpoint)) = test_quic_pair().await;
+
+        let ids = [b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+
+        // The client opens the streams in order, while the server waits for them concurrently
+        // in reverse order, so the streams which arrive first have to be stashed.
+        let client_fut = async {
+            for id in &ids {
+                let mut stream = client.open(id).await.unwrap();
+                stream.write_all(id).await.unwrap();
+                stream.close().await.unwrap();
+            }
+        };
+
+        let server_fut = futures::future::join_all(ids.iter().rev().map(|id| {
+            let server = server.clone();
+            async move {
+                let mut stream = server.open(id).await.unwrap();
+                let mut payload = Vec::new();
+                stream.read_to_end(&mut payload).await.unwrap();
+                payload
+            }
+        }));
+
+        let (_, payloads) = futures::join!(client_fut, server_fut);
+
+        assert_eq!(payloads, ids.iter().rev().cloned().collect::<Vec<_>>());
+        assert!(server.shared.streams.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quic_mux_duplicate_id() {
+        let ((client, _client_endpoint), (server, _server_endpoint)) = test_quic_pair().await;
+
+        let _first = client.open(b"a").await.unwrap();
+        let _second = client.open(b"a").await.unwrap();
+        let _other = client.open(b"b").await.unwrap();
+
+        // The first stream with id `a` is stashed while waiting for `b`, so the second one is
+        // rejected.
+        assert!(matches!(
+            UidMux::open(&server, b"b").await.unwrap_err(),
+            QuicError::DuplicateId(id) if id == b"a"
+        ));
+    }
+
+    #[test]
+    fn test_stream_id_header() {
+        let id = ThreadId::default().fork();
+
+        let mut buf = Cursor::new(Vec::new());
+        block_on(write_id(&mut buf, id.as_bytes())).unwrap();
+
+        let mut buf = Cursor::new(buf.into_inner());
+        let received = block_on(read_id(&mut buf)).unwrap();
+
+        assert_eq!(received, id.as_bytes());
+    }
+
+    #[test]
+    fn test_stream_id_too_long() {
+        let mut buf = Cursor::new(Vec::new());
+        let err = block_on(write_id(&mut buf, &vec![0u8; MAX_ID_LEN + 1])).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,267 @@
+//! WebSocket transport.
+//!
+//! WebSockets are message oriented, so [`WsStream`] adapts a stream of binary messages into a
+//! byte stream which can be framed or multiplexed like any other I/O channel.
+//!
+//! The adapter is generic over the WebSocket implementation, so the same code runs natively
+//! (eg. `async-tungstenite`) and in the browser (eg. `ws_stream_wasm`), as long as the
+//! implementation is mapped to a [`Stream`] and [`Sink`] of binary message payloads.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context as StdContext, Poll},
+};
+
+use futures::{AsyncRead, AsyncWrite, Sink, Stream};
+
+/// The default maximum size of a message sent by [`WsStream`].
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 1 << 20;
+
+pin_project_lite::pin_project! {
+    /// A byte stream over a WebSocket connection.
+    ///
+    /// Written bytes are buffered and sent as a single binary message when the stream is flushed,
+    /// or when the buffer reaches the maximum message size.
+    #[derive(Debug)]
+    pub struct WsStream<S> {
+        #[pin]
+        inner: S,
+        read_buf: Vec<u8>,
+        read_pos: usize,
+        write_buf: Vec<u8>,
+        max_message_len: usize,
+    }
+}
+
+impl<S> WsStream<S> {
+    /// Creates a new WebSocket stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The WebSocket connection, as a stream and sink of binary messages.
+    pub fn new(inner: S) -> Self {
+        Self::with_max_message_len(inner, DEFAULT_MAX_MESSAGE_LEN)
+    }
+
+    /// Creates a new WebSocket stream with the provided maximum message size.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The WebSocket connection, as a stream and sink of binary messages.
+    /// * `max_message_len` - The maximum size of a message sent by the stream.
+    pub fn with_max_message_len(inner: S, max_message_len: usize) -> Self {
+        assert!(max_message_len > 0, "max message length must be non-zero");
+
+        Self {
+            inner,
+            read_buf: Vec::new(),
+            read_pos: 0,
+            write_buf: Vec::new(),
+            max_message_len,
+        }
+    }
+
+    /// Returns the inner WebSocket connection.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, E> WsStream<S>
+where
+    S: Sink<Vec<u8>, Error = E>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Sends the buffered bytes as a message.
+    fn poll_send_buffer(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+
+        if this.write_buf.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+
+        ready!(this.inner.as_mut().poll_ready(cx)).map_err(into_io_error)?;
+        this.inner
+            .start_send(std::mem::take(this.write_buf))
+            .map_err(into_io_error)?;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S, E> AsyncRead for WsStream<S>
+where
+    S: Stream<Item = Result<Vec<u8>, E>>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut this = self.project();
+
+        // Skip empty messages, which would otherwise be interpreted as EOF.
+        while *this.read_pos == this.read_buf.len() {
+            match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some(Ok(msg)) => {
+                    *this.read_buf = msg;
+                    *this.read_pos = 0;
+                }
+                Some(Err(e)) => return Poll::Ready(Err(into_io_error(e))),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let available = &this.read_buf[*this.read_pos..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        *this.read_pos += n;
+
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<S, E> AsyncWrite for WsStream<S>
+where
+    S: Sink<Vec<u8>, Error = E>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.write_buf.len() >= self.max_message_len {
+            ready!(self.as_mut().poll_send_buffer(cx))?;
+        }
+
+        let this = self.project();
+        let n = buf.len().min(*this.max_message_len - this.write_buf.len());
+        this.write_buf.extend_from_slice(&buf[..n]);
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_send_buffer(cx))?;
+        self.project().inner.poll_flush(cx).map_err(into_io_error)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_send_buffer(cx))?;
+        self.project().inner.poll_close(cx).map_err(into_io_error)
+    }
+}
+
+fn into_io_error<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+    io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{
+        channel::mpsc::{unbounded, SendError, UnboundedReceiver, UnboundedSender},
+        executor::block_on,
+        AsyncReadExt, AsyncWriteExt,
+    };
+
+    use super::*;
+
+    /// An in-memory WebSocket connection.
+    struct MemoryWs {
+        tx: UnboundedSender<Vec<u8>>,
+        rx: UnboundedReceiver<Vec<u8>>,
+    }
+
+    fn memory_ws() -> (MemoryWs, MemoryWs) {
+        let (tx_0, rx_0) = unbounded();
+        let (tx_1, rx_1) = unbounded();
+
+        (
+            MemoryWs { tx: tx_0, rx: rx_1 },
+            MemoryWs { tx: tx_1, rx: rx_0 },
+        )
+    }
+
+    impl Stream for MemoryWs {
+        type Item = Result<Vec<u8>, SendError>;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            Pin::new(&mut self.rx).poll_next(cx).map(|msg| msg.map(Ok))
+        }
+    }
+
+    impl Sink<Vec<u8>> for MemoryWs {
+        type Error = SendError;
+
+        fn poll_ready(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), SendError>> {
+            Pin::new(&mut self.tx).poll_ready(cx)
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: Vec<u8>) -> Result<(), SendError> {
+            Pin::new(&mut self.tx).start_send(item)
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), SendError>> {
+            Pin::new(&mut self.tx).poll_flush(cx)
+        }
+
+        fn poll_close(
+            mut self: Pin<&mut Self>,
+            cx: &mut StdContext<'_>,
+        ) -> Poll<Result<(), SendError>> {
+            Pin::new(&mut self.tx).poll_close(cx)
+        }
+    }
+
+    #[test]
+    fn test_ws_stream() {
+        let (ws_0, ws_1) = memory_ws();
+        let mut stream_0 = WsStream::with_max_message_len(ws_0, 4);
+        let mut stream_1 = WsStream::new(ws_1);
+
+        let data: Vec<u8> = (0..=255).collect();
+
+        block_on(async {
+            stream_0.write_all(&data).await.unwrap();
+            stream_0.flush().await.unwrap();
+
+            let mut received = vec![0u8; data.len()];
+            stream_1.read_exact(&mut received).await.unwrap();
+            assert_eq!(received, data);
+
+            stream_0.close().await.unwrap();
+            let mut rest = Vec::new();
+            stream_1.read_to_end(&mut rest).await.unwrap();
+            assert!(rest.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_ws_stream_message_len() {
+        let (ws_0, ws_1) = memory_ws();
+        let mut stream_0 = WsStream::with_max_message_len(ws_0, 4);
+
+        block_on(async {
+            stream_0.write_all(&[0u8; 10]).await.unwrap();
+            stream_0.close().await.unwrap();
+        });
+        drop(stream_0);
+
+        let messages: Vec<_> = block_on(futures::StreamExt::collect::<Vec<_>>(ws_1.rx));
+        let lens: Vec<_> = messages.iter().map(Vec::len).collect();
+
+        assert_eq!(lens, vec![4, 4, 2]);
+    }
+}
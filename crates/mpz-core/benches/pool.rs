@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use mpz_core::{block::Block, pool::BufferPool};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool");
+
+    // The size of the matrix produced by a KOS extension of 2^20 OTs.
+    const BLOCKS_PER: usize = 1 << 20;
+    group.throughput(Throughput::Elements(BLOCKS_PER as u64));
+
+    group.bench_function("alloc", move |bench| {
+        bench.iter(|| {
+            let buf = vec![Block::ZERO; BLOCKS_PER];
+            black_box(buf);
+        });
+    });
+
+    group.bench_function("take", move |bench| {
+        let pool = BufferPool::<Block>::default();
+        bench.iter(|| {
+            let buf = pool.take(BLOCKS_PER);
+            pool.put(black_box(buf));
+        });
+    });
+
+    group.bench_function("alloc_bytes", move |bench| {
+        bench.iter(|| {
+            let buf = vec![0u8; BLOCKS_PER * 16];
+            black_box(buf);
+        });
+    });
+
+    group.bench_function("take_bytes", move |bench| {
+        let pool = BufferPool::<u8>::default();
+        bench.iter(|| {
+            let buf = pool.take(BLOCKS_PER * 16);
+            pool.put(black_box(buf));
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
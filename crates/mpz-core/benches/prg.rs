@@ -49,6 +49,16 @@ fn criterion_benchmark(c: &mut Criterion) {
             prg.random_blocks(black_box(&mut x));
         });
     });
+
+    group.bench_function("blocks_into", move |bench| {
+        let mut prg = Prg::new();
+        let mut x = (0..BLOCKS_PER)
+            .map(|_| rand::random::<Block>())
+            .collect::<Vec<Block>>();
+        bench.iter(|| {
+            prg.random_blocks_into(black_box(&mut x));
+        });
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -57,17 +57,14 @@ impl FixedKeyAes {
             .encrypt_blocks(Block::as_generic_array_mut_slice(blocks));
 
         // Write π(x) ⊕ i into `buf`
-        let mut buf: [Block; N] = std::array::from_fn(|i| blocks[i] ^ tweaks[i]);
+        let mut buf: [Block; N] = core::array::from_fn(|i| blocks[i] ^ tweaks[i]);
 
         // Write π(π(x) ⊕ i) in `buf`
         self.aes
             .encrypt_blocks(Block::as_generic_array_mut_slice(&mut buf));
 
         // Write π(π(x) ⊕ i) ⊕ π(x) into `blocks`
-        blocks
-            .iter_mut()
-            .zip(buf.iter())
-            .for_each(|(a, b)| *a ^= *b);
+        Block::xor_slice(blocks, &buf);
     }
 
     /// Correlation-robust hash function instantiated using fixed-key AES
@@ -96,10 +93,7 @@ impl FixedKeyAes {
         self.aes
             .encrypt_blocks(Block::as_generic_array_mut_slice(&mut buf));
 
-        blocks
-            .iter_mut()
-            .zip(buf.iter())
-            .for_each(|(a, b)| *a ^= *b);
+        Block::xor_slice(blocks, &buf);
     }
 
     /// Circular correlation-robust hash function instantiated using fixed-key AES
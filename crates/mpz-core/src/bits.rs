@@ -0,0 +1,240 @@
+//! Conversions between bits, packed bytes and blocks.
+//!
+//! Bits are packed 8 at a time using word operations rather than per-bit loops. With the `simd`
+//! feature enabled, packing uses `core::simd` to process 64 bits at a time, which requires a
+//! nightly compiler.
+//!
+//! Packed bits are either in [`BitOrder::Lsb0`], ie bit `i` is bit `i % 8` of byte `i / 8`, or in
+//! [`BitOrder::Msb0`], ie bit `i` is bit `7 - i % 8` of byte `i / 8`. Blocks contain 128 bits
+//! packed into their bytes.
+
+use alloc::{vec, vec::Vec};
+
+use crate::Block;
+
+#[cfg(feature = "simd")]
+use core::simd::{cmp::SimdPartialEq, u8x64};
+
+/// Spreads the bits of a byte in LSB0 order into the bytes of a word.
+const SPREAD: u64 = 0x8040201008040201;
+/// Gathers the lowest bit of every byte of a word into the top byte in LSB0 order.
+const GATHER: u64 = 0x0102040810204080;
+
+/// The order of bits within a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// Least significant bit first.
+    Lsb0,
+    /// Most significant bit first.
+    Msb0,
+}
+
+impl BitOrder {
+    #[inline(always)]
+    fn order(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::Lsb0 => byte,
+            BitOrder::Msb0 => byte.reverse_bits(),
+        }
+    }
+}
+
+/// Packs bits into bytes.
+///
+/// The last byte is padded with zeros.
+///
+/// # Arguments
+///
+/// * `bits` - The bits to pack.
+/// * `order` - The bit order of the packed bytes.
+pub fn pack_bits(bits: &[bool], order: BitOrder) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    pack_bits_into(bits, order, &mut bytes);
+    bytes
+}
+
+/// Packs bits into the provided bytes.
+///
+/// The last byte is padded with zeros.
+///
+/// # Arguments
+///
+/// * `bits` - The bits to pack.
+/// * `order` - The bit order of the packed bytes.
+/// * `bytes` - The bytes to write to.
+///
+/// # Panics
+///
+/// Panics if `bytes` does not have a length of `bits.len().div_ceil(8)`.
+pub fn pack_bits_into(bits: &[bool], order: BitOrder, bytes: &mut [u8]) {
+    assert_eq!(
+        bytes.len(),
+        bits.len().div_ceil(8),
+        "bytes must be the packed length of the bits"
+    );
+
+    // Bools are guaranteed to be 0 or 1.
+    let bits: &[u8] = bytemuck::cast_slice(bits);
+
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "simd")] {
+            let mut bit_chunks = bits.chunks_exact(64);
+            let mut byte_chunks = bytes.chunks_exact_mut(8);
+            for (bits, bytes) in (&mut bit_chunks).zip(&mut byte_chunks) {
+                let mask = u8x64::from_slice(bits).simd_ne(u8x64::splat(0)).to_bitmask();
+                bytes.copy_from_slice(&mask.to_le_bytes());
+                bytes.iter_mut().for_each(|byte| *byte = order.order(*byte));
+            }
+
+            pack_bytes(bit_chunks.remainder(), order, byte_chunks.into_remainder());
+        } else {
+            pack_bytes(bits, order, bytes);
+        }
+    }
+}
+
+/// Packs bits, represented as bytes which are 0 or 1, into bytes.
+#[inline]
+fn pack_bytes(bits: &[u8], order: BitOrder, bytes: &mut [u8]) {
+    let mut bit_chunks = bits.chunks_exact(8);
+    for (bits, byte) in (&mut bit_chunks).zip(bytes.iter_mut()) {
+        let word = u64::from_le_bytes(bits.try_into().unwrap());
+        *byte = order.order((word.wrapping_mul(GATHER) >> 56) as u8);
+    }
+
+    let rem = bit_chunks.remainder();
+    if !rem.is_empty() {
+        let byte = rem
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (i, bit)| byte | (bit << i));
+        bytes[bytes.len() - 1] = order.order(byte);
+    }
+}
+
+/// Unpacks bits from bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The packed bytes.
+/// * `order` - The bit order of the packed bytes.
+pub fn unpack_bits(bytes: &[u8], order: BitOrder) -> Vec<bool> {
+    let mut bits = vec![false; bytes.len() * 8];
+    unpack_bits_into(bytes, order, &mut bits);
+    bits
+}
+
+/// Unpacks bits from bytes into the provided buffer.
+///
+/// Only the first `bits.len()` bits are unpacked, the remaining bits of `bytes` are ignored.
+///
+/// # Arguments
+///
+/// * `bytes` - The packed bytes.
+/// * `order` - The bit order of the packed bytes.
+/// * `bits` - The bits to write to.
+///
+/// # Panics
+///
+/// Panics if `bytes` contains less than `bits.len()` bits.
+pub fn unpack_bits_into(bytes: &[u8], order: BitOrder, bits: &mut [bool]) {
+    assert!(
+        bits.len() <= bytes.len() * 8,
+        "bytes must contain at least {} bits",
+        bits.len()
+    );
+
+    for (byte, bits) in bytes.iter().zip(bits.chunks_mut(8)) {
+        let word = (u64::from(order.order(*byte)) * 0x0101010101010101) & SPREAD;
+        bits.iter_mut()
+            .zip(word.to_le_bytes())
+            .for_each(|(bit, byte)| *bit = byte != 0);
+    }
+}
+
+/// Packs bits into blocks.
+///
+/// The last block is padded with zeros.
+///
+/// # Arguments
+///
+/// * `bits` - The bits to pack.
+/// * `order` - The bit order of the bytes of the blocks.
+pub fn pack_bits_to_blocks(bits: &[bool], order: BitOrder) -> Vec<Block> {
+    let mut blocks = vec![Block::ZERO; bits.len().div_ceil(128)];
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut blocks);
+    pack_bits_into(bits, order, &mut bytes[..bits.len().div_ceil(8)]);
+    blocks
+}
+
+/// Unpacks bits from blocks.
+///
+/// # Arguments
+///
+/// * `blocks` - The blocks.
+/// * `order` - The bit order of the bytes of the blocks.
+pub fn unpack_bits_from_blocks(blocks: &[Block], order: BitOrder) -> Vec<bool> {
+    unpack_bits(bytemuck::cast_slice(blocks), order)
+}
+
+#[cfg(test)]
+mod tests {
+    use itybity::{FromBitIterator, ToBits};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    fn bits(len: usize) -> Vec<bool> {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        (0..len).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_pack_bits() {
+        for len in [0, 1, 7, 8, 63, 64, 65, 200] {
+            let bits = bits(len);
+
+            assert_eq!(
+                pack_bits(&bits, BitOrder::Lsb0),
+                Vec::<u8>::from_lsb0_iter(bits.iter().copied())
+            );
+            assert_eq!(
+                pack_bits(&bits, BitOrder::Msb0),
+                Vec::<u8>::from_msb0_iter(bits.iter().copied())
+            );
+        }
+    }
+
+    #[test]
+    fn test_unpack_bits() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(
+            unpack_bits(&bytes, BitOrder::Lsb0),
+            bytes.iter_lsb0().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            unpack_bits(&bytes, BitOrder::Msb0),
+            bytes.iter_msb0().collect::<Vec<_>>()
+        );
+
+        let mut bits = vec![false; 13];
+        unpack_bits_into(&bytes[1..], BitOrder::Lsb0, &mut bits);
+        assert_eq!(bits, unpack_bits(&bytes[1..3], BitOrder::Lsb0)[..13]);
+    }
+
+    #[test]
+    fn test_pack_bits_to_blocks() {
+        for order in [BitOrder::Lsb0, BitOrder::Msb0] {
+            let bits = bits(200);
+            let blocks = pack_bits_to_blocks(&bits, order);
+
+            assert_eq!(blocks.len(), 2);
+
+            let unpacked = unpack_bits_from_blocks(&blocks, order);
+            assert_eq!(unpacked[..200], bits);
+            assert!(unpacked[200..].iter().all(|bit| !bit));
+        }
+    }
+}
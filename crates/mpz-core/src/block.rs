@@ -1,5 +1,6 @@
 //! A block of 128 bits and its operations.
 
+use alloc::vec::Vec;
 use bytemuck::{Pod, Zeroable};
 use clmul::Clmul;
 use core::ops::{BitAnd, BitAndAssign, BitXor, BitXorAssign};
@@ -7,6 +8,12 @@ use generic_array::{typenum::consts::U16, GenericArray};
 use itybity::{BitIterable, BitLength, GetBit, Lsb0, Msb0};
 use rand::{distributions::Standard, prelude::Distribution, CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+
+pub(crate) mod ops;
+mod typed;
+
+pub use typed::{KeyBlock, MacBlock};
 
 /// A block of 128 bits
 #[repr(transparent)]
@@ -46,7 +53,7 @@ impl Block {
     /// Generate a random array of blocks using the provided RNG
     #[inline]
     pub fn random_array<const N: usize, R: Rng + CryptoRng>(rng: &mut R) -> [Self; N] {
-        std::array::from_fn(|_| rng.gen::<[u8; 16]>().into())
+        core::array::from_fn(|_| rng.gen::<[u8; 16]>().into())
     }
 
     /// Generate a random vector of blocks using the provided RNG
@@ -81,14 +88,72 @@ impl Block {
     #[inline]
     pub fn inn_prdt_no_red(a: &[Block], b: &[Block]) -> (Block, Block) {
         assert_eq!(a.len(), b.len());
-        a.iter()
-            .zip(b.iter())
-            .fold((Block::ZERO, Block::ZERO), |acc, (x, y)| {
-                let t = x.clmul(*y);
+
+        // Independent accumulators allow consecutive multiplications to be pipelined.
+        const BATCH: usize = 4;
+        let mut acc = [(Block::ZERO, Block::ZERO); BATCH];
+        let mut a_chunks = a.chunks_exact(BATCH);
+        let mut b_chunks = b.chunks_exact(BATCH);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            for i in 0..BATCH {
+                let t = x[i].clmul(y[i]);
+                acc[i].0 ^= t.0;
+                acc[i].1 ^= t.1;
+            }
+        }
+
+        a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| x.clmul(*y))
+            .chain(acc)
+            .fold((Block::ZERO, Block::ZERO), |acc, t| {
                 (t.0 ^ acc.0, t.1 ^ acc.1)
             })
     }
 
+    /// Computes the carry-less products of two block vectors element-wise, without reducing.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first vector.
+    /// * `b` - The second vector.
+    /// * `out` - The output buffer, which must have the same length as the inputs.
+    #[inline]
+    pub fn clmul_slice(a: &[Block], b: &[Block], out: &mut [(Block, Block)]) {
+        assert_eq!(a.len(), b.len());
+        assert_eq!(a.len(), out.len());
+
+        out.iter_mut()
+            .zip(a.iter().zip(b))
+            .for_each(|(out, (a, b))| *out = a.clmul(*b));
+    }
+
+    /// Computes `a[i] ^= b[i]` for all `i`.
+    #[inline]
+    pub fn xor_slice(a: &mut [Block], b: &[Block]) {
+        ops::xor(bytemuck::cast_slice_mut(a), bytemuck::cast_slice(b));
+    }
+
+    /// Computes `a[i] &= b[i]` for all `i`.
+    #[inline]
+    pub fn and_slice(a: &mut [Block], b: &[Block]) {
+        ops::and(bytemuck::cast_slice_mut(a), bytemuck::cast_slice(b));
+    }
+
+    /// Computes `a[i] ^= b` for all `i`.
+    #[inline]
+    pub fn xor_all(a: &mut [Block], b: Block) {
+        ops::xor_repeat(bytemuck::cast_slice_mut(a), b.0);
+    }
+
+    /// Computes `a[i] &= b` for all `i`.
+    #[inline]
+    pub fn and_all(a: &mut [Block], b: Block) {
+        ops::and_repeat(bytemuck::cast_slice_mut(a), b.0);
+    }
+
     /// Compute the inner product of two block vectors.
     #[inline]
     pub fn inn_prdt_red(a: &[Block], b: &[Block]) -> Block {
@@ -144,7 +209,7 @@ impl Block {
         // This is always safe because `Block` and `GenericArray<u8, U16>` have the same memory layout.
         // See https://github.com/fizyk20/generic-array/blob/37dc6aefc3ed5c423ad7402d4febf06a3e78a223/src/lib.rs#L838-L845
         // TODO: Use methods provided by `generic-array` once 1.0 is released.
-        unsafe { std::mem::transmute(slice) }
+        unsafe { core::mem::transmute(slice) }
     }
 
     /// Converts a mutable slice of blocks to a mutable slice of [`GenericArray<u8, U16>`](cipher::generic_array::GenericArray)
@@ -154,14 +219,14 @@ impl Block {
         // This is always safe because `Block` and `GenericArray<u8, U16>` have the same memory layout.
         // See https://github.com/fizyk20/generic-array/blob/37dc6aefc3ed5c423ad7402d4febf06a3e78a223/src/lib.rs#L847-L854
         // TODO: Use methods provided by `generic-array` once 1.0 is released.
-        unsafe { std::mem::transmute(slice) }
+        unsafe { core::mem::transmute(slice) }
     }
 }
 
 /// A trait for converting a type to blocks
 pub trait BlockSerialize {
     /// The block representation of the type
-    type Serialized: std::fmt::Debug + Clone + Copy + Send + Sync + 'static;
+    type Serialized: core::fmt::Debug + Clone + Copy + Send + Sync + 'static;
 
     /// Convert the type to blocks
     fn to_blocks(self) -> Self::Serialized;
@@ -257,7 +322,7 @@ impl BitXor for Block {
 
     #[inline]
     fn bitxor(self, other: Self) -> Self::Output {
-        Self(std::array::from_fn(|i| self.0[i] ^ other.0[i]))
+        Self(core::array::from_fn(|i| self.0[i] ^ other.0[i]))
     }
 }
 
@@ -273,7 +338,7 @@ impl BitAnd for Block {
 
     #[inline]
     fn bitand(self, other: Self) -> Self::Output {
-        Self(std::array::from_fn(|i| self.0[i] & other.0[i]))
+        Self(core::array::from_fn(|i| self.0[i] & other.0[i]))
     }
 }
 
@@ -297,6 +362,16 @@ impl AsMut<[u8]> for Block {
     }
 }
 
+impl ConstantTimeEq for Block {
+    #[inline]
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::DefaultIsZeroes for Block {}
+
 #[cfg(test)]
 mod tests {
     use itybity::ToBits;
@@ -384,6 +459,40 @@ mod tests {
         assert_eq!(d, Block::inn_prdt_red(&a, &b));
     }
 
+    #[test]
+    fn batch_ops_test() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha12Rng;
+        let mut rng = ChaCha12Rng::from_seed([0; 32]);
+
+        let a = Block::random_vec(&mut rng, 37);
+        let b = Block::random_vec(&mut rng, 37);
+        let c = Block::random(&mut rng);
+
+        let mut x = a.clone();
+        Block::xor_slice(&mut x, &b);
+        assert!(x.iter().zip(&a).zip(&b).all(|((x, a), b)| *x == *a ^ *b));
+
+        let mut x = a.clone();
+        Block::and_slice(&mut x, &b);
+        assert!(x.iter().zip(&a).zip(&b).all(|((x, a), b)| *x == *a & *b));
+
+        let mut x = a.clone();
+        Block::xor_all(&mut x, c);
+        assert!(x.iter().zip(&a).all(|(x, a)| *x == *a ^ c));
+
+        let mut x = a.clone();
+        Block::and_all(&mut x, c);
+        assert!(x.iter().zip(&a).all(|(x, a)| *x == *a & c));
+
+        let mut products = vec![(Block::ZERO, Block::ZERO); a.len()];
+        Block::clmul_slice(&a, &b, &mut products);
+        assert!(products
+            .iter()
+            .zip(a.iter().zip(&b))
+            .all(|(p, (a, b))| *p == a.clmul(*b)));
+    }
+
     #[test]
     fn sigma_test() {
         use rand::{Rng, SeedableRng};
@@ -395,9 +504,32 @@ mod tests {
 
         for (x, y) in xl.iter_mut().zip(xr.iter_mut()) {
             *x ^= *y;
-            std::mem::swap(x, y);
+            core::mem::swap(x, y);
         }
         let expected_sigma = Block::from(x);
         assert_eq!(bx, expected_sigma);
     }
+
+    #[test]
+    fn ct_eq_test() {
+        assert!(bool::from(Block::ONES.ct_eq(&Block::ONES)));
+        assert!(!bool::from(Block::ONES.ct_eq(&Block::ZERO)));
+        assert!(bool::from(
+            [Block::ZERO, Block::ONES][..].ct_eq(&[Block::ZERO, Block::ONES])
+        ));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_test() {
+        use zeroize::Zeroize;
+
+        let mut blocks = vec![Block::ONES; 4];
+        blocks.zeroize();
+        assert!(blocks.is_empty());
+
+        let mut block = Block::ONES;
+        block.zeroize();
+        assert_eq!(block, Block::ZERO);
+    }
 }
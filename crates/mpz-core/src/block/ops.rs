@@ -0,0 +1,135 @@
+//! Element-wise operations over byte slices.
+//!
+//! With the `simd` feature enabled these use `core::simd`, which requires a nightly compiler.
+//! Otherwise the operations are implemented with scalar loops over `u64` words, which the
+//! compiler is typically able to auto-vectorize.
+
+#[cfg(feature = "simd")]
+use core::simd::u8x64;
+
+/// Number of bytes processed per iteration.
+#[cfg(feature = "simd")]
+const LANES: usize = 64;
+#[cfg(not(feature = "simd"))]
+const LANES: usize = 8;
+
+macro_rules! impl_op {
+    ($name:ident, $name_repeat:ident, $op:tt, $op_assign:tt) => {
+        /// Computes `a[i] op= b[i]` for all `i`.
+        #[inline]
+        pub(crate) fn $name(a: &mut [u8], b: &[u8]) {
+            assert_eq!(a.len(), b.len(), "slices must have the same length");
+
+            let mut a_chunks = a.chunks_exact_mut(LANES);
+            let mut b_chunks = b.chunks_exact(LANES);
+            for (a, b) in (&mut a_chunks).zip(&mut b_chunks) {
+                cfg_if::cfg_if! {
+                    if #[cfg(feature = "simd")] {
+                        (u8x64::from_slice(a) $op u8x64::from_slice(b)).copy_to_slice(a);
+                    } else {
+                        let x = u64::from_ne_bytes((&*a).try_into().unwrap())
+                            $op u64::from_ne_bytes(b.try_into().unwrap());
+                        a.copy_from_slice(&x.to_ne_bytes());
+                    }
+                }
+            }
+
+            a_chunks
+                .into_remainder()
+                .iter_mut()
+                .zip(b_chunks.remainder())
+                .for_each(|(a, b)| *a $op_assign *b);
+        }
+
+        /// Computes `a[i] op= pattern[i % N]` for all `i`.
+        ///
+        /// The length of `a` must be a multiple of `N`, and `N` must divide the lane count
+        /// or vice versa.
+        #[inline]
+        pub(crate) fn $name_repeat<const N: usize>(a: &mut [u8], pattern: [u8; N]) {
+            assert_eq!(a.len() % N, 0, "slice length must be a multiple of the pattern");
+
+            if LANES % N == 0 {
+                let repeated: [u8; LANES] = core::array::from_fn(|i| pattern[i % N]);
+                let mut chunks = a.chunks_exact_mut(LANES);
+                for a in &mut chunks {
+                    cfg_if::cfg_if! {
+                        if #[cfg(feature = "simd")] {
+                            (u8x64::from_slice(a) $op u8x64::from_array(repeated)).copy_to_slice(a);
+                        } else {
+                            let x = u64::from_ne_bytes((&*a).try_into().unwrap())
+                                $op u64::from_ne_bytes(repeated);
+                            a.copy_from_slice(&x.to_ne_bytes());
+                        }
+                    }
+                }
+
+                chunks
+                    .into_remainder()
+                    .iter_mut()
+                    .zip(pattern.iter().cycle())
+                    .for_each(|(a, b)| *a $op_assign *b);
+            } else {
+                for a in a.chunks_exact_mut(N) {
+                    $name(a, &pattern);
+                }
+            }
+        }
+    };
+}
+
+impl_op!(xor, xor_repeat, ^, ^=);
+impl_op!(and, and_repeat, &, &=);
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        // Include lengths which are not a multiple of the lane count.
+        for len in [0, 1, 15, 16, 64, 100, 1024, 1040] {
+            let a: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let b: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+
+            let mut x = a.clone();
+            xor(&mut x, &b);
+            assert!(x.iter().zip(&a).zip(&b).all(|((x, a), b)| *x == a ^ b));
+
+            let mut x = a.clone();
+            and(&mut x, &b);
+            assert!(x.iter().zip(&a).zip(&b).all(|((x, a), b)| *x == a & b));
+        }
+    }
+
+    #[test]
+    fn test_repeat_ops() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let pattern: [u8; 16] = rng.gen();
+
+        for len in [0, 16, 64, 80, 1024, 1040] {
+            let a: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+
+            let mut x = a.clone();
+            xor_repeat(&mut x, pattern);
+            assert!(x
+                .iter()
+                .zip(&a)
+                .zip(pattern.iter().cycle())
+                .all(|((x, a), p)| *x == a ^ p));
+
+            let mut x = a.clone();
+            and_repeat(&mut x, pattern);
+            assert!(x
+                .iter()
+                .zip(&a)
+                .zip(pattern.iter().cycle())
+                .all(|((x, a), p)| *x == a & p));
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Typed wrappers over [`Block`].
+//!
+//! Blocks are used for many different things, such as keys and MACs of correlated OTs. Wrapping
+//! them in distinct types prevents them from being mixed up, converting between them requires
+//! calling one of the explicit conversion methods.
+
+use alloc::vec::Vec;
+use core::ops::{BitXor, BitXorAssign};
+
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::Block;
+
+macro_rules! typed_block {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize, Pod, Zeroable)]
+        pub struct $name(Block);
+
+        impl $name {
+            #[doc = concat!("Creates a new ", stringify!($name), " from a block.")]
+            #[inline]
+            pub const fn new(block: Block) -> Self {
+                Self(block)
+            }
+
+            /// Returns the inner block.
+            #[inline]
+            pub const fn into_block(self) -> Block {
+                self.0
+            }
+
+            /// Returns a reference to the inner block.
+            #[inline]
+            pub const fn as_block(&self) -> &Block {
+                &self.0
+            }
+
+            #[doc = concat!("Converts a slice of blocks to a slice of ", stringify!($name), "s, without copying.")]
+            #[inline]
+            pub fn from_blocks(blocks: &[Block]) -> &[Self] {
+                bytemuck::cast_slice(blocks)
+            }
+
+            #[doc = concat!("Converts a slice of ", stringify!($name), "s to a slice of blocks, without copying.")]
+            #[inline]
+            pub fn as_blocks(values: &[Self]) -> &[Block] {
+                bytemuck::cast_slice(values)
+            }
+
+            #[doc = concat!("Converts a vector of blocks to a vector of ", stringify!($name), "s.")]
+            #[inline]
+            pub fn from_block_vec(blocks: Vec<Block>) -> Vec<Self> {
+                blocks.into_iter().map(Self).collect()
+            }
+
+            #[doc = concat!("Converts a vector of ", stringify!($name), "s to a vector of blocks.")]
+            #[inline]
+            pub fn into_block_vec(values: Vec<Self>) -> Vec<Block> {
+                values.into_iter().map(Self::into_block).collect()
+            }
+        }
+
+        impl BitXor for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitxor(self, rhs: Self) -> Self::Output {
+                Self(self.0 ^ rhs.0)
+            }
+        }
+
+        impl BitXorAssign for $name {
+            #[inline]
+            fn bitxor_assign(&mut self, rhs: Self) {
+                self.0 ^= rhs.0;
+            }
+        }
+
+        impl ConstantTimeEq for $name {
+            #[inline]
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.0.ct_eq(&other.0)
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl zeroize::DefaultIsZeroes for $name {}
+    };
+}
+
+typed_block!(
+    /// A key of a correlated OT, held by the sender.
+    ///
+    /// The receiver holds the MAC `key ^ (bit * delta)` of its choice bit.
+    KeyBlock
+);
+
+typed_block!(
+    /// A MAC of a bit of a correlated OT, held by the receiver.
+    ///
+    /// A MAC is the sender's key of the bit if the bit is 0, and the key xor delta otherwise.
+    MacBlock
+);
+
+impl KeyBlock {
+    /// Returns the MAC of a bit under this key.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit` - The authenticated bit.
+    /// * `delta` - The global correlation.
+    #[inline]
+    pub fn authenticate(&self, bit: bool, delta: Block) -> MacBlock {
+        MacBlock(self.0 ^ (Block::SELECT_MASK[bit as usize] & delta))
+    }
+
+    /// Returns whether the MAC authenticates the bit under this key.
+    ///
+    /// This comparison is in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit` - The authenticated bit.
+    /// * `mac` - The MAC of the bit.
+    /// * `delta` - The global correlation.
+    #[inline]
+    pub fn verify(&self, bit: bool, mac: &MacBlock, delta: Block) -> bool {
+        self.authenticate(bit, delta).ct_eq(mac).into()
+    }
+}
+
+impl MacBlock {
+    /// Returns the key of the bit authenticated by this MAC.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit` - The authenticated bit.
+    /// * `delta` - The global correlation.
+    #[inline]
+    pub fn to_key(self, bit: bool, delta: Block) -> KeyBlock {
+        KeyBlock(self.0 ^ (Block::SELECT_MASK[bit as usize] & delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_key_mac() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let delta = Block::random(&mut rng);
+        let key = KeyBlock::new(Block::random(&mut rng));
+
+        for bit in [false, true] {
+            let mac = key.authenticate(bit, delta);
+
+            assert!(key.verify(bit, &mac, delta));
+            assert!(!key.verify(!bit, &mac, delta));
+            assert_eq!(mac.to_key(bit, delta), key);
+        }
+    }
+
+    #[test]
+    fn test_block_slice_conversion() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let blocks = Block::random_vec(&mut rng, 4);
+
+        let keys = KeyBlock::from_blocks(&blocks);
+        assert_eq!(KeyBlock::as_blocks(keys), blocks);
+        assert_eq!(keys[1].into_block(), blocks[1]);
+        assert_eq!(
+            KeyBlock::into_block_vec(KeyBlock::from_block_vec(blocks.clone())),
+            blocks
+        );
+    }
+}
@@ -1,5 +1,8 @@
 //! This module provides a hash commitment scheme for types which implement
 //! [`CanonicalSerialize`](crate::serialize::CanonicalSerialize)
+//!
+//! Commitments can be bound to a domain, eg. `"mpz-cointoss seeds"`, so that a commitment
+//! made in one protocol can not be opened in another.
 
 use crate::{
     hash::{Hash, SecureHash},
@@ -14,6 +17,10 @@ use serde::{Deserialize, Serialize};
 pub enum CommitmentError {
     #[error("Invalid decommitment")]
     InvalidDecommitment,
+    #[error("Invalid decommitment at index {0}")]
+    InvalidBatchDecommitment(usize),
+    #[error("Expected {expected} decommitments, got {actual}")]
+    BatchLengthMismatch { expected: usize, actual: usize },
 }
 
 /// A randomly generated 32 byte nonce
@@ -23,7 +30,14 @@ pub struct Nonce([u8; 32]);
 impl Nonce {
     /// Creates a random 32 byte nonce
     fn random() -> Self {
-        Self(thread_rng().gen())
+        Self::random_with_rng(&mut thread_rng())
+    }
+
+    /// Creates a random 32 byte nonce using the provided RNG
+    ///
+    /// This allows commitments to be reproduced from a seed, which should only be done in tests.
+    pub fn random_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self(rng.gen())
     }
 }
 
@@ -59,6 +73,13 @@ where
         self.hash()
     }
 
+    /// Creates a hash commitment bound to the provided domain
+    pub fn commit_with_domain(&self, domain: &str) -> Hash {
+        let mut hasher = blake3::Hasher::new_derive_key(domain);
+        hasher.update(&self.to_bytes());
+        Hash::from(*hasher.finalize().as_bytes())
+    }
+
     /// Verifies that the provided commitment corresponds to this decommitment
     pub fn verify(&self, commitment: &Hash) -> Result<(), CommitmentError> {
         if commitment != &self.commit() {
@@ -68,6 +89,19 @@ where
         Ok(())
     }
 
+    /// Verifies that the provided commitment corresponds to this decommitment and domain
+    pub fn verify_with_domain(
+        &self,
+        commitment: &Hash,
+        domain: &str,
+    ) -> Result<(), CommitmentError> {
+        if commitment != &self.commit_with_domain(domain) {
+            return Err(CommitmentError::InvalidDecommitment);
+        }
+
+        Ok(())
+    }
+
     /// Returns the data
     pub fn data(&self) -> &T {
         &self.data
@@ -96,10 +130,63 @@ where
 
         (decommitment, commitment)
     }
+
+    /// Creates a hash commitment to self bound to the provided domain
+    fn hash_commit_with_domain(self, domain: &str) -> (Decommitment<Self>, Hash) {
+        let decommitment = Decommitment::new(self);
+        let commitment = decommitment.commit_with_domain(domain);
+
+        (decommitment, commitment)
+    }
 }
 
 impl<T> HashCommit for T where T: serde::Serialize {}
 
+/// Creates hash commitments to each of the provided messages bound to the provided domain
+pub fn hash_commit_batch<T>(
+    messages: impl IntoIterator<Item = T>,
+    domain: &str,
+) -> (Vec<Decommitment<T>>, Vec<Hash>)
+where
+    T: HashCommit,
+{
+    messages
+        .into_iter()
+        .map(|message| message.hash_commit_with_domain(domain))
+        .unzip()
+}
+
+/// Verifies that each decommitment corresponds to the commitment at the same position and the
+/// provided domain
+///
+/// # Errors
+///
+/// Returns an error if the number of decommitments differs from the number of commitments, or
+/// with the index of the first invalid decommitment.
+pub fn verify_batch<T>(
+    decommitments: &[Decommitment<T>],
+    commitments: &[Hash],
+    domain: &str,
+) -> Result<(), CommitmentError>
+where
+    T: CanonicalSerialize,
+{
+    if decommitments.len() != commitments.len() {
+        return Err(CommitmentError::BatchLengthMismatch {
+            expected: commitments.len(),
+            actual: decommitments.len(),
+        });
+    }
+
+    for (index, (decommitment, commitment)) in decommitments.iter().zip(commitments).enumerate() {
+        decommitment
+            .verify_with_domain(commitment, domain)
+            .map_err(|_| CommitmentError::InvalidBatchDecommitment(index))?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -135,4 +222,45 @@ mod test {
 
         assert!(matches!(err, CommitmentError::InvalidDecommitment));
     }
+
+    #[test]
+    fn test_commitment_domain_separation() {
+        let message = [0, 1, 2, 3u8];
+        let (decommitment, commitment) = message.hash_commit_with_domain("foo");
+
+        decommitment.verify_with_domain(&commitment, "foo").unwrap();
+
+        assert!(decommitment.verify_with_domain(&commitment, "bar").is_err());
+        assert!(decommitment.verify(&commitment).is_err());
+    }
+
+    #[test]
+    fn test_commitment_batch() {
+        #[derive(Serialize)]
+        struct Message {
+            id: u32,
+            payload: Vec<u8>,
+        }
+
+        let messages = (0..4).map(|id| Message {
+            id,
+            payload: vec![id as u8; 8],
+        });
+        let (mut decommitments, commitments) = hash_commit_batch(messages, "foo");
+
+        verify_batch(&decommitments, &commitments, "foo").unwrap();
+
+        let err = verify_batch(&decommitments[..3], &commitments, "foo").unwrap_err();
+        assert!(matches!(
+            err,
+            CommitmentError::BatchLengthMismatch {
+                expected: 4,
+                actual: 3
+            }
+        ));
+
+        decommitments[2].data.payload[0] ^= 1;
+        let err = verify_batch(&decommitments, &commitments, "foo").unwrap_err();
+        assert!(matches!(err, CommitmentError::InvalidBatchDecommitment(2)));
+    }
 }
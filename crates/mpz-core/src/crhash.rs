@@ -0,0 +1,150 @@
+//! Correlation-robust hash functions.
+//!
+//! See <https://eprint.iacr.org/2019/074> for definitions of the security notions.
+//!
+//! Hashes are instantiated either using fixed-key AES, see [`FixedKeyAes`], or using Blake3
+//! modelled as a random oracle, see [`Blake3Hash`].
+
+use crate::{aes::FixedKeyAes, Block};
+
+/// Returns the tweak for the provided index.
+#[inline]
+pub fn tweak(index: u128) -> Block {
+    Block::new(index.to_be_bytes())
+}
+
+/// A correlation-robust hash function.
+pub trait CrHash {
+    /// Hashes a block.
+    fn cr(&self, block: Block) -> Block;
+
+    /// Hashes many blocks in-place.
+    fn cr_many<const N: usize>(&self, blocks: &mut [Block; N]) {
+        blocks.iter_mut().for_each(|block| *block = self.cr(*block));
+    }
+}
+
+/// A tweakable circular correlation-robust hash function.
+pub trait TccrHash {
+    /// Hashes a block with a tweak.
+    fn tccr(&self, tweak: Block, block: Block) -> Block;
+
+    /// Hashes many blocks in-place.
+    ///
+    /// # Arguments
+    ///
+    /// * `tweaks` - The tweaks to use for each block in `blocks`.
+    /// * `blocks` - The blocks to hash in-place.
+    fn tccr_many<const N: usize>(&self, tweaks: &[Block; N], blocks: &mut [Block; N]) {
+        blocks
+            .iter_mut()
+            .zip(tweaks)
+            .for_each(|(block, tweak)| *block = self.tccr(*tweak, *block));
+    }
+}
+
+impl CrHash for FixedKeyAes {
+    #[inline]
+    fn cr(&self, block: Block) -> Block {
+        FixedKeyAes::cr(self, block)
+    }
+
+    #[inline]
+    fn cr_many<const N: usize>(&self, blocks: &mut [Block; N]) {
+        FixedKeyAes::cr_many(self, blocks)
+    }
+}
+
+impl TccrHash for FixedKeyAes {
+    #[inline]
+    fn tccr(&self, tweak: Block, block: Block) -> Block {
+        FixedKeyAes::tccr(self, tweak, block)
+    }
+
+    #[inline]
+    fn tccr_many<const N: usize>(&self, tweaks: &[Block; N], blocks: &mut [Block; N]) {
+        FixedKeyAes::tccr_many(self, tweaks, blocks)
+    }
+}
+
+/// Correlation-robust hash functions instantiated using Blake3.
+///
+/// Unlike the fixed-key AES instantiations, these can hash inputs of arbitrary length, eg.
+/// elliptic curve points.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3Hash;
+
+impl Blake3Hash {
+    /// Hashes bytes with a tweak.
+    ///
+    /// `H(i || x)`, truncated to 128 bits.
+    pub fn tccr_bytes(&self, tweak: Block, bytes: &[u8]) -> Block {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&tweak.to_bytes());
+        hasher.update(bytes);
+
+        truncate(hasher.finalize())
+    }
+}
+
+impl CrHash for Blake3Hash {
+    fn cr(&self, block: Block) -> Block {
+        truncate(blake3::hash(&block.to_bytes()))
+    }
+}
+
+impl TccrHash for Blake3Hash {
+    fn tccr(&self, tweak: Block, block: Block) -> Block {
+        self.tccr_bytes(tweak, &block.to_bytes())
+    }
+}
+
+fn truncate(hash: blake3::Hash) -> Block {
+    let mut block = [0u8; 16];
+    block.copy_from_slice(&hash.as_bytes()[..16]);
+    block.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::FIXED_KEY_AES;
+
+    fn check_tccr<H: TccrHash>(hash: &H) {
+        let x = Block::ONES;
+
+        assert_ne!(hash.tccr(tweak(0), x), hash.tccr(tweak(1), x));
+        assert_ne!(hash.tccr(tweak(0), x), hash.tccr(tweak(0), Block::ZERO));
+
+        let mut blocks = [x, Block::ZERO];
+        hash.tccr_many(&[tweak(0), tweak(1)], &mut blocks);
+        assert_eq!(
+            blocks,
+            [hash.tccr(tweak(0), x), hash.tccr(tweak(1), Block::ZERO)]
+        );
+    }
+
+    fn check_cr<H: CrHash>(hash: &H) {
+        let mut blocks = [Block::ONES, Block::ZERO];
+        hash.cr_many(&mut blocks);
+        assert_eq!(blocks, [hash.cr(Block::ONES), hash.cr(Block::ZERO)]);
+        assert_ne!(blocks[0], blocks[1]);
+    }
+
+    #[test]
+    fn test_crhash() {
+        check_tccr(&*FIXED_KEY_AES);
+        check_tccr(&Blake3Hash);
+        check_cr(&*FIXED_KEY_AES);
+        check_cr(&Blake3Hash);
+    }
+
+    #[test]
+    fn test_tweak_encoding() {
+        assert_eq!(tweak(1).to_bytes()[15], 1);
+        assert_eq!(
+            Blake3Hash.tccr(tweak(7), Block::ONES),
+            Blake3Hash.tccr_bytes(tweak(7), &Block::ONES.to_bytes())
+        );
+    }
+}
@@ -2,6 +2,8 @@
 //! Implementation of GGM based on the procedure explained in the write-up
 //! (<https://eprint.iacr.org/2020/925.pdf>, Page 14)
 
+use alloc::{vec, vec::Vec};
+
 use crate::{tkprp::TwoKeyPrp, Block};
 
 /// Struct of GGM
@@ -91,6 +93,137 @@ impl GgmTree {
         }
     }
 
+    /// Creates a GGM tree subtree by subtree, so that only one subtree is in memory at a time.
+    ///
+    /// The tree is split `split` levels below the root into `2^split` subtrees of depth
+    /// `depth - split`. The leaves of each subtree are passed to `f` in order, together with the
+    /// index of the subtree, such that the leaves of subtree `i` are the leaves
+    /// `i * 2^{depth - split}..(i + 1) * 2^{depth - split}` of the tree created by
+    /// [`GgmTree::gen`].
+    ///
+    /// Returns the subtrees, whose leaves can be expanded again from their roots.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - a seed.
+    /// * `split` - the depth at which the tree is split into subtrees.
+    /// * `k0` - XORs of all the left-node values in each level, with size `depth`.
+    /// * `k1`- XORs of all the right-node values in each level, with size `depth`.
+    /// * `f` - called with the index and the leaves of each subtree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `split` or `depth - split` is less than 2.
+    pub fn gen_chunked(
+        &self,
+        seed: Block,
+        split: usize,
+        k0: &mut [Block],
+        k1: &mut [Block],
+        mut f: impl FnMut(usize, &[Block]),
+    ) -> GgmSubtrees {
+        assert!(split >= 2 && self.depth >= split + 2);
+        assert_eq!(k0.len(), self.depth);
+        assert_eq!(k1.len(), self.depth);
+
+        let mut roots = vec![Block::ZERO; 1 << split];
+        GgmTree::new(split).gen(seed, &mut roots, &mut k0[..split], &mut k1[..split]);
+
+        let depth = self.depth - split;
+        let subtree = GgmTree::new(depth);
+        let mut leaves = vec![Block::ZERO; 1 << depth];
+        let mut sub_k0 = vec![Block::ZERO; depth];
+        let mut sub_k1 = vec![Block::ZERO; depth];
+
+        k0[split..].fill(Block::ZERO);
+        k1[split..].fill(Block::ZERO);
+        for (i, root) in roots.iter().enumerate() {
+            subtree.gen(*root, &mut leaves, &mut sub_k0, &mut sub_k1);
+            Block::xor_slice(&mut k0[split..], &sub_k0);
+            Block::xor_slice(&mut k1[split..], &sub_k1);
+            f(i, &leaves);
+        }
+
+        GgmSubtrees {
+            tree: subtree,
+            roots,
+            punctured: None,
+        }
+    }
+
+    /// Reconstructs a GGM tree subtree by subtree, except the value in a given position, see
+    /// [`GgmTree::gen_chunked`] and [`GgmTree::reconstruct`].
+    ///
+    /// The leaves of each subtree are passed to `f` together with the index of the subtree, the
+    /// subtree which contains the punctured position last. The punctured leaf is `Block::ZERO`.
+    ///
+    /// Returns the subtrees, whose leaves can be expanded again.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - a slice of blocks with length `depth`, see [`GgmTree::reconstruct`].
+    /// * `alpha` - a slice of bits with length `depth`.
+    /// * `split` - the depth at which the tree is split into subtrees.
+    /// * `f` - called with the index and the leaves of each subtree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `split` or `depth - split` is less than 2.
+    pub fn reconstruct_chunked(
+        &self,
+        k: &[Block],
+        alpha: &[bool],
+        split: usize,
+        mut f: impl FnMut(usize, &[Block]),
+    ) -> GgmSubtrees {
+        assert!(split >= 2 && self.depth >= split + 2);
+        assert_eq!(k.len(), self.depth);
+        assert_eq!(alpha.len(), self.depth);
+
+        let mut roots = vec![Block::ZERO; 1 << split];
+        GgmTree::new(split).reconstruct(&mut roots, &k[..split], &alpha[..split]);
+        let index = punctured_position(&alpha[..split]);
+
+        let depth = self.depth - split;
+        let subtree = GgmTree::new(depth);
+        let mut leaves = vec![Block::ZERO; 1 << depth];
+        let mut sub_k0 = vec![Block::ZERO; depth];
+        let mut sub_k1 = vec![Block::ZERO; depth];
+
+        // XORs of the left and right nodes of the subtrees which are not punctured.
+        let mut sum_k0 = vec![Block::ZERO; depth];
+        let mut sum_k1 = vec![Block::ZERO; depth];
+        for (i, root) in roots.iter().enumerate().filter(|(i, _)| *i != index) {
+            subtree.gen(*root, &mut leaves, &mut sub_k0, &mut sub_k1);
+            Block::xor_slice(&mut sum_k0, &sub_k0);
+            Block::xor_slice(&mut sum_k1, &sub_k1);
+            f(i, &leaves);
+        }
+
+        // The punctured subtree is reconstructed from the XORs of its own nodes in each level.
+        let k: Vec<Block> = k[split..]
+            .iter()
+            .zip(&alpha[split..])
+            .zip(sum_k0.iter().zip(&sum_k1))
+            .map(|((&k, &a), (&k0, &k1))| if a { k ^ k1 } else { k ^ k0 })
+            .collect();
+        let alpha = alpha[split..].to_vec();
+        subtree.reconstruct(&mut leaves, &k, &alpha);
+        f(index, &leaves);
+
+        GgmSubtrees {
+            tree: subtree,
+            roots,
+            punctured: Some(Punctured {
+                index,
+                pos: punctured_position(&alpha),
+                k,
+                alpha,
+                leaf: Block::ZERO,
+            }),
+        }
+    }
+
     // Handle each layer.
     fn reconstruct_layer(
         &self,
@@ -128,6 +261,83 @@ impl GgmTree {
     }
 }
 
+/// Returns the position which is punctured by [`GgmTree::reconstruct`].
+fn punctured_position(alpha: &[bool]) -> usize {
+    alpha.iter().fold(0, |pos, &a| (pos << 1) | usize::from(!a))
+}
+
+/// The subtrees of a GGM tree which was created or reconstructed subtree by subtree, see
+/// [`GgmTree::gen_chunked`].
+///
+/// Only the roots of the subtrees are stored, the leaves of a subtree are expanded on demand.
+pub struct GgmSubtrees {
+    tree: GgmTree,
+    roots: Vec<Block>,
+    punctured: Option<Punctured>,
+}
+
+opaque_debug::implement!(GgmSubtrees);
+
+/// The subtree which contains the punctured position of a reconstructed tree.
+struct Punctured {
+    /// Index of the subtree.
+    index: usize,
+    /// Punctured position in the subtree.
+    pos: usize,
+    /// The keys to reconstruct the subtree.
+    k: Vec<Block>,
+    /// The bits of the punctured position in the subtree.
+    alpha: Vec<bool>,
+    /// The value of the punctured leaf.
+    leaf: Block,
+}
+
+impl GgmSubtrees {
+    /// Returns the number of subtrees.
+    pub fn subtree_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns the number of leaves of each subtree.
+    pub fn subtree_len(&self) -> usize {
+        1 << self.tree.depth
+    }
+
+    /// Sets the value of the punctured leaf of a reconstructed tree, which is `Block::ZERO` by
+    /// default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree was not reconstructed.
+    pub fn set_punctured_leaf(&mut self, leaf: Block) {
+        self.punctured
+            .as_mut()
+            .expect("tree should be reconstructed")
+            .leaf = leaf;
+    }
+
+    /// Expands the leaves of a subtree.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the subtree.
+    /// * `leaves` - the destination to write the leaves, with size [`GgmSubtrees::subtree_len`].
+    pub fn expand(&self, index: usize, leaves: &mut [Block]) {
+        match &self.punctured {
+            Some(punctured) if punctured.index == index => {
+                self.tree
+                    .reconstruct(leaves, &punctured.k, &punctured.alpha);
+                leaves[punctured.pos] = punctured.leaf;
+            }
+            _ => {
+                let mut k0 = vec![Block::ZERO; self.tree.depth];
+                let mut k1 = vec![Block::ZERO; self.tree.depth];
+                self.tree.gen(self.roots[index], leaves, &mut k0, &mut k1);
+            }
+        }
+    }
+}
+
 #[test]
 fn ggm_test() {
     use crate::ggm_tree::GgmTree;
@@ -167,3 +377,63 @@ fn ggm_test() {
     tree_reconstruct[pos] = tree[pos];
     assert_eq!(tree, tree_reconstruct);
 }
+
+#[test]
+fn ggm_chunked_test() {
+    use crate::ggm_tree::GgmTree;
+    use crate::Block;
+
+    let (depth, split) = (7, 3);
+    let alpha = [true, false, false, true, true, false, true];
+    let pos = punctured_position(&alpha);
+    let chunk = 1 << (depth - split);
+
+    let ggm = GgmTree::new(depth);
+
+    let mut tree = vec![Block::ZERO; 1 << depth];
+    let mut k0 = vec![Block::ZERO; depth];
+    let mut k1 = vec![Block::ZERO; depth];
+    ggm.gen(Block::ONES, &mut tree, &mut k0, &mut k1);
+
+    let mut chunked = vec![Block::ZERO; 1 << depth];
+    let mut chunked_k0 = vec![Block::ZERO; depth];
+    let mut chunked_k1 = vec![Block::ZERO; depth];
+    let subtrees = ggm.gen_chunked(
+        Block::ONES,
+        split,
+        &mut chunked_k0,
+        &mut chunked_k1,
+        |i, leaves| chunked[i * chunk..(i + 1) * chunk].copy_from_slice(leaves),
+    );
+
+    assert_eq!(chunked, tree);
+    assert_eq!(chunked_k0, k0);
+    assert_eq!(chunked_k1, k1);
+
+    let mut leaves = vec![Block::ZERO; chunk];
+    subtrees.expand(5, &mut leaves);
+    assert_eq!(leaves, tree[5 * chunk..6 * chunk]);
+
+    let k: Vec<Block> = alpha
+        .iter()
+        .enumerate()
+        .map(|(i, &a)| if a { k1[i] } else { k0[i] })
+        .collect();
+
+    let mut reconstructed = vec![Block::ZERO; 1 << depth];
+    let mut order = Vec::new();
+    let mut subtrees = ggm.reconstruct_chunked(&k, &alpha, split, |i, leaves| {
+        order.push(i);
+        reconstructed[i * chunk..(i + 1) * chunk].copy_from_slice(leaves);
+    });
+
+    // The punctured subtree is passed last.
+    assert_eq!(order.last(), Some(&(pos / chunk)));
+    assert_eq!(reconstructed[pos], Block::ZERO);
+    reconstructed[pos] = tree[pos];
+    assert_eq!(reconstructed, tree);
+
+    subtrees.set_punctured_leaf(tree[pos]);
+    subtrees.expand(pos / chunk, &mut leaves);
+    assert_eq!(leaves, tree[pos / chunk * chunk..(pos / chunk + 1) * chunk]);
+}
@@ -3,16 +3,37 @@
 //! All types are serialized using [Binary Canonical Serialization (BCS)](https://docs.rs/bcs/latest/bcs/)
 //!
 //! Default implementations use [Blake3](https://docs.rs/blake3/latest/blake3/) as the hash function
+//!
+//! The hashing traits require the `std` feature.
 
+#[cfg(feature = "std")]
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
 
+#[cfg(feature = "std")]
 use crate::serialize::CanonicalSerialize;
 
 /// A secure hash
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Hashes are compared in constant time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Hash([u8; 32]);
 
+impl ConstantTimeEq for Hash {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for Hash {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Hash {}
+
 impl Hash {
     /// Returns the hash as a byte slice
     pub fn as_bytes(&self) -> &[u8; 32] {
@@ -27,6 +48,7 @@ impl From<[u8; 32]> for Hash {
 }
 
 /// A trait for hashing serde serializable types
+#[cfg(feature = "std")]
 pub trait SecureHash
 where
     Self: CanonicalSerialize,
@@ -37,9 +59,11 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> SecureHash for T where T: serde::Serialize {}
 
 /// A trait for hashing serde serializable types with a domain separator
+#[cfg(feature = "std")]
 pub trait DomainSeparatedHash
 where
     Self: serde::Serialize,
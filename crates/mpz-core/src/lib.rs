@@ -1,23 +1,45 @@
 //! Core types and utilities for MPC protocols
+//!
+//! # `no_std`
+//!
+//! Without the default `std` feature this crate is `no_std` and requires `alloc`. The
+//! `commit` and `serialize` modules are not available, randomness is sourced from
+//! [`getrandom`](https://docs.rs/getrandom) which must support the target, and the
+//! `critical-section` feature must be enabled to provide lazily initialized statics.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 
+extern crate alloc;
+// `thiserror` derives implement `std::error::Error`, which is `core::error::Error`.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
 pub mod aes;
+pub mod bits;
 pub mod block;
+#[cfg(feature = "std")]
 pub mod commit;
+pub mod crhash;
 pub mod ggm_tree;
 pub mod hash;
 pub mod lpn;
+#[cfg(feature = "pedersen")]
+pub mod pedersen;
+pub mod pool;
 pub mod prg;
 pub mod prp;
+#[cfg(feature = "std")]
 pub mod serialize;
 pub mod tkprp;
+pub mod transpose;
 pub mod utils;
 
-pub use block::{Block, BlockSerialize};
+pub use block::{Block, BlockSerialize, KeyBlock, MacBlock};
 
 /// A protocol with a message type.
 pub trait ProtocolMessage {
     /// The type of message used in the protocol.
-    type Msg: Send + Sync + std::fmt::Debug + 'static;
+    type Msg: Send + Sync + core::fmt::Debug + 'static;
 }
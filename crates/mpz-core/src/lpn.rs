@@ -1,8 +1,10 @@
 //! Implement LPN with local linear code.
 //! More specifically, a local linear code is a random boolean matrix with at most D non-zero values in each row.
 
-use crate::{prp::Prp, Block};
-use rand::{seq::SliceRandom, thread_rng};
+use crate::{prp::Prp, utils::rng, Block};
+use alloc::{vec, vec::Vec};
+use rand::seq::SliceRandom;
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 /// An LPN encoder.
 ///
@@ -17,6 +19,7 @@ use rayon::prelude::*;
 /// `e` - is a `F_{2^128}` vector with length `n`.
 ///
 /// Note that in the standard LPN problem, `x` is a binary vector, `e` is a sparse binary vector. The way we defined here is a more generic way in term of computing `y`.
+#[derive(Clone)]
 pub struct LpnEncoder<const D: usize> {
     /// The seed to generate the random sparse matrix A.
     seed: Block,
@@ -43,7 +46,7 @@ impl<const D: usize> LpnEncoder<D> {
     #[inline]
     fn compute_four_rows_indep(&self, y: &mut [Block], x: &[Block], pos: usize, prp: &Prp) {
         let mut cnt = 0u64;
-        let mut index: [Block; D] = std::array::from_fn(|_| {
+        let mut index: [Block; D] = core::array::from_fn(|_| {
             let i = cnt;
             cnt += 1;
             Block::from(bytemuck::cast::<_, [u8; 16]>([pos as u64, i]))
@@ -95,9 +98,9 @@ impl<const D: usize> LpnEncoder<D> {
         let size = y.len() - (y.len() % 4);
 
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")]{
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let iter = y.par_chunks_exact_mut(4).enumerate();
-            }else{
+            } else {
                 let iter = y.chunks_exact_mut(4).enumerate();
             }
         }
@@ -135,7 +138,7 @@ impl LpnParameters {
         let one: Block = bytemuck::cast(1_u128);
         let mut res = vec![Block::ZERO; self.n];
         res[0..self.t].iter_mut().for_each(|x| *x = one);
-        let mut rng = thread_rng();
+        let mut rng = rng();
         res.shuffle(&mut rng);
         res
     }
@@ -145,7 +148,7 @@ impl LpnParameters {
         assert_eq!(self.n % self.t, 0);
         let one: Block = bytemuck::cast(1_u128);
         let mut res = vec![Block::ZERO; self.n];
-        let mut rng = thread_rng();
+        let mut rng = rng();
 
         res.chunks_exact_mut(self.n / self.t).for_each(|x| {
             x[0] = one;
@@ -0,0 +1,171 @@
+//! Pedersen commitments over the Ristretto group.
+//!
+//! A commitment to a value `m` with blinding factor `r` is `m·G + r·H`, where `G` is the
+//! Ristretto basepoint and `H` is a second generator whose discrete logarithm relative to
+//! `G` is unknown. Commitments are perfectly hiding, computationally binding and additively
+//! homomorphic.
+
+use std::ops::Add;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_TABLE,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use once_cell::sync::Lazy;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::commit::CommitmentError;
+
+/// The second generator, derived by hashing to the group.
+static GENERATOR_H: Lazy<RistrettoPoint> = Lazy::new(|| {
+    let mut bytes = [0u8; 64];
+    blake3::Hasher::new_derive_key("mpz-core 2023 pedersen generator")
+        .finalize_xof()
+        .fill(&mut bytes);
+    RistrettoPoint::from_uniform_bytes(&bytes)
+});
+
+/// A Pedersen commitment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PedersenCommitment(RistrettoPoint);
+
+impl PedersenCommitment {
+    /// Returns the commitment as a point.
+    pub fn as_point(&self) -> &RistrettoPoint {
+        &self.0
+    }
+
+    /// Returns the compressed encoding of the commitment.
+    pub fn compress(&self) -> CompressedRistretto {
+        self.0.compress()
+    }
+}
+
+impl Add for PedersenCommitment {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+/// Opening of a Pedersen commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PedersenOpening {
+    value: Scalar,
+    blinding: Scalar,
+}
+
+impl PedersenOpening {
+    /// Creates a new opening with a random blinding factor.
+    pub fn new<R: RngCore + CryptoRng>(rng: &mut R, value: Scalar) -> Self {
+        Self {
+            value,
+            blinding: Scalar::random(rng),
+        }
+    }
+
+    /// Creates a new opening with the provided blinding factor.
+    pub fn new_with_blinding(value: Scalar, blinding: Scalar) -> Self {
+        Self { value, blinding }
+    }
+
+    /// Creates a Pedersen commitment.
+    pub fn commit(&self) -> PedersenCommitment {
+        PedersenCommitment(&self.value * RISTRETTO_BASEPOINT_TABLE + self.blinding * *GENERATOR_H)
+    }
+
+    /// Verifies that the provided commitment corresponds to this opening.
+    pub fn verify(&self, commitment: &PedersenCommitment) -> Result<(), CommitmentError> {
+        if commitment != &self.commit() {
+            return Err(CommitmentError::InvalidDecommitment);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the committed value.
+    pub fn value(&self) -> &Scalar {
+        &self.value
+    }
+
+    /// Returns the blinding factor.
+    pub fn blinding(&self) -> &Scalar {
+        &self.blinding
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for PedersenOpening {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.value);
+        zeroize::Zeroize::zeroize(&mut self.blinding);
+    }
+}
+
+impl Add for PedersenOpening {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            value: self.value + rhs.value,
+            blinding: self.blinding + rhs.blinding,
+        }
+    }
+}
+
+/// Creates a Pedersen commitment to a value, returning the opening and the commitment.
+pub fn pedersen_commit<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    value: Scalar,
+) -> (PedersenOpening, PedersenCommitment) {
+    let opening = PedersenOpening::new(rng, value);
+    let commitment = opening.commit();
+
+    (opening, commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha12Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_pedersen_commitment() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        let (opening, commitment) = pedersen_commit(&mut rng, Scalar::from(42u64));
+        opening.verify(&commitment).unwrap();
+
+        let wrong_value =
+            PedersenOpening::new_with_blinding(Scalar::from(43u64), *opening.blinding());
+        let err = wrong_value.verify(&commitment).unwrap_err();
+        assert!(matches!(err, CommitmentError::InvalidDecommitment));
+    }
+
+    #[test]
+    fn test_pedersen_commitment_hiding() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        let (_, a) = pedersen_commit(&mut rng, Scalar::ONE);
+        let (_, b) = pedersen_commit(&mut rng, Scalar::ONE);
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_pedersen_commitment_homomorphic() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        let (opening_a, a) = pedersen_commit(&mut rng, Scalar::from(2u64));
+        let (opening_b, b) = pedersen_commit(&mut rng, Scalar::from(3u64));
+
+        let opening = opening_a + opening_b;
+        assert_eq!(opening.value(), &Scalar::from(5u64));
+        opening.verify(&(a + b)).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! Pools of reusable buffers.
+//!
+//! OT extension and garbling allocate large buffers for every batch, which puts a significant
+//! load on the allocator. Buffers can be taken from a pool and returned to it once they are no
+//! longer needed, so that the allocation is reused by the next batch.
+//!
+//! With the `zeroize` feature enabled, buffers are zeroized when they are returned to a pool.
+//!
+//! Without the `std` feature pools do not retain any buffers, so every buffer is freshly
+//! allocated.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use bytemuck::Zeroable;
+
+use crate::Block;
+
+/// Process-wide pool of [`Block`] buffers.
+pub static BLOCK_POOL: BufferPool<Block> =
+    BufferPool::new(BufferPool::<Block>::DEFAULT_MAX_BUFFERS);
+
+/// Process-wide pool of byte buffers.
+pub static BYTE_POOL: BufferPool<u8> = BufferPool::new(BufferPool::<u8>::DEFAULT_MAX_BUFFERS);
+
+/// A pool of reusable buffers.
+#[derive(Debug)]
+pub struct BufferPool<T> {
+    #[cfg(feature = "std")]
+    buffers: Mutex<Vec<Vec<T>>>,
+    #[cfg(not(feature = "std"))]
+    _pd: core::marker::PhantomData<T>,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    max_buffers: usize,
+}
+
+impl<T> Default for BufferPool<T> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_BUFFERS)
+    }
+}
+
+impl<T> BufferPool<T> {
+    /// Default maximum number of buffers retained by a pool.
+    pub const DEFAULT_MAX_BUFFERS: usize = 16;
+
+    /// Creates a new pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_buffers` - The maximum number of buffers retained by the pool.
+    pub const fn new(max_buffers: usize) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            buffers: Mutex::new(Vec::new()),
+            #[cfg(not(feature = "std"))]
+            _pd: core::marker::PhantomData,
+            max_buffers,
+        }
+    }
+
+    /// Returns the number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "std")]
+        {
+            self.buffers.lock().unwrap().len()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            0
+        }
+    }
+
+    /// Returns `true` if the pool contains no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> BufferPool<T>
+where
+    T: Copy + Zeroable,
+{
+    /// Returns a zeroed buffer of the provided length.
+    ///
+    /// The smallest pooled buffer which is large enough is reused, otherwise a new buffer is
+    /// allocated.
+    pub fn take(&self, len: usize) -> Vec<T> {
+        #[cfg(feature = "std")]
+        let mut buf = {
+            let mut buffers = self.buffers.lock().unwrap();
+            buffers
+                .iter()
+                .enumerate()
+                .filter(|(_, buf)| buf.capacity() >= len)
+                .min_by_key(|(_, buf)| buf.capacity())
+                .map(|(idx, _)| idx)
+                .map(|idx| buffers.swap_remove(idx))
+                .unwrap_or_else(|| Vec::with_capacity(len))
+        };
+        #[cfg(not(feature = "std"))]
+        let mut buf = Vec::with_capacity(len);
+
+        buf.resize(len, T::zeroed());
+        buf
+    }
+
+    /// Returns a buffer to the pool.
+    ///
+    /// If the pool is full the smallest buffer is dropped.
+    pub fn put(&self, mut buf: Vec<T>) {
+        if buf.capacity() == 0 {
+            return;
+        }
+
+        #[cfg(feature = "zeroize")]
+        {
+            buf.fill(T::zeroed());
+            core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        }
+        buf.clear();
+
+        #[cfg(feature = "std")]
+        {
+            let mut buffers = self.buffers.lock().unwrap();
+            if buffers.len() < self.max_buffers {
+                buffers.push(buf);
+            } else if let Some(smallest) = buffers
+                .iter_mut()
+                .min_by_key(|pooled| pooled.capacity())
+                .filter(|pooled| pooled.capacity() < buf.capacity())
+            {
+                *smallest = buf;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_reuses_buffers() {
+        let pool = BufferPool::<Block>::new(2);
+
+        let mut buf = pool.take(1024);
+        buf.fill(Block::ONES);
+        let ptr = buf.as_ptr();
+        pool.put(buf);
+
+        assert_eq!(pool.len(), 1);
+
+        let buf = pool.take(512);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(buf.len(), 512);
+        assert!(buf.iter().all(|block| *block == Block::ZERO));
+        assert!(pool.is_empty());
+
+        // A buffer which is too small is not reused.
+        pool.put(buf);
+        let larger = pool.take(2048);
+        assert_ne!(larger.as_ptr(), ptr);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_pool_retains_largest_buffers() {
+        let pool = BufferPool::<u8>::new(2);
+
+        pool.put(Vec::with_capacity(16));
+        pool.put(Vec::with_capacity(32));
+        pool.put(Vec::with_capacity(64));
+        pool.put(Vec::with_capacity(8));
+
+        let mut capacities: Vec<_> = pool
+            .buffers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|buf| buf.capacity())
+            .collect();
+        capacities.sort();
+
+        assert_eq!(capacities, vec![32, 64]);
+    }
+}
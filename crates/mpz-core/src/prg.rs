@@ -1,9 +1,13 @@
 //! Implement AES-based PRG.
+//!
+//! PRGs implementing [`PrgBackend`] can be forked into independent child streams, which
+//! allows parallel tasks to each own a deterministic stream derived from a single seed.
 
-use std::collections::HashMap;
+use alloc::collections::BTreeMap;
 
-use crate::{aes::AesEncryptor, Block};
+use crate::{aes::AesEncryptor, utils::rng, Block};
 use rand::Rng;
+use rand_chacha::ChaCha12Rng;
 use rand_core::{
     block::{BlockRng, BlockRngCore},
     CryptoRng, RngCore, SeedableRng,
@@ -12,9 +16,10 @@ use rand_core::{
 /// Struct of PRG Core
 #[derive(Clone)]
 struct PrgCore {
+    seed: Block,
     aes: AesEncryptor,
     // Stores the counter for each stream id.
-    state: HashMap<u64, u64>,
+    state: BTreeMap<u64, u64>,
     stream_id: u64,
     counter: u64,
 }
@@ -51,6 +56,7 @@ impl SeedableRng for PrgCore {
     fn from_seed(seed: Self::Seed) -> Self {
         let aes = AesEncryptor::new(seed);
         Self {
+            seed,
             aes,
             state: Default::default(),
             stream_id: 0u64,
@@ -61,6 +67,33 @@ impl SeedableRng for PrgCore {
 
 impl CryptoRng for PrgCore {}
 
+#[cfg(feature = "zeroize")]
+impl Drop for PrgCore {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.seed);
+    }
+}
+
+/// A PRG which can be forked into independent child streams.
+pub trait PrgBackend: RngCore + CryptoRng + SeedableRng + Clone + Send + Sync {
+    /// Returns a child PRG whose stream is derived from the seed of this PRG and the label.
+    ///
+    /// The child stream does not depend on the state of this PRG, so forking with the same
+    /// label always returns the same stream.
+    fn fork(&self, label: &[u8]) -> Self;
+}
+
+/// Derives the seed of a child stream.
+fn derive_seed<const N: usize>(context: &str, seed: &[u8], label: &[u8]) -> [u8; N] {
+    let mut hasher = blake3::Hasher::new_derive_key(context);
+    hasher.update(seed);
+    hasher.update(label);
+
+    let mut child = [0u8; N];
+    hasher.finalize_xof().fill(&mut child);
+    child
+}
+
 /// AES-based PRG.
 ///
 /// This PRG is based on AES128 used in counter-mode to generate pseudo-random data streams.
@@ -111,11 +144,22 @@ impl SeedableRng for Prg {
 
 impl CryptoRng for Prg {}
 
+impl PrgBackend for Prg {
+    fn fork(&self, label: &[u8]) -> Self {
+        let seed = derive_seed::<16>(
+            "mpz-core 2023 aes prg fork",
+            &self.0.core.seed.to_bytes(),
+            label,
+        );
+        Prg::from_seed(seed.into())
+    }
+}
+
 impl Prg {
     /// New Prg with random seed.
     #[inline(always)]
     pub fn new() -> Self {
-        Prg::from_seed(rand::random::<Block>())
+        Prg::from_seed(rng().gen::<Block>())
     }
 
     /// Returns the current counter.
@@ -175,6 +219,41 @@ impl Prg {
         let bytes: &mut [u8] = bytemuck::cast_slice_mut(buf);
         self.fill_bytes(bytes);
     }
+
+    /// Fill a block slice with random block values, generating them in pipelined AES batches.
+    ///
+    /// Whole batches of [`AesEncryptor::AES_BLOCK_COUNT`] blocks are encrypted directly into `buf`,
+    /// which is faster than [`Prg::random_blocks`] for large slices. The output is the same as
+    /// [`Prg::random_blocks`], unless the PRG was previously used to generate values which are not
+    /// a multiple of a block, in which case the remainder of the partially used block is
+    /// discarded.
+    pub fn random_blocks_into(&mut self, mut buf: &mut [Block]) {
+        const RESULTS_LEN: usize = 4 * AesEncryptor::AES_BLOCK_COUNT;
+
+        // Use up the buffered blocks first so that the stream is not changed.
+        while !buf.is_empty() && self.0.index() + 4 <= RESULTS_LEN {
+            let (block, rest) = buf.split_at_mut(1);
+            self.random_blocks(block);
+            buf = rest;
+        }
+
+        if buf.is_empty() {
+            return;
+        }
+
+        // Discard the remainder of a partially used block.
+        self.0.reset();
+
+        let mut batches = buf.chunks_exact_mut(AesEncryptor::AES_BLOCK_COUNT);
+        for batch in &mut batches {
+            let results: &mut [u32] = bytemuck::cast_slice_mut(batch);
+            self.0
+                .core
+                .generate(results.try_into().expect("batch should fill the results"));
+        }
+
+        self.random_blocks(batches.into_remainder());
+    }
 }
 
 impl Default for Prg {
@@ -184,6 +263,83 @@ impl Default for Prg {
     }
 }
 
+/// ChaCha-based PRG.
+///
+/// This PRG uses ChaCha with 12 rounds, which is faster than [`Prg`] on platforms without
+/// hardware AES support.
+#[derive(Clone)]
+pub struct ChaChaPrg(ChaCha12Rng);
+
+opaque_debug::implement!(ChaChaPrg);
+
+impl RngCore for ChaChaPrg {
+    #[inline(always)]
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    #[inline(always)]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    #[inline(always)]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl SeedableRng for ChaChaPrg {
+    type Seed = [u8; 32];
+
+    #[inline(always)]
+    fn from_seed(seed: Self::Seed) -> Self {
+        ChaChaPrg(ChaCha12Rng::from_seed(seed))
+    }
+}
+
+impl CryptoRng for ChaChaPrg {}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ChaChaPrg {
+    fn drop(&mut self) {
+        // `ChaCha12Rng` does not implement `Zeroize`, so its state is overwritten with the
+        // state of an all-zero seed instead.
+        //
+        // SAFETY: `self.0` is valid for writes, and `ChaCha12Rng` does not implement `Drop`
+        // so overwriting it without dropping does not leak.
+        unsafe { core::ptr::write_volatile(&mut self.0, ChaCha12Rng::from_seed([0u8; 32])) };
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl PrgBackend for ChaChaPrg {
+    fn fork(&self, label: &[u8]) -> Self {
+        let seed = derive_seed::<32>("mpz-core 2023 chacha prg fork", &self.0.get_seed(), label);
+        ChaChaPrg::from_seed(seed)
+    }
+}
+
+impl ChaChaPrg {
+    /// New ChaChaPrg with random seed.
+    #[inline(always)]
+    pub fn new() -> Self {
+        ChaChaPrg::from_seed(rng().gen())
+    }
+}
+
+impl Default for ChaChaPrg {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +379,57 @@ mod tests {
 
         assert_eq!(prg.counter(), counter);
     }
+
+    #[test]
+    fn test_prg_random_blocks_into() {
+        let mut expected = Prg::from_seed(Block::ZERO);
+        let mut prg = Prg::from_seed(Block::ZERO);
+
+        // Interleave with single blocks so that the buffered output is not aligned to a batch.
+        for len in [0, 3, 8, 21, 64, 1] {
+            let mut x = vec![Block::ZERO; len];
+            let mut y = vec![Block::ZERO; len];
+            expected.random_blocks(&mut x);
+            prg.random_blocks_into(&mut y);
+
+            assert_eq!(x, y);
+            assert_eq!(expected.random_block(), prg.random_block());
+        }
+
+        // A partially used block is discarded.
+        prg.random_byte();
+        let mut y = vec![Block::ZERO; 16];
+        prg.random_blocks_into(&mut y);
+        assert_ne!(y[0], y[1]);
+    }
+
+    fn fork_is_deterministic<P: PrgBackend>(prg: P) {
+        let mut parent = prg.clone();
+        let mut a = prg.fork(b"a");
+        let mut b = prg.fork(b"b");
+
+        let x: [u8; 32] = a.gen();
+        let y: [u8; 32] = b.gen();
+        let z: [u8; 32] = parent.gen();
+
+        assert_ne!(x, y);
+        assert_ne!(x, z);
+
+        // Advancing the parent does not change the forked streams.
+        assert_eq!(parent.fork(b"a").gen::<[u8; 32]>(), x);
+    }
+
+    #[test]
+    fn test_prg_fork() {
+        fork_is_deterministic(Prg::new());
+        fork_is_deterministic(ChaChaPrg::new());
+    }
+
+    #[test]
+    fn test_prg_fork_seed_dependent() {
+        let mut a = Prg::from_seed(Block::ZERO).fork(b"ggm");
+        let mut b = Prg::from_seed(Block::ONES).fork(b"ggm");
+
+        assert_ne!(a.random_block(), b.random_block());
+    }
 }
@@ -1,5 +1,13 @@
 //! Traits for canonical serialization of serde serializable types.
 
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Version of the wire format, see [`WireMessage`].
+pub const WIRE_FORMAT_VERSION: u8 = 1;
+
+/// Length of the wire message header in bytes.
+const HEADER_LEN: usize = 1 + 4;
+
 /// A trait for canonical serialization of serde serializable types.
 ///
 /// This trait provides a default implementation which uses
@@ -12,3 +20,203 @@ pub trait CanonicalSerialize: serde::Serialize {
 }
 
 impl<T> CanonicalSerialize for T where T: serde::Serialize {}
+
+/// An error that can occur when decoding a wire message.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum WireError {
+    #[error("unexpected end of message")]
+    UnexpectedEof,
+    #[error("unsupported wire format version {0}, expected {WIRE_FORMAT_VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("header length {expected} does not match payload length {actual}")]
+    LengthMismatch { expected: usize, actual: usize },
+    #[error("invalid message: {0}")]
+    InvalidMessage(#[from] bcs::Error),
+}
+
+/// A protocol message with a canonical wire encoding.
+///
+/// A message is encoded as the wire format version byte, followed by the payload length as a
+/// little-endian `u32`, followed by the payload which is the
+/// [BCS](https://docs.rs/bcs/latest/bcs/) encoding of the message. The encoding does not depend
+/// on the serde backend picked by the transport, so independently built binaries interoperate.
+pub trait WireMessage: Serialize + DeserializeOwned {
+    /// Encodes the message.
+    fn to_wire(&self) -> Vec<u8> {
+        let payload = bcs::to_bytes(self).expect("serialization should not fail");
+        let len = u32::try_from(payload.len()).expect("message should be less than 4GiB");
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.push(WIRE_FORMAT_VERSION);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    /// Decodes a message.
+    fn from_wire(bytes: &[u8]) -> Result<Self, WireError> {
+        Ok(bcs::from_bytes(wire_payload(bytes)?)?)
+    }
+}
+
+/// Returns the payload of a wire message after checking its header, see [`WireMessage`].
+///
+/// This allows messages with a fixed layout to be read in place, without deserializing them.
+pub fn wire_payload(bytes: &[u8]) -> Result<&[u8], WireError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(WireError::UnexpectedEof);
+    }
+
+    let (header, payload) = bytes.split_at(HEADER_LEN);
+    if header[0] != WIRE_FORMAT_VERSION {
+        return Err(WireError::UnsupportedVersion(header[0]));
+    }
+
+    let len = u32::from_le_bytes(header[1..].try_into().unwrap()) as usize;
+    if len != payload.len() {
+        return Err(WireError::LengthMismatch {
+            expected: len,
+            actual: payload.len(),
+        });
+    }
+
+    Ok(payload)
+}
+
+/// An error that can occur when decoding a protobuf message.
+#[cfg(feature = "proto")]
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ProtoError {
+    #[error("invalid protobuf encoding: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[error("missing field: {0}")]
+    MissingField(&'static str),
+    #[error("invalid field {field}: {reason}")]
+    InvalidField { field: &'static str, reason: String },
+}
+
+#[cfg(feature = "proto")]
+impl ProtoError {
+    /// Creates a new invalid field error.
+    pub fn invalid_field(field: &'static str, reason: impl ToString) -> Self {
+        Self::InvalidField {
+            field,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// A protocol message with a protobuf encoding.
+///
+/// This is an alternative to [`WireMessage`] for implementations which are not written in Rust,
+/// or middleboxes which need to parse protocol traffic. The schemas are published alongside the
+/// crates which define the messages.
+#[cfg(feature = "proto")]
+pub trait ProtoMessage: Sized {
+    /// The protobuf representation of the message.
+    type Proto: prost::Message + Default;
+
+    /// Converts the message into its protobuf representation.
+    fn to_proto(&self) -> Self::Proto;
+
+    /// Converts the message from its protobuf representation.
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError>;
+
+    /// Encodes the message using protobuf.
+    fn encode_proto(&self) -> Vec<u8> {
+        prost::Message::encode_to_vec(&self.to_proto())
+    }
+
+    /// Decodes a message encoded using protobuf.
+    fn decode_proto(bytes: &[u8]) -> Result<Self, ProtoError> {
+        Self::from_proto(<Self::Proto as prost::Message>::decode(bytes)?)
+    }
+}
+
+/// Encodes blocks as their concatenated bytes.
+#[cfg(feature = "proto")]
+pub fn blocks_to_bytes(blocks: impl IntoIterator<Item = crate::Block>) -> Vec<u8> {
+    blocks
+        .into_iter()
+        .flat_map(|block| block.to_bytes())
+        .collect()
+}
+
+/// Decodes blocks from their concatenated bytes.
+///
+/// # Arguments
+///
+/// * `field` - The name of the field, used for error reporting.
+/// * `bytes` - The bytes to decode.
+#[cfg(feature = "proto")]
+pub fn blocks_from_bytes(
+    field: &'static str,
+    bytes: &[u8],
+) -> Result<Vec<crate::Block>, ProtoError> {
+    if !bytes.len().is_multiple_of(16) {
+        return Err(ProtoError::invalid_field(
+            field,
+            format!("length {} is not a multiple of 16", bytes.len()),
+        ));
+    }
+
+    Ok(bytes
+        .chunks_exact(16)
+        .map(|chunk| crate::Block::new(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::Block;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        id: u64,
+        blocks: Vec<Block>,
+    }
+
+    impl WireMessage for Message {}
+
+    #[test]
+    fn test_wire_message_roundtrip() {
+        let msg = Message {
+            id: 42,
+            blocks: vec![Block::ONES, Block::ZERO],
+        };
+
+        let bytes = msg.to_wire();
+
+        assert_eq!(bytes[0], WIRE_FORMAT_VERSION);
+        assert_eq!(Message::from_wire(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_wire_message_errors() {
+        let mut bytes = Message {
+            id: 42,
+            blocks: vec![Block::ONES],
+        }
+        .to_wire();
+
+        assert!(matches!(
+            Message::from_wire(&bytes[..3]),
+            Err(WireError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Message::from_wire(&bytes[..bytes.len() - 1]),
+            Err(WireError::LengthMismatch { .. })
+        ));
+
+        bytes[0] = WIRE_FORMAT_VERSION + 1;
+        assert!(matches!(
+            Message::from_wire(&bytes),
+            Err(WireError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+//! Bit-matrix transposition.
+//!
+//! Matrices are stored in row-major order with an LSB0 bit encoding, ie bit `j` of a row is
+//! bit `j % 8` of byte `j / 8` of the row.
+//!
+//! On `x86_64` the transpose uses AVX2 when it is detected at runtime, and SSE2 otherwise. Without
+//! the `std` feature AVX2 is only used if it is enabled at compile time.
+//! Other targets use a portable implementation which transposes 8x8 bit blocks in a `u64`.
+
+use crate::pool::BYTE_POOL;
+
+/// An error that can occur when transposing a bit-matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum TransposeError {
+    #[error("number of rows must be a non-zero multiple of 8, got {0}")]
+    InvalidRows(usize),
+    #[error("number of columns must be a non-zero multiple of 8, got {0}")]
+    InvalidColumns(usize),
+    #[error("matrix of {len} bytes is not rectangular with {rows} rows")]
+    MalformedMatrix { len: usize, rows: usize },
+}
+
+/// Transposes a bit-matrix in place.
+///
+/// After transposition the matrix has `columns` rows, each of `rows / 8` bytes.
+///
+/// # Arguments
+///
+/// * `matrix` - The matrix, consisting of `rows` rows of equal length.
+/// * `rows` - The number of rows, which must be a multiple of 8.
+pub fn transpose_bits(matrix: &mut [u8], rows: usize) -> Result<(), TransposeError> {
+    let mut input = BYTE_POOL.take(matrix.len());
+    input.copy_from_slice(matrix);
+    let res = transpose_bits_into(&input, rows, matrix);
+    BYTE_POOL.put(input);
+    res
+}
+
+/// Transposes a bit-matrix into the provided buffer.
+///
+/// # Arguments
+///
+/// * `input` - The matrix, consisting of `rows` rows of equal length.
+/// * `rows` - The number of rows, which must be a multiple of 8.
+/// * `output` - The buffer to write the transposed matrix to, which must have the same
+///   length as `input`.
+pub fn transpose_bits_into(
+    input: &[u8],
+    rows: usize,
+    output: &mut [u8],
+) -> Result<(), TransposeError> {
+    let columns = validate(input, rows)?;
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "output must have the same length"
+    );
+
+    let mut row = 0;
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if has_avx2() {
+            while row + 32 <= rows {
+                // SAFETY: AVX2 support was detected above.
+                unsafe { x86::transpose_strip_avx2(input, output, rows, columns, row) };
+                row += 32;
+            }
+        }
+
+        while row + 16 <= rows {
+            x86::transpose_strip_sse2(input, output, rows, columns, row);
+            row += 16;
+        }
+    }
+
+    while row < rows {
+        transpose_strip_portable(input, output, rows, columns, row);
+        row += 8;
+    }
+
+    Ok(())
+}
+
+/// Returns whether AVX2 is available.
+#[cfg(target_arch = "x86_64")]
+fn has_avx2() -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")] {
+            std::arch::is_x86_feature_detected!("avx2")
+        } else {
+            cfg!(target_feature = "avx2")
+        }
+    }
+}
+
+/// Validates the shape of a matrix, returning the number of columns.
+fn validate(matrix: &[u8], rows: usize) -> Result<usize, TransposeError> {
+    if rows == 0 || !rows.is_multiple_of(8) {
+        return Err(TransposeError::InvalidRows(rows));
+    }
+
+    if !matrix.len().is_multiple_of(rows) {
+        return Err(TransposeError::MalformedMatrix {
+            len: matrix.len(),
+            rows,
+        });
+    }
+
+    let columns = matrix.len() / rows * 8;
+    if columns == 0 {
+        return Err(TransposeError::InvalidColumns(columns));
+    }
+
+    Ok(columns)
+}
+
+/// Transposes 8 rows starting at `row` into 8-bit columns of the output.
+fn transpose_strip_portable(
+    input: &[u8],
+    output: &mut [u8],
+    rows: usize,
+    columns: usize,
+    row: usize,
+) {
+    let row_bytes = columns / 8;
+    let col_bytes = rows / 8;
+
+    for byte in 0..row_bytes {
+        // Byte `k` holds 8 columns of row `row + k`.
+        let mut x = u64::from_le_bytes(core::array::from_fn(|k| {
+            input[(row + k) * row_bytes + byte]
+        }));
+
+        // Transpose the 8x8 block, where element (k, i) is bit 8k + i.
+        let t = (x ^ (x >> 7)) & 0x00AA_00AA_00AA_00AA;
+        x ^= t ^ (t << 7);
+        let t = (x ^ (x >> 14)) & 0x0000_CCCC_0000_CCCC;
+        x ^= t ^ (t << 14);
+        let t = (x ^ (x >> 28)) & 0x0000_0000_F0F0_F0F0;
+        x ^= t ^ (t << 28);
+
+        for (i, b) in x.to_le_bytes().into_iter().enumerate() {
+            output[(byte * 8 + i) * col_bytes + row / 8] = b;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use core::arch::x86_64::*;
+
+    /// Transposes 16 rows starting at `row` into 16-bit columns of the output.
+    pub(super) fn transpose_strip_sse2(
+        input: &[u8],
+        output: &mut [u8],
+        rows: usize,
+        columns: usize,
+        row: usize,
+    ) {
+        let row_bytes = columns / 8;
+        let col_bytes = rows / 8;
+
+        for byte in 0..row_bytes {
+            let gathered: [u8; 16] = core::array::from_fn(|k| input[(row + k) * row_bytes + byte]);
+
+            // SAFETY: SSE2 is part of the x86_64 baseline, and the load is unaligned.
+            unsafe {
+                let mut vec = _mm_loadu_si128(gathered.as_ptr() as *const __m128i);
+                // The most significant bit of each byte is column `byte * 8 + i`.
+                for i in (0..8).rev() {
+                    let mask = (_mm_movemask_epi8(vec) as u16).to_le_bytes();
+                    let offset = (byte * 8 + i) * col_bytes + row / 8;
+                    output[offset..offset + 2].copy_from_slice(&mask);
+                    vec = _mm_slli_epi64(vec, 1);
+                }
+            }
+        }
+    }
+
+    /// Transposes 32 rows starting at `row` into 32-bit columns of the output.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn transpose_strip_avx2(
+        input: &[u8],
+        output: &mut [u8],
+        rows: usize,
+        columns: usize,
+        row: usize,
+    ) {
+        let row_bytes = columns / 8;
+        let col_bytes = rows / 8;
+
+        for byte in 0..row_bytes {
+            let gathered: [u8; 32] = core::array::from_fn(|k| input[(row + k) * row_bytes + byte]);
+
+            let mut vec = _mm256_loadu_si256(gathered.as_ptr() as *const __m256i);
+            for i in (0..8).rev() {
+                let mask = (_mm256_movemask_epi8(vec) as u32).to_le_bytes();
+                let offset = (byte * 8 + i) * col_bytes + row / 8;
+                output[offset..offset + 4].copy_from_slice(&mask);
+                vec = _mm256_slli_epi64(vec, 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itybity::{FromBitIterator, ToBits};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn transpose_naive(matrix: &[u8], rows: usize) -> Vec<u8> {
+        let row_bytes = matrix.len() / rows;
+        let bits: Vec<Vec<bool>> = matrix
+            .chunks(row_bytes)
+            .map(|row| row.to_lsb0_vec())
+            .collect();
+
+        (0..row_bytes * 8)
+            .flat_map(|col| Vec::<u8>::from_lsb0_iter(bits.iter().map(|row| row[col])))
+            .collect()
+    }
+
+    fn random_matrix(rng: &mut StdRng, rows: usize, row_bytes: usize) -> Vec<u8> {
+        (0..rows * row_bytes).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_transpose_bits() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // Cover every combination of 32, 16 and 8 row strips.
+        for rows in [8, 16, 24, 32, 40, 48, 56, 64, 128, 136] {
+            for row_bytes in [1, 2, 3, 16, 33] {
+                let matrix = random_matrix(&mut rng, rows, row_bytes);
+                let expected = transpose_naive(&matrix, rows);
+
+                let mut transposed = matrix.clone();
+                transpose_bits(&mut transposed, rows).unwrap();
+
+                assert_eq!(transposed, expected, "rows: {rows}, row bytes: {row_bytes}");
+
+                // Transposing twice returns the original matrix.
+                transpose_bits(&mut transposed, row_bytes * 8).unwrap();
+                assert_eq!(transposed, matrix);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpose_backends() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let rows = 128;
+        let columns = 1024;
+        let matrix = random_matrix(&mut rng, rows, columns / 8);
+        let expected = transpose_naive(&matrix, rows);
+
+        let mut output = vec![0u8; matrix.len()];
+        for row in (0..rows).step_by(8) {
+            transpose_strip_portable(&matrix, &mut output, rows, columns, row);
+        }
+        assert_eq!(output, expected);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            let mut output = vec![0u8; matrix.len()];
+            for row in (0..rows).step_by(16) {
+                x86::transpose_strip_sse2(&matrix, &mut output, rows, columns, row);
+            }
+            assert_eq!(output, expected);
+
+            if std::arch::is_x86_feature_detected!("avx2") {
+                let mut output = vec![0u8; matrix.len()];
+                for row in (0..rows).step_by(32) {
+                    unsafe { x86::transpose_strip_avx2(&matrix, &mut output, rows, columns, row) };
+                }
+                assert_eq!(output, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpose_invalid() {
+        let mut matrix = vec![0u8; 64];
+
+        assert_eq!(
+            transpose_bits(&mut matrix, 0),
+            Err(TransposeError::InvalidRows(0))
+        );
+        assert_eq!(
+            transpose_bits(&mut matrix, 12),
+            Err(TransposeError::InvalidRows(12))
+        );
+        assert_eq!(
+            transpose_bits(&mut matrix, 128),
+            Err(TransposeError::MalformedMatrix { len: 64, rows: 128 })
+        );
+        assert_eq!(
+            transpose_bits(&mut matrix[..60], 8),
+            Err(TransposeError::MalformedMatrix { len: 60, rows: 8 })
+        );
+    }
+}
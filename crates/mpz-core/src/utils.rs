@@ -1,8 +1,36 @@
 //! Utilities for MPC protocols
 
+/// Returns a cryptographically secure RNG seeded with entropy from the OS.
+///
+/// This is [`rand::thread_rng`] if the `std` feature is enabled, otherwise it is
+/// [`rand::rngs::OsRng`].
+#[cfg(feature = "std")]
+pub fn rng() -> rand::rngs::ThreadRng {
+    rand::thread_rng()
+}
+
+/// Returns a cryptographically secure RNG seeded with entropy from the OS.
+///
+/// This is `rand::thread_rng` if the `std` feature is enabled, otherwise it is
+/// [`rand::rngs::OsRng`].
+#[cfg(not(feature = "std"))]
+pub fn rng() -> rand::rngs::OsRng {
+    rand::rngs::OsRng
+}
+
 /// Returns the blake3 hash of the given data.
 pub fn blake3(data: &[u8]) -> [u8; 32] {
     let mut hasher = blake3::Hasher::new();
     hasher.update(data);
     hasher.finalize().into()
 }
+
+/// Computes `a[i] ^= b[i]` for all `i`.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+#[inline]
+pub fn xor_bytes(a: &mut [u8], b: &[u8]) {
+    crate::block::ops::xor(a, b)
+}
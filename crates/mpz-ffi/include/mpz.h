@@ -0,0 +1,94 @@
+/*
+ * C bindings for the mpz protocols.
+ *
+ * See the documentation of the `mpz-ffi` crate for details.
+ */
+
+#ifndef MPZ_H
+#define MPZ_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Size of a block in bytes. */
+#define MPZ_BLOCK_LEN 16
+
+/* Status code returned by every function of this library. */
+typedef enum MpzStatus {
+    MPZ_STATUS_OK = 0,
+    MPZ_STATUS_NULL_POINTER = 1,
+    MPZ_STATUS_INVALID_INPUT = 2,
+    MPZ_STATUS_IO = 3,
+    MPZ_STATUS_PROTOCOL = 4,
+    MPZ_STATUS_PANIC = 5,
+} MpzStatus;
+
+/* Sends `len` bytes from `data` to the peer. Returns 0 on success. */
+typedef int32_t (*MpzSendFn)(void *ctx, const uint8_t *data, size_t len);
+
+/*
+ * Receives exactly `len` bytes from the peer into `data`, blocking until they are
+ * available. Returns 0 on success.
+ */
+typedef int32_t (*MpzRecvFn)(void *ctx, uint8_t *data, size_t len);
+
+/* Byte-buffer I/O callbacks used to communicate with the peer. */
+typedef struct MpzIo {
+    void *ctx;
+    MpzSendFn send;
+    MpzRecvFn recv;
+} MpzIo;
+
+/* A byte buffer allocated by this library, released with `mpz_buffer_free`. */
+typedef struct MpzBuffer {
+    uint8_t *ptr;
+    size_t len;
+} MpzBuffer;
+
+/* Releases a buffer allocated by this library. */
+void mpz_buffer_free(MpzBuffer *buffer);
+
+/*
+ * Returns a description of the last error on the calling thread, or NULL. The string is
+ * valid until the next call into this library on the same thread.
+ */
+const char *mpz_last_error(void);
+
+/* Base OT (CO15). `msgs` holds `count` pairs `m0 || m1` of 16 byte messages. */
+MpzStatus mpz_ot_send(const MpzIo *io, const uint8_t *msgs, size_t count);
+
+/* Base OT (CO15). `choices` holds `count` bytes of 0 or 1, `out` receives `count * 16` bytes. */
+MpzStatus mpz_ot_receive(const MpzIo *io, const uint8_t *choices, size_t count, uint8_t *out);
+
+/* OT extension (KOS15). Same layout as `mpz_ot_send`. */
+MpzStatus mpz_ot_ext_send(const MpzIo *io, const uint8_t *msgs, size_t count);
+
+/* OT extension (KOS15). Same layout as `mpz_ot_receive`. */
+MpzStatus mpz_ot_ext_receive(const MpzIo *io, const uint8_t *choices, size_t count,
+                             uint8_t *out);
+
+/*
+ * Garbles a circuit as the generator.
+ *
+ * `circuit` is in the stable binary encoding of `mpz-circuits`. `owners` holds one byte
+ * per circuit input, 0 for the generator and 1 for the evaluator. `bits` holds the
+ * generator's input bits in LSB0 order. `out` receives the output bits.
+ */
+MpzStatus mpz_garble(const MpzIo *io, const uint8_t *circuit, size_t circuit_len,
+                     const uint8_t *owners, const uint8_t *bits, size_t bits_len,
+                     MpzBuffer *out);
+
+/* Evaluates a garbled circuit as the evaluator. See `mpz_garble`. */
+MpzStatus mpz_evaluate(const MpzIo *io, const uint8_t *circuit, size_t circuit_len,
+                       const uint8_t *owners, const uint8_t *bits, size_t bits_len,
+                       MpzBuffer *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MPZ_H */
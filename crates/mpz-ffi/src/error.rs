@@ -0,0 +1,58 @@
+use mpz_circuits::{types::TypeError, DecodeError};
+use mpz_garble_core::{EvaluatorError, GeneratorError, ValueError};
+use mpz_ot_core::{chou_orlandi, kos};
+
+use crate::MpzStatus;
+
+/// Errors that can occur in the bindings.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub(crate) enum FfiError {
+    #[error("null pointer: {0}")]
+    NullPointer(&'static str),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("io callback failed with code {0}")]
+    Io(i32),
+    #[error("invalid message: {0}")]
+    Message(String),
+    #[error(transparent)]
+    BaseOTSender(#[from] chou_orlandi::SenderError),
+    #[error(transparent)]
+    BaseOTReceiver(#[from] chou_orlandi::ReceiverError),
+    #[error(transparent)]
+    ExtensionSender(#[from] kos::SenderError),
+    #[error(transparent)]
+    ExtensionReceiver(#[from] kos::ReceiverError),
+    #[error(transparent)]
+    Circuit(#[from] DecodeError),
+    #[error(transparent)]
+    Type(#[from] TypeError),
+    #[error(transparent)]
+    Value(#[from] ValueError),
+    #[error(transparent)]
+    Generator(#[from] GeneratorError),
+    #[error(transparent)]
+    Evaluator(#[from] EvaluatorError),
+}
+
+impl FfiError {
+    /// Returns the status code corresponding to the error.
+    pub(crate) fn status(&self) -> MpzStatus {
+        match self {
+            FfiError::NullPointer(_) => MpzStatus::NullPointer,
+            FfiError::InvalidInput(_)
+            | FfiError::Circuit(_)
+            | FfiError::Type(_)
+            | FfiError::Value(_) => MpzStatus::InvalidInput,
+            FfiError::Io(_) => MpzStatus::Io,
+            _ => MpzStatus::Protocol,
+        }
+    }
+}
+
+impl From<bincode::Error> for FfiError {
+    fn from(err: bincode::Error) -> Self {
+        FfiError::Message(err.to_string())
+    }
+}
@@ -0,0 +1,444 @@
+use itybity::IntoBits;
+use mpz_circuits::{types::Value, Circuit};
+use mpz_core::Block;
+use mpz_garble_core::{
+    encoding_state, ChaChaEncoder, Decoding, EncodedValue, Encoder, EncryptedGateBatch, Evaluator,
+    Generator, Label,
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    bits_from_bytes,
+    error::FfiError,
+    ffi_call,
+    io::Channel,
+    ot::{ot_ext_receive, ot_ext_send},
+    slice_from_raw, MpzBuffer, MpzIo, MpzStatus,
+};
+
+/// Owner of a circuit input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Owner {
+    Generator,
+    Evaluator,
+}
+
+/// Parsed arguments shared by both parties.
+struct Args {
+    circ: Circuit,
+    owners: Vec<Owner>,
+    bits: Vec<bool>,
+}
+
+impl Args {
+    /// Parses the arguments provided by the caller.
+    ///
+    /// # Safety
+    ///
+    /// See [`mpz_garble`].
+    unsafe fn parse(
+        circuit: *const u8,
+        circuit_len: usize,
+        owners: *const u8,
+        bits: *const u8,
+        bits_len: usize,
+        party: Owner,
+    ) -> Result<Self, FfiError> {
+        let circ = Circuit::from_bytes(slice_from_raw(circuit, circuit_len, "circuit")?)?;
+
+        let owners = slice_from_raw(owners, circ.inputs().len(), "owners")?
+            .iter()
+            .map(|owner| match owner {
+                0 => Ok(Owner::Generator),
+                1 => Ok(Owner::Evaluator),
+                _ => Err(FfiError::InvalidInput(format!(
+                    "owners must only contain 0 or 1, got {owner}"
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let bits = bits_from_bytes(slice_from_raw(bits, bits_len, "bits")?, "bits")?;
+
+        let expected_len: usize = circ
+            .inputs()
+            .iter()
+            .zip(&owners)
+            .filter(|(_, owner)| **owner == party)
+            .map(|(input, _)| input.len())
+            .sum();
+
+        if bits.len() != expected_len {
+            return Err(FfiError::InvalidInput(format!(
+                "expected {expected_len} input bits, got {}",
+                bits.len()
+            )));
+        }
+
+        Ok(Self { circ, owners, bits })
+    }
+
+    /// Returns the values of the party's inputs, in order.
+    fn values(&self, party: Owner) -> Result<Vec<Value>, FfiError> {
+        let mut bits = self.bits.as_slice();
+        self.circ
+            .inputs()
+            .iter()
+            .zip(&self.owners)
+            .filter(|(_, owner)| **owner == party)
+            .map(|(input, _)| {
+                let (value, rest) = bits.split_at(input.len());
+                bits = rest;
+                Ok(input.from_bin_repr(value)?)
+            })
+            .collect()
+    }
+}
+
+/// Flattens output values into one byte per bit.
+fn output_bytes(outputs: Vec<Value>) -> Vec<u8> {
+    outputs
+        .into_iter()
+        .flat_map(|output| output.into_iter_lsb0())
+        .map(u8::from)
+        .collect()
+}
+
+fn garble(channel: &mut Channel, args: Args) -> Result<Vec<u8>, FfiError> {
+    let Args { circ, owners, .. } = &args;
+
+    let encoder = ChaChaEncoder::new(thread_rng().gen());
+    let full_inputs: Vec<EncodedValue<encoding_state::Full>> = circ
+        .inputs()
+        .iter()
+        .enumerate()
+        .map(|(id, input)| encoder.encode_by_type(id as u64, &input.value_type()))
+        .collect();
+
+    // Send the active encodings of the generator's inputs.
+    let active_inputs = full_inputs
+        .iter()
+        .zip(owners)
+        .filter(|(_, owner)| **owner == Owner::Generator)
+        .zip(args.values(Owner::Generator)?)
+        .map(|((full, _), value)| full.select(value))
+        .collect::<Result<Vec<_>, _>>()?;
+    channel.send(&active_inputs)?;
+
+    // Transfer the evaluator's input labels.
+    let label_pairs: Vec<[Block; 2]> = full_inputs
+        .iter()
+        .zip(owners)
+        .filter(|(_, owner)| **owner == Owner::Evaluator)
+        .flat_map(|(full, _)| full.iter_blocks())
+        .collect();
+    if !label_pairs.is_empty() {
+        ot_ext_send(channel, &label_pairs)?;
+    }
+
+    let mut gen = Generator::default();
+    let mut iter = gen.generate_batched(circ, encoder.delta(), full_inputs)?;
+    for batch in iter.by_ref() {
+        channel.send(&batch)?;
+    }
+    let output = iter.finish()?;
+
+    let decodings: Vec<Decoding> = output
+        .outputs
+        .iter()
+        .map(|output| output.decoding())
+        .collect();
+    channel.send(&decodings)?;
+
+    // The evaluator sends the decoded outputs back.
+    let outputs: Vec<u8> = channel.recv()?;
+    let expected_len: usize = circ.outputs().iter().map(|output| output.len()).sum();
+    if outputs.len() != expected_len || outputs.iter().any(|bit| *bit > 1) {
+        return Err(FfiError::Message("invalid outputs".to_string()));
+    }
+
+    Ok(outputs)
+}
+
+fn evaluate(channel: &mut Channel, args: Args) -> Result<Vec<u8>, FfiError> {
+    let Args { circ, owners, bits } = &args;
+
+    let gen_inputs: Vec<EncodedValue<encoding_state::Active>> = channel.recv()?;
+    let gen_count = owners
+        .iter()
+        .filter(|owner| **owner == Owner::Generator)
+        .count();
+    if gen_inputs.len() != gen_count {
+        return Err(FfiError::Message(format!(
+            "expected {gen_count} generator inputs, got {}",
+            gen_inputs.len()
+        )));
+    }
+
+    let labels = if bits.is_empty() {
+        Vec::new()
+    } else {
+        ot_ext_receive(channel, bits)?
+    };
+
+    // Assemble the active inputs in circuit order.
+    let mut gen_inputs = gen_inputs.into_iter();
+    let mut labels = labels.into_iter().map(Label::new);
+    let active_inputs = circ
+        .inputs()
+        .iter()
+        .zip(owners)
+        .map(|(input, owner)| match owner {
+            Owner::Generator => {
+                let active = gen_inputs.next().expect("input count was checked");
+                if active.value_type() != input.value_type() {
+                    return Err(FfiError::Message(
+                        "generator input has the wrong type".to_string(),
+                    ));
+                }
+                Ok(active)
+            }
+            Owner::Evaluator => {
+                let labels: Vec<Label> = labels.by_ref().take(input.len()).collect();
+                Ok(EncodedValue::<encoding_state::Active>::from_labels(
+                    input.value_type(),
+                    &labels,
+                )?)
+            }
+        })
+        .collect::<Result<Vec<_>, FfiError>>()?;
+
+    let mut ev = Evaluator::default();
+    let mut consumer = ev.evaluate_batched(circ, active_inputs)?;
+    while consumer.wants_gates() {
+        let batch: EncryptedGateBatch = channel.recv()?;
+        consumer.next(batch);
+    }
+    let output = consumer.finish()?;
+
+    let decodings: Vec<Decoding> = channel.recv()?;
+    if decodings.len() != output.outputs.len() {
+        return Err(FfiError::Message(format!(
+            "expected {} decodings, got {}",
+            output.outputs.len(),
+            decodings.len()
+        )));
+    }
+
+    let outputs = output
+        .outputs
+        .iter()
+        .zip(&decodings)
+        .map(|(output, decoding)| output.decode(decoding))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let outputs = output_bytes(outputs);
+    channel.send(&outputs)?;
+
+    Ok(outputs)
+}
+
+/// Garbles a circuit as the generator.
+///
+/// The peer must call [`mpz_evaluate`] with the same circuit and input owners. Both parties
+/// learn the outputs.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `circuit` - The circuit in the stable binary encoding of `mpz-circuits`.
+/// * `circuit_len` - Length of the circuit in bytes.
+/// * `owners` - The owner of each circuit input, `0` for the generator and `1` for the
+///   evaluator.
+/// * `bits` - The bits of the generator's inputs, in input order.
+/// * `bits_len` - The number of input bits.
+/// * `out` - Receives the output bits of the circuit, in output order.
+///
+/// # Safety
+///
+/// `io` must point to a valid [`MpzIo`], `circuit` must be valid for reads of `circuit_len`
+/// bytes, `owners` must be valid for reads of one byte per circuit input, `bits` must be valid
+/// for reads of `bits_len` bytes and `out` must be valid for writes of an [`MpzBuffer`].
+#[no_mangle]
+pub unsafe extern "C" fn mpz_garble(
+    io: *const MpzIo,
+    circuit: *const u8,
+    circuit_len: usize,
+    owners: *const u8,
+    bits: *const u8,
+    bits_len: usize,
+    out: *mut MpzBuffer,
+) -> MpzStatus {
+    ffi_call(|| {
+        let out = out.as_mut().ok_or(FfiError::NullPointer("out"))?;
+        let mut channel = Channel::new(io)?;
+        let args = Args::parse(
+            circuit,
+            circuit_len,
+            owners,
+            bits,
+            bits_len,
+            Owner::Generator,
+        )?;
+
+        *out = MpzBuffer::new(garble(&mut channel, args)?);
+
+        Ok(())
+    })
+}
+
+/// Evaluates a garbled circuit as the evaluator.
+///
+/// The peer must call [`mpz_garble`] with the same circuit and input owners. Both parties
+/// learn the outputs.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `circuit` - The circuit in the stable binary encoding of `mpz-circuits`.
+/// * `circuit_len` - Length of the circuit in bytes.
+/// * `owners` - The owner of each circuit input, `0` for the generator and `1` for the
+///   evaluator.
+/// * `bits` - The bits of the evaluator's inputs, in input order.
+/// * `bits_len` - The number of input bits.
+/// * `out` - Receives the output bits of the circuit, in output order.
+///
+/// # Safety
+///
+/// See [`mpz_garble`].
+#[no_mangle]
+pub unsafe extern "C" fn mpz_evaluate(
+    io: *const MpzIo,
+    circuit: *const u8,
+    circuit_len: usize,
+    owners: *const u8,
+    bits: *const u8,
+    bits_len: usize,
+    out: *mut MpzBuffer,
+) -> MpzStatus {
+    ffi_call(|| {
+        let out = out.as_mut().ok_or(FfiError::NullPointer("out"))?;
+        let mut channel = Channel::new(io)?;
+        let args = Args::parse(
+            circuit,
+            circuit_len,
+            owners,
+            bits,
+            bits_len,
+            Owner::Evaluator,
+        )?;
+
+        *out = MpzBuffer::new(evaluate(&mut channel, args)?);
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mpz_buffer_free, test::MemoryIo};
+
+    use mpz_circuits::circuits::AES128;
+    use std::{ptr, thread};
+
+    fn to_bytes(value: impl Into<Value>) -> Vec<u8> {
+        value.into().into_iter_lsb0().map(u8::from).collect()
+    }
+
+    #[test]
+    fn test_garble_evaluate() {
+        let key = [69u8; 16];
+        let msg = [42u8; 16];
+
+        let expected = AES128.evaluate(&[key.into(), msg.into()]).unwrap();
+        let expected = output_bytes(expected);
+
+        let circuit = AES128.to_bytes();
+        let owners = [0u8, 1u8];
+
+        let (mut gen_io, mut ev_io) = MemoryIo::pair();
+
+        let gen = {
+            let circuit = circuit.clone();
+            thread::spawn(move || {
+                let io = gen_io.as_io();
+                let bits = to_bytes(key);
+                let mut out = MpzBuffer {
+                    ptr: ptr::null_mut(),
+                    len: 0,
+                };
+                let status = unsafe {
+                    mpz_garble(
+                        &io,
+                        circuit.as_ptr(),
+                        circuit.len(),
+                        owners.as_ptr(),
+                        bits.as_ptr(),
+                        bits.len(),
+                        &mut out,
+                    )
+                };
+                let outputs = unsafe { std::slice::from_raw_parts(out.ptr, out.len).to_vec() };
+                unsafe { mpz_buffer_free(&mut out) };
+                (status, outputs)
+            })
+        };
+
+        let io = ev_io.as_io();
+        let bits = to_bytes(msg);
+        let mut out = MpzBuffer {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+        let status = unsafe {
+            mpz_evaluate(
+                &io,
+                circuit.as_ptr(),
+                circuit.len(),
+                owners.as_ptr(),
+                bits.as_ptr(),
+                bits.len(),
+                &mut out,
+            )
+        };
+        assert_eq!(status, MpzStatus::Ok);
+
+        let outputs = unsafe { std::slice::from_raw_parts(out.ptr, out.len).to_vec() };
+        unsafe { mpz_buffer_free(&mut out) };
+        assert!(out.ptr.is_null());
+
+        let (gen_status, gen_outputs) = gen.join().unwrap();
+        assert_eq!(gen_status, MpzStatus::Ok);
+
+        assert_eq!(outputs, expected);
+        assert_eq!(gen_outputs, expected);
+    }
+
+    #[test]
+    fn test_garble_invalid_input_len() {
+        let (mut io, _) = MemoryIo::pair();
+        let io = io.as_io();
+        let circuit = AES128.to_bytes();
+        let owners = [0u8, 1u8];
+        let bits = [0u8; 8];
+        let mut out = MpzBuffer {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+
+        let status = unsafe {
+            mpz_garble(
+                &io,
+                circuit.as_ptr(),
+                circuit.len(),
+                owners.as_ptr(),
+                bits.as_ptr(),
+                bits.len(),
+                &mut out,
+            )
+        };
+
+        assert_eq!(status, MpzStatus::InvalidInput);
+        assert!(out.ptr.is_null());
+    }
+}
@@ -0,0 +1,108 @@
+use std::ffi::c_void;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::FfiError;
+
+/// Maximum length of a message in bytes.
+const MAX_MESSAGE_LEN: usize = 1 << 30;
+
+/// Sends `len` bytes from `data` to the peer.
+///
+/// Returns `0` on success.
+pub type MpzSendFn = unsafe extern "C" fn(ctx: *mut c_void, data: *const u8, len: usize) -> i32;
+
+/// Receives exactly `len` bytes from the peer into `data`, blocking until they are available.
+///
+/// Returns `0` on success.
+pub type MpzRecvFn = unsafe extern "C" fn(ctx: *mut c_void, data: *mut u8, len: usize) -> i32;
+
+/// Byte-buffer I/O callbacks used to communicate with the peer.
+///
+/// The callbacks must provide a reliable, ordered byte stream, such as a TCP connection.
+/// Messages are framed by this library, so the transport does not need to preserve message
+/// boundaries.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MpzIo {
+    /// Opaque pointer which is passed to the callbacks.
+    pub ctx: *mut c_void,
+    /// Callback which sends bytes to the peer.
+    pub send: Option<MpzSendFn>,
+    /// Callback which receives bytes from the peer.
+    pub recv: Option<MpzRecvFn>,
+}
+
+/// A framed channel over the caller's I/O callbacks.
+///
+/// Each message is serialized with `bincode` and prefixed with its length as a little-endian `u32`.
+pub(crate) struct Channel {
+    ctx: *mut c_void,
+    send: MpzSendFn,
+    recv: MpzRecvFn,
+}
+
+impl Channel {
+    /// Creates a new channel.
+    ///
+    /// # Safety
+    ///
+    /// `io` must be null or point to a valid [`MpzIo`].
+    pub(crate) unsafe fn new(io: *const MpzIo) -> Result<Self, FfiError> {
+        let io = io.as_ref().ok_or(FfiError::NullPointer("io"))?;
+
+        Ok(Self {
+            ctx: io.ctx,
+            send: io.send.ok_or(FfiError::NullPointer("io.send"))?,
+            recv: io.recv.ok_or(FfiError::NullPointer("io.recv"))?,
+        })
+    }
+
+    /// Sends a message to the peer.
+    pub(crate) fn send<T: Serialize>(&mut self, msg: &T) -> Result<(), FfiError> {
+        let len = bincode::serialized_size(msg)? as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(FfiError::Message(format!(
+                "message length {len} exceeds maximum {MAX_MESSAGE_LEN}"
+            )));
+        }
+
+        let mut bytes = Vec::with_capacity(4 + len);
+        bytes.extend_from_slice(&(len as u32).to_le_bytes());
+        bincode::serialize_into(&mut bytes, msg)?;
+
+        // SAFETY: the caller of `Channel::new` guarantees that the callbacks are valid, and
+        // `bytes` is valid for reads of its length.
+        match unsafe { (self.send)(self.ctx, bytes.as_ptr(), bytes.len()) } {
+            0 => Ok(()),
+            code => Err(FfiError::Io(code)),
+        }
+    }
+
+    /// Receives a message from the peer.
+    pub(crate) fn recv<T: DeserializeOwned>(&mut self) -> Result<T, FfiError> {
+        let mut len = [0u8; 4];
+        self.read(&mut len)?;
+
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(FfiError::Message(format!(
+                "message length {len} exceeds maximum {MAX_MESSAGE_LEN}"
+            )));
+        }
+
+        let mut bytes = vec![0u8; len];
+        self.read(&mut bytes)?;
+
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), FfiError> {
+        // SAFETY: the caller of `Channel::new` guarantees that the callbacks are valid, and
+        // `buf` is valid for writes of its length.
+        match unsafe { (self.recv)(self.ctx, buf.as_mut_ptr(), buf.len()) } {
+            0 => Ok(()),
+            code => Err(FfiError::Io(code)),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! C bindings for the mpz protocols.
+//!
+//! This crate exposes a stable C ABI for the main two-party flows so that they can be driven from
+//! other languages without reimplementing the protocols:
+//!
+//! - Base oblivious transfer ([`mpz_ot_send`], [`mpz_ot_receive`]) using CO15.
+//! - Oblivious transfer extension ([`mpz_ot_ext_send`], [`mpz_ot_ext_receive`]) using KOS15.
+//! - Semi-honest garbled circuit evaluation ([`mpz_garble`], [`mpz_evaluate`]).
+//!
+//! The caller provides the transport as a pair of byte-buffer callbacks, see [`MpzIo`]. Every
+//! function blocks until the protocol has completed.
+//!
+//! A C header is provided in `include/mpz.h`.
+//!
+//! # Conventions
+//!
+//! - Blocks are 16 bytes.
+//! - Bits, such as OT choices or circuit inputs, are passed as one byte per bit which is either
+//!   `0` or `1`. Values are encoded in LSB0 order.
+//! - Every function returns an [`MpzStatus`]. On failure a description of the error can be
+//!   retrieved with [`mpz_last_error`].
+//! - Buffers allocated by this library are returned as [`MpzBuffer`] and must be released with
+//!   [`mpz_buffer_free`].
+//!
+//! # ⚠️ Warning ⚠️
+//!
+//! The garbling flow is only secure against semi-honest adversaries.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+
+mod error;
+mod garble;
+mod io;
+mod ot;
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+pub use garble::{mpz_evaluate, mpz_garble};
+pub use io::{MpzIo, MpzRecvFn, MpzSendFn};
+pub use ot::{mpz_ot_ext_receive, mpz_ot_ext_send, mpz_ot_receive, mpz_ot_send};
+
+use error::FfiError;
+
+/// Size of a block in bytes.
+pub const MPZ_BLOCK_LEN: usize = 16;
+
+/// Status code returned by every function of this library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpzStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An argument was invalid.
+    InvalidInput = 2,
+    /// An I/O callback reported an error.
+    Io = 3,
+    /// The peer sent a malformed message or deviated from the protocol.
+    Protocol = 4,
+    /// The library panicked.
+    Panic = 5,
+}
+
+/// A byte buffer allocated by this library.
+///
+/// Must be released with [`mpz_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct MpzBuffer {
+    /// Pointer to the data.
+    pub ptr: *mut u8,
+    /// Length of the data in bytes.
+    pub len: usize,
+}
+
+impl MpzBuffer {
+    pub(crate) fn new(data: Vec<u8>) -> Self {
+        let len = data.len();
+        let ptr = Box::into_raw(data.into_boxed_slice()) as *mut u8;
+
+        Self { ptr, len }
+    }
+}
+
+/// Releases a buffer allocated by this library.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to a buffer returned by this library which has not been
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn mpz_buffer_free(buffer: *mut MpzBuffer) {
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+
+    if !buffer.ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.ptr, buffer.len,
+        )));
+    }
+
+    buffer.ptr = ptr::null_mut();
+    buffer.len = 0;
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Returns a description of the last error which occurred on the calling thread, or null if the
+/// last call succeeded.
+///
+/// The returned string is valid until the next call into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn mpz_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|msg| msg.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+fn set_last_error(msg: Option<String>) {
+    let msg = msg.map(|msg| CString::new(msg.replace('\0', " ")).expect("nul bytes are removed"));
+    LAST_ERROR.with(|last| *last.borrow_mut() = msg);
+}
+
+/// Runs `f`, records its error and converts the result into a status code.
+///
+/// Panics are caught so that they do not unwind into foreign code.
+pub(crate) fn ffi_call(f: impl FnOnce() -> Result<(), FfiError>) -> MpzStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            set_last_error(None);
+            MpzStatus::Ok
+        }
+        Ok(Err(err)) => {
+            let status = err.status();
+            set_last_error(Some(err.to_string()));
+            status
+        }
+        Err(panic) => {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(Some(format!("panic: {msg}")));
+            MpzStatus::Panic
+        }
+    }
+}
+
+/// Returns a slice from a pointer and length provided by the caller.
+///
+/// # Safety
+///
+/// If `len` is not zero, `ptr` must be valid for reads of `len` elements.
+pub(crate) unsafe fn slice_from_raw<'a, T>(
+    ptr: *const T,
+    len: usize,
+    name: &'static str,
+) -> Result<&'a [T], FfiError> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(FfiError::NullPointer(name))
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Returns a mutable slice from a pointer and length provided by the caller.
+///
+/// # Safety
+///
+/// If `len` is not zero, `ptr` must be valid for writes of `len` elements.
+pub(crate) unsafe fn slice_from_raw_mut<'a, T>(
+    ptr: *mut T,
+    len: usize,
+    name: &'static str,
+) -> Result<&'a mut [T], FfiError> {
+    if len == 0 {
+        Ok(&mut [])
+    } else if ptr.is_null() {
+        Err(FfiError::NullPointer(name))
+    } else {
+        Ok(std::slice::from_raw_parts_mut(ptr, len))
+    }
+}
+
+/// Converts bits passed as one byte per bit.
+pub(crate) fn bits_from_bytes(bytes: &[u8], name: &'static str) -> Result<Vec<bool>, FfiError> {
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(FfiError::InvalidInput(format!(
+                "{name} must only contain 0 or 1, got {byte}"
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    //! In-memory transport for testing.
+
+    use std::{
+        collections::VecDeque,
+        ffi::c_void,
+        sync::mpsc::{channel, Receiver, Sender},
+    };
+
+    use super::MpzIo;
+
+    pub(crate) struct MemoryIo {
+        tx: Sender<Vec<u8>>,
+        rx: Receiver<Vec<u8>>,
+        buf: VecDeque<u8>,
+    }
+
+    impl MemoryIo {
+        pub(crate) fn pair() -> (Self, Self) {
+            let (tx_a, rx_b) = channel();
+            let (tx_b, rx_a) = channel();
+
+            (
+                Self {
+                    tx: tx_a,
+                    rx: rx_a,
+                    buf: VecDeque::new(),
+                },
+                Self {
+                    tx: tx_b,
+                    rx: rx_b,
+                    buf: VecDeque::new(),
+                },
+            )
+        }
+
+        pub(crate) fn as_io(&mut self) -> MpzIo {
+            MpzIo {
+                ctx: self as *mut Self as *mut c_void,
+                send: Some(send),
+                recv: Some(recv),
+            }
+        }
+    }
+
+    unsafe extern "C" fn send(ctx: *mut c_void, data: *const u8, len: usize) -> i32 {
+        let io = &mut *(ctx as *mut MemoryIo);
+        let data = std::slice::from_raw_parts(data, len).to_vec();
+        match io.tx.send(data) {
+            Ok(()) => 0,
+            Err(_) => -1,
+        }
+    }
+
+    unsafe extern "C" fn recv(ctx: *mut c_void, data: *mut u8, len: usize) -> i32 {
+        let io = &mut *(ctx as *mut MemoryIo);
+        while io.buf.len() < len {
+            match io.rx.recv() {
+                Ok(bytes) => io.buf.extend(bytes),
+                Err(_) => return -1,
+            }
+        }
+
+        let data = std::slice::from_raw_parts_mut(data, len);
+        for (dst, src) in data.iter_mut().zip(io.buf.drain(..len)) {
+            *dst = src;
+        }
+
+        0
+    }
+}
@@ -0,0 +1,301 @@
+use itybity::IntoBits;
+use mpz_core::Block;
+use mpz_ot_core::{
+    chou_orlandi,
+    kos::{self, pad_ot_count, CSP},
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    bits_from_bytes, error::FfiError, ffi_call, io::Channel, slice_from_raw, slice_from_raw_mut,
+    MpzIo, MpzStatus, MPZ_BLOCK_LEN,
+};
+
+/// Sends messages using base oblivious transfer.
+pub(crate) fn base_ot_send(channel: &mut Channel, msgs: &[[Block; 2]]) -> Result<(), FfiError> {
+    let (setup, mut sender) =
+        chou_orlandi::Sender::new(chou_orlandi::SenderConfig::default()).setup();
+    channel.send(&setup)?;
+
+    let receiver_payload = channel.recv()?;
+    let sender_payload = sender.send(msgs, receiver_payload)?;
+    channel.send(&sender_payload)?;
+
+    Ok(())
+}
+
+/// Receives messages using base oblivious transfer.
+pub(crate) fn base_ot_receive(
+    channel: &mut Channel,
+    choices: &[bool],
+) -> Result<Vec<Block>, FfiError> {
+    let setup = channel.recv()?;
+    let mut receiver =
+        chou_orlandi::Receiver::new(chou_orlandi::ReceiverConfig::default()).setup(setup);
+
+    let receiver_payload = receiver.receive_random(choices);
+    channel.send(&receiver_payload)?;
+
+    let sender_payload = channel.recv()?;
+
+    Ok(receiver.receive(sender_payload)?)
+}
+
+/// Sends messages using oblivious transfer extension.
+///
+/// The base OTs are performed with reversed roles.
+pub(crate) fn ot_ext_send(channel: &mut Channel, msgs: &[[Block; 2]]) -> Result<(), FfiError> {
+    let delta: Block = thread_rng().gen();
+    let seeds: [Block; CSP] = base_ot_receive(channel, &delta.into_lsb0_vec())?
+        .try_into()
+        .map_err(|_| FfiError::Message("unexpected number of base OT seeds".to_string()))?;
+
+    let mut sender = kos::Sender::new(kos::SenderConfig::default()).setup(delta, seeds);
+
+    let count = pad_ot_count(msgs.len());
+    sender.extend(count, channel.recv()?)?;
+
+    // The consistency check challenge is sampled after the receiver committed to the extension.
+    let chi_seed: Block = thread_rng().gen();
+    channel.send(&chi_seed)?;
+    sender.check(chi_seed, channel.recv()?)?;
+
+    let mut keys = sender.keys(msgs.len())?;
+    keys.derandomize(channel.recv()?)?;
+    channel.send(&keys.encrypt_blocks(msgs)?)?;
+
+    Ok(())
+}
+
+/// Receives messages using oblivious transfer extension.
+///
+/// The base OTs are performed with reversed roles.
+pub(crate) fn ot_ext_receive(
+    channel: &mut Channel,
+    choices: &[bool],
+) -> Result<Vec<Block>, FfiError> {
+    let mut rng = thread_rng();
+    let seeds: [[Block; 2]; CSP] = std::array::from_fn(|_| [rng.gen(), rng.gen()]);
+    base_ot_send(channel, &seeds)?;
+
+    let mut receiver = kos::Receiver::new(kos::ReceiverConfig::default()).setup(seeds);
+
+    let count = pad_ot_count(choices.len());
+    channel.send(&receiver.extend(count)?)?;
+
+    let chi_seed: Block = channel.recv()?;
+    channel.send(&receiver.check(chi_seed)?)?;
+
+    let mut keys = receiver.keys(choices.len())?;
+    channel.send(&keys.derandomize(choices)?)?;
+
+    Ok(keys.decrypt_blocks(channel.recv()?)?)
+}
+
+/// Reads `count` message pairs from the caller.
+unsafe fn read_msgs(msgs: *const u8, count: usize) -> Result<Vec<[Block; 2]>, FfiError> {
+    let len = count
+        .checked_mul(2 * MPZ_BLOCK_LEN)
+        .ok_or_else(|| FfiError::InvalidInput(format!("count {count} is too large")))?;
+    let msgs = slice_from_raw(msgs, len, "msgs")?;
+
+    Ok(msgs
+        .chunks_exact(2 * MPZ_BLOCK_LEN)
+        .map(|pair| {
+            let (zero, one) = pair.split_at(MPZ_BLOCK_LEN);
+            [
+                Block::new(zero.try_into().unwrap()),
+                Block::new(one.try_into().unwrap()),
+            ]
+        })
+        .collect())
+}
+
+/// Writes the received messages to the caller's buffer.
+unsafe fn write_blocks(out: *mut u8, blocks: &[Block]) -> Result<(), FfiError> {
+    let out = slice_from_raw_mut(out, blocks.len() * MPZ_BLOCK_LEN, "out")?;
+    for (dst, block) in out.chunks_exact_mut(MPZ_BLOCK_LEN).zip(blocks) {
+        dst.copy_from_slice(&block.to_bytes());
+    }
+
+    Ok(())
+}
+
+/// Sends `count` pairs of messages using base oblivious transfer (CO15).
+///
+/// The peer must call [`mpz_ot_receive`] with the same `count`.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `msgs` - `count` pairs of 16 byte messages, laid out as `m0 || m1` for each pair.
+/// * `count` - The number of pairs.
+///
+/// # Safety
+///
+/// `io` must point to a valid [`MpzIo`], and `msgs` must be valid for reads of `count * 32`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mpz_ot_send(io: *const MpzIo, msgs: *const u8, count: usize) -> MpzStatus {
+    ffi_call(|| {
+        let mut channel = Channel::new(io)?;
+        let msgs = read_msgs(msgs, count)?;
+
+        base_ot_send(&mut channel, &msgs)
+    })
+}
+
+/// Receives `count` messages using base oblivious transfer (CO15).
+///
+/// The peer must call [`mpz_ot_send`] with the same `count`.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `choices` - `count` choice bits.
+/// * `count` - The number of messages.
+/// * `out` - Output buffer for the `count` chosen 16 byte messages.
+///
+/// # Safety
+///
+/// `io` must point to a valid [`MpzIo`], `choices` must be valid for reads of `count` bytes
+/// and `out` must be valid for writes of `count * 16` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mpz_ot_receive(
+    io: *const MpzIo,
+    choices: *const u8,
+    count: usize,
+    out: *mut u8,
+) -> MpzStatus {
+    ffi_call(|| {
+        let mut channel = Channel::new(io)?;
+        let choices = bits_from_bytes(slice_from_raw(choices, count, "choices")?, "choices")?;
+
+        let msgs = base_ot_receive(&mut channel, &choices)?;
+        write_blocks(out, &msgs)
+    })
+}
+
+/// Sends `count` pairs of messages using oblivious transfer extension (KOS15).
+///
+/// The peer must call [`mpz_ot_ext_receive`] with the same `count`.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `msgs` - `count` pairs of 16 byte messages, laid out as `m0 || m1` for each pair.
+/// * `count` - The number of pairs.
+///
+/// # Safety
+///
+/// `io` must point to a valid [`MpzIo`], and `msgs` must be valid for reads of `count * 32`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mpz_ot_ext_send(
+    io: *const MpzIo,
+    msgs: *const u8,
+    count: usize,
+) -> MpzStatus {
+    ffi_call(|| {
+        let mut channel = Channel::new(io)?;
+        let msgs = read_msgs(msgs, count)?;
+
+        ot_ext_send(&mut channel, &msgs)
+    })
+}
+
+/// Receives `count` messages using oblivious transfer extension (KOS15).
+///
+/// The peer must call [`mpz_ot_ext_send`] with the same `count`.
+///
+/// # Arguments
+///
+/// * `io` - The I/O callbacks.
+/// * `choices` - `count` choice bits.
+/// * `count` - The number of messages.
+/// * `out` - Output buffer for the `count` chosen 16 byte messages.
+///
+/// # Safety
+///
+/// `io` must point to a valid [`MpzIo`], `choices` must be valid for reads of `count` bytes
+/// and `out` must be valid for writes of `count * 16` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mpz_ot_ext_receive(
+    io: *const MpzIo,
+    choices: *const u8,
+    count: usize,
+    out: *mut u8,
+) -> MpzStatus {
+    ffi_call(|| {
+        let mut channel = Channel::new(io)?;
+        let choices = bits_from_bytes(slice_from_raw(choices, count, "choices")?, "choices")?;
+
+        let msgs = ot_ext_receive(&mut channel, &choices)?;
+        write_blocks(out, &msgs)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MemoryIo;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use std::thread;
+
+    fn transfer(
+        send: unsafe extern "C" fn(*const MpzIo, *const u8, usize) -> MpzStatus,
+        receive: unsafe extern "C" fn(*const MpzIo, *const u8, usize, *mut u8) -> MpzStatus,
+    ) {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let count = 200;
+        let msgs: Vec<u8> = (0..count * 32).map(|_| rng.gen()).collect();
+        let choices: Vec<u8> = (0..count).map(|_| rng.gen_range(0..2)).collect();
+
+        let (mut sender_io, mut receiver_io) = MemoryIo::pair();
+
+        let sender = {
+            let msgs = msgs.clone();
+            thread::spawn(move || {
+                let io = sender_io.as_io();
+                unsafe { send(&io, msgs.as_ptr(), count) }
+            })
+        };
+
+        let io = receiver_io.as_io();
+        let mut out = vec![0u8; count * 16];
+        let status = unsafe { receive(&io, choices.as_ptr(), count, out.as_mut_ptr()) };
+
+        assert_eq!(status, MpzStatus::Ok);
+        assert_eq!(sender.join().unwrap(), MpzStatus::Ok);
+
+        for (i, choice) in choices.iter().enumerate() {
+            let offset = i * 32 + *choice as usize * 16;
+            assert_eq!(&out[i * 16..(i + 1) * 16], &msgs[offset..offset + 16]);
+        }
+    }
+
+    #[test]
+    fn test_base_ot() {
+        transfer(mpz_ot_send, mpz_ot_receive);
+    }
+
+    #[test]
+    fn test_ot_ext() {
+        transfer(mpz_ot_ext_send, mpz_ot_ext_receive);
+    }
+
+    #[test]
+    fn test_invalid_choices() {
+        let (_, mut io) = MemoryIo::pair();
+        let io = io.as_io();
+        let choices = [2u8];
+        let mut out = [0u8; 16];
+
+        let status = unsafe { mpz_ot_receive(&io, choices.as_ptr(), 1, out.as_mut_ptr()) };
+
+        assert_eq!(status, MpzStatus::InvalidInput);
+        assert!(!crate::mpz_last_error().is_null());
+    }
+}
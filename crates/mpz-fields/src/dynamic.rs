@@ -0,0 +1,251 @@
+//! This module implements fields which are selected at runtime.
+//!
+//! The [`Field`] trait is generic-only, so code which uses it is monomorphized for every field.
+//! A [`DynField`] is an object-safe handle to the operations of a field, which work on the byte
+//! representation of the field elements, so that the field can be chosen from a [`FieldKind`] at
+//! runtime.
+
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use hybrid_array::Array;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{gf2_128::Gf2_128, p256::P256, Field, FieldError};
+
+/// The kind of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    /// The prime field of P256, see [`P256`].
+    P256,
+    /// The extension field GF(2^128), see [`Gf2_128`].
+    Gf2_128,
+}
+
+impl FieldKind {
+    /// Returns the operations of the field.
+    pub fn field(self) -> Box<dyn DynField> {
+        match self {
+            FieldKind::P256 => Box::new(FieldOps::<P256>::new(self)),
+            FieldKind::Gf2_128 => Box::new(FieldOps::<Gf2_128>::new(self)),
+        }
+    }
+}
+
+impl Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldKind::P256 => write!(f, "p256"),
+            FieldKind::Gf2_128 => write!(f, "gf2_128"),
+        }
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = UnknownFieldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "p256" => Ok(FieldKind::P256),
+            "gf2_128" => Ok(FieldKind::Gf2_128),
+            _ => Err(UnknownFieldError(s.to_string())),
+        }
+    }
+}
+
+/// Error for an unknown field name.
+#[derive(Debug, Error)]
+#[error("unknown field: {0}, expected p256 or gf2_128")]
+pub struct UnknownFieldError(String);
+
+/// Error for a byte representation with the wrong length.
+#[derive(Debug, Error)]
+#[error("invalid length of field element: expected {expected} bytes, got {actual}")]
+pub struct ByteLengthError {
+    expected: usize,
+    actual: usize,
+}
+
+/// The operations of a field which is selected at runtime.
+///
+/// Field elements are represented as bytes, with the representation of the field, i.e., the
+/// little-endian bytes for [`P256`] and the big-endian bytes for [`Gf2_128`]. The operations
+/// return an error if an input is not a valid field element.
+pub trait DynField: Debug + Send + Sync {
+    /// Returns the kind of the field.
+    fn kind(&self) -> FieldKind;
+
+    /// Returns the number of bytes of a field element.
+    fn byte_size(&self) -> usize;
+
+    /// Returns the additive identity element.
+    fn zero(&self) -> Vec<u8>;
+
+    /// Returns the multiplicative identity element.
+    fn one(&self) -> Vec<u8>;
+
+    /// Returns a random field element.
+    fn rand(&self, rng: &mut dyn RngCore) -> Vec<u8>;
+
+    /// Returns `a + b`.
+    fn add(&self, a: &[u8], b: &[u8]) -> Result<Vec<u8>, FieldError>;
+
+    /// Returns `a * b`.
+    fn mul(&self, a: &[u8], b: &[u8]) -> Result<Vec<u8>, FieldError>;
+
+    /// Returns `-a`.
+    fn neg(&self, a: &[u8]) -> Result<Vec<u8>, FieldError>;
+
+    /// Returns the multiplicative inverse of `a`.
+    fn inverse(&self, a: &[u8]) -> Result<Vec<u8>, FieldError>;
+}
+
+/// The byte representation of a field element.
+trait ByteRepr: Field {
+    fn to_repr(&self) -> Vec<u8>;
+
+    fn from_repr(bytes: &[u8]) -> Result<Self, FieldError> {
+        let bytes = Array::<u8, Self::ByteSize>::try_from(bytes).map_err(|_| {
+            FieldError(Box::new(ByteLengthError {
+                expected: Self::BYTE_SIZE,
+                actual: bytes.len(),
+            }))
+        })?;
+
+        Self::try_from(bytes)
+    }
+}
+
+impl ByteRepr for P256 {
+    fn to_repr(&self) -> Vec<u8> {
+        <[u8; 32]>::from(*self).to_vec()
+    }
+}
+
+impl ByteRepr for Gf2_128 {
+    fn to_repr(&self) -> Vec<u8> {
+        self.to_be_bytes()
+    }
+}
+
+/// The operations of a field `F`.
+#[derive(Debug)]
+struct FieldOps<F> {
+    kind: FieldKind,
+    _pd: PhantomData<fn() -> F>,
+}
+
+impl<F> FieldOps<F> {
+    fn new(kind: FieldKind) -> Self {
+        Self {
+            kind,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<F: ByteRepr> DynField for FieldOps<F> {
+    fn kind(&self) -> FieldKind {
+        self.kind
+    }
+
+    fn byte_size(&self) -> usize {
+        F::BYTE_SIZE
+    }
+
+    fn zero(&self) -> Vec<u8> {
+        F::zero().to_repr()
+    }
+
+    fn one(&self) -> Vec<u8> {
+        F::one().to_repr()
+    }
+
+    fn rand(&self, rng: &mut dyn RngCore) -> Vec<u8> {
+        F::rand(rng).to_repr()
+    }
+
+    fn add(&self, a: &[u8], b: &[u8]) -> Result<Vec<u8>, FieldError> {
+        Ok((F::from_repr(a)? + F::from_repr(b)?).to_repr())
+    }
+
+    fn mul(&self, a: &[u8], b: &[u8]) -> Result<Vec<u8>, FieldError> {
+        Ok((F::from_repr(a)? * F::from_repr(b)?).to_repr())
+    }
+
+    fn neg(&self, a: &[u8]) -> Result<Vec<u8>, FieldError> {
+        Ok((-F::from_repr(a)?).to_repr())
+    }
+
+    fn inverse(&self, a: &[u8]) -> Result<Vec<u8>, FieldError> {
+        Ok(F::from_repr(a)?.inverse().to_repr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpz_core::{prg::Prg, Block};
+    use rand::{Rng, SeedableRng};
+
+    fn test_dyn_field_basic(kind: FieldKind) {
+        let mut rng = Prg::from_seed(Block::ZERO);
+        let field = kind.field();
+
+        let a = field.rand(&mut rng);
+        let zero = field.zero();
+        let one = field.one();
+
+        assert_eq!(a.len(), field.byte_size());
+        assert_eq!(field.add(&a, &zero).unwrap(), a);
+        assert_eq!(field.mul(&a, &zero).unwrap(), zero);
+        assert_eq!(field.mul(&a, &one).unwrap(), a);
+        assert_eq!(field.mul(&a, &field.inverse(&a).unwrap()).unwrap(), one);
+        assert_eq!(field.add(&a, &field.neg(&a).unwrap()).unwrap(), zero);
+        assert!(field.add(&a, &a[1..]).is_err());
+    }
+
+    #[test]
+    fn test_dyn_field_p256() {
+        test_dyn_field_basic(FieldKind::P256);
+
+        let mut rng = Prg::from_seed(Block::ZERO);
+        let (a, b): (P256, P256) = (rng.gen(), rng.gen());
+        assert_eq!(
+            FieldKind::P256
+                .field()
+                .mul(&a.to_repr(), &b.to_repr())
+                .unwrap(),
+            (a * b).to_repr()
+        );
+    }
+
+    #[test]
+    fn test_dyn_field_gf2_128() {
+        test_dyn_field_basic(FieldKind::Gf2_128);
+
+        let mut rng = Prg::from_seed(Block::ZERO);
+        let (a, b): (Gf2_128, Gf2_128) = (rng.gen(), rng.gen());
+        assert_eq!(
+            FieldKind::Gf2_128
+                .field()
+                .mul(&a.to_repr(), &b.to_repr())
+                .unwrap(),
+            (a * b).to_repr()
+        );
+    }
+
+    #[test]
+    fn test_field_kind_from_str() {
+        for kind in [FieldKind::P256, FieldKind::Gf2_128] {
+            assert_eq!(kind.to_string().parse::<FieldKind>().unwrap(), kind);
+        }
+        assert!("p384".parse::<FieldKind>().is_err());
+    }
+}
@@ -4,6 +4,7 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+pub mod dynamic;
 pub mod gf2_128;
 pub mod p256;
 
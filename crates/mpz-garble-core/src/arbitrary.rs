@@ -0,0 +1,140 @@
+//! [`Arbitrary`] implementations for the garbled circuit messages.
+//!
+//! These are used to generate messages for property tests, including tests of how peers handle
+//! malformed input. Vectors in generated messages have at most [`MAX_LEN`] elements.
+
+use mpz_circuits::types::ValueType;
+use mpz_core::Block;
+use proptest::{
+    collection::vec,
+    option,
+    prelude::{any, prop_oneof, Arbitrary, BoxedStrategy, Just, Strategy},
+};
+
+use crate::{
+    ChaChaEncoder, Encoder, EncodingCommitment, EncryptedGate, EncryptedGateBatch, GarbledCircuit,
+};
+
+/// The maximum number of elements of a vector in a generated message.
+pub const MAX_LEN: usize = 32;
+
+fn block() -> impl Strategy<Value = Block> {
+    any::<[u8; 16]>().prop_map(Block::new)
+}
+
+fn value_type() -> impl Strategy<Value = ValueType> {
+    prop_oneof![
+        Just(ValueType::Bit),
+        Just(ValueType::U8),
+        Just(ValueType::U16),
+        Just(ValueType::U32),
+        Just(ValueType::U64),
+        Just(ValueType::U128),
+        Just(ValueType::I8),
+        Just(ValueType::I16),
+        Just(ValueType::I32),
+        Just(ValueType::I64),
+        Just(ValueType::I128),
+        (1..MAX_LEN).prop_map(|len| ValueType::Array(Box::new(ValueType::U8), len)),
+    ]
+}
+
+impl Arbitrary for EncryptedGate {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        [block(), block()].prop_map(EncryptedGate::new).boxed()
+    }
+}
+
+impl<const N: usize> Arbitrary for EncryptedGateBatch<N> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<EncryptedGate>(), N)
+            .prop_map(|gates| Self::new(gates.try_into().expect("batch has N gates")))
+            .boxed()
+    }
+}
+
+impl Arbitrary for EncodingCommitment {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (any::<[u8; 32]>(), value_type())
+            .prop_map(|(seed, ty)| ChaChaEncoder::new(seed).encode_by_type(0, &ty).commit())
+            .boxed()
+    }
+}
+
+impl Arbitrary for GarbledCircuit {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            vec(any::<EncryptedGate>(), 0..MAX_LEN),
+            option::of(vec(any::<EncodingCommitment>(), 0..4)),
+        )
+            .prop_map(|(gates, commitments)| Self { gates, commitments })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_core::serialize::WireMessage;
+    use proptest::{
+        prelude::{prop_assert, prop_assert_eq, proptest, TestCaseError},
+        sample::Index,
+    };
+
+    use super::*;
+
+    /// Checks that a message round-trips, and that malformed input is handled without panicking.
+    fn check<T: WireMessage>(
+        msg: &T,
+        index: Index,
+        mask: u8,
+        noise: &[u8],
+    ) -> Result<(), TestCaseError> {
+        let bytes = msg.to_wire();
+        let decoded = T::from_wire(&bytes).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert_eq!(decoded.to_wire(), bytes.clone());
+
+        prop_assert!(T::from_wire(&bytes[..index.index(bytes.len())]).is_err());
+
+        let mut corrupted = bytes.clone();
+        corrupted[index.index(bytes.len())] ^= mask;
+        _ = T::from_wire(&corrupted);
+
+        _ = T::from_wire(noise);
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_encrypted_gate_batch(
+            msg in any::<EncryptedGateBatch<8>>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_garbled_circuit(
+            msg in any::<GarbledCircuit>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+    }
+}
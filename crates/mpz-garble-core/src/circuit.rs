@@ -1,6 +1,11 @@
 use std::ops::Index;
 
-use mpz_core::Block;
+use bytemuck::{Pod, Zeroable};
+use bytes::Bytes;
+use mpz_core::{
+    serialize::{wire_payload, WireError, WireMessage},
+    Block,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{EncodingCommitment, DEFAULT_BATCH_SIZE};
@@ -11,7 +16,8 @@ use crate::{EncodingCommitment, DEFAULT_BATCH_SIZE};
 /// privacy-free garbling mode where it will be reduced to 1.
 ///
 /// We do not yet support privacy-free garbling.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, Pod, Zeroable)]
+#[repr(transparent)]
 pub struct EncryptedGate(#[serde(with = "serde_arrays")] pub(crate) [Block; 2]);
 
 impl EncryptedGate {
@@ -42,7 +48,7 @@ impl Index<usize> for EncryptedGate {
 /// - `N`: The size of a batch.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EncryptedGateBatch<const N: usize = DEFAULT_BATCH_SIZE>(
-    #[serde(with = "serde_arrays")] [EncryptedGate; N],
+    #[serde(with = "serde_arrays")] pub(crate) [EncryptedGate; N],
 );
 
 impl<const N: usize> EncryptedGateBatch<N> {
@@ -51,12 +57,65 @@ impl<const N: usize> EncryptedGateBatch<N> {
         Self(batch)
     }
 
+    /// Returns the encrypted gates of the batch.
+    pub fn gates(&self) -> &[EncryptedGate] {
+        &self.0
+    }
+
     /// Returns the inner array.
     pub fn into_array(self) -> [EncryptedGate; N] {
         self.0
     }
 }
 
+impl<const N: usize> WireMessage for EncryptedGateBatch<N> {}
+
+/// A batch of encrypted gates which is read in place from a receive buffer.
+///
+/// This is the wire encoding of an [`EncryptedGateBatch`], which is a plain concatenation of the
+/// encrypted gates. The gates are borrowed from the buffer instead of being copied into an array.
+///
+/// # Parameters
+///
+/// - `N`: The size of a batch.
+#[derive(Debug, Clone)]
+pub struct EncryptedGateBatchView<const N: usize = DEFAULT_BATCH_SIZE> {
+    payload: Bytes,
+}
+
+impl<const N: usize> EncryptedGateBatchView<N> {
+    /// Creates a view of a batch from its wire encoding, see [`WireMessage`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The received message.
+    pub fn from_wire(bytes: Bytes) -> Result<Self, WireError> {
+        let payload = wire_payload(&bytes)?;
+
+        let expected = N * std::mem::size_of::<EncryptedGate>();
+        if payload.len() != expected {
+            return Err(WireError::LengthMismatch {
+                expected,
+                actual: payload.len(),
+            });
+        }
+
+        Ok(Self {
+            payload: bytes.slice_ref(payload),
+        })
+    }
+
+    /// Returns the encrypted gates of the batch.
+    pub fn gates(&self) -> &[EncryptedGate] {
+        bytemuck::cast_slice(&self.payload)
+    }
+
+    /// Copies the batch into an owned [`EncryptedGateBatch`].
+    pub fn to_batch(&self) -> EncryptedGateBatch<N> {
+        EncryptedGateBatch::new(self.gates().try_into().expect("batch has N gates"))
+    }
+}
+
 /// A garbled circuit
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GarbledCircuit {
@@ -65,3 +124,31 @@ pub struct GarbledCircuit {
     /// Encoding commitments of the circuit outputs
     pub commitments: Option<Vec<EncodingCommitment>>,
 }
+
+impl WireMessage for GarbledCircuit {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_gate_batch_view() {
+        let gates: [EncryptedGate; 4] = std::array::from_fn(|i| {
+            EncryptedGate::new([Block::new([i as u8; 16]), Block::new([!(i as u8); 16])])
+        });
+
+        let bytes = Bytes::from(EncryptedGateBatch::new(gates).to_wire());
+        let view = EncryptedGateBatchView::<4>::from_wire(bytes.clone()).unwrap();
+
+        assert_eq!(view.gates(), &gates);
+        assert_eq!(view.to_batch().into_array(), gates);
+        // The gates are read from the receive buffer.
+        assert_eq!(view.gates().as_ptr() as *const u8, bytes[5..].as_ptr());
+
+        assert!(matches!(
+            EncryptedGateBatchView::<8>::from_wire(bytes.clone()),
+            Err(WireError::LengthMismatch { .. })
+        ));
+        assert!(EncryptedGateBatchView::<4>::from_wire(bytes.slice(..bytes.len() - 1)).is_err());
+    }
+}
@@ -0,0 +1,559 @@
+//! Interoperability with the semi-honest garbling of [EMP-toolkit](https://github.com/emp-toolkit).
+//!
+//! EMP-toolkit implements the same half-gate scheme, so a garbler or evaluator from this crate can
+//! be paired with an EMP peer (eg. `emp-sh2pc`) on a Bristol circuit once the following
+//! conventions are matched.
+//!
+//! # Hash and tweaks
+//!
+//! Instead of a single fixed-key AES with the gate id as tweak, EMP uses a multi-instance
+//! tweakable correlation-robust hash (`MITCCRH`), see [`MiTccrh`]. Every AND gate consumes two
+//! AES keys, `start_point ⊕ (gid << 64)` and `start_point ⊕ ((gid + 1) << 64)`, which are
+//! scheduled in batches of 8. The generator samples `start_point` and sends it to the
+//! evaluator before garbling.
+//!
+//! # Wire ordering
+//!
+//! Gates are garbled in the order in which they appear in the circuit, so a circuit loaded with
+//! [`Circuit::parse`](mpz_circuits::Circuit::parse) assigns the same gate ids as EMP's
+//! `BristolFashion`. The bits of each input and output are the consecutive Bristol wires of that
+//! value, in LSB0 order. Constant wires use EMP's public labels: the zero label for `0` and
+//! `delta` for `1` on the generator side, and the zero label on the evaluator side.
+//!
+//! # Framing
+//!
+//! EMP's `NetIO` sends raw data without any framing:
+//!
+//! - Labels are sent as 16 byte blocks, see [`encode_labels`].
+//! - Every AND gate is sent as its two 16 byte rows as soon as it is garbled, see
+//!   [`encode_gates`].
+//! - Outputs are revealed by the generator sending the pointer bit of each 0-label as one byte,
+//!   see [`output_pointer_bits`] and [`decode_output`].
+//!
+//! The inputs of the evaluator must be transferred with an OT which the EMP peer supports, which
+//! is out of scope for this module.
+//!
+//! # ⚠️ Warning ⚠️
+//!
+//! This mode is provided for cross-validation and mixed deployments. It is only secure against
+//! semi-honest adversaries, and it does not support the commitments or hashing of the regular
+//! generator and evaluator.
+
+use mpz_circuits::{
+    types::{TypeError, Value, ValueType},
+    Circuit, CircuitError, Gate,
+};
+use mpz_core::{aes::AesEncryptor, Block};
+
+use crate::{
+    circuit::EncryptedGate,
+    encoding::{state, Delta, EncodedValue, Label},
+    ValueError,
+};
+
+/// Number of AES keys scheduled at once by [`MiTccrh`].
+const KEY_BATCH_SIZE: usize = 8;
+
+/// Size of an encrypted gate in bytes.
+const GATE_LEN: usize = 32;
+
+/// Size of a label in bytes.
+const LABEL_LEN: usize = 16;
+
+/// Errors that can occur in EMP-toolkit compatibility mode.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum EmpError {
+    #[error(transparent)]
+    TypeError(#[from] TypeError),
+    #[error(transparent)]
+    CircuitError(#[from] CircuitError),
+    #[error(transparent)]
+    ValueError(#[from] ValueError),
+    #[error("invalid message length: expected {expected}, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("invalid pointer bit: {0}")]
+    InvalidPointerBit(u8),
+}
+
+/// EMP-toolkit's multi-instance tweakable circular correlation-robust hash (`MITCCRH`).
+///
+/// `H_k(x) = π_k(x) ⊕ x`, where `π_k` is AES keyed with `start_point ⊕ (gid << 64)` and the key
+/// id `gid` is incremented for every key used.
+pub struct MiTccrh {
+    start_point: Block,
+    gid: u64,
+    keys: Vec<AesEncryptor>,
+    key_used: usize,
+}
+
+opaque_debug::implement!(MiTccrh);
+
+impl MiTccrh {
+    /// Creates a new hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_point` - The start point sampled by the generator.
+    pub fn new(start_point: Block) -> Self {
+        Self {
+            start_point,
+            gid: 0,
+            keys: Vec::with_capacity(KEY_BATCH_SIZE),
+            key_used: KEY_BATCH_SIZE,
+        }
+    }
+
+    /// Returns the id of the next key to be scheduled.
+    pub fn gid(&self) -> u64 {
+        self.gid
+    }
+
+    fn renew_keys(&mut self) {
+        self.keys.clear();
+        for _ in 0..KEY_BATCH_SIZE {
+            let key = self.start_point ^ Block::from(((self.gid as u128) << 64).to_le_bytes());
+            self.keys.push(AesEncryptor::new(key));
+            self.gid += 1;
+        }
+        self.key_used = 0;
+    }
+
+    /// Hashes `blocks` in-place with `K` keys, using each key for `H` consecutive blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` does not divide the key batch size or if `blocks` does not contain `K * H`
+    /// blocks.
+    pub fn hash<const K: usize, const H: usize>(&mut self, blocks: &mut [Block]) {
+        assert!(K <= KEY_BATCH_SIZE && KEY_BATCH_SIZE.is_multiple_of(K));
+        assert_eq!(blocks.len(), K * H);
+
+        if self.key_used == KEY_BATCH_SIZE {
+            self.renew_keys();
+        }
+
+        for (key, blocks) in self.keys[self.key_used..self.key_used + K]
+            .iter()
+            .zip(blocks.chunks_exact_mut(H))
+        {
+            for block in blocks {
+                *block ^= key.encrypt_block(*block);
+            }
+        }
+
+        self.key_used += K;
+    }
+}
+
+/// Computes an EMP-compatible half-gate garbled AND gate.
+#[inline]
+fn and_gate(hash: &mut MiTccrh, x_0: Label, y_0: Label, delta: Block) -> (Label, EncryptedGate) {
+    let x_0 = x_0.into_block();
+    let y_0 = y_0.into_block();
+
+    let p_a = x_0.lsb();
+    let p_b = y_0.lsb();
+
+    let mut h = [x_0, x_0 ^ delta, y_0, y_0 ^ delta];
+    hash.hash::<2, 2>(&mut h);
+    let [hx_0, hx_1, hy_0, hy_1] = h;
+
+    // Garbled row of generator half-gate
+    let t_g = hx_0 ^ hx_1 ^ (Block::SELECT_MASK[p_b] & delta);
+    let w_g = hx_0 ^ (Block::SELECT_MASK[p_a] & t_g);
+
+    // Garbled row of evaluator half-gate
+    let t_e = hy_0 ^ hy_1;
+    let w_e = hy_0 ^ (Block::SELECT_MASK[p_b] & t_e);
+
+    (Label::new(w_g ^ w_e), EncryptedGate::new([t_g, t_e ^ x_0]))
+}
+
+/// Evaluates an EMP-compatible half-gate garbled AND gate.
+#[inline]
+fn and_gate_eval(hash: &mut MiTccrh, x: Label, y: Label, gate: &EncryptedGate) -> Label {
+    let x = x.into_block();
+    let y = y.into_block();
+
+    let s_a = x.lsb();
+    let s_b = y.lsb();
+
+    let mut h = [x, y];
+    hash.hash::<2, 1>(&mut h);
+    let [hx, hy] = h;
+
+    let w_g = hx ^ (Block::SELECT_MASK[s_a] & gate[0]);
+    let w_e = hy ^ (Block::SELECT_MASK[s_b] & (gate[1] ^ x));
+
+    Label::new(w_g ^ w_e)
+}
+
+/// Assigns the encoded inputs to the input wires of a circuit.
+pub(crate) fn assign_inputs<S, E>(
+    circ: &Circuit,
+    inputs: &[EncodedValue<S>],
+    labels: &mut [Label],
+) -> Result<(), E>
+where
+    S: state::LabelState,
+    E: From<CircuitError> + From<TypeError>,
+{
+    if inputs.len() != circ.inputs().len() {
+        Err(CircuitError::InvalidInputCount(
+            circ.inputs().len(),
+            inputs.len(),
+        ))?;
+    }
+
+    for (encoded, input) in inputs.iter().zip(circ.inputs()) {
+        if encoded.value_type() != input.value_type() {
+            Err(TypeError::UnexpectedType {
+                expected: input.value_type(),
+                actual: encoded.value_type(),
+            })?;
+        }
+
+        for (label, node) in encoded.iter().zip(input.iter()) {
+            labels[node.id()] = *label;
+        }
+    }
+
+    Ok(())
+}
+
+/// EMP-toolkit compatible garbled circuit generator.
+#[derive(Debug)]
+pub struct EmpGenerator {
+    hash: MiTccrh,
+    delta: Delta,
+}
+
+impl EmpGenerator {
+    /// Creates a new generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_point` - The start point of the hash, which must be sent to the evaluator.
+    /// * `delta` - The global offset.
+    pub fn new(start_point: Block, delta: Delta) -> Self {
+        Self {
+            hash: MiTccrh::new(start_point),
+            delta,
+        }
+    }
+
+    /// Garbles a circuit.
+    ///
+    /// Consecutive calls continue the key schedule, as EMP does for consecutive circuits.
+    ///
+    /// Returns the encrypted AND gates in circuit order, and the encoded outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to garble.
+    /// * `inputs` - The full encodings of the circuit inputs, using the generator's delta.
+    pub fn garble(
+        &mut self,
+        circ: &Circuit,
+        inputs: &[EncodedValue<state::Full>],
+    ) -> Result<(Vec<EncryptedGate>, Vec<EncodedValue<state::Full>>), EmpError> {
+        let delta = *self.delta;
+
+        let mut labels = vec![Label::default(); circ.feed_count()];
+        // Public labels of the constant wires.
+        labels[1] = Label::new(delta);
+        assign_inputs::<_, EmpError>(circ, inputs, &mut labels)?;
+
+        let mut gates = Vec::with_capacity(circ.and_count());
+        for gate in circ.gates() {
+            match gate {
+                Gate::Xor { x, y, z } => labels[z.id()] = labels[x.id()] ^ labels[y.id()],
+                Gate::And { x, y, z } => {
+                    let (z_0, gate) =
+                        and_gate(&mut self.hash, labels[x.id()], labels[y.id()], delta);
+                    labels[z.id()] = z_0;
+                    gates.push(gate);
+                }
+                Gate::Inv { x, z } => labels[z.id()] = labels[x.id()] ^ Label::new(delta),
+            }
+        }
+
+        let outputs = circ
+            .outputs()
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| labels[node.id()]).collect();
+                EncodedValue::<state::Full>::from_labels(output.value_type(), self.delta, &labels)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((gates, outputs))
+    }
+}
+
+/// EMP-toolkit compatible garbled circuit evaluator.
+#[derive(Debug)]
+pub struct EmpEvaluator {
+    hash: MiTccrh,
+}
+
+impl EmpEvaluator {
+    /// Creates a new evaluator.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_point` - The start point of the hash, received from the generator.
+    pub fn new(start_point: Block) -> Self {
+        Self {
+            hash: MiTccrh::new(start_point),
+        }
+    }
+
+    /// Evaluates a garbled circuit.
+    ///
+    /// Returns the active encodings of the outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to evaluate.
+    /// * `inputs` - The active encodings of the circuit inputs.
+    /// * `gates` - The encrypted AND gates, in circuit order.
+    pub fn evaluate(
+        &mut self,
+        circ: &Circuit,
+        inputs: &[EncodedValue<state::Active>],
+        gates: &[EncryptedGate],
+    ) -> Result<Vec<EncodedValue<state::Active>>, EmpError> {
+        if gates.len() != circ.and_count() {
+            return Err(EmpError::InvalidLength {
+                expected: circ.and_count(),
+                actual: gates.len(),
+            });
+        }
+
+        let mut labels = vec![Label::default(); circ.feed_count()];
+        assign_inputs::<_, EmpError>(circ, inputs, &mut labels)?;
+
+        let mut gates = gates.iter();
+        for gate in circ.gates() {
+            match gate {
+                Gate::Xor { x, y, z } => labels[z.id()] = labels[x.id()] ^ labels[y.id()],
+                Gate::And { x, y, z } => {
+                    let gate = gates.next().expect("gate count was checked");
+                    labels[z.id()] =
+                        and_gate_eval(&mut self.hash, labels[x.id()], labels[y.id()], gate);
+                }
+                Gate::Inv { x, z } => labels[z.id()] = labels[x.id()],
+            }
+        }
+
+        circ.outputs()
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| labels[node.id()]).collect();
+                Ok(EncodedValue::<state::Active>::from_labels(
+                    output.value_type(),
+                    &labels,
+                )?)
+            })
+            .collect()
+    }
+}
+
+/// Encodes encrypted gates as EMP sends them, 32 bytes per gate.
+pub fn encode_gates(gates: &[EncryptedGate]) -> Vec<u8> {
+    gates.iter().flat_map(|gate| gate.to_bytes()).collect()
+}
+
+/// Decodes encrypted gates sent by an EMP generator.
+pub fn decode_gates(bytes: &[u8]) -> Result<Vec<EncryptedGate>, EmpError> {
+    if !bytes.len().is_multiple_of(GATE_LEN) {
+        return Err(EmpError::InvalidLength {
+            expected: bytes.len() / GATE_LEN * GATE_LEN,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(bytes
+        .chunks_exact(GATE_LEN)
+        .map(|gate| {
+            let (t_g, t_e) = gate.split_at(LABEL_LEN);
+            EncryptedGate::new([
+                Block::new(t_g.try_into().unwrap()),
+                Block::new(t_e.try_into().unwrap()),
+            ])
+        })
+        .collect())
+}
+
+/// Encodes the labels of an active encoding as EMP sends them, 16 bytes per label in LSB0 order.
+pub fn encode_labels(value: &EncodedValue<state::Active>) -> Vec<u8> {
+    value
+        .iter()
+        .flat_map(|label| label.into_block().to_bytes())
+        .collect()
+}
+
+/// Decodes the labels of an active encoding sent by an EMP peer.
+///
+/// # Arguments
+///
+/// * `value_type` - The type of the encoded value.
+/// * `bytes` - The labels, 16 bytes per label in LSB0 order.
+pub fn decode_labels(
+    value_type: ValueType,
+    bytes: &[u8],
+) -> Result<EncodedValue<state::Active>, EmpError> {
+    let expected = value_type.len() * LABEL_LEN;
+    if bytes.len() != expected {
+        return Err(EmpError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    let labels: Vec<Label> = bytes
+        .chunks_exact(LABEL_LEN)
+        .map(|label| Label::new(Block::new(label.try_into().unwrap())))
+        .collect();
+
+    Ok(EncodedValue::<state::Active>::from_labels(
+        value_type, &labels,
+    )?)
+}
+
+/// Returns the pointer bits of the 0-labels of an output, one byte per bit, as an EMP generator
+/// reveals them.
+pub fn output_pointer_bits(value: &EncodedValue<state::Full>) -> Vec<u8> {
+    value
+        .iter_blocks()
+        .map(|[low, _]| low.lsb() as u8)
+        .collect()
+}
+
+/// Decodes an active output with the pointer bits revealed by an EMP generator.
+///
+/// # Arguments
+///
+/// * `value` - The active encoding of the output.
+/// * `pointer_bits` - The pointer bits of the 0-labels, one byte per bit.
+pub fn decode_output(
+    value: &EncodedValue<state::Active>,
+    pointer_bits: &[u8],
+) -> Result<Value, EmpError> {
+    let labels: Vec<&Label> = value.iter().collect();
+    if pointer_bits.len() != labels.len() {
+        return Err(EmpError::InvalidLength {
+            expected: labels.len(),
+            actual: pointer_bits.len(),
+        });
+    }
+
+    let bits = labels
+        .iter()
+        .zip(pointer_bits)
+        .map(|(label, bit)| match bit {
+            0 | 1 => Ok((label.into_block().lsb() as u8 ^ bit) == 1),
+            _ => Err(EmpError::InvalidPointerBit(*bit)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(value.value_type().from_bin_repr(&bits)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChaChaEncoder, Encoder};
+
+    use aes::{
+        cipher::{BlockEncrypt, KeyInit},
+        Aes128,
+    };
+    use mpz_circuits::circuits::AES128;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_mitccrh_key_schedule() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let start_point: Block = rng.gen();
+        let mut hash = MiTccrh::new(start_point);
+
+        // Hash enough blocks to schedule a second batch of keys.
+        for i in 0..5u64 {
+            let x: Block = rng.gen();
+            let y: Block = rng.gen();
+            let mut h = [x, y];
+            hash.hash::<2, 1>(&mut h);
+
+            let gid = 2 * i as u128;
+            let expected: [Block; 2] = std::array::from_fn(|k| {
+                let key = start_point ^ Block::from(((gid + k as u128) << 64).to_le_bytes());
+                let cipher = Aes128::new_from_slice(&key.to_bytes()).unwrap();
+                let input = [x, y][k];
+                let mut block = input.to_bytes().into();
+                cipher.encrypt_block(&mut block);
+                Block::from(<[u8; 16]>::from(block)) ^ input
+            });
+
+            assert_eq!(h, expected);
+        }
+
+        assert_eq!(hash.gid(), 16);
+    }
+
+    #[test]
+    fn test_emp_garble() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new(rng.gen());
+        let start_point: Block = rng.gen();
+
+        let key = [69u8; 16];
+        let msg = [42u8; 16];
+        let expected = AES128.evaluate(&[key.into(), msg.into()]).unwrap();
+
+        let full_inputs: Vec<EncodedValue<state::Full>> = AES128
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(id, input)| encoder.encode_by_type(id as u64, &input.value_type()))
+            .collect();
+
+        let mut gen = EmpGenerator::new(start_point, encoder.delta());
+        let mut ev = EmpEvaluator::new(start_point);
+
+        // Garble twice to check that the key schedule stays in sync across circuits.
+        for _ in 0..2 {
+            let (gates, full_outputs) = gen.garble(&AES128, &full_inputs).unwrap();
+
+            let bytes = encode_gates(&gates);
+            assert_eq!(bytes.len(), AES128.and_count() * 32);
+
+            let active_inputs = vec![
+                decode_labels(
+                    full_inputs[0].value_type(),
+                    &encode_labels(&full_inputs[0].select(key).unwrap()),
+                )
+                .unwrap(),
+                full_inputs[1].select(msg).unwrap(),
+            ];
+
+            let active_outputs = ev
+                .evaluate(&AES128, &active_inputs, &decode_gates(&bytes).unwrap())
+                .unwrap();
+
+            let outputs: Vec<Value> = active_outputs
+                .iter()
+                .zip(&full_outputs)
+                .map(|(active, full)| {
+                    full.verify(active).unwrap();
+                    decode_output(active, &output_pointer_bits(full)).unwrap()
+                })
+                .collect();
+
+            assert_eq!(outputs, expected);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Conversions of binary encoded values to one-hot and arithmetic encodings.
+//!
+//! A value which is encoded with a label for every bit can be converted to other encodings without
+//! being decoded, so that protocols can switch between garbled circuits and arithmetic
+//! computation on the same value.
+//!
+//! # One-hot encoding
+//!
+//! A [`OneHotEncoding`] of an `n` bit value assigns a key to each of the `2^n` possible values,
+//! which is derived from the labels of the bits of the value. The evaluator can derive the key of
+//! the active value and its position in a table permuted by the pointer bits, but nothing about
+//! the other keys. Converting a binary encoded value to a one-hot encoding is free, which makes it
+//! suitable for computing arbitrary functions of small values with a single table lookup.
+//!
+//! # Arithmetic encoding
+//!
+//! An arithmetic encoding of a value `x` is an additive sharing of `x` in a field, i.e. the
+//! generator holds a share `g` and the evaluator holds a share `e` such that `g + e = x`. Both
+//! conversions to an arithmetic encoding require the generator to send a [`ConversionTable`] to
+//! the evaluator.
+//!
+//! A binary encoded value is converted to an arithmetic encoding using one projection per bit,
+//! ie the table has `2n` rows. A one-hot encoded value is converted to an arithmetic encoding of a
+//! function of the value with a lookup table of `2^n` rows.
+//!
+//! Every conversion must use a unique id, otherwise the evaluator learns the difference between
+//! the tables.
+
+use blake3::Hasher;
+use mpz_fields::Field;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::{state, EncodedValue, Label, LabelState, ValueError};
+
+/// The maximum number of bits of a one-hot encoded value.
+pub const MAX_ONE_HOT_BITS: usize = 16;
+
+const PROJECTION_DOMAIN: &[u8] = b"mpz-garble projection";
+const ONE_HOT_DOMAIN: &[u8] = b"mpz-garble one-hot";
+
+/// A table sent from the generator to the evaluator to convert an encoded value to an arithmetic
+/// encoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversionTable<F>(Vec<F>);
+
+impl<F> ConversionTable<F> {
+    /// Returns the number of rows of the table.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// A one-hot encoding of a value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneHotEncoding<S: LabelState> {
+    state: S,
+    labels: Vec<Label>,
+}
+
+impl<S: LabelState> OneHotEncoding<S> {
+    /// Returns the number of bits of the value.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+}
+
+impl OneHotEncoding<state::Full> {
+    /// Returns the key of a value.
+    fn key(&self, id: u64, value: u64) -> Hasher {
+        let delta = self.state.delta;
+        let mut hasher = Hasher::new();
+        hasher.update(ONE_HOT_DOMAIN);
+        hasher.update(&id.to_le_bytes());
+        for (i, label) in self.labels.iter().enumerate() {
+            let label = if (value >> i) & 1 == 1 {
+                label ^ delta
+            } else {
+                *label
+            };
+            hasher.update(&label.into_block().to_bytes());
+        }
+        hasher
+    }
+
+    /// Returns the position of the zero value in the table, which is permuted by the pointer
+    /// bits.
+    fn permutation(&self) -> u64 {
+        pointer_bits(&self.labels)
+    }
+
+    /// Creates a lookup table which converts the value to an arithmetic encoding of `f(value)`.
+    ///
+    /// Returns the share of the generator and the table which is sent to the evaluator.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique id of the conversion.
+    /// * `rng` - The rng used to sample the share of the generator.
+    /// * `f` - The function to compute.
+    pub fn lookup<F: Field, R: Rng + ?Sized>(
+        &self,
+        id: u64,
+        rng: &mut R,
+        f: impl Fn(u64) -> F,
+    ) -> (F, ConversionTable<F>) {
+        let share = F::rand(rng);
+        let permutation = self.permutation();
+
+        let rows = (0..1u64 << self.len())
+            .map(|index| {
+                let value = index ^ permutation;
+                hash_to_field::<F>(self.key(id, value)) + f(value) + -share
+            })
+            .collect();
+
+        (share, ConversionTable(rows))
+    }
+}
+
+impl OneHotEncoding<state::Active> {
+    /// Returns the key of the active value.
+    fn key(&self, id: u64) -> Hasher {
+        let mut hasher = Hasher::new();
+        hasher.update(ONE_HOT_DOMAIN);
+        hasher.update(&id.to_le_bytes());
+        for label in &self.labels {
+            hasher.update(&label.into_block().to_bytes());
+        }
+        hasher
+    }
+
+    /// Returns the position of the active value in the table.
+    pub fn index(&self) -> usize {
+        pointer_bits(&self.labels) as usize
+    }
+
+    /// Looks up the active value in the table, returning the share of the evaluator.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique id of the conversion.
+    /// * `table` - The lookup table received from the generator.
+    pub fn lookup<F: Field>(&self, id: u64, table: &ConversionTable<F>) -> Result<F, ValueError> {
+        let expected = 1usize << self.len();
+        if table.len() != expected {
+            return Err(ValueError::InvalidLength {
+                expected,
+                actual: table.len(),
+            });
+        }
+
+        Ok(table.0[self.index()] + -hash_to_field::<F>(self.key(id)))
+    }
+}
+
+impl<S: LabelState> EncodedValue<S> {
+    /// Returns the labels of a value which can be converted, ie which is not an array.
+    fn convertible_labels(&self) -> Result<Vec<Label>, ValueError> {
+        if let EncodedValue::Array(_) = self {
+            return Err(ValueError::UnsupportedConversion(self.value_type()));
+        }
+
+        Ok(self.iter().copied().collect())
+    }
+}
+
+impl EncodedValue<state::Full> {
+    /// Converts the value to a one-hot encoding.
+    ///
+    /// Returns an error if the value is an array or has more than [`MAX_ONE_HOT_BITS`] bits.
+    pub fn to_one_hot(&self) -> Result<OneHotEncoding<state::Full>, ValueError> {
+        let labels = self.convertible_labels()?;
+        if labels.len() > MAX_ONE_HOT_BITS {
+            return Err(ValueError::UnsupportedConversion(self.value_type()));
+        }
+
+        Ok(OneHotEncoding {
+            state: state::Full {
+                delta: self.delta(),
+            },
+            labels,
+        })
+    }
+
+    /// Creates a table which converts the value to an arithmetic encoding.
+    ///
+    /// The bits of the value are interpreted as the bits of an integer in LSB0 order, which is
+    /// mapped to `sum(bit_i * 2^i)` in the field.
+    ///
+    /// Returns the share of the generator and the table which is sent to the evaluator.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique id of the conversion.
+    /// * `rng` - The rng used to sample the masks of the table.
+    pub fn to_arithmetic<F: Field, R: Rng + ?Sized>(
+        &self,
+        id: u64,
+        rng: &mut R,
+    ) -> Result<(F, ConversionTable<F>), ValueError> {
+        let labels = self.convertible_labels()?;
+        if labels.len() > F::BIT_SIZE {
+            return Err(ValueError::UnsupportedConversion(self.value_type()));
+        }
+
+        let delta = self.delta();
+        let mut share = F::zero();
+        let mut rows = vec![F::zero(); 2 * labels.len()];
+        for (i, (low, rows)) in labels.iter().zip(rows.chunks_exact_mut(2)).enumerate() {
+            let mask = F::rand(rng);
+            share = share + -mask;
+
+            let high = low ^ delta;
+            let low_row = low.pointer_bit() as usize;
+            rows[low_row] = hash_to_field::<F>(projection_key(id, i, low)) + mask;
+            rows[low_row ^ 1] =
+                hash_to_field::<F>(projection_key(id, i, &high)) + F::two_pow(i as u32) + mask;
+        }
+
+        Ok((share, ConversionTable(rows)))
+    }
+}
+
+impl EncodedValue<state::Active> {
+    /// Converts the value to a one-hot encoding.
+    ///
+    /// Returns an error if the value is an array or has more than [`MAX_ONE_HOT_BITS`] bits.
+    pub fn to_one_hot(&self) -> Result<OneHotEncoding<state::Active>, ValueError> {
+        let labels = self.convertible_labels()?;
+        if labels.len() > MAX_ONE_HOT_BITS {
+            return Err(ValueError::UnsupportedConversion(self.value_type()));
+        }
+
+        Ok(OneHotEncoding {
+            state: state::Active,
+            labels,
+        })
+    }
+
+    /// Converts the value to an arithmetic encoding, returning the share of the evaluator.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique id of the conversion.
+    /// * `table` - The table received from the generator.
+    pub fn to_arithmetic<F: Field>(
+        &self,
+        id: u64,
+        table: &ConversionTable<F>,
+    ) -> Result<F, ValueError> {
+        let labels = self.convertible_labels()?;
+        if table.len() != 2 * labels.len() {
+            return Err(ValueError::InvalidLength {
+                expected: 2 * labels.len(),
+                actual: table.len(),
+            });
+        }
+
+        Ok(labels.iter().zip(table.0.chunks_exact(2)).enumerate().fold(
+            F::zero(),
+            |share, (i, (label, rows))| {
+                share
+                    + rows[label.pointer_bit() as usize]
+                    + -hash_to_field::<F>(projection_key(id, i, label))
+            },
+        ))
+    }
+}
+
+/// Returns the key of the projection of a bit.
+fn projection_key(id: u64, bit: usize, label: &Label) -> Hasher {
+    let mut hasher = Hasher::new();
+    hasher.update(PROJECTION_DOMAIN);
+    hasher.update(&id.to_le_bytes());
+    hasher.update(&(bit as u64).to_le_bytes());
+    hasher.update(&label.into_block().to_bytes());
+    hasher
+}
+
+/// Derives a field element from a key.
+fn hash_to_field<F: Field>(key: Hasher) -> F {
+    let seed: [u8; 32] = key.finalize().into();
+    F::rand(&mut ChaCha12Rng::from_seed(seed))
+}
+
+/// Returns the pointer bits of the labels as an integer in LSB0 order.
+fn pointer_bits(labels: &[Label]) -> u64 {
+    labels.iter().enumerate().fold(0, |bits, (i, label)| {
+        bits | ((label.pointer_bit() as u64) << i)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::types::StaticValueType;
+    use mpz_fields::{gf2_128::Gf2_128, p256::P256};
+
+    use super::*;
+    use crate::{ChaChaEncoder, Encoder};
+
+    #[test]
+    fn test_to_arithmetic() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+        let full = encoder.encode_by_type(0, &u32::value_type());
+
+        for value in [0u32, 1, 0xdeadbeef, u32::MAX] {
+            let active = full.select(value).unwrap();
+
+            let (gen_share, table) = full.to_arithmetic::<P256, _>(1, &mut rng).unwrap();
+            let ev_share = active.to_arithmetic(1, &table).unwrap();
+
+            assert_eq!(gen_share + ev_share, P256::new(value as u128).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_one_hot_lookup() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+        let full = encoder.encode_by_type(0, &u8::value_type());
+        let one_hot = full.to_one_hot().unwrap();
+
+        let f = |value: u64| Gf2_128::new((value * value) as u128);
+        for value in [0u8, 3, 42, 255] {
+            let active = full.select(value).unwrap().to_one_hot().unwrap();
+
+            let (gen_share, table) = one_hot.lookup(2, &mut rng, f);
+            let ev_share = active.lookup(2, &table).unwrap();
+
+            assert_eq!(table.len(), 256);
+            assert_eq!(gen_share + ev_share, f(value as u64));
+        }
+    }
+
+    #[test]
+    fn test_conversion_wrong_id() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+        let full = encoder.encode_by_type(0, &u8::value_type());
+        let active = full.select(42u8).unwrap();
+
+        let (gen_share, table) = full.to_arithmetic::<P256, _>(1, &mut rng).unwrap();
+        let ev_share = active.to_arithmetic(2, &table).unwrap();
+
+        assert_ne!(gen_share + ev_share, P256::new(42u128).unwrap());
+    }
+
+    #[test]
+    fn test_conversion_unsupported() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+
+        let array = encoder.encode_by_type(0, &<[u8; 2]>::value_type());
+        assert!(array.to_arithmetic::<P256, _>(1, &mut rng).is_err());
+        assert!(array.to_one_hot().is_err());
+
+        let wide = encoder.encode_by_type(1, &u32::value_type());
+        assert!(wide.to_one_hot().is_err());
+    }
+}
@@ -40,6 +40,16 @@ pub struct ChaChaEncoder {
     delta: Delta,
 }
 
+#[cfg(feature = "zeroize")]
+impl Drop for ChaChaEncoder {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+
+        self.seed.zeroize();
+        self.delta.zeroize();
+    }
+}
+
 impl Default for ChaChaEncoder {
     fn default() -> Self {
         Self::new(OsRng.gen())
@@ -105,6 +115,11 @@ impl Encoder for ChaChaEncoder {
             ValueType::U32 => self.encode::<u32>(id).into(),
             ValueType::U64 => self.encode::<u64>(id).into(),
             ValueType::U128 => self.encode::<u128>(id).into(),
+            ValueType::I8 => self.encode::<i8>(id).into(),
+            ValueType::I16 => self.encode::<i16>(id).into(),
+            ValueType::I32 => self.encode::<i32>(id).into(),
+            ValueType::I64 => self.encode::<i64>(id).into(),
+            ValueType::I128 => self.encode::<i128>(id).into(),
             ValueType::Array(_, _) => {
                 let mut rng = self.get_rng(id);
 
@@ -140,6 +155,7 @@ mod test {
     #[case::u64(PhantomData::<u64>)]
     #[case::u64(PhantomData::<u64>)]
     #[case::u128(PhantomData::<u128>)]
+    #[case::i32(PhantomData::<i32>)]
     fn test_encoder_idempotent<T: Encode + BinaryLength + Default>(
         encoder: ChaChaEncoder,
         #[case] _pd: PhantomData<T>,
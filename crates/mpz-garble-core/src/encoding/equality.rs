@@ -2,6 +2,7 @@ use blake3::Hasher;
 
 use mpz_circuits::types::Value;
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
 
 use crate::{encoding_state, EncodedValue};
 
@@ -13,9 +14,23 @@ use crate::{encoding_state, EncodedValue};
 /// equality check to ensure that the output values are equal.
 ///
 /// This equality check guarantees the authenticity of the decoded output values.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Equality checks are compared in constant time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EqualityCheck([u8; 32]);
 
+impl ConstantTimeEq for EqualityCheck {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for EqualityCheck {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 impl EqualityCheck {
     /// Creates a new equality check value from the given encodings and purported
     /// values.
@@ -48,13 +63,15 @@ impl EqualityCheck {
                     .select(purported_value.clone())
                     .expect("value type should match encoding type")
                     .iter()
-                    .flat_map(|label| label.to_inner().to_bytes())
+                    .flat_map(|label| label.into_block().to_bytes())
                     .collect::<Vec<_>>()
             },
         );
-        let peer_active_iter = peer_encodings
-            .iter()
-            .flat_map(|encoded| encoded.iter().flat_map(|label| label.to_inner().to_bytes()));
+        let peer_active_iter = peer_encodings.iter().flat_map(|encoded| {
+            encoded
+                .iter()
+                .flat_map(|label| label.into_block().to_bytes())
+        });
 
         let bytes: Vec<u8> = if order {
             our_active_iter.chain(peer_active_iter).collect()
@@ -12,6 +12,7 @@
 //! The Free-XOR technique stipulates that a [global binary offset](Delta) is used such that the labels for bit
 //! value 1 are generated by XORing the label for bit value 0 with the global offset, ie W_1 = W_0 ^ Delta.
 
+mod convert;
 mod encoder;
 mod equality;
 mod ops;
@@ -25,7 +26,9 @@ use std::{
 use mpz_core::Block;
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Deserializer, Serialize};
+use subtle::{Choice, ConstantTimeEq};
 
+pub use convert::{ConversionTable, OneHotEncoding, MAX_ONE_HOT_BITS};
 pub use encoder::{ChaChaEncoder, Encoder};
 pub use equality::EqualityCheck;
 pub use value::{Decoding, Encode, EncodedValue, EncodingCommitment, ValueError};
@@ -61,6 +64,13 @@ impl Deref for Delta {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Delta {
+    fn zeroize(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
 /// Module containing the states of an encoded value.
 pub mod state {
     use super::*;
@@ -151,7 +161,7 @@ impl<const N: usize> Labels<N, state::Full> {
     pub(crate) fn verify(&self, active: &Labels<N, state::Active>) -> Result<(), ValueError> {
         for (low, active) in self.labels.iter().zip(active.labels.iter()) {
             let high = low ^ self.state.delta;
-            if !(active == low || active == &high) {
+            if !bool::from(active.ct_eq(low) | active.ct_eq(&high)) {
                 return Err(ValueError::InvalidActiveEncoding);
             }
         }
@@ -263,6 +273,16 @@ impl<const N: usize> BitXor<Labels<N, state::Active>> for &Labels<N, state::Acti
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<const N: usize, S: LabelState> Drop for Labels<N, S> {
+    fn drop(&mut self) {
+        // Labels may be shared, in which case only the last reference zeroizes them.
+        if let Some(labels) = Arc::get_mut(&mut self.labels) {
+            zeroize::Zeroize::zeroize(labels);
+        }
+    }
+}
+
 impl<const N: usize, S: LabelState> Index<usize> for Labels<N, S> {
     type Output = Label;
 
@@ -285,9 +305,9 @@ impl Label {
         Self(value)
     }
 
-    /// Returns inner block
+    /// Returns the inner block.
     #[inline]
-    pub(crate) fn to_inner(self) -> Block {
+    pub fn into_block(self) -> Block {
         self.0
     }
 
@@ -305,6 +325,16 @@ impl Label {
     }
 }
 
+impl ConstantTimeEq for Label {
+    #[inline]
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::DefaultIsZeroes for Label {}
+
 impl BitXor<Label> for Label {
     type Output = Self;
 
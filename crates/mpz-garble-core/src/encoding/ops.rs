@@ -20,6 +20,11 @@ macro_rules! impl_encoded_xor {
                     (EncodedValue::U32(a), EncodedValue::U32(b)) => Ok(EncodedValue::U32(a ^ b)),
                     (EncodedValue::U64(a), EncodedValue::U64(b)) => Ok(EncodedValue::U64(a ^ b)),
                     (EncodedValue::U128(a), EncodedValue::U128(b)) => Ok(EncodedValue::U128(a ^ b)),
+                    (EncodedValue::I8(a), EncodedValue::I8(b)) => Ok(EncodedValue::I8(a ^ b)),
+                    (EncodedValue::I16(a), EncodedValue::I16(b)) => Ok(EncodedValue::I16(a ^ b)),
+                    (EncodedValue::I32(a), EncodedValue::I32(b)) => Ok(EncodedValue::I32(a ^ b)),
+                    (EncodedValue::I64(a), EncodedValue::I64(b)) => Ok(EncodedValue::I64(a ^ b)),
+                    (EncodedValue::I128(a), EncodedValue::I128(b)) => Ok(EncodedValue::I128(a ^ b)),
                     (EncodedValue::Array(a), EncodedValue::Array(b))
                         if self.value_type() == rhs.value_type() =>
                     {
@@ -49,6 +54,11 @@ macro_rules! impl_encoded_xor {
                     (EncodedValue::U32(a), EncodedValue::U32(b)) => Ok(EncodedValue::U32(a ^ b)),
                     (EncodedValue::U64(a), EncodedValue::U64(b)) => Ok(EncodedValue::U64(a ^ b)),
                     (EncodedValue::U128(a), EncodedValue::U128(b)) => Ok(EncodedValue::U128(a ^ b)),
+                    (EncodedValue::I8(a), EncodedValue::I8(b)) => Ok(EncodedValue::I8(a ^ b)),
+                    (EncodedValue::I16(a), EncodedValue::I16(b)) => Ok(EncodedValue::I16(a ^ b)),
+                    (EncodedValue::I32(a), EncodedValue::I32(b)) => Ok(EncodedValue::I32(a ^ b)),
+                    (EncodedValue::I64(a), EncodedValue::I64(b)) => Ok(EncodedValue::I64(a ^ b)),
+                    (EncodedValue::I128(a), EncodedValue::I128(b)) => Ok(EncodedValue::I128(a ^ b)),
                     (EncodedValue::Array(a), EncodedValue::Array(b))
                         if self.value_type() == rhs.value_type() =>
                     {
@@ -78,6 +88,11 @@ macro_rules! impl_encoded_xor {
                     (EncodedValue::U32(a), EncodedValue::U32(b)) => Ok(EncodedValue::U32(a ^ b)),
                     (EncodedValue::U64(a), EncodedValue::U64(b)) => Ok(EncodedValue::U64(a ^ b)),
                     (EncodedValue::U128(a), EncodedValue::U128(b)) => Ok(EncodedValue::U128(a ^ b)),
+                    (EncodedValue::I8(a), EncodedValue::I8(b)) => Ok(EncodedValue::I8(a ^ b)),
+                    (EncodedValue::I16(a), EncodedValue::I16(b)) => Ok(EncodedValue::I16(a ^ b)),
+                    (EncodedValue::I32(a), EncodedValue::I32(b)) => Ok(EncodedValue::I32(a ^ b)),
+                    (EncodedValue::I64(a), EncodedValue::I64(b)) => Ok(EncodedValue::I64(a ^ b)),
+                    (EncodedValue::I128(a), EncodedValue::I128(b)) => Ok(EncodedValue::I128(a ^ b)),
                     (EncodedValue::Array(a), EncodedValue::Array(b))
                         if self.value_type() == rhs.value_type() =>
                     {
@@ -107,6 +122,11 @@ macro_rules! impl_encoded_xor {
                     (EncodedValue::U32(a), EncodedValue::U32(b)) => Ok(EncodedValue::U32(a ^ b)),
                     (EncodedValue::U64(a), EncodedValue::U64(b)) => Ok(EncodedValue::U64(a ^ b)),
                     (EncodedValue::U128(a), EncodedValue::U128(b)) => Ok(EncodedValue::U128(a ^ b)),
+                    (EncodedValue::I8(a), EncodedValue::I8(b)) => Ok(EncodedValue::I8(a ^ b)),
+                    (EncodedValue::I16(a), EncodedValue::I16(b)) => Ok(EncodedValue::I16(a ^ b)),
+                    (EncodedValue::I32(a), EncodedValue::I32(b)) => Ok(EncodedValue::I32(a ^ b)),
+                    (EncodedValue::I64(a), EncodedValue::I64(b)) => Ok(EncodedValue::I64(a ^ b)),
+                    (EncodedValue::I128(a), EncodedValue::I128(b)) => Ok(EncodedValue::I128(a ^ b)),
                     (EncodedValue::Array(a), EncodedValue::Array(b))
                         if self.value_type() == rhs.value_type() =>
                     {
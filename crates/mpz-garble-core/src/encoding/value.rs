@@ -1,5 +1,4 @@
 use itybity::{FromBitIterator, ToBits};
-use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use std::ops::BitXor;
 
@@ -20,6 +19,10 @@ pub enum ValueError {
     InvalidActiveEncoding,
     #[error("invalid commitment")]
     InvalidCommitment,
+    #[error("value of type {0:?} can not be converted")]
+    UnsupportedConversion(ValueType),
+    #[error("value of type {0:?} can not be encoded")]
+    UnsupportedType(ValueType),
 }
 
 /// A trait for encoding values.
@@ -119,7 +122,7 @@ macro_rules! define_encoded_value {
                             .map(|labels| Self::from_labels((*ty).clone(), delta, labels).expect("length should match"))
                             .collect(),
                     ),
-                    _ => unimplemented!("unimplemented value type: {:?}", value_type),
+                    _ => return Err(ValueError::UnsupportedType(value_type)),
                 };
 
                 Ok(encoded)
@@ -229,7 +232,7 @@ macro_rules! define_encoded_value {
                             .map(|labels| Self::from_labels((*ty).clone(), labels).unwrap())
                             .collect(),
                     ),
-                    _ => unimplemented!("unimplemented value type: {:?}", value_type),
+                    _ => return Err(ValueError::UnsupportedType(value_type)),
                 };
 
                 Ok(encoded)
@@ -306,10 +309,13 @@ macro_rules! define_encoded_value {
     };
 }
 
-define_encoded_value!(Bit, U8, U16, U32, U64, U128);
+define_encoded_value!(Bit, U8, U16, U32, U64, U128, I8, I16, I32, I64, I128);
 
 macro_rules! define_encoded_variant {
     ($EncodedTy:ident, $PlaintextTy:ty, $len:expr) => {
+        define_encoded_variant!($EncodedTy, $PlaintextTy, $PlaintextTy, $len);
+    };
+    ($EncodedTy:ident, $PlaintextTy:ty, $BitsTy:ty, $len:expr) => {
         #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
         pub struct $EncodedTy<S: LabelState>(Labels<$len, S>);
 
@@ -320,6 +326,7 @@ macro_rules! define_encoded_variant {
 
             /// Returns the active encoding of the plaintext value
             pub(crate) fn select(&self, value: $PlaintextTy) -> $EncodedTy<state::Active> {
+                let value = value as $BitsTy;
                 let mut bits = value.iter_lsb0();
                 let delta = self.0.delta();
                 $EncodedTy::<state::Active>::new(self.0.labels.map(|label| {
@@ -459,6 +466,11 @@ define_encoded_variant!(U16, u16, 16);
 define_encoded_variant!(U32, u32, 32);
 define_encoded_variant!(U64, u64, 64);
 define_encoded_variant!(U128, u128, 128);
+define_encoded_variant!(I8, i8, u8, 8);
+define_encoded_variant!(I16, i16, u16, 16);
+define_encoded_variant!(I32, i32, u32, 32);
+define_encoded_variant!(I64, i64, u64, 64);
+define_encoded_variant!(I128, i128, u128, 128);
 
 macro_rules! define_decoding {
     ($( ($EncodedTy:ident, $DecodingTy:ident) ),*) => {
@@ -503,7 +515,12 @@ define_decoding!(
     (U16, U16Decoding),
     (U32, U32Decoding),
     (U64, U64Decoding),
-    (U128, U128Decoding)
+    (U128, U128Decoding),
+    (I8, I8Decoding),
+    (I16, I16Decoding),
+    (I32, I32Decoding),
+    (I64, I64Decoding),
+    (I128, I128Decoding)
 );
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -545,12 +562,15 @@ impl Bit<state::Active> {
 
 macro_rules! define_decoding_info_variant {
     ($name:ident, $value:ident, $ty:ty) => {
+        define_decoding_info_variant!($name, $value, $ty, $ty);
+    };
+    ($name:ident, $value:ident, $ty:ty, $bits:ty) => {
         #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-        pub struct $name($ty);
+        pub struct $name($bits);
 
         impl $value<state::Full> {
             pub(crate) fn decoding(&self) -> $name {
-                $name(<$ty>::from_lsb0_iter(
+                $name(<$bits>::from_lsb0_iter(
                     self.0.iter().map(|label| label.pointer_bit()),
                 ))
             }
@@ -578,6 +598,7 @@ macro_rules! define_decoding_info_variant {
                 value: $ty,
                 delta: Delta,
             ) -> $value<state::Full> {
+                let value = value as $bits;
                 let mut value = value.iter_lsb0();
                 $value::<state::Full>::new(
                     delta,
@@ -593,13 +614,12 @@ macro_rules! define_decoding_info_variant {
 
             /// Decodes this value using the decoding information.
             pub(crate) fn decode(&self, decoding: &$name) -> $ty {
-                <$ty>::from_lsb0_iter(
+                <$bits>::from_lsb0_iter(
                     self.0
                         .iter()
                         .zip(decoding.0.iter_lsb0())
                         .map(|(label, dec)| label.pointer_bit() ^ dec),
-                )
-                .into()
+                ) as $ty
             }
         }
     };
@@ -610,6 +630,11 @@ define_decoding_info_variant!(U16Decoding, U16, u16);
 define_decoding_info_variant!(U32Decoding, U32, u32);
 define_decoding_info_variant!(U64Decoding, U64, u64);
 define_decoding_info_variant!(U128Decoding, U128, u128);
+define_decoding_info_variant!(I8Decoding, I8, i8, u8);
+define_decoding_info_variant!(I16Decoding, I16, i16, u16);
+define_decoding_info_variant!(I32Decoding, I32, i32, u32);
+define_decoding_info_variant!(I64Decoding, I64, i64, u64);
+define_decoding_info_variant!(I128Decoding, I128, i128, u128);
 
 #[derive(Serialize)]
 struct LabelCommit(Label);
@@ -692,6 +717,57 @@ macro_rules! define_encoding_commitment {
                 }
             }
         }
+
+        #[cfg(feature = "proto")]
+        impl mpz_core::serialize::ProtoMessage for EncodingCommitment {
+            type Proto = crate::proto::schema::EncodingCommitment;
+
+            fn to_proto(&self) -> Self::Proto {
+                use crate::proto::schema::{encoding_commitment::Value, EncodingCommitmentArray};
+
+                let value = match self {
+                    $(
+                        EncodingCommitment::$EncodedTy(c) => Value::$EncodedTy(c.to_bytes()),
+                    )*
+                    EncodingCommitment::Array(v) => Value::Array(EncodingCommitmentArray {
+                        elements: v.iter().map(|c| c.to_proto()).collect(),
+                    }),
+                };
+
+                Self::Proto { value: Some(value) }
+            }
+
+            fn from_proto(proto: Self::Proto) -> Result<Self, mpz_core::serialize::ProtoError> {
+                use crate::proto::schema::encoding_commitment::Value;
+                use mpz_core::serialize::ProtoError;
+
+                Ok(match proto.value.ok_or(ProtoError::MissingField("value"))? {
+                    $(
+                        Value::$EncodedTy(bytes) => {
+                            EncodingCommitment::$EncodedTy(Box::new($CommitmentTy::from_bytes(&bytes)?))
+                        }
+                    )*
+                    Value::Array(array) => {
+                        let elements = array
+                            .elements
+                            .into_iter()
+                            .map(Self::from_proto)
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        if elements.is_empty()
+                            || elements.iter().any(|c| c.value_type() != elements[0].value_type())
+                        {
+                            return Err(ProtoError::invalid_field(
+                                "array",
+                                "array must be non-empty and homogeneous",
+                            ));
+                        }
+
+                        EncodingCommitment::Array(elements)
+                    }
+                })
+            }
+        }
     };
 }
 
@@ -701,7 +777,12 @@ define_encoding_commitment!(
     (U16, U16Commitment),
     (U32, U32Commitment),
     (U64, U64Commitment),
-    (U128, U128Commitment)
+    (U128, U128Commitment),
+    (I8, I8Commitment),
+    (I16, I16Commitment),
+    (I32, I32Commitment),
+    (I64, I64Commitment),
+    (I128, I128Commitment)
 );
 
 macro_rules! define_encoding_commitment_variant {
@@ -717,11 +798,8 @@ macro_rules! define_encoding_commitment_variant {
 
         impl $name {
             pub(crate) fn new(value: &$value_ident<state::Full>) -> Self {
-                // randomly shuffle the two labels inside each pair in order to prevent
+                // order the two labels inside each pair by their commitment in order to prevent
                 // the evaluator from decoding their active labels using this commitment
-                let mut flip = [false; $len];
-                thread_rng().fill::<[bool]>(&mut flip);
-
                 let delta = value.0.delta();
 
                 let commitments = std::array::from_fn(|i| {
@@ -731,7 +809,7 @@ macro_rules! define_encoding_commitment_variant {
                     let low = Self::compute_commitment(low);
                     let high = Self::compute_commitment(high);
 
-                    if flip[i] {
+                    if low.to_bytes() < high.to_bytes() {
                         [low, high]
                     } else {
                         [high, low]
@@ -758,6 +836,28 @@ macro_rules! define_encoding_commitment_variant {
                 }
             }
 
+            #[cfg(feature = "proto")]
+            pub(crate) fn to_bytes(&self) -> Vec<u8> {
+                mpz_core::serialize::blocks_to_bytes(self.0.iter().flatten().copied())
+            }
+
+            #[cfg(feature = "proto")]
+            pub(crate) fn from_bytes(
+                bytes: &[u8],
+            ) -> Result<Self, mpz_core::serialize::ProtoError> {
+                let blocks = mpz_core::serialize::blocks_from_bytes("commitment", bytes)?;
+                if blocks.len() != 2 * $len {
+                    return Err(mpz_core::serialize::ProtoError::invalid_field(
+                        "commitment",
+                        format!("expected {} blocks, got {}", 2 * $len, blocks.len()),
+                    ));
+                }
+
+                Ok(Self(std::array::from_fn(|i| {
+                    [blocks[2 * i], blocks[2 * i + 1]]
+                })))
+            }
+
             // We use a truncated Blake3 hash to commit to the labels
             fn compute_commitment(label: Label) -> Block {
                 let commitment: [u8; 16] = LabelCommit(label).domain_separated_hash().as_bytes()
@@ -776,6 +876,11 @@ define_encoding_commitment_variant!(U16Commitment, U16, 16);
 define_encoding_commitment_variant!(U32Commitment, U32, 32);
 define_encoding_commitment_variant!(U64Commitment, U64, 64);
 define_encoding_commitment_variant!(U128Commitment, U128, 128);
+define_encoding_commitment_variant!(I8Commitment, I8, 8);
+define_encoding_commitment_variant!(I16Commitment, I16, 16);
+define_encoding_commitment_variant!(I32Commitment, I32, 32);
+define_encoding_commitment_variant!(I64Commitment, I64, 64);
+define_encoding_commitment_variant!(I128Commitment, I128, 128);
 
 #[cfg(test)]
 mod tests {
@@ -785,7 +890,7 @@ mod tests {
 
     use rand::{
         distributions::{Distribution, Standard},
-        SeedableRng,
+        Rng, SeedableRng,
     };
     use rand_chacha::ChaCha12Rng;
     use rstest::*;
@@ -804,12 +909,18 @@ mod tests {
     #[case::u64(PhantomData::<u64>)]
     #[case::u64(PhantomData::<u64>)]
     #[case::u128(PhantomData::<u128>)]
+    #[case::i8(PhantomData::<i8>)]
+    #[case::i16(PhantomData::<i16>)]
+    #[case::i32(PhantomData::<i32>)]
+    #[case::i64(PhantomData::<i64>)]
+    #[case::i128(PhantomData::<i128>)]
     #[case::bit_array(PhantomData::<[bool; 16]>)]
     #[case::u8_array(PhantomData::<[u8; 16]>)]
     #[case::u16_array(PhantomData::<[u16; 16]>)]
     #[case::u32_array(PhantomData::<[u32; 16]>)]
     #[case::u64_array(PhantomData::<[u64; 16]>)]
     #[case::u128_array(PhantomData::<[u128; 16]>)]
+    #[case::i32_array(PhantomData::<[i32; 16]>)]
     fn test_encoding<T>(encoder: ChaChaEncoder, #[case] _pd: PhantomData<T>)
     where
         Standard: Distribution<T>,
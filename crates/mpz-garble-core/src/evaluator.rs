@@ -5,14 +5,15 @@ use blake3::Hasher;
 use crate::{
     circuit::EncryptedGate,
     encoding::{state, EncodedValue, Label},
-    EncryptedGateBatch, DEFAULT_BATCH_SIZE,
+    EncryptedGateBatch, EncryptedGateBatchView, DEFAULT_BATCH_SIZE,
 };
 use mpz_circuits::{
     types::{BinaryRepr, TypeError},
-    Circuit, CircuitError, Gate,
+    Circuit, CircuitError, Feed, Gate, Node,
 };
 use mpz_core::{
     aes::{FixedKeyAes, FIXED_KEY_AES},
+    crhash::tweak,
     hash::Hash,
     Block,
 };
@@ -27,6 +28,10 @@ pub enum EvaluatorError {
     CircuitError(#[from] CircuitError),
     #[error("evaluator not finished")]
     NotFinished,
+    #[error("wire {0} is not in the circuit")]
+    InvalidWire(usize),
+    #[error("invalid number of traced wires: expected {0}, got {1}")]
+    InvalidTraceCount(usize, usize),
 }
 
 /// Evaluates half-gate garbled AND gate
@@ -38,14 +43,14 @@ pub(crate) fn and_gate(
     encrypted_gate: &EncryptedGate,
     gid: usize,
 ) -> Label {
-    let x = x.to_inner();
-    let y = y.to_inner();
+    let x = x.into_block();
+    let y = y.into_block();
 
     let s_a = x.lsb();
     let s_b = y.lsb();
 
-    let j = Block::new((gid as u128).to_be_bytes());
-    let k = Block::new(((gid + 1) as u128).to_be_bytes());
+    let j = tweak(gid as u128);
+    let k = tweak((gid + 1) as u128);
 
     let mut h = [x, y];
     cipher.tccr_many(&[j, k], &mut h);
@@ -81,6 +86,7 @@ impl Evaluator {
     ///
     /// * `circ` - The circuit to evaluate.
     /// * `inputs` - The input values to the circuit.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(and_count = circ.and_count()), skip_all, err))]
     pub fn evaluate<'a>(
         &'a mut self,
         circ: &'a Circuit,
@@ -132,6 +138,30 @@ impl Evaluator {
     ) -> Result<EncryptedGateBatchConsumer<'_, std::slice::Iter<'_, Gate>>, EvaluatorError> {
         self.evaluate(circ, inputs).map(EncryptedGateBatchConsumer)
     }
+
+    /// Returns the values of the provided wires of the last evaluated circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `wires` - The wires to trace.
+    /// * `bits` - The pointer bits of the 0-bit labels of the wires, see
+    ///   [`Generator::trace`](crate::Generator::trace).
+    pub fn trace(&self, wires: &[Node<Feed>], bits: &[bool]) -> Result<Vec<bool>, EvaluatorError> {
+        if wires.len() != bits.len() {
+            return Err(EvaluatorError::InvalidTraceCount(wires.len(), bits.len()));
+        }
+
+        wires
+            .iter()
+            .zip(bits)
+            .map(|(wire, bit)| {
+                self.buffer
+                    .get(wire.id())
+                    .map(|label| label.pointer_bit() ^ bit)
+                    .ok_or(EvaluatorError::InvalidWire(wire.id()))
+            })
+            .collect()
+    }
 }
 
 /// Consumer over the encrypted gates of a circuit.
@@ -194,6 +224,12 @@ where
     /// Evaluates the next encrypted gate in the circuit.
     #[inline]
     pub fn next(&mut self, encrypted_gate: EncryptedGate) {
+        self.next_ref(&encrypted_gate)
+    }
+
+    /// Evaluates the next encrypted gate in the circuit, without taking ownership of it.
+    #[inline]
+    pub fn next_ref(&mut self, encrypted_gate: &EncryptedGate) {
         while let Some(gate) = self.gates.next() {
             match gate {
                 Gate::Xor {
@@ -212,14 +248,14 @@ where
                 } => {
                     let x = self.labels[node_x.id()];
                     let y = self.labels[node_y.id()];
-                    let z = and_gate(self.cipher, &x, &y, &encrypted_gate, self.gid);
+                    let z = and_gate(self.cipher, &x, &y, encrypted_gate, self.gid);
                     self.labels[node_z.id()] = z;
 
                     self.gid += 2;
                     self.counter += 1;
 
                     if let Some(hasher) = &mut self.hasher {
-                        hasher.update(&encrypted_gate.to_bytes());
+                        hasher.update(bytemuck::bytes_of(encrypted_gate));
                     }
 
                     // If we have more AND gates to evaluate, return.
@@ -241,6 +277,7 @@ where
     }
 
     /// Returns the encoded outputs of the circuit.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(outputs = self.outputs.len()), skip_all, err))]
     pub fn finish(mut self) -> Result<EvaluatorOutput, EvaluatorError> {
         if self.wants_gates() {
             return Err(EvaluatorError::NotFinished);
@@ -295,8 +332,19 @@ where
     /// Evaluates the next batch of gates in the circuit.
     #[inline]
     pub fn next(&mut self, batch: EncryptedGateBatch<N>) {
-        for encrypted_gate in batch.into_array() {
-            self.0.next(encrypted_gate);
+        self.next_gates(batch.gates())
+    }
+
+    /// Evaluates the next batch of gates in the circuit, reading them in place from the receive
+    /// buffer.
+    #[inline]
+    pub fn next_view(&mut self, batch: &EncryptedGateBatchView<N>) {
+        self.next_gates(batch.gates())
+    }
+
+    fn next_gates(&mut self, gates: &[EncryptedGate]) {
+        for encrypted_gate in gates {
+            self.0.next_ref(encrypted_gate);
             if !self.0.wants_gates() {
                 // Skipping any remaining gates which may have been used to pad the last batch.
                 return;
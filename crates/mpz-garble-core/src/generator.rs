@@ -9,10 +9,11 @@ use crate::{
 };
 use mpz_circuits::{
     types::{BinaryRepr, TypeError},
-    Circuit, CircuitError, Gate,
+    Circuit, CircuitError, Feed, Gate, Node,
 };
 use mpz_core::{
     aes::{FixedKeyAes, FIXED_KEY_AES},
+    crhash::tweak,
     hash::Hash,
     Block,
 };
@@ -27,6 +28,8 @@ pub enum GeneratorError {
     CircuitError(#[from] CircuitError),
     #[error("generator not finished")]
     NotFinished,
+    #[error("wire {0} is not in the circuit")]
+    InvalidWire(usize),
 }
 
 /// Computes half-gate garbled AND gate
@@ -39,15 +42,15 @@ pub(crate) fn and_gate(
     gid: usize,
 ) -> (Label, EncryptedGate) {
     let delta = delta.into_inner();
-    let x_0 = x_0.to_inner();
+    let x_0 = x_0.into_block();
     let x_1 = x_0 ^ delta;
-    let y_0 = y_0.to_inner();
+    let y_0 = y_0.into_block();
     let y_1 = y_0 ^ delta;
 
     let p_a = x_0.lsb();
     let p_b = y_0.lsb();
-    let j = Block::new((gid as u128).to_be_bytes());
-    let k = Block::new(((gid + 1) as u128).to_be_bytes());
+    let j = tweak(gid as u128);
+    let k = tweak((gid + 1) as u128);
 
     let mut h = [x_0, y_0, x_1, y_1];
     cipher.tccr_many(&[j, k, j, k], &mut h);
@@ -83,6 +86,13 @@ pub struct Generator {
     buffer: Vec<Label>,
 }
 
+#[cfg(feature = "zeroize")]
+impl Drop for Generator {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.buffer);
+    }
+}
+
 impl Generator {
     /// Returns an iterator over the encrypted gates of a circuit.
     ///
@@ -91,6 +101,7 @@ impl Generator {
     /// * `circ` - The circuit to garble.
     /// * `delta` - The delta value to use for garbling.
     /// * `inputs` - The input values to the circuit.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(and_count = circ.and_count()), skip_all, err))]
     pub fn generate<'a>(
         &'a mut self,
         circ: &'a Circuit,
@@ -147,6 +158,28 @@ impl Generator {
         self.generate(circ, delta, inputs)
             .map(EncryptedGateBatchIter)
     }
+
+    /// Returns the pointer bits of the 0-bit labels of the provided wires of the last garbled
+    /// circuit.
+    ///
+    /// Together with the pointer bits of the active labels these reveal the values of the wires,
+    /// see [`Evaluator::trace`](crate::Evaluator::trace). This must only be sent to the evaluator
+    /// if the generator consents to revealing the wires.
+    ///
+    /// # Arguments
+    ///
+    /// * `wires` - The wires to trace.
+    pub fn trace(&self, wires: &[Node<Feed>]) -> Result<Vec<bool>, GeneratorError> {
+        wires
+            .iter()
+            .map(|wire| {
+                self.buffer
+                    .get(wire.id())
+                    .map(Label::pointer_bit)
+                    .ok_or(GeneratorError::InvalidWire(wire.id()))
+            })
+            .collect()
+    }
 }
 
 /// Iterator over encrypted gates of a garbled circuit.
@@ -216,6 +249,7 @@ where
     }
 
     /// Returns the encoded outputs of the circuit, and the hash of the encrypted gates if present.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(outputs = self.outputs.len()), skip_all, err))]
     pub fn finish(mut self) -> Result<GeneratorOutput, GeneratorError> {
         if self.has_gates() {
             return Err(GeneratorError::NotFinished);
@@ -0,0 +1,273 @@
+use std::sync::mpsc;
+
+use mpz_core::{aes::FIXED_KEY, Block};
+use wgpu::util::DeviceExt;
+
+use super::GpuError;
+
+/// The maximum number of gates processed in a single dispatch.
+///
+/// This keeps the buffers within the default binding size limit of 128MiB.
+const MAX_DISPATCH_GATES: usize = 1 << 20;
+/// The number of invocations in a workgroup, see the shader.
+const WORKGROUP_SIZE: usize = 64;
+
+#[rustfmt::skip]
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// Expands an AES-128 key into the 11 round keys.
+fn expand_key(key: [u8; 16]) -> [[u8; 16]; 11] {
+    const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+    let mut words = [[0u8; 4]; 44];
+    for (i, word) in key.chunks_exact(4).enumerate() {
+        words[i].copy_from_slice(word);
+    }
+
+    for i in 4..44 {
+        let mut temp = words[i - 1];
+        if i % 4 == 0 {
+            temp.rotate_left(1);
+            temp = temp.map(|byte| SBOX[byte as usize]);
+            temp[0] ^= RCON[i / 4 - 1];
+        }
+
+        words[i] = std::array::from_fn(|j| words[i - 4][j] ^ temp[j]);
+    }
+
+    std::array::from_fn(|round| {
+        let mut round_key = [0u8; 16];
+        for (j, word) in words[4 * round..4 * round + 4].iter().enumerate() {
+            round_key[4 * j..4 * j + 4].copy_from_slice(word);
+        }
+        round_key
+    })
+}
+
+/// Returns the parameters of the shader, see `Params` in the shader.
+fn params(delta: Block) -> Vec<u32> {
+    let words = |bytes: &[u8]| {
+        bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>()
+    };
+
+    let mut params = words(&delta.to_bytes());
+    for round_key in expand_key(FIXED_KEY) {
+        params.extend(words(&round_key));
+    }
+    params.extend(SBOX.iter().map(|byte| *byte as u32));
+    params
+}
+
+/// A GPU device with the garbling pipelines.
+pub(crate) struct Device {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    garble: wgpu::ComputePipeline,
+    evaluate: wgpu::ComputePipeline,
+}
+
+impl Device {
+    /// Requests a device from the first available high performance adapter.
+    pub(crate) fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .ok_or(GpuError::NoAdapter)?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("mpz-garble-core"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+            },
+            None,
+        ))
+        .map_err(|e| GpuError::Device(e.to_string()))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("garble"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("garble.wgsl").into()),
+        });
+
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point,
+            })
+        };
+
+        let garble = pipeline("garble");
+        let evaluate = pipeline("evaluate");
+
+        Ok(Self {
+            device,
+            queue,
+            garble,
+            evaluate,
+        })
+    }
+
+    /// Garbles AND gates, returning the 0-bit output label and the encrypted gate of each gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The global offset.
+    /// * `inputs` - The 0-bit input labels `x_0, y_0` of each gate.
+    /// * `gids` - The gate id of each gate.
+    pub(crate) fn garble(
+        &self,
+        delta: Block,
+        inputs: &[Block],
+        gids: &[u32],
+    ) -> Result<Vec<Block>, GpuError> {
+        self.dispatch(&self.garble, delta, inputs, 2, gids, 3)
+    }
+
+    /// Evaluates AND gates, returning the active output label of each gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The active input labels `x, y` and the encrypted gate `t_g, t_e` of each gate.
+    /// * `gids` - The gate id of each gate.
+    pub(crate) fn evaluate(&self, inputs: &[Block], gids: &[u32]) -> Result<Vec<Block>, GpuError> {
+        self.dispatch(&self.evaluate, Block::ZERO, inputs, 4, gids, 1)
+    }
+
+    fn dispatch(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        delta: Block,
+        inputs: &[Block],
+        inputs_per_gate: usize,
+        gids: &[u32],
+        outputs_per_gate: usize,
+    ) -> Result<Vec<Block>, GpuError> {
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::cast_slice(&params(delta)),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+        let mut outputs = Vec::with_capacity(gids.len() * outputs_per_gate);
+        for (inputs, gids) in inputs
+            .chunks(MAX_DISPATCH_GATES * inputs_per_gate)
+            .zip(gids.chunks(MAX_DISPATCH_GATES))
+        {
+            let storage = |label, contents: &[u8]| {
+                self.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some(label),
+                        contents,
+                        usage: wgpu::BufferUsages::STORAGE,
+                    })
+            };
+
+            let inputs = storage("inputs", bytemuck::cast_slice(inputs));
+            let gid_buffer = storage("gids", bytemuck::cast_slice(gids));
+
+            let size = (gids.len() * outputs_per_gate * Block::LEN) as u64;
+            let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("outputs"),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("staging"),
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[&params, &inputs, &gid_buffer, &output]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                        binding: binding as u32,
+                        resource: buffer.as_entire_binding(),
+                    })
+                    .collect::<Vec<_>>(),
+            });
+
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.dispatch_workgroups(gids.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+            }
+            encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, size);
+            self.queue.submit(Some(encoder.finish()));
+
+            let slice = staging.slice(..);
+            let (sender, receiver) = mpsc::channel();
+            slice.map_async(wgpu::MapMode::Read, move |result| {
+                _ = sender.send(result);
+            });
+            self.device.poll(wgpu::Maintain::Wait);
+            receiver
+                .recv()
+                .map_err(|e| GpuError::Map(e.to_string()))?
+                .map_err(|e| GpuError::Map(e.to_string()))?;
+
+            outputs.extend_from_slice(bytemuck::cast_slice::<u8, Block>(&slice.get_mapped_range()));
+            staging.unmap();
+        }
+
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_key() {
+        // FIPS-197, Appendix A.1.
+        let key = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+
+        assert_eq!(
+            expand_key(key)[10],
+            [
+                0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
+                0x0c, 0xa6
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// Half-gate garbling and evaluation of AND gates.
+//
+// A block is represented as 4 little-endian words, so that byte `i` of the block is byte `i % 4`
+// of word `i / 4`. This matches the column-major AES state, ie. word `c` is column `c`.
+
+struct Params {
+    delta: vec4<u32>,
+    round_keys: array<vec4<u32>, 11>,
+    sbox: array<u32, 256>,
+}
+
+@group(0) @binding(0) var<storage, read> params: Params;
+// Garbling: the 0-bit labels `x_0, y_0` of each gate.
+// Evaluation: the active labels `x, y` and the encrypted gate `t_g, t_e` of each gate.
+@group(0) @binding(1) var<storage, read> inputs: array<vec4<u32>>;
+@group(0) @binding(2) var<storage, read> gids: array<u32>;
+// Garbling: the 0-bit output label `z_0` and the encrypted gate `t_g, t_e` of each gate.
+// Evaluation: the active output label `z` of each gate.
+@group(0) @binding(3) var<storage, read_write> outputs: array<vec4<u32>>;
+
+fn xtime(x: u32) -> u32 {
+    return ((x << 1u) ^ (((x >> 7u) & 1u) * 0x1bu)) & 0xffu;
+}
+
+fn byte_at(w: u32, i: u32) -> u32 {
+    return (w >> (8u * i)) & 0xffu;
+}
+
+// SubBytes followed by ShiftRows.
+fn sub_shift(s: vec4<u32>) -> vec4<u32> {
+    var out: vec4<u32>;
+    for (var c = 0u; c < 4u; c++) {
+        out[c] = params.sbox[byte_at(s[c], 0u)]
+            | (params.sbox[byte_at(s[(c + 1u) % 4u], 1u)] << 8u)
+            | (params.sbox[byte_at(s[(c + 2u) % 4u], 2u)] << 16u)
+            | (params.sbox[byte_at(s[(c + 3u) % 4u], 3u)] << 24u);
+    }
+    return out;
+}
+
+fn mix_column(w: u32) -> u32 {
+    let a0 = byte_at(w, 0u);
+    let a1 = byte_at(w, 1u);
+    let a2 = byte_at(w, 2u);
+    let a3 = byte_at(w, 3u);
+
+    let b0 = xtime(a0) ^ xtime(a1) ^ a1 ^ a2 ^ a3;
+    let b1 = a0 ^ xtime(a1) ^ xtime(a2) ^ a2 ^ a3;
+    let b2 = a0 ^ a1 ^ xtime(a2) ^ xtime(a3) ^ a3;
+    let b3 = xtime(a0) ^ a0 ^ a1 ^ a2 ^ xtime(a3);
+
+    return b0 | (b1 << 8u) | (b2 << 16u) | (b3 << 24u);
+}
+
+// Fixed-key AES-128.
+fn aes(block: vec4<u32>) -> vec4<u32> {
+    var s = block ^ params.round_keys[0];
+    for (var round = 1u; round < 10u; round++) {
+        s = sub_shift(s);
+        s = vec4<u32>(mix_column(s.x), mix_column(s.y), mix_column(s.z), mix_column(s.w));
+        s ^= params.round_keys[round];
+    }
+    return sub_shift(s) ^ params.round_keys[10];
+}
+
+// `π(π(x) ⊕ i) ⊕ π(x)`, see `FixedKeyAes::tccr`.
+fn tccr(tweak: vec4<u32>, x: vec4<u32>) -> vec4<u32> {
+    let h = aes(x);
+    return h ^ aes(h ^ tweak);
+}
+
+// The big-endian encoding of the gate id, see `tweak` in `mpz-core`.
+fn tweak(gid: u32) -> vec4<u32> {
+    let swapped = (gid << 24u) | ((gid << 8u) & 0xff0000u) | ((gid >> 8u) & 0xff00u) | (gid >> 24u);
+    return vec4<u32>(0u, 0u, 0u, swapped);
+}
+
+fn select_mask(x: vec4<u32>) -> vec4<u32> {
+    return vec4<u32>(0u - (x.x & 1u));
+}
+
+@compute @workgroup_size(64)
+fn garble(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= arrayLength(&gids) {
+        return;
+    }
+
+    let delta = params.delta;
+    let x_0 = inputs[2u * i];
+    let y_0 = inputs[2u * i + 1u];
+    let x_1 = x_0 ^ delta;
+    let y_1 = y_0 ^ delta;
+
+    let j = tweak(gids[i]);
+    let k = tweak(gids[i] + 1u);
+
+    let hx_0 = tccr(j, x_0);
+    let hy_0 = tccr(k, y_0);
+    let hx_1 = tccr(j, x_1);
+    let hy_1 = tccr(k, y_1);
+
+    // Garbled row of generator half-gate
+    let t_g = hx_0 ^ hx_1 ^ (select_mask(y_0) & delta);
+    let w_g = hx_0 ^ (select_mask(x_0) & t_g);
+
+    // Garbled row of evaluator half-gate
+    let t_e = hy_0 ^ hy_1 ^ x_0;
+    let w_e = hy_0 ^ (select_mask(y_0) & (t_e ^ x_0));
+
+    outputs[3u * i] = w_g ^ w_e;
+    outputs[3u * i + 1u] = t_g;
+    outputs[3u * i + 2u] = t_e;
+}
+
+@compute @workgroup_size(64)
+fn evaluate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= arrayLength(&gids) {
+        return;
+    }
+
+    let x = inputs[4u * i];
+    let y = inputs[4u * i + 1u];
+    let t_g = inputs[4u * i + 2u];
+    let t_e = inputs[4u * i + 3u];
+
+    let hx = tccr(tweak(gids[i]), x);
+    let hy = tccr(tweak(gids[i] + 1u), y);
+
+    let w_g = hx ^ (t_g & select_mask(x));
+    let w_e = hy ^ (select_mask(y) & (t_e ^ x));
+
+    outputs[i] = w_g ^ w_e;
+}
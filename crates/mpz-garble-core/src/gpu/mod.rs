@@ -0,0 +1,678 @@
+//! Experimental GPU garbling backend.
+//!
+//! This module garbles and evaluates AND gates on the GPU using a `wgpu` compute shader. The
+//! circuit is scheduled in layers by AND depth, all AND gates of a layer being processed in a
+//! single dispatch, while the free gates are computed on the CPU.
+//!
+//! The encrypted gates are produced in the same order, and with the same gate ids, as the CPU
+//! [`Generator`](crate::Generator). The [`GpuGenerator`] and [`GpuEvaluator`] are thus
+//! interoperable with their CPU counterparts, and the batches can be sent through the existing
+//! message path.
+//!
+//! Dispatches are only efficient for wide circuits, ie. circuits with many AND gates per layer.
+
+mod device;
+
+use core::fmt;
+
+use blake3::Hasher;
+use mpz_circuits::{
+    types::{BinaryRepr, TypeError},
+    Circuit, CircuitError, Gate,
+};
+use mpz_core::{hash::Hash, Block};
+
+use crate::{
+    encoding::{state, Delta, EncodedValue, Label},
+    EncryptedGate, EncryptedGateBatch, EvaluatorError, EvaluatorOutput, GeneratorError,
+    GeneratorOutput, DEFAULT_BATCH_SIZE,
+};
+use device::Device;
+
+/// Errors that can occur when using the GPU backend.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum GpuError {
+    #[error("no GPU adapter available")]
+    NoAdapter,
+    #[error("failed to request GPU device: {0}")]
+    Device(String),
+    #[error("failed to read GPU buffer: {0}")]
+    Map(String),
+    #[error("circuit has too many AND gates for the GPU backend")]
+    CircuitTooLarge,
+    #[error(transparent)]
+    Generator(#[from] GeneratorError),
+    #[error(transparent)]
+    Evaluator(#[from] EvaluatorError),
+}
+
+/// A layer of the circuit schedule.
+#[derive(Debug, Default)]
+struct Step {
+    /// Indices of the free gates of the layer, in circuit order.
+    free: Vec<usize>,
+    /// Indices of the AND gates which depend on the layer, and their position among the AND gates.
+    and: Vec<(usize, usize)>,
+}
+
+impl Step {
+    /// Returns the number of AND gates which must be available to process this step.
+    fn required_gates(&self) -> usize {
+        self.and.last().map(|(_, pos)| pos + 1).unwrap_or_default()
+    }
+}
+
+/// Schedules a circuit in layers by AND depth.
+///
+/// Step `i` contains the free gates at depth `i`, followed by the AND gates at depth `i + 1`.
+fn schedule(circ: &Circuit) -> Result<Vec<Step>, GpuError> {
+    // The gate id of the last AND gate must fit in a `u32`.
+    if circ.and_count() >= (u32::MAX / 2) as usize {
+        return Err(GpuError::CircuitTooLarge);
+    }
+
+    fn step(steps: &mut Vec<Step>, i: usize) -> &mut Step {
+        if i >= steps.len() {
+            steps.resize_with(i + 1, Default::default);
+        }
+        &mut steps[i]
+    }
+
+    let mut depth = vec![0usize; circ.feed_count()];
+    let mut steps: Vec<Step> = Vec::new();
+
+    let mut pos = 0;
+    for (i, gate) in circ.gates().iter().enumerate() {
+        match gate {
+            Gate::Xor { x, y, z } => {
+                let d = depth[x.id()].max(depth[y.id()]);
+                depth[z.id()] = d;
+                step(&mut steps, d).free.push(i);
+            }
+            Gate::Inv { x, z } => {
+                let d = depth[x.id()];
+                depth[z.id()] = d;
+                step(&mut steps, d).free.push(i);
+            }
+            Gate::And { x, y, z } => {
+                let d = depth[x.id()].max(depth[y.id()]);
+                depth[z.id()] = d + 1;
+                step(&mut steps, d).and.push((i, pos));
+                pos += 1;
+            }
+        }
+    }
+
+    // Sort the AND gates of each step by position so the required gate count is the last one.
+    for step in steps.iter_mut() {
+        step.and.sort_unstable_by_key(|(_, pos)| *pos);
+    }
+
+    Ok(steps)
+}
+
+/// Assigns the input labels of a circuit.
+fn assign_inputs<S: state::LabelState>(
+    circ: &Circuit,
+    inputs: Vec<EncodedValue<S>>,
+    buffer: &mut Vec<Label>,
+) -> Result<(), TypeError> {
+    if circ.feed_count() > buffer.len() {
+        buffer.resize(circ.feed_count(), Default::default());
+    }
+
+    for (encoded, input) in inputs.into_iter().zip(circ.inputs()) {
+        if encoded.value_type() != input.value_type() {
+            return Err(TypeError::UnexpectedType {
+                expected: input.value_type(),
+                actual: encoded.value_type(),
+            });
+        }
+
+        for (label, node) in encoded.iter().zip(input.iter()) {
+            buffer[node.id()] = *label;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the gate id of the AND gate at the given position, see [`Generator`](crate::Generator).
+#[inline]
+fn gid(pos: usize) -> u32 {
+    (2 * pos + 1) as u32
+}
+
+/// GPU garbled circuit generator.
+pub struct GpuGenerator {
+    device: Device,
+    /// Buffer for the 0-bit labels.
+    buffer: Vec<Label>,
+}
+
+impl fmt::Debug for GpuGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GpuGenerator {{ .. }}")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for GpuGenerator {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.buffer);
+    }
+}
+
+impl GpuGenerator {
+    /// Creates a new generator using the first available GPU.
+    pub fn new() -> Result<Self, GpuError> {
+        Ok(Self {
+            device: Device::new()?,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns an iterator over batched encrypted gates of a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to garble.
+    /// * `delta` - The delta value to use for garbling.
+    /// * `inputs` - The input values to the circuit.
+    pub fn generate<'a, const N: usize>(
+        &'a mut self,
+        circ: &'a Circuit,
+        delta: Delta,
+        inputs: Vec<EncodedValue<state::Full>>,
+    ) -> Result<GpuGateBatchIter<'a, N>, GpuError> {
+        if inputs.len() != circ.inputs().len() {
+            return Err(GeneratorError::from(CircuitError::InvalidInputCount(
+                circ.inputs().len(),
+                inputs.len(),
+            )))?;
+        }
+
+        let steps = schedule(circ)?;
+        assign_inputs(circ, inputs, &mut self.buffer).map_err(GeneratorError::from)?;
+
+        Ok(GpuGateBatchIter {
+            device: &self.device,
+            delta,
+            gates: circ.gates(),
+            outputs: circ.outputs(),
+            labels: &mut self.buffer,
+            steps: steps.into_iter(),
+            encrypted_gates: vec![Default::default(); circ.and_count()],
+            garbled: vec![false; circ.and_count()],
+            ready: 0,
+            sent: 0,
+            hasher: None,
+        })
+    }
+}
+
+/// Iterator returned by [`GpuGenerator::generate`].
+pub struct GpuGateBatchIter<'a, const N: usize = DEFAULT_BATCH_SIZE> {
+    device: &'a Device,
+    /// Global offset.
+    delta: Delta,
+    /// Gates of the circuit.
+    gates: &'a [Gate],
+    /// Circuit outputs.
+    outputs: &'a [BinaryRepr],
+    /// Buffer for the 0-bit labels.
+    labels: &'a mut [Label],
+    /// Remaining steps of the schedule.
+    steps: std::vec::IntoIter<Step>,
+    /// Encrypted gates, in circuit order.
+    encrypted_gates: Vec<EncryptedGate>,
+    /// Whether each encrypted gate has been garbled.
+    garbled: Vec<bool>,
+    /// Number of encrypted gates which have been garbled in order.
+    ready: usize,
+    /// Number of encrypted gates which have been returned.
+    sent: usize,
+    /// Hasher to use to hash the encrypted gates.
+    hasher: Option<Hasher>,
+}
+
+impl<'a, const N: usize> fmt::Debug for GpuGateBatchIter<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GpuGateBatchIter {{ .. }}")
+    }
+}
+
+impl<'a, const N: usize> GpuGateBatchIter<'a, N> {
+    /// Enables hashing of the encrypted gates.
+    pub fn enable_hasher(&mut self) {
+        self.hasher = Some(Hasher::new());
+    }
+
+    /// Returns `true` if the generator has more encrypted gates to generate.
+    pub fn has_gates(&self) -> bool {
+        self.sent != self.encrypted_gates.len()
+    }
+
+    /// Returns the encoded outputs of the circuit, and the hash of the encrypted gates if present.
+    pub fn finish(mut self) -> Result<GeneratorOutput, GpuError> {
+        if self.has_gates() {
+            return Err(GeneratorError::NotFinished)?;
+        }
+
+        // Finish computing any "free" gates.
+        while let Some(step) = self.steps.next() {
+            self.run(step)?;
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| self.labels[node.id()]).collect();
+
+                EncodedValue::<state::Full>::from_labels(output.value_type(), self.delta, &labels)
+                    .expect("encoding should be correct")
+            })
+            .collect();
+
+        Ok(GeneratorOutput {
+            outputs,
+            hash: self.hasher.as_ref().map(|hasher| {
+                let hash: [u8; 32] = hasher.finalize().into();
+                Hash::from(hash)
+            }),
+        })
+    }
+
+    /// Computes the free gates of a step on the CPU, and garbles its AND gates on the GPU.
+    fn run(&mut self, step: Step) -> Result<(), GpuError> {
+        for &i in &step.free {
+            match self.gates[i] {
+                Gate::Xor { x, y, z } => {
+                    self.labels[z.id()] = self.labels[x.id()] ^ self.labels[y.id()];
+                }
+                Gate::Inv { x, z } => {
+                    self.labels[z.id()] = self.labels[x.id()] ^ self.delta;
+                }
+                Gate::And { .. } => unreachable!("AND gates are not free"),
+            }
+        }
+
+        if step.and.is_empty() {
+            return Ok(());
+        }
+
+        let mut inputs = Vec::with_capacity(2 * step.and.len());
+        let mut gids = Vec::with_capacity(step.and.len());
+        for &(i, pos) in &step.and {
+            let Gate::And { x, y, .. } = self.gates[i] else {
+                unreachable!("step contains only AND gates");
+            };
+
+            inputs.push(self.labels[x.id()].into_block());
+            inputs.push(self.labels[y.id()].into_block());
+            gids.push(gid(pos));
+        }
+
+        let outputs = self
+            .device
+            .garble(self.delta.into_inner(), &inputs, &gids)?;
+
+        for (&(i, pos), output) in step.and.iter().zip(outputs.chunks_exact(3)) {
+            let Gate::And { z, .. } = self.gates[i] else {
+                unreachable!("step contains only AND gates");
+            };
+
+            self.labels[z.id()] = Label::new(output[0]);
+            self.encrypted_gates[pos] = EncryptedGate::new([output[1], output[2]]);
+            self.garbled[pos] = true;
+        }
+
+        while self.ready < self.garbled.len() && self.garbled[self.ready] {
+            self.ready += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, const N: usize> Iterator for GpuGateBatchIter<'a, N> {
+    type Item = Result<EncryptedGateBatch<N>, GpuError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.has_gates() {
+            return None;
+        }
+
+        let end = (self.sent + N).min(self.encrypted_gates.len());
+        while self.ready < end {
+            let step = self
+                .steps
+                .next()
+                .expect("schedule should garble every AND gate");
+
+            if let Err(e) = self.run(step) {
+                return Some(Err(e));
+            }
+        }
+
+        let mut batch = [EncryptedGate::default(); N];
+        for (slot, encrypted_gate) in batch.iter_mut().zip(&self.encrypted_gates[self.sent..end]) {
+            if let Some(hasher) = &mut self.hasher {
+                hasher.update(&encrypted_gate.to_bytes());
+            }
+            *slot = *encrypted_gate;
+        }
+        self.sent = end;
+
+        Some(Ok(EncryptedGateBatch::new(batch)))
+    }
+}
+
+/// GPU garbled circuit evaluator.
+pub struct GpuEvaluator {
+    device: Device,
+    /// Buffer for the active labels.
+    buffer: Vec<Label>,
+}
+
+impl fmt::Debug for GpuEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GpuEvaluator {{ .. }}")
+    }
+}
+
+impl GpuEvaluator {
+    /// Creates a new evaluator using the first available GPU.
+    pub fn new() -> Result<Self, GpuError> {
+        Ok(Self {
+            device: Device::new()?,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns a consumer over batched encrypted gates of a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to evaluate.
+    /// * `inputs` - The input values to the circuit.
+    pub fn evaluate<'a, const N: usize>(
+        &'a mut self,
+        circ: &'a Circuit,
+        inputs: Vec<EncodedValue<state::Active>>,
+    ) -> Result<GpuGateBatchConsumer<'a, N>, GpuError> {
+        if inputs.len() != circ.inputs().len() {
+            return Err(EvaluatorError::from(CircuitError::InvalidInputCount(
+                circ.inputs().len(),
+                inputs.len(),
+            )))?;
+        }
+
+        let steps = schedule(circ)?;
+        assign_inputs(circ, inputs, &mut self.buffer).map_err(EvaluatorError::from)?;
+
+        Ok(GpuGateBatchConsumer {
+            device: &self.device,
+            gates: circ.gates(),
+            outputs: circ.outputs(),
+            labels: &mut self.buffer,
+            steps: steps.into_iter().peekable(),
+            encrypted_gates: Vec::with_capacity(circ.and_count()),
+            and_count: circ.and_count(),
+            hasher: None,
+        })
+    }
+}
+
+/// Consumer returned by [`GpuEvaluator::evaluate`].
+pub struct GpuGateBatchConsumer<'a, const N: usize = DEFAULT_BATCH_SIZE> {
+    device: &'a Device,
+    /// Gates of the circuit.
+    gates: &'a [Gate],
+    /// Circuit outputs.
+    outputs: &'a [BinaryRepr],
+    /// Buffer for the active labels.
+    labels: &'a mut [Label],
+    /// Remaining steps of the schedule.
+    steps: std::iter::Peekable<std::vec::IntoIter<Step>>,
+    /// Encrypted gates received so far, in circuit order.
+    encrypted_gates: Vec<EncryptedGate>,
+    /// Number of AND gates in the circuit.
+    and_count: usize,
+    /// Hasher to use to hash the encrypted gates.
+    hasher: Option<Hasher>,
+}
+
+impl<'a, const N: usize> fmt::Debug for GpuGateBatchConsumer<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GpuGateBatchConsumer {{ .. }}")
+    }
+}
+
+impl<'a, const N: usize> GpuGateBatchConsumer<'a, N> {
+    /// Enables hashing of the encrypted gates.
+    pub fn enable_hasher(&mut self) {
+        self.hasher = Some(Hasher::new());
+    }
+
+    /// Returns `true` if the evaluator wants more encrypted gates.
+    pub fn wants_gates(&self) -> bool {
+        self.encrypted_gates.len() != self.and_count
+    }
+
+    /// Evaluates the next batch of gates in the circuit.
+    ///
+    /// Gates are evaluated once every AND gate of their layer has been received.
+    pub fn next(&mut self, batch: EncryptedGateBatch<N>) -> Result<(), GpuError> {
+        // Skipping any remaining gates which may have been used to pad the last batch.
+        let count = N.min(self.and_count - self.encrypted_gates.len());
+        for encrypted_gate in batch.into_array().into_iter().take(count) {
+            if let Some(hasher) = &mut self.hasher {
+                hasher.update(&encrypted_gate.to_bytes());
+            }
+            self.encrypted_gates.push(encrypted_gate);
+        }
+
+        while let Some(step) = self
+            .steps
+            .next_if(|step| step.required_gates() <= self.encrypted_gates.len())
+        {
+            self.run(step)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the encoded outputs of the circuit, and the hash of the encrypted gates if present.
+    pub fn finish(mut self) -> Result<EvaluatorOutput, GpuError> {
+        if self.wants_gates() {
+            return Err(EvaluatorError::NotFinished)?;
+        }
+
+        // Finish computing any "free" gates.
+        while let Some(step) = self.steps.next() {
+            self.run(step)?;
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| self.labels[node.id()]).collect();
+
+                EncodedValue::<state::Active>::from_labels(output.value_type(), &labels)
+                    .expect("encoding should be correct")
+            })
+            .collect();
+
+        Ok(EvaluatorOutput {
+            outputs,
+            hash: self.hasher.as_ref().map(|hasher| {
+                let hash: [u8; 32] = hasher.finalize().into();
+                Hash::from(hash)
+            }),
+        })
+    }
+
+    /// Computes the free gates of a step on the CPU, and evaluates its AND gates on the GPU.
+    fn run(&mut self, step: Step) -> Result<(), GpuError> {
+        for &i in &step.free {
+            match self.gates[i] {
+                Gate::Xor { x, y, z } => {
+                    self.labels[z.id()] = self.labels[x.id()] ^ self.labels[y.id()];
+                }
+                Gate::Inv { x, z } => {
+                    self.labels[z.id()] = self.labels[x.id()];
+                }
+                Gate::And { .. } => unreachable!("AND gates are not free"),
+            }
+        }
+
+        if step.and.is_empty() {
+            return Ok(());
+        }
+
+        let mut inputs: Vec<Block> = Vec::with_capacity(4 * step.and.len());
+        let mut gids = Vec::with_capacity(step.and.len());
+        for &(i, pos) in &step.and {
+            let Gate::And { x, y, .. } = self.gates[i] else {
+                unreachable!("step contains only AND gates");
+            };
+
+            let encrypted_gate = &self.encrypted_gates[pos];
+            inputs.extend([
+                self.labels[x.id()].into_block(),
+                self.labels[y.id()].into_block(),
+                encrypted_gate[0],
+                encrypted_gate[1],
+            ]);
+            gids.push(gid(pos));
+        }
+
+        let outputs = self.device.evaluate(&inputs, &gids)?;
+
+        for (&(i, _), output) in step.and.iter().zip(outputs) {
+            let Gate::And { z, .. } = self.gates[i] else {
+                unreachable!("step contains only AND gates");
+            };
+
+            self.labels[z.id()] = Label::new(output);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChaChaEncoder, Encoder, Evaluator, Generator};
+    use mpz_circuits::circuits::AES128;
+
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let steps = schedule(&AES128).unwrap();
+
+        let mut and_gates: Vec<_> = steps
+            .iter()
+            .flat_map(|step| step.and.iter().map(|(_, pos)| *pos))
+            .collect();
+        and_gates.sort_unstable();
+
+        let free_count: usize = steps.iter().map(|step| step.free.len()).sum();
+
+        assert_eq!(and_gates, (0..AES128.and_count()).collect::<Vec<_>>());
+        assert_eq!(free_count + AES128.and_count(), AES128.gates().len());
+    }
+
+    #[test]
+    fn test_gpu_garble() {
+        let (mut gpu_gen, mut gpu_ev) = match (GpuGenerator::new(), GpuEvaluator::new()) {
+            (Ok(gen), Ok(ev)) => (gen, ev),
+            (Err(GpuError::NoAdapter), _) | (_, Err(GpuError::NoAdapter)) => return,
+            (Err(e), _) | (_, Err(e)) => panic!("{e}"),
+        };
+
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+        let encoded_key = encoder.encode::<[u8; 16]>(0);
+        let encoded_msg = encoder.encode::<[u8; 16]>(1);
+
+        let key = [69u8; 16];
+        let msg = [42u8; 16];
+
+        let active_key = encoded_key.select(key).unwrap();
+        let active_msg = encoded_msg.select(msg).unwrap();
+
+        let mut gen = Generator::default();
+        let mut gen_iter = gen
+            .generate_batched(
+                &AES128,
+                encoder.delta(),
+                vec![encoded_key.clone(), encoded_msg.clone()],
+            )
+            .unwrap();
+        gen_iter.enable_hasher();
+
+        let mut gpu_gen_iter = gpu_gen
+            .generate::<DEFAULT_BATCH_SIZE>(
+                &AES128,
+                encoder.delta(),
+                vec![encoded_key, encoded_msg],
+            )
+            .unwrap();
+        gpu_gen_iter.enable_hasher();
+
+        let mut ev = Evaluator::default();
+        let mut ev_consumer = ev
+            .evaluate_batched(&AES128, vec![active_key.clone(), active_msg.clone()])
+            .unwrap();
+
+        let mut gpu_ev_consumer = gpu_ev
+            .evaluate::<DEFAULT_BATCH_SIZE>(&AES128, vec![active_key, active_msg])
+            .unwrap();
+        gpu_ev_consumer.enable_hasher();
+
+        // The GPU backend garbles the same gates as the CPU, and each side accepts the other's.
+        for (batch, gpu_batch) in gen_iter.by_ref().zip(gpu_gen_iter.by_ref()) {
+            let gpu_batch = gpu_batch.unwrap();
+            assert_eq!(batch.0, gpu_batch.0);
+
+            ev_consumer.next(gpu_batch);
+            gpu_ev_consumer.next(batch).unwrap();
+        }
+
+        let gen_output = gen_iter.finish().unwrap();
+        let gpu_gen_output = gpu_gen_iter.finish().unwrap();
+        let ev_output = ev_consumer.finish().unwrap();
+        let gpu_ev_output = gpu_ev_consumer.finish().unwrap();
+
+        assert_eq!(gen_output.outputs, gpu_gen_output.outputs);
+        assert_eq!(gen_output.hash, gpu_gen_output.hash);
+        assert_eq!(gpu_gen_output.hash, gpu_ev_output.hash);
+
+        let expected: [u8; 16] = {
+            use aes::{
+                cipher::{BlockEncrypt, KeyInit},
+                Aes128,
+            };
+
+            let aes = Aes128::new_from_slice(&key).unwrap();
+            let mut ciphertext = msg.into();
+            aes.encrypt_block(&mut ciphertext);
+            ciphertext.into()
+        };
+
+        for output in [ev_output, gpu_ev_output] {
+            let ciphertext: [u8; 16] = output.outputs[0]
+                .decode(&gen_output.outputs[0].decoding())
+                .unwrap()
+                .try_into()
+                .unwrap();
+
+            assert_eq!(ciphertext, expected);
+        }
+    }
+}
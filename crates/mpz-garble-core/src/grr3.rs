@@ -0,0 +1,377 @@
+//! Classic garbling with point-and-permute and 3-row garbled row reduction (GRR3).
+//!
+//! This scheme predates half-gates: every AND gate is garbled as a table of 4 rows, ordered by the
+//! pointer bits of its input labels, and the first row is made implicit by deriving the output
+//! label from it. XOR gates are free, as in the half-gate scheme, so an AND gate costs 3 labels
+//! instead of 2.
+//!
+//! It is provided for interoperability with older garblers, and as a baseline when comparing
+//! garbling schemes. The regular [`Generator`](crate::Generator) and
+//! [`Evaluator`](crate::Evaluator) should be used otherwise.
+//!
+//! # Hash
+//!
+//! The row of an AND gate with input labels `a` and `b` is encrypted with the fixed-key AES hash
+//! of [BHKR13](https://eprint.iacr.org/2013/426), `H(a, b, i) = π(K) ⊕ K` where
+//! `K = 2a ⊕ 4b ⊕ i`. Doubling is in `GF(2^128)` modulo `x^128 + x^7 + x^2 + x + 1`, where a block
+//! is the little-endian encoding of a polynomial, and the tweak `i` is the big-endian encoding of
+//! the index of the AND gate. AND gates are indexed in the order in which they are garbled,
+//! continuing across circuits.
+//!
+//! # Table
+//!
+//! The rows of the table of an AND gate are ordered by the pointer bits `(i, j)` of its input
+//! labels. The output label of row `(0, 0)` is its hash, and the table consists of the rows
+//! `(0, 1)`, `(1, 0)` and `(1, 1)`, each the hash of its input labels XORed with its output
+//! label. A table is sent as its 3 rows, 16 bytes each, see [`encode_gates`].
+//!
+//! Constant wires use public labels as in [`emp`](crate::emp).
+//!
+//! # ⚠️ Warning ⚠️
+//!
+//! This scheme is only secure against semi-honest adversaries, and it does not support the
+//! commitments or hashing of the regular generator and evaluator.
+
+use mpz_circuits::{types::TypeError, Circuit, CircuitError, Gate};
+use mpz_core::{aes::FIXED_KEY_AES, crhash::tweak, Block};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    emp::assign_inputs,
+    encoding::{state, Delta, EncodedValue, Label},
+    ValueError,
+};
+
+/// Size of a garbled table in bytes.
+const TABLE_LEN: usize = 48;
+
+/// Size of a label in bytes.
+const LABEL_LEN: usize = 16;
+
+/// Errors that can occur in the GRR3 scheme.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Grr3Error {
+    #[error(transparent)]
+    TypeError(#[from] TypeError),
+    #[error(transparent)]
+    CircuitError(#[from] CircuitError),
+    #[error(transparent)]
+    ValueError(#[from] ValueError),
+    #[error("invalid message length: expected {expected}, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+}
+
+/// The garbled table of an AND gate, without its implicit first row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Grr3Gate([Block; 3]);
+
+impl Grr3Gate {
+    /// Creates a new garbled table from the rows `(0, 1)`, `(1, 0)` and `(1, 1)`.
+    pub fn new(rows: [Block; 3]) -> Self {
+        Self(rows)
+    }
+
+    /// Returns the rows of the table.
+    pub fn rows(&self) -> &[Block; 3] {
+        &self.0
+    }
+}
+
+/// Doubles a block in `GF(2^128)`.
+#[inline]
+fn double(block: Block) -> Block {
+    let x = u128::from_le_bytes(block.to_bytes());
+    let x = (x << 1) ^ ((x >> 127) * 0x87);
+    Block::new(x.to_le_bytes())
+}
+
+/// Hashes the input labels of a row, `π(K) ⊕ K` where `K = 2a ⊕ 4b ⊕ i`.
+#[inline]
+fn hash(a: Block, b: Block, gid: u128) -> Block {
+    FIXED_KEY_AES.cr(double(a) ^ double(double(b)) ^ tweak(gid))
+}
+
+/// Computes a GRR3 garbled AND gate.
+#[inline]
+fn and_gate(x_0: Label, y_0: Label, delta: Block, gid: u128) -> (Label, Grr3Gate) {
+    let x_0 = x_0.into_block();
+    let y_0 = y_0.into_block();
+
+    let p_x = x_0.lsb();
+    let p_y = y_0.lsb();
+
+    // The semantic values of the inputs of row `(i, j)` are `(i ⊕ p_x, j ⊕ p_y)`.
+    let keys: [Block; 4] = core::array::from_fn(|row| {
+        let (a, b) = ((row >> 1) ^ p_x, (row & 1) ^ p_y);
+        hash(
+            x_0 ^ (Block::SELECT_MASK[a] & delta),
+            y_0 ^ (Block::SELECT_MASK[b] & delta),
+            gid,
+        )
+    });
+
+    // The implicit row `(0, 0)` determines the output labels.
+    let z_0 = keys[0] ^ (Block::SELECT_MASK[p_x & p_y] & delta);
+
+    let rows = core::array::from_fn(|idx| {
+        let row = idx + 1;
+        let (a, b) = ((row >> 1) ^ p_x, (row & 1) ^ p_y);
+        keys[row] ^ z_0 ^ (Block::SELECT_MASK[a & b] & delta)
+    });
+
+    (Label::new(z_0), Grr3Gate(rows))
+}
+
+/// Evaluates a GRR3 garbled AND gate.
+#[inline]
+fn and_gate_eval(x: Label, y: Label, gate: &Grr3Gate, gid: u128) -> Label {
+    let x = x.into_block();
+    let y = y.into_block();
+
+    let row = (x.lsb() << 1) | y.lsb();
+    let key = hash(x, y, gid);
+
+    match row {
+        0 => Label::new(key),
+        row => Label::new(key ^ gate.0[row - 1]),
+    }
+}
+
+/// GRR3 garbled circuit generator.
+#[derive(Debug)]
+pub struct Grr3Generator {
+    delta: Delta,
+    gid: u128,
+}
+
+impl Grr3Generator {
+    /// Creates a new generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The global offset.
+    pub fn new(delta: Delta) -> Self {
+        Self { delta, gid: 0 }
+    }
+
+    /// Garbles a circuit.
+    ///
+    /// Consecutive calls continue the indices of the AND gates, the evaluator must evaluate the
+    /// circuits in the same order.
+    ///
+    /// Returns the garbled tables of the AND gates in circuit order, and the encoded outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to garble.
+    /// * `inputs` - The full encodings of the circuit inputs, using the generator's delta.
+    pub fn garble(
+        &mut self,
+        circ: &Circuit,
+        inputs: &[EncodedValue<state::Full>],
+    ) -> Result<(Vec<Grr3Gate>, Vec<EncodedValue<state::Full>>), Grr3Error> {
+        let delta = *self.delta;
+
+        let mut labels = vec![Label::default(); circ.feed_count()];
+        // Public labels of the constant wires.
+        labels[1] = Label::new(delta);
+        assign_inputs::<_, Grr3Error>(circ, inputs, &mut labels)?;
+
+        let mut gates = Vec::with_capacity(circ.and_count());
+        for gate in circ.gates() {
+            match gate {
+                Gate::Xor { x, y, z } => labels[z.id()] = labels[x.id()] ^ labels[y.id()],
+                Gate::And { x, y, z } => {
+                    let (z_0, gate) = and_gate(labels[x.id()], labels[y.id()], delta, self.gid);
+                    labels[z.id()] = z_0;
+                    gates.push(gate);
+                    self.gid += 1;
+                }
+                Gate::Inv { x, z } => labels[z.id()] = labels[x.id()] ^ Label::new(delta),
+            }
+        }
+
+        let outputs = circ
+            .outputs()
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| labels[node.id()]).collect();
+                EncodedValue::<state::Full>::from_labels(output.value_type(), self.delta, &labels)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((gates, outputs))
+    }
+}
+
+/// GRR3 garbled circuit evaluator.
+#[derive(Debug, Default)]
+pub struct Grr3Evaluator {
+    gid: u128,
+}
+
+impl Grr3Evaluator {
+    /// Creates a new evaluator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates a garbled circuit.
+    ///
+    /// Returns the active encodings of the outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to evaluate.
+    /// * `inputs` - The active encodings of the circuit inputs.
+    /// * `gates` - The garbled tables of the AND gates, in circuit order.
+    pub fn evaluate(
+        &mut self,
+        circ: &Circuit,
+        inputs: &[EncodedValue<state::Active>],
+        gates: &[Grr3Gate],
+    ) -> Result<Vec<EncodedValue<state::Active>>, Grr3Error> {
+        if gates.len() != circ.and_count() {
+            return Err(Grr3Error::InvalidLength {
+                expected: circ.and_count(),
+                actual: gates.len(),
+            });
+        }
+
+        let mut labels = vec![Label::default(); circ.feed_count()];
+        assign_inputs::<_, Grr3Error>(circ, inputs, &mut labels)?;
+
+        let mut gates = gates.iter();
+        for gate in circ.gates() {
+            match gate {
+                Gate::Xor { x, y, z } => labels[z.id()] = labels[x.id()] ^ labels[y.id()],
+                Gate::And { x, y, z } => {
+                    let gate = gates.next().expect("gate count was checked");
+                    labels[z.id()] = and_gate_eval(labels[x.id()], labels[y.id()], gate, self.gid);
+                    self.gid += 1;
+                }
+                Gate::Inv { x, z } => labels[z.id()] = labels[x.id()],
+            }
+        }
+
+        circ.outputs()
+            .iter()
+            .map(|output| {
+                let labels: Vec<Label> = output.iter().map(|node| labels[node.id()]).collect();
+                Ok(EncodedValue::<state::Active>::from_labels(
+                    output.value_type(),
+                    &labels,
+                )?)
+            })
+            .collect()
+    }
+}
+
+/// Encodes garbled tables, 48 bytes per table.
+pub fn encode_gates(gates: &[Grr3Gate]) -> Vec<u8> {
+    gates
+        .iter()
+        .flat_map(|gate| gate.0.iter().flat_map(|row| row.to_bytes()))
+        .collect()
+}
+
+/// Decodes garbled tables, 48 bytes per table.
+pub fn decode_gates(bytes: &[u8]) -> Result<Vec<Grr3Gate>, Grr3Error> {
+    if !bytes.len().is_multiple_of(TABLE_LEN) {
+        return Err(Grr3Error::InvalidLength {
+            expected: bytes.len() / TABLE_LEN * TABLE_LEN,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(bytes
+        .chunks_exact(TABLE_LEN)
+        .map(|table| {
+            Grr3Gate(core::array::from_fn(|row| {
+                Block::new(
+                    table[row * LABEL_LEN..(row + 1) * LABEL_LEN]
+                        .try_into()
+                        .unwrap(),
+                )
+            }))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChaChaEncoder, Encoder};
+
+    use mpz_circuits::{circuits::AES128, types::Value};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_grr3_and_gate() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let delta = Delta::random(&mut rng);
+        let x_0 = Label::new(rng.gen());
+        let y_0 = Label::new(rng.gen());
+        let delta_label = Label::new(*delta);
+
+        let (z_0, gate) = and_gate(x_0, y_0, *delta, 42);
+
+        for (a, b) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            let x = if a == 1 { x_0 ^ delta_label } else { x_0 };
+            let y = if b == 1 { y_0 ^ delta_label } else { y_0 };
+            let z = if a & b == 1 { z_0 ^ delta_label } else { z_0 };
+
+            assert_eq!(and_gate_eval(x, y, &gate, 42), z);
+        }
+    }
+
+    #[test]
+    fn test_grr3_garble() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let encoder = ChaChaEncoder::new(rng.gen());
+
+        let key = [69u8; 16];
+        let msg = [42u8; 16];
+        let expected = AES128.evaluate(&[key.into(), msg.into()]).unwrap();
+
+        let full_inputs: Vec<EncodedValue<state::Full>> = AES128
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(id, input)| encoder.encode_by_type(id as u64, &input.value_type()))
+            .collect();
+
+        let mut gen = Grr3Generator::new(encoder.delta());
+        let mut ev = Grr3Evaluator::new();
+
+        // Garble twice to check that the gate indices stay in sync across circuits.
+        for _ in 0..2 {
+            let (gates, full_outputs) = gen.garble(&AES128, &full_inputs).unwrap();
+
+            let bytes = encode_gates(&gates);
+            assert_eq!(bytes.len(), AES128.and_count() * TABLE_LEN);
+
+            let active_inputs = vec![
+                full_inputs[0].select(key).unwrap(),
+                full_inputs[1].select(msg).unwrap(),
+            ];
+
+            let active_outputs = ev
+                .evaluate(&AES128, &active_inputs, &decode_gates(&bytes).unwrap())
+                .unwrap();
+
+            let outputs: Vec<Value> = active_outputs
+                .iter()
+                .zip(&full_outputs)
+                .map(|(active, full)| {
+                    full.verify(active).unwrap();
+                    active.decode(&full.decoding()).unwrap()
+                })
+                .collect();
+
+            assert_eq!(outputs, expected);
+        }
+    }
+}
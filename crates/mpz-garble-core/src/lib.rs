@@ -47,15 +47,24 @@
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod arbitrary;
 pub(crate) mod circuit;
+pub mod emp;
 pub mod encoding;
 mod evaluator;
 mod generator;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod grr3;
+#[cfg(feature = "proto")]
+pub mod proto;
 
-pub use circuit::{EncryptedGate, EncryptedGateBatch, GarbledCircuit};
+pub use circuit::{EncryptedGate, EncryptedGateBatch, EncryptedGateBatchView, GarbledCircuit};
 pub use encoding::{
-    state as encoding_state, ChaChaEncoder, Decoding, Delta, Encode, EncodedValue, Encoder,
-    EncodingCommitment, EqualityCheck, Label, ValueError,
+    state as encoding_state, ChaChaEncoder, ConversionTable, Decoding, Delta, Encode, EncodedValue,
+    Encoder, EncodingCommitment, EqualityCheck, Label, OneHotEncoding, ValueError,
+    MAX_ONE_HOT_BITS,
 };
 pub use evaluator::{
     EncryptedGateBatchConsumer, EncryptedGateConsumer, Evaluator, EvaluatorError, EvaluatorOutput,
@@ -244,4 +253,57 @@ mod tests {
         assert_eq!(actual, a ^ b);
         assert_eq!(gen_hash, ev_hash);
     }
+
+    #[test]
+    fn test_trace() {
+        let encoder = ChaChaEncoder::new([0; 32]);
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        let c = a & b;
+        builder.add_output(c ^ a);
+        let circ = builder.build().unwrap();
+
+        let wires: Vec<_> = circ
+            .gates()
+            .iter()
+            .filter(|gate| matches!(gate, mpz_circuits::Gate::And { .. }))
+            .map(|gate| gate.z())
+            .collect();
+        assert_eq!(wires.len(), 8);
+
+        for (a, b) in [(u8::MAX, u8::MAX), (u8::MAX, 0)] {
+            let full_inputs: Vec<EncodedValue<encoding_state::Full>> = circ
+                .inputs()
+                .iter()
+                .map(|input| encoder.encode_by_type(0, &input.value_type()))
+                .collect();
+
+            let active_inputs: Vec<EncodedValue<encoding_state::Active>> = vec![
+                full_inputs[0].clone().select(a).unwrap(),
+                full_inputs[1].clone().select(b).unwrap(),
+            ];
+
+            let mut gen = Generator::default();
+            let mut ev = Evaluator::default();
+
+            let mut gen_iter = gen
+                .generate_batched(&circ, encoder.delta(), full_inputs)
+                .unwrap();
+            let mut ev_consumer = ev.evaluate_batched(&circ, active_inputs).unwrap();
+
+            for batch in gen_iter.by_ref() {
+                ev_consumer.next(batch);
+            }
+
+            _ = gen_iter.finish().unwrap();
+            _ = ev_consumer.finish().unwrap();
+
+            let bits = gen.trace(&wires).unwrap();
+            let values = ev.trace(&wires, &bits).unwrap();
+
+            assert!(values.iter().all(|value| *value == (a & b == u8::MAX)));
+        }
+    }
 }
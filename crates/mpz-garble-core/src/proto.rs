@@ -0,0 +1,127 @@
+//! Protobuf encoding of the garbled circuit messages.
+//!
+//! The schema is published in `proto/mpz/garble/v1/garble.proto`, enabling implementations in
+//! other languages and middleboxes to parse protocol traffic. Messages are converted to and from
+//! their protobuf representation with [`ProtoMessage`].
+
+use mpz_core::serialize::{blocks_from_bytes, blocks_to_bytes, ProtoError, ProtoMessage};
+
+use crate::{EncodingCommitment, EncryptedGate, EncryptedGateBatch, GarbledCircuit};
+
+/// Types generated from the protobuf schema.
+#[allow(missing_docs, unreachable_pub, clippy::all)]
+pub mod schema {
+    include!(concat!(env!("OUT_DIR"), "/mpz.garble.v1.rs"));
+}
+
+fn gates_to_bytes<'a>(gates: impl IntoIterator<Item = &'a EncryptedGate>) -> Vec<u8> {
+    blocks_to_bytes(gates.into_iter().flat_map(|gate| gate.0))
+}
+
+fn gates_from_bytes(bytes: &[u8]) -> Result<Vec<EncryptedGate>, ProtoError> {
+    let blocks = blocks_from_bytes("gates", bytes)?;
+    if blocks.len() % 2 != 0 {
+        return Err(ProtoError::invalid_field(
+            "gates",
+            "length is not a multiple of 32",
+        ));
+    }
+
+    Ok(blocks
+        .chunks_exact(2)
+        .map(|gate| EncryptedGate::new([gate[0], gate[1]]))
+        .collect())
+}
+
+impl<const N: usize> ProtoMessage for EncryptedGateBatch<N> {
+    type Proto = schema::EncryptedGateBatch;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::EncryptedGateBatch {
+            gates: gates_to_bytes(&self.0),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        let gates: [EncryptedGate; N] =
+            gates_from_bytes(&proto.gates)?
+                .try_into()
+                .map_err(|gates: Vec<_>| {
+                    ProtoError::invalid_field(
+                        "gates",
+                        format!("expected {N} gates, got {}", gates.len()),
+                    )
+                })?;
+
+        Ok(Self::new(gates))
+    }
+}
+
+impl ProtoMessage for GarbledCircuit {
+    type Proto = schema::GarbledCircuit;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::GarbledCircuit {
+            gates: gates_to_bytes(&self.gates),
+            commitments: self
+                .commitments
+                .as_ref()
+                .map(|commitments| schema::EncodingCommitments {
+                    commitments: commitments.iter().map(ProtoMessage::to_proto).collect(),
+                }),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            gates: gates_from_bytes(&proto.gates)?,
+            commitments: proto
+                .commitments
+                .map(|commitments| {
+                    commitments
+                        .commitments
+                        .into_iter()
+                        .map(EncodingCommitment::from_proto)
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::circuits::AES128;
+
+    use crate::{ChaChaEncoder, Encoder, Generator, GeneratorOutput};
+
+    use super::*;
+
+    #[test]
+    fn test_proto_roundtrip() {
+        let encoder = ChaChaEncoder::new([0u8; 32]);
+        let inputs = vec![encoder.encode::<[u8; 16]>(0), encoder.encode::<[u8; 16]>(1)];
+
+        let mut gen = Generator::default();
+        let mut iter = gen.generate(&AES128, encoder.delta(), inputs).unwrap();
+        let gates: Vec<_> = iter.by_ref().collect();
+        let GeneratorOutput { outputs, .. } = iter.finish().unwrap();
+
+        let circ = GarbledCircuit {
+            gates,
+            commitments: Some(outputs.iter().map(|output| output.commit()).collect()),
+        };
+
+        let decoded = GarbledCircuit::decode_proto(&circ.encode_proto()).unwrap();
+
+        assert_eq!(decoded.gates, circ.gates);
+        assert_eq!(decoded.commitments, circ.commitments);
+    }
+
+    #[test]
+    fn test_proto_invalid_batch() {
+        let proto = schema::EncryptedGateBatch { gates: vec![0; 32] };
+
+        assert!(EncryptedGateBatch::<2>::from_proto(proto).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Secure comparison of two private integers.
+//!
+//! [`compare`] solves Yao's millionaires' problem in a single call: each party provides one
+//! integer, and the parties learn how the integers compare without learning anything else about
+//! them. The result can be revealed to both parties, or to only one of them.
+//!
+//! ```
+//! use std::cmp::Ordering;
+//!
+//! use mpz_garble::{
+//!     compare::{compare, Operand, Reveal},
+//!     protocol::deap::mock::create_mock_deap_vm,
+//! };
+//!
+//! # futures::executor::block_on(async {
+//! let (mut alice, mut bob) = create_mock_deap_vm();
+//!
+//! let (alice_result, bob_result) = futures::join!(
+//!     compare(&mut alice, "wealth", Operand::Left(1_000_000u64), Reveal::Left),
+//!     compare(&mut bob, "wealth", Operand::Right(2_500_000u64), Reveal::Left),
+//! );
+//!
+//! // Only Alice learns that she is less wealthy than Bob.
+//! assert_eq!(alice_result.unwrap(), Some(Ordering::Less));
+//! assert_eq!(bob_result.unwrap(), None);
+//! # });
+//! ```
+
+use std::{cmp::Ordering, sync::Arc};
+
+use mpz_circuits::{
+    once_cell::sync::Lazy,
+    ops::Compare,
+    types::{StaticValueType, Value},
+    Circuit, CircuitBuilder,
+};
+
+use crate::{Decode, DecodePrivate, Execute, Memory, VmError};
+
+/// An integer type which can be compared.
+pub trait Comparable: StaticValueType + Into<Value> {
+    /// Returns a circuit with the signature `fn(a: Self, b: Self) -> (bool, bool)` which outputs
+    /// `a < b` and `a > b`.
+    ///
+    /// The circuit requires one AND gate per bit for each output.
+    fn comparison_circuit() -> Arc<Circuit>;
+}
+
+macro_rules! impl_comparable {
+    ($($ty:ty),*) => {
+        $(
+            impl Comparable for $ty {
+                fn comparison_circuit() -> Arc<Circuit> {
+                    static CIRCUIT: Lazy<Arc<Circuit>> = Lazy::new(|| {
+                        let builder = CircuitBuilder::new();
+
+                        let a = builder.add_input::<$ty>();
+                        let b = builder.add_input::<$ty>();
+
+                        builder.add_output(Compare::lt(a, b));
+                        builder.add_output(Compare::gt(a, b));
+
+                        Arc::new(builder.build().expect("circuit is valid"))
+                    });
+
+                    CIRCUIT.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_comparable!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// This party's operand of a comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand<T> {
+    /// This party provides the left operand, `a` in `a.cmp(b)`.
+    Left(T),
+    /// This party provides the right operand, `b` in `a.cmp(b)`.
+    Right(T),
+}
+
+/// The parties which learn the result of a comparison.
+///
+/// Both parties must provide the same value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reveal {
+    /// Both parties learn the result.
+    Both,
+    /// Only the party providing the left operand learns the result.
+    Left,
+    /// Only the party providing the right operand learns the result.
+    Right,
+}
+
+/// Compares this party's integer with the other party's integer.
+///
+/// Returns the ordering of the left operand relative to the right operand, or `None` if the result
+/// is only revealed to the other party.
+///
+/// # Arguments
+///
+/// * `thread` - The VM thread.
+/// * `id` - The id of the comparison, which must be unique within the thread.
+/// * `operand` - This party's operand.
+/// * `reveal` - The parties which learn the result.
+pub async fn compare<T, V>(
+    thread: &mut T,
+    id: &str,
+    operand: Operand<V>,
+    reveal: Reveal,
+) -> Result<Option<Ordering>, VmError>
+where
+    T: Memory + Execute + Decode + DecodePrivate + Send,
+    V: Comparable,
+{
+    let a_id = format!("{id}/a");
+    let b_id = format!("{id}/b");
+    let (a, b, is_left) = match operand {
+        Operand::Left(value) => {
+            let a = thread.new_private_input::<V>(&a_id)?;
+            thread.assign(&a, value)?;
+            (a, thread.new_blind_input::<V>(&b_id)?, true)
+        }
+        Operand::Right(value) => {
+            let b = thread.new_private_input::<V>(&b_id)?;
+            thread.assign(&b, value)?;
+            (thread.new_blind_input::<V>(&a_id)?, b, false)
+        }
+    };
+
+    let lt = thread.new_output::<bool>(&format!("{id}/lt"))?;
+    let gt = thread.new_output::<bool>(&format!("{id}/gt"))?;
+    let outputs = [lt, gt];
+
+    thread
+        .execute(V::comparison_circuit(), &[a, b], &outputs)
+        .await?;
+
+    let values = match (reveal, is_left) {
+        (Reveal::Both, _) => Some(thread.decode(&outputs).await?),
+        (Reveal::Left, true) | (Reveal::Right, false) => {
+            Some(thread.decode_private(&outputs).await?)
+        }
+        (Reveal::Left, false) | (Reveal::Right, true) => {
+            thread.decode_blind(&outputs).await?;
+            None
+        }
+    };
+
+    Ok(values.map(|values| match values.as_slice() {
+        [Value::Bit(true), _] => Ordering::Less,
+        [_, Value::Bit(true)] => Ordering::Greater,
+        _ => Ordering::Equal,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::protocol::deap::mock::create_mock_deap_vm;
+
+    #[test]
+    fn test_comparison_circuit() {
+        let circ = u32::comparison_circuit();
+
+        assert_eq!(circ.and_count(), 64);
+
+        for (a, b) in [(0u32, 0u32), (1, 2), (2, 1), (u32::MAX, 0), (7, 7)] {
+            let outputs = circ.evaluate(&[a.into(), b.into()]).unwrap();
+            assert_eq!(outputs, vec![Value::Bit(a < b), Value::Bit(a > b)]);
+        }
+
+        let circ = i32::comparison_circuit();
+        for (a, b) in [(-7i32, 3i32), (3, -7), (i32::MIN, i32::MAX), (-5, -5)] {
+            let outputs = circ.evaluate(&[a.into(), b.into()]).unwrap();
+            assert_eq!(outputs, vec![Value::Bit(a < b), Value::Bit(a > b)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare() {
+        let (mut leader, mut follower) = create_mock_deap_vm();
+
+        let cases = [
+            (3u64, 5u64, Reveal::Both),
+            (5, 3, Reveal::Left),
+            (4, 4, Reveal::Right),
+        ];
+
+        for (i, (a, b, reveal)) in cases.into_iter().enumerate() {
+            let id = format!("cmp/{i}");
+            let (leader_result, follower_result) = futures::join!(
+                compare(&mut leader, &id, Operand::Left(a), reveal),
+                compare(&mut follower, &id, Operand::Right(b), reveal)
+            );
+
+            let expected = Some(a.cmp(&b));
+            let (leader_expected, follower_expected) = match reveal {
+                Reveal::Both => (expected, expected),
+                Reveal::Left => (expected, None),
+                Reveal::Right => (None, expected),
+            };
+
+            assert_eq!(leader_result.unwrap(), leader_expected);
+            assert_eq!(follower_result.unwrap(), follower_expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_signed() {
+        let (mut leader, mut follower) = create_mock_deap_vm();
+
+        let (leader_result, follower_result) = futures::join!(
+            compare(&mut leader, "cmp", Operand::Right(-7i32), Reveal::Both),
+            compare(&mut follower, "cmp", Operand::Left(3i32), Reveal::Both)
+        );
+
+        assert_eq!(leader_result.unwrap(), Some(Ordering::Greater));
+        assert_eq!(follower_result.unwrap(), Some(Ordering::Greater));
+    }
+}
@@ -0,0 +1,423 @@
+//! Cost estimation of circuit execution.
+//!
+//! The estimates are intended to help with budgeting bandwidth and latency before running a
+//! protocol. They do not account for framing overhead of the transport or for the cost of
+//! setting up the OT extension.
+//!
+//! [`CostEstimate`] summarizes the cost of executing a circuit, while [`ProtocolCost`] predicts
+//! the bytes sent and received by each party for a given [`ProtocolConfig`], for capacity
+//! planning.
+
+use mpz_circuits::{Circuit, CircuitStats};
+
+use crate::config::Visibility;
+
+/// Size of an encrypted AND gate in bytes.
+const AND_GATE_SIZE: usize = 32;
+/// Size of a label commitment in bytes.
+const COMMITMENT_SIZE: usize = 32;
+/// Size of the KOS consistency check sent by the receiver in blocks.
+const KOS_CHECK_BLOCKS: usize = 3;
+/// Size of the seed of an encoder in bytes.
+const SEED_SIZE: usize = 32;
+// LPN parameters of the Ferret extension, ie. `LPN_PARAMETERS_REGULAR` of `mpz-ot-core`.
+const FERRET_LPN_N: usize = 10180608;
+const FERRET_LPN_K: usize = 124000;
+const FERRET_LPN_T: usize = 4971;
+
+/// The mode in which a circuit is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// A single semi-honest execution where one party garbles and the other evaluates.
+    SemiHonest,
+    /// Dual execution with asymmetric privacy, where the circuit is garbled by both parties.
+    Deap,
+    /// Zero-knowledge proof of the circuit output, where the prover evaluates.
+    Zk,
+}
+
+/// An estimate of the cost of executing a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// Statistics of the circuit.
+    pub stats: CircuitStats,
+    /// Total size of the garbled circuits sent in bytes.
+    pub garbled_size: usize,
+    /// Number of oblivious transfers required.
+    pub ot_count: usize,
+    /// Number of communication round trips.
+    pub rounds: usize,
+}
+
+impl CostEstimate {
+    /// Estimates the cost of executing a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit.
+    /// * `mode` - The execution mode.
+    /// * `inputs` - The visibility of each circuit input from the perspective of the garbler,
+    ///   ie `Blind` inputs are private to the evaluator. For [`ExecutionMode::Zk`] the garbler
+    ///   is the verifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of visibilities does not match the number of circuit inputs.
+    pub fn new(circ: &Circuit, mode: ExecutionMode, inputs: &[Visibility]) -> Self {
+        assert_eq!(
+            circ.inputs().len(),
+            inputs.len(),
+            "visibility must be provided for every input"
+        );
+
+        let stats = circ.stats();
+
+        let bits = |f: fn(&Visibility) -> bool| -> usize {
+            circ.inputs()
+                .iter()
+                .zip(inputs)
+                .filter(|(_, visibility)| f(visibility))
+                .map(|(input, _)| input.len())
+                .sum()
+        };
+
+        let evaluator_bits = bits(|v| matches!(v, Visibility::Blind));
+        let private_bits = bits(|v| !matches!(v, Visibility::Public));
+
+        let garbled_size = stats.and_count * AND_GATE_SIZE;
+        // Transferring input labels requires a round trip if any OTs are needed, garbling
+        // and decoding are pipelined into a single round trip.
+        let exec_rounds = usize::from(evaluator_bits > 0) + 1;
+
+        let (garbled_size, ot_count, rounds) = match mode {
+            ExecutionMode::SemiHonest => (garbled_size, evaluator_bits, exec_rounds),
+            // Both parties garble, so every private input is transferred via OT in one of the
+            // executions. The outputs are then checked for equality using a commit-reveal.
+            ExecutionMode::Deap => (
+                2 * garbled_size + 2 * stats.output_bits * COMMITMENT_SIZE,
+                private_bits,
+                usize::from(private_bits > 0) + 1 + 2,
+            ),
+            // The prover commits to the output and the verifier opens its garbling.
+            ExecutionMode::Zk => (
+                garbled_size + stats.output_bits * COMMITMENT_SIZE,
+                evaluator_bits,
+                exec_rounds + 1,
+            ),
+        };
+
+        Self {
+            stats,
+            garbled_size,
+            ot_count,
+            rounds,
+        }
+    }
+}
+
+/// The garbling scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GarblingScheme {
+    /// The half-gates scheme of Zahur, Rosulek and Evans, with 2 labels per AND gate.
+    HalfGates,
+    /// Classic point-and-permute garbling with 3-row reduction, with 3 labels per AND gate, see
+    /// [`mpz_garble_core::grr3`].
+    Grr3,
+}
+
+impl GarblingScheme {
+    /// Returns the size of an encrypted AND gate in bytes.
+    fn and_gate_size(&self, label_size: usize) -> usize {
+        match self {
+            GarblingScheme::HalfGates => 2 * label_size,
+            GarblingScheme::Grr3 => 3 * label_size,
+        }
+    }
+}
+
+/// The OT extension used to transfer the input labels of the evaluator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OTFlavor {
+    /// The KOS15 extension, which requires communication linear in the security parameter for
+    /// every OT.
+    Kos,
+    /// The Ferret silent extension, whose communication is amortized over an LPN extension.
+    Ferret,
+}
+
+impl OTFlavor {
+    /// Returns the bytes sent by the OT sender and the OT receiver to transfer `count` pairs of
+    /// messages.
+    fn transfer_cost(&self, count: usize, msg_size: usize, security: usize) -> (usize, usize) {
+        if count == 0 {
+            return (0, 0);
+        }
+
+        // Random OTs are derandomized with one bit per OT, then the sender sends the masked
+        // messages.
+        let payload = 2 * count * msg_size;
+        let derandomize = count.div_ceil(8);
+        let block_size = security / 8;
+
+        match self {
+            OTFlavor::Kos => {
+                // The receiver sends one column of `security` bits for every OT, padded for the
+                // consistency check and the matrix transpose.
+                let padded = (count + 2 * security + 63) & !63;
+                let extend = padded * block_size + KOS_CHECK_BLOCKS * block_size;
+
+                (payload, extend + derandomize)
+            }
+            OTFlavor::Ferret => {
+                // Every SPCOT instance expands a GGM tree, the sender sends a pair of masks per
+                // level and the receiver a bit per level.
+                let depth = (FERRET_LPN_N / FERRET_LPN_T)
+                    .next_power_of_two()
+                    .trailing_zeros() as usize;
+                let sender = FERRET_LPN_T * (2 * depth + 1) * block_size + COMMITMENT_SIZE;
+                let receiver = (FERRET_LPN_T * depth).div_ceil(8) + block_size;
+                // Some of the outputs are consumed by the next extension.
+                let usable = FERRET_LPN_N - FERRET_LPN_K - FERRET_LPN_T * depth - security;
+
+                let amortize = |bytes: usize| (count * bytes).div_ceil(usable);
+
+                (payload + amortize(sender), derandomize + amortize(receiver))
+            }
+        }
+    }
+}
+
+/// The configuration of a protocol, for cost estimation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    /// The execution mode.
+    pub mode: ExecutionMode,
+    /// The garbling scheme.
+    pub scheme: GarblingScheme,
+    /// The OT extension.
+    pub ot: OTFlavor,
+    /// The computational security parameter in bits, which is also the length of a label.
+    pub security: usize,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            mode: ExecutionMode::Deap,
+            scheme: GarblingScheme::HalfGates,
+            ot: OTFlavor::Kos,
+            security: 128,
+        }
+    }
+}
+
+/// The communication of one party.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PartyCost {
+    /// Bytes sent.
+    pub bytes_sent: usize,
+    /// Bytes received.
+    pub bytes_received: usize,
+}
+
+/// A prediction of the cost of executing a circuit with a given protocol configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolCost {
+    /// The communication of the garbler.
+    ///
+    /// In [`ExecutionMode::Deap`] this is the leader, and in [`ExecutionMode::Zk`] the verifier.
+    pub garbler: PartyCost,
+    /// The communication of the evaluator.
+    pub evaluator: PartyCost,
+    /// Number of communication round trips.
+    pub rounds: usize,
+    /// Number of oblivious transfers consumed.
+    pub ot_count: usize,
+}
+
+impl ProtocolCost {
+    /// Predicts the cost of executing a circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit.
+    /// * `config` - The protocol configuration.
+    /// * `inputs` - The visibility of each circuit input from the perspective of the garbler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of visibilities does not match the number of circuit inputs.
+    pub fn new(circ: &Circuit, config: &ProtocolConfig, inputs: &[Visibility]) -> Self {
+        let CostEstimate {
+            stats,
+            ot_count,
+            rounds,
+            ..
+        } = CostEstimate::new(circ, config.mode, inputs);
+
+        let bits = |f: fn(&Visibility) -> bool| -> usize {
+            circ.inputs()
+                .iter()
+                .zip(inputs)
+                .filter(|(_, visibility)| f(visibility))
+                .map(|(input, _)| input.len())
+                .sum()
+        };
+
+        let garbler_bits = bits(|v| matches!(v, Visibility::Private));
+        let evaluator_bits = bits(|v| matches!(v, Visibility::Blind));
+        let public_bits = bits(|v| matches!(v, Visibility::Public));
+
+        let label_size = config.security / 8;
+        let garbled_size = stats.and_count * config.scheme.and_gate_size(label_size);
+        let decoding_size = stats.output_bits.div_ceil(8);
+        let commitments_size = stats.output_bits * COMMITMENT_SIZE;
+
+        // Returns the bytes sent by the garbler and the evaluator in a single execution. The
+        // garbler sends the garbled circuit, the active labels of its own and the public inputs,
+        // and the decoding, and the evaluator sends back the output.
+        let execute = |garbler_bits: usize, evaluator_bits: usize, commit: bool| {
+            let (ot_sender, ot_receiver) =
+                config
+                    .ot
+                    .transfer_cost(evaluator_bits, label_size, config.security);
+
+            let garbler_sent = garbled_size
+                + (garbler_bits + public_bits) * label_size
+                + ot_sender
+                + decoding_size
+                + if commit { commitments_size } else { 0 };
+            let evaluator_sent = ot_receiver + decoding_size;
+
+            (garbler_sent, evaluator_sent)
+        };
+
+        let (garbler_sent, evaluator_sent) = match config.mode {
+            ExecutionMode::SemiHonest => execute(garbler_bits, evaluator_bits, false),
+            // The parties swap roles for the second execution, then each sends a commitment to
+            // its output encoding and opens it.
+            ExecutionMode::Deap => {
+                let (leader_sent, follower_sent) = execute(garbler_bits, evaluator_bits, true);
+                let (follower_garbled, leader_evaluated) =
+                    execute(evaluator_bits, garbler_bits, true);
+
+                (
+                    leader_sent + leader_evaluated + 2 * COMMITMENT_SIZE,
+                    follower_sent + follower_garbled + 2 * COMMITMENT_SIZE,
+                )
+            }
+            // The prover commits to the output and opens it after the verifier reveals its
+            // encoder seed.
+            ExecutionMode::Zk => {
+                let (verifier_sent, prover_sent) = execute(garbler_bits, evaluator_bits, true);
+
+                (verifier_sent + SEED_SIZE, prover_sent + 2 * COMMITMENT_SIZE)
+            }
+        };
+
+        Self {
+            garbler: PartyCost {
+                bytes_sent: garbler_sent,
+                bytes_received: evaluator_sent,
+            },
+            evaluator: PartyCost {
+                bytes_sent: evaluator_sent,
+                bytes_received: garbler_sent,
+            },
+            rounds,
+            ot_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::circuits::AES128;
+
+    use super::*;
+
+    #[test]
+    fn test_cost_estimate() {
+        let inputs = [Visibility::Private, Visibility::Blind];
+
+        let semi_honest = CostEstimate::new(&AES128, ExecutionMode::SemiHonest, &inputs);
+        let deap = CostEstimate::new(&AES128, ExecutionMode::Deap, &inputs);
+
+        assert_eq!(semi_honest.garbled_size, AES128.and_count() * AND_GATE_SIZE);
+        assert_eq!(semi_honest.ot_count, 128);
+        assert_eq!(deap.ot_count, 256);
+        assert!(deap.garbled_size > 2 * semi_honest.garbled_size);
+        assert!(deap.rounds > semi_honest.rounds);
+
+        let public = CostEstimate::new(
+            &AES128,
+            ExecutionMode::SemiHonest,
+            &[Visibility::Public, Visibility::Public],
+        );
+        assert_eq!(public.ot_count, 0);
+        assert_eq!(public.rounds, 1);
+    }
+
+    #[test]
+    fn test_protocol_cost() {
+        let inputs = [Visibility::Private, Visibility::Blind];
+        let config = ProtocolConfig {
+            mode: ExecutionMode::SemiHonest,
+            ..Default::default()
+        };
+
+        let kos = ProtocolCost::new(&AES128, &config, &inputs);
+        assert_eq!(kos.ot_count, 128);
+        assert_eq!(kos.garbler.bytes_sent, kos.evaluator.bytes_received);
+        assert_eq!(kos.evaluator.bytes_sent, kos.garbler.bytes_received);
+        assert!(kos.garbler.bytes_sent > AES128.and_count() * AND_GATE_SIZE);
+        // The evaluator sends a 128-bit column for every padded OT.
+        assert!(kos.evaluator.bytes_sent > 384 * 16);
+
+        let ferret = ProtocolCost::new(
+            &AES128,
+            &ProtocolConfig {
+                ot: OTFlavor::Ferret,
+                ..config
+            },
+            &inputs,
+        );
+        assert!(ferret.evaluator.bytes_sent < kos.evaluator.bytes_sent);
+
+        let high_security = ProtocolCost::new(
+            &AES128,
+            &ProtocolConfig {
+                security: 256,
+                ..config
+            },
+            &inputs,
+        );
+        assert!(high_security.garbler.bytes_sent > 2 * AES128.and_count() * AND_GATE_SIZE);
+
+        let grr3 = ProtocolCost::new(
+            &AES128,
+            &ProtocolConfig {
+                scheme: GarblingScheme::Grr3,
+                ..config
+            },
+            &inputs,
+        );
+        // Every AND gate costs one more label.
+        assert_eq!(
+            grr3.garbler.bytes_sent - kos.garbler.bytes_sent,
+            AES128.and_count() * 16
+        );
+
+        let deap = ProtocolCost::new(&AES128, &ProtocolConfig::default(), &inputs);
+        // Both parties garble.
+        assert!(deap.garbler.bytes_sent > kos.garbler.bytes_sent);
+        assert!(deap.evaluator.bytes_sent > kos.garbler.bytes_sent);
+        assert!(deap.rounds > kos.rounds);
+
+        // Without private inputs of the evaluator no OTs are needed.
+        let public =
+            ProtocolCost::new(&AES128, &config, &[Visibility::Public, Visibility::Private]);
+        assert_eq!(public.ot_count, 0);
+        assert_eq!(public.evaluator.bytes_sent, 16);
+    }
+}
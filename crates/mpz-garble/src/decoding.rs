@@ -0,0 +1,175 @@
+//! Commitments to the decodings of output values.
+//!
+//! If configured, the generator commits to the decoding of every output value when it garbles a
+//! circuit, and opens the commitment when the value is decoded. This binds the decodings to the
+//! garbled circuit, so a malicious generator can not flip the decoding of an output after the
+//! circuit was garbled without the evaluator detecting it.
+//!
+//! Which parties may learn an output value is set with a [`DecodePolicy`]. The generator refuses
+//! to send the decoding of a value the evaluator is not authorized to learn, and the evaluator
+//! refuses to decode such values and can keep a record of the values it decoded.
+//!
+//! The policy is not exchanged, so both parties must be configured with the same policy. If they
+//! disagree, one party returns an error while the other waits for a message from its peer.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use mpz_garble_core::Decoding;
+
+use crate::value::{ValueId, ValueRef};
+
+/// The parties which are authorized to learn a decoded value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeAuth {
+    /// Only the generator, which knows the decoding of every value.
+    Generator,
+    /// Both parties.
+    Both,
+}
+
+impl DecodeAuth {
+    /// Returns `true` if the evaluator is authorized to learn the value.
+    pub fn allows_evaluator(&self) -> bool {
+        matches!(self, DecodeAuth::Both)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PolicyState {
+    auths: HashMap<ValueId, DecodeAuth>,
+    strict: bool,
+}
+
+/// A policy of which parties are authorized to learn each value.
+///
+/// By default values which are not tagged may be decoded by both parties, whereas a strict policy
+/// only authorizes the generator to learn them. Clones share the same policy, so that the
+/// generator and evaluator of a party can enforce the same policy.
+///
+/// The policy is not exchanged, so the generator and evaluator of the two parties must be
+/// configured with the same policy.
+#[derive(Clone, Default)]
+pub struct DecodePolicy(Arc<Mutex<PolicyState>>);
+
+impl fmt::Debug for DecodePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.0.lock().unwrap();
+        f.debug_struct("DecodePolicy")
+            .field("tagged", &state.auths.len())
+            .field("strict", &state.strict)
+            .finish()
+    }
+}
+
+impl DecodePolicy {
+    /// Creates a new policy which authorizes both parties to learn values which are not tagged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new policy which only authorizes the generator to learn values which are not
+    /// tagged.
+    pub fn strict() -> Self {
+        Self(Arc::new(Mutex::new(PolicyState {
+            auths: HashMap::new(),
+            strict: true,
+        })))
+    }
+
+    /// Tags a value with the parties which are authorized to learn it.
+    ///
+    /// The elements of an array are tagged individually.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value.
+    /// * `auth` - The authorized parties.
+    pub fn tag(&self, value: &ValueRef, auth: DecodeAuth) {
+        let mut state = self.0.lock().unwrap();
+        for id in value.iter() {
+            state.auths.insert(id.clone(), auth);
+        }
+    }
+
+    /// Returns the parties which are authorized to learn a value.
+    pub fn get(&self, id: &ValueId) -> DecodeAuth {
+        let state = self.0.lock().unwrap();
+        match state.auths.get(id) {
+            Some(auth) => *auth,
+            None if state.strict => DecodeAuth::Generator,
+            None => DecodeAuth::Both,
+        }
+    }
+
+    /// Checks that the evaluator is authorized to learn the values, returning the first element
+    /// which it is not authorized to learn otherwise.
+    pub(crate) fn check_evaluator(&self, values: &[ValueRef]) -> Result<(), ValueRef> {
+        values
+            .iter()
+            .flat_map(|value| value.iter())
+            .find(|id| !self.get(id).allows_evaluator())
+            .map_or(Ok(()), |id| Err(ValueRef::Value { id: id.clone() }))
+    }
+}
+
+/// The domain of the decoding commitments.
+pub(crate) const DECODING_COMMITMENT_DOMAIN: &str = "mpz-garble decoding commitment";
+
+/// Returns the decoding of every value id of a value.
+///
+/// Returns `None` if the decoding does not have the shape of the value.
+pub(crate) fn decodings_by_id<'a>(
+    value: &'a ValueRef,
+    decoding: &'a Decoding,
+) -> Option<Vec<(&'a ValueId, &'a Decoding)>> {
+    match (value, decoding) {
+        (ValueRef::Value { id }, decoding) => Some(vec![(id, decoding)]),
+        (ValueRef::Array(array), Decoding::Array(decodings)) if array.len() == decodings.len() => {
+            Some(array.ids().iter().zip(decodings).collect())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_policy() {
+        let public = ValueRef::Value {
+            id: ValueId::new("public"),
+        };
+        let private = ValueRef::Value {
+            id: ValueId::new("private"),
+        };
+        let untagged = ValueRef::Value {
+            id: ValueId::new("untagged"),
+        };
+
+        let policy = DecodePolicy::new();
+        policy.tag(&public, DecodeAuth::Both);
+        policy.tag(&private, DecodeAuth::Generator);
+
+        assert!(policy
+            .check_evaluator(&[public.clone(), untagged.clone()])
+            .is_ok());
+        assert_eq!(
+            policy.check_evaluator(&[public.clone(), private.clone()]),
+            Err(private.clone())
+        );
+
+        let policy = DecodePolicy::strict();
+        policy.tag(&public, DecodeAuth::Both);
+
+        assert!(policy.check_evaluator(&[public]).is_ok());
+        assert_eq!(
+            policy.check_evaluator(std::slice::from_ref(&untagged)),
+            Err(untagged)
+        );
+    }
+}
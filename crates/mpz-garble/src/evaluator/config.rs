@@ -6,12 +6,22 @@ pub struct EvaluatorConfig {
     /// Whether to expect commitments to output encodings from the generator.
     #[builder(default = "false", setter(custom))]
     pub(crate) encoding_commitments: bool,
+    /// Whether to expect commitments to the decodings of outputs from the generator.
+    #[builder(default = "false", setter(custom))]
+    pub(crate) decoding_commitments: bool,
     /// Whether to log circuits.
     #[builder(default = "false", setter(custom))]
     pub(crate) log_circuits: bool,
     /// Whether to log decodings.
     #[builder(default = "false", setter(custom))]
     pub(crate) log_decodings: bool,
+    /// Whether to record the decoded values, see
+    /// [`Evaluator::take_decode_audit`](crate::Evaluator::take_decode_audit).
+    #[builder(default = "false", setter(custom))]
+    pub(crate) decode_audit: bool,
+    /// Whether to accept the values of traced wires from the generator.
+    #[builder(default = "false", setter(custom))]
+    pub(crate) trace: bool,
 }
 
 impl EvaluatorConfig {
@@ -28,6 +38,15 @@ impl EvaluatorConfigBuilder {
         self
     }
 
+    /// Enable decoding commitments.
+    ///
+    /// The evaluator checks the decodings of outputs against the commitments which the generator
+    /// sent with the garbled circuit.
+    pub fn decoding_commitments(&mut self) -> &mut Self {
+        self.decoding_commitments = Some(true);
+        self
+    }
+
     /// Enable circuit logs.
     pub fn log_circuits(&mut self) -> &mut Self {
         self.log_circuits = Some(true);
@@ -39,4 +58,22 @@ impl EvaluatorConfigBuilder {
         self.log_decodings = Some(true);
         self
     }
+
+    /// Enable the decode audit.
+    ///
+    /// The evaluator records every value it decodes, which must be drained with
+    /// [`Evaluator::take_decode_audit`](crate::Evaluator::take_decode_audit).
+    pub fn decode_audit(&mut self) -> &mut Self {
+        self.decode_audit = Some(true);
+        self
+    }
+
+    /// Enable tracing, consenting to learn the values of traced wires.
+    ///
+    /// This is intended for debugging only, see
+    /// [`Evaluator::evaluate_traced`](crate::Evaluator::evaluate_traced).
+    pub fn trace(&mut self) -> &mut Self {
+        self.trace = Some(true);
+        self
+    }
 }
@@ -1,4 +1,4 @@
-use crate::value::{ValueId, ValueRef};
+use crate::value::{ValueDiagnostics, ValueId};
 
 /// Errors that can occur while performing the role of an evaluator
 #[derive(Debug, thiserror::Error)]
@@ -21,14 +21,18 @@ pub enum EvaluatorError {
     ValueError(#[from] mpz_garble_core::ValueError),
     #[error(transparent)]
     EncodingRegistryError(#[from] crate::memory::EncodingMemoryError),
-    #[error("missing active encoding for value")]
-    MissingEncoding(ValueRef),
+    #[error("missing active encoding for value {0}")]
+    MissingEncoding(Box<ValueDiagnostics>),
+    #[error("evaluator is not authorized to decode value {0}")]
+    UnauthorizedDecode(Box<ValueDiagnostics>),
     #[error("duplicate garbled circuit")]
     DuplicateCircuit,
     #[error("duplicate decoding for value: {0:?}")]
     DuplicateDecoding(ValueId),
     #[error(transparent)]
     VerificationError(#[from] VerificationError),
+    #[error("tracing is not enabled in the evaluator config")]
+    TraceDisabled,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -12,22 +12,27 @@ use std::{
 
 use mpz_circuits::{
     types::{TypeError, Value, ValueType},
-    Circuit,
+    Circuit, Feed, Node,
 };
 use mpz_common::{cpu::CpuBackend, executor::DummyExecutor, scoped, Context};
-use mpz_core::hash::Hash;
+use mpz_core::{
+    commit::{Decommitment, Nonce},
+    hash::Hash,
+};
+use mpz_fields::Field;
 use mpz_garble_core::{
-    encoding_state, Decoding, EncodedValue, EncodingCommitment, EncryptedGateBatch,
-    Evaluator as EvaluatorCore, EvaluatorOutput, GarbledCircuit,
+    encoding_state, ConversionTable, Decoding, EncodedValue, EncodingCommitment,
+    EncryptedGateBatch, Evaluator as EvaluatorCore, EvaluatorOutput, GarbledCircuit,
 };
 use mpz_ot::TransferId;
-use serio::stream::IoStreamExt;
+use serio::{stream::IoStreamExt, Deserialize};
 use utils::iter::FilterDrain;
 
 use crate::{
-    memory::EncodingMemory,
+    decoding::{decodings_by_id, DecodeAuth, DecodePolicy, DECODING_COMMITMENT_DOMAIN},
+    memory::{DebugNames, EncodingMemory},
     ot::{EncodingReceiverOutput, OTReceiveEncoding, OTVerifyEncoding},
-    value::{CircuitRefs, ValueId, ValueRef},
+    value::{CircuitRefs, Phase, ValueId, ValueRef},
     AssignedValues, Generator, GeneratorConfigBuilder,
 };
 
@@ -36,11 +41,16 @@ pub use error::EvaluatorError;
 
 use error::VerificationError;
 
+/// The encoded outputs of an evaluated circuit and the values of any traced wires.
+type TracedOutput = (Vec<EncodedValue<encoding_state::Active>>, Option<Vec<bool>>);
+
 /// A garbled circuit evaluator.
 #[derive(Debug)]
 pub struct Evaluator {
     config: EvaluatorConfig,
     state: Mutex<State>,
+    debug_names: DebugNames,
+    decode_policy: DecodePolicy,
 }
 
 impl Default for Evaluator {
@@ -48,6 +58,8 @@ impl Default for Evaluator {
         Self {
             config: EvaluatorConfigBuilder::default().build().unwrap(),
             state: Mutex::new(State::default()),
+            debug_names: DebugNames::default(),
+            decode_policy: DecodePolicy::default(),
         }
     }
 }
@@ -70,6 +82,10 @@ struct State {
     circuit_logs: Vec<EvaluatorLog>,
     /// Decodings of values received from the generator
     decoding_logs: HashMap<ValueRef, Decoding>,
+    /// Commitments to the decodings of outputs which have not been decoded yet
+    decoding_commitments: HashMap<ValueId, Hash>,
+    /// Decoded values and the parties which were authorized to learn them
+    decode_audit: Vec<(ValueId, DecodeAuth)>,
 }
 
 impl Evaluator {
@@ -81,6 +97,24 @@ impl Evaluator {
         }
     }
 
+    /// Sets the registry used to include the names of values in errors.
+    pub fn set_debug_names(&mut self, debug_names: DebugNames) {
+        self.debug_names = debug_names;
+    }
+
+    /// Sets the policy of which values the evaluator is authorized to decode.
+    pub fn set_decode_policy(&mut self, decode_policy: DecodePolicy) {
+        self.decode_policy = decode_policy;
+    }
+
+    /// Returns the values which were decoded since the last call, in order, with the parties which
+    /// were authorized to learn them.
+    ///
+    /// Values are only recorded if the decode audit is enabled in the config.
+    pub fn take_decode_audit(&self) -> Vec<(ValueId, DecodeAuth)> {
+        mem::take(&mut self.state().decode_audit)
+    }
+
     /// Convenience method for grabbing a lock to the state.
     fn state(&self) -> impl DerefMut<Target = State> + '_ {
         self.state.lock().unwrap()
@@ -114,16 +148,25 @@ impl Evaluator {
     pub fn get_encodings(
         &self,
         values: &[ValueRef],
+    ) -> Result<Vec<EncodedValue<encoding_state::Active>>, EvaluatorError> {
+        self.get_encodings_in(values, None)
+    }
+
+    /// Returns the encodings for a slice of values, reporting the phase of execution if any
+    /// are missing.
+    pub(crate) fn get_encodings_in(
+        &self,
+        values: &[ValueRef],
+        phase: Option<Phase>,
     ) -> Result<Vec<EncodedValue<encoding_state::Active>>, EvaluatorError> {
         let state = self.state();
 
         values
             .iter()
             .map(|value| {
-                state
-                    .memory
-                    .get_encoding(value)
-                    .ok_or_else(|| EvaluatorError::MissingEncoding(value.clone()))
+                state.memory.get_encoding(value).ok_or_else(|| {
+                    EvaluatorError::MissingEncoding(self.debug_names.diagnose(value, None, phase))
+                })
             })
             .collect()
     }
@@ -191,7 +234,7 @@ impl Evaluator {
     /// - `id` - The id of this operation
     /// - `values` - The values to receive via oblivious transfer.
     /// - `ot` - The oblivious transfer receiver
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
+    #[tracing::instrument(fields(thread = %ctx.id(), values = values.len()), skip_all)]
     pub async fn ot_receive_active_encodings<Ctx: Context, OT: OTReceiveEncoding<Ctx>>(
         &self,
         ctx: &mut Ctx,
@@ -247,7 +290,7 @@ impl Evaluator {
     /// # Arguments
     /// - `values` - The values and types expected to be received
     /// - `stream` - The stream of messages from the generator
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
+    #[tracing::instrument(fields(thread = %ctx.id(), values = values.len()), skip_all)]
     pub async fn direct_receive_active_encodings<Ctx: Context>(
         &self,
         ctx: &mut Ctx,
@@ -295,7 +338,7 @@ impl Evaluator {
     /// * `inputs` - The inputs to the circuit
     /// * `outputs` - The outputs from the circuit
     /// * `stream` - The stream from the generator
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
+    #[tracing::instrument(fields(thread = %ctx.id(), and_count = circ.and_count()), skip_all)]
     pub async fn receive_garbled_circuit<Ctx: Context>(
         &self,
         ctx: &mut Ctx,
@@ -317,7 +360,7 @@ impl Evaluator {
 
         while gates.len() < gate_count {
             let batch: EncryptedGateBatch = ctx.io_mut().expect_next().await?;
-            gates.extend_from_slice(&batch.into_array());
+            gates.extend_from_slice(batch.gates());
         }
 
         // Trim off any batch padding.
@@ -340,6 +383,8 @@ impl Evaluator {
             None
         };
 
+        self.receive_decoding_commitments(ctx, outputs).await?;
+
         self.state().garbled_circuits.insert(
             refs,
             GarbledCircuit {
@@ -361,7 +406,6 @@ impl Evaluator {
     /// * `inputs` - The inputs to the circuit.
     /// * `outputs` - The outputs from the circuit.
     /// * `stream` - The stream of encrypted gates
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all, err)]
     pub async fn evaluate<Ctx: Context>(
         &self,
         ctx: &mut Ctx,
@@ -369,6 +413,53 @@ impl Evaluator {
         inputs: &[ValueRef],
         outputs: &[ValueRef],
     ) -> Result<Vec<EncodedValue<encoding_state::Active>>, EvaluatorError> {
+        self.evaluate_inner(ctx, circ, inputs, outputs, None)
+            .await
+            .map(|(outputs, _)| outputs)
+    }
+
+    /// Evaluate a circuit, learning the values of the provided intermediate wires.
+    ///
+    /// This is intended for debugging only, and requires tracing to be enabled in the config of
+    /// both parties. The generator must call
+    /// [`Generator::generate_traced`](crate::Generator::generate_traced) with the same wires.
+    ///
+    /// The values of the wires are logged at the debug level as they are received.
+    ///
+    /// Returns the encoded outputs of the evaluated circuit, and the values of the wires.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to evaluate
+    /// * `inputs` - The inputs to the circuit.
+    /// * `outputs` - The outputs from the circuit.
+    /// * `wires` - The wires to trace.
+    pub async fn evaluate_traced<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        wires: &[Node<Feed>],
+    ) -> Result<(Vec<EncodedValue<encoding_state::Active>>, Vec<bool>), EvaluatorError> {
+        if !self.config.trace {
+            return Err(EvaluatorError::TraceDisabled);
+        }
+
+        self.evaluate_inner(ctx, circ, inputs, outputs, Some(wires))
+            .await
+            .map(|(outputs, values)| (outputs, values.expect("wires were traced")))
+    }
+
+    #[tracing::instrument(fields(thread = %ctx.id(), and_count = circ.and_count()), skip_all, err)]
+    async fn evaluate_inner<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        trace: Option<&[Node<Feed>]>,
+    ) -> Result<TracedOutput, EvaluatorError> {
         let refs = CircuitRefs {
             inputs: inputs.to_vec(),
             outputs: outputs.to_vec(),
@@ -379,10 +470,13 @@ impl Evaluator {
             inputs
                 .iter()
                 .map(|value_ref| {
-                    state
-                        .memory
-                        .get_encoding(value_ref)
-                        .ok_or_else(|| EvaluatorError::MissingEncoding(value_ref.clone()))
+                    state.memory.get_encoding(value_ref).ok_or_else(|| {
+                        EvaluatorError::MissingEncoding(self.debug_names.diagnose(
+                            value_ref,
+                            Some(outputs),
+                            Some(Phase::Evaluate),
+                        ))
+                    })
                 })
                 .collect::<Result<Vec<_>, _>>()?
         };
@@ -391,13 +485,16 @@ impl Evaluator {
 
         // If we've already received the garbled circuit, we evaluate it, otherwise we stream the encrypted gates
         // from the generator.
-        let EvaluatorOutput {
-            outputs: encoded_outputs,
-            hash,
-        } = if let Some(GarbledCircuit { gates, commitments }) = existing_garbled_circuit {
+        let (
+            EvaluatorOutput {
+                outputs: encoded_outputs,
+                hash,
+            },
+            ev,
+        ) = if let Some(GarbledCircuit { gates, commitments }) = existing_garbled_circuit {
             let circ = circ.clone();
             let hash = self.config.log_circuits;
-            let output = CpuBackend::blocking(move || {
+            let (output, ev) = CpuBackend::blocking(move || {
                 let mut ev = EvaluatorCore::default();
                 let mut ev_consumer = ev.evaluate(&circ, encoded_inputs)?;
 
@@ -409,7 +506,9 @@ impl Evaluator {
                     ev_consumer.next(gate);
                 }
 
-                ev_consumer.finish().map_err(EvaluatorError::from)
+                let output = ev_consumer.finish()?;
+
+                Ok::<_, EvaluatorError>((output, ev))
             })
             .await?;
 
@@ -423,11 +522,11 @@ impl Evaluator {
                 }
             }
 
-            output
+            (output, ev)
         } else {
             let circ = circ.clone();
             let hash = self.config.log_circuits;
-            let output = ctx
+            let (output, ev) = ctx
                 .blocking(scoped!(move |ctx| async move {
                     let mut ev = EvaluatorCore::default();
                     let mut ev_consumer = ev.evaluate_batched(&circ, encoded_inputs)?;
@@ -442,7 +541,9 @@ impl Evaluator {
                         ev_consumer.next(batch);
                     }
 
-                    ev_consumer.finish().map_err(EvaluatorError::from)
+                    let output = ev_consumer.finish()?;
+
+                    Ok::<_, EvaluatorError>((output, ev))
                 }))
                 .await??;
 
@@ -462,7 +563,22 @@ impl Evaluator {
                 }
             }
 
-            output
+            self.receive_decoding_commitments(ctx, outputs).await?;
+
+            (output, ev)
+        };
+
+        let trace_values = if let Some(wires) = trace {
+            let bits: Vec<bool> = ctx.io_mut().expect_next().await?;
+            let values = ev.trace(wires, &bits)?;
+
+            for (wire, value) in wires.iter().zip(&values) {
+                tracing::debug!(wire = wire.id(), value, "traced wire");
+            }
+
+            Some(values)
+        } else {
+            None
         };
 
         // Add the output encodings to the memory.
@@ -482,12 +598,17 @@ impl Evaluator {
             ));
         }
 
-        Ok(encoded_outputs)
+        Ok((encoded_outputs, trace_values))
     }
 
     /// Receive decoding information for a set of values from the generator
     /// and decode them.
     ///
+    /// # Errors
+    ///
+    /// Returns an error, without receiving any decodings, if the evaluator is not authorized to
+    /// decode one of the values, see [`DecodePolicy`].
+    ///
     /// # Arguments
     ///
     /// * `values` - The values to decode
@@ -497,6 +618,16 @@ impl Evaluator {
         ctx: &mut Ctx,
         values: &[ValueRef],
     ) -> Result<Vec<Value>, EvaluatorError> {
+        self.decode_policy
+            .check_evaluator(values)
+            .map_err(|value| {
+                EvaluatorError::UnauthorizedDecode(self.debug_names.diagnose(
+                    &value,
+                    None,
+                    Some(Phase::Decode),
+                ))
+            })?;
+
         let decodings: Vec<Decoding> = ctx.io_mut().expect_next().await?;
 
         // Make sure the generator sent the expected number of decodings.
@@ -507,6 +638,10 @@ impl Evaluator {
             });
         }
 
+        if self.config.decoding_commitments {
+            self.verify_decodings(ctx, values, &decodings).await?;
+        }
+
         for (value, decoding) in values.iter().zip(decodings.iter()) {
             self.set_decoded(value)?;
             if self.config.log_decodings {
@@ -514,13 +649,7 @@ impl Evaluator {
             }
         }
 
-        let active_encodings = values
-            .iter()
-            .map(|value| {
-                self.get_encoding(value)
-                    .ok_or_else(|| EvaluatorError::MissingEncoding(value.clone()))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let active_encodings = self.get_encodings_in(values, Some(Phase::Decode))?;
 
         let decoded_values = decodings
             .iter()
@@ -528,9 +657,140 @@ impl Evaluator {
             .map(|(decoding, encoding)| encoding.decode(decoding))
             .collect::<Result<Vec<_>, _>>()?;
 
+        if self.config.decode_audit {
+            self.state().decode_audit.extend(
+                values
+                    .iter()
+                    .flat_map(|value| value.iter())
+                    .map(|id| (id.clone(), self.decode_policy.get(id))),
+            );
+        }
+
         Ok(decoded_values)
     }
 
+    /// Converts a value to an arithmetic encoding in the field `F`, returning the share of the
+    /// evaluator.
+    ///
+    /// The shares of the generator and the evaluator sum to the value, see
+    /// [`EncodedValue::to_arithmetic`]. The generator must call
+    /// [`Generator::convert_to_arithmetic`] for the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to convert, which must not be an array.
+    pub async fn convert_to_arithmetic<Ctx: Context, F: Field + Deserialize>(
+        &self,
+        ctx: &mut Ctx,
+        value: &ValueRef,
+    ) -> Result<F, EvaluatorError> {
+        let encoding = self.get_conversion_encoding(value)?;
+
+        let (id, table): (u64, ConversionTable<F>) = ctx.io_mut().expect_next().await?;
+
+        Ok(encoding.to_arithmetic(id, &table)?)
+    }
+
+    /// Computes a function of a value with a lookup table on its one-hot encoding, returning the
+    /// share of the evaluator of an arithmetic encoding of the result.
+    ///
+    /// The generator must call [`Generator::lookup`] for the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to look up, which must not be an array.
+    pub async fn lookup<Ctx: Context, F: Field + Deserialize>(
+        &self,
+        ctx: &mut Ctx,
+        value: &ValueRef,
+    ) -> Result<F, EvaluatorError> {
+        let encoding = self.get_conversion_encoding(value)?.to_one_hot()?;
+
+        let (id, table): (u64, ConversionTable<F>) = ctx.io_mut().expect_next().await?;
+
+        Ok(encoding.lookup(id, &table)?)
+    }
+
+    /// Returns the active encoding of a value which is converted to another encoding.
+    fn get_conversion_encoding(
+        &self,
+        value: &ValueRef,
+    ) -> Result<EncodedValue<encoding_state::Active>, EvaluatorError> {
+        Ok(self
+            .get_encodings_in(std::slice::from_ref(value), Some(Phase::Convert))?
+            .pop()
+            .expect("one encoding should be returned"))
+    }
+
+    /// Receives the commitments to the decodings of the outputs of a circuit, if configured.
+    async fn receive_decoding_commitments<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        outputs: &[ValueRef],
+    ) -> Result<(), EvaluatorError> {
+        if !self.config.decoding_commitments {
+            return Ok(());
+        }
+
+        let commitments: Vec<Hash> = ctx.io_mut().expect_next().await?;
+
+        let ids: Vec<&ValueId> = outputs.iter().flat_map(|output| output.iter()).collect();
+        if commitments.len() != ids.len() {
+            return Err(EvaluatorError::IncorrectValueCount {
+                expected: ids.len(),
+                actual: commitments.len(),
+            });
+        }
+
+        self.state()
+            .decoding_commitments
+            .extend(ids.into_iter().cloned().zip(commitments));
+
+        Ok(())
+    }
+
+    /// Receives the openings of the commitments to the decodings, and verifies the decodings
+    /// against them.
+    ///
+    /// Decodings of values which are not outputs of a garbled circuit are not committed to.
+    async fn verify_decodings<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        values: &[ValueRef],
+        decodings: &[Decoding],
+    ) -> Result<(), EvaluatorError> {
+        let nonces: Vec<Nonce> = ctx.io_mut().expect_next().await?;
+
+        let mut committed = Vec::new();
+        {
+            let mut state = self.state();
+            for (value, decoding) in values.iter().zip(decodings) {
+                let decodings =
+                    decodings_by_id(value, decoding).ok_or(VerificationError::InvalidDecoding)?;
+                for (id, decoding) in decodings {
+                    if let Some(commitment) = state.decoding_commitments.remove(id) {
+                        committed.push((decoding, commitment));
+                    }
+                }
+            }
+        }
+
+        if nonces.len() != committed.len() {
+            return Err(EvaluatorError::IncorrectValueCount {
+                expected: committed.len(),
+                actual: nonces.len(),
+            });
+        }
+
+        for ((decoding, commitment), nonce) in committed.into_iter().zip(nonces) {
+            Decommitment::new_with_nonce(decoding.clone(), nonce)
+                .verify_with_domain(&commitment, DECODING_COMMITMENT_DOMAIN)
+                .map_err(|_| VerificationError::InvalidDecoding)?;
+        }
+
+        Ok(())
+    }
+
     /// Verifies all the evaluator state using the generator's encoder seed and the OT verifier.
     ///
     /// # Arguments
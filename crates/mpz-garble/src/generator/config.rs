@@ -6,6 +6,12 @@ pub struct GeneratorConfig {
     /// Whether to send commitments to output encodings.
     #[builder(default = "false", setter(custom))]
     pub(crate) encoding_commitments: bool,
+    /// Whether to commit to the decodings of outputs.
+    #[builder(default = "false", setter(custom))]
+    pub(crate) decoding_commitments: bool,
+    /// Whether to reveal the values of traced wires to the evaluator.
+    #[builder(default = "false", setter(custom))]
+    pub(crate) trace: bool,
 }
 
 impl GeneratorConfig {
@@ -21,6 +27,24 @@ impl GeneratorConfigBuilder {
         self.encoding_commitments = Some(true);
         self
     }
+
+    /// Enable decoding commitments.
+    ///
+    /// The generator commits to the decodings of the outputs of every garbled circuit, and
+    /// opens the commitments when the outputs are decoded.
+    pub fn decoding_commitments(&mut self) -> &mut Self {
+        self.decoding_commitments = Some(true);
+        self
+    }
+
+    /// Enable tracing, consenting to reveal the values of traced wires to the evaluator.
+    ///
+    /// This is intended for debugging only, see
+    /// [`Generator::generate_traced`](crate::Generator::generate_traced).
+    pub fn trace(&mut self) -> &mut Self {
+        self.trace = Some(true);
+        self
+    }
 }
 
 impl Default for GeneratorConfig {
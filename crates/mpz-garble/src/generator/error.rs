@@ -1,6 +1,6 @@
 use mpz_garble_core::ValueError;
 
-use crate::value::ValueRef;
+use crate::value::ValueDiagnostics;
 
 /// Errors that can occur while performing the role of a generator
 #[derive(Debug, thiserror::Error)]
@@ -17,12 +17,18 @@ pub enum GeneratorError {
     ContextError(#[from] mpz_common::ContextError),
     #[error(transparent)]
     ValueError(#[from] ValueError),
-    #[error("duplicate encoding for value: {0:?}")]
-    DuplicateEncoding(ValueRef),
-    #[error("missing encoding for value: {0:?}")]
-    MissingEncoding(ValueRef),
+    #[error("duplicate encoding for value {0}")]
+    DuplicateEncoding(Box<ValueDiagnostics>),
+    #[error("missing encoding for value {0}")]
+    MissingEncoding(Box<ValueDiagnostics>),
+    #[error("evaluator is not authorized to decode value {0}")]
+    UnauthorizedDecode(Box<ValueDiagnostics>),
     #[error(transparent)]
     EncodingRegistryError(#[from] crate::memory::EncodingMemoryError),
+    #[error("tracing is not enabled in the generator config")]
+    TraceDisabled,
+    #[error("circuit was already garbled, its wires can not be traced")]
+    TraceUnavailable,
 }
 
 impl From<mpz_ot::OTError> for GeneratorError {
@@ -2,6 +2,7 @@
 
 mod config;
 mod error;
+mod secret;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -11,32 +12,41 @@ use std::{
 
 use mpz_circuits::{
     types::{Value, ValueType},
-    Circuit,
+    Circuit, Feed, Node,
 };
 use mpz_common::{scoped, Context};
-use mpz_core::hash::Hash;
+use mpz_core::{
+    commit::{Decommitment, Nonce},
+    hash::Hash,
+};
+use mpz_fields::Field;
 use mpz_garble_core::{
     encoding_state, ChaChaEncoder, EncodedValue, Encoder, EncodingCommitment,
     Generator as GeneratorCore, GeneratorOutput,
 };
-use serio::SinkExt;
+use rand::{thread_rng, Rng};
+use serio::{Serialize, SinkExt};
 use tracing::{span, Level};
 
 use crate::{
-    memory::EncodingMemory,
+    decoding::{decodings_by_id, DecodePolicy, DECODING_COMMITMENT_DOMAIN},
+    memory::{DebugNames, EncodingMemory},
     ot::OTSendEncoding,
-    value::{CircuitRefs, ValueId, ValueRef},
+    value::{CircuitRefs, Phase, ValueId, ValueRef},
     AssignedValues,
 };
 
 pub use config::{GeneratorConfig, GeneratorConfigBuilder};
 pub use error::GeneratorError;
+pub use secret::GeneratorSecret;
 
 /// A garbled circuit generator.
 #[derive(Debug, Default)]
 pub struct Generator {
     config: GeneratorConfig,
     state: Mutex<State>,
+    debug_names: DebugNames,
+    decode_policy: DecodePolicy,
 }
 
 #[derive(Debug, Default)]
@@ -56,6 +66,8 @@ struct State {
     /// This is used to guarantee that the same encoding is never used
     /// with different active values.
     active: HashSet<ValueId>,
+    /// Nonces of the commitments to the decodings of outputs which have not been decoded yet.
+    decoding_nonces: HashMap<ValueId, Nonce>,
 }
 
 impl Generator {
@@ -64,9 +76,74 @@ impl Generator {
         Self {
             config,
             state: Mutex::new(State::new(ChaChaEncoder::new(encoder_seed))),
+            debug_names: DebugNames::default(),
+            decode_policy: DecodePolicy::default(),
+        }
+    }
+
+    /// Creates a generator from the secret state of a generator of a previous session.
+    ///
+    /// The generator uses the same global offset (delta) as the previous session, and the
+    /// encodings of values which were already encoded or sent to the evaluator.
+    ///
+    /// # Warning
+    ///
+    /// The state must not be imported if the encoder seed was revealed to the evaluator, eg. when
+    /// finalizing DEAP, nor imported more than once.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The generator configuration.
+    /// * `secret` - The secret state exported with [`Generator::export`].
+    pub fn import(config: GeneratorConfig, secret: GeneratorSecret) -> Self {
+        let GeneratorSecret {
+            seed,
+            encodings,
+            active,
+        } = secret;
+
+        let mut state = State::new(ChaChaEncoder::new(seed));
+        state.memory = EncodingMemory::from_encodings(encodings);
+        state.active = active.iter().map(|id| ValueId::new(id)).collect();
+
+        Self {
+            config,
+            state: Mutex::new(state),
+            debug_names: DebugNames::default(),
+            decode_policy: DecodePolicy::default(),
+        }
+    }
+
+    /// Exports the secret state of the generator, so that it can be imported into a generator of
+    /// a later session with [`Generator::import`].
+    ///
+    /// The generator is consumed, so that the same state is not used by two generators. See
+    /// [`GeneratorSecret`] for how the state must be handled.
+    pub fn export(self) -> GeneratorSecret {
+        let State {
+            encoder,
+            memory,
+            active,
+            ..
+        } = self.state.into_inner().unwrap();
+
+        GeneratorSecret {
+            seed: encoder.seed().try_into().expect("encoder seed is 32 bytes"),
+            encodings: memory.into_encodings().collect(),
+            active: active.iter().map(|id| id.as_ref().to_string()).collect(),
         }
     }
 
+    /// Sets the registry used to include the names of values in errors.
+    pub fn set_debug_names(&mut self, debug_names: DebugNames) {
+        self.debug_names = debug_names;
+    }
+
+    /// Sets the policy of which values the evaluator is authorized to decode.
+    pub fn set_decode_policy(&mut self, decode_policy: DecodePolicy) {
+        self.decode_policy = decode_policy;
+    }
+
     /// Convenience method for grabbing a lock to the state.
     fn state(&self) -> impl DerefMut<Target = State> + '_ {
         self.state.lock().unwrap()
@@ -91,10 +168,9 @@ impl Generator {
         values
             .iter()
             .map(|value| {
-                state
-                    .memory
-                    .get_encoding(value)
-                    .ok_or_else(|| GeneratorError::MissingEncoding(value.clone()))
+                state.memory.get_encoding(value).ok_or_else(|| {
+                    GeneratorError::MissingEncoding(self.debug_names.diagnose(value, None, None))
+                })
             })
             .collect()
     }
@@ -175,7 +251,7 @@ impl Generator {
     /// - `id` - The ID of this operation
     /// - `values` - The values to send
     /// - `ot` - The OT sender
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
+    #[tracing::instrument(fields(thread = %ctx.id(), values = values.len()), skip_all)]
     pub(crate) async fn ot_send_active_encodings<Ctx: Context, OT: OTSendEncoding<Ctx>>(
         &self,
         ctx: &mut Ctx,
@@ -197,7 +273,7 @@ impl Generator {
 
             values
                 .iter()
-                .map(|(id, _)| state.activate_encoding(id))
+                .map(|(id, _)| state.activate_encoding(id, &self.debug_names))
                 .collect::<Result<Vec<_>, GeneratorError>>()?
         };
 
@@ -212,7 +288,7 @@ impl Generator {
     ///
     /// - `values` - The values to send
     /// - `sink` - The sink to send the encodings to the evaluator
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
+    #[tracing::instrument(fields(thread = %ctx.id(), values = values.len()), skip_all)]
     pub(crate) async fn direct_send_active_encodings<Ctx: Context>(
         &self,
         ctx: &mut Ctx,
@@ -234,7 +310,7 @@ impl Generator {
             values
                 .iter()
                 .map(|(id, value)| {
-                    let full_encoding = state.activate_encoding(id)?;
+                    let full_encoding = state.activate_encoding(id, &self.debug_names)?;
                     Ok(full_encoding.select(value.clone())?)
                 })
                 .collect::<Result<Vec<_>, GeneratorError>>()?
@@ -256,7 +332,6 @@ impl Generator {
     /// * `outputs` - The outputs of the circuit
     /// * `sink` - The sink to send the garbled circuit to the evaluator
     /// * `hash` - Whether to hash the circuit
-    #[tracing::instrument(fields(thread = %ctx.id()), skip_all)]
     pub async fn generate<Ctx: Context>(
         &self,
         ctx: &mut Ctx,
@@ -264,6 +339,52 @@ impl Generator {
         inputs: &[ValueRef],
         outputs: &[ValueRef],
         hash: bool,
+    ) -> Result<(Vec<EncodedValue<encoding_state::Full>>, Option<Hash>), GeneratorError> {
+        self.generate_inner(ctx, circ, inputs, outputs, hash, None)
+            .await
+    }
+
+    /// Generate a garbled circuit, revealing the values of the provided intermediate wires to
+    /// the evaluator.
+    ///
+    /// This is intended for debugging only, and requires tracing to be enabled in the config of
+    /// both parties. The evaluator must call
+    /// [`Evaluator::evaluate_traced`](crate::Evaluator::evaluate_traced) with the same wires.
+    ///
+    /// Returns the encodings of the outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to garble
+    /// * `inputs` - The inputs of the circuit
+    /// * `outputs` - The outputs of the circuit
+    /// * `wires` - The wires to trace
+    pub async fn generate_traced<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        wires: &[Node<Feed>],
+    ) -> Result<Vec<EncodedValue<encoding_state::Full>>, GeneratorError> {
+        if !self.config.trace {
+            return Err(GeneratorError::TraceDisabled);
+        }
+
+        self.generate_inner(ctx, circ, inputs, outputs, false, Some(wires))
+            .await
+            .map(|(outputs, _)| outputs)
+    }
+
+    #[tracing::instrument(fields(thread = %ctx.id(), and_count = circ.and_count()), skip_all)]
+    async fn generate_inner<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        hash: bool,
+        trace: Option<&[Node<Feed>]>,
     ) -> Result<(Vec<EncodedValue<encoding_state::Full>>, Option<Hash>), GeneratorError> {
         let refs = CircuitRefs {
             inputs: inputs.to_vec(),
@@ -275,6 +396,10 @@ impl Generator {
 
             // If the circuit has already been garbled, return early
             if let Some(hash) = state.garbled.get(&refs) {
+                if trace.is_some() {
+                    return Err(GeneratorError::TraceUnavailable);
+                }
+
                 return Ok((
                     outputs
                         .iter()
@@ -293,10 +418,13 @@ impl Generator {
             let inputs = inputs
                 .iter()
                 .map(|value| {
-                    state
-                        .memory
-                        .get_encoding(value)
-                        .ok_or(GeneratorError::MissingEncoding(value.clone()))
+                    state.memory.get_encoding(value).ok_or_else(|| {
+                        GeneratorError::MissingEncoding(self.debug_names.diagnose(
+                            value,
+                            Some(outputs),
+                            Some(Phase::Garble),
+                        ))
+                    })
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
@@ -305,10 +433,14 @@ impl Generator {
 
         // Garble the circuit in batches, streaming the encrypted gates from the worker thread.
         let span = span!(Level::TRACE, "worker");
-        let GeneratorOutput {
-            outputs: encoded_outputs,
-            hash,
-        } = ctx
+        let trace = trace.map(<[Node<Feed>]>::to_vec);
+        let (
+            GeneratorOutput {
+                outputs: encoded_outputs,
+                hash,
+            },
+            trace_bits,
+        ) = ctx
             .blocking(scoped!(move |ctx| async move {
                 let _enter = span.enter();
                 let mut gen = GeneratorCore::default();
@@ -323,7 +455,10 @@ impl Generator {
                     io.feed(batch).await?;
                 }
 
-                gen_iter.finish().map_err(GeneratorError::from)
+                let output = gen_iter.finish()?;
+                let trace_bits = trace.map(|wires| gen.trace(&wires)).transpose()?;
+
+                Ok::<_, GeneratorError>((output, trace_bits))
             }))
             .await??;
 
@@ -335,6 +470,26 @@ impl Generator {
             ctx.io_mut().feed(commitments).await?;
         }
 
+        let mut decoding_nonces = Vec::new();
+        if self.config.decoding_commitments {
+            let mut commitments = Vec::new();
+            for (output, encoding) in outputs.iter().zip(encoded_outputs.iter()) {
+                let decoding = encoding.decoding();
+                let decodings = decodings_by_id(output, &decoding)
+                    .expect("decoding should have the shape of the output");
+                for (id, decoding) in decodings {
+                    let decommitment = Decommitment::new(decoding.clone());
+                    commitments.push(decommitment.commit_with_domain(DECODING_COMMITMENT_DOMAIN));
+                    decoding_nonces.push((id.clone(), *decommitment.nonce()));
+                }
+            }
+            ctx.io_mut().feed(commitments).await?;
+        }
+
+        if let Some(trace_bits) = trace_bits {
+            ctx.io_mut().feed(trace_bits).await?;
+        }
+
         ctx.io_mut().flush().await?;
 
         // Add the outputs to the memory and set as active.
@@ -346,6 +501,7 @@ impl Generator {
             });
         }
 
+        state.decoding_nonces.extend(decoding_nonces);
         state.garbled.insert(refs, hash);
 
         Ok((encoded_outputs, hash))
@@ -353,6 +509,11 @@ impl Generator {
 
     /// Send value decoding information to the evaluator.
     ///
+    /// # Errors
+    ///
+    /// Returns an error, without sending any decodings, if the evaluator is not authorized to
+    /// decode one of the values, see [`DecodePolicy`].
+    ///
     /// # Arguments
     ///
     /// * `values` - The values to decode
@@ -362,6 +523,16 @@ impl Generator {
         ctx: &mut Ctx,
         values: &[ValueRef],
     ) -> Result<(), GeneratorError> {
+        self.decode_policy
+            .check_evaluator(values)
+            .map_err(|value| {
+                GeneratorError::UnauthorizedDecode(self.debug_names.diagnose(
+                    &value,
+                    None,
+                    Some(Phase::Decode),
+                ))
+            })?;
+
         let decodings = {
             let state = self.state();
             values
@@ -370,16 +541,108 @@ impl Generator {
                     state
                         .memory
                         .get_encoding(value)
-                        .ok_or(GeneratorError::MissingEncoding(value.clone()))
+                        .ok_or_else(|| {
+                            GeneratorError::MissingEncoding(self.debug_names.diagnose(
+                                value,
+                                None,
+                                Some(Phase::Decode),
+                            ))
+                        })
                         .map(|encoding| encoding.decoding())
                 })
                 .collect::<Result<Vec<_>, _>>()?
         };
 
-        ctx.io_mut().send(decodings).await?;
+        if self.config.decoding_commitments {
+            // Open the commitments to the decodings of outputs.
+            let nonces: Vec<Nonce> = {
+                let mut state = self.state();
+                values
+                    .iter()
+                    .zip(&decodings)
+                    .flat_map(|(value, decoding)| {
+                        decodings_by_id(value, decoding)
+                            .expect("decoding should have the shape of the value")
+                    })
+                    .filter_map(|(id, _)| state.decoding_nonces.remove(id))
+                    .collect()
+            };
+
+            ctx.io_mut().feed(decodings).await?;
+            ctx.io_mut().send(nonces).await?;
+        } else {
+            ctx.io_mut().send(decodings).await?;
+        }
 
         Ok(())
     }
+
+    /// Converts a value to an arithmetic encoding in the field `F`, returning the share of the
+    /// generator.
+    ///
+    /// The shares of the generator and the evaluator sum to the value, see
+    /// [`EncodedValue::to_arithmetic`]. The evaluator must call
+    /// [`Evaluator::convert_to_arithmetic`](crate::Evaluator::convert_to_arithmetic) for the same
+    /// value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to convert, which must not be an array.
+    pub async fn convert_to_arithmetic<Ctx: Context, F: Field + Serialize>(
+        &self,
+        ctx: &mut Ctx,
+        value: &ValueRef,
+    ) -> Result<F, GeneratorError> {
+        let encoding = self.get_conversion_encoding(value)?;
+
+        let id: u64 = thread_rng().gen();
+        let (share, table) = encoding.to_arithmetic::<F, _>(id, &mut thread_rng())?;
+
+        ctx.io_mut().send((id, table)).await?;
+
+        Ok(share)
+    }
+
+    /// Computes `f(value)` with a lookup table on the one-hot encoding of a value, returning the
+    /// share of the generator of an arithmetic encoding of the result.
+    ///
+    /// The table has a row for every possible value, so the value must have at most
+    /// [`MAX_ONE_HOT_BITS`](mpz_garble_core::MAX_ONE_HOT_BITS) bits. The evaluator must call
+    /// [`Evaluator::lookup`](crate::Evaluator::lookup) for the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to look up, which must not be an array.
+    /// * `f` - The function to compute, which is called with the bits of the value as an integer.
+    pub async fn lookup<Ctx: Context, F: Field + Serialize>(
+        &self,
+        ctx: &mut Ctx,
+        value: &ValueRef,
+        f: impl Fn(u64) -> F + Send,
+    ) -> Result<F, GeneratorError> {
+        let encoding = self.get_conversion_encoding(value)?.to_one_hot()?;
+
+        let id: u64 = thread_rng().gen();
+        let (share, table) = encoding.lookup(id, &mut thread_rng(), f);
+
+        ctx.io_mut().send((id, table)).await?;
+
+        Ok(share)
+    }
+
+    /// Returns the encoding of a value which is converted to another encoding.
+    fn get_conversion_encoding(
+        &self,
+        value: &ValueRef,
+    ) -> Result<EncodedValue<encoding_state::Full>, GeneratorError> {
+        self.get_encoding(value).ok_or_else(|| {
+            GeneratorError::MissingEncoding(self.debug_names.diagnose(
+                value,
+                None,
+                Some(Phase::Convert),
+            ))
+        })
+    }
 }
 
 impl State {
@@ -427,17 +690,20 @@ impl State {
     fn activate_encoding(
         &mut self,
         id: &ValueId,
+        debug_names: &DebugNames,
     ) -> Result<EncodedValue<encoding_state::Full>, GeneratorError> {
-        let encoding = self
-            .memory
-            .get_encoding_by_id(id)
-            .ok_or_else(|| GeneratorError::MissingEncoding(ValueRef::Value { id: id.clone() }))?;
+        let value = ValueRef::Value { id: id.clone() };
+        let encoding = self.memory.get_encoding_by_id(id).ok_or_else(|| {
+            GeneratorError::MissingEncoding(debug_names.diagnose(&value, None, Some(Phase::Setup)))
+        })?;
 
         // Returns error if the encoding is already active
         if !self.active.insert(id.clone()) {
-            return Err(GeneratorError::DuplicateEncoding(ValueRef::Value {
-                id: id.clone(),
-            }));
+            return Err(GeneratorError::DuplicateEncoding(debug_names.diagnose(
+                &value,
+                None,
+                Some(Phase::Setup),
+            )));
         }
 
         Ok(encoding)
@@ -0,0 +1,22 @@
+use mpz_garble_core::{encoding_state, EncodedValue};
+use serde::{Deserialize, Serialize};
+
+/// The secret state of a [`Generator`](super::Generator), exported so that it can be imported
+/// into a generator of a later session.
+///
+/// The state includes the seed of the generator's encoder, which determines its global offset
+/// (delta), the encodings of values and the set of values which were sent to the evaluator.
+///
+/// # Warning
+///
+/// Anyone who learns this state can decode every value of the session. It must only be
+/// persisted to secure storage. This type does not implement `Clone`, and its `Debug`
+/// implementation does not reveal its contents.
+#[derive(Serialize, Deserialize)]
+pub struct GeneratorSecret {
+    pub(crate) seed: [u8; 32],
+    pub(crate) encodings: Vec<(u64, EncodedValue<encoding_state::Full>)>,
+    pub(crate) active: Vec<String>,
+}
+
+opaque_debug::implement!(GeneratorSecret);
@@ -41,3 +41,23 @@ pub(crate) fn build_otp_shared_circuit(inputs: &[ValueType]) -> Arc<Circuit> {
 
     Arc::new(circ)
 }
+
+/// Builds an identity circuit for the provided values.
+///
+/// Each output is computed as `input & input`, so that the outputs are assigned fresh encodings
+/// instead of the encodings of the inputs, as would be the case with free XOR.
+pub(crate) fn build_refresh_circuit(inputs: &[ValueType]) -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+
+    for input_ty in inputs {
+        let input = builder.add_input_by_type(input_ty.clone());
+
+        let input = Tracer::new(builder.state(), input);
+        let refreshed = input.clone() & input;
+        builder.add_output(refreshed);
+    }
+
+    let circ = builder.build().expect("circuit should be valid");
+
+    Arc::new(circ)
+}
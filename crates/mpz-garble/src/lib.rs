@@ -14,18 +14,25 @@ use mpz_circuits::{
     Circuit,
 };
 
+pub mod compare;
 pub mod config;
+pub mod cost;
+pub(crate) mod decoding;
 pub(crate) mod evaluator;
 pub(crate) mod generator;
 pub(crate) mod internal_circuits;
 pub(crate) mod memory;
 pub mod ot;
+pub mod program;
 pub mod protocol;
 pub mod value;
 
+pub use decoding::{DecodeAuth, DecodePolicy};
 pub use evaluator::{Evaluator, EvaluatorConfig, EvaluatorConfigBuilder, EvaluatorError};
-pub use generator::{Generator, GeneratorConfig, GeneratorConfigBuilder, GeneratorError};
-pub use memory::{AssignedValues, ValueMemory};
+pub use generator::{
+    Generator, GeneratorConfig, GeneratorConfigBuilder, GeneratorError, GeneratorSecret,
+};
+pub use memory::{AssignedValues, DebugNames, Scope, ValueMemory};
 
 use value::{ArrayRef, ValueId, ValueRef};
 
@@ -63,6 +70,8 @@ pub enum VmError {
 pub enum MemoryError {
     #[error("duplicate value id: {0:?}")]
     DuplicateValueId(ValueId),
+    #[error("encoding id of value {0:?} collides with value {1:?}")]
+    EncodingIdCollision(ValueId, ValueId),
     #[error("duplicate value: {0:?}")]
     DuplicateValue(ValueRef),
     #[error("value with id {0} has not been defined")]
@@ -238,6 +247,16 @@ pub trait Memory {
         self.new_output_with_type(id, ValueType::new_array::<T>(len))
     }
 
+    /// Returns a scope, in which the IDs of new values are prefixed with the name of the scope.
+    ///
+    /// See [`Scope`] for more details.
+    fn scope(&self, name: &str) -> Scope<'_, Self>
+    where
+        Self: Sized,
+    {
+        Scope::new(self, name)
+    }
+
     /// Assigns a value.
     fn assign(&self, value_ref: &ValueRef, value: impl Into<Value>) -> Result<(), MemoryError>;
 
@@ -315,6 +334,10 @@ pub trait Execute {
         inputs: &[ValueRef],
         outputs: &[ValueRef],
     ) -> Result<(), ExecutionError>;
+
+    /// Refreshes the encodings of the provided values, returning references to new values which
+    /// are equal to the provided values but have fresh encodings.
+    async fn refresh(&mut self, values: &[ValueRef]) -> Result<Vec<ValueRef>, ExecutionError>;
 }
 
 /// This trait provides methods for proving the authenticity and correctness of the output of a
@@ -1,12 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+};
 
 use mpz_circuits::types::{Value, ValueType};
 use mpz_garble_core::{encoding_state::LabelState, EncodedValue};
 
 use crate::{
     config::Visibility,
-    value::{ArrayRef, ValueId, ValueRef},
-    AssignmentError, MemoryError,
+    value::{ArrayRef, Phase, ValueDiagnostics, ValueId, ValueRef},
+    AssignmentError, Memory, MemoryError,
 };
 
 /// Collection of assigned values.
@@ -45,6 +49,85 @@ impl ValueDetails {
     }
 }
 
+/// An optional registry of human-readable names of values.
+///
+/// When enabled, names are included in errors, see [`ValueDiagnostics`]. The registry is
+/// disabled by default as it retains a name for every value. Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct DebugNames(Option<Arc<Mutex<HashMap<ValueRef, String>>>>);
+
+impl fmt::Debug for DebugNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugNames")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl DebugNames {
+    /// Creates a new enabled registry.
+    pub fn new() -> Self {
+        Self(Some(Default::default()))
+    }
+
+    /// Returns `true` if the registry is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Registers the name of a value, doing nothing if the registry is disabled.
+    ///
+    /// The elements of an array are registered as `name[i]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value.
+    /// * `name` - The name of the value.
+    pub fn register(&self, value: &ValueRef, name: &str) {
+        let Some(names) = &self.0 else {
+            return;
+        };
+
+        let mut names = names.lock().unwrap();
+        if let ValueRef::Array(array) = value {
+            for (i, id) in array.ids().iter().enumerate() {
+                names.insert(ValueRef::Value { id: id.clone() }, format!("{name}[{i}]"));
+            }
+        }
+        names.insert(value.clone(), name.to_string());
+    }
+
+    /// Returns the name of a value if it is registered.
+    pub fn name(&self, value: &ValueRef) -> Option<String> {
+        self.0.as_ref()?.lock().unwrap().get(value).cloned()
+    }
+
+    /// Returns the diagnostics of a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value.
+    /// * `outputs` - The outputs of the circuit being executed, if any.
+    /// * `phase` - The phase of execution.
+    pub(crate) fn diagnose(
+        &self,
+        value: &ValueRef,
+        outputs: Option<&[ValueRef]>,
+        phase: Option<Phase>,
+    ) -> Box<ValueDiagnostics> {
+        let circuit = outputs
+            .and_then(|outputs| outputs.first())
+            .map(|output| self.name(output).unwrap_or_else(|| output.to_string()));
+
+        Box::new(ValueDiagnostics {
+            value: value.clone(),
+            name: self.name(value),
+            circuit,
+            phase,
+        })
+    }
+}
+
 /// A memory for storing values.
 #[derive(Default)]
 pub struct ValueMemory {
@@ -54,15 +137,24 @@ pub struct ValueMemory {
     ref_to_id: HashMap<ValueRef, String>,
     /// Details for each value
     details: HashMap<ValueId, ValueDetails>,
+    /// Value IDs by their encoding ID
+    encoding_ids: HashMap<u64, ValueId>,
     /// Values that have been assigned and blind values
     assigned: HashSet<ValueId>,
     /// Buffer containing assigned values
     assigned_buffer: HashMap<ValueId, AssignedValue>,
+    /// Names of values for diagnostics
+    debug_names: DebugNames,
 }
 
 opaque_debug::implement!(ValueMemory);
 
 impl ValueMemory {
+    /// Sets the registry in which the names of new values are registered.
+    pub fn set_debug_names(&mut self, debug_names: DebugNames) {
+        self.debug_names = debug_names;
+    }
+
     /// Adds a new input value to the memory.
     ///
     /// # Arguments
@@ -83,17 +175,13 @@ impl ValueMemory {
             for i in 0..len {
                 let elem_id = value_id.append_counter(i);
 
-                if self.details.contains_key(&elem_id) {
-                    return Err(MemoryError::DuplicateValueId(elem_id));
-                }
-
-                self.details.insert(
+                self.insert_details(
                     elem_id.clone(),
                     ValueDetails::Input {
                         typ: typ.clone(),
                         visibility,
                     },
-                );
+                )?;
                 ids.push(elem_id);
             }
 
@@ -107,17 +195,13 @@ impl ValueMemory {
 
             ValueRef::Array(ArrayRef::new(ids))
         } else {
-            if self.details.contains_key(&value_id) {
-                return Err(MemoryError::DuplicateValueId(value_id));
-            }
-
-            self.details.insert(
+            self.insert_details(
                 value_id.clone(),
                 ValueDetails::Input {
                     typ: typ.clone(),
                     visibility,
                 },
-            );
+            )?;
 
             if let Visibility::Blind = visibility {
                 self.assigned.insert(value_id.clone());
@@ -128,6 +212,7 @@ impl ValueMemory {
             ValueRef::Value { id: value_id }
         };
 
+        self.debug_names.register(&value_ref, id);
         self.id_to_ref.insert(id.to_string(), value_ref.clone());
         self.ref_to_id.insert(value_ref.clone(), id.to_string());
 
@@ -148,34 +233,46 @@ impl ValueMemory {
             for i in 0..len {
                 let elem_id = value_id.append_counter(i);
 
-                if self.details.contains_key(&elem_id) {
-                    return Err(MemoryError::DuplicateValueId(elem_id));
-                }
-
-                self.details
-                    .insert(elem_id.clone(), ValueDetails::Output { typ: typ.clone() });
+                self.insert_details(elem_id.clone(), ValueDetails::Output { typ: typ.clone() })?;
 
                 ids.push(elem_id);
             }
 
             ValueRef::Array(ArrayRef::new(ids))
         } else {
-            if self.details.contains_key(&value_id) {
-                return Err(MemoryError::DuplicateValueId(value_id));
-            }
-
-            self.details
-                .insert(value_id.clone(), ValueDetails::Output { typ });
+            self.insert_details(value_id.clone(), ValueDetails::Output { typ })?;
 
             ValueRef::Value { id: value_id }
         };
 
+        self.debug_names.register(&value_ref, id);
         self.id_to_ref.insert(id.to_string(), value_ref.clone());
         self.ref_to_id.insert(value_ref.clone(), id.to_string());
 
         Ok(value_ref)
     }
 
+    /// Inserts the details of a new value.
+    ///
+    /// Returns an error if the ID is already defined, or if its encoding ID collides with the
+    /// encoding ID of another value. Both parties define the same IDs, so a collision is detected
+    /// by both parties before any encoding is generated for the value.
+    fn insert_details(&mut self, id: ValueId, details: ValueDetails) -> Result<(), MemoryError> {
+        if self.details.contains_key(&id) {
+            return Err(MemoryError::DuplicateValueId(id));
+        }
+
+        let encoding_id = id.to_u64();
+        if let Some(other) = self.encoding_ids.get(&encoding_id) {
+            return Err(MemoryError::EncodingIdCollision(id, other.clone()));
+        }
+
+        self.encoding_ids.insert(encoding_id, id.clone());
+        self.details.insert(id, details);
+
+        Ok(())
+    }
+
     /// Assigns a value to a value reference.
     ///
     /// # Arguments
@@ -300,6 +397,75 @@ impl ValueMemory {
     }
 }
 
+/// A namespace of values, returned by [`Memory::scope`].
+///
+/// The IDs of values in a scope are prefixed with the name of the scope, eg. `key` in the scope
+/// `handshake` has the ID `handshake/key`, so that independent subprotocols can be composed in one
+/// session without coordinating their IDs. Scopes can be nested.
+///
+/// Both parties must use the same scopes. IDs which collide, eg. a scoped ID and an ID which
+/// was prefixed manually, are rejected like any other duplicate ID.
+#[derive(Debug)]
+pub struct Scope<'a, M> {
+    memory: &'a M,
+    prefix: String,
+}
+
+impl<'a, M> Scope<'a, M> {
+    pub(crate) fn new(memory: &'a M, name: &str) -> Self {
+        Self {
+            memory,
+            prefix: name.to_string(),
+        }
+    }
+
+    /// Returns the prefix of the IDs in this scope.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the ID of a value in this scope.
+    pub fn id(&self, id: &str) -> String {
+        format!("{}/{}", self.prefix, id)
+    }
+}
+
+impl<'a, M: Memory> Memory for Scope<'a, M> {
+    fn new_input_with_type(
+        &self,
+        id: &str,
+        typ: ValueType,
+        visibility: Visibility,
+    ) -> Result<ValueRef, MemoryError> {
+        self.memory
+            .new_input_with_type(&self.id(id), typ, visibility)
+    }
+
+    fn new_output_with_type(&self, id: &str, typ: ValueType) -> Result<ValueRef, MemoryError> {
+        self.memory.new_output_with_type(&self.id(id), typ)
+    }
+
+    fn assign(&self, value_ref: &ValueRef, value: impl Into<Value>) -> Result<(), MemoryError> {
+        self.memory.assign(value_ref, value)
+    }
+
+    fn assign_by_id(&self, id: &str, value: impl Into<Value>) -> Result<(), MemoryError> {
+        self.memory.assign_by_id(&self.id(id), value)
+    }
+
+    fn get_value(&self, id: &str) -> Option<ValueRef> {
+        self.memory.get_value(&self.id(id))
+    }
+
+    fn get_value_type(&self, value_ref: &ValueRef) -> ValueType {
+        self.memory.get_value_type(value_ref)
+    }
+
+    fn get_value_type_by_id(&self, id: &str) -> Option<ValueType> {
+        self.memory.get_value_type_by_id(&self.id(id))
+    }
+}
+
 /// A unique ID for an encoding.
 ///
 /// # Warning
@@ -444,6 +610,26 @@ where
     pub(crate) fn contains(&self, id: &ValueId) -> bool {
         self.encodings.contains_key(&id.to_u64().into())
     }
+
+    /// Creates a memory from encodings, each identified by the `u64` representation of its
+    /// value id.
+    pub(crate) fn from_encodings(
+        encodings: impl IntoIterator<Item = (u64, EncodedValue<T>)>,
+    ) -> Self {
+        Self {
+            encodings: encodings
+                .into_iter()
+                .map(|(id, encoding)| (EncodingId::new(id), encoding))
+                .collect(),
+        }
+    }
+
+    /// Returns the encodings, each identified by the `u64` representation of its value id.
+    pub(crate) fn into_encodings(self) -> impl Iterator<Item = (u64, EncodedValue<T>)> {
+        self.encodings
+            .into_iter()
+            .map(|(EncodingId(id), encoding)| (id, encoding))
+    }
 }
 
 #[cfg(test)]
@@ -561,4 +747,52 @@ mod tests {
 
         assert!(matches!(err, EncodingMemoryError::DuplicateId(_)));
     }
+
+    #[test]
+    fn test_value_memory_encoding_id_collision_fails() {
+        let mut memory = ValueMemory::default();
+
+        // Simulate a value whose encoding ID collides with the encoding ID of `test`.
+        memory
+            .encoding_ids
+            .insert(ValueId::new("test").to_u64(), ValueId::new("other"));
+
+        let err = memory
+            .new_input("test", u8::value_type(), Visibility::Private)
+            .unwrap_err();
+
+        assert!(matches!(err, MemoryError::EncodingIdCollision(..)));
+    }
+
+    #[test]
+    fn test_debug_names() {
+        let mut memory = ValueMemory::default();
+        memory.set_debug_names(DebugNames::new());
+
+        let array = memory
+            .new_input("test", <[u8; 4]>::value_type(), Visibility::Private)
+            .unwrap();
+        let ValueRef::Array(elems) = &array else {
+            panic!("value should be an array");
+        };
+        let elem = ValueRef::Value {
+            id: elems.ids()[1].clone(),
+        };
+
+        let diagnostics = memory.debug_names.diagnose(
+            &elem,
+            Some(std::slice::from_ref(&array)),
+            Some(Phase::Evaluate),
+        );
+
+        assert_eq!(
+            diagnostics.to_string(),
+            "`test[1]` during evaluation of circuit `test`"
+        );
+
+        let diagnostics = DebugNames::default().diagnose(&elem, None, None);
+
+        assert_eq!(diagnostics.name, None);
+        assert_eq!(diagnostics.to_string(), format!("`{elem}`"));
+    }
 }
@@ -36,17 +36,41 @@ where
         ctx: &mut Ctx,
         input: Vec<EncodedValue<encoding_state::Full>>,
     ) -> Result<EncodingSenderOutput, mpz_ot::OTError> {
-        let blocks: Vec<[Block; 2]> = input
-            .into_iter()
-            .flat_map(|v| v.iter_blocks().collect::<Vec<_>>())
-            .collect();
+        // Gather the blocks from the encodings without copying them into a buffer.
+        let len = input.iter().map(|v| v.value_type().len()).sum();
+        let blocks = ExactLen {
+            iter: input.iter().flat_map(|v| v.iter_blocks()),
+            len,
+        };
 
-        let output = self.send(ctx, &blocks).await?;
+        let output = self.send_iter(ctx, blocks).await?;
 
         Ok(EncodingSenderOutput { id: output.id })
     }
 }
 
+/// An iterator with a known number of items.
+struct ExactLen<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I: Iterator> Iterator for ExactLen<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for ExactLen<I> {}
+
 /// A trait for receiving encodings via oblivious transfer.
 #[async_trait]
 pub trait OTReceiveEncoding<Ctx> {
@@ -0,0 +1,376 @@
+//! Programs of circuit executions which are scheduled automatically.
+//!
+//! A [`Program`] is a graph of circuit executions, where the outputs of one circuit may be used as
+//! the inputs of another. Running a program on a VM thread commits the inputs of the program,
+//! loads every circuit ahead of execution, executes the circuits in dependency order, and decodes
+//! the requested values as soon as they are computed.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use mpz_circuits::{ops::WrappingAdd, Circuit, CircuitBuilder};
+//! use mpz_garble::{
+//!     program::{Output, Program},
+//!     protocol::deap::mock::{create_mock_deap_vm, MockLeader},
+//!     Memory,
+//! };
+//!
+//! fn build(
+//!     thread: &mut MockLeader,
+//!     adder: Arc<Circuit>,
+//!     private: [Option<u8>; 2],
+//! ) -> (Program, Output) {
+//!     let [a, b] = [("a", private[0]), ("b", private[1])].map(|(id, value)| match value {
+//!         Some(value) => {
+//!             let input = thread.new_private_input::<u8>(id).unwrap();
+//!             thread.assign(&input, value).unwrap();
+//!             input
+//!         }
+//!         None => thread.new_blind_input::<u8>(id).unwrap(),
+//!     });
+//!     let c = thread.new_output::<u8>("c").unwrap();
+//!     let d = thread.new_output::<u8>("d").unwrap();
+//!
+//!     let mut program = Program::new();
+//!     // `d` depends on `c`, so it is computed second even though it is called first.
+//!     program.call(adder.clone(), &[c.clone(), b.clone()], &[d.clone()]);
+//!     program.call(adder, &[a, b], &[c]);
+//!     let d = program.decode(&d);
+//!
+//!     (program, d)
+//! }
+//!
+//! # futures::executor::block_on(async {
+//! let builder = CircuitBuilder::new();
+//! let a = builder.add_input::<u8>();
+//! let b = builder.add_input::<u8>();
+//! builder.add_output(a.wrapping_add(b));
+//! let adder = Arc::new(builder.build().unwrap());
+//!
+//! let (mut leader, mut follower) = create_mock_deap_vm();
+//! let (leader_program, leader_d) = build(&mut leader, adder.clone(), [Some(1), None]);
+//! let (follower_program, follower_d) = build(&mut follower, adder, [None, Some(2)]);
+//!
+//! let (leader_result, follower_result, leader_d, follower_d) = futures::join!(
+//!     leader_program.run(&mut leader),
+//!     follower_program.run(&mut follower),
+//!     leader_d,
+//!     follower_d,
+//! );
+//!
+//! leader_result.unwrap();
+//! follower_result.unwrap();
+//! // d = (a + b) + b
+//! assert_eq!(leader_d.unwrap(), 5u8.into());
+//! assert_eq!(follower_d.unwrap(), 5u8.into());
+//! # });
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::channel::oneshot;
+use mpz_circuits::{types::Value, Circuit};
+
+use crate::{
+    value::{ValueId, ValueRef},
+    Decode, DecodeError, Execute, ExecutionError, Load, LoadError,
+};
+
+/// Errors that can occur when running a [`Program`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ProgramError {
+    #[error(transparent)]
+    LoadError(#[from] LoadError),
+    #[error(transparent)]
+    ExecutionError(#[from] ExecutionError),
+    #[error(transparent)]
+    DecodeError(#[from] DecodeError),
+    #[error("value {0:?} is the output of more than one call")]
+    DuplicateOutput(ValueId),
+    #[error("program contains a cycle")]
+    Cycle,
+    #[error("program did not decode the value, it was dropped or failed")]
+    Aborted,
+}
+
+/// A call of a circuit in a [`Program`].
+#[derive(Debug)]
+struct Call {
+    circ: Arc<Circuit>,
+    inputs: Vec<ValueRef>,
+    outputs: Vec<ValueRef>,
+}
+
+/// A program of circuit executions.
+///
+/// Both parties must build the same program, with values allocated in the same order.
+#[derive(Debug, Default)]
+pub struct Program {
+    calls: Vec<Call>,
+    decodes: Vec<(ValueRef, oneshot::Sender<Value>)>,
+}
+
+impl Program {
+    /// Creates a new empty program.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a call of a circuit to the program.
+    ///
+    /// Calls may be added in any order, they are executed after the calls which compute their
+    /// inputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to call.
+    /// * `inputs` - The inputs to the circuit.
+    /// * `outputs` - The outputs of the circuit.
+    pub fn call(
+        &mut self,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+    ) -> &mut Self {
+        self.calls.push(Call {
+            circ,
+            inputs: inputs.to_vec(),
+            outputs: outputs.to_vec(),
+        });
+        self
+    }
+
+    /// Decodes a value, revealing it to both parties.
+    ///
+    /// Returns a future which resolves to the value once the program has computed and decoded
+    /// it.
+    pub fn decode(&mut self, value: &ValueRef) -> Output {
+        let (sender, receiver) = oneshot::channel();
+        self.decodes.push((value.clone(), sender));
+        Output(receiver)
+    }
+
+    /// Runs the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread` - The VM thread to run the program on.
+    pub async fn run<T>(self, thread: &mut T) -> Result<(), ProgramError>
+    where
+        T: Load + Execute + Decode + Send,
+    {
+        let Program { calls, decodes } = self;
+
+        let producers = producers(&calls)?;
+        let order = schedule(&calls, &producers)?;
+
+        // Values which are not computed by a call are inputs of the program.
+        let mut inputs: Vec<ValueRef> = Vec::new();
+        for input in calls.iter().flat_map(|call| call.inputs.iter()) {
+            if input.iter().all(|id| !producers.contains_key(id)) && !inputs.contains(input) {
+                inputs.push(input.clone());
+            }
+        }
+
+        // Decode each value after the last call which computes it.
+        let mut decodes_after: HashMap<Option<usize>, Vec<(ValueRef, oneshot::Sender<Value>)>> =
+            HashMap::new();
+        for (value, sender) in decodes {
+            let last = value
+                .iter()
+                .filter_map(|id| producers.get(id))
+                .max_by_key(|idx| order.iter().position(|i| i == *idx))
+                .copied();
+            decodes_after.entry(last).or_default().push((value, sender));
+        }
+
+        // Transfer the encodings of the inputs.
+        thread.commit(&inputs).await?;
+        decode(thread, decodes_after.remove(&None)).await?;
+
+        // Garble every call ahead of execution.
+        for &idx in &order {
+            let call = &calls[idx];
+            thread
+                .load(call.circ.clone(), &call.inputs, &call.outputs)
+                .await?;
+        }
+
+        for &idx in &order {
+            let call = &calls[idx];
+            thread
+                .execute(call.circ.clone(), &call.inputs, &call.outputs)
+                .await?;
+
+            decode(thread, decodes_after.remove(&Some(idx))).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A future which resolves to a value decoded by a [`Program`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Output(oneshot::Receiver<Value>);
+
+impl Future for Output {
+    type Output = Result<Value, ProgramError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0)
+            .poll(cx)
+            .map_err(|_| ProgramError::Aborted)
+    }
+}
+
+/// Decodes the provided values, sending them to their outputs.
+async fn decode<T: Decode + Send>(
+    thread: &mut T,
+    decodes: Option<Vec<(ValueRef, oneshot::Sender<Value>)>>,
+) -> Result<(), ProgramError> {
+    let Some(decodes) = decodes else {
+        return Ok(());
+    };
+
+    let (values, senders): (Vec<_>, Vec<_>) = decodes.into_iter().unzip();
+    let decoded = thread.decode(&values).await?;
+
+    for (value, sender) in decoded.into_iter().zip(senders) {
+        // The output may have been dropped, which is fine.
+        _ = sender.send(value);
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the call which computes each value.
+fn producers(calls: &[Call]) -> Result<HashMap<ValueId, usize>, ProgramError> {
+    let mut producers = HashMap::new();
+    for (idx, call) in calls.iter().enumerate() {
+        for id in call.outputs.iter().flat_map(|output| output.iter()) {
+            if producers.insert(id.clone(), idx).is_some() {
+                return Err(ProgramError::DuplicateOutput(id.clone()));
+            }
+        }
+    }
+
+    Ok(producers)
+}
+
+/// Returns the order in which to execute the calls, such that every call is executed after the
+/// calls which compute its inputs.
+///
+/// Calls which are independent are executed in the order they were added.
+fn schedule(
+    calls: &[Call],
+    producers: &HashMap<ValueId, usize>,
+) -> Result<Vec<usize>, ProgramError> {
+    let mut dependents = vec![Vec::new(); calls.len()];
+    let mut pending = vec![0usize; calls.len()];
+    for (idx, call) in calls.iter().enumerate() {
+        let mut deps: Vec<usize> = call
+            .inputs
+            .iter()
+            .flat_map(|input| input.iter())
+            .filter_map(|id| producers.get(id).copied())
+            .collect();
+        deps.sort_unstable();
+        deps.dedup();
+
+        for dep in deps {
+            dependents[dep].push(idx);
+            pending[idx] += 1;
+        }
+    }
+
+    let mut ready: VecDeque<usize> = (0..calls.len()).filter(|idx| pending[*idx] == 0).collect();
+    let mut order = Vec::with_capacity(calls.len());
+    while let Some(idx) = ready.pop_front() {
+        order.push(idx);
+        for &dependent in &dependents[idx] {
+            pending[dependent] -= 1;
+            if pending[dependent] == 0 {
+                ready.push_back(dependent);
+            }
+        }
+    }
+
+    if order.len() != calls.len() {
+        return Err(ProgramError::Cycle);
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{ops::WrappingAdd, CircuitBuilder};
+
+    use super::*;
+
+    fn adder_circ() -> Arc<Circuit> {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+
+        builder.add_output(a.wrapping_add(b));
+
+        Arc::new(builder.build().unwrap())
+    }
+
+    fn value(id: &str) -> ValueRef {
+        ValueRef::Value {
+            id: ValueId::new(id),
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut program = Program::new();
+        program
+            .call(adder_circ(), &[value("d"), value("a")], &[value("e")])
+            .call(adder_circ(), &[value("a"), value("b")], &[value("c")])
+            .call(adder_circ(), &[value("c"), value("c")], &[value("d")]);
+
+        let producers = producers(&program.calls).unwrap();
+
+        assert_eq!(schedule(&program.calls, &producers).unwrap(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_schedule_cycle() {
+        let mut program = Program::new();
+        program
+            .call(adder_circ(), &[value("a"), value("d")], &[value("c")])
+            .call(adder_circ(), &[value("c"), value("b")], &[value("d")]);
+
+        let producers = producers(&program.calls).unwrap();
+
+        assert!(matches!(
+            schedule(&program.calls, &producers).unwrap_err(),
+            ProgramError::Cycle
+        ));
+    }
+
+    #[test]
+    fn test_producers_duplicate_output() {
+        let mut program = Program::new();
+        program
+            .call(adder_circ(), &[value("a"), value("b")], &[value("c")])
+            .call(adder_circ(), &[value("b"), value("a")], &[value("c")]);
+
+        assert!(matches!(
+            producers(&program.calls).unwrap_err(),
+            ProgramError::DuplicateOutput(_)
+        ));
+    }
+}
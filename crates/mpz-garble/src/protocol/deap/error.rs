@@ -1,6 +1,9 @@
 use mpz_garble_core::ValueError;
 
-use crate::{value::ValueRef, DecodeError, ExecutionError, LoadError, ProveError, VerifyError};
+use crate::{
+    value::{ValueDiagnostics, ValueRef},
+    DecodeError, ExecutionError, LoadError, ProveError, VerifyError,
+};
 
 /// Errors that can occur during the DEAP protocol.
 #[derive(Debug, thiserror::Error)]
@@ -20,8 +23,12 @@ pub enum DEAPError {
     ValueError(#[from] ValueError),
     #[error("value does not exist: {0:?}")]
     ValueDoesNotExist(ValueRef),
-    #[error("missing encoding for value: {0:?}")]
-    MissingEncoding(ValueRef),
+    #[error("missing encoding for value {0}")]
+    MissingEncoding(Box<ValueDiagnostics>),
+    #[error("not authorized to decode value {0}")]
+    UnauthorizedDecode(Box<ValueDiagnostics>),
+    #[error("incorrect number of values: expected {expected}, got {actual}")]
+    IncorrectValueCount { expected: usize, actual: usize },
     #[error(transparent)]
     FinalizationError(#[from] FinalizationError),
 }
@@ -21,21 +21,23 @@ use mpz_circuits::{
 };
 use mpz_common::{try_join, Context, Counter, ThreadId};
 use mpz_core::{
-    commit::{Decommitment, HashCommit},
+    commit::{verify_batch, Decommitment, Nonce},
     hash::{Hash, SecureHash},
 };
-use mpz_garble_core::EqualityCheck;
-use rand::thread_rng;
+use mpz_garble_core::{encoding_state, EncodedValue, EqualityCheck};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serio::{stream::IoStreamExt, SinkExt};
 
 use crate::{
     config::{Role, Visibility},
+    decoding::DecodePolicy,
     evaluator::{Evaluator, EvaluatorConfigBuilder},
     generator::{Generator, GeneratorConfigBuilder},
-    internal_circuits::{build_otp_circuit, build_otp_shared_circuit},
-    memory::ValueMemory,
+    internal_circuits::{build_otp_circuit, build_otp_shared_circuit, build_refresh_circuit},
+    memory::{DebugNames, ValueMemory},
     ot::{OTReceiveEncoding, OTSendEncoding, OTVerifyEncoding},
-    value::ValueRef,
+    value::{Phase, ValueRef},
 };
 
 pub use error::{DEAPError, PeerEncodingsError};
@@ -43,6 +45,11 @@ pub use vm::{DEAPThread, PeerEncodings};
 
 use self::error::FinalizationError;
 
+/// Domain of the equality check commitments.
+const EQ_CHECK_DOMAIN: &str = "mpz-garble 2023 deap equality check";
+/// Domain of the output proof commitments.
+const PROOF_DOMAIN: &str = "mpz-garble 2023 deap output proof";
+
 /// The DEAP protocol.
 #[derive(Debug)]
 pub struct DEAP {
@@ -50,13 +57,17 @@ pub struct DEAP {
     gen: Generator,
     ev: Evaluator,
     state: Mutex<State>,
+    debug_names: DebugNames,
+    decode_policy: DecodePolicy,
     finalized: bool,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct State {
     memory: ValueMemory,
     logs: HashMap<ThreadId, ThreadLog>,
+    /// RNG used to sample one-time pads and commitment nonces.
+    rng: ChaCha20Rng,
 }
 
 #[derive(Debug, Default)]
@@ -104,6 +115,28 @@ struct FinalizedState {
 impl DEAP {
     /// Creates a new DEAP protocol instance.
     pub fn new(role: Role, encoder_seed: [u8; 32]) -> Self {
+        Self::new_with_rng(
+            role,
+            encoder_seed,
+            ChaCha20Rng::from_seed(thread_rng().gen()),
+        )
+    }
+
+    /// Creates a new DEAP protocol instance whose randomness is derived from the provided seeds.
+    ///
+    /// Two sessions created with the same seeds, which perform the same operations, send the
+    /// same messages. This is useful for regression tests against recorded transcripts.
+    ///
+    /// # Warning
+    ///
+    /// This must not be used outside of tests, the one-time pads and commitment nonces of the
+    /// session are predictable from `rng_seed`.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_seeded(role: Role, encoder_seed: [u8; 32], rng_seed: [u8; 32]) -> Self {
+        Self::new_with_rng(role, encoder_seed, ChaCha20Rng::from_seed(rng_seed))
+    }
+
+    fn new_with_rng(role: Role, encoder_seed: [u8; 32], rng: ChaCha20Rng) -> Self {
         let mut gen_config_builder = GeneratorConfigBuilder::default();
         let mut ev_config_builder = EvaluatorConfigBuilder::default();
 
@@ -130,15 +163,62 @@ impl DEAP {
             role,
             gen,
             ev,
-            state: Mutex::new(State::default()),
+            state: Mutex::new(State {
+                memory: ValueMemory::default(),
+                logs: HashMap::new(),
+                rng,
+            }),
+            debug_names: DebugNames::default(),
+            decode_policy: DecodePolicy::default(),
             finalized: false,
         }
     }
 
+    /// Sets the registry used to include the names of values in errors.
+    pub(crate) fn set_debug_names(&mut self, debug_names: DebugNames) {
+        self.state
+            .get_mut()
+            .unwrap()
+            .memory
+            .set_debug_names(debug_names.clone());
+        self.gen.set_debug_names(debug_names.clone());
+        self.ev.set_debug_names(debug_names.clone());
+        self.debug_names = debug_names;
+    }
+
+    /// Sets the policy of which values may be decoded.
+    ///
+    /// Each party garbles one of the circuits and evaluates the other, so in DEAP the policy
+    /// applies to both parties: [`decode`](Self::decode), [`decode_single`](Self::decode_single),
+    /// `decode_private` and `decode_blind` reveal a value to at least one of the parties, and
+    /// refuse values which are not tagged with [`DecodeAuth::Both`](crate::DecodeAuth::Both).
+    /// `decode_shared` reveals the value to neither party and is not restricted.
+    ///
+    /// Both parties must be configured with the same policy, see [`DecodePolicy`].
+    pub fn set_decode_policy(&mut self, decode_policy: DecodePolicy) {
+        self.decode_policy = decode_policy;
+    }
+
+    /// Checks that the values may be revealed according to the decode policy.
+    fn check_decode_policy(&self, values: &[ValueRef]) -> Result<(), DEAPError> {
+        self.decode_policy.check_evaluator(values).map_err(|value| {
+            DEAPError::UnauthorizedDecode(self.debug_names.diagnose(
+                &value,
+                None,
+                Some(Phase::Decode),
+            ))
+        })
+    }
+
     fn state(&self) -> impl DerefMut<Target = State> + '_ {
         self.state.lock().unwrap()
     }
 
+    /// Returns a nonce for a commitment.
+    fn nonce(&self) -> Nonce {
+        Nonce::random_with_rng(&mut self.state().rng)
+    }
+
     /// Commits the provided input values.
     ///
     /// Values which are already committed are ignored.
@@ -358,6 +438,164 @@ impl DEAP {
         Ok(())
     }
 
+    /// Executes a circuit with a single garbled circuit, where the provided party is the
+    /// generator and the other party is the evaluator.
+    ///
+    /// The parties may alternate roles between executions to balance their load, reusing the OT
+    /// setup in both directions. The outputs can only be used as inputs to executions with the
+    /// same generator, and must be decoded with [`decode_single`](Self::decode_single).
+    ///
+    /// # Notes
+    ///
+    /// Circuits garbled by the follower are verified by the leader during
+    /// [`finalize`](Self::finalize). Circuits garbled by the leader are not verified, as
+    /// with [`execute_verify`](Self::execute_verify).
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit to execute.
+    /// * `inputs` - The inputs to the circuit.
+    /// * `outputs` - The outputs of the circuit.
+    /// * `generator` - The party which garbles the circuit.
+    /// * `ot_send` - The OT sender.
+    /// * `ot_recv` - The OT receiver.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(fields(role = %self.role, thread = %ctx.id()), skip_all)]
+    pub async fn execute_single<Ctx, OTS, OTR>(
+        &self,
+        ctx: &mut Ctx,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        generator: Role,
+        ot_send: &mut OTS,
+        ot_recv: &mut OTR,
+    ) -> Result<(), DEAPError>
+    where
+        Ctx: Context,
+        OTS: OTSendEncoding<Ctx> + Send,
+        OTR: OTReceiveEncoding<Ctx> + Send,
+    {
+        let assigned_values = self.state().memory.drain_assigned(inputs);
+
+        if self.role == generator {
+            self.gen
+                .setup_assigned_values(ctx, &assigned_values, ot_send)
+                .await?;
+
+            self.gen.generate(ctx, circ, inputs, outputs, false).await?;
+        } else {
+            self.ev
+                .setup_assigned_values(ctx, &assigned_values, ot_recv)
+                .await?;
+
+            self.ev.evaluate(ctx, circ, inputs, outputs).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the outputs of [`execute_single`](Self::execute_single), revealing the plaintext
+    /// values to both parties.
+    ///
+    /// The generator sends the decodings to the evaluator, and the evaluator sends the active
+    /// encodings back, which authenticates the values for the generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to decode.
+    /// * `generator` - The party which garbled the circuits computing the values.
+    #[tracing::instrument(fields(role = %self.role, thread = %ctx.id()), skip_all)]
+    pub async fn decode_single<Ctx>(
+        &self,
+        ctx: &mut Ctx,
+        values: &[ValueRef],
+        generator: Role,
+    ) -> Result<Vec<Value>, DEAPError>
+    where
+        Ctx: Context,
+    {
+        self.check_decode_policy(values)?;
+
+        if self.role == generator {
+            let full = self.gen.get_encodings(values)?;
+
+            self.gen.decode(ctx, values).await?;
+
+            let active: Vec<EncodedValue<encoding_state::Active>> =
+                ctx.io_mut().expect_next().await?;
+
+            if active.len() != full.len() {
+                return Err(DEAPError::IncorrectValueCount {
+                    expected: full.len(),
+                    actual: active.len(),
+                });
+            }
+
+            Ok(active
+                .into_iter()
+                .zip(full)
+                .map(|(active, full)| full.decode(&active))
+                .collect::<Result<Vec<_>, _>>()?)
+        } else {
+            let decoded = self.ev.decode(ctx, values).await?;
+
+            ctx.io_mut().send(self.ev.get_encodings(values)?).await?;
+
+            Ok(decoded)
+        }
+    }
+
+    /// Refreshes the encodings of the provided values.
+    ///
+    /// Returns references to new values which are equal to the provided values, but have fresh
+    /// encodings. This is done by executing an identity circuit in which every output bit is
+    /// computed with an AND gate.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to refresh.
+    /// * `ot_send` - The OT sender.
+    /// * `ot_recv` - The OT receiver.
+    #[tracing::instrument(fields(role = %self.role, thread = %ctx.id()), skip_all)]
+    pub async fn refresh<Ctx, OTS, OTR>(
+        &self,
+        ctx: &mut Ctx,
+        values: &[ValueRef],
+        ot_send: &mut OTS,
+        ot_recv: &mut OTR,
+    ) -> Result<Vec<ValueRef>, DEAPError>
+    where
+        Ctx: Context,
+        OTS: OTSendEncoding<Ctx> + Send,
+        OTR: OTReceiveEncoding<Ctx> + Send,
+    {
+        let id = self.state().log(ctx.id()).operation_counter.next();
+        let (typs, refreshed_refs): (Vec<_>, Vec<_>) = {
+            let mut state = self.state();
+
+            values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    let typ = state.memory.get_value_type(value);
+                    let refreshed_ref = state
+                        .memory
+                        .new_output(&format!("{}/{id}/{idx}/refresh", ctx.id()), typ.clone())
+                        .expect("refresh id is unique");
+                    (typ, refreshed_ref)
+                })
+                .unzip()
+        };
+
+        let circ = build_refresh_circuit(&typs);
+
+        self.execute(ctx, circ, values, &refreshed_refs, ot_send, ot_recv)
+            .await?;
+
+        Ok(refreshed_refs)
+    }
+
     /// Proves the output of a circuit to the other party.
     ///
     /// # Notes
@@ -478,7 +716,8 @@ impl DEAP {
         let encoded_values = self.ev.get_encodings(values)?;
 
         let encoding_digest = encoded_values.hash();
-        let (decommitment, commitment) = encoding_digest.hash_commit();
+        let decommitment = Decommitment::new_with_nonce(encoding_digest, self.nonce());
+        let commitment = decommitment.commit_with_domain(PROOF_DOMAIN);
 
         // Store output proof decommitment until finalization
         self.state()
@@ -556,24 +795,45 @@ impl DEAP {
         ctx: &mut Ctx,
         values: &[ValueRef],
     ) -> Result<Vec<Value>, DEAPError>
+    where
+        Ctx: Context,
+    {
+        self.check_decode_policy(values)?;
+        self.decode_unchecked(ctx, values).await
+    }
+
+    /// Decodes values without checking the decode policy, used for values which are masked.
+    async fn decode_unchecked<Ctx>(
+        &self,
+        ctx: &mut Ctx,
+        values: &[ValueRef],
+    ) -> Result<Vec<Value>, DEAPError>
     where
         Ctx: Context,
     {
         let full = values
             .iter()
             .map(|value| {
-                self.gen
-                    .get_encoding(value)
-                    .ok_or(DEAPError::MissingEncoding(value.clone()))
+                self.gen.get_encoding(value).ok_or_else(|| {
+                    DEAPError::MissingEncoding(self.debug_names.diagnose(
+                        value,
+                        None,
+                        Some(Phase::Decode),
+                    ))
+                })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
         let active = values
             .iter()
             .map(|value| {
-                self.ev
-                    .get_encoding(value)
-                    .ok_or(DEAPError::MissingEncoding(value.clone()))
+                self.ev.get_encoding(value).ok_or_else(|| {
+                    DEAPError::MissingEncoding(self.debug_names.diagnose(
+                        value,
+                        None,
+                        Some(Phase::Decode),
+                    ))
+                })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -609,7 +869,8 @@ impl DEAP {
 
         let output = match self.role {
             Role::Leader => {
-                let (decommitment, commit) = eq_check.hash_commit();
+                let decommitment = Decommitment::new_with_nonce(eq_check, self.nonce());
+                let commit = decommitment.commit_with_domain(EQ_CHECK_DOMAIN);
 
                 // Store equality check decommitment until finalization
                 self.state()
@@ -664,6 +925,8 @@ impl DEAP {
         OTS: OTSendEncoding<Ctx> + Send,
         OTR: OTReceiveEncoding<Ctx> + Send,
     {
+        self.check_decode_policy(values)?;
+
         let id = self.state().log(ctx.id()).operation_counter.next();
         let (((otp_refs, otp_typs), otp_values), mask_refs): (((Vec<_>, Vec<_>), Vec<_>), Vec<_>) = {
             let mut state = self.state();
@@ -697,7 +960,7 @@ impl DEAP {
             .await?;
 
         // Decode masked values
-        let masked_values = self.decode(ctx, &mask_refs).await?;
+        let masked_values = self.decode_unchecked(ctx, &mask_refs).await?;
 
         // Remove OTPs, returning plaintext values
         Ok(masked_values
@@ -720,6 +983,8 @@ impl DEAP {
         OTS: OTSendEncoding<Ctx> + Send,
         OTR: OTReceiveEncoding<Ctx> + Send,
     {
+        self.check_decode_policy(values)?;
+
         let id = self.state().log(ctx.id()).operation_counter.next();
         let ((otp_refs, otp_typs), mask_refs): ((Vec<_>, Vec<_>), Vec<_>) = {
             let mut state = self.state();
@@ -752,7 +1017,7 @@ impl DEAP {
             .await?;
 
         // Discard masked values
-        _ = self.decode(ctx, &mask_refs).await?;
+        _ = self.decode_unchecked(ctx, &mask_refs).await?;
 
         Ok(())
     }
@@ -822,7 +1087,7 @@ impl DEAP {
             .await?;
 
         // Decode masked values
-        let masked_values = self.decode(ctx, &mask_refs).await?;
+        let masked_values = self.decode_unchecked(ctx, &mask_refs).await?;
 
         match self.role {
             Role::Leader => {
@@ -915,27 +1180,27 @@ impl DEAP {
                     ctx.io_mut().expect_next().await?;
 
                 // Verify all equality checks.
-                for (decommitment, (expected_check, commitment)) in
-                    eq_decommitments.iter().zip(eq_commitments.iter())
-                {
-                    decommitment
-                        .verify(commitment)
-                        .map_err(FinalizationError::from)?;
+                let (expected_checks, commitments): (Vec<_>, Vec<_>) =
+                    eq_commitments.into_iter().unzip();
+                verify_batch(&eq_decommitments, &commitments, EQ_CHECK_DOMAIN)
+                    .map_err(FinalizationError::from)?;
 
-                    if decommitment.data() != expected_check {
+                for (decommitment, expected_check) in eq_decommitments.iter().zip(expected_checks) {
+                    if decommitment.data() != &expected_check {
                         return Err(FinalizationError::InvalidEqualityCheck)?;
                     }
                 }
 
                 // Verify all proofs.
-                for (decommitment, (expected_digest, commitment)) in
-                    proof_decommitments.iter().zip(proof_commitments.iter())
-                {
-                    decommitment
-                        .verify(commitment)
-                        .map_err(FinalizationError::from)?;
+                let (expected_digests, commitments): (Vec<_>, Vec<_>) =
+                    proof_commitments.into_iter().unzip();
+                verify_batch(&proof_decommitments, &commitments, PROOF_DOMAIN)
+                    .map_err(FinalizationError::from)?;
 
-                    if decommitment.data() != expected_digest {
+                for (decommitment, expected_digest) in
+                    proof_decommitments.iter().zip(expected_digests)
+                {
+                    if decommitment.data() != &expected_digest {
                         return Err(FinalizationError::InvalidProof)?;
                     }
                 }
@@ -958,7 +1223,7 @@ impl State {
 
     pub(crate) fn new_private_otp(&mut self, id: &str, value_ref: &ValueRef) -> (ValueRef, Value) {
         let typ = self.memory.get_value_type(value_ref);
-        let value = Value::random(&mut thread_rng(), &typ);
+        let value = Value::random(&mut self.rng, &typ);
 
         let value_ref = self
             .memory
@@ -1020,11 +1285,16 @@ impl State {
 #[cfg(test)]
 mod tests {
     use mpz_circuits::{circuits::AES128, ops::WrappingAdd, CircuitBuilder};
-    use mpz_common::executor::test_st_executor;
+    use mpz_common::{
+        executor::{test_st_executor, test_tamper_executor, Deviation, STExecutor},
+        replay::{Recorder, Recording},
+    };
     use mpz_core::Block;
     use mpz_ot::ideal::ot::ideal_ot;
+    use serio::codec::{Bincode, Codec};
+    use tokio_util::compat::TokioAsyncReadCompatExt;
 
-    use crate::Memory;
+    use crate::{DecodeAuth, Memory, MemoryError};
 
     use super::*;
 
@@ -1041,6 +1311,30 @@ mod tests {
         Arc::new(builder.build().unwrap())
     }
 
+    #[test]
+    fn test_deap_scope() {
+        let deap = DEAP::new(Role::Leader, [42u8; 32]);
+
+        let handshake = deap.scope("handshake");
+        let key_ref = handshake.new_private_input::<[u8; 16]>("key").unwrap();
+        let tag_ref = handshake.scope("record").new_output::<u8>("tag").unwrap();
+
+        assert_eq!(deap.get_value("handshake/key"), Some(key_ref.clone()));
+        assert_eq!(handshake.get_value("key"), Some(key_ref));
+        assert_eq!(deap.get_value("handshake/record/tag"), Some(tag_ref));
+
+        // The same ID in another scope does not collide.
+        deap.scope("record")
+            .new_private_input::<[u8; 16]>("key")
+            .unwrap();
+
+        // A manually prefixed ID collides with the scoped ID.
+        let err = deap
+            .new_private_input::<[u8; 16]>("handshake/key")
+            .unwrap_err();
+        assert!(matches!(err, MemoryError::DuplicateValueId(_)));
+    }
+
     #[tokio::test]
     async fn test_deap() {
         let (mut ctx_a, mut ctx_b) = test_st_executor(8);
@@ -1124,7 +1418,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_deap_commit() {
+    async fn test_deap_signed() {
         let (mut ctx_a, mut ctx_b) = test_st_executor(8);
         let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
         let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
@@ -1132,40 +1426,41 @@ mod tests {
         let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
         let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
 
-        let key = [42u8; 16];
-        let msg = [69u8; 16];
+        let circ = {
+            let builder = CircuitBuilder::new();
+
+            let a = builder.add_input::<i32>();
+            let b = builder.add_input::<i32>();
+
+            let c = a.wrapping_add(b);
+
+            builder.add_output(c);
+
+            Arc::new(builder.build().unwrap())
+        };
 
         let leader_fut = {
-            let key_ref = leader.new_private_input::<[u8; 16]>("key").unwrap();
-            let msg_ref = leader.new_blind_input::<[u8; 16]>("msg").unwrap();
-            let ciphertext_ref = leader.new_output::<[u8; 16]>("ciphertext").unwrap();
+            let a_ref = leader.new_private_input::<i32>("a").unwrap();
+            let b_ref = leader.new_blind_input::<i32>("b").unwrap();
+            let c_ref = leader.new_output::<i32>("c").unwrap();
 
-            leader.assign(&key_ref, key).unwrap();
+            leader.assign(&a_ref, -7i32).unwrap();
 
+            let circ = circ.clone();
             async move {
-                leader
-                    .commit(
-                        &mut ctx_a,
-                        &[key_ref.clone(), msg_ref.clone()],
-                        &mut leader_ot_send,
-                        &mut leader_ot_recv,
-                    )
-                    .await
-                    .unwrap();
-
                 leader
                     .execute(
                         &mut ctx_a,
-                        AES128.clone(),
-                        &[key_ref, msg_ref],
-                        &[ciphertext_ref.clone()],
+                        circ,
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
                         &mut leader_ot_send,
                         &mut leader_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                let outputs = leader.decode(&mut ctx_a, &[ciphertext_ref]).await.unwrap();
+                let outputs = leader.decode(&mut ctx_a, &[c_ref]).await.unwrap();
 
                 leader
                     .finalize(&mut ctx_a, &mut leader_ot_recv)
@@ -1177,39 +1472,26 @@ mod tests {
         };
 
         let follower_fut = {
-            let key_ref = follower.new_blind_input::<[u8; 16]>("key").unwrap();
-            let msg_ref = follower.new_private_input::<[u8; 16]>("msg").unwrap();
-            let ciphertext_ref = follower.new_output::<[u8; 16]>("ciphertext").unwrap();
+            let a_ref = follower.new_blind_input::<i32>("a").unwrap();
+            let b_ref = follower.new_private_input::<i32>("b").unwrap();
+            let c_ref = follower.new_output::<i32>("c").unwrap();
 
-            follower.assign(&msg_ref, msg).unwrap();
+            follower.assign(&b_ref, 3i32).unwrap();
 
             async move {
-                follower
-                    .commit(
-                        &mut ctx_b,
-                        &[key_ref.clone(), msg_ref.clone()],
-                        &mut follower_ot_send,
-                        &mut follower_ot_recv,
-                    )
-                    .await
-                    .unwrap();
-
                 follower
                     .execute(
                         &mut ctx_b,
-                        AES128.clone(),
-                        &[key_ref, msg_ref],
-                        &[ciphertext_ref.clone()],
+                        circ,
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
                         &mut follower_ot_send,
                         &mut follower_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                let outputs = follower
-                    .decode(&mut ctx_b, &[ciphertext_ref])
-                    .await
-                    .unwrap();
+                let outputs = follower.decode(&mut ctx_b, &[c_ref]).await.unwrap();
 
                 follower
                     .finalize(&mut ctx_b, &mut follower_ot_recv)
@@ -1222,11 +1504,12 @@ mod tests {
 
         let (leader_output, follower_output) = tokio::join!(leader_fut, follower_fut);
 
+        assert_eq!(leader_output, vec![Value::I32(-4)]);
         assert_eq!(leader_output, follower_output);
     }
 
     #[tokio::test]
-    async fn test_deap_load() {
+    async fn test_deap_refresh() {
         let (mut ctx_a, mut ctx_b) = test_st_executor(8);
         let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
         let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
@@ -1234,40 +1517,45 @@ mod tests {
         let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
         let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
 
-        let key = [42u8; 16];
-        let msg = [69u8; 16];
+        let a = 1u8;
+        let b = 2u8;
 
         let leader_fut = {
-            let key_ref = leader.new_private_input::<[u8; 16]>("key").unwrap();
-            let msg_ref = leader.new_blind_input::<[u8; 16]>("msg").unwrap();
-            let ciphertext_ref = leader.new_output::<[u8; 16]>("ciphertext").unwrap();
+            let a_ref = leader.new_private_input::<u8>("a").unwrap();
+            let b_ref = leader.new_blind_input::<u8>("b").unwrap();
+            let c_ref = leader.new_output::<u8>("c").unwrap();
+
+            leader.assign(&a_ref, a).unwrap();
 
             async move {
                 leader
-                    .load(
+                    .execute(
                         &mut ctx_a,
-                        AES128.clone(),
-                        &[key_ref.clone(), msg_ref.clone()],
-                        &[ciphertext_ref.clone()],
+                        adder_circ(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                leader.assign(&key_ref, key).unwrap();
-
-                leader
-                    .execute(
+                let refreshed = leader
+                    .refresh(
                         &mut ctx_a,
-                        AES128.clone(),
-                        &[key_ref, msg_ref],
-                        &[ciphertext_ref.clone()],
+                        std::slice::from_ref(&c_ref),
                         &mut leader_ot_send,
                         &mut leader_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                let outputs = leader.decode(&mut ctx_a, &[ciphertext_ref]).await.unwrap();
+                assert_ne!(
+                    leader.gen.get_encoding(&c_ref).unwrap(),
+                    leader.gen.get_encoding(&refreshed[0]).unwrap()
+                );
+
+                let outputs = leader.decode(&mut ctx_a, &refreshed).await.unwrap();
 
                 leader
                     .finalize(&mut ctx_a, &mut leader_ot_recv)
@@ -1279,39 +1567,36 @@ mod tests {
         };
 
         let follower_fut = {
-            let key_ref = follower.new_blind_input::<[u8; 16]>("key").unwrap();
-            let msg_ref = follower.new_private_input::<[u8; 16]>("msg").unwrap();
-            let ciphertext_ref = follower.new_output::<[u8; 16]>("ciphertext").unwrap();
+            let a_ref = follower.new_blind_input::<u8>("a").unwrap();
+            let b_ref = follower.new_private_input::<u8>("b").unwrap();
+            let c_ref = follower.new_output::<u8>("c").unwrap();
+
+            follower.assign(&b_ref, b).unwrap();
 
             async move {
                 follower
-                    .load(
+                    .execute(
                         &mut ctx_b,
-                        AES128.clone(),
-                        &[key_ref.clone(), msg_ref.clone()],
-                        &[ciphertext_ref.clone()],
+                        adder_circ(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                follower.assign(&msg_ref, msg).unwrap();
-
-                follower
-                    .execute(
+                let refreshed = follower
+                    .refresh(
                         &mut ctx_b,
-                        AES128.clone(),
-                        &[key_ref, msg_ref],
-                        &[ciphertext_ref.clone()],
+                        &[c_ref],
                         &mut follower_ot_send,
                         &mut follower_ot_recv,
                     )
                     .await
                     .unwrap();
 
-                let outputs = follower
-                    .decode(&mut ctx_b, &[ciphertext_ref])
-                    .await
-                    .unwrap();
+                let outputs = follower.decode(&mut ctx_b, &refreshed).await.unwrap();
 
                 follower
                     .finalize(&mut ctx_b, &mut follower_ot_recv)
@@ -1325,11 +1610,11 @@ mod tests {
         let (leader_output, follower_output) = tokio::join!(leader_fut, follower_fut);
 
         assert_eq!(leader_output, follower_output);
+        assert_eq!(leader_output, vec![Value::from(a + b)]);
     }
 
     #[tokio::test]
-    async fn test_deap_decode_private() {
-        tracing_subscriber::fmt::init();
+    async fn test_deap_role_swap() {
         let (mut ctx_a, mut ctx_b) = test_st_executor(8);
         let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
         let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
@@ -1337,15 +1622,332 @@ mod tests {
         let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
         let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
 
-        let circ = adder_circ();
-
         let a = 1u8;
         let b = 2u8;
-        let c: Value = (a + b).into();
 
         let leader_fut = {
-            let circ = circ.clone();
-            let a_ref = leader.new_private_input::<u8>("a").unwrap();
+            let refs = [Role::Leader, Role::Follower].map(|generator| {
+                let a_ref = leader
+                    .new_private_input::<u8>(&format!("{generator}/a"))
+                    .unwrap();
+                let b_ref = leader
+                    .new_blind_input::<u8>(&format!("{generator}/b"))
+                    .unwrap();
+                let c_ref = leader.new_output::<u8>(&format!("{generator}/c")).unwrap();
+                leader.assign(&a_ref, a).unwrap();
+                (generator, a_ref, b_ref, c_ref)
+            });
+
+            async move {
+                let mut outputs = Vec::new();
+                for (generator, a_ref, b_ref, c_ref) in refs {
+                    leader
+                        .execute_single(
+                            &mut ctx_a,
+                            adder_circ(),
+                            &[a_ref, b_ref],
+                            std::slice::from_ref(&c_ref),
+                            generator,
+                            &mut leader_ot_send,
+                            &mut leader_ot_recv,
+                        )
+                        .await
+                        .unwrap();
+
+                    outputs.extend(
+                        leader
+                            .decode_single(&mut ctx_a, &[c_ref], generator)
+                            .await
+                            .unwrap(),
+                    );
+                }
+
+                leader
+                    .finalize(&mut ctx_a, &mut leader_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let follower_fut = {
+            let refs = [Role::Leader, Role::Follower].map(|generator| {
+                let a_ref = follower
+                    .new_blind_input::<u8>(&format!("{generator}/a"))
+                    .unwrap();
+                let b_ref = follower
+                    .new_private_input::<u8>(&format!("{generator}/b"))
+                    .unwrap();
+                let c_ref = follower
+                    .new_output::<u8>(&format!("{generator}/c"))
+                    .unwrap();
+                follower.assign(&b_ref, b).unwrap();
+                (generator, a_ref, b_ref, c_ref)
+            });
+
+            async move {
+                let mut outputs = Vec::new();
+                for (generator, a_ref, b_ref, c_ref) in refs {
+                    follower
+                        .execute_single(
+                            &mut ctx_b,
+                            adder_circ(),
+                            &[a_ref, b_ref],
+                            std::slice::from_ref(&c_ref),
+                            generator,
+                            &mut follower_ot_send,
+                            &mut follower_ot_recv,
+                        )
+                        .await
+                        .unwrap();
+
+                    outputs.extend(
+                        follower
+                            .decode_single(&mut ctx_b, &[c_ref], generator)
+                            .await
+                            .unwrap(),
+                    );
+                }
+
+                follower
+                    .finalize(&mut ctx_b, &mut follower_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let (leader_output, follower_output) = tokio::join!(leader_fut, follower_fut);
+
+        assert_eq!(leader_output, follower_output);
+        assert_eq!(leader_output, vec![Value::from(a + b); 2]);
+    }
+
+    #[tokio::test]
+    async fn test_deap_commit() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
+        let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
+
+        let key = [42u8; 16];
+        let msg = [69u8; 16];
+
+        let leader_fut = {
+            let key_ref = leader.new_private_input::<[u8; 16]>("key").unwrap();
+            let msg_ref = leader.new_blind_input::<[u8; 16]>("msg").unwrap();
+            let ciphertext_ref = leader.new_output::<[u8; 16]>("ciphertext").unwrap();
+
+            leader.assign(&key_ref, key).unwrap();
+
+            async move {
+                leader
+                    .commit(
+                        &mut ctx_a,
+                        &[key_ref.clone(), msg_ref.clone()],
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                leader
+                    .execute(
+                        &mut ctx_a,
+                        AES128.clone(),
+                        &[key_ref, msg_ref],
+                        &[ciphertext_ref.clone()],
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let outputs = leader.decode(&mut ctx_a, &[ciphertext_ref]).await.unwrap();
+
+                leader
+                    .finalize(&mut ctx_a, &mut leader_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let follower_fut = {
+            let key_ref = follower.new_blind_input::<[u8; 16]>("key").unwrap();
+            let msg_ref = follower.new_private_input::<[u8; 16]>("msg").unwrap();
+            let ciphertext_ref = follower.new_output::<[u8; 16]>("ciphertext").unwrap();
+
+            follower.assign(&msg_ref, msg).unwrap();
+
+            async move {
+                follower
+                    .commit(
+                        &mut ctx_b,
+                        &[key_ref.clone(), msg_ref.clone()],
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                follower
+                    .execute(
+                        &mut ctx_b,
+                        AES128.clone(),
+                        &[key_ref, msg_ref],
+                        &[ciphertext_ref.clone()],
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let outputs = follower
+                    .decode(&mut ctx_b, &[ciphertext_ref])
+                    .await
+                    .unwrap();
+
+                follower
+                    .finalize(&mut ctx_b, &mut follower_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let (leader_output, follower_output) = tokio::join!(leader_fut, follower_fut);
+
+        assert_eq!(leader_output, follower_output);
+    }
+
+    #[tokio::test]
+    async fn test_deap_load() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
+        let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
+
+        let key = [42u8; 16];
+        let msg = [69u8; 16];
+
+        let leader_fut = {
+            let key_ref = leader.new_private_input::<[u8; 16]>("key").unwrap();
+            let msg_ref = leader.new_blind_input::<[u8; 16]>("msg").unwrap();
+            let ciphertext_ref = leader.new_output::<[u8; 16]>("ciphertext").unwrap();
+
+            async move {
+                leader
+                    .load(
+                        &mut ctx_a,
+                        AES128.clone(),
+                        &[key_ref.clone(), msg_ref.clone()],
+                        &[ciphertext_ref.clone()],
+                    )
+                    .await
+                    .unwrap();
+
+                leader.assign(&key_ref, key).unwrap();
+
+                leader
+                    .execute(
+                        &mut ctx_a,
+                        AES128.clone(),
+                        &[key_ref, msg_ref],
+                        &[ciphertext_ref.clone()],
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let outputs = leader.decode(&mut ctx_a, &[ciphertext_ref]).await.unwrap();
+
+                leader
+                    .finalize(&mut ctx_a, &mut leader_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let follower_fut = {
+            let key_ref = follower.new_blind_input::<[u8; 16]>("key").unwrap();
+            let msg_ref = follower.new_private_input::<[u8; 16]>("msg").unwrap();
+            let ciphertext_ref = follower.new_output::<[u8; 16]>("ciphertext").unwrap();
+
+            async move {
+                follower
+                    .load(
+                        &mut ctx_b,
+                        AES128.clone(),
+                        &[key_ref.clone(), msg_ref.clone()],
+                        &[ciphertext_ref.clone()],
+                    )
+                    .await
+                    .unwrap();
+
+                follower.assign(&msg_ref, msg).unwrap();
+
+                follower
+                    .execute(
+                        &mut ctx_b,
+                        AES128.clone(),
+                        &[key_ref, msg_ref],
+                        &[ciphertext_ref.clone()],
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let outputs = follower
+                    .decode(&mut ctx_b, &[ciphertext_ref])
+                    .await
+                    .unwrap();
+
+                follower
+                    .finalize(&mut ctx_b, &mut follower_ot_recv)
+                    .await
+                    .unwrap();
+
+                outputs
+            }
+        };
+
+        let (leader_output, follower_output) = tokio::join!(leader_fut, follower_fut);
+
+        assert_eq!(leader_output, follower_output);
+    }
+
+    #[tokio::test]
+    async fn test_deap_decode_private() {
+        tracing_subscriber::fmt::init();
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
+        let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
+
+        let circ = adder_circ();
+
+        let a = 1u8;
+        let b = 2u8;
+        let c: Value = (a + b).into();
+
+        let leader_fut = {
+            let circ = circ.clone();
+            let a_ref = leader.new_private_input::<u8>("a").unwrap();
             let b_ref = leader.new_blind_input::<u8>("b").unwrap();
             let c_ref = leader.new_output::<u8>("c").unwrap();
 
@@ -1422,6 +2024,130 @@ mod tests {
         assert_eq!(leader_output, vec![c]);
     }
 
+    #[tokio::test]
+    async fn test_deap_decode_policy() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
+        let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
+
+        let leader_policy = DecodePolicy::strict();
+        let follower_policy = DecodePolicy::strict();
+        leader.set_decode_policy(leader_policy.clone());
+        follower.set_decode_policy(follower_policy.clone());
+
+        let circ = adder_circ();
+
+        let leader_fut = {
+            let circ = circ.clone();
+            let a_ref = leader.new_private_input::<u8>("a").unwrap();
+            let b_ref = leader.new_blind_input::<u8>("b").unwrap();
+            let c_ref = leader.new_output::<u8>("c").unwrap();
+
+            leader.assign(&a_ref, 1u8).unwrap();
+            leader_policy.tag(&c_ref, DecodeAuth::Both);
+
+            async move {
+                leader
+                    .execute(
+                        &mut ctx_a,
+                        circ,
+                        &[a_ref.clone(), b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let outputs = leader
+                    .decode_private(
+                        &mut ctx_a,
+                        &[c_ref],
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let err = leader
+                    .decode_private(
+                        &mut ctx_a,
+                        &[a_ref],
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap_err();
+
+                leader
+                    .finalize(&mut ctx_a, &mut leader_ot_recv)
+                    .await
+                    .unwrap();
+
+                (outputs, err)
+            }
+        };
+
+        let follower_fut = {
+            let a_ref = follower.new_blind_input::<u8>("a").unwrap();
+            let b_ref = follower.new_private_input::<u8>("b").unwrap();
+            let c_ref = follower.new_output::<u8>("c").unwrap();
+
+            follower.assign(&b_ref, 2u8).unwrap();
+            follower_policy.tag(&c_ref, DecodeAuth::Both);
+
+            async move {
+                follower
+                    .execute(
+                        &mut ctx_b,
+                        circ,
+                        &[a_ref.clone(), b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                follower
+                    .decode_blind(
+                        &mut ctx_b,
+                        &[c_ref],
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                let err = follower
+                    .decode_blind(
+                        &mut ctx_b,
+                        &[a_ref],
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap_err();
+
+                follower
+                    .finalize(&mut ctx_b, &mut follower_ot_recv)
+                    .await
+                    .unwrap();
+
+                err
+            }
+        };
+
+        let ((leader_output, leader_err), follower_err) = tokio::join!(leader_fut, follower_fut);
+
+        assert_eq!(leader_output, vec![Value::U8(3)]);
+        assert!(matches!(leader_err, DEAPError::UnauthorizedDecode(_)));
+        assert!(matches!(follower_err, DEAPError::UnauthorizedDecode(_)));
+    }
+
     #[tokio::test]
     async fn test_deap_decode_shared() {
         let (mut ctx_a, mut ctx_b) = test_st_executor(8);
@@ -1622,4 +2348,171 @@ mod tests {
 
         futures::join!(leader_fut, follower_fut);
     }
+
+    #[tokio::test]
+    async fn test_deap_malicious_leader_equality_check() {
+        // The leader commits to an equality check other than the one it computed.
+        let (mut ctx_a, mut ctx_b) =
+            test_tamper_executor(vec![Deviation::modify::<Hash, _>(0, |commit| {
+                *commit = Hash::from([0u8; 32])
+            })]);
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let mut leader = DEAP::new(Role::Leader, [42u8; 32]);
+        let mut follower = DEAP::new(Role::Follower, [69u8; 32]);
+
+        let circ = adder_circ();
+
+        let leader_fut = {
+            let a_ref = leader.new_private_input::<u8>("a").unwrap();
+            let b_ref = leader.new_blind_input::<u8>("b").unwrap();
+            let c_ref = leader.new_output::<u8>("c").unwrap();
+
+            leader.assign(&a_ref, 1u8).unwrap();
+
+            async {
+                leader
+                    .execute(
+                        &mut ctx_a,
+                        circ.clone(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                leader.decode(&mut ctx_a, &[c_ref]).await.unwrap();
+
+                leader.finalize(&mut ctx_a, &mut leader_ot_recv).await
+            }
+        };
+
+        let follower_fut = {
+            let a_ref = follower.new_blind_input::<u8>("a").unwrap();
+            let b_ref = follower.new_private_input::<u8>("b").unwrap();
+            let c_ref = follower.new_output::<u8>("c").unwrap();
+
+            follower.assign(&b_ref, 2u8).unwrap();
+
+            async {
+                follower
+                    .execute(
+                        &mut ctx_b,
+                        circ.clone(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                follower.decode(&mut ctx_b, &[c_ref]).await.unwrap();
+
+                follower.finalize(&mut ctx_b, &mut follower_ot_recv).await
+            }
+        };
+
+        let (leader_result, follower_result) = futures::join!(leader_fut, follower_fut);
+
+        leader_result.unwrap();
+        assert!(matches!(
+            follower_result,
+            Err(DEAPError::FinalizationError(
+                FinalizationError::CommitmentError(_)
+            ))
+        ));
+        assert_eq!(ctx_a.io_mut().applied(), 1);
+    }
+
+    /// Runs a seeded session, returning the transcripts recorded by the leader and the follower.
+    async fn seeded_session(rng_seed: [u8; 32]) -> (Recording, Recording) {
+        let (io_a, io_b) = tokio::io::duplex(1 << 16);
+        let (recorder_a, recorder_b) = (
+            Recorder::new_with_seed(Block::ZERO),
+            Recorder::new_with_seed(Block::ZERO),
+        );
+        let mut ctx_a =
+            STExecutor::new(Bincode.new_framed(recorder_a.record(b"main", io_a.compat())));
+        let mut ctx_b =
+            STExecutor::new(Bincode.new_framed(recorder_b.record(b"main", io_b.compat())));
+        let (mut leader_ot_send, mut follower_ot_recv) = ideal_ot();
+        let (mut follower_ot_send, mut leader_ot_recv) = ideal_ot();
+
+        let leader = DEAP::new_seeded(Role::Leader, [42u8; 32], rng_seed);
+        let follower = DEAP::new_seeded(Role::Follower, [69u8; 32], rng_seed);
+
+        let circ = adder_circ();
+
+        let leader_fut = {
+            let a_ref = leader.new_private_input::<u8>("a").unwrap();
+            let b_ref = leader.new_blind_input::<u8>("b").unwrap();
+            let c_ref = leader.new_output::<u8>("c").unwrap();
+
+            leader.assign(&a_ref, 1u8).unwrap();
+
+            async {
+                leader
+                    .execute(
+                        &mut ctx_a,
+                        circ.clone(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut leader_ot_send,
+                        &mut leader_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                leader.decode(&mut ctx_a, &[c_ref]).await.unwrap()
+            }
+        };
+
+        let follower_fut = {
+            let a_ref = follower.new_blind_input::<u8>("a").unwrap();
+            let b_ref = follower.new_private_input::<u8>("b").unwrap();
+            let c_ref = follower.new_output::<u8>("c").unwrap();
+
+            follower.assign(&b_ref, 2u8).unwrap();
+
+            async {
+                follower
+                    .execute(
+                        &mut ctx_b,
+                        circ.clone(),
+                        &[a_ref, b_ref],
+                        std::slice::from_ref(&c_ref),
+                        &mut follower_ot_send,
+                        &mut follower_ot_recv,
+                    )
+                    .await
+                    .unwrap();
+
+                follower.decode(&mut ctx_b, &[c_ref]).await.unwrap()
+            }
+        };
+
+        let (leader_result, follower_result) = futures::join!(leader_fut, follower_fut);
+
+        assert_eq!(leader_result, follower_result);
+
+        (recorder_a.recording(), recorder_b.recording())
+    }
+
+    #[tokio::test]
+    async fn test_deap_seeded_reproducible() {
+        let (leader, follower) = seeded_session([1u8; 32]).await;
+        let (leader_rerun, follower_rerun) = seeded_session([1u8; 32]).await;
+
+        assert_eq!(leader_rerun, leader);
+        assert_eq!(follower_rerun, follower);
+
+        let (leader_other, follower_other) = seeded_session([2u8; 32]).await;
+
+        assert_ne!(leader_other, leader);
+        assert_ne!(follower_other, follower);
+    }
 }
@@ -15,6 +15,8 @@ use mpz_garble_core::{encoding_state::Active, EncodedValue};
 
 use crate::{
     config::{Role, Visibility},
+    decoding::DecodePolicy,
+    memory::DebugNames,
     ot::{VerifiableOTReceiveEncoding, VerifiableOTSendEncoding},
     value::ValueRef,
     Decode, DecodeError, DecodePrivate, Execute, ExecutionError, Load, LoadError, Memory,
@@ -70,6 +72,67 @@ impl<Ctx, OTS, OTR> DEAPThread<Ctx, OTS, OTR> {
         }
     }
 
+    /// Creates a new DEAP instance whose randomness is derived from the provided seeds.
+    ///
+    /// See [`DEAP::new_seeded`], this must not be used outside of tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_seeded(
+        role: Role,
+        encoder_seed: [u8; 32],
+        rng_seed: [u8; 32],
+        ctx: Ctx,
+        ot_send: OTS,
+        ot_recv: OTR,
+    ) -> Self {
+        Self {
+            ctx,
+            ot_send,
+            ot_recv,
+            state: State::Main(Arc::new(DEAP::new_seeded(role, encoder_seed, rng_seed))),
+        }
+    }
+
+    /// Includes the names of values in errors, using the provided registry.
+    ///
+    /// Values allocated in memory are registered by their id. Other names can be registered
+    /// directly with the registry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after threads have been created from this instance, or on a thread
+    /// which is not the main thread.
+    pub fn with_debug_names(mut self, debug_names: DebugNames) -> Self {
+        let State::Main(deap) = &mut self.state else {
+            panic!("debug names can only be set on the main thread");
+        };
+
+        Arc::get_mut(deap)
+            .expect("debug names must be set before threads are created")
+            .set_debug_names(debug_names);
+
+        self
+    }
+
+    /// Sets the policy of which values may be decoded, see [`DEAP::set_decode_policy`].
+    ///
+    /// The policy can still be tagged after it is set, as clones share the same policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after threads have been created from this instance, or on a thread
+    /// which is not the main thread.
+    pub fn with_decode_policy(mut self, decode_policy: DecodePolicy) -> Self {
+        let State::Main(deap) = &mut self.state else {
+            panic!("decode policy can only be set on the main thread");
+        };
+
+        Arc::get_mut(deap)
+            .expect("decode policy must be set before threads are created")
+            .set_decode_policy(decode_policy);
+
+        self
+    }
+
     /// Creates a new DEAP thread.
     pub fn new_thread(&self, ctx: Ctx, ot_send: OTS, ot_recv: OTR) -> Result<Self, DEAPError> {
         match &self.state {
@@ -112,6 +175,55 @@ where
     }
 }
 
+impl<Ctx, OTS, OTR> DEAPThread<Ctx, OTS, OTR>
+where
+    Ctx: Context,
+    OTS: VerifiableOTSendEncoding<Ctx> + Send + Sync,
+    OTR: VerifiableOTReceiveEncoding<Ctx> + Send + Sync,
+{
+    /// Executes a circuit with a single garbled circuit, where the provided party is the
+    /// generator.
+    ///
+    /// See [`DEAP::execute_single`] for more information.
+    pub async fn execute_single(
+        &mut self,
+        circ: Arc<Circuit>,
+        inputs: &[ValueRef],
+        outputs: &[ValueRef],
+        generator: Role,
+    ) -> Result<(), ExecutionError> {
+        self.state
+            .get()
+            .execute_single(
+                &mut self.ctx,
+                circ,
+                inputs,
+                outputs,
+                generator,
+                &mut self.ot_send,
+                &mut self.ot_recv,
+            )
+            .map_err(ExecutionError::from)
+            .await
+    }
+
+    /// Decodes the outputs of [`execute_single`](Self::execute_single), revealing the plaintext
+    /// values to both parties.
+    ///
+    /// See [`DEAP::decode_single`] for more information.
+    pub async fn decode_single(
+        &mut self,
+        values: &[ValueRef],
+        generator: Role,
+    ) -> Result<Vec<Value>, DecodeError> {
+        self.state
+            .get()
+            .decode_single(&mut self.ctx, values, generator)
+            .map_err(DecodeError::from)
+            .await
+    }
+}
+
 impl<Ctx, OTS, OTR> Thread for DEAPThread<Ctx, OTS, OTR> {}
 
 impl<Ctx, OTS, OTR> Memory for DEAPThread<Ctx, OTS, OTR> {
@@ -204,6 +316,14 @@ where
             .map_err(ExecutionError::from)
             .await
     }
+
+    async fn refresh(&mut self, values: &[ValueRef]) -> Result<Vec<ValueRef>, ExecutionError> {
+        self.state
+            .get()
+            .refresh(&mut self.ctx, values, &mut self.ot_send, &mut self.ot_recv)
+            .map_err(ExecutionError::from)
+            .await
+    }
 }
 
 #[async_trait]
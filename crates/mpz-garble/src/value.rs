@@ -1,6 +1,6 @@
 //! Types associated with values in MPC.
 
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 
 use mpz_core::utils::blake3;
 
@@ -131,6 +131,19 @@ impl ValueRef {
     }
 }
 
+impl fmt::Display for ValueRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueRef::Value { id } => write!(f, "{}", id.as_ref()),
+            ValueRef::Array(array) => match array.ids() {
+                [id] => write!(f, "[{}]", id.as_ref()),
+                [first, .., last] => write!(f, "[{}, .., {}]", first.as_ref(), last.as_ref()),
+                [] => unreachable!("arrays have at least one value"),
+            },
+        }
+    }
+}
+
 /// An iterator over value IDs of a reference.
 pub enum ValueRefIter<'a> {
     /// A single value.
@@ -157,3 +170,68 @@ pub(crate) struct CircuitRefs {
     pub(crate) inputs: Vec<ValueRef>,
     pub(crate) outputs: Vec<ValueRef>,
 }
+
+/// The phase of execution in which an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Transferring the encodings of input values.
+    Setup,
+    /// Garbling a circuit.
+    Garble,
+    /// Evaluating a circuit.
+    Evaluate,
+    /// Decoding values.
+    Decode,
+    /// Converting values to other encodings.
+    Convert,
+    /// Proving or verifying values.
+    Prove,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Setup => write!(f, "setup"),
+            Phase::Garble => write!(f, "garbling"),
+            Phase::Evaluate => write!(f, "evaluation"),
+            Phase::Decode => write!(f, "decoding"),
+            Phase::Convert => write!(f, "conversion"),
+            Phase::Prove => write!(f, "proving"),
+        }
+    }
+}
+
+/// Diagnostics of a value, included in errors so that failed sessions are debuggable from logs.
+///
+/// Names are only available if the debug-name registry is enabled, see
+/// [`DebugNames`](crate::DebugNames).
+#[derive(Debug, Clone)]
+pub struct ValueDiagnostics {
+    /// The value.
+    pub value: ValueRef,
+    /// The name of the value.
+    pub name: Option<String>,
+    /// The circuit being executed, identified by the name or id of its first output.
+    pub circuit: Option<String>,
+    /// The phase of execution.
+    pub phase: Option<Phase>,
+}
+
+impl fmt::Display for ValueDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "`{name}`")?,
+            None => write!(f, "`{}`", self.value)?,
+        }
+
+        if let Some(phase) = self.phase {
+            write!(f, " during {phase}")?;
+        }
+
+        if let Some(circuit) = &self.circuit {
+            write!(f, " of circuit `{circuit}`")?;
+        }
+
+        Ok(())
+    }
+}
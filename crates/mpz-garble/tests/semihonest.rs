@@ -1,8 +1,122 @@
-use mpz_circuits::{circuits::AES128, types::StaticValueType};
-use mpz_common::executor::test_st_executor;
+use std::sync::Arc;
+
+use mpz_circuits::{
+    circuits::AES128,
+    types::{StaticValueType, Value},
+    Circuit, CircuitBuilder, Gate,
+};
+use mpz_common::{
+    executor::{test_st_executor, TestSTExecutor},
+    Context,
+};
+use mpz_core::commit::Nonce;
+use mpz_fields::p256::P256;
 use mpz_ot::ideal::ot::ideal_ot;
+use serio::SinkExt;
+
+use mpz_garble::{
+    config::Visibility, value::ValueRef, DecodeAuth, DecodePolicy, Evaluator,
+    EvaluatorConfigBuilder, EvaluatorError, Generator, GeneratorConfigBuilder, GeneratorError,
+    GeneratorSecret, ValueMemory,
+};
+
+fn aes128(key: [u8; 16], msg: [u8; 16]) -> [u8; 16] {
+    use aes::{
+        cipher::{BlockEncrypt, KeyInit},
+        Aes128,
+    };
+
+    let mut msg = msg.into();
+
+    let cipher = Aes128::new_from_slice(&key).unwrap();
+    cipher.encrypt_block(&mut msg);
+
+    msg.into()
+}
+
+/// Sets up `a` as a private input of the generator and `b` as a private input of the evaluator,
+/// transferring the encodings of the inputs to the evaluator.
+///
+/// Returns references to the inputs.
+async fn setup_inputs(
+    ctx_a: &mut TestSTExecutor,
+    ctx_b: &mut TestSTExecutor,
+    gen: &Generator,
+    ev: &Evaluator,
+    a: impl Into<Value>,
+    b: impl Into<Value>,
+) -> [ValueRef; 2] {
+    let (mut ot_send, mut ot_recv) = ideal_ot();
+    let (a, b) = (a.into(), b.into());
+    let (a_typ, b_typ) = (a.value_type(), b.value_type());
+
+    let mut gen_memory = ValueMemory::default();
+    let a_ref = gen_memory
+        .new_input("a", a_typ.clone(), Visibility::Private)
+        .unwrap();
+    let b_ref = gen_memory
+        .new_input("b", b_typ.clone(), Visibility::Blind)
+        .unwrap();
+    gen_memory.assign(&a_ref, a).unwrap();
+
+    let mut ev_memory = ValueMemory::default();
+    ev_memory
+        .new_input("a", a_typ.clone(), Visibility::Blind)
+        .unwrap();
+    ev_memory
+        .new_input("b", b_typ.clone(), Visibility::Private)
+        .unwrap();
+    ev_memory.assign(&b_ref, b).unwrap();
 
-use mpz_garble::{config::Visibility, Evaluator, Generator, GeneratorConfigBuilder, ValueMemory};
+    gen.generate_input_encoding(&a_ref, &a_typ);
+    gen.generate_input_encoding(&b_ref, &b_typ);
+
+    let inputs = [a_ref, b_ref];
+    let gen_values = gen_memory.drain_assigned(&inputs);
+    let ev_values = ev_memory.drain_assigned(&inputs);
+    let (gen_result, ev_result) = tokio::join!(
+        gen.setup_assigned_values(ctx_a, &gen_values, &mut ot_send),
+        ev.setup_assigned_values(ctx_b, &ev_values, &mut ot_recv),
+    );
+    gen_result.unwrap();
+    ev_result.unwrap();
+
+    inputs
+}
+
+/// Returns a reference to the output of a circuit with a single output.
+fn output_ref(circ: &Circuit) -> ValueRef {
+    ValueMemory::default()
+        .new_output("output", circ.outputs()[0].value_type())
+        .unwrap()
+}
+
+/// Garbles and evaluates a circuit with a single output, returning a reference to the output.
+async fn execute(
+    ctx_a: &mut TestSTExecutor,
+    ctx_b: &mut TestSTExecutor,
+    gen: &Generator,
+    ev: &Evaluator,
+    circ: Arc<Circuit>,
+    inputs: &[ValueRef],
+) -> ValueRef {
+    let outputs = [output_ref(&circ)];
+    let (gen_result, ev_result) = tokio::join!(
+        gen.generate(ctx_a, circ.clone(), inputs, &outputs, false),
+        ev.evaluate(ctx_b, circ.clone(), inputs, &outputs),
+    );
+    gen_result.unwrap();
+    ev_result.unwrap();
+
+    let [output] = outputs;
+    output
+}
+
+/// Decodes an output with the full encoding of the generator.
+fn decode(gen: &Generator, ev: &Evaluator, output: &ValueRef) -> Value {
+    let decoding = gen.get_encoding(output).unwrap().decoding();
+    ev.get_encoding(output).unwrap().decode(&decoding).unwrap()
+}
 
 #[tokio::test]
 async fn test_semi_honest() {
@@ -122,3 +236,235 @@ async fn test_semi_honest() {
 
     assert_eq!(ciphertext, expected)
 }
+
+#[tokio::test]
+async fn test_semi_honest_export_import() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    let gen = Generator::new(
+        GeneratorConfigBuilder::default().build().unwrap(),
+        [0u8; 32],
+    );
+    let ev = Evaluator::default();
+
+    let key = [69u8; 16];
+    let msg = [42u8; 16];
+
+    // Offline phase, the encodings of the inputs are transferred.
+    let inputs = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, key, msg).await;
+
+    let secret = bincode::serialize(&gen.export()).unwrap();
+
+    // Online phase, a new generator garbles the circuit using the transferred encodings.
+    let secret: GeneratorSecret = bincode::deserialize(&secret).unwrap();
+    let gen = Generator::import(GeneratorConfigBuilder::default().build().unwrap(), secret);
+
+    let ciphertext_ref = execute(&mut ctx_a, &mut ctx_b, &gen, &ev, AES128.clone(), &inputs).await;
+
+    let ciphertext: [u8; 16] = decode(&gen, &ev, &ciphertext_ref).try_into().unwrap();
+
+    assert_eq!(ciphertext, aes128(key, msg));
+}
+
+#[tokio::test]
+async fn test_semi_honest_trace() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    let gen = Generator::new(
+        GeneratorConfigBuilder::default().trace().build().unwrap(),
+        [0u8; 32],
+    );
+    let ev = Evaluator::new(EvaluatorConfigBuilder::default().trace().build().unwrap());
+
+    let builder = CircuitBuilder::new();
+    let a = builder.add_input::<u8>();
+    let b = builder.add_input::<u8>();
+    let c = a & b;
+    builder.add_output(c ^ a);
+    let circ = Arc::new(builder.build().unwrap());
+
+    // Trace the intermediate `a & b`.
+    let wires: Vec<_> = circ
+        .gates()
+        .iter()
+        .filter(|gate| matches!(gate, Gate::And { .. }))
+        .map(|gate| gate.z())
+        .collect();
+
+    let inputs = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, u8::MAX, u8::MAX).await;
+    let outputs = [output_ref(&circ)];
+
+    let (gen_result, ev_result) = tokio::join!(
+        gen.generate_traced(&mut ctx_a, circ.clone(), &inputs, &outputs, &wires),
+        ev.evaluate_traced(&mut ctx_b, circ.clone(), &inputs, &outputs, &wires),
+    );
+    gen_result.unwrap();
+    let (_, values) = ev_result.unwrap();
+
+    assert_eq!(values, vec![true; 8]);
+}
+
+#[tokio::test]
+async fn test_semi_honest_decoding_commitments() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    let gen = Generator::new(
+        GeneratorConfigBuilder::default()
+            .decoding_commitments()
+            .build()
+            .unwrap(),
+        [0u8; 32],
+    );
+    let ev = Evaluator::new(
+        EvaluatorConfigBuilder::default()
+            .decoding_commitments()
+            .build()
+            .unwrap(),
+    );
+
+    let key = [69u8; 16];
+    let msg = [42u8; 16];
+
+    let inputs = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, key, msg).await;
+    let ciphertext_ref = execute(&mut ctx_a, &mut ctx_b, &gen, &ev, AES128.clone(), &inputs).await;
+
+    let values = [ciphertext_ref];
+    let (gen_result, ev_result) = tokio::join!(
+        gen.decode(&mut ctx_a, &values),
+        ev.decode(&mut ctx_b, &values),
+    );
+    gen_result.unwrap();
+
+    let ciphertext: [u8; 16] = ev_result.unwrap().pop().unwrap().try_into().unwrap();
+
+    assert_eq!(ciphertext, aes128(key, msg));
+}
+
+#[tokio::test]
+async fn test_semi_honest_decoding_commitments_invalid() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    let gen = Generator::new(
+        GeneratorConfigBuilder::default()
+            .decoding_commitments()
+            .build()
+            .unwrap(),
+        [0u8; 32],
+    );
+    let ev = Evaluator::new(
+        EvaluatorConfigBuilder::default()
+            .decoding_commitments()
+            .build()
+            .unwrap(),
+    );
+
+    let inputs = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, [69u8; 16], [42u8; 16]).await;
+    let ciphertext_ref = execute(&mut ctx_a, &mut ctx_b, &gen, &ev, AES128.clone(), &inputs).await;
+
+    // A malicious generator sends a different decoding for the ciphertext, which it can not
+    // open the commitment to.
+    let decoding = gen.get_encoding(&inputs[0]).unwrap().decoding();
+    let mut rng = rand::thread_rng();
+    let nonces: Vec<_> = (0..16).map(|_| Nonce::random_with_rng(&mut rng)).collect();
+
+    let values = [ciphertext_ref];
+    let (send_result, ev_result) = tokio::join!(
+        async {
+            ctx_a.io_mut().feed(vec![decoding]).await?;
+            ctx_a.io_mut().send(nonces).await
+        },
+        ev.decode(&mut ctx_b, &values),
+    );
+    send_result.unwrap();
+
+    assert!(matches!(
+        ev_result.unwrap_err(),
+        EvaluatorError::VerificationError(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_semi_honest_decode_policy() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    // Each party has its own policy, which must agree with the policy of its peer.
+    let gen_policy = DecodePolicy::strict();
+    let ev_policy = DecodePolicy::strict();
+
+    let mut gen = Generator::new(
+        GeneratorConfigBuilder::default().build().unwrap(),
+        [0u8; 32],
+    );
+    gen.set_decode_policy(gen_policy.clone());
+    let mut ev = Evaluator::new(
+        EvaluatorConfigBuilder::default()
+            .decode_audit()
+            .build()
+            .unwrap(),
+    );
+    ev.set_decode_policy(ev_policy.clone());
+
+    let [key_ref, msg_ref] = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, 69u8, 42u8).await;
+
+    // The key is not tagged, so the strict policy does not authorize the evaluator to decode it.
+    let values = [key_ref];
+    assert!(matches!(
+        gen.decode(&mut ctx_a, &values).await.unwrap_err(),
+        GeneratorError::UnauthorizedDecode(_)
+    ));
+    assert!(matches!(
+        ev.decode(&mut ctx_b, &values).await.unwrap_err(),
+        EvaluatorError::UnauthorizedDecode(_)
+    ));
+
+    gen_policy.tag(&msg_ref, DecodeAuth::Both);
+    ev_policy.tag(&msg_ref, DecodeAuth::Both);
+
+    let values = [msg_ref.clone()];
+    let (gen_result, ev_result) = tokio::join!(
+        gen.decode(&mut ctx_a, &values),
+        ev.decode(&mut ctx_b, &values),
+    );
+    gen_result.unwrap();
+
+    assert_eq!(ev_result.unwrap(), vec![Value::U8(42)]);
+
+    let audit = ev.take_decode_audit();
+    assert_eq!(
+        audit,
+        vec![(msg_ref.iter().next().unwrap().clone(), DecodeAuth::Both)]
+    );
+    assert!(ev.take_decode_audit().is_empty());
+}
+
+#[tokio::test]
+async fn test_semi_honest_convert() {
+    let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+
+    let gen = Generator::new(
+        GeneratorConfigBuilder::default().build().unwrap(),
+        [0u8; 32],
+    );
+    let ev = Evaluator::default();
+
+    let a = 0xdeadbeefu32;
+    let b = 42u8;
+
+    let [a_ref, b_ref] = setup_inputs(&mut ctx_a, &mut ctx_b, &gen, &ev, a, b).await;
+
+    let (gen_share, ev_share) = tokio::join!(
+        gen.convert_to_arithmetic::<_, P256>(&mut ctx_a, &a_ref),
+        ev.convert_to_arithmetic::<_, P256>(&mut ctx_b, &a_ref),
+    );
+    assert_eq!(
+        gen_share.unwrap() + ev_share.unwrap(),
+        P256::new(a).unwrap()
+    );
+
+    let square = |value: u64| P256::new(value * value).unwrap();
+    let (gen_share, ev_share) = tokio::join!(
+        gen.lookup(&mut ctx_a, &b_ref, square),
+        ev.lookup::<_, P256>(&mut ctx_b, &b_ref),
+    );
+    assert_eq!(gen_share.unwrap() + ev_share.unwrap(), square(b as u64));
+}
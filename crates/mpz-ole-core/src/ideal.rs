@@ -1,4 +1,4 @@
-//! Ideal functionality for Oblivious Linear Function Evaluation (OLE).
+//! Ideal functionalities for Oblivious Linear Function Evaluation (OLE) and Vector OLE (VOLE).
 
 use mpz_fields::Field;
 use rand::{rngs::ThreadRng, thread_rng};
@@ -45,9 +45,43 @@ impl Default for IdealOLE {
     }
 }
 
+/// The VOLE functionality.
+pub struct IdealVOLE(ThreadRng);
+
+impl IdealVOLE {
+    /// Creates a new functionality.
+    pub fn new() -> Self {
+        Self(thread_rng())
+    }
+
+    /// Generates VOLEs.
+    ///
+    /// The sender inputs a single field element `delta` and gets outputs `v_k`, the receiver
+    /// inputs field elements `u_k` and gets outputs `w_k`, such that `w_k = u_k * delta + v_k`.
+    pub fn generate<F: Field>(&mut self, delta: F, receiver_input: &[F]) -> (Vec<F>, Vec<F>) {
+        let sender_output: Vec<F> = (0..receiver_input.len())
+            .map(|_| F::rand(&mut self.0))
+            .collect();
+
+        let receiver_output: Vec<F> = receiver_input
+            .iter()
+            .zip(sender_output.iter().copied())
+            .map(|(&u, v)| u * delta + v)
+            .collect();
+
+        (sender_output, receiver_output)
+    }
+}
+
+impl Default for IdealVOLE {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::ideal::IdealOLE;
+    use crate::ideal::{IdealOLE, IdealVOLE};
     use mpz_core::{prg::Prg, Block};
     use mpz_fields::{p256::P256, UniformRand};
     use rand::SeedableRng;
@@ -69,4 +103,22 @@ mod tests {
             .zip(bk)
             .for_each(|(((&y, x), a), b)| assert_eq!(y, a * b + x));
     }
+
+    #[test]
+    fn test_vole_functionality() {
+        let count = 12;
+        let mut vole = IdealVOLE::default();
+        let mut rng = Prg::from_seed(Block::ZERO);
+
+        let delta = P256::rand(&mut rng);
+        let uk: Vec<P256> = (0..count).map(|_| P256::rand(&mut rng)).collect();
+
+        let (vk, wk) = vole.generate(delta, &uk);
+
+        assert_eq!(vk.len(), count);
+        wk.iter()
+            .zip(vk)
+            .zip(uk)
+            .for_each(|((&w, v), u)| assert_eq!(w, u * delta + v));
+    }
 }
@@ -1,12 +1,13 @@
-//! Ideal OLE implementation.
+//! Ideal OLE and VOLE implementations.
 
-use crate::{OLEError, OLEReceiver, OLESender};
+use crate::{OLEError, OLEReceiver, OLESender, VOLEReceiver, VOLESender};
 use async_trait::async_trait;
 use mpz_common::{
     ideal::{ideal_f2p, Alice, Bob},
     Allocate, Context, Preprocess,
 };
 use mpz_fields::Field;
+use mpz_ole_core::ideal::IdealVOLE;
 use rand::thread_rng;
 
 /// Ideal OLESender.
@@ -76,9 +77,71 @@ impl<F: Field, Ctx: Context> OLEReceiver<Ctx, F> for IdealOLEReceiver {
     }
 }
 
+/// Ideal VOLESender.
+pub struct IdealVOLESender(Alice<()>);
+
+/// Ideal VOLEReceiver.
+pub struct IdealVOLEReceiver(Bob<()>);
+
+/// Returns a VOLE sender and receiver pair.
+pub fn ideal_vole() -> (IdealVOLESender, IdealVOLEReceiver) {
+    let (alice, bob) = ideal_f2p(());
+
+    (IdealVOLESender(alice), IdealVOLEReceiver(bob))
+}
+
+fn vole<F: Field>(_: &mut (), (delta, count): (F, usize), bob_input: Vec<F>) -> (Vec<F>, Vec<F>) {
+    assert_eq!(count, bob_input.len());
+
+    IdealVOLE::new().generate(delta, &bob_input)
+}
+
+impl Allocate for IdealVOLESender {
+    fn alloc(&mut self, _: usize) {}
+}
+
+impl Allocate for IdealVOLEReceiver {
+    fn alloc(&mut self, _: usize) {}
+}
+
+#[async_trait]
+impl<Ctx: Context> Preprocess<Ctx> for IdealVOLESender {
+    type Error = OLEError;
+
+    async fn preprocess(&mut self, _: &mut Ctx) -> Result<(), OLEError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Ctx: Context> Preprocess<Ctx> for IdealVOLEReceiver {
+    type Error = OLEError;
+
+    async fn preprocess(&mut self, _: &mut Ctx) -> Result<(), OLEError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<F: Field, Ctx: Context> VOLESender<Ctx, F> for IdealVOLESender {
+    async fn send(&mut self, ctx: &mut Ctx, delta: F, count: usize) -> Result<Vec<F>, OLEError> {
+        Ok(self.0.call(ctx, (delta, count), vole).await)
+    }
+}
+
+#[async_trait]
+impl<F: Field, Ctx: Context> VOLEReceiver<Ctx, F> for IdealVOLEReceiver {
+    async fn receive(&mut self, ctx: &mut Ctx, u_k: Vec<F>) -> Result<Vec<F>, OLEError> {
+        Ok(self.0.call(ctx, u_k, vole).await)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{ideal::ideal_ole, OLEReceiver, OLESender};
+    use crate::{
+        ideal::{ideal_ole, ideal_vole},
+        OLEReceiver, OLESender, VOLEReceiver, VOLESender,
+    };
     use mpz_common::executor::test_st_executor;
     use mpz_core::{prg::Prg, Block};
     use mpz_fields::{p256::P256, UniformRand};
@@ -110,4 +173,30 @@ mod tests {
             .zip(y_k)
             .for_each(|(((&a, b), x), y)| assert_eq!(y, a * b + x));
     }
+
+    #[tokio::test]
+    async fn test_ideal_vole() {
+        let count = 12;
+        let mut rng = Prg::from_seed(Block::ZERO);
+
+        let delta = P256::rand(&mut rng);
+        let u_k: Vec<P256> = (0..count).map(|_| P256::rand(&mut rng)).collect();
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(10);
+
+        let (mut sender, mut receiver) = ideal_vole();
+
+        let (v_k, w_k) = tokio::try_join!(
+            sender.send(&mut ctx_sender, delta, count),
+            receiver.receive(&mut ctx_receiver, u_k.clone())
+        )
+        .unwrap();
+
+        assert_eq!(v_k.len(), count);
+        assert_eq!(w_k.len(), count);
+        u_k.into_iter()
+            .zip(v_k)
+            .zip(w_k)
+            .for_each(|((u, v), w)| assert_eq!(w, u * delta + v));
+    }
 }
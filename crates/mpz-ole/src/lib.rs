@@ -59,6 +59,47 @@ pub trait OLEReceiver<Ctx: Context, F: Field> {
     async fn receive(&mut self, ctx: &mut Ctx, inputs: Vec<F>) -> Result<Vec<F>, OLEError>;
 }
 
+/// Batch VOLE Sender.
+///
+/// The sender inputs a single field element `delta` and gets outputs `v_k`, such that
+/// `w_k = u_k * delta + v_k` holds, where `u_k` and `w_k` are the [`VOLEReceiver`]'s inputs and
+/// outputs respectively.
+#[async_trait]
+pub trait VOLESender<Ctx: Context, F: Field> {
+    /// Sends his masked input to the [`VOLEReceiver`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context.
+    /// * `delta` - The sender's VOLE input.
+    /// * `count` - The number of VOLEs.
+    ///
+    /// # Returns
+    ///
+    /// * The sender's VOLE outputs `v_k`.
+    async fn send(&mut self, ctx: &mut Ctx, delta: F, count: usize) -> Result<Vec<F>, OLEError>;
+}
+
+/// Batch VOLE Receiver.
+///
+/// The receiver inputs field elements `u_k` and gets outputs `w_k`, such that
+/// `w_k = u_k * delta + v_k` holds, where `delta` and `v_k` are the [`VOLESender`]'s input and
+/// outputs respectively.
+#[async_trait]
+pub trait VOLEReceiver<Ctx: Context, F: Field> {
+    /// Receives the masked input of the [`VOLESender`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context.
+    /// * `inputs` - The receiver's VOLE inputs.
+    ///
+    /// # Returns
+    ///
+    /// * The receiver's VOLE outputs `w_k`.
+    async fn receive(&mut self, ctx: &mut Ctx, inputs: Vec<F>) -> Result<Vec<F>, OLEError>;
+}
+
 /// An OLE error.
 #[derive(Debug, thiserror::Error)]
 pub struct OLEError {
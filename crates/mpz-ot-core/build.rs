@@ -0,0 +1,8 @@
+fn main() {
+    #[cfg(feature = "proto")]
+    {
+        println!("cargo:rerun-if-changed=proto");
+        prost_build::compile_protos(&["proto/mpz/ot/v1/ot.proto"], &["proto"])
+            .expect("protobuf schema should compile");
+    }
+}
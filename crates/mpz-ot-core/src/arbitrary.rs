@@ -0,0 +1,276 @@
+//! [`Arbitrary`] implementations for the OT messages.
+//!
+//! These are used to generate messages for property tests, including tests of how peers handle
+//! malformed input. Vectors in generated messages have at most [`MAX_LEN`] elements.
+
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, RistrettoPoint, Scalar};
+use mpz_core::{hash::Hash, Block};
+use proptest::{
+    collection::vec,
+    prelude::{any, prop_oneof, Arbitrary, BoxedStrategy, Strategy},
+};
+
+use crate::{
+    chou_orlandi::msgs as co,
+    ferret::{mpcot::msgs as mpcot, spcot::msgs as spcot},
+    TransferId,
+};
+
+/// The maximum number of elements of a vector in a generated message.
+pub const MAX_LEN: usize = 32;
+
+fn block() -> impl Strategy<Value = Block> {
+    any::<[u8; 16]>().prop_map(Block::new)
+}
+
+fn point() -> impl Strategy<Value = RistrettoPoint> {
+    any::<[u8; 32]>()
+        .prop_map(|bytes| RISTRETTO_BASEPOINT_POINT * Scalar::from_bytes_mod_order(bytes))
+}
+
+impl Arbitrary for TransferId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<u64>().prop_map(TransferId).boxed()
+    }
+}
+
+impl Arbitrary for co::SenderSetup {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        point().prop_map(|public_key| Self { public_key }).boxed()
+    }
+}
+
+impl Arbitrary for co::SenderPayload {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (any::<TransferId>(), vec([block(), block()], 0..MAX_LEN))
+            .prop_map(|(id, payload)| Self { id, payload })
+            .boxed()
+    }
+}
+
+impl Arbitrary for co::ReceiverPayload {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (any::<TransferId>(), vec(point(), 0..MAX_LEN))
+            .prop_map(|(id, blinded_choices)| Self {
+                id,
+                blinded_choices: blinded_choices
+                    .iter()
+                    .map(|point| point.compress())
+                    .collect(),
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for co::ReceiverReveal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<u8>(), 0..MAX_LEN)
+            .prop_map(|choices| Self { choices })
+            .boxed()
+    }
+}
+
+impl Arbitrary for spcot::MaskBits {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<bool>(), 0..MAX_LEN)
+            .prop_map(|bs| Self { bs })
+            .boxed()
+    }
+}
+
+impl Arbitrary for spcot::ExtendFromSender {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (vec([block(), block()], 0..MAX_LEN), block())
+            .prop_map(|(ms, sum)| Self { ms, sum })
+            .boxed()
+    }
+}
+
+impl Arbitrary for spcot::CheckFromReceiver {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<bool>(), 0..MAX_LEN)
+            .prop_map(|x_prime| Self { x_prime })
+            .boxed()
+    }
+}
+
+impl Arbitrary for spcot::CheckFromSender {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<[u8; 32]>()
+            .prop_map(|hashed_v| Self {
+                hashed_v: Hash::from(hashed_v),
+            })
+            .boxed()
+    }
+}
+
+impl<CotMsg> Arbitrary for spcot::Message<CotMsg>
+where
+    CotMsg: Arbitrary + 'static,
+{
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<CotMsg>().prop_map(Self::CotMsg),
+            any::<spcot::MaskBits>().prop_map(Self::MaskBits),
+            any::<spcot::ExtendFromSender>().prop_map(Self::ExtendFromSender),
+            any::<spcot::CheckFromReceiver>().prop_map(Self::CheckFromReceiver),
+            any::<spcot::CheckFromSender>().prop_map(Self::CheckFromSender),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for mpcot::HashSeed {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        block().prop_map(|seed| Self { seed }).boxed()
+    }
+}
+
+impl<SpcotMsg> Arbitrary for mpcot::Message<SpcotMsg>
+where
+    SpcotMsg: Arbitrary + 'static,
+{
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<SpcotMsg>().prop_map(Self::SpcotMsg),
+            any::<mpcot::HashSeed>().prop_map(Self::HashSeed),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_core::serialize::WireMessage;
+    use proptest::{
+        prelude::{prop_assert, prop_assert_eq, proptest, TestCaseError},
+        sample::Index,
+    };
+
+    use super::*;
+
+    /// The SPCOT message as sent by the Ferret protocol.
+    type SpcotMessage = spcot::Message<()>;
+    /// The MPCOT message as sent by the Ferret protocol.
+    type MpcotMessage = mpcot::Message<SpcotMessage>;
+
+    /// Checks that a message round-trips, and that malformed input is handled without panicking.
+    fn check<T: WireMessage>(
+        msg: &T,
+        index: Index,
+        mask: u8,
+        noise: &[u8],
+    ) -> Result<(), TestCaseError> {
+        let bytes = msg.to_wire();
+        let decoded = T::from_wire(&bytes).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert_eq!(decoded.to_wire(), bytes.clone());
+
+        prop_assert!(T::from_wire(&bytes[..index.index(bytes.len())]).is_err());
+
+        let mut corrupted = bytes.clone();
+        corrupted[index.index(bytes.len())] ^= mask;
+        _ = T::from_wire(&corrupted);
+
+        _ = T::from_wire(noise);
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_co_sender_setup(
+            msg in any::<co::SenderSetup>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_co_sender_payload(
+            msg in any::<co::SenderPayload>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_co_receiver_payload(
+            msg in any::<co::ReceiverPayload>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_co_receiver_reveal(
+            msg in any::<co::ReceiverReveal>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_spcot_message(
+            msg in any::<SpcotMessage>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+
+        #[test]
+        fn test_mpcot_message(
+            msg in any::<MpcotMessage>(),
+            index in any::<Index>(),
+            mask in any::<u8>(),
+            noise in vec(any::<u8>(), 0..256)
+        ) {
+            check(&msg, index, mask, &noise)?;
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use derive_builder::Builder;
 
+use crate::msgs::ConfigFingerprint;
+
 /// CO15 sender configuration.
 #[derive(Debug, Default, Clone, Builder)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct SenderConfig {
     /// Whether the Receiver should commit to their choices.
     #[builder(setter(custom), default = "false")]
@@ -26,10 +29,16 @@ impl SenderConfig {
     pub fn receiver_commit(&self) -> bool {
         self.receiver_commit
     }
+
+    /// Returns the fingerprint of the settings which the sender and receiver must agree on.
+    pub fn fingerprint(&self) -> ConfigFingerprint {
+        fingerprint(self.receiver_commit)
+    }
 }
 
 /// CO15 receiver configuration.
 #[derive(Debug, Default, Clone, Builder)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct ReceiverConfig {
     /// Whether the Receiver should commit to their choices.
     #[builder(setter(custom), default = "false")]
@@ -54,4 +63,13 @@ impl ReceiverConfig {
     pub fn receiver_commit(&self) -> bool {
         self.receiver_commit
     }
+
+    /// Returns the fingerprint of the settings which the sender and receiver must agree on.
+    pub fn fingerprint(&self) -> ConfigFingerprint {
+        fingerprint(self.receiver_commit)
+    }
+}
+
+fn fingerprint(receiver_commit: bool) -> ConfigFingerprint {
+    ConfigFingerprint::new(b"CO15", &[receiver_commit as u8])
 }
@@ -1,4 +1,6 @@
-use crate::TransferId;
+use alloc::string::String;
+
+use crate::{ErrorKind, TransferId};
 
 /// Errors that can occur when using the CO15 sender.
 #[derive(Debug, thiserror::Error)]
@@ -10,6 +12,8 @@ pub enum SenderError {
     IdMismatch(TransferId, TransferId),
     #[error("count mismatch: sender expected {0} but receiver sent {1}")]
     CountMismatch(usize, usize),
+    #[error("receiver sent an invalid point")]
+    InvalidPoint,
     #[error(transparent)]
     VerifyError(#[from] SenderVerifyError),
 }
@@ -39,3 +43,51 @@ pub enum SenderVerifyError {
     #[error("tape was not recorded")]
     TapeNotRecorded,
 }
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::InvalidState(_) => ErrorKind::CallerBug,
+            SenderError::IdMismatch(..) | SenderError::CountMismatch(..) => {
+                ErrorKind::ProtocolAbort
+            }
+            SenderError::InvalidPoint => ErrorKind::PeerMisbehavior,
+            SenderError::VerifyError(err) => err.kind(),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::InvalidState(_) => ErrorKind::CallerBug,
+            ReceiverError::IdMismatch(..) | ReceiverError::CountMismatch(..) => {
+                ErrorKind::ProtocolAbort
+            }
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl SenderVerifyError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderVerifyError::TapeNotRecorded => ErrorKind::CallerBug,
+            SenderVerifyError::ChoiceCountMismatch(..)
+            | SenderVerifyError::KeyCountMismatch(..)
+            | SenderVerifyError::InconsistentChoice => ErrorKind::PeerMisbehavior,
+        }
+    }
+}
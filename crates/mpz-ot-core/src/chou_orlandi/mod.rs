@@ -14,31 +14,68 @@ pub use error::{ReceiverError, SenderError, SenderVerifyError};
 pub use receiver::{state as receiver_state, Receiver};
 pub use sender::{state as sender_state, Sender};
 
-use blake3::Hasher;
-use curve25519_dalek::ristretto::RistrettoPoint;
-use mpz_core::Block;
+use alloc::vec::Vec;
 
-/// Hashes a ristretto point to a symmetric key
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+
+/// Returns the index of the next OT of a session, used for the key derivation tweak.
+///
+/// Each session occupies a disjoint range of indices, so keys derived with the same key pair are
+/// never derived with the same tweak. Session 0 uses the same indices as a single session.
 ///
-/// Prepending a tweak is suggested in Section 2, "Non-Malleability in Practice"
-pub(crate) fn hash_point(point: &RistrettoPoint, tweak: u128) -> Block {
-    // Compute H(tweak || point)
-    let mut h = Hasher::new();
-    h.update(&tweak.to_be_bytes());
-    h.update(point.compress().as_bytes());
-    let digest = h.finalize();
-    let digest: &[u8; 32] = digest.as_bytes();
-
-    // Copy the first 16 bytes into a Block
-    let mut block = [0u8; 16];
-    block.copy_from_slice(&digest[..16]);
-    block.into()
+/// # Arguments
+///
+/// * `session_id` - The session id.
+/// * `counter` - The number of OTs performed in the session so far.
+fn session_offset(session_id: u64, counter: usize) -> u128 {
+    ((session_id as u128) << 64) | counter as u128
+}
+
+/// Number of points which are compressed in one batch.
+const COMPRESSION_BATCH_SIZE: usize = 1024;
+
+/// Doubles and compresses points in batches.
+///
+/// Compressing a point requires a field inversion, which dominates the cost of compression.
+/// Compressing in batches shares one inversion between all points of a batch. The batched
+/// compression of curve25519-dalek only compresses doubled points, so callers compute half of
+/// each point, ie. scale the scalars by [`half`], at no additional cost.
+///
+/// # Arguments
+///
+/// * `halves` - Half of each point to compress.
+fn double_and_compress(halves: &[RistrettoPoint]) -> Vec<CompressedRistretto> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
+            use rayon::prelude::{ParallelIterator, ParallelSlice};
+
+            halves
+                .par_chunks(COMPRESSION_BATCH_SIZE)
+                .map(RistrettoPoint::double_and_compress_batch)
+                .collect::<Vec<_>>()
+                .concat()
+        } else {
+            halves
+                .chunks(COMPRESSION_BATCH_SIZE)
+                .flat_map(RistrettoPoint::double_and_compress_batch)
+                .collect()
+        }
+    }
+}
+
+/// Returns the inverse of 2, which halves a point when multiplied with it.
+fn half() -> Scalar {
+    Scalar::from(2u8).invert()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use itybity::IntoBitIterator;
+    use mpz_core::Block;
     use rstest::*;
 
     use rand::Rng;
@@ -83,6 +120,26 @@ mod tests {
         (sender, receiver)
     }
 
+    #[test]
+    fn test_double_and_compress() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let points = (0..COMPRESSION_BATCH_SIZE + 3)
+            .map(|_| RistrettoPoint::random(&mut rng))
+            .collect::<Vec<_>>();
+
+        let halves = points
+            .iter()
+            .map(|point| point * half())
+            .collect::<Vec<_>>();
+
+        let expected = points
+            .iter()
+            .map(|point| point.compress())
+            .collect::<Vec<_>>();
+
+        assert_eq!(double_and_compress(&halves), expected);
+    }
+
     #[rstest]
     fn test_ot_pass(choices: Vec<bool>, data: Vec<[Block; 2]>, expected: Vec<Block>) {
         let (mut sender, mut receiver) = setup(SenderConfig::default(), ReceiverConfig::default());
@@ -95,6 +152,19 @@ mod tests {
         assert_eq!(received_data, expected);
     }
 
+    #[rstest]
+    fn test_ot_invalid_point(choices: Vec<bool>, data: Vec<[Block; 2]>) {
+        let (mut sender, mut receiver) = setup(SenderConfig::default(), ReceiverConfig::default());
+
+        let mut receiver_payload = receiver.receive_random(&choices);
+        // A non-canonical encoding, which is not a valid point.
+        receiver_payload.blinded_choices[0] = CompressedRistretto([0xff; 32]);
+
+        let err = sender.send(&data, receiver_payload).unwrap_err();
+
+        assert!(matches!(err, SenderError::InvalidPoint));
+    }
+
     #[rstest]
     fn test_multiple_ot_pass(choices: Vec<bool>, data: Vec<[Block; 2]>, expected: Vec<Block>) {
         let (mut sender, mut receiver) = setup(SenderConfig::default(), ReceiverConfig::default());
@@ -114,6 +184,32 @@ mod tests {
         assert_eq!(received_data, expected);
     }
 
+    #[rstest]
+    fn test_ot_sessions(choices: Vec<bool>, data: Vec<[Block; 2]>, expected: Vec<Block>) {
+        let (sender_setup, sender) =
+            Sender::new_with_seed(SenderConfig::default(), SENDER_SEED).setup();
+
+        let mut sessions = [1, 2].map(|id| sender.session(id));
+        let mut receivers = [1, 2].map(|id| {
+            Receiver::new_with_seed(ReceiverConfig::default(), RECEIVER_SEED)
+                .setup_with_session(sender_setup, id)
+        });
+
+        let mut payloads = Vec::new();
+        for (sender, receiver) in sessions.iter_mut().zip(receivers.iter_mut()) {
+            let receiver_payload = receiver.receive_random(&choices);
+            let sender_payload = sender.send(&data, receiver_payload).unwrap();
+            payloads.push(sender_payload.payload.clone());
+
+            let received_data = receiver.receive(sender_payload).unwrap();
+
+            assert_eq!(received_data, expected);
+        }
+
+        // The same receiver randomness yields independent keys in each session.
+        assert_ne!(payloads[0], payloads[1]);
+    }
+
     #[rstest]
     fn test_committed_ot_receiver_pass(
         choices: Vec<bool>,
@@ -1,6 +1,9 @@
 //! Messages for the Chou-Orlandi protocol.
 
-use curve25519_dalek::RistrettoPoint;
+use alloc::vec::Vec;
+use curve25519_dalek::{ristretto::CompressedRistretto, RistrettoPoint};
+#[cfg(feature = "std")]
+use mpz_core::serialize::WireMessage;
 use mpz_core::Block;
 use serde::{Deserialize, Serialize};
 
@@ -28,7 +31,10 @@ pub struct ReceiverPayload {
     /// The transfer ID.
     pub id: TransferId,
     /// The receiver's blinded choices.
-    pub blinded_choices: Vec<RistrettoPoint>,
+    ///
+    /// The points are compressed by the receiver in batches, which is much faster than
+    /// compressing each point when it is serialized.
+    pub blinded_choices: Vec<CompressedRistretto>,
 }
 
 /// Receiver reveal message.
@@ -37,3 +43,12 @@ pub struct ReceiverReveal {
     /// The receiver's choices.
     pub choices: Vec<u8>,
 }
+
+#[cfg(feature = "std")]
+impl WireMessage for SenderSetup {}
+#[cfg(feature = "std")]
+impl WireMessage for SenderPayload {}
+#[cfg(feature = "std")]
+impl WireMessage for ReceiverPayload {}
+#[cfg(feature = "std")]
+impl WireMessage for ReceiverReveal {}
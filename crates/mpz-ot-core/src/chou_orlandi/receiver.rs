@@ -1,23 +1,28 @@
 use crate::chou_orlandi::{
-    hash_point,
+    double_and_compress, half,
     msgs::{ReceiverPayload, ReceiverReveal, SenderPayload, SenderSetup},
-    ReceiverConfig, ReceiverError,
+    session_offset, ReceiverConfig, ReceiverError,
 };
 use crate::TransferId;
+use alloc::vec::Vec;
 
-use itybity::{BitIterable, FromBitIterator, ToBits};
-use mpz_core::Block;
+use itybity::{BitIterable, ToBits};
+use mpz_core::{
+    bits::{pack_bits, BitOrder},
+    crhash::{tweak, Blake3Hash},
+    Block,
+};
 
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_TABLE,
-    ristretto::{RistrettoBasepointTable, RistrettoPoint},
+    ristretto::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint},
     scalar::Scalar,
 };
 use rand_chacha::ChaCha20Rng;
 use rand_core::SeedableRng;
 
-#[cfg(feature = "rayon")]
-use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 /// A [CO15](https://eprint.iacr.org/2015/267.pdf) receiver.
 #[derive(Debug, Default)]
@@ -83,13 +88,34 @@ impl Receiver {
     ///
     /// * `sender_setup` - The sender's setup message
     pub fn setup(self, sender_setup: SenderSetup) -> Receiver<state::Setup> {
+        self.setup_with_session(sender_setup, 0)
+    }
+
+    /// Sets up the receiver for a session of a sender which serves multiple receivers, see
+    /// [`Sender::session`](crate::chou_orlandi::Sender::session).
+    ///
+    /// Session id `0` is the session of a receiver set up with [`Receiver::setup`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sender_setup` - The sender's setup message
+    /// * `session_id` - The id of the sender's session
+    pub fn setup_with_session(
+        self,
+        sender_setup: SenderSetup,
+        session_id: u64,
+    ) -> Receiver<state::Setup> {
         let state::Initialized { rng } = self.state;
+        let sender_base_table = RistrettoBasepointTable::create(&sender_setup.public_key);
+        let sender_half_public_key = &half() * &sender_base_table;
 
         Receiver {
             config: self.config,
             state: state::Setup {
                 rng,
-                sender_base_table: RistrettoBasepointTable::create(&sender_setup.public_key),
+                sender_base_table,
+                sender_half_public_key,
+                session_id,
                 transfer_id: TransferId::default(),
                 counter: 0,
                 choice_log: Vec::default(),
@@ -109,6 +135,8 @@ impl Receiver<state::Setup> {
         let state::Setup {
             rng,
             sender_base_table,
+            sender_half_public_key,
+            session_id,
             counter,
             choice_log,
             decryption_keys: cached_decryption_keys,
@@ -120,8 +148,13 @@ impl Receiver<state::Setup> {
             .map(|_| Scalar::random(rng))
             .collect::<Vec<_>>();
 
-        let (blinded_choices, decryption_keys) =
-            compute_decryption_keys(sender_base_table, &private_keys, choices, *counter);
+        let (blinded_choices, decryption_keys) = compute_decryption_keys(
+            sender_base_table,
+            sender_half_public_key,
+            &private_keys,
+            choices,
+            session_offset(*session_id, *counter),
+        );
 
         *counter += blinded_choices.len();
         cached_decryption_keys.extend(decryption_keys);
@@ -187,59 +220,79 @@ impl Receiver<state::Setup> {
         let state::Setup { choice_log, .. } = self.state;
 
         Ok(ReceiverReveal {
-            choices: Vec::<u8>::from_lsb0_iter(choice_log),
+            choices: pack_bits(&choice_log, BitOrder::Lsb0),
         })
     }
 }
 
 /// Computes the blinded choices `B` and the decryption keys for the OT receiver.
 ///
+/// Half of each point is computed, so that the points are compressed in batches, see
+/// [`double_and_compress`].
+///
 /// # Arguments
 ///
 /// * `base_table` - A Ristretto basepoint table from the sender's public key
+/// * `half_public_key` - Half of the sender's public key
 /// * `receiver_private_keys` - The private keys of the OT receiver
 /// * `choices` - The choices of the OT receiver
-/// * `offset` - The number of decryption keys that have already been computed
-///              (used for the key derivation tweak)
+/// * `offset` - The index of the first decryption key (used for the key derivation tweak)
 fn compute_decryption_keys<T: BitIterable + Sync>(
     base_table: &RistrettoBasepointTable,
+    half_public_key: &RistrettoPoint,
     receiver_private_keys: &[Scalar],
     choices: &[T],
-    offset: usize,
-) -> (Vec<RistrettoPoint>, Vec<(bool, Block)>) {
+    offset: u128,
+) -> (Vec<CompressedRistretto>, Vec<(bool, Block)>) {
+    let half = half();
     let zero = &Scalar::ZERO * base_table;
-    // a is A in [ref1]
-    let a = &Scalar::ONE * base_table;
 
     cfg_if::cfg_if! {
-        if #[cfg(feature = "rayon")] {
+        if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
             // itybity currently doesn't support `IndexedParallelIterator` for collections,
             // so we allocate instead.
             let temp = receiver_private_keys.iter().zip(choices.iter_lsb0()).collect::<Vec<_>>();
-            let iter = temp.into_par_iter().enumerate();
+            let iter = temp.into_par_iter();
         } else {
-            let iter = receiver_private_keys.iter().zip(choices.iter_lsb0()).enumerate();
+            let iter = receiver_private_keys.iter().zip(choices.iter_lsb0());
         }
     }
 
-    iter.map(|(i, (b, c))| {
-        // blinded_choice is B in [ref1]
-        //
-        // if c = 0: B = g ^ b
-        // if c = 1: B = A * g ^ b
-        //
-        // when choice is 0, we add the zero element to keep constant time.
-        let blinded_choice = if c {
-            a + b * RISTRETTO_BASEPOINT_TABLE
-        } else {
-            zero + b * RISTRETTO_BASEPOINT_TABLE
-        };
-
-        let decryption_key = hash_point(&(b * base_table), (offset + i) as u128);
+    let (half_blinded_choices, half_keys): (Vec<_>, Vec<_>) = iter
+        .map(|(b, c)| {
+            let half_b = b * half;
+
+            // blinded_choice is B in [ref1]
+            //
+            // if c = 0: B = g ^ b
+            // if c = 1: B = A * g ^ b
+            //
+            // when choice is 0, we add the zero element to keep constant time.
+            let half_blinded_choice = if c {
+                half_public_key + &half_b * RISTRETTO_BASEPOINT_TABLE
+            } else {
+                zero + &half_b * RISTRETTO_BASEPOINT_TABLE
+            };
+
+            (half_blinded_choice, &half_b * base_table)
+        })
+        .unzip();
+
+    let blinded_choices = double_and_compress(&half_blinded_choices);
+    let decryption_keys = double_and_compress(&half_keys)
+        .iter()
+        .zip(choices.iter_lsb0())
+        .enumerate()
+        .map(|(i, (key, c))| {
+            // Prepending a tweak is suggested in Section 2, "Non-Malleability in Practice".
+            (
+                c,
+                Blake3Hash.tccr_bytes(tweak(offset + i as u128), key.as_bytes()),
+            )
+        })
+        .collect();
 
-        (blinded_choice, (c, decryption_key))
-    })
-    .unzip()
+    (blinded_choices, decryption_keys)
 }
 
 /// The receiver's state.
@@ -280,6 +333,10 @@ pub mod state {
         pub(super) rng: ChaCha20Rng,
         /// Sender's public key (precomputed table)
         pub(super) sender_base_table: RistrettoBasepointTable,
+        /// Half of the sender's public key, used to compute the blinded choices
+        pub(super) sender_half_public_key: RistrettoPoint,
+        /// Id of the sender's session.
+        pub(super) session_id: u64,
         /// Current transfer id.
         pub(super) transfer_id: TransferId,
         /// Counts how many decryption keys we've computed so far
@@ -1,29 +1,35 @@
 use crate::{
     chou_orlandi::{
-        hash_point,
+        double_and_compress, half,
         msgs::{ReceiverPayload, ReceiverReveal, SenderPayload, SenderSetup},
-        Receiver, ReceiverConfig, SenderConfig, SenderError, SenderVerifyError,
+        session_offset, Receiver, ReceiverConfig, SenderConfig, SenderError, SenderVerifyError,
     },
     TransferId,
 };
+use alloc::vec::Vec;
 
 use itybity::IntoBitIterator;
-use mpz_core::Block;
+use mpz_core::{
+    crhash::{tweak, Blake3Hash},
+    Block,
+};
 
 use curve25519_dalek::{
-    constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoPoint, scalar::Scalar,
+    constants::RISTRETTO_BASEPOINT_TABLE,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
 };
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 
-#[cfg(feature = "rayon")]
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 
 /// A tape used to record all the blinded choices made by the receiver, which
 /// can later be used to perform a consistency check.
 #[derive(Debug, Default)]
 struct Tape {
-    receiver_choices: Vec<RistrettoPoint>,
+    receiver_choices: Vec<CompressedRistretto>,
 }
 
 /// A [CO15](https://eprint.iacr.org/2015/267.pdf) sender.
@@ -104,6 +110,7 @@ impl Sender {
                 state: state::Setup {
                     private_key,
                     public_key,
+                    session_id: 0,
                     transfer_id: TransferId::default(),
                     counter: 0,
                 },
@@ -114,6 +121,53 @@ impl Sender {
 }
 
 impl Sender<state::Setup> {
+    /// Returns the setup message to be sent to a receiver of a new session, see
+    /// [`Sender::session`].
+    pub fn setup_message(&self) -> SenderSetup {
+        SenderSetup {
+            public_key: self.state.public_key,
+        }
+    }
+
+    /// Returns the id of the sender's session.
+    pub fn session_id(&self) -> u64 {
+        self.state.session_id
+    }
+
+    /// Returns a sender for a new session, using the same key pair.
+    ///
+    /// This allows a single key pair to serve multiple receivers. Each session has its own
+    /// transfer ids and tape, and keys are derived with tweaks which are distinct from any other
+    /// session. The receiver must be set up with the same session id, see
+    /// [`Receiver::setup_with_session`].
+    ///
+    /// Session id `0` is the session of the sender returned by [`Sender::setup`], so it must not
+    /// be used while that sender is in use. The caller is responsible for never reusing a
+    /// session id with the same key pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_id` - The id of the session.
+    pub fn session(&self, session_id: u64) -> Sender<state::Setup> {
+        let tape = if self.config.receiver_commit() {
+            Some(Tape::default())
+        } else {
+            None
+        };
+
+        Sender {
+            config: self.config.clone(),
+            state: state::Setup {
+                private_key: self.state.private_key,
+                public_key: self.state.public_key,
+                session_id,
+                transfer_id: TransferId::default(),
+                counter: 0,
+            },
+            tape,
+        }
+    }
+
     /// Obliviously sends `inputs` to the receiver.
     ///
     /// # Arguments
@@ -128,9 +182,9 @@ impl Sender<state::Setup> {
         let state::Setup {
             private_key,
             public_key,
+            session_id,
             transfer_id: current_id,
             counter,
-            ..
         } = &mut self.state;
 
         let ReceiverPayload {
@@ -152,14 +206,18 @@ impl Sender<state::Setup> {
             ));
         }
 
+        let mut payload = compute_encryption_keys(
+            private_key,
+            public_key,
+            &blinded_choices,
+            session_offset(*session_id, *counter),
+        )?;
+
         if let Some(tape) = self.tape.as_mut() {
             // Record the receiver's choices
             tape.receiver_choices.extend_from_slice(&blinded_choices);
         }
 
-        let mut payload =
-            compute_encryption_keys(private_key, public_key, &blinded_choices, *counter);
-
         *counter += inputs.len();
 
         // Encrypt the inputs
@@ -187,7 +245,11 @@ impl Sender<state::Setup> {
         receiver_seed: [u8; 32],
         receiver_reveal: ReceiverReveal,
     ) -> Result<Vec<bool>, SenderError> {
-        let state::Setup { public_key, .. } = self.state;
+        let state::Setup {
+            public_key,
+            session_id,
+            ..
+        } = self.state;
 
         let Some(tape) = &self.tape else {
             return Err(SenderVerifyError::TapeNotRecorded)?;
@@ -211,7 +273,7 @@ impl Sender<state::Setup> {
         // Simulate the receiver
         let receiver = Receiver::new_with_seed(ReceiverConfig::default(), receiver_seed);
 
-        let mut receiver = receiver.setup(SenderSetup { public_key });
+        let mut receiver = receiver.setup_with_session(SenderSetup { public_key }, session_id);
 
         let ReceiverPayload {
             blinded_choices, ..
@@ -228,44 +290,60 @@ impl Sender<state::Setup> {
 
 /// Computes the encryption keys for the sender.
 ///
+/// Half of each point is computed, so that the points are compressed in batches, see
+/// [`double_and_compress`].
+///
 /// # Arguments
 ///
 /// * `private_key` - The sender's private key.
 /// * `public_key` - The sender's public key.
 /// * `blinded_choices` - The receiver's blinded choices.
-/// * `offset` - The number of OTs that have already been performed
-///              (used for the key derivation tweak)
+/// * `offset` - The index of the first OT (used for the key derivation tweak)
 fn compute_encryption_keys(
     private_key: &Scalar,
     public_key: &RistrettoPoint,
-    blinded_choices: &[RistrettoPoint],
-    offset: usize,
-) -> Vec<[Block; 2]> {
+    blinded_choices: &[CompressedRistretto],
+    offset: u128,
+) -> Result<Vec<[Block; 2]>, SenderError> {
+    let half_private_key = private_key * half();
     // ys is A^a in [ref1]
-    let ys = private_key * public_key;
+    let half_ys = half_private_key * public_key;
 
     cfg_if::cfg_if! {
-        if #[cfg(feature = "rayon")] {
-            let iter = blinded_choices
-                .par_iter()
-                .enumerate();
+        if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
+            let iter = blinded_choices.par_iter();
         } else {
-            let iter = blinded_choices
-                .iter()
-                .enumerate();
+            let iter = blinded_choices.iter();
         }
     }
 
-    iter.map(|(i, blinded_choice)| {
-        // yr is B^a in [ref1]
-        let yr = private_key * blinded_choice;
-        let k0 = hash_point(&yr, (offset + i) as u128);
-        // yr - ys == (B/A)^a in [ref1]
-        let k1 = hash_point(&(yr - ys), (offset + i) as u128);
-
-        [k0, k1]
-    })
-    .collect()
+    let half_yrs: Vec<[RistrettoPoint; 2]> = iter
+        .map(|blinded_choice| {
+            let blinded_choice = blinded_choice
+                .decompress()
+                .ok_or(SenderError::InvalidPoint)?;
+
+            // yr is B^a in [ref1], and yr - ys == (B/A)^a in [ref1]
+            let half_yr = half_private_key * blinded_choice;
+
+            Ok([half_yr, half_yr - half_ys])
+        })
+        .collect::<Result<_, SenderError>>()?;
+
+    let half_yrs: Vec<RistrettoPoint> = half_yrs.into_iter().flatten().collect();
+    let keys = double_and_compress(&half_yrs);
+
+    Ok(keys
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, keys)| {
+            let tweak = tweak(offset + i as u128);
+            [
+                Blake3Hash.tccr_bytes(tweak, keys[0].as_bytes()),
+                Blake3Hash.tccr_bytes(tweak, keys[1].as_bytes()),
+            ]
+        })
+        .collect())
 }
 
 /// The sender's state.
@@ -294,6 +372,13 @@ pub mod state {
 
     opaque_debug::implement!(Initialized);
 
+    #[cfg(feature = "zeroize")]
+    impl Drop for Initialized {
+        fn drop(&mut self) {
+            zeroize::Zeroize::zeroize(&mut self.private_key);
+        }
+    }
+
     impl Default for Initialized {
         fn default() -> Self {
             let mut rng = ChaCha20Rng::from_entropy();
@@ -312,6 +397,8 @@ pub mod state {
         pub(super) private_key: Scalar,
         // The public_key is `A == g^a` in [ref1]
         pub(super) public_key: RistrettoPoint,
+        /// Id of the session.
+        pub(super) session_id: u64,
         /// Current transfer id.
         pub(super) transfer_id: TransferId,
         /// Number of OTs sent so far
@@ -321,4 +408,11 @@ pub mod state {
     impl State for Setup {}
 
     opaque_debug::implement!(Setup);
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for Setup {
+        fn drop(&mut self) {
+            zeroize::Zeroize::zeroize(&mut self.private_key);
+        }
+    }
 }
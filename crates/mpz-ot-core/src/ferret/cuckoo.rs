@@ -49,28 +49,44 @@ impl CuckooHash {
     /// * `alphas` - A u32 vector being inserted.
     #[inline]
     pub fn insert(&self, alphas: &[u32]) -> Result<Vec<Option<Item>>, CuckooHashError> {
+        self.insert_with_evictions(alphas).map(|(table, _)| table)
+    }
+
+    /// Insert elements into a Cuckoo hash table, also returning the number of evictions which
+    /// occurred during the insertion.
+    ///
+    /// * Argument
+    ///
+    /// * `alphas` - A u32 vector being inserted.
+    #[inline]
+    pub fn insert_with_evictions(
+        &self,
+        alphas: &[u32],
+    ) -> Result<(Vec<Option<Item>>, usize), CuckooHashError> {
         // Always sets m = 1.5 * t. t is the length of `alphas`.
         let m = compute_table_length(alphas.len() as u32);
 
         // Allocates table.
         let mut table = vec![None; m];
+        let mut evictions = 0;
         // Inserts each alpha.
         for &value in alphas {
-            self.hash(&mut table, value)?
+            evictions += self.hash(&mut table, value)?;
         }
-        Ok(table)
+        Ok((table, evictions))
     }
 
-    // Hash an element to a position with the current hash function.
+    // Hash an element to a position with the current hash function, returning the number of
+    // evicted elements.
     #[inline]
-    fn hash(&self, table: &mut [Option<Item>], value: u32) -> Result<(), CuckooHashError> {
+    fn hash(&self, table: &mut [Option<Item>], value: u32) -> Result<usize, CuckooHashError> {
         // The item consists of the value and hash index, starting from 0.
         let mut item = Item {
             value,
             hash_index: 0,
         };
 
-        for _ in 0..CUCKOO_TRIAL_NUM {
+        for evictions in 0..CUCKOO_TRIAL_NUM {
             // Computes the position of the value.
             let pos = hash_to_index(&self.hashes[item.hash_index], table.len(), item.value);
 
@@ -83,7 +99,7 @@ impl CuckooHash {
                 item.hash_index = (item.hash_index + 1) % CUCKOO_HASH_NUM;
             } else {
                 // If no value assigned to position `pos`, end the process.
-                return Ok(());
+                return Ok(evictions);
             }
         }
         Err(CuckooHashError)
@@ -1,5 +1,7 @@
 //! Errors that can occur when using the Ferret protocol.
 
+use crate::ErrorKind;
+
 /// Errors that can occur when using the Ferret sender.
 #[derive(Debug, thiserror::Error)]
 #[error("invalid input: expected {0}")]
@@ -9,3 +11,27 @@ pub struct SenderError(pub String);
 #[derive(Debug, thiserror::Error)]
 #[error("invalid input: expected {0}")]
 pub struct ReceiverError(pub String);
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::CallerBug
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::CallerBug
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
@@ -6,6 +6,7 @@ pub mod cuckoo;
 pub mod error;
 pub mod mpcot;
 pub mod msgs;
+pub mod output;
 pub mod receiver;
 pub mod sender;
 pub mod spcot;
@@ -125,7 +126,7 @@ mod tests {
         let msgs = sender.extend(&s).unwrap();
         let (choices, received) = receiver.extend(&r).unwrap();
 
-        assert_cot(delta, &choices, &msgs, &received);
+        assert_cot(delta, &choices.to_bits(), &msgs, &received);
 
         // extend twice
         let _ = sender.get_mpcot_query();
@@ -137,6 +138,28 @@ mod tests {
         let msgs = sender.extend(&s).unwrap();
         let (choices, received) = receiver.extend(&r).unwrap();
 
-        assert_cot(delta, &choices, &msgs, &received);
+        assert_cot(delta, &choices.to_bits(), &msgs, &received);
+
+        // extend with the LPN expansion computed ahead of time
+        let sender_expansion = sender.pre_expand();
+        let receiver_expansion = receiver.pre_expand();
+
+        sender.set_expansion(sender_expansion()).unwrap();
+        receiver.set_expansion(receiver_expansion()).unwrap();
+
+        let _ = sender.get_mpcot_query();
+        let query = receiver.get_mpcot_query();
+
+        let (MPCOTSenderOutput { s, .. }, MPCOTReceiverOutput { r, .. }) =
+            ideal_mpcot.extend(&query.0, query.1);
+
+        let sender_expansion = sender.pre_expand();
+        let msgs = sender.extend(&s).unwrap();
+        let (choices, received) = receiver.extend(&r).unwrap();
+
+        assert_cot(delta, &choices.to_bits(), &msgs, &received);
+
+        // A stale expansion is rejected.
+        assert!(sender.set_expansion(sender_expansion()).is_err());
     }
 }
@@ -1,6 +1,10 @@
 //! Errors that can occur when using the MPCOT protocol.
 
-use crate::ferret::cuckoo::{BucketError, CuckooHashError};
+use crate::{
+    ferret::cuckoo::{BucketError, CuckooHashError},
+    ErrorKind,
+};
+
 /// Errors that can occur when using the MPCOT sender.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
@@ -22,3 +26,35 @@ pub enum ReceiverError {
     #[error(transparent)]
     BucketError(#[from] BucketError),
 }
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::InvalidInput(_) => ErrorKind::CallerBug,
+            SenderError::BucketError(_) => ErrorKind::ProtocolAbort,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::InvalidInput(_) => ErrorKind::CallerBug,
+            ReceiverError::CuckooHashError(_) | ReceiverError::BucketError(_) => {
+                ErrorKind::ProtocolAbort
+            }
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
@@ -6,13 +6,16 @@ pub mod receiver;
 pub mod receiver_regular;
 pub mod sender;
 pub mod sender_regular;
+pub mod stats;
 
 #[cfg(test)]
 mod tests {
     use super::{
         receiver::Receiver as MpcotReceiver, receiver_regular::Receiver as RegularReceiver,
         sender::Sender as MpcotSender, sender_regular::Sender as RegularSender,
+        stats::HashingStats,
     };
+    use crate::ferret::CUCKOO_HASH_NUM;
     use crate::ideal::spcot::IdealSpcot;
     use crate::{SPCOTReceiverOutput, SPCOTSenderOutput};
     use mpz_core::prg::Prg;
@@ -94,6 +97,50 @@ mod tests {
         assert_eq!(output_sender, output_receiver);
     }
 
+    #[test]
+    fn mpcot_hashing_stats_test() {
+        let mut prg = Prg::from_seed([3u8; 16].into());
+        let delta = prg.random_block();
+
+        let (receiver_pre, hash_seed) = MpcotReceiver::new().setup(prg.random_block());
+        let sender_pre = MpcotSender::new().setup(delta, hash_seed);
+
+        let alphas = [0, 1, 3, 4, 2, 12, 31, 17];
+        let n = 32;
+
+        let (_, sender_queries, sender_stats) = sender_pre
+            .pre_extend_with_stats(alphas.len() as u32, n)
+            .unwrap();
+        let (_, queries, receiver_stats) = receiver_pre.pre_extend_with_stats(&alphas, n).unwrap();
+
+        assert_eq!(sender_stats.bucket_count, sender_queries.len());
+        assert_eq!(
+            sender_stats.occupancy.iter().sum::<usize>(),
+            sender_stats.bucket_count
+        );
+        assert_eq!(sender_stats.item_count, CUCKOO_HASH_NUM * n as usize);
+        assert_eq!(
+            sender_stats.padded_len,
+            queries.iter().map(|(h, _)| 1 << h).sum::<usize>()
+        );
+        assert_eq!(
+            sender_stats.spcot_depth,
+            sender_queries.iter().sum::<usize>()
+        );
+        assert!(sender_stats.padding() >= sender_stats.bucket_count);
+        assert!(sender_stats.evictions.is_none());
+
+        // Both parties see the same buckets, only the receiver knows the evictions.
+        assert!(receiver_stats.evictions.is_some());
+        assert_eq!(
+            HashingStats {
+                evictions: None,
+                ..receiver_stats
+            },
+            sender_stats
+        );
+    }
+
     #[test]
     fn mpcot_regular_test() {
         let mut prg = Prg::from_seed([2u8; 16].into());
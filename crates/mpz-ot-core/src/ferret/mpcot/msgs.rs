@@ -1,7 +1,7 @@
 //! Messages for the MPCOT protocol.
 
-use mpz_core::Block;
-use serde::{Deserialize, Serialize};
+use mpz_core::{serialize::WireMessage, Block};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 /// An MPCOT message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,3 +17,6 @@ pub struct HashSeed {
     /// The seed.
     pub seed: Block,
 }
+
+impl<SpcotMsg> WireMessage for Message<SpcotMsg> where SpcotMsg: Serialize + DeserializeOwned {}
+impl WireMessage for HashSeed {}
@@ -9,7 +9,7 @@ use crate::ferret::{
 use mpz_core::{aes::AesEncryptor, prg::Prg, Block};
 use rand_core::SeedableRng;
 
-use super::msgs::HashSeed;
+use super::{msgs::HashSeed, stats::HashingStats};
 
 /// MPCOT receiver.
 #[derive(Debug, Default)]
@@ -64,6 +64,24 @@ impl Receiver<state::PreExtension> {
         alphas: &[u32],
         n: u32,
     ) -> Result<(Receiver<state::Extension>, Vec<(usize, u32)>), ReceiverError> {
+        self.pre_extend_with_stats(alphas, n)
+            .map(|(receiver, p, _)| (receiver, p))
+    }
+
+    /// Performs the hash procedure in MPCOT extension, see [`Receiver::pre_extend`].
+    ///
+    /// Also outputs the statistics of the hashing procedure.
+    ///
+    /// # Arguments
+    ///
+    /// * `alphas` - The queried indices.
+    /// * `n` - The total number of indices.
+    #[allow(clippy::type_complexity)]
+    pub fn pre_extend_with_stats(
+        self,
+        alphas: &[u32],
+        n: u32,
+    ) -> Result<(Receiver<state::Extension>, Vec<(usize, u32)>, HashingStats), ReceiverError> {
         if alphas.len() as u32 > n {
             return Err(ReceiverError::InvalidInput(
                 "length of alphas should not exceed n".to_string(),
@@ -72,7 +90,7 @@ impl Receiver<state::PreExtension> {
         let cuckoo = CuckooHash::new(self.state.hashes.clone());
 
         // Inserts all the alpha's.
-        let table = cuckoo.insert(alphas)?;
+        let (table, evictions) = cuckoo.insert_with_evictions(alphas)?;
 
         let m = table.len();
 
@@ -103,6 +121,8 @@ impl Receiver<state::PreExtension> {
             buckets_length.push(power_of_two);
         }
 
+        let stats = HashingStats::new(&buckets, &buckets_length, Some(evictions));
+
         let receiver = Receiver {
             state: state::Extension {
                 counter: self.state.counter,
@@ -114,7 +134,7 @@ impl Receiver<state::PreExtension> {
             },
         };
 
-        Ok((receiver, p))
+        Ok((receiver, p, stats))
     }
 }
 impl Receiver<state::Extension> {
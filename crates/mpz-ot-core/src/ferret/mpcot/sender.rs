@@ -9,7 +9,7 @@ use crate::ferret::{
 use mpz_core::{aes::AesEncryptor, prg::Prg, Block};
 use rand_core::SeedableRng;
 
-use super::msgs::HashSeed;
+use super::{msgs::HashSeed, stats::HashingStats};
 
 /// MPCOT sender.
 #[derive(Debug, Default)]
@@ -60,6 +60,25 @@ impl Sender<state::PreExtension> {
         t: u32,
         n: u32,
     ) -> Result<(Sender<state::Extension>, Vec<usize>), SenderError> {
+        self.pre_extend_with_stats(t, n)
+            .map(|(sender, bs, _)| (sender, bs))
+    }
+
+    /// Performs the hash procedure in MPCOT extension, see [`Sender::pre_extend`].
+    ///
+    /// Also outputs the statistics of the hashing procedure, without the number of Cuckoo hash
+    /// evictions which are only known to the receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `t` - The number of queried indices.
+    /// * `n` - The total number of indices.
+    #[allow(clippy::type_complexity)]
+    pub fn pre_extend_with_stats(
+        self,
+        t: u32,
+        n: u32,
+    ) -> Result<(Sender<state::Extension>, Vec<usize>, HashingStats), SenderError> {
         if t > n {
             return Err(SenderError::InvalidInput(
                 "t should not exceed n".to_string(),
@@ -85,6 +104,8 @@ impl Sender<state::PreExtension> {
             buckets_length.push(power_of_two);
         }
 
+        let stats = HashingStats::new(&buckets, &buckets_length, None);
+
         let sender = Sender {
             state: state::Extension {
                 delta: self.state.delta,
@@ -97,7 +118,7 @@ impl Sender<state::PreExtension> {
             },
         };
 
-        Ok((sender, bs))
+        Ok((sender, bs, stats))
     }
 }
 
@@ -0,0 +1,64 @@
+//! Statistics of the hashing procedure in MPCOT extension.
+
+use crate::ferret::cuckoo::Item;
+
+/// Statistics of the hashing procedure of an MPCOT extension.
+///
+/// The indices are hashed into buckets, and each bucket is padded to a power of 2 for SPCOT. These
+/// statistics show how the SPCOT extensions, and thus the bandwidth, are distributed over the
+/// buckets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashingStats {
+    /// Number of buckets, i.e., the number of SPCOT extensions.
+    pub bucket_count: usize,
+    /// Distribution of the bucket occupancy, the `i`-th entry is the number of buckets which
+    /// contain `i` items.
+    pub occupancy: Vec<usize>,
+    /// Number of evictions during the Cuckoo hash insertion of the queried indices.
+    ///
+    /// This is only known to the receiver.
+    pub evictions: Option<usize>,
+    /// Total number of items in the buckets.
+    pub item_count: usize,
+    /// Total length of the buckets after padding, i.e., the number of SPCOT outputs.
+    pub padded_len: usize,
+    /// Total depth of the GGM trees, i.e., the number of COTs consumed by the SPCOT extensions
+    /// excluding the consistency check.
+    pub spcot_depth: usize,
+}
+
+impl HashingStats {
+    /// Computes the statistics of the buckets.
+    pub(crate) fn new(
+        buckets: &[Vec<Item>],
+        buckets_length: &[usize],
+        evictions: Option<usize>,
+    ) -> Self {
+        let mut occupancy = Vec::new();
+        for bin in buckets {
+            if occupancy.len() <= bin.len() {
+                occupancy.resize(bin.len() + 1, 0);
+            }
+            occupancy[bin.len()] += 1;
+        }
+
+        Self {
+            bucket_count: buckets.len(),
+            occupancy,
+            evictions,
+            item_count: buckets.iter().map(Vec::len).sum(),
+            padded_len: buckets_length.iter().sum(),
+            spcot_depth: buckets_length.iter().map(|len| len.ilog2() as usize).sum(),
+        }
+    }
+
+    /// Returns the number of SPCOT outputs which are padding, i.e., not used by any item.
+    pub fn padding(&self) -> usize {
+        self.padded_len - self.item_count
+    }
+
+    /// Returns the ratio of the padded length to the number of items.
+    pub fn padding_overhead(&self) -> f64 {
+        self.padded_len as f64 / self.item_count as f64
+    }
+}
@@ -1,6 +1,6 @@
 //! Ferret protocol messages.
 
-use mpz_core::Block;
+use mpz_core::{serialize::WireMessage, Block};
 use serde::{Deserialize, Serialize};
 
 /// The seed to generate Lpn matrix.
@@ -9,3 +9,5 @@ pub struct LpnMatrixSeed {
     /// The seed.
     pub seed: Block,
 }
+
+impl WireMessage for LpnMatrixSeed {}
@@ -0,0 +1,284 @@
+//! Outputs of the Ferret receiver.
+//!
+//! An extension outputs millions of COTs, which are consumed in much smaller batches, eg. one
+//! batch per garbled circuit input. The choice bits are kept packed, 8 per byte in LSB0 order, and
+//! a [`ReceiverOutputBuffer`] hands out batches of exactly the requested size, so consumers do not
+//! need to repack them.
+
+use mpz_core::{
+    bits::{unpack_bits_into, BitOrder},
+    Block, MacBlock,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::ferret::error::ReceiverError;
+
+/// Packed choice bits, 8 per byte in LSB0 order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChoiceBits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl ChoiceBits {
+    /// Creates new choice bits from bits.
+    pub fn from_bits(bits: &[bool]) -> Self {
+        Self {
+            bytes: mpz_core::bits::pack_bits(bits, BitOrder::Lsb0),
+            len: bits.len(),
+        }
+    }
+
+    /// Creates new choice bits from the least significant bits of blocks.
+    pub fn from_lsbs(blocks: &[Block]) -> Self {
+        let bytes = blocks
+            .chunks(8)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, block)| byte | ((block.lsb() as u8) << i))
+            })
+            .collect();
+
+        Self {
+            bytes,
+            len: blocks.len(),
+        }
+    }
+
+    /// Returns the number of choice bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no choice bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the packed bytes.
+    ///
+    /// The unused bits of the last byte are zero.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the choice bit at an index, or `None` if it is out of bounds.
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        (idx < self.len).then(|| (self.bytes[idx / 8] >> (idx % 8)) & 1 == 1)
+    }
+
+    /// Returns the unpacked choice bits.
+    pub fn to_bits(&self) -> Vec<bool> {
+        let mut bits = vec![false; self.len];
+        unpack_bits_into(&self.bytes, BitOrder::Lsb0, &mut bits);
+        bits
+    }
+
+    /// Returns `len` choice bits starting at `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    fn range(&self, start: usize, len: usize) -> Self {
+        assert!(start + len <= self.len, "range out of bounds");
+
+        let (offset, shift) = (start / 8, start % 8);
+        let byte_len = len.div_ceil(8);
+        let mut bytes: Vec<u8> = if shift == 0 {
+            self.bytes[offset..offset + byte_len].to_vec()
+        } else {
+            (offset..offset + byte_len)
+                .map(|i| {
+                    let next = self.bytes.get(i + 1).copied().unwrap_or(0);
+                    (self.bytes[i] >> shift) | (next << (8 - shift))
+                })
+                .collect()
+        };
+
+        clear_padding(&mut bytes, len);
+
+        Self { bytes, len }
+    }
+
+    /// Appends choice bits.
+    fn append(&mut self, other: &ChoiceBits) {
+        let shift = self.len % 8;
+        if shift == 0 {
+            self.bytes.extend_from_slice(&other.bytes);
+        } else {
+            for byte in &other.bytes {
+                *self.bytes.last_mut().expect("bytes should not be empty") |= byte << shift;
+                self.bytes.push(byte >> (8 - shift));
+            }
+        }
+
+        self.len += other.len;
+        self.bytes.truncate(self.len.div_ceil(8));
+        clear_padding(&mut self.bytes, self.len);
+    }
+}
+
+/// Clears the unused bits of the last byte.
+fn clear_padding(bytes: &mut [u8], len: usize) {
+    if !len.is_multiple_of(8) {
+        if let Some(last) = bytes.last_mut() {
+            *last &= (1u8 << (len % 8)) - 1;
+        }
+    }
+}
+
+/// A buffer of the COTs output by the Ferret receiver.
+///
+/// The outputs of extensions are pushed to the buffer, and consumed in batches of any size with
+/// [`ReceiverOutputBuffer::take`].
+#[derive(Debug, Default)]
+pub struct ReceiverOutputBuffer {
+    choices: ChoiceBits,
+    msgs: Vec<MacBlock>,
+    /// The number of consumed COTs at the front of the buffer.
+    consumed: usize,
+}
+
+impl ReceiverOutputBuffer {
+    /// Creates a new buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of available COTs.
+    pub fn available(&self) -> usize {
+        self.msgs.len() - self.consumed
+    }
+
+    /// Pushes the output of an extension to the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `choices` - The choice bits.
+    /// * `msgs` - The received messages.
+    pub fn push(&mut self, choices: ChoiceBits, msgs: Vec<Block>) -> Result<(), ReceiverError> {
+        if choices.len() != msgs.len() {
+            return Err(ReceiverError(format!(
+                "{} choice bits, got {}",
+                msgs.len(),
+                choices.len()
+            )));
+        }
+
+        self.compact();
+        self.choices.append(&choices);
+        self.msgs.extend(MacBlock::from_block_vec(msgs));
+
+        Ok(())
+    }
+
+    /// Takes the next `count` COTs, returning their choice bits and MACs.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of COTs.
+    pub fn take(&mut self, count: usize) -> Result<(ChoiceBits, Vec<MacBlock>), ReceiverError> {
+        if count > self.available() {
+            return Err(ReceiverError(format!(
+                "at most {} COTs to be taken, got {}",
+                self.available(),
+                count
+            )));
+        }
+
+        let start = self.consumed;
+        self.consumed += count;
+
+        let choices = self.choices.range(start, count);
+        let msgs = self.msgs[start..start + count].to_vec();
+
+        // Compacting when half of the buffer is consumed keeps the cost of compaction linear in
+        // the number of consumed COTs.
+        if self.consumed * 2 >= self.msgs.len() {
+            self.compact();
+        }
+
+        Ok((choices, msgs))
+    }
+
+    /// Removes the consumed COTs from the buffer.
+    fn compact(&mut self) {
+        if self.consumed == 0 {
+            return;
+        }
+
+        self.choices = self.choices.range(self.consumed, self.available());
+        self.msgs.drain(..self.consumed);
+        self.consumed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_choice_bits() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let bits: Vec<bool> = (0..100).map(|_| rng.gen()).collect();
+        let blocks: Vec<Block> = bits
+            .iter()
+            .map(|&bit| {
+                let mut bytes: [u8; 16] = rng.gen();
+                bytes[0] = (bytes[0] & !1) | bit as u8;
+                Block::from(bytes)
+            })
+            .collect();
+
+        let choices = ChoiceBits::from_bits(&bits);
+
+        assert_eq!(ChoiceBits::from_lsbs(&blocks), choices);
+        assert_eq!(choices.to_bits(), bits);
+        assert_eq!(choices.get(99), Some(bits[99]));
+        assert_eq!(choices.get(100), None);
+
+        for (start, len) in [(0, 100), (3, 50), (8, 17), (13, 87)] {
+            assert_eq!(
+                choices.range(start, len),
+                ChoiceBits::from_bits(&bits[start..start + len])
+            );
+        }
+    }
+
+    #[test]
+    fn test_receiver_output_buffer() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let mut buffer = ReceiverOutputBuffer::new();
+
+        let mut bits = Vec::new();
+        let mut msgs = Vec::new();
+        for len in [37, 100] {
+            let ext_bits: Vec<bool> = (0..len).map(|_| rng.gen()).collect();
+            let ext_msgs = Block::random_vec(&mut rng, len);
+
+            buffer
+                .push(ChoiceBits::from_bits(&ext_bits), ext_msgs.clone())
+                .unwrap();
+
+            bits.extend(ext_bits);
+            msgs.extend(ext_msgs);
+        }
+
+        let mut start = 0;
+        for count in [5, 64, 1, 40] {
+            let (choices, macs) = buffer.take(count).unwrap();
+
+            assert_eq!(choices.to_bits(), bits[start..start + count]);
+            assert_eq!(MacBlock::as_blocks(&macs), &msgs[start..start + count]);
+            start += count;
+        }
+
+        assert_eq!(buffer.available(), 27);
+        assert!(buffer.take(28).is_err());
+    }
+}
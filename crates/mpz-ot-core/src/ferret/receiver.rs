@@ -4,7 +4,7 @@ use mpz_core::{
     Block,
 };
 
-use crate::ferret::{error::ReceiverError, LpnType};
+use crate::ferret::{error::ReceiverError, output::ChoiceBits, LpnType};
 
 use super::msgs::LpnMatrixSeed;
 
@@ -59,6 +59,7 @@ impl Receiver {
                     u: u.to_vec(),
                     w: w.to_vec(),
                     e: Vec::default(),
+                    expansion: None,
                 },
             },
             LpnMatrixSeed { seed },
@@ -92,40 +93,95 @@ impl Receiver<state::Extension> {
         (alphas, self.state.lpn_parameters.n)
     }
 
+    /// Returns a task which computes the LPN expansion for the next extension.
+    ///
+    /// The expansions `A * w` and `A * u` only depend on the output of the previous extension, so
+    /// the task can run in the background, eg. while the COTs of the current extension are
+    /// consumed. Its output is passed to [`Receiver::set_expansion`].
+    ///
+    /// Note that the expansion holds `2n` blocks until the next extension.
+    pub fn pre_expand(&self) -> impl FnOnce() -> ReceiverExpansion + Send + 'static {
+        let counter = self.state.counter;
+        let n = self.state.lpn_parameters.n;
+        let lpn_encoder = self.state.lpn_encoder.clone();
+        let u = self.state.u.clone();
+        let w = self.state.w.clone();
+
+        move || {
+            let mut z = vec![Block::ZERO; n];
+            lpn_encoder.compute(&mut z, &w);
+
+            let u_block = u
+                .iter()
+                .map(|x| if *x { Block::ONE } else { Block::ZERO })
+                .collect::<Vec<Block>>();
+            let mut x = vec![Block::ZERO; n];
+            lpn_encoder.compute(&mut x, &u_block);
+
+            ReceiverExpansion { counter, z, x }
+        }
+    }
+
+    /// Sets the precomputed LPN expansion for the next extension, see [`Receiver::pre_expand`].
+    ///
+    /// # Arguments
+    ///
+    /// * `expansion` - The expansion.
+    pub fn set_expansion(&mut self, expansion: ReceiverExpansion) -> Result<(), ReceiverError> {
+        if expansion.counter != self.state.counter {
+            return Err(ReceiverError(format!(
+                "an expansion for extension {}, got {}",
+                self.state.counter, expansion.counter
+            )));
+        }
+
+        self.state.expansion = Some((expansion.z, expansion.x));
+
+        Ok(())
+    }
+
     /// Performs the Ferret extension.
-    /// Outputs exactly l = n - t COTs.
+    /// Outputs exactly l = n - t COTs, with the choice bits packed.
     ///
     /// See step 5 and 6.
     ///
     /// # Arguments.
     ///
     /// * `r` - The vector received from the MPCOT protocol.
-    pub fn extend(&mut self, r: &[Block]) -> Result<(Vec<bool>, Vec<Block>), ReceiverError> {
+    pub fn extend(&mut self, r: &[Block]) -> Result<(ChoiceBits, Vec<Block>), ReceiverError> {
         if r.len() != self.state.lpn_parameters.n {
             return Err(ReceiverError("the length of r should be n".to_string()));
         }
 
-        // Compute z = A * w + r.
-        let mut z = r.to_vec();
-        self.state.lpn_encoder.compute(&mut z, &self.state.w);
+        let (mut z, x) = if let Some((mut z, mut x)) = self.state.expansion.take() {
+            // Compute z = A * w + r.
+            z.iter_mut().zip(r).for_each(|(z, r)| *z ^= *r);
+            // Compute x = A * u + e.
+            x.iter_mut().zip(&self.state.e).for_each(|(x, e)| *x ^= *e);
+            (z, x)
+        } else {
+            // Compute z = A * w + r.
+            let mut z = r.to_vec();
+            self.state.lpn_encoder.compute(&mut z, &self.state.w);
 
-        // Compute x = A * u + e.
-        let u_block = self
-            .state
-            .u
-            .iter()
-            .map(|x| if *x { Block::ONE } else { Block::ZERO })
-            .collect::<Vec<Block>>();
-        let mut x = self.state.e.clone();
-        self.state.lpn_encoder.compute(&mut x, &u_block);
+            // Compute x = A * u + e.
+            let u_block = self
+                .state
+                .u
+                .iter()
+                .map(|x| if *x { Block::ONE } else { Block::ZERO })
+                .collect::<Vec<Block>>();
+            let mut x = self.state.e.clone();
+            self.state.lpn_encoder.compute(&mut x, &u_block);
+            (z, x)
+        };
 
-        let mut x = x.iter().map(|a| a.lsb() == 1).collect::<Vec<bool>>();
-
-        let x_ = x.split_off(self.state.lpn_parameters.k);
-        let z_ = z.split_off(self.state.lpn_parameters.k);
+        let k = self.state.lpn_parameters.k;
+        let x_ = ChoiceBits::from_lsbs(&x[k..]);
+        let z_ = z.split_off(k);
 
         // Update u, w
-        self.state.u = x;
+        self.state.u = x[..k].iter().map(|a| a.lsb() == 1).collect();
         self.state.w = z;
 
         // Update counter
@@ -135,6 +191,18 @@ impl Receiver<state::Extension> {
     }
 }
 
+/// An LPN expansion precomputed for the next extension, see [`Receiver::pre_expand`].
+pub struct ReceiverExpansion {
+    /// The extension the expansion was computed for.
+    counter: usize,
+    /// `A * w`.
+    z: Vec<Block>,
+    /// `A * u`.
+    x: Vec<Block>,
+}
+
+opaque_debug::implement!(ReceiverExpansion);
+
 /// The receiver's state.
 pub mod state {
     use super::*;
@@ -176,9 +244,26 @@ pub mod state {
 
         /// Receiver's lpn error vector.
         pub(super) e: Vec<Block>,
+        /// Precomputed LPN expansions `A * w` and `A * u` for the next extension.
+        pub(super) expansion: Option<(Vec<Block>, Vec<Block>)>,
     }
 
     impl State for Extension {}
 
     opaque_debug::implement!(Extension);
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for Extension {
+        fn drop(&mut self) {
+            use zeroize::Zeroize;
+
+            self.u.zeroize();
+            self.w.zeroize();
+            self.e.zeroize();
+            if let Some((z, x)) = &mut self.expansion {
+                z.zeroize();
+                x.zeroize();
+            }
+        }
+    }
 }
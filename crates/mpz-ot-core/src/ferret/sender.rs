@@ -54,6 +54,7 @@ impl Sender {
                 lpn_type,
                 lpn_encoder,
                 v: v.to_vec(),
+                expansion: None,
             },
         })
     }
@@ -70,6 +71,45 @@ impl Sender<state::Extension> {
         )
     }
 
+    /// Returns a task which computes the LPN expansion for the next extension.
+    ///
+    /// The expansion `A * v` only depends on the output of the previous extension, so the task
+    /// can run in the background, eg. while the COTs of the current extension are consumed. Its
+    /// output is passed to [`Sender::set_expansion`].
+    ///
+    /// Note that the expansion holds `n` blocks until the next extension.
+    pub fn pre_expand(&self) -> impl FnOnce() -> SenderExpansion + Send + 'static {
+        let counter = self.state.counter;
+        let n = self.state.lpn_parameters.n;
+        let lpn_encoder = self.state.lpn_encoder.clone();
+        let v = self.state.v.clone();
+
+        move || {
+            let mut y = vec![Block::ZERO; n];
+            lpn_encoder.compute(&mut y, &v);
+
+            SenderExpansion { counter, y }
+        }
+    }
+
+    /// Sets the precomputed LPN expansion for the next extension, see [`Sender::pre_expand`].
+    ///
+    /// # Arguments
+    ///
+    /// * `expansion` - The expansion.
+    pub fn set_expansion(&mut self, expansion: SenderExpansion) -> Result<(), SenderError> {
+        if expansion.counter != self.state.counter {
+            return Err(SenderError(format!(
+                "an expansion for extension {}, got {}",
+                self.state.counter, expansion.counter
+            )));
+        }
+
+        self.state.expansion = Some(expansion.y);
+
+        Ok(())
+    }
+
     /// Performs the Ferret extension.
     /// Outputs exactly l = n-t COTs.
     ///
@@ -84,8 +124,14 @@ impl Sender<state::Extension> {
         }
 
         // Compute y = A * v + s
-        let mut y = s.to_vec();
-        self.state.lpn_encoder.compute(&mut y, &self.state.v);
+        let mut y = if let Some(mut y) = self.state.expansion.take() {
+            y.iter_mut().zip(s).for_each(|(y, s)| *y ^= *s);
+            y
+        } else {
+            let mut y = s.to_vec();
+            self.state.lpn_encoder.compute(&mut y, &self.state.v);
+            y
+        };
 
         let y_ = y.split_off(self.state.lpn_parameters.k);
 
@@ -99,6 +145,16 @@ impl Sender<state::Extension> {
     }
 }
 
+/// An LPN expansion precomputed for the next extension, see [`Sender::pre_expand`].
+pub struct SenderExpansion {
+    /// The extension the expansion was computed for.
+    counter: usize,
+    /// `A * v`.
+    y: Vec<Block>,
+}
+
+opaque_debug::implement!(SenderExpansion);
+
 /// The sender's state.
 pub mod state {
     use super::*;
@@ -141,9 +197,22 @@ pub mod state {
 
         /// Sender's COT message in the setup phase.
         pub(super) v: Vec<Block>,
+        /// Precomputed LPN expansion for the next extension.
+        pub(super) expansion: Option<Vec<Block>>,
     }
 
     impl State for Extension {}
 
     opaque_debug::implement!(Extension);
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for Extension {
+        fn drop(&mut self) {
+            use zeroize::Zeroize;
+
+            self.delta.zeroize();
+            self.v.zeroize();
+            self.expansion.zeroize();
+        }
+    }
 }
@@ -1,11 +1,15 @@
 //! Errors that can occur when using the SPCOT.
 
+use crate::ErrorKind;
+
 /// Errors that can occur when using the SPCOT sender.
 #[derive(Debug, thiserror::Error)]
 #[allow(missing_docs)]
 pub enum SenderError {
     #[error("invalid state: expected {0}")]
     InvalidState(String),
+    #[error("invalid input: expected {0}")]
+    InvalidInput(String),
     #[error("invalid length: expected {0}")]
     InvalidLength(String),
 }
@@ -23,3 +27,34 @@ pub enum ReceiverError {
     #[error("consistency check failed")]
     ConsistencyCheckFailed,
 }
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::InvalidState(_) | SenderError::InvalidInput(_) => ErrorKind::CallerBug,
+            SenderError::InvalidLength(_) => ErrorKind::ProtocolAbort,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::InvalidState(_) | ReceiverError::InvalidInput(_) => ErrorKind::CallerBug,
+            ReceiverError::InvalidLength(_) => ErrorKind::ProtocolAbort,
+            ReceiverError::ConsistencyCheckFailed => ErrorKind::PeerMisbehavior,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
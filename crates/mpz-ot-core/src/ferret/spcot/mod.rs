@@ -7,9 +7,11 @@ pub mod sender;
 
 #[cfg(test)]
 mod tests {
-    use mpz_core::prg::Prg;
+    use mpz_core::{prg::Prg, Block};
 
-    use super::{receiver::Receiver as SpcotReceiver, sender::Sender as SpcotSender};
+    use super::{
+        error::ReceiverError, receiver::Receiver as SpcotReceiver, sender::Sender as SpcotSender,
+    };
     use crate::{ferret::CSP, ideal::cot::IdealCOT, RCOTReceiverOutput, RCOTSenderOutput};
 
     #[test]
@@ -87,4 +89,99 @@ mod tests {
                 vs == ws
             }));
     }
+
+    #[test]
+    fn spcot_chunked_test() {
+        let mut ideal_cot = IdealCOT::default();
+        let mut prg = Prg::new();
+        let delta = ideal_cot.delta();
+        let mut sender = SpcotSender::new().setup(delta, prg.random_block());
+        let mut receiver = SpcotReceiver::new().setup();
+
+        for (h, split, alpha) in [(8, 3, 3), (6, 2, 47)] {
+            let (
+                RCOTSenderOutput { msgs: qs, .. },
+                RCOTReceiverOutput {
+                    choices: rs,
+                    msgs: ts,
+                    ..
+                },
+            ) = ideal_cot.random_correlated(h);
+
+            let maskbits = receiver.extend_mask_bits(h, alpha, &rs).unwrap();
+            let msg_from_sender = sender.extend_chunked(h, split, &qs, maskbits).unwrap();
+            receiver
+                .extend_chunked(h, split, alpha, &ts, msg_from_sender)
+                .unwrap();
+        }
+
+        let (
+            RCOTSenderOutput { msgs: y_star, .. },
+            RCOTReceiverOutput {
+                choices: x_star,
+                msgs: z_star,
+                ..
+            },
+        ) = ideal_cot.random_correlated(CSP);
+
+        let check_from_receiver = receiver.check_pre(&x_star).unwrap();
+        let (output_sender, check) = sender.check_chunked(&y_star, check_from_receiver).unwrap();
+        let output_receiver = receiver.check_chunked(&z_star, check).unwrap();
+
+        for (vs, (ws, alpha)) in output_sender.iter().zip(output_receiver.iter()) {
+            let n = vs.subtree_len();
+            let mut v = vec![Block::ZERO; n];
+            let mut w = vec![Block::ZERO; n];
+            for i in 0..vs.subtree_count() {
+                vs.expand(i, &mut v);
+                ws.expand(i, &mut w);
+                if i == *alpha as usize / n {
+                    v[*alpha as usize % n] ^= delta;
+                }
+                assert_eq!(v, w);
+            }
+        }
+    }
+
+    #[test]
+    fn spcot_malicious_sender_check() {
+        let mut ideal_cot = IdealCOT::default();
+        let mut prg = Prg::new();
+        let mut sender = SpcotSender::new().setup(ideal_cot.delta(), prg.random_block());
+        let mut receiver = SpcotReceiver::new().setup();
+
+        let (h, alpha) = (8, 3);
+        let (
+            RCOTSenderOutput { msgs: qs, .. },
+            RCOTReceiverOutput {
+                choices: rs,
+                msgs: ts,
+                ..
+            },
+        ) = ideal_cot.random_correlated(h);
+
+        let maskbits = receiver.extend_mask_bits(h, alpha, &rs).unwrap();
+        let msg_from_sender = sender.extend(h, &qs, maskbits).unwrap();
+        receiver.extend(h, alpha, &ts, msg_from_sender).unwrap();
+
+        let (
+            RCOTSenderOutput { msgs: y_star, .. },
+            RCOTReceiverOutput {
+                choices: x_star,
+                msgs: z_star,
+                ..
+            },
+        ) = ideal_cot.random_correlated(CSP);
+
+        let check_from_receiver = receiver.check_pre(&x_star).unwrap();
+        let (_, mut check) = sender.check(&y_star, check_from_receiver).unwrap();
+
+        // The sender sends a check which is inconsistent with its outputs.
+        check.hashed_v = [0u8; 32].into();
+
+        assert!(matches!(
+            receiver.check(&z_star, check),
+            Err(ReceiverError::ConsistencyCheckFailed)
+        ));
+    }
 }
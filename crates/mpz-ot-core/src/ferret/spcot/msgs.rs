@@ -1,7 +1,7 @@
 //! Messages for the SPCOT protocol
 
-use mpz_core::{hash::Hash, Block};
-use serde::{Deserialize, Serialize};
+use mpz_core::{hash::Hash, serialize::WireMessage, Block};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 /// An SPCOT message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,3 +43,9 @@ pub struct CheckFromSender {
     /// The hashed `V` from the sender.
     pub hashed_v: Hash,
 }
+
+impl<CotMsg> WireMessage for Message<CotMsg> where CotMsg: Serialize + DeserializeOwned {}
+impl WireMessage for MaskBits {}
+impl WireMessage for ExtendFromSender {}
+impl WireMessage for CheckFromReceiver {}
+impl WireMessage for CheckFromSender {}
@@ -2,8 +2,13 @@
 use crate::ferret::{spcot::error::ReceiverError, CSP};
 use itybity::ToBits;
 use mpz_core::{
-    aes::FIXED_KEY_AES, ggm_tree::GgmTree, hash::Hash, prg::Prg, serialize::CanonicalSerialize,
-    utils::blake3, Block,
+    aes::FIXED_KEY_AES,
+    ggm_tree::{GgmSubtrees, GgmTree},
+    hash::Hash,
+    prg::Prg,
+    serialize::CanonicalSerialize,
+    utils::blake3,
+    Block,
 };
 use rand_core::SeedableRng;
 
@@ -33,6 +38,7 @@ impl Receiver {
                 unchecked_ws: Vec::default(),
                 chis: Vec::default(),
                 alphas_and_length: Vec::default(),
+                chunked_ws: Vec::default(),
                 cot_counter: 0,
                 exec_counter: 0,
                 extended: false,
@@ -109,6 +115,88 @@ impl Receiver<state::Extension> {
         ts: &[Block],
         extendfs: ExtendFromSender,
     ) -> Result<(), ReceiverError> {
+        if !self.state.chunked_ws.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "extension is not allowed after a chunked extension".to_string(),
+            ));
+        }
+
+        let (k, alpha_bar_vec, sum) = self.extend_keys(h, alpha, ts, extendfs)?;
+
+        // Reconstructs GGM tree except `ws[alpha]`.
+        let ggm_tree = GgmTree::new(h);
+        let mut tree = vec![Block::ZERO; 1 << h];
+        ggm_tree.reconstruct(&mut tree, &k, &alpha_bar_vec);
+
+        // Sets `tree[alpha]`, which is `ws[alpha]`.
+        tree[alpha as usize] = tree.iter().fold(sum, |acc, &x| acc ^ x);
+
+        self.state.unchecked_ws.extend_from_slice(&tree);
+        self.state.alphas_and_length.push((alpha, 1 << h));
+
+        Ok(())
+    }
+
+    /// Performs the GGM reconstruction step in extension with bounded memory, for very large
+    /// domains. This function can be called multiple times before checking.
+    ///
+    /// The GGM tree is reconstructed subtree by subtree, see [`Sender::extend_chunked`](super::sender::Sender::extend_chunked).
+    ///
+    /// Chunked extensions can not be mixed with regular extensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - The depth of the GGM tree.
+    /// * `split` - The depth at which the GGM tree is split into subtrees, the same as the sender's.
+    /// * `alpha` - The chosen position.
+    /// * `ts` - The message from COT ideal functionality for the receiver. Only the chosen blocks are used.
+    /// * `extendfs` - The message sent by the sender.
+    pub fn extend_chunked(
+        &mut self,
+        h: usize,
+        split: usize,
+        alpha: u32,
+        ts: &[Block],
+        extendfs: ExtendFromSender,
+    ) -> Result<(), ReceiverError> {
+        if !self.state.alphas_and_length.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "chunked extension is not allowed after a regular extension".to_string(),
+            ));
+        }
+
+        if split < 2 || split + 2 > h {
+            return Err(ReceiverError::InvalidInput(
+                "the split should be at least 2 and at most h-2".to_string(),
+            ));
+        }
+
+        let (k, alpha_bar_vec, sum) = self.extend_keys(h, alpha, ts, extendfs)?;
+
+        // Reconstructs GGM tree except `ws[alpha]`, computing the sum of the leaves.
+        let ggm_tree = GgmTree::new(h);
+        let mut w_alpha = sum;
+        let mut subtrees = ggm_tree.reconstruct_chunked(&k, &alpha_bar_vec, split, |_, leaves| {
+            w_alpha = leaves.iter().fold(w_alpha, |acc, &x| acc ^ x);
+        });
+        subtrees.set_punctured_leaf(w_alpha);
+
+        self.state.chunked_ws.push((subtrees, alpha));
+
+        Ok(())
+    }
+
+    /// Checks the inputs of an extension and computes the keys of the GGM tree.
+    ///
+    /// Returns the keys, the negated bits of `alpha` and the sum sent by the sender.
+    #[allow(clippy::type_complexity)]
+    fn extend_keys(
+        &mut self,
+        h: usize,
+        alpha: u32,
+        ts: &[Block],
+        extendfs: ExtendFromSender,
+    ) -> Result<(Vec<Block>, Vec<bool>, Block), ReceiverError> {
         if self.state.extended {
             return Err(ReceiverError::InvalidState(
                 "extension is not allowed".to_string(),
@@ -158,20 +246,9 @@ impl Receiver<state::Extension> {
             })
             .collect();
 
-        // Reconstructs GGM tree except `ws[alpha]`.
-        let ggm_tree = GgmTree::new(h);
-        let mut tree = vec![Block::ZERO; 1 << h];
-        ggm_tree.reconstruct(&mut tree, &k, &alpha_bar_vec);
-
-        // Sets `tree[alpha]`, which is `ws[alpha]`.
-        tree[alpha as usize] = tree.iter().fold(sum, |acc, &x| acc ^ x);
-
-        self.state.unchecked_ws.extend_from_slice(&tree);
-        self.state.alphas_and_length.push((alpha, 1 << h));
-
         self.state.exec_counter += 1;
 
-        Ok(())
+        Ok((k, alpha_bar_vec, sum))
     }
 
     /// Performs the decomposition and bit-mask steps in check.
@@ -201,6 +278,19 @@ impl Receiver<state::Extension> {
             self.state.chis.extend_from_slice(&chis);
         }
 
+        // The challenges of chunked extensions are generated again in the check, one subtree at
+        // a time.
+        for (subtrees, alpha) in &self.state.chunked_ws {
+            let n = subtrees.subtree_len();
+            let mut chis = vec![Block::ZERO; n];
+            for i in 0..subtrees.subtree_count() {
+                prg.random_blocks(&mut chis);
+                if i == *alpha as usize / n {
+                    sum_chi_alpha ^= chis[*alpha as usize % n];
+                }
+            }
+        }
+
         let x_prime: Vec<bool> = sum_chi_alpha
             .iter_lsb0()
             .zip(x_star)
@@ -223,19 +313,16 @@ impl Receiver<state::Extension> {
         z_star: &[Block],
         check: CheckFromSender,
     ) -> Result<Vec<(Vec<Block>, u32)>, ReceiverError> {
-        let CheckFromSender { hashed_v } = check;
-
-        if z_star.len() != CSP {
-            return Err(ReceiverError::InvalidLength(format!(
-                "the length of z* should be {CSP}"
-            )));
+        if !self.state.chunked_ws.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "chunked extensions are checked with check_chunked".to_string(),
+            ));
         }
 
-        // Computes the base X^i
-        let base: Vec<Block> = (0..CSP).map(|x| bytemuck::cast((1_u128) << x)).collect();
+        let CheckFromSender { hashed_v } = check;
 
         // Computes Z.
-        let mut w = Block::inn_prdt_red(z_star, &base);
+        let mut w = Self::check_z(z_star)?;
 
         // Computes W.
         w ^= Block::inn_prdt_red(&self.state.chis, &self.state.unchecked_ws);
@@ -258,6 +345,76 @@ impl Receiver<state::Extension> {
 
         Ok(res)
     }
+
+    /// Performs the final step of the consistency check for chunked extensions.
+    ///
+    /// See step 9 in Figure 6.
+    ///
+    /// Returns the subtrees and the chosen position of each extension, whose leaves are the
+    /// output of the receiver, see [`GgmSubtrees::expand`].
+    ///
+    /// # Arguments
+    ///
+    /// * `z_star` - The message from COT ideal functionality for the receiver. Only the chosen blocks are used.
+    /// * `check` - The hashed value sent by the Sender.
+    pub fn check_chunked(
+        &mut self,
+        z_star: &[Block],
+        check: CheckFromSender,
+    ) -> Result<Vec<(GgmSubtrees, u32)>, ReceiverError> {
+        if !self.state.alphas_and_length.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "regular extensions are checked with check".to_string(),
+            ));
+        }
+
+        let CheckFromSender { hashed_v } = check;
+
+        // Computes Z.
+        let mut w = Self::check_z(z_star)?;
+
+        // Computes W, generating the same challenges as in `check_pre`.
+        let seed = *self.state.hasher.finalize().as_bytes();
+        let mut prg = Prg::from_seed(Block::try_from(&seed[0..16]).unwrap());
+
+        for (subtrees, _) in &self.state.chunked_ws {
+            let mut leaves = vec![Block::ZERO; subtrees.subtree_len()];
+            let mut chis = vec![Block::ZERO; subtrees.subtree_len()];
+            for i in 0..subtrees.subtree_count() {
+                subtrees.expand(i, &mut leaves);
+                prg.random_blocks(&mut chis);
+                w ^= Block::inn_prdt_red(&chis, &leaves);
+            }
+        }
+
+        // Computes H'(W)
+        let hashed_w = Hash::from(blake3(&w.to_bytes()));
+
+        if hashed_v != hashed_w {
+            return Err(ReceiverError::ConsistencyCheckFailed);
+        }
+
+        for (subtrees, _) in &self.state.chunked_ws {
+            self.state.cot_counter += subtrees.subtree_count() * subtrees.subtree_len();
+        }
+        self.state.extended = true;
+
+        Ok(std::mem::take(&mut self.state.chunked_ws))
+    }
+
+    /// Computes `Z` of the consistency check.
+    fn check_z(z_star: &[Block]) -> Result<Block, ReceiverError> {
+        if z_star.len() != CSP {
+            return Err(ReceiverError::InvalidLength(format!(
+                "the length of z* should be {CSP}"
+            )));
+        }
+
+        // Computes the base X^i
+        let base: Vec<Block> = (0..CSP).map(|x| bytemuck::cast((1_u128) << x)).collect();
+
+        Ok(Block::inn_prdt_red(z_star, &base))
+    }
 }
 
 /// The receiver's state.
@@ -292,6 +449,8 @@ pub mod state {
         pub(super) chis: Vec<Block>,
         /// Stores the alpha and the length in each extend phase.
         pub(super) alphas_and_length: Vec<(u32, u32)>,
+        /// The subtrees and the alpha of each chunked extension.
+        pub(super) chunked_ws: Vec<(GgmSubtrees, u32)>,
 
         /// Current COT counter
         pub(super) cot_counter: usize,
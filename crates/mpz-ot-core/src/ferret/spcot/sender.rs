@@ -1,8 +1,13 @@
 //! SPCOT sender.
 use crate::ferret::{spcot::error::SenderError, CSP};
 use mpz_core::{
-    aes::FIXED_KEY_AES, ggm_tree::GgmTree, hash::Hash, prg::Prg, serialize::CanonicalSerialize,
-    utils::blake3, Block,
+    aes::FIXED_KEY_AES,
+    ggm_tree::{GgmSubtrees, GgmTree},
+    hash::Hash,
+    prg::Prg,
+    serialize::CanonicalSerialize,
+    utils::blake3,
+    Block,
 };
 use rand_core::SeedableRng;
 
@@ -36,6 +41,7 @@ impl Sender {
                 delta,
                 unchecked_vs: Vec::default(),
                 vs_length: Vec::default(),
+                chunked_vs: Vec::default(),
                 cot_counter: 0,
                 exec_counter: 0,
                 extended: false,
@@ -62,28 +68,13 @@ impl Sender<state::Extension> {
         qs: &[Block],
         mask: MaskBits,
     ) -> Result<ExtendFromSender, SenderError> {
-        if self.state.extended {
+        if !self.state.chunked_vs.is_empty() {
             return Err(SenderError::InvalidState(
-                "extension is not allowed".to_string(),
-            ));
-        }
-
-        if qs.len() != h {
-            return Err(SenderError::InvalidLength(
-                "the length of q should be h".to_string(),
-            ));
-        }
-
-        let MaskBits { bs } = mask;
-
-        if bs.len() != h {
-            return Err(SenderError::InvalidLength(
-                "the length of b should be h".to_string(),
+                "extension is not allowed after a chunked extension".to_string(),
             ));
         }
 
-        // Updates hasher.
-        self.state.hasher.update(&bs.to_bytes());
+        let bs = self.extend_mask(h, qs, mask)?;
 
         // Step 3-4, Figure 6.
 
@@ -104,6 +95,102 @@ impl Sender<state::Extension> {
         // Computes the sum of the leaves and delta.
         let sum = tree.iter().fold(self.state.delta, |acc, &x| acc ^ x);
 
+        Ok(self.extend_finish(qs, bs, k0, k1, sum))
+    }
+
+    /// Performs the SPCOT extension with bounded memory, for very large domains.
+    ///
+    /// The GGM tree is split into subtrees, of which only one is in memory at a time, and only
+    /// the roots of the subtrees are stored. The output of the extension is expanded chunk by
+    /// chunk after the check, see [`Sender::check_chunked`].
+    ///
+    /// Chunked extensions can not be mixed with regular extensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - The depth of the GGM tree.
+    /// * `split` - The depth at which the GGM tree is split into `2^split` subtrees, at least 2
+    ///   and at most `h - 2`.
+    /// * `qs`- The blocks received by calling the COT functionality.
+    /// * `mask`- The mask bits sent by the receiver.
+    pub fn extend_chunked(
+        &mut self,
+        h: usize,
+        split: usize,
+        qs: &[Block],
+        mask: MaskBits,
+    ) -> Result<ExtendFromSender, SenderError> {
+        if !self.state.vs_length.is_empty() {
+            return Err(SenderError::InvalidState(
+                "chunked extension is not allowed after a regular extension".to_string(),
+            ));
+        }
+
+        if split < 2 || split + 2 > h {
+            return Err(SenderError::InvalidInput(
+                "the split should be at least 2 and at most h-2".to_string(),
+            ));
+        }
+
+        let bs = self.extend_mask(h, qs, mask)?;
+
+        // Generates the GGM tree subtree by subtree, computing the sum of the leaves and delta.
+        let s = self.state.prg.random_block();
+        let ggm_tree = GgmTree::new(h);
+        let mut k0 = vec![Block::ZERO; h];
+        let mut k1 = vec![Block::ZERO; h];
+        let mut sum = self.state.delta;
+        let subtrees = ggm_tree.gen_chunked(s, split, &mut k0, &mut k1, |_, leaves| {
+            sum = leaves.iter().fold(sum, |acc, &x| acc ^ x);
+        });
+
+        self.state.chunked_vs.push(subtrees);
+
+        Ok(self.extend_finish(qs, bs, k0, k1, sum))
+    }
+
+    /// Checks the inputs of an extension and updates the hasher with the mask bits.
+    fn extend_mask(
+        &mut self,
+        h: usize,
+        qs: &[Block],
+        mask: MaskBits,
+    ) -> Result<Vec<bool>, SenderError> {
+        if self.state.extended {
+            return Err(SenderError::InvalidState(
+                "extension is not allowed".to_string(),
+            ));
+        }
+
+        if qs.len() != h {
+            return Err(SenderError::InvalidLength(
+                "the length of q should be h".to_string(),
+            ));
+        }
+
+        let MaskBits { bs } = mask;
+
+        if bs.len() != h {
+            return Err(SenderError::InvalidLength(
+                "the length of b should be h".to_string(),
+            ));
+        }
+
+        // Updates hasher.
+        self.state.hasher.update(&bs.to_bytes());
+
+        Ok(bs)
+    }
+
+    /// Computes the message of an extension from the XORs of the levels of the GGM tree.
+    fn extend_finish(
+        &mut self,
+        qs: &[Block],
+        bs: Vec<bool>,
+        k0: Vec<Block>,
+        k1: Vec<Block>,
+        sum: Block,
+    ) -> ExtendFromSender {
         // Computes M0 and M1.
         let mut ms: Vec<[Block; 2]> = Vec::with_capacity(qs.len());
         for (((i, &q), b), (k0, k1)) in qs.iter().enumerate().zip(bs).zip(k0.into_iter().zip(k1)) {
@@ -125,7 +212,7 @@ impl Sender<state::Extension> {
 
         self.state.exec_counter += 1;
 
-        Ok(ExtendFromSender { ms, sum })
+        ExtendFromSender { ms, sum }
     }
 
     /// Performs the consistency check for the resulting COTs.
@@ -141,6 +228,92 @@ impl Sender<state::Extension> {
         y_star: &[Block],
         checkfr: CheckFromReceiver,
     ) -> Result<(Vec<Vec<Block>>, CheckFromSender), SenderError> {
+        if !self.state.chunked_vs.is_empty() {
+            return Err(SenderError::InvalidState(
+                "chunked extensions are checked with check_chunked".to_string(),
+            ));
+        }
+
+        let (mut v, mut prg) = self.check_start(y_star, checkfr)?;
+
+        let mut chis = Vec::new();
+        for n in &self.state.vs_length {
+            let mut chi = vec![Block::ZERO; *n as usize];
+            prg.random_blocks(&mut chi);
+            chis.extend_from_slice(&chi);
+        }
+        v ^= Block::inn_prdt_red(&chis, &self.state.unchecked_vs);
+
+        // Computes H'(V)
+        let hashed_v = Hash::from(blake3(&v.to_bytes()));
+
+        self.state.cot_counter += self.state.unchecked_vs.len();
+
+        let mut res = Vec::new();
+        for n in &self.state.vs_length {
+            let tmp: Vec<Block> = self.state.unchecked_vs.drain(..*n as usize).collect();
+            res.push(tmp);
+        }
+
+        self.state.extended = true;
+
+        Ok((res, CheckFromSender { hashed_v }))
+    }
+
+    /// Performs the consistency check for the COTs of chunked extensions.
+    ///
+    /// See Step 6-9 in Figure 6.
+    ///
+    /// Returns the subtrees of each extension, whose leaves are the output of the sender, see
+    /// [`GgmSubtrees::expand`].
+    ///
+    /// # Arguments
+    ///
+    /// * `y_star` - The blocks received from the ideal functionality for the check.
+    /// * `checkfr` - The bits received from the receiver for the check.
+    pub fn check_chunked(
+        &mut self,
+        y_star: &[Block],
+        checkfr: CheckFromReceiver,
+    ) -> Result<(Vec<GgmSubtrees>, CheckFromSender), SenderError> {
+        if !self.state.vs_length.is_empty() {
+            return Err(SenderError::InvalidState(
+                "regular extensions are checked with check".to_string(),
+            ));
+        }
+
+        let (mut v, mut prg) = self.check_start(y_star, checkfr)?;
+
+        // Computes V, expanding the leaves of one subtree at a time.
+        for subtrees in &self.state.chunked_vs {
+            let mut leaves = vec![Block::ZERO; subtrees.subtree_len()];
+            let mut chi = vec![Block::ZERO; subtrees.subtree_len()];
+            for i in 0..subtrees.subtree_count() {
+                subtrees.expand(i, &mut leaves);
+                prg.random_blocks(&mut chi);
+                v ^= Block::inn_prdt_red(&chi, &leaves);
+            }
+
+            self.state.cot_counter += subtrees.subtree_count() * subtrees.subtree_len();
+        }
+
+        // Computes H'(V)
+        let hashed_v = Hash::from(blake3(&v.to_bytes()));
+
+        self.state.extended = true;
+
+        Ok((
+            std::mem::take(&mut self.state.chunked_vs),
+            CheckFromSender { hashed_v },
+        ))
+    }
+
+    /// Computes `Y` of the consistency check, returning it with the PRG of the challenges.
+    fn check_start(
+        &self,
+        y_star: &[Block],
+        checkfr: CheckFromReceiver,
+    ) -> Result<(Block, Prg), SenderError> {
         let CheckFromReceiver { x_prime } = checkfr;
 
         if y_star.len() != CSP {
@@ -168,34 +341,13 @@ impl Sender<state::Extension> {
         let base: Vec<Block> = (0..CSP).map(|x| bytemuck::cast((1_u128) << x)).collect();
 
         // Computes Y
-        let mut v = Block::inn_prdt_red(&y, &base);
+        let v = Block::inn_prdt_red(&y, &base);
 
         // Computes V
         let seed = *self.state.hasher.finalize().as_bytes();
-        let mut prg = Prg::from_seed(Block::try_from(&seed[0..16]).unwrap());
+        let prg = Prg::from_seed(Block::try_from(&seed[0..16]).unwrap());
 
-        let mut chis = Vec::new();
-        for n in &self.state.vs_length {
-            let mut chi = vec![Block::ZERO; *n as usize];
-            prg.random_blocks(&mut chi);
-            chis.extend_from_slice(&chi);
-        }
-        v ^= Block::inn_prdt_red(&chis, &self.state.unchecked_vs);
-
-        // Computes H'(V)
-        let hashed_v = Hash::from(blake3(&v.to_bytes()));
-
-        self.state.cot_counter += self.state.unchecked_vs.len();
-
-        let mut res = Vec::new();
-        for n in &self.state.vs_length {
-            let tmp: Vec<Block> = self.state.unchecked_vs.drain(..*n as usize).collect();
-            res.push(tmp);
-        }
-
-        self.state.extended = true;
-
-        Ok((res, CheckFromSender { hashed_v }))
+        Ok((v, prg))
     }
 }
 
@@ -231,6 +383,8 @@ pub mod state {
         pub(super) unchecked_vs: Vec<Block>,
         /// Store the length of each extension.
         pub(super) vs_length: Vec<u32>,
+        /// The subtrees of each chunked extension.
+        pub(super) chunked_vs: Vec<GgmSubtrees>,
 
         /// Current COT counter
         pub(super) cot_counter: usize,
@@ -1,6 +1,7 @@
 //! Ideal Correlated Oblivious Transfer functionality.
 
-use mpz_core::{prg::Prg, Block};
+use alloc::{vec, vec::Vec};
+use mpz_core::{prg::Prg, Block, KeyBlock};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
@@ -69,10 +70,10 @@ impl IdealCOT {
         self.prg.random_blocks(&mut msgs);
         self.prg.random_bools(&mut choices);
 
-        let chosen: Vec<Block> = msgs
+        let chosen: Vec<Block> = KeyBlock::from_blocks(&msgs)
             .iter()
             .zip(choices.iter())
-            .map(|(&q, &r)| if r { q ^ self.delta } else { q })
+            .map(|(key, &r)| key.authenticate(r, self.delta).into_block())
             .collect();
 
         self.counter += count;
@@ -164,4 +165,18 @@ mod tests {
 
         assert_cot(ideal.delta(), &choices, &msgs, &received)
     }
+
+    #[test]
+    fn test_ideal_rcot_keys_macs() {
+        let mut ideal = IdealCOT::default();
+
+        let (sender_output, receiver_output) = ideal.random_correlated(100);
+
+        assert!(sender_output
+            .keys()
+            .iter()
+            .zip(receiver_output.macs())
+            .zip(&receiver_output.choices)
+            .all(|((key, mac), &choice)| key.verify(choice, mac, ideal.delta())));
+    }
 }
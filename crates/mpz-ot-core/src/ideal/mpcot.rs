@@ -1,5 +1,6 @@
 //! Ideal functionality for the multi-point correlated OT.
 
+use alloc::vec;
 use mpz_core::{prg::Prg, Block};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
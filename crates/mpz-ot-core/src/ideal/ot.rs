@@ -1,6 +1,7 @@
 //! Ideal Chosen-Message Oblivious Transfer functionality.
 
 use crate::{OTReceiverOutput, OTSenderOutput, TransferId};
+use alloc::vec::Vec;
 
 /// The ideal OT functionality.
 #[derive(Debug, Default)]
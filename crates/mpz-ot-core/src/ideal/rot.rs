@@ -1,5 +1,6 @@
 //! Ideal Random Oblivious Transfer functionality.
 
+use alloc::{vec, vec::Vec};
 use mpz_core::{prg::Prg, Block};
 use rand::{
     distributions::{Distribution, Standard},
@@ -1,5 +1,6 @@
 //! Ideal functionality for single-point correlated OT.
 
+use alloc::vec;
 use mpz_core::{prg::Prg, Block};
 
 use crate::{SPCOTReceiverOutput, SPCOTSenderOutput, TransferId};
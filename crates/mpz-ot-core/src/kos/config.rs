@@ -1,11 +1,39 @@
 use derive_builder::Builder;
 
+use crate::msgs::{ConfigFingerprint, ConfigMismatch};
+
+/// The default maximum number of OTs extended in a single batch, 1M.
+pub const DEFAULT_BATCH_SIZE: usize = 1 << 20;
+
 /// KOS15 sender configuration.
-#[derive(Debug, Default, Clone, Builder)]
+#[derive(Debug, Clone, Builder)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct SenderConfig {
     /// Enables committed sender functionality.
     #[builder(setter(custom), default = "false")]
     sender_commit: bool,
+    /// The maximum number of OTs extended in a single batch.
+    ///
+    /// Larger extensions are split into batches of this size, rounded up to a multiple of 64,
+    /// bounding memory usage. The sender and receiver must use the same batch size.
+    #[builder(default = "DEFAULT_BATCH_SIZE")]
+    batch_size: usize,
+    /// Enables the hashed correlation check, see [`HashedCheck`](crate::kos::msgs::HashedCheck).
+    ///
+    /// This setting is negotiated during setup: the hashed check is only used if the peer enables
+    /// it as well, otherwise both parties fall back to the full check.
+    #[builder(setter(custom), default = "false")]
+    hashed_check: bool,
+}
+
+impl Default for SenderConfig {
+    fn default() -> Self {
+        Self {
+            sender_commit: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            hashed_check: false,
+        }
+    }
 }
 
 impl SenderConfigBuilder {
@@ -14,6 +42,12 @@ impl SenderConfigBuilder {
         self.sender_commit = Some(true);
         self
     }
+
+    /// Enables the hashed correlation check, if the peer enables it as well.
+    pub fn hashed_check(&mut self) -> &mut Self {
+        self.hashed_check = Some(true);
+        self
+    }
 }
 
 impl SenderConfig {
@@ -26,14 +60,80 @@ impl SenderConfig {
     pub fn sender_commit(&self) -> bool {
         self.sender_commit
     }
+
+    /// Returns the maximum number of OTs extended in a single batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Returns whether the hashed correlation check is enabled.
+    pub fn hashed_check(&self) -> bool {
+        self.hashed_check
+    }
+
+    /// Returns the fingerprint of the settings which the sender and receiver must agree on.
+    pub fn fingerprint(&self) -> ConfigFingerprint {
+        fingerprint(self.sender_commit, self.batch_size, self.hashed_check)
+    }
+
+    /// Negotiates the configuration with the receiver, given the fingerprint of its configuration.
+    ///
+    /// Returns the configuration to use, which only enables the hashed check if the receiver
+    /// enables it as well. All other settings must match.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The fingerprint of the receiver's configuration.
+    pub fn negotiate(&self, peer: &ConfigFingerprint) -> Result<Self, ConfigMismatch> {
+        let expected = self.fingerprint();
+        if expected == *peer {
+            return Ok(self.clone());
+        }
+
+        let toggled = fingerprint(self.sender_commit, self.batch_size, !self.hashed_check);
+        if toggled == *peer {
+            return Ok(Self {
+                hashed_check: false,
+                ..self.clone()
+            });
+        }
+
+        Err(ConfigMismatch {
+            expected,
+            actual: *peer,
+        })
+    }
 }
 
 /// KOS15 receiver configuration.
-#[derive(Debug, Default, Clone, Builder)]
+#[derive(Debug, Clone, Builder)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
 pub struct ReceiverConfig {
     /// Enables committed sender functionality.
     #[builder(setter(custom), default = "false")]
     sender_commit: bool,
+    /// The maximum number of OTs extended in a single batch.
+    ///
+    /// Larger extensions are split into batches of this size, rounded up to a multiple of 64,
+    /// bounding memory usage. The sender and receiver must use the same batch size.
+    #[builder(default = "DEFAULT_BATCH_SIZE")]
+    batch_size: usize,
+    /// Enables the hashed correlation check, see [`HashedCheck`](crate::kos::msgs::HashedCheck).
+    ///
+    /// This setting is negotiated during setup: the hashed check is only used if the peer enables
+    /// it as well, otherwise both parties fall back to the full check.
+    #[builder(setter(custom), default = "false")]
+    hashed_check: bool,
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self {
+            sender_commit: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            hashed_check: false,
+        }
+    }
 }
 
 impl ReceiverConfigBuilder {
@@ -42,6 +142,12 @@ impl ReceiverConfigBuilder {
         self.sender_commit = Some(true);
         self
     }
+
+    /// Enables the hashed correlation check, if the peer enables it as well.
+    pub fn hashed_check(&mut self) -> &mut Self {
+        self.hashed_check = Some(true);
+        self
+    }
 }
 
 impl ReceiverConfig {
@@ -54,4 +160,57 @@ impl ReceiverConfig {
     pub fn sender_commit(&self) -> bool {
         self.sender_commit
     }
+
+    /// Returns the maximum number of OTs extended in a single batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Returns whether the hashed correlation check is enabled.
+    pub fn hashed_check(&self) -> bool {
+        self.hashed_check
+    }
+
+    /// Returns the fingerprint of the settings which the sender and receiver must agree on.
+    pub fn fingerprint(&self) -> ConfigFingerprint {
+        fingerprint(self.sender_commit, self.batch_size, self.hashed_check)
+    }
+
+    /// Negotiates the configuration with the sender, given the fingerprint of its configuration.
+    ///
+    /// Returns the configuration to use, which only enables the hashed check if the sender
+    /// enables it as well. All other settings must match.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The fingerprint of the sender's configuration.
+    pub fn negotiate(&self, peer: &ConfigFingerprint) -> Result<Self, ConfigMismatch> {
+        let expected = self.fingerprint();
+        if expected == *peer {
+            return Ok(self.clone());
+        }
+
+        let toggled = fingerprint(self.sender_commit, self.batch_size, !self.hashed_check);
+        if toggled == *peer {
+            return Ok(Self {
+                hashed_check: false,
+                ..self.clone()
+            });
+        }
+
+        Err(ConfigMismatch {
+            expected,
+            actual: *peer,
+        })
+    }
+}
+
+fn fingerprint(sender_commit: bool, batch_size: usize, hashed_check: bool) -> ConfigFingerprint {
+    let mut settings = [0u8; 10];
+    settings[0] = sender_commit as u8;
+    settings[1] = hashed_check as u8;
+    // The batch size is rounded up to a multiple of 64 when extending.
+    settings[2..].copy_from_slice(&(((batch_size + 63) & !63) as u64).to_le_bytes());
+
+    ConfigFingerprint::new(b"KOS15", &settings)
 }
@@ -1,4 +1,6 @@
-use crate::TransferId;
+use alloc::string::String;
+
+use crate::{ErrorKind, TransferId};
 
 /// Errors that can occur when using the KOS15 sender.
 #[derive(Debug, thiserror::Error)]
@@ -18,6 +20,8 @@ pub enum SenderError {
     ConsistencyCheckFailed,
     #[error("not enough OTs are setup: expected {0}, actual {1}")]
     InsufficientSetup(usize, usize),
+    #[error("invalid shard count: {0}")]
+    InvalidShardCount(usize),
 }
 
 /// Errors that can occur when using the KOS15 receiver.
@@ -34,6 +38,8 @@ pub enum ReceiverError {
     IdMismatch(TransferId, TransferId),
     #[error("not enough OTs are setup: expected {0}, actual {1}")]
     InsufficientSetup(usize, usize),
+    #[error("invalid shard count: {0}")]
+    InvalidShardCount(usize),
     #[error("invalid payload")]
     InvalidPayload(String),
     #[error(transparent)]
@@ -51,3 +57,60 @@ pub enum ReceiverVerifyError {
     #[error("payload inconsistent")]
     InconsistentPayload,
 }
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::InvalidState(_)
+            | SenderError::InvalidCount(_)
+            | SenderError::InsufficientSetup(..)
+            | SenderError::InvalidShardCount(_) => ErrorKind::CallerBug,
+            SenderError::CountMismatch(..)
+            | SenderError::IdMismatch(..)
+            | SenderError::InvalidExtend => ErrorKind::ProtocolAbort,
+            SenderError::ConsistencyCheckFailed => ErrorKind::PeerMisbehavior,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    ///
+    /// A count mismatch is classified as a [`ErrorKind::ProtocolAbort`], as it is returned both
+    /// for invalid choices and for payloads of the peer.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::InvalidState(_)
+            | ReceiverError::InvalidCount(_)
+            | ReceiverError::InsufficientSetup(..)
+            | ReceiverError::InvalidShardCount(_) => ErrorKind::CallerBug,
+            ReceiverError::CountMismatch(..)
+            | ReceiverError::IdMismatch(..)
+            | ReceiverError::InvalidPayload(_) => ErrorKind::ProtocolAbort,
+            ReceiverError::ReceiverVerifyError(err) => err.kind(),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverVerifyError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverVerifyError::TapeNotRecorded | ReceiverVerifyError::InvalidTransferId(_) => {
+                ErrorKind::CallerBug
+            }
+            ReceiverVerifyError::InconsistentPayload => ErrorKind::PeerMisbehavior,
+        }
+    }
+}
@@ -8,9 +8,10 @@ mod sender;
 
 pub use config::{
     ReceiverConfig, ReceiverConfigBuilder, ReceiverConfigBuilderError, SenderConfig,
-    SenderConfigBuilder, SenderConfigBuilderError,
+    SenderConfigBuilder, SenderConfigBuilderError, DEFAULT_BATCH_SIZE,
 };
 pub use error::{ReceiverError, ReceiverVerifyError, SenderError};
+use mpz_core::Block;
 use rand_chacha::ChaCha20Rng;
 use rand_core::SeedableRng;
 pub use receiver::{state as receiver_state, PayloadRecord, Receiver, ReceiverKeys};
@@ -25,6 +26,17 @@ pub(crate) type Rng = ChaCha20Rng;
 /// Rng seed type
 pub(crate) type RngSeed = <Rng as SeedableRng>::Seed;
 
+/// Returns an rng seeded with a seed obtained from base OT.
+pub(crate) fn seed_rng(seed: &Block) -> Rng {
+    // Stretch the Block-sized seed to a 32-byte seed.
+    let mut seed_ = RngSeed::default();
+    seed_
+        .iter_mut()
+        .zip(seed.to_bytes().into_iter().cycle())
+        .for_each(|(s, c)| *s = c);
+    Rng::from_seed(seed_)
+}
+
 /// AES-128 CTR used for encryption.
 pub(crate) type Aes128Ctr = ctr::Ctr64LE<aes::Aes128>;
 
@@ -37,6 +49,24 @@ pub fn pad_ot_count(mut count: usize) -> usize {
     (count + 63) & !63
 }
 
+/// The maximum number of shards an extension can be partitioned into.
+pub const MAX_SHARDS: usize = 1 << 16;
+
+/// Returns the number of OTs in each of `k` shards of `count` OTs.
+pub(crate) fn shard_sizes(count: usize, k: usize) -> impl Iterator<Item = usize> {
+    (0..k).map(move |i| count / k + usize::from(i < count % k))
+}
+
+/// Hashes the receiver's check values, see [`msgs::HashedCheck`].
+pub(crate) fn hash_check(t0: Block, t1: Block) -> Block {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&t0.to_bytes());
+    hasher.update(&t1.to_bytes());
+
+    let hash: [u8; 32] = hasher.finalize().into();
+    Block::new(hash[..16].try_into().unwrap())
+}
+
 /// Returns the size in bytes of the extension matrix for a given number of OTs.
 pub fn extension_matrix_size(count: usize) -> usize {
     count * CSP / 8
@@ -45,7 +75,8 @@ pub fn extension_matrix_size(count: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use itybity::ToBits;
+    use crate::ErrorKind;
+    use itybity::{FromBitIterator, ToBits};
     use rstest::*;
 
     use mpz_core::Block;
@@ -139,6 +170,102 @@ mod tests {
         assert_eq!(received, expected);
     }
 
+    #[rstest]
+    fn test_kos_extension_packed_choices(
+        delta: Block,
+        sender_seeds: [Block; CSP],
+        receiver_seeds: [[Block; 2]; CSP],
+        chi_seed: Block,
+        choices: Vec<bool>,
+        data: Vec<[Block; 2]>,
+        expected: Vec<Block>,
+    ) {
+        let sender = Sender::new(SenderConfig::default());
+        let receiver = Receiver::new(ReceiverConfig::default());
+
+        let mut sender = sender.setup(delta, sender_seeds);
+        let mut receiver = receiver.setup(receiver_seeds);
+
+        let receiver_setup = receiver.extend(choices.len() + 256).unwrap();
+        sender.extend(data.len() + 256, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap();
+        sender.check(chi_seed, receiver_check).unwrap();
+
+        let packed = Vec::<u8>::from_lsb0_iter(choices.iter().copied());
+
+        let mut receiver_keys = receiver.keys(choices.len()).unwrap();
+
+        // The number of choices must match the number of keys.
+        let err = receiver_keys.derandomize_bits(&packed[1..]).unwrap_err();
+        assert!(matches!(err, ReceiverError::CountMismatch(128, 120)));
+
+        let derandomize = receiver_keys.derandomize_bits(&packed).unwrap();
+
+        let mut sender_keys = sender.keys(data.len()).unwrap();
+        sender_keys.derandomize(derandomize).unwrap();
+        let payload = sender_keys.encrypt_blocks(&data).unwrap();
+
+        let received = receiver_keys.decrypt_blocks(payload).unwrap();
+
+        assert_eq!(received, expected);
+    }
+
+    #[rstest]
+    fn test_kos_partition(
+        delta: Block,
+        sender_seeds: [Block; CSP],
+        receiver_seeds: [[Block; 2]; CSP],
+        chi_seed: Block,
+        choices: Vec<bool>,
+        data: Vec<[Block; 2]>,
+        expected: Vec<Block>,
+    ) {
+        let sender = Sender::new(SenderConfig::default());
+        let receiver = Receiver::new(ReceiverConfig::default());
+
+        let mut sender = sender.setup(delta, sender_seeds);
+        let mut receiver = receiver.setup(receiver_seeds);
+
+        let receiver_setup = receiver.extend(choices.len() + 256).unwrap();
+        sender.extend(data.len() + 256, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap();
+        sender.check(chi_seed, receiver_check).unwrap();
+
+        let mut sender_shards = sender.partition(3).unwrap();
+        let receiver_shards = receiver.partition(3).unwrap();
+
+        assert_eq!(sender.remaining(), 0);
+        assert!(sender_shards[0].partition(2).is_err());
+
+        let mut ids = Vec::new();
+        let mut received = Vec::new();
+        let mut offset = 0;
+        for (mut sender, mut receiver) in sender_shards.into_iter().zip(receiver_shards) {
+            let count = receiver.remaining();
+            assert_eq!(sender.remaining(), count);
+
+            let choices = &choices[offset..offset + count];
+            let data = &data[offset..offset + count];
+            offset += count;
+
+            let mut receiver_keys = receiver.keys(count).unwrap();
+            let derandomize = receiver_keys.derandomize(choices).unwrap();
+
+            let mut sender_keys = sender.keys(count).unwrap();
+            sender_keys.derandomize(derandomize).unwrap();
+            let payload = sender_keys.encrypt_blocks(data).unwrap();
+
+            ids.push(payload.id);
+            received.extend(receiver_keys.decrypt_blocks(payload).unwrap());
+        }
+
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(received, expected);
+    }
+
     #[rstest]
     fn test_kos_extension_bytes(
         delta: Block,
@@ -244,6 +371,79 @@ mod tests {
         assert!(matches!(receiver_setup, ReceiverError::InvalidState(_)));
     }
 
+    #[rstest]
+    fn test_kos_extension_rekey(
+        delta: Block,
+        sender_seeds: [Block; CSP],
+        receiver_seeds: [[Block; 2]; CSP],
+        chi_seed: Block,
+        choices: Vec<bool>,
+        data: Vec<[Block; 2]>,
+        expected: Vec<Block>,
+    ) {
+        let sender = Sender::new(SenderConfig::default());
+        let receiver = Receiver::new(ReceiverConfig::default());
+
+        let mut sender = sender.setup(delta, sender_seeds);
+        let mut receiver = receiver.setup(receiver_seeds);
+
+        let receiver_setup = receiver.extend(choices.len() + 256).unwrap();
+        sender.extend(data.len() + 256, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap();
+        sender.check(chi_seed, receiver_check).unwrap();
+
+        // Rekey with fresh base OTs, keeping the remaining OTs.
+        let mut rng = ChaCha12Rng::seed_from_u64(5);
+        let new_delta: Block = rng.gen::<[u8; 16]>().into();
+        let new_receiver_seeds: [[Block; 2]; CSP] = std::array::from_fn(|_| [rng.gen(), rng.gen()]);
+        let new_sender_seeds: [Block; CSP] = std::array::from_fn(|i| {
+            new_receiver_seeds[i][new_delta.iter_lsb0().nth(i).unwrap() as usize]
+        });
+
+        sender.rekey(new_delta, new_sender_seeds).unwrap();
+        receiver.rekey(new_receiver_seeds).unwrap();
+
+        let receiver_setup = receiver.extend(choices.len() + 256).unwrap();
+        sender.extend(data.len() + 256, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap();
+        sender.check(chi_seed, receiver_check).unwrap();
+
+        assert_eq!(sender.remaining(), 2 * data.len());
+        assert_eq!(receiver.remaining(), 2 * choices.len());
+
+        // Transfer with the OTs of both extensions.
+        for _ in 0..2 {
+            let mut receiver_keys = receiver.keys(choices.len()).unwrap();
+            let derandomize = receiver_keys.derandomize(&choices).unwrap();
+
+            let mut sender_keys = sender.keys(data.len()).unwrap();
+            sender_keys.derandomize(derandomize).unwrap();
+            let payload = sender_keys.encrypt_blocks(&data).unwrap();
+
+            let received = receiver_keys.decrypt_blocks(payload).unwrap();
+
+            assert_eq!(received, expected);
+        }
+    }
+
+    #[rstest]
+    fn test_kos_rekey_shard_fails(delta: Block, sender_seeds: [Block; CSP]) {
+        let mut sender = Sender::new(SenderConfig::default()).setup(delta, sender_seeds);
+
+        let mut shards = sender.partition(2).unwrap();
+
+        assert!(matches!(
+            shards[0].check_rekey().unwrap_err(),
+            SenderError::InvalidState(_)
+        ));
+        assert!(matches!(
+            shards[0].rekey(delta, sender_seeds).unwrap_err(),
+            SenderError::InvalidState(_)
+        ));
+    }
+
     #[rstest]
     fn test_kos_extension_insufficient_setup(
         delta: Block,
@@ -264,6 +464,7 @@ mod tests {
         let err = receiver.check(chi_seed).unwrap_err();
 
         assert!(matches!(err, ReceiverError::InsufficientSetup(_, _)));
+        assert!(err.is_recoverable());
     }
 
     #[rstest]
@@ -289,6 +490,44 @@ mod tests {
         let receiver_check = receiver.check(chi_seed).unwrap();
         let err = sender.check(chi_seed, receiver_check).unwrap_err();
 
+        assert!(matches!(err, SenderError::ConsistencyCheckFailed));
+        assert_eq!(err.kind(), ErrorKind::PeerMisbehavior);
+        assert!(!err.is_recoverable());
+    }
+
+    #[rstest]
+    fn test_kos_extension_hashed_check(
+        delta: Block,
+        sender_seeds: [Block; CSP],
+        receiver_seeds: [[Block; 2]; CSP],
+        chi_seed: Block,
+    ) {
+        let sender = Sender::new(SenderConfig::builder().hashed_check().build().unwrap());
+        let receiver = Receiver::new(ReceiverConfig::builder().hashed_check().build().unwrap());
+
+        let mut sender = sender.setup(delta, sender_seeds);
+        let mut receiver = receiver.setup(receiver_seeds);
+
+        let receiver_setup = receiver.extend(512).unwrap();
+        sender.extend(512, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap().into_hashed();
+        sender.check_hashed(chi_seed, receiver_check).unwrap();
+
+        // A malicious receiver is still caught.
+        let sender = Sender::new(SenderConfig::default());
+        let receiver = Receiver::new(ReceiverConfig::default());
+
+        let mut sender = sender.setup(delta, sender_seeds);
+        let mut receiver = receiver.setup(receiver_seeds);
+
+        let mut receiver_setup = receiver.extend(512).unwrap();
+        *receiver_setup.us.first_mut().unwrap() ^= 1;
+        sender.extend(512, receiver_setup).unwrap();
+
+        let receiver_check = receiver.check(chi_seed).unwrap().into_hashed();
+        let err = sender.check_hashed(chi_seed, receiver_check).unwrap_err();
+
         assert!(matches!(err, SenderError::ConsistencyCheckFailed));
     }
 
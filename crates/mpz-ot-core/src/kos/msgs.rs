@@ -1,9 +1,12 @@
 //! Messages for the KOS15 protocol.
 
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use mpz_core::serialize::WireMessage;
 use mpz_core::Block;
 use serde::{Deserialize, Serialize};
 
-use crate::TransferId;
+use crate::{kos::hash_check, TransferId};
 
 /// Extension message sent by the receiver to agree upon the number of OTs to set up.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -58,6 +61,27 @@ pub struct Check {
     pub t1: Block,
 }
 
+impl Check {
+    /// Returns the hashed form of the check values, see [`HashedCheck`].
+    pub fn into_hashed(self) -> HashedCheck {
+        HashedCheck {
+            x: self.x,
+            hash: hash_check(self.t0, self.t1),
+        }
+    }
+}
+
+/// Values for the correlation check sent by the receiver, with `t0` and `t1` replaced by a hash.
+///
+/// The sender recomputes `t0` and `t1` from `x`, so only their hash needs to be sent, saving a
+/// block per check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct HashedCheck {
+    pub x: Block,
+    pub hash: Block,
+}
+
 /// Sender payload message.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SenderPayload {
@@ -85,3 +109,14 @@ pub enum Ciphertexts {
         length: u32,
     },
 }
+
+#[cfg(feature = "std")]
+impl WireMessage for StartExtend {}
+#[cfg(feature = "std")]
+impl WireMessage for Extend {}
+#[cfg(feature = "std")]
+impl WireMessage for Check {}
+#[cfg(feature = "std")]
+impl WireMessage for HashedCheck {}
+#[cfg(feature = "std")]
+impl WireMessage for SenderPayload {}
@@ -1,33 +1,40 @@
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
+use alloc::{collections::BTreeMap, format, string::ToString, sync::Arc, vec, vec::Vec};
 
 use crate::{
     kos::{
         error::ReceiverVerifyError,
         msgs::{Check, Ciphertexts, Extend, SenderPayload},
-        Aes128Ctr, ReceiverConfig, ReceiverError, Rng, RngSeed, CSP, SSP,
+        seed_rng, shard_sizes, Aes128Ctr, ReceiverConfig, ReceiverError, Rng, RngSeed, CSP,
+        MAX_SHARDS, SSP,
     },
     msgs::Derandomize,
+    sync::Mutex,
     TransferId,
 };
 
-use itybity::{FromBitIterator, IntoBits, ToBits};
-use mpz_core::{aes::FIXED_KEY_AES, Block};
+use itybity::{FromBitIterator, IntoBitIterator, ToBits};
+use mpz_core::{
+    aes::FIXED_KEY_AES,
+    bits::{pack_bits, unpack_bits, BitOrder},
+    crhash::tweak,
+    pool::{BLOCK_POOL, BYTE_POOL},
+    utils::{rng, xor_bytes},
+    Block,
+};
 
 use blake3::Hasher;
 use cipher::{KeyIvInit, StreamCipher};
-use rand::{thread_rng, Rng as _, SeedableRng};
+use rand::{Rng as _, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use rand_core::RngCore;
+use subtle::ConstantTimeEq;
 
-#[cfg(feature = "rayon")]
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 
 #[derive(Debug, Default)]
 struct Tape {
-    records: HashMap<TransferId, PayloadRecordNoDelta>,
+    records: BTreeMap<TransferId, PayloadRecordNoDelta>,
 }
 
 /// KOS15 receiver.
@@ -74,17 +81,7 @@ impl Receiver {
     pub fn setup(self, seeds: [[Block; 2]; CSP]) -> Receiver<state::Extension> {
         let rngs = seeds
             .iter()
-            .map(|seeds| {
-                seeds.map(|seed| {
-                    // Stretch the Block-sized seed to a 32-byte seed.
-                    let mut seed_ = RngSeed::default();
-                    seed_
-                        .iter_mut()
-                        .zip(seed.to_bytes().into_iter().cycle())
-                        .for_each(|(s, c)| *s = c);
-                    Rng::from_seed(seed_)
-                })
-            })
+            .map(|seeds| seeds.map(|seed| seed_rng(&seed)))
             .collect();
 
         Receiver {
@@ -116,6 +113,12 @@ impl Receiver<state::Extension> {
         self.state.keys.len()
     }
 
+    /// Returns `true` if the OTs have been extended since the setup or the last rekey, in which
+    /// case the base OTs must be rekeyed before extending again.
+    pub fn is_extended(&self) -> bool {
+        self.state.extended
+    }
+
     /// Perform the IKNP OT extension.
     ///
     /// The provided count _must_ be a multiple of 64, otherwise an error will be returned.
@@ -150,20 +153,19 @@ impl Receiver<state::Extension> {
         const NROWS: usize = CSP;
         let row_width = count / 8;
 
-        let mut rng = thread_rng();
-        // x₁,...,xₗ bits in Figure 3, step 1.
-        let choices = (0..row_width)
-            .flat_map(|_| rng.gen::<u8>().into_iter_lsb0())
-            .collect::<Vec<_>>();
-
+        let mut rng = rng();
         // 𝐱ⁱ in Figure 3. Note that it is the same for all i = 1,...,k.
-        let choice_vector = Vec::<u8>::from_lsb0_iter(choices.iter().copied());
+        let mut choice_vector = vec![0u8; row_width];
+        rng.fill(&mut choice_vector[..]);
+
+        // x₁,...,xₗ bits in Figure 3, step 1.
+        let choices = unpack_bits(&choice_vector, BitOrder::Lsb0);
 
         // 𝐭₀ⁱ in Figure 3.
-        let mut ts = vec![0u8; NROWS * row_width];
+        let mut ts = BYTE_POOL.take(NROWS * row_width);
         let mut us = vec![0u8; NROWS * row_width];
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let iter = self.state.rngs
                     .par_iter_mut()
                     .zip(ts.par_chunks_exact_mut(row_width))
@@ -184,20 +186,17 @@ impl Receiver<state::Extension> {
 
             // Figure 3, step 3.
             // Computing `u = t_0 + t_1 + x`.
-            u.iter_mut()
-                .zip(t_0)
-                .zip(&choice_vector)
-                .for_each(|((u, t_0), x)| {
-                    *u ^= *t_0 ^ x;
-                });
+            xor_bytes(u, t_0);
+            xor_bytes(u, &choice_vector);
         });
 
-        matrix_transpose::transpose_bits(&mut ts, NROWS).expect("matrix is rectangular");
+        mpz_core::transpose::transpose_bits(&mut ts, NROWS).expect("matrix is rectangular");
 
         self.state.unchecked_ts.extend(
             ts.chunks_exact(NROWS / 8)
                 .map(|t| Block::try_from(t).unwrap()),
         );
+        BYTE_POOL.put(ts);
         self.state.unchecked_choices.extend(choices);
 
         Ok(Extend { us })
@@ -237,23 +236,23 @@ impl Receiver<state::Extension> {
 
         let mut rng = Rng::from_seed(seed);
 
-        let mut unchecked_ts = std::mem::take(&mut self.state.unchecked_ts);
-        let mut unchecked_choices = std::mem::take(&mut self.state.unchecked_choices);
+        let mut unchecked_ts = core::mem::take(&mut self.state.unchecked_ts);
+        let mut unchecked_choices = core::mem::take(&mut self.state.unchecked_choices);
 
         // Figure 7, "Check correlation", point 1.
         // Sample random weights for the consistency check.
-        let chis = (0..unchecked_ts.len())
-            .map(|_| Block::random(&mut rng))
-            .collect::<Vec<_>>();
+        let mut chis = BLOCK_POOL.take(unchecked_ts.len());
+        chis.iter_mut()
+            .for_each(|chi| *chi = Block::random(&mut rng));
 
         // Figure 7, "Check correlation", point 2.
         // Compute the random linear combinations.
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let (x, t0, t1) = unchecked_choices.par_iter()
                     .zip(&unchecked_ts)
-                    .zip(chis)
-                    .map(|((c, t), chi)| {
+                    .zip(chis.par_iter())
+                    .map(|((c, t), &chi)| {
                         let x = if *c { chi } else { Block::ZERO };
                         let (t0, t1) = t.clmul(chi);
                         (x, t0, t1)
@@ -267,8 +266,8 @@ impl Receiver<state::Extension> {
             } else {
                 let (x, t0, t1) = unchecked_choices.iter()
                     .zip(&unchecked_ts)
-                    .zip(chis)
-                    .map(|((c, t), chi)| {
+                    .zip(&chis)
+                    .map(|((c, t), &chi)| {
                         let x = if *c { chi } else { Block::ZERO };
                         let (t0, t1) = t.clmul(chi);
                         (x, t0, t1)
@@ -279,13 +278,15 @@ impl Receiver<state::Extension> {
             }
         }
 
+        BLOCK_POOL.put(chis);
+
         // Strip off the rows sacrificed for the consistency check.
         let nrows = unchecked_ts.len() - (CSP + SSP);
         unchecked_ts.truncate(nrows);
         unchecked_choices.truncate(nrows);
 
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let iter = unchecked_ts.par_iter().enumerate();
             } else {
                 let iter = unchecked_ts.iter().enumerate();
@@ -295,7 +296,7 @@ impl Receiver<state::Extension> {
         let cipher = &(*FIXED_KEY_AES);
         let keys = iter
             .map(|(j, t)| {
-                let j = Block::from(((self.state.index + j) as u128).to_be_bytes());
+                let j = tweak((self.state.index + j) as u128);
                 cipher.tccr(j, *t)
             })
             .collect::<Vec<_>>();
@@ -308,8 +309,9 @@ impl Receiver<state::Extension> {
 
         // If we're recording, we track `ts` too
         if self.state.tape.is_some() {
-            self.state.ts.extend(unchecked_ts);
+            self.state.ts.extend_from_slice(&unchecked_ts);
         }
+        BLOCK_POOL.put(unchecked_ts);
 
         // Disable any further extensions.
         self.state.extended = true;
@@ -317,6 +319,50 @@ impl Receiver<state::Extension> {
         Ok(Check { x, t0, t1 })
     }
 
+    /// Refreshes the base OTs.
+    ///
+    /// Rekeying allows the receiver to extend again, as an extension can only be performed once
+    /// per set of base OTs. The remaining OTs are kept, and transfer ids continue from the current
+    /// transfer id. The sender must rekey with its choices of the same base OTs.
+    ///
+    /// A receiver which records a tape for verification can not be rekeyed, as the transfers are
+    /// verified with a single delta.
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - The rng seeds to send to the sender via the new base OT
+    pub fn rekey(&mut self, seeds: [[Block; 2]; CSP]) -> Result<(), ReceiverError> {
+        self.check_rekey()?;
+
+        self.state.rngs = seeds
+            .iter()
+            .map(|seeds| seeds.map(|seed| seed_rng(&seed)))
+            .collect();
+        self.state.extended = false;
+
+        Ok(())
+    }
+
+    /// Checks that the receiver can be rekeyed, so that the new base OT is only performed if
+    /// [`rekey`](Self::rekey) would succeed.
+    pub fn check_rekey(&self) -> Result<(), ReceiverError> {
+        if self.state.rngs.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "shards can not be rekeyed".to_string(),
+            ));
+        } else if !self.state.unchecked_ts.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "can not rekey during extension".to_string(),
+            ));
+        } else if self.state.tape.is_some() {
+            return Err(ReceiverError::InvalidState(
+                "a receiver recording a tape can not be rekeyed".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Returns receiver's keys for the given number of OTs.
     ///
     /// # Arguments
@@ -347,6 +393,67 @@ impl Receiver<state::Extension> {
         })
     }
 
+    /// Partitions the remaining OTs into `k` shards.
+    ///
+    /// Each shard can be used independently of the others, eg. concurrently on a forked
+    /// context, and assigns transfer IDs from a range which is disjoint from the other shards.
+    /// The sender must partition in the same order with the same `k`.
+    ///
+    /// Shards share the tape of this receiver, so transfers made with a shard can be verified
+    /// once this receiver enters the verification state.
+    ///
+    /// Shards can not be extended or partitioned further.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The number of shards, at most [`MAX_SHARDS`].
+    pub fn partition(
+        &mut self,
+        k: usize,
+    ) -> Result<Vec<Receiver<state::Extension>>, ReceiverError> {
+        if k == 0 || k > MAX_SHARDS {
+            return Err(ReceiverError::InvalidShardCount(k));
+        } else if self.state.rngs.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "shards can not be partitioned".to_string(),
+            ));
+        } else if !self.state.unchecked_ts.is_empty() {
+            return Err(ReceiverError::InvalidState(
+                "can not partition during extension".to_string(),
+            ));
+        }
+
+        let partition = self.state.transfer_id.next();
+        let count = self.state.keys.len();
+        let mut index = self.state.index - count;
+
+        Ok(shard_sizes(count, k)
+            .enumerate()
+            .map(|(i, size)| {
+                index += size;
+                Receiver {
+                    config: self.config.clone(),
+                    state: state::Extension {
+                        rngs: Vec::new(),
+                        ts: if self.state.tape.is_some() {
+                            self.state.ts.drain(..size).collect()
+                        } else {
+                            Vec::new()
+                        },
+                        keys: self.state.keys.drain(..size).collect(),
+                        choices: self.state.choices.drain(..size).collect(),
+                        index,
+                        transfer_id: TransferId::shard(partition, i),
+                        extended: true,
+                        unchecked_ts: Vec::new(),
+                        unchecked_choices: Vec::new(),
+                        tape: self.state.tape.clone(),
+                    },
+                }
+            })
+            .collect())
+    }
+
     /// Enters the verification state for verifiable OT.
     ///
     /// # ⚠️ Warning ⚠️
@@ -393,7 +500,6 @@ impl Receiver<state::Verify> {
             .state
             .tape
             .lock()
-            .unwrap()
             .records
             .remove(&id)
             .ok_or(ReceiverVerifyError::InvalidTransferId(id))
@@ -442,27 +548,45 @@ impl ReceiverKeys {
 
     /// Derandomizes the receiver's choices.
     pub fn derandomize(&mut self, choices: &[bool]) -> Result<Derandomize, ReceiverError> {
-        if choices.len() != self.choices.len() {
-            return Err(ReceiverError::CountMismatch(
-                self.choices.len(),
-                choices.len(),
-            ));
+        self.derandomize_bits(choices)
+    }
+
+    /// Derandomizes the receiver's choices, reading them from a bit iterator.
+    ///
+    /// This accepts packed choices, eg. a `&[u8]` with 8 choices per byte in LSB0 order, so
+    /// callers do not need to allocate a `Vec<bool>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `choices` - The receiver's choices, the number of bits must match the number of keys.
+    pub fn derandomize_bits<I: IntoBitIterator>(
+        &mut self,
+        choices: I,
+    ) -> Result<Derandomize, ReceiverError> {
+        let mut count = 0;
+        let mut choices = choices.into_iter_lsb0();
+        let flip = Vec::<u8>::from_lsb0_iter(self.choices.iter().zip(choices.by_ref()).map(
+            |(setup_choice, new_choice)| {
+                count += 1;
+                setup_choice ^ new_choice
+            },
+        ));
+
+        let actual = count + choices.count();
+        if actual != self.choices.len() {
+            return Err(ReceiverError::CountMismatch(self.choices.len(), actual));
         }
 
-        let derandomize = Derandomize {
+        self.choices
+            .iter_mut()
+            .zip(flip.iter_lsb0())
+            .for_each(|(choice, flip)| *choice ^= flip);
+
+        Ok(Derandomize {
             id: self.id,
             count: self.choices.len() as u32,
-            flip: Vec::<u8>::from_lsb0_iter(
-                self.choices
-                    .iter()
-                    .zip(choices)
-                    .map(|(setup_choice, new_choice)| setup_choice ^ new_choice),
-            ),
-        };
-
-        self.choices.copy_from_slice(choices);
-
-        Ok(derandomize)
+            flip,
+        })
     }
 
     /// Decrypts the sender's payload.
@@ -494,11 +618,11 @@ impl ReceiverKeys {
                 hasher.update(&ct.to_bytes());
             });
 
-            tape.lock().unwrap().records.insert(
+            tape.lock().records.insert(
                 id,
                 PayloadRecordNoDelta {
                     index: self.index,
-                    choices: Vec::from_lsb0_iter(self.choices.iter().copied()),
+                    choices: pack_bits(&self.choices, BitOrder::Lsb0),
                     ts,
                     keys: self.keys.clone(),
                     ciphertext_digest: hasher.finalize().into(),
@@ -647,7 +771,7 @@ impl PayloadRecord {
             .zip(purported_msgs)
             .enumerate()
         {
-            let j = Block::new(((counter + j) as u128).to_be_bytes());
+            let j = tweak((counter + j) as u128);
             let key_ = cipher.tccr(j, t ^ delta);
 
             let (ct0, ct1) = if c {
@@ -662,7 +786,7 @@ impl PayloadRecord {
 
         let digest: [u8; 32] = hasher.finalize().into();
 
-        if ciphertext_digest != digest {
+        if !bool::from(ciphertext_digest.ct_eq(&digest)) {
             return Err(ReceiverVerifyError::InconsistentPayload)?;
         }
 
@@ -732,6 +856,19 @@ pub mod state {
 
     opaque_debug::implement!(Extension);
 
+    #[cfg(feature = "zeroize")]
+    impl Drop for Extension {
+        fn drop(&mut self) {
+            use zeroize::Zeroize;
+
+            self.ts.zeroize();
+            self.keys.zeroize();
+            self.choices.zeroize();
+            self.unchecked_ts.zeroize();
+            self.unchecked_choices.zeroize();
+        }
+    }
+
     /// The receiver's state after receiving the sender's base OT choice bits, a.k.a delta.
     pub struct Verify {
         /// Protocol tape
@@ -743,4 +880,11 @@ pub mod state {
     impl State for Verify {}
 
     opaque_debug::implement!(Verify);
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for Verify {
+        fn drop(&mut self) {
+            zeroize::Zeroize::zeroize(&mut self.delta);
+        }
+    }
 }
@@ -1,8 +1,11 @@
+use alloc::{string::ToString, vec, vec::Vec};
+
 use crate::{
     kos::{
-        extension_matrix_size,
-        msgs::{Check, Ciphertexts, Extend, SenderPayload},
-        Aes128Ctr, Rng, RngSeed, SenderConfig, SenderError, CSP, SSP,
+        extension_matrix_size, hash_check,
+        msgs::{Check, Ciphertexts, Extend, HashedCheck, SenderPayload},
+        seed_rng, shard_sizes, Aes128Ctr, Rng, RngSeed, SenderConfig, SenderError, CSP, MAX_SHARDS,
+        SSP,
     },
     msgs::Derandomize,
     TransferId,
@@ -10,18 +13,23 @@ use crate::{
 
 use cipher::{KeyIvInit, StreamCipher};
 use itybity::ToBits;
-use mpz_core::{aes::FIXED_KEY_AES, Block};
+use mpz_core::{
+    aes::FIXED_KEY_AES,
+    crhash::tweak,
+    pool::{BLOCK_POOL, BYTE_POOL},
+    utils::{rng, xor_bytes},
+    Block,
+};
 
 use rand::{Rng as _, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use rand_core::RngCore;
+use subtle::{Choice, ConstantTimeEq};
 
 cfg_if::cfg_if! {
-    if #[cfg(feature = "rayon")] {
+    if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
         use itybity::ToParallelBits;
         use rayon::prelude::*;
-    } else {
-        use itybity::ToBits;
     }
 }
 
@@ -62,18 +70,7 @@ impl Sender {
     /// * `delta` - The sender's base OT choice bits
     /// * `seeds` - The rng seeds chosen during base OT
     pub fn setup(self, delta: Block, seeds: [Block; CSP]) -> Sender<state::Extension> {
-        let rngs = seeds
-            .iter()
-            .map(|seed| {
-                // Stretch the Block-sized seed to a 32-byte seed.
-                let mut seed_ = RngSeed::default();
-                seed_
-                    .iter_mut()
-                    .zip(seed.to_bytes().into_iter().cycle())
-                    .for_each(|(s, c)| *s = c);
-                Rng::from_seed(seed_)
-            })
-            .collect();
+        let rngs = seeds.iter().map(seed_rng).collect();
 
         Sender {
             config: self.config,
@@ -96,6 +93,12 @@ impl Sender<state::Extension> {
         self.state.keys.len()
     }
 
+    /// Returns `true` if the OTs have been extended since the setup or the last rekey, in which
+    /// case the base OTs must be rekeyed before extending again.
+    pub fn is_extended(&self) -> bool {
+        self.state.extended
+    }
+
     /// Perform the IKNP OT extension.
     ///
     /// The provided count _must_ be a multiple of 64, otherwise an error will be returned.
@@ -137,9 +140,9 @@ impl Sender<state::Extension> {
             return Err(SenderError::InvalidExtend);
         }
 
-        let mut qs = vec![0u8; NROWS * row_width];
+        let mut qs = BYTE_POOL.take(NROWS * row_width);
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let iter = self.state.delta
                     .par_iter_lsb0()
                     .zip(self.state.rngs.par_iter_mut())
@@ -161,11 +164,11 @@ impl Sender<state::Extension> {
             rng.fill_bytes(q);
             // If `b` (i.e. ∆ᵢ) is true, xor `u` into `q`, otherwise xor 0 into `q` (constant time).
             let u = if b { u } else { &zero };
-            q.iter_mut().zip(u).for_each(|(q, u)| *q ^= u);
+            xor_bytes(q, u);
         });
 
         // Figure 3, step 5.
-        matrix_transpose::transpose_bits(&mut qs, NROWS).expect("matrix is rectangular");
+        mpz_core::transpose::transpose_bits(&mut qs, NROWS).expect("matrix is rectangular");
 
         self.state
             .unchecked_qs
@@ -173,6 +176,7 @@ impl Sender<state::Extension> {
                 let q: Block = q.try_into().unwrap();
                 q
             }));
+        BYTE_POOL.put(qs);
 
         Ok(())
     }
@@ -197,6 +201,36 @@ impl Sender<state::Extension> {
     /// * `chi_seed` - The seed used to generate the consistency check weights.
     /// * `receiver_check` - The receiver's consistency check message.
     pub fn check(&mut self, chi_seed: Block, receiver_check: Check) -> Result<(), SenderError> {
+        let Check { x, t0, t1 } = receiver_check;
+        self.check_inner(chi_seed, x, |check| check.0.ct_eq(&t0) & check.1.ct_eq(&t1))
+    }
+
+    /// Performs the correlation check for all outstanding OTS, using the hash of the receiver's
+    /// check values.
+    ///
+    /// This is the same check as [`Sender::check`], see [`HashedCheck`].
+    ///
+    /// # Arguments
+    ///
+    /// * `chi_seed` - The seed used to generate the consistency check weights.
+    /// * `receiver_check` - The receiver's hashed consistency check message.
+    pub fn check_hashed(
+        &mut self,
+        chi_seed: Block,
+        receiver_check: HashedCheck,
+    ) -> Result<(), SenderError> {
+        let HashedCheck { x, hash } = receiver_check;
+        self.check_inner(chi_seed, x, |check| {
+            hash_check(check.0, check.1).ct_eq(&hash)
+        })
+    }
+
+    fn check_inner(
+        &mut self,
+        chi_seed: Block,
+        x: Block,
+        verify: impl FnOnce((Block, Block)) -> Choice,
+    ) -> Result<(), SenderError> {
         // Make sure we have enough sacrificial OTs to perform the consistency check.
         if self.state.unchecked_qs.len() < CSP + SSP {
             return Err(SenderError::InsufficientSetup(
@@ -212,43 +246,37 @@ impl Sender<state::Extension> {
 
         let mut rng = Rng::from_seed(seed);
 
-        let mut unchecked_qs = std::mem::take(&mut self.state.unchecked_qs);
+        let mut unchecked_qs = core::mem::take(&mut self.state.unchecked_qs);
 
         // Figure 7, "Check correlation", point 1.
         // Sample random weights for the consistency check.
-        let chis = (0..unchecked_qs.len())
-            .map(|_| rng.gen())
-            .collect::<Vec<_>>();
+        let mut chis = BLOCK_POOL.take(unchecked_qs.len());
+        chis.iter_mut().for_each(|chi| *chi = rng.gen());
 
         // Figure 7, "Check correlation", point 3.
         // Compute the random linear combinations.
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
                 let check = unchecked_qs.par_iter()
-                    .zip(chis)
-                    .map(|(q, chi)| q.clmul(chi))
+                    .zip(chis.par_iter())
+                    .map(|(q, chi)| q.clmul(*chi))
                     .reduce(
                         || (Block::ZERO, Block::ZERO),
                         |(_a, _b), (a, b)| (a ^ _a, b ^ _b),
                     );
             } else {
-                let check = unchecked_qs.iter()
-                    .zip(chis)
-                    .map(|(q, chi)| q.clmul(chi))
-                    .reduce(
-                        |(_a, _b), (a, b)| (a ^ _a, b ^ _b),
-                    ).unwrap();
+                let check = Block::inn_prdt_no_red(&unchecked_qs, &chis);
             }
         }
+        BLOCK_POOL.put(chis);
 
-        let Check { x, t0, t1 } = receiver_check;
         let tmp = x.clmul(self.state.delta);
         let check = (check.0 ^ tmp.0, check.1 ^ tmp.1);
 
         // The Receiver is malicious.
         //
         // Call the police!
-        if check != (t0, t1) {
+        if !bool::from(verify(check)) {
             return Err(SenderError::ConsistencyCheckFailed);
         }
 
@@ -258,17 +286,17 @@ impl Sender<state::Extension> {
 
         // Figure 7, "Randomization"
         cfg_if::cfg_if! {
-            if #[cfg(feature = "rayon")] {
-                let iter = unchecked_qs.into_par_iter().enumerate();
+            if #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))] {
+                let iter = unchecked_qs.par_iter().enumerate();
             } else {
-                let iter = unchecked_qs.into_iter().enumerate();
+                let iter = unchecked_qs.iter().enumerate();
             }
         }
 
         let cipher = &(*FIXED_KEY_AES);
         let keys = iter
-            .map(|(j, q)| {
-                let j = Block::new(((self.state.counter + j) as u128).to_be_bytes());
+            .map(|(j, &q)| {
+                let j = tweak((self.state.counter + j) as u128);
 
                 let k0 = cipher.tccr(j, q);
                 let k1 = cipher.tccr(j, q ^ self.state.delta);
@@ -276,6 +304,7 @@ impl Sender<state::Extension> {
                 [k0, k1]
             })
             .collect::<Vec<_>>();
+        BLOCK_POOL.put(unchecked_qs);
 
         self.state.counter += keys.len();
         self.state.keys.extend(keys);
@@ -284,6 +313,42 @@ impl Sender<state::Extension> {
         Ok(())
     }
 
+    /// Refreshes the base OTs, with a new delta.
+    ///
+    /// Rekeying allows the sender to extend again, as an extension can only be performed once per
+    /// set of base OTs. The remaining OTs are kept, and transfer ids continue from the current
+    /// transfer id. The receiver must rekey with the seeds of the same base OTs.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The sender's new base OT choice bits
+    /// * `seeds` - The rng seeds chosen during the new base OT
+    pub fn rekey(&mut self, delta: Block, seeds: [Block; CSP]) -> Result<(), SenderError> {
+        self.check_rekey()?;
+
+        self.state.delta = delta;
+        self.state.rngs = seeds.iter().map(seed_rng).collect();
+        self.state.extended = false;
+
+        Ok(())
+    }
+
+    /// Checks that the sender can be rekeyed, so that the new base OT is only performed if
+    /// [`rekey`](Self::rekey) would succeed.
+    pub fn check_rekey(&self) -> Result<(), SenderError> {
+        if self.state.rngs.is_empty() {
+            return Err(SenderError::InvalidState(
+                "shards can not be rekeyed".to_string(),
+            ));
+        } else if !self.state.unchecked_qs.is_empty() {
+            return Err(SenderError::InvalidState(
+                "can not rekey during extension".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Reserves a set of keys which can be used to encrypt a payload later.
     ///
     /// # Arguments
@@ -302,6 +367,50 @@ impl Sender<state::Extension> {
             derandomize: None,
         })
     }
+
+    /// Partitions the remaining OTs into `k` shards.
+    ///
+    /// Each shard can be used independently of the others, eg. concurrently on a forked
+    /// context, and assigns transfer IDs from a range which is disjoint from the other shards.
+    /// The receiver must partition in the same order with the same `k`.
+    ///
+    /// Shards can not be extended or partitioned further.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The number of shards, at most [`MAX_SHARDS`].
+    pub fn partition(&mut self, k: usize) -> Result<Vec<Sender<state::Extension>>, SenderError> {
+        if k == 0 || k > MAX_SHARDS {
+            return Err(SenderError::InvalidShardCount(k));
+        } else if self.state.rngs.is_empty() {
+            return Err(SenderError::InvalidState(
+                "shards can not be partitioned".to_string(),
+            ));
+        } else if !self.state.unchecked_qs.is_empty() {
+            return Err(SenderError::InvalidState(
+                "can not partition during extension".to_string(),
+            ));
+        }
+
+        let partition = self.state.transfer_id.next();
+        let count = self.state.keys.len();
+
+        Ok(shard_sizes(count, k)
+            .enumerate()
+            .map(|(i, size)| Sender {
+                config: self.config.clone(),
+                state: state::Extension {
+                    delta: self.state.delta,
+                    rngs: Vec::new(),
+                    keys: self.state.keys.drain(..size).collect(),
+                    transfer_id: TransferId::shard(partition, i),
+                    counter: 0,
+                    extended: true,
+                    unchecked_qs: Vec::new(),
+                },
+            })
+            .collect())
+    }
 }
 
 /// KOS sender's keys for a single transfer.
@@ -348,6 +457,20 @@ impl SenderKeys {
     ///
     /// * `msgs` - The messages to encrypt
     pub fn encrypt_blocks(self, msgs: &[[Block; 2]]) -> Result<SenderPayload, SenderError> {
+        self.encrypt_blocks_iter(msgs.iter().copied())
+    }
+
+    /// Encrypts the messages provided by an iterator using the keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `msgs` - The messages to encrypt
+    pub fn encrypt_blocks_iter<I>(self, msgs: I) -> Result<SenderPayload, SenderError>
+    where
+        I: IntoIterator<Item = [Block; 2]>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let msgs = msgs.into_iter();
         if msgs.len() != self.keys.len() {
             return Err(SenderError::InsufficientSetup(msgs.len(), self.keys.len()));
         }
@@ -369,9 +492,9 @@ impl SenderKeys {
                 // Use Beaver derandomization to correct the receiver's choices
                 // from the extension phase.
                 if flip {
-                    [k1 ^ *m0, k0 ^ *m1]
+                    [k1 ^ m0, k0 ^ m1]
                 } else {
-                    [k0 ^ *m0, k1 ^ *m1]
+                    [k0 ^ m0, k1 ^ m1]
                 }
             })
             .collect();
@@ -397,7 +520,7 @@ impl SenderKeys {
 
         // Generate a random IV which is used for all messages.
         // This is safe because every message is encrypted with a different key.
-        let iv: [u8; 16] = rand::thread_rng().gen();
+        let iv: [u8; 16] = rng().gen();
 
         // If we have derandomization, use it to correct the receiver's choices, else we use
         // default
@@ -502,4 +625,15 @@ pub mod state {
     impl State for Extension {}
 
     opaque_debug::implement!(Extension);
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for Extension {
+        fn drop(&mut self) {
+            use zeroize::Zeroize;
+
+            self.delta.zeroize();
+            self.keys.zeroize();
+            self.unchecked_qs.zeroize();
+        }
+    }
 }
@@ -9,7 +9,14 @@
 //! low-level APIs naively. Failing to uphold these invariants may result in security vulnerabilities.
 //!
 //! USE AT YOUR OWN RISK.
+//!
+//! # `no_std`
+//!
+//! Without the default `std` feature this crate is `no_std` and requires `alloc`. The CO15, KOS15,
+//! OPRF and ideal state machines are available, while Ferret requires `std`. See the `mpz-core`
+//! documentation for the requirements of `no_std` targets.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(
     unsafe_code,
     missing_docs,
@@ -19,13 +26,27 @@
     clippy::all
 )]
 
+extern crate alloc;
+// `thiserror` derives implement `std::error::Error`, which is `core::error::Error`.
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
+use alloc::vec::Vec;
+use mpz_core::{Block, KeyBlock, MacBlock};
 use serde::{Deserialize, Serialize};
 
+#[cfg(all(feature = "std", any(test, feature = "arbitrary")))]
+pub mod arbitrary;
 pub mod chou_orlandi;
+#[cfg(feature = "std")]
 pub mod ferret;
 pub mod ideal;
 pub mod kos;
 pub mod msgs;
+pub mod oprf;
+#[cfg(feature = "proto")]
+pub mod proto;
+mod sync;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test;
 
@@ -37,8 +58,8 @@ pub mod test;
 )]
 pub struct TransferId(u64);
 
-impl std::fmt::Display for TransferId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for TransferId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "TransferId({})", self.0)
     }
 }
@@ -50,6 +71,61 @@ impl TransferId {
         self.0 += 1;
         id
     }
+
+    /// Returns the first transfer ID of a shard of a partition.
+    ///
+    /// Each shard is allotted `2^16` transfer IDs, which are disjoint from the IDs of other
+    /// shards and of unpartitioned transfers.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition` - The transfer ID consumed by the partition.
+    /// * `index` - The index of the shard, must be less than `2^16`.
+    pub(crate) fn shard(partition: TransferId, index: usize) -> Self {
+        debug_assert!(index < 1 << 16);
+        Self(((partition.0 + 1) << 32) | ((index as u64) << 16))
+    }
+}
+
+/// The category of an OT error, which determines whether it is safe to retry.
+///
+/// OT protocols must be handled with care when they fail: a failure caused by a malicious peer
+/// may itself leak private inputs, eg. in a selective failure attack, so retrying blindly is not
+/// safe. Each error reports its category with a `kind` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The caller violated the API contract, eg. by calling a method in the wrong state or with
+    /// an invalid count.
+    ///
+    /// The error is returned before any message of the operation is sent. State machines in this
+    /// crate are left unchanged, so the call can be retried with valid arguments.
+    CallerBug,
+    /// An IO error occurred.
+    ///
+    /// Messages of the operation may have been partially sent or received, so the instance must
+    /// be discarded. No check failed, so the protocol can be retried from setup with new
+    /// instances.
+    TransientIo,
+    /// A message of the peer did not match what was expected, eg. a transfer id, a count or the
+    /// configuration.
+    ///
+    /// This happens when the parties are out of sync, whether due to a bug or to a malicious
+    /// peer. The instance must be discarded, and the protocol must only be retried once the
+    /// cause is resolved.
+    ProtocolAbort,
+    /// The peer failed a check which an honest peer always passes, eg. a consistency check.
+    ///
+    /// The instance must be discarded and the protocol must not be retried with the same peer,
+    /// as the failure may leak private inputs.
+    PeerMisbehavior,
+}
+
+impl ErrorKind {
+    /// Returns `true` if it is safe to retry after an error of this kind, see the documentation
+    /// of each kind for whether the instance can be reused.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, ErrorKind::CallerBug | ErrorKind::TransientIo)
+    }
 }
 
 /// The output the sender receives from the COT functionality.
@@ -90,6 +166,34 @@ pub struct RCOTReceiverOutput<T, U> {
     pub msgs: Vec<U>,
 }
 
+impl COTSenderOutput<Block> {
+    /// Returns the `0-bit` messages as the keys of the receiver's MACs.
+    pub fn keys(&self) -> &[KeyBlock] {
+        KeyBlock::from_blocks(&self.msgs)
+    }
+}
+
+impl COTReceiverOutput<Block> {
+    /// Returns the chosen messages as the MACs of the receiver's choices.
+    pub fn macs(&self) -> &[MacBlock] {
+        MacBlock::from_blocks(&self.msgs)
+    }
+}
+
+impl RCOTSenderOutput<Block> {
+    /// Returns the `0-bit` messages as the keys of the receiver's MACs.
+    pub fn keys(&self) -> &[KeyBlock] {
+        KeyBlock::from_blocks(&self.msgs)
+    }
+}
+
+impl<T> RCOTReceiverOutput<T, Block> {
+    /// Returns the chosen messages as the MACs of the choice bits.
+    pub fn macs(&self) -> &[MacBlock] {
+        MacBlock::from_blocks(&self.msgs)
+    }
+}
+
 /// The output the sender receives from the ROT functionality.
 #[derive(Debug)]
 pub struct ROTSenderOutput<T> {
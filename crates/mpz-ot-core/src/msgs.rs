@@ -1,5 +1,8 @@
 //! General OT message types
 
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use mpz_core::serialize::WireMessage;
 use serde::{Deserialize, Serialize};
 
 use crate::TransferId;
@@ -24,18 +27,20 @@ struct UncheckedDerandomize {
     flip: Vec<u8>,
 }
 
+/// An error returned when a [`Derandomize`] message is malformed.
+#[derive(Debug, thiserror::Error)]
+#[error("flip length does not match count")]
+pub struct DerandomizeError;
+
 impl TryFrom<UncheckedDerandomize> for Derandomize {
-    type Error = std::io::Error;
+    type Error = DerandomizeError;
 
     fn try_from(value: UncheckedDerandomize) -> Result<Self, Self::Error> {
         // Divide by 8, rounding up
         let expected_len = (value.count as usize + 7) / 8;
 
         if value.flip.len() != expected_len {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "flip length does not match count",
-            ));
+            return Err(DerandomizeError);
         }
 
         Ok(Derandomize {
@@ -46,6 +51,69 @@ impl TryFrom<UncheckedDerandomize> for Derandomize {
     }
 }
 
+/// A fingerprint of the settings of an OT configuration which the sender and receiver must
+/// agree on.
+///
+/// Peers exchange fingerprints during setup, so that mismatched configurations are detected
+/// before any other message is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigFingerprint(pub [u8; 8]);
+
+impl ConfigFingerprint {
+    /// Creates a new fingerprint of the settings of a protocol.
+    pub(crate) fn new(protocol: &[u8], settings: &[u8]) -> Self {
+        let hash = blake3::Hasher::new()
+            .update(protocol)
+            .update(settings)
+            .finalize();
+
+        let mut fingerprint = [0u8; 8];
+        fingerprint.copy_from_slice(&hash.as_bytes()[..8]);
+
+        Self(fingerprint)
+    }
+
+    /// Checks that the fingerprint of the peer's configuration matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The fingerprint of the peer's configuration.
+    pub fn check(&self, peer: &ConfigFingerprint) -> Result<(), ConfigMismatch> {
+        if self != peer {
+            return Err(ConfigMismatch {
+                expected: *self,
+                actual: *peer,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for ConfigFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when the configuration of the peer does not match.
+#[derive(Debug, thiserror::Error)]
+#[error("config mismatch: expected fingerprint {expected}, got {actual}")]
+pub struct ConfigMismatch {
+    /// The fingerprint of our configuration.
+    pub expected: ConfigFingerprint,
+    /// The fingerprint of the peer's configuration.
+    pub actual: ConfigFingerprint,
+}
+
+#[cfg(feature = "std")]
+impl WireMessage for Derandomize {}
+#[cfg(feature = "std")]
+impl WireMessage for ConfigFingerprint {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +134,33 @@ mod tests {
         })
         .is_err());
     }
+
+    #[test]
+    fn test_config_fingerprint() {
+        let a = ConfigFingerprint::new(b"test", &[0]);
+        let b = ConfigFingerprint::new(b"test", &[1]);
+
+        assert!(a.check(&ConfigFingerprint::new(b"test", &[0])).is_ok());
+
+        let err = a.check(&b).unwrap_err();
+        assert_eq!(err.expected, a);
+        assert_eq!(err.actual, b);
+    }
+
+    #[test]
+    fn test_derandomize_wire_format() {
+        let msg = Derandomize {
+            id: TransferId::default(),
+            count: 9,
+            flip: vec![1, 0],
+        };
+
+        assert_eq!(Derandomize::from_wire(&msg.to_wire()).unwrap(), msg);
+
+        // Messages are validated when decoding, so a count which does not match the flip
+        // length is rejected. The count follows the header and the 8 byte id.
+        let mut bytes = msg.to_wire();
+        bytes[13..17].copy_from_slice(&17u32.to_le_bytes());
+        assert!(Derandomize::from_wire(&bytes).is_err());
+    }
 }
@@ -0,0 +1,45 @@
+use crate::ErrorKind;
+
+/// Errors that can occur when using the OPRF sender.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum SenderError {
+    #[error("COT count mismatch: expected {0}, got {1}")]
+    CountMismatch(usize, usize),
+}
+
+/// Errors that can occur when using the OPRF receiver.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ReceiverError {
+    #[error("COT count mismatch: expected {0}, got {1}")]
+    CountMismatch(usize, usize),
+}
+
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::CountMismatch(..) => ErrorKind::CallerBug,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::CountMismatch(..) => ErrorKind::CallerBug,
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
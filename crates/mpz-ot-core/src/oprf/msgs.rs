@@ -0,0 +1,17 @@
+//! Messages for the OPRF protocol.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use mpz_core::serialize::WireMessage;
+use mpz_core::Block;
+use serde::{Deserialize, Serialize};
+
+/// The receiver's masked inputs, `H(x_i) + a_i`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Masks {
+    /// The masked inputs.
+    pub masks: Vec<Block>,
+}
+
+#[cfg(feature = "std")]
+impl WireMessage for Masks {}
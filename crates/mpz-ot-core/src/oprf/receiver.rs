@@ -0,0 +1,61 @@
+use alloc::vec::Vec;
+
+use mpz_core::Block;
+
+use super::{
+    basis, cot_count, hash_input, hash_output, msgs::Masks, pack_choices, ReceiverError, CSP,
+};
+
+/// OPRF receiver.
+#[derive(Debug, Default)]
+pub struct Receiver {
+    /// The number of OPRF instances evaluated so far.
+    counter: u64,
+}
+
+impl Receiver {
+    /// Creates a new receiver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates a batch of OPRF instances, one for each input.
+    ///
+    /// Returns the masked inputs which must be sent to the sender, and the outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The inputs.
+    /// * `choices` - The receiver's random COT choices, [`cot_count`] per instance.
+    /// * `cot` - The receiver's random COT messages.
+    pub fn receive<T: AsRef<[u8]>>(
+        &mut self,
+        inputs: &[T],
+        choices: &[bool],
+        cot: &[Block],
+    ) -> Result<(Masks, Vec<Block>), ReceiverError> {
+        let expected = cot_count(inputs.len());
+        if choices.len() != expected {
+            return Err(ReceiverError::CountMismatch(expected, choices.len()));
+        } else if cot.len() != expected {
+            return Err(ReceiverError::CountMismatch(expected, cot.len()));
+        }
+
+        let basis = basis();
+        let (masks, outputs) = inputs
+            .iter()
+            .zip(choices.chunks_exact(CSP).zip(cot.chunks_exact(CSP)))
+            .enumerate()
+            .map(|(i, (input, (choices, cot)))| {
+                let mask = hash_input(input.as_ref()) ^ pack_choices(choices);
+                let output = hash_output(self.counter + i as u64, Block::inn_prdt_red(cot, &basis));
+
+                (mask, output)
+            })
+            .unzip();
+
+        self.counter += inputs.len() as u64;
+
+        Ok((Masks { masks }, outputs))
+    }
+}
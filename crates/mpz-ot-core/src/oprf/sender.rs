@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+
+use mpz_core::Block;
+
+use super::{basis, cot_count, hash_input, hash_output, msgs::Masks, SenderError, CSP};
+
+/// OPRF sender.
+#[derive(Debug)]
+pub struct Sender {
+    delta: Block,
+    /// The number of OPRF instances evaluated so far.
+    counter: u64,
+}
+
+impl Sender {
+    /// Creates a new sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The correlation of the COTs used by the sender.
+    pub fn new(delta: Block) -> Self {
+        Self { delta, counter: 0 }
+    }
+
+    /// Returns the correlation, delta.
+    pub fn delta(&self) -> Block {
+        self.delta
+    }
+
+    /// Computes the keys of a batch of OPRF instances.
+    ///
+    /// # Arguments
+    ///
+    /// * `cot` - The sender's random COT messages, [`cot_count`] per instance.
+    /// * `masks` - The receiver's masked inputs.
+    pub fn send(&mut self, cot: &[Block], masks: Masks) -> Result<SenderKeys, SenderError> {
+        let Masks { masks } = masks;
+
+        let expected = cot_count(masks.len());
+        if cot.len() != expected {
+            return Err(SenderError::CountMismatch(expected, cot.len()));
+        }
+
+        let basis = basis();
+        let keys = cot
+            .chunks_exact(CSP)
+            .zip(masks)
+            .map(|(cot, mask)| Block::inn_prdt_red(cot, &basis) ^ mask.gfmul(self.delta))
+            .collect::<Vec<_>>();
+
+        let offset = self.counter;
+        self.counter += keys.len() as u64;
+
+        Ok(SenderKeys {
+            delta: self.delta,
+            offset,
+            keys,
+        })
+    }
+}
+
+/// The sender's keys for a batch of OPRF instances.
+pub struct SenderKeys {
+    delta: Block,
+    /// The index of the first instance.
+    offset: u64,
+    keys: Vec<Block>,
+}
+
+opaque_debug::implement!(SenderKeys);
+
+impl SenderKeys {
+    /// Returns the number of instances.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if there are no instances.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Evaluates the PRF of an instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the instance within the batch.
+    /// * `input` - The input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn eval(&self, index: usize, input: &[u8]) -> Block {
+        let value = self.keys[index] ^ hash_input(input).gfmul(self.delta);
+
+        hash_output(self.offset + index as u64, value)
+    }
+
+    /// Evaluates the PRF of an instance on many inputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the instance within the batch.
+    /// * `inputs` - The inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn eval_batch<T: AsRef<[u8]>>(&self, index: usize, inputs: &[T]) -> Vec<Block> {
+        inputs
+            .iter()
+            .map(|input| self.eval(index, input.as_ref()))
+            .collect()
+    }
+}
@@ -0,0 +1,404 @@
+//! Protobuf encoding of the OT messages.
+//!
+//! The schema is published in `proto/mpz/ot/v1/ot.proto`, enabling implementations in other
+//! languages and middleboxes to parse protocol traffic. Messages are converted to and from their
+//! protobuf representation with [`ProtoMessage`].
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use mpz_core::{
+    serialize::{blocks_from_bytes, blocks_to_bytes, ProtoError, ProtoMessage},
+    Block,
+};
+
+use crate::{
+    chou_orlandi, kos,
+    msgs::{ConfigFingerprint, Derandomize},
+    TransferId,
+};
+
+/// Types generated from the protobuf schema.
+#[allow(missing_docs, unreachable_pub, clippy::all)]
+pub mod schema {
+    include!(concat!(env!("OUT_DIR"), "/mpz.ot.v1.rs"));
+}
+
+fn point_to_bytes(point: &RistrettoPoint) -> Vec<u8> {
+    point.compress().to_bytes().to_vec()
+}
+
+fn point_from_bytes(field: &'static str, bytes: &[u8]) -> Result<RistrettoPoint, ProtoError> {
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| ProtoError::invalid_field(field, "expected 32 bytes"))?;
+
+    CompressedRistretto(bytes)
+        .decompress()
+        .ok_or_else(|| ProtoError::invalid_field(field, "invalid ristretto point"))
+}
+
+fn compressed_point_from_bytes(
+    field: &'static str,
+    bytes: &[u8],
+) -> Result<CompressedRistretto, ProtoError> {
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| ProtoError::invalid_field(field, "expected 32 bytes"))?;
+
+    Ok(CompressedRistretto(bytes))
+}
+
+fn block_from_bytes(field: &'static str, bytes: &[u8]) -> Result<Block, ProtoError> {
+    let bytes: [u8; 16] = bytes
+        .try_into()
+        .map_err(|_| ProtoError::invalid_field(field, "expected 16 bytes"))?;
+
+    Ok(Block::new(bytes))
+}
+
+impl ProtoMessage for Derandomize {
+    type Proto = schema::Derandomize;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::Derandomize {
+            id: self.id.0,
+            count: self.count,
+            flip: self.flip.clone(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        // Divide by 8, rounding up
+        if proto.flip.len() != (proto.count as usize + 7) / 8 {
+            return Err(ProtoError::invalid_field(
+                "flip",
+                "length does not match count",
+            ));
+        }
+
+        Ok(Derandomize {
+            id: TransferId(proto.id),
+            count: proto.count,
+            flip: proto.flip,
+        })
+    }
+}
+
+impl ProtoMessage for ConfigFingerprint {
+    type Proto = schema::ConfigFingerprint;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::ConfigFingerprint {
+            fingerprint: self.0.to_vec(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        let fingerprint = proto
+            .fingerprint
+            .try_into()
+            .map_err(|_| ProtoError::invalid_field("fingerprint", "expected 8 bytes"))?;
+
+        Ok(Self(fingerprint))
+    }
+}
+
+impl ProtoMessage for chou_orlandi::msgs::SenderSetup {
+    type Proto = schema::CoSenderSetup;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::CoSenderSetup {
+            public_key: point_to_bytes(&self.public_key),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            public_key: point_from_bytes("public_key", &proto.public_key)?,
+        })
+    }
+}
+
+impl ProtoMessage for chou_orlandi::msgs::SenderPayload {
+    type Proto = schema::CoSenderPayload;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::CoSenderPayload {
+            id: self.id.0,
+            payload: blocks_to_bytes(self.payload.iter().flatten().copied()),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        let blocks = blocks_from_bytes("payload", &proto.payload)?;
+        if blocks.len() % 2 != 0 {
+            return Err(ProtoError::invalid_field(
+                "payload",
+                "expected pairs of blocks",
+            ));
+        }
+
+        Ok(Self {
+            id: TransferId(proto.id),
+            payload: blocks
+                .chunks_exact(2)
+                .map(|pair| [pair[0], pair[1]])
+                .collect(),
+        })
+    }
+}
+
+impl ProtoMessage for chou_orlandi::msgs::ReceiverPayload {
+    type Proto = schema::CoReceiverPayload;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::CoReceiverPayload {
+            id: self.id.0,
+            blinded_choices: self
+                .blinded_choices
+                .iter()
+                .map(|point| point.to_bytes().to_vec())
+                .collect(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            id: TransferId(proto.id),
+            blinded_choices: proto
+                .blinded_choices
+                .iter()
+                .map(|point| compressed_point_from_bytes("blinded_choices", point))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl ProtoMessage for chou_orlandi::msgs::ReceiverReveal {
+    type Proto = schema::CoReceiverReveal;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::CoReceiverReveal {
+            choices: self.choices.clone(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            choices: proto.choices,
+        })
+    }
+}
+
+impl ProtoMessage for kos::msgs::StartExtend {
+    type Proto = schema::KosStartExtend;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::KosStartExtend {
+            count: self.count as u64,
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            count: usize::try_from(proto.count)
+                .map_err(|_| ProtoError::invalid_field("count", "count exceeds usize"))?,
+        })
+    }
+}
+
+impl ProtoMessage for kos::msgs::Extend {
+    type Proto = schema::KosExtend;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::KosExtend {
+            us: self.us.clone(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self { us: proto.us })
+    }
+}
+
+impl ProtoMessage for kos::msgs::Check {
+    type Proto = schema::KosCheck;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::KosCheck {
+            x: self.x.to_bytes().to_vec(),
+            t0: self.t0.to_bytes().to_vec(),
+            t1: self.t1.to_bytes().to_vec(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            x: block_from_bytes("x", &proto.x)?,
+            t0: block_from_bytes("t0", &proto.t0)?,
+            t1: block_from_bytes("t1", &proto.t1)?,
+        })
+    }
+}
+
+impl ProtoMessage for kos::msgs::HashedCheck {
+    type Proto = schema::KosHashedCheck;
+
+    fn to_proto(&self) -> Self::Proto {
+        schema::KosHashedCheck {
+            x: self.x.to_bytes().to_vec(),
+            hash: self.hash.to_bytes().to_vec(),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        Ok(Self {
+            x: block_from_bytes("x", &proto.x)?,
+            hash: block_from_bytes("hash", &proto.hash)?,
+        })
+    }
+}
+
+impl ProtoMessage for kos::msgs::SenderPayload {
+    type Proto = schema::KosSenderPayload;
+
+    fn to_proto(&self) -> Self::Proto {
+        use schema::kos_sender_payload::Ciphertexts;
+
+        let ciphertexts = match &self.ciphertexts {
+            kos::msgs::Ciphertexts::Blocks { ciphertexts } => {
+                Ciphertexts::Blocks(schema::BlockCiphertexts {
+                    ciphertexts: blocks_to_bytes(ciphertexts.iter().copied()),
+                })
+            }
+            kos::msgs::Ciphertexts::Bytes {
+                ciphertexts,
+                iv,
+                length,
+            } => Ciphertexts::Bytes(schema::ByteCiphertexts {
+                ciphertexts: ciphertexts.clone(),
+                iv: iv.clone(),
+                length: *length,
+            }),
+        };
+
+        schema::KosSenderPayload {
+            id: self.id.0,
+            ciphertexts: Some(ciphertexts),
+        }
+    }
+
+    fn from_proto(proto: Self::Proto) -> Result<Self, ProtoError> {
+        use schema::kos_sender_payload::Ciphertexts;
+
+        let ciphertexts = match proto
+            .ciphertexts
+            .ok_or(ProtoError::MissingField("ciphertexts"))?
+        {
+            Ciphertexts::Blocks(blocks) => kos::msgs::Ciphertexts::Blocks {
+                ciphertexts: blocks_from_bytes("ciphertexts", &blocks.ciphertexts)?,
+            },
+            Ciphertexts::Bytes(bytes) => kos::msgs::Ciphertexts::Bytes {
+                ciphertexts: bytes.ciphertexts,
+                iv: bytes.iv,
+                length: bytes.length,
+            },
+        };
+
+        Ok(Self {
+            id: TransferId(proto.id),
+            ciphertexts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use rand::Rng;
+    use rand_chacha::ChaCha12Rng;
+    use rand_core::SeedableRng;
+
+    fn roundtrip<T: ProtoMessage + PartialEq + core::fmt::Debug>(msg: T) {
+        assert_eq!(T::decode_proto(&msg.encode_proto()).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_proto_roundtrip() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        roundtrip(chou_orlandi::msgs::SenderSetup {
+            public_key: RISTRETTO_BASEPOINT_POINT,
+        });
+        roundtrip(chou_orlandi::msgs::SenderPayload {
+            id: TransferId(1),
+            payload: (0..4).map(|_| [rng.gen(), rng.gen()]).collect(),
+        });
+        roundtrip(chou_orlandi::msgs::ReceiverPayload {
+            id: TransferId(2),
+            blinded_choices: vec![RISTRETTO_BASEPOINT_POINT.compress(); 3],
+        });
+        roundtrip(kos::msgs::StartExtend { count: 1024 });
+        roundtrip(kos::msgs::Extend { us: vec![1, 2, 3] });
+        roundtrip(kos::msgs::Check {
+            x: rng.gen(),
+            t0: rng.gen(),
+            t1: rng.gen(),
+        });
+        roundtrip(kos::msgs::HashedCheck {
+            x: rng.gen(),
+            hash: rng.gen(),
+        });
+        roundtrip(kos::msgs::SenderPayload {
+            id: TransferId(3),
+            ciphertexts: kos::msgs::Ciphertexts::Blocks {
+                ciphertexts: (0..4).map(|_| rng.gen()).collect(),
+            },
+        });
+        roundtrip(kos::msgs::SenderPayload {
+            id: TransferId(4),
+            ciphertexts: kos::msgs::Ciphertexts::Bytes {
+                ciphertexts: vec![7; 64],
+                iv: vec![0; 16],
+                length: 32,
+            },
+        });
+        roundtrip(Derandomize {
+            id: TransferId(5),
+            count: 9,
+            flip: vec![0xff, 0x01],
+        });
+        roundtrip(kos::SenderConfig::default().fingerprint());
+    }
+
+    #[test]
+    fn test_proto_invalid() {
+        let payload = schema::CoSenderPayload {
+            id: 0,
+            payload: vec![0; 16],
+        };
+        assert!(chou_orlandi::msgs::SenderPayload::from_proto(payload).is_err());
+
+        let setup = schema::CoSenderSetup {
+            public_key: vec![0; 31],
+        };
+        assert!(chou_orlandi::msgs::SenderSetup::from_proto(setup).is_err());
+
+        let derandomize = schema::Derandomize {
+            id: 0,
+            count: 9,
+            flip: vec![0xff],
+        };
+        assert!(Derandomize::from_proto(derandomize).is_err());
+
+        let payload = schema::KosSenderPayload {
+            id: 0,
+            ciphertexts: None,
+        };
+        assert!(matches!(
+            kos::msgs::SenderPayload::from_proto(payload),
+            Err(ProtoError::MissingField("ciphertexts"))
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+//! Synchronization primitives which are available without `std`.
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "std")] {
+        type Inner<T> = std::sync::Mutex<T>;
+        type Guard<'a, T> = std::sync::MutexGuard<'a, T>;
+    } else {
+        type Inner<T> = spin::Mutex<T>;
+        type Guard<'a, T> = spin::MutexGuard<'a, T>;
+    }
+}
+
+/// A mutex, which spins if `std` is not available.
+#[derive(Debug, Default)]
+pub(crate) struct Mutex<T>(Inner<T>);
+
+impl<T> Mutex<T> {
+    /// Acquires the mutex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mutex is poisoned.
+    pub(crate) fn lock(&self) -> Guard<'_, T> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "std")] {
+                self.0.lock().unwrap()
+            } else {
+                self.0.lock()
+            }
+        }
+    }
+}
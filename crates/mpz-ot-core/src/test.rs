@@ -1,19 +1,14 @@
 //! OT test utilities.
 
-use mpz_core::Block;
+use mpz_core::{Block, KeyBlock, MacBlock};
 
 /// Asserts the correctness of correlated oblivious transfer.
 pub fn assert_cot(delta: Block, choices: &[bool], msgs: &[Block], received: &[Block]) {
     assert!(choices
         .iter()
-        .zip(msgs.iter().zip(received))
-        .all(|(&choice, (&msg, &received))| {
-            if choice {
-                received == msg ^ delta
-            } else {
-                received == msg
-            }
-        }));
+        .zip(KeyBlock::from_blocks(msgs))
+        .zip(MacBlock::from_blocks(received))
+        .all(|((&choice, key), mac)| key.verify(choice, mac, delta)));
 }
 
 /// Asserts the correctness of random oblivious transfer.
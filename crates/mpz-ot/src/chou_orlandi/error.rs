@@ -1,4 +1,4 @@
-use crate::OTError;
+use crate::{cointoss_error_kind, ErrorKind, OTError};
 
 /// A Chou-Orlandi sender error.
 #[derive(Debug, thiserror::Error)]
@@ -10,12 +10,32 @@ pub enum SenderError {
     CoreError(#[from] mpz_ot_core::chou_orlandi::SenderError),
     #[error("{0}")]
     StateError(String),
+    #[error(transparent)]
+    ConfigMismatch(#[from] mpz_ot_core::msgs::ConfigMismatch),
     #[error("coin-toss error: {0}")]
     CointossError(#[from] mpz_cointoss::CointossError),
     #[error("invalid configuration: {0}")]
     InvalidConfig(String),
 }
 
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::IOError(_) => ErrorKind::TransientIo,
+            SenderError::CoreError(err) => err.kind(),
+            SenderError::StateError(_) | SenderError::InvalidConfig(_) => ErrorKind::CallerBug,
+            SenderError::ConfigMismatch(_) => ErrorKind::ProtocolAbort,
+            SenderError::CointossError(err) => cointoss_error_kind(err),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
 impl From<SenderError> for OTError {
     fn from(err: SenderError) -> Self {
         match err {
@@ -41,12 +61,32 @@ pub enum ReceiverError {
     CoreError(#[from] mpz_ot_core::chou_orlandi::ReceiverError),
     #[error("{0}")]
     StateError(String),
+    #[error(transparent)]
+    ConfigMismatch(#[from] mpz_ot_core::msgs::ConfigMismatch),
     #[error("coin-toss error: {0}")]
     CointossError(#[from] mpz_cointoss::CointossError),
     #[error("invalid configuration: {0}")]
     InvalidConfig(String),
 }
 
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::IOError(_) => ErrorKind::TransientIo,
+            ReceiverError::CoreError(err) => err.kind(),
+            ReceiverError::StateError(_) | ReceiverError::InvalidConfig(_) => ErrorKind::CallerBug,
+            ReceiverError::ConfigMismatch(_) => ErrorKind::ProtocolAbort,
+            ReceiverError::CointossError(err) => cointoss_error_kind(err),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
 impl From<ReceiverError> for OTError {
     fn from(err: ReceiverError) -> Self {
         match err {
@@ -58,7 +58,9 @@ mod tests {
     use rand_chacha::ChaCha12Rng;
     use rand_core::SeedableRng;
 
-    use crate::{CommittedOTReceiver, OTError, OTReceiver, OTSender, OTSetup, VerifiableOTSender};
+    use crate::{
+        CommittedOTReceiver, ErrorKind, OTError, OTReceiver, OTSender, OTSetup, VerifiableOTSender,
+    };
 
     use super::*;
     use rstest::*;
@@ -151,4 +153,32 @@ mod tests {
 
         assert_eq!(verified_choices, choices);
     }
+
+    #[tokio::test]
+    async fn test_chou_orlandi_config_mismatch() {
+        let (mut sender_ctx, mut receiver_ctx) = test_st_executor(8);
+        let mut sender = Sender::new(SenderConfig::builder().receiver_commit().build().unwrap());
+        let mut receiver = Receiver::new(ReceiverConfig::default());
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.setup(&mut sender_ctx),
+            receiver.setup(&mut receiver_ctx)
+        );
+
+        let sender_err = sender_result.unwrap_err();
+        let receiver_err = receiver_result.unwrap_err();
+
+        assert_eq!(sender_err.kind(), ErrorKind::ProtocolAbort);
+        assert_eq!(receiver_err.kind(), ErrorKind::ProtocolAbort);
+        assert!(matches!(
+            sender_err,
+            OTError::SenderError(err)
+                if matches!(err.downcast_ref(), Some(SenderError::ConfigMismatch(_)))
+        ));
+        assert!(matches!(
+            receiver_err,
+            OTError::ReceiverError(err)
+                if matches!(err.downcast_ref(), Some(ReceiverError::ConfigMismatch(_)))
+        ));
+    }
 }
@@ -2,17 +2,17 @@ use async_trait::async_trait;
 
 use itybity::BitIterable;
 use mpz_cointoss as cointoss;
-use mpz_common::Context;
+use mpz_common::{cpu::CpuBackend, Context};
 use mpz_core::Block;
 use mpz_ot_core::chou_orlandi::msgs::SenderPayload;
 use mpz_ot_core::chou_orlandi::{
     receiver_state as state, Receiver as ReceiverCore, ReceiverConfig,
 };
+use mpz_ot_core::msgs::ConfigFingerprint;
 
 use enum_try_as_inner::EnumTryAsInner;
 use rand::{thread_rng, Rng};
 use serio::{stream::IoStreamExt as _, SinkExt as _};
-use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
 use crate::{CommittedOTReceiver, OTError, OTReceiver, OTReceiverOutput, OTSetup};
 
@@ -81,6 +81,7 @@ impl Receiver {
 
 #[async_trait]
 impl<Ctx: Context> OTSetup<Ctx> for Receiver {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_setup() {
             return Ok(());
@@ -90,6 +91,14 @@ impl<Ctx: Context> OTSetup<Ctx> for Receiver {
             .try_into_initialized()
             .map_err(ReceiverError::from)?;
 
+        // Check that the sender's configuration matches before anything else is sent.
+        let fingerprint = config.fingerprint();
+        let sender_fingerprint: ConfigFingerprint = ctx.io_mut().expect_next().await?;
+        ctx.io_mut().send(fingerprint).await?;
+        fingerprint
+            .check(&sender_fingerprint)
+            .map_err(ReceiverError::from)?;
+
         // If the receiver is committed, we generate the seed using a cointoss.
         let seed = if config.receiver_commit() {
             if seed.is_some() {
@@ -121,9 +130,10 @@ impl<Ctx: Context> OTSetup<Ctx> for Receiver {
         };
 
         let sender_setup = ctx.io_mut().expect_next().await?;
-        let receiver =
-            Backend::spawn(move || ReceiverCore::new_with_seed(config, seed).setup(sender_setup))
-                .await;
+        let receiver = CpuBackend::blocking(move || {
+            ReceiverCore::new_with_seed(config, seed).setup(sender_setup)
+        })
+        .await;
 
         self.state = State::Setup(Box::new(receiver));
 
@@ -137,6 +147,7 @@ where
     Ctx: Context,
     T: BitIterable + Send + Sync + Clone + 'static,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = choices.len(), id = tracing::field::Empty), skip_all, err))]
     async fn receive(
         &mut self,
         ctx: &mut Ctx,
@@ -147,7 +158,7 @@ where
             .map_err(ReceiverError::from)?;
 
         let choices = choices.to_vec();
-        let (mut receiver, receiver_payload) = Backend::spawn(move || {
+        let (mut receiver, receiver_payload) = CpuBackend::blocking(move || {
             let payload = receiver.receive_random(&choices);
             (receiver, payload)
         })
@@ -157,8 +168,10 @@ where
 
         let sender_payload: SenderPayload = ctx.io_mut().expect_next().await?;
         let id = sender_payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
-        let (receiver, msgs) = Backend::spawn(move || {
+        let (receiver, msgs) = CpuBackend::blocking(move || {
             receiver
                 .receive(sender_payload)
                 .map(|msgs| (receiver, msgs))
@@ -174,6 +187,7 @@ where
 
 #[async_trait]
 impl<Ctx: Context> CommittedOTReceiver<Ctx, bool, Block> for Receiver {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn reveal_choices(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         let receiver = std::mem::replace(&mut self.state, State::Error)
             .try_into_setup()
@@ -4,12 +4,14 @@ use crate::{
 
 use async_trait::async_trait;
 use mpz_cointoss as cointoss;
-use mpz_common::Context;
+use mpz_common::{cpu::CpuBackend, Context};
 use mpz_core::Block;
-use mpz_ot_core::chou_orlandi::{sender_state as state, Sender as SenderCore, SenderConfig};
+use mpz_ot_core::{
+    chou_orlandi::{sender_state as state, Sender as SenderCore, SenderConfig},
+    msgs::ConfigFingerprint,
+};
 use rand::{thread_rng, Rng};
 use serio::{stream::IoStreamExt, SinkExt as _};
-use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
 use enum_try_as_inner::EnumTryAsInner;
 
@@ -69,6 +71,7 @@ impl Sender {
 
 #[async_trait]
 impl<Ctx: Context> OTSetup<Ctx> for Sender {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_setup() {
             return Ok(());
@@ -78,6 +81,14 @@ impl<Ctx: Context> OTSetup<Ctx> for Sender {
             .try_into_initialized()
             .map_err(SenderError::from)?;
 
+        // Check that the receiver's configuration matches before anything else is sent.
+        let fingerprint = sender.config().fingerprint();
+        ctx.io_mut().send(fingerprint).await?;
+        let receiver_fingerprint: ConfigFingerprint = ctx.io_mut().expect_next().await?;
+        fingerprint
+            .check(&receiver_fingerprint)
+            .map_err(SenderError::from)?;
+
         // If the receiver is committed, we run the cointoss protocol
         if sender.config().receiver_commit() {
             let cointoss_seed = thread_rng().gen();
@@ -101,6 +112,7 @@ impl<Ctx: Context> OTSetup<Ctx> for Sender {
 
 #[async_trait]
 impl<Ctx: Context> OTSender<Ctx, [Block; 2]> for Sender {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = input.len(), id = tracing::field::Empty), skip_all, err))]
     async fn send(
         &mut self,
         ctx: &mut Ctx,
@@ -113,7 +125,7 @@ impl<Ctx: Context> OTSender<Ctx, [Block; 2]> for Sender {
         let receiver_payload = ctx.io_mut().expect_next().await?;
 
         let input = input.to_vec();
-        let (sender, payload) = Backend::spawn(move || {
+        let (sender, payload) = CpuBackend::blocking(move || {
             sender
                 .send(&input, receiver_payload)
                 .map(|payload| (sender, payload))
@@ -122,6 +134,8 @@ impl<Ctx: Context> OTSender<Ctx, [Block; 2]> for Sender {
         .map_err(SenderError::from)?;
 
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         ctx.io_mut().send(payload).await?;
 
@@ -133,6 +147,7 @@ impl<Ctx: Context> OTSender<Ctx, [Block; 2]> for Sender {
 
 #[async_trait]
 impl<Ctx: Context> VerifiableOTSender<Ctx, bool, [Block; 2]> for Sender {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn verify_choices(&mut self, ctx: &mut Ctx) -> Result<Vec<bool>, OTError> {
         let sender = std::mem::replace(&mut self.state, State::Error)
             .try_into_setup()
@@ -157,7 +172,7 @@ impl<Ctx: Context> VerifiableOTSender<Ctx, bool, [Block; 2]> for Sender {
 
         let receiver_reveal = ctx.io_mut().expect_next().await?;
         let verified_choices =
-            Backend::spawn(move || sender.verify_choices(stretched_seed, receiver_reveal))
+            CpuBackend::blocking(move || sender.verify_choices(stretched_seed, receiver_reveal))
                 .await
                 .map_err(SenderError::from)?;
 
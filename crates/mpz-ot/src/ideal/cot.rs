@@ -1,6 +1,7 @@
 //! Ideal functionality for correlated oblivious transfer.
 
 use async_trait::async_trait;
+use futures::{channel::mpsc, SinkExt as _};
 
 use mpz_common::{
     ideal::{ideal_f2p, Alice, Bob},
@@ -11,6 +12,7 @@ use mpz_ot_core::{
     ideal::cot::IdealCOT, COTReceiverOutput, COTSenderOutput, RCOTReceiverOutput, RCOTSenderOutput,
 };
 
+use super::chunk_lens;
 use crate::{COTReceiver, COTSender, OTError, OTSetup, RandomCOTReceiver, RandomCOTSender};
 
 fn cot(
@@ -97,6 +99,42 @@ impl<Ctx: Context> RandomCOTSender<Ctx, Block> for IdealCOTSender {
     }
 }
 
+impl IdealCOTSender {
+    /// Obliviously transfers random correlated messages in chunks.
+    ///
+    /// The output of each chunk is sent to `sink` as soon as it is produced, and `sink` is closed
+    /// after the last chunk. The receiver must call
+    /// [`IdealCOTReceiver::receive_random_correlated_chunked`] with the same `count` and
+    /// `chunk_size`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of correlated messages to obliviously transfer.
+    /// * `chunk_size` - The maximum number of messages in a chunk.
+    /// * `sink` - The channel to send the output of each chunk to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub async fn send_random_correlated_chunked<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        chunk_size: usize,
+        mut sink: mpsc::Sender<RCOTSenderOutput<Block>>,
+    ) -> Result<(), OTError> {
+        for len in chunk_lens(count, chunk_size) {
+            let output = self.0.call(ctx, len, rcot).await;
+            sink.send(output)
+                .await
+                .map_err(|err| OTError::SenderError(Box::new(err)))?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Ideal COT receiver.
 #[derive(Debug, Clone)]
 pub struct IdealCOTReceiver(Bob<IdealCOT>);
@@ -149,9 +187,43 @@ impl<Ctx: Context> RandomCOTReceiver<Ctx, bool, Block> for IdealCOTReceiver {
     }
 }
 
+impl IdealCOTReceiver {
+    /// Obliviously receives random correlated messages in chunks.
+    ///
+    /// See [`IdealCOTSender::send_random_correlated_chunked`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of correlated messages to obliviously receive.
+    /// * `chunk_size` - The maximum number of messages in a chunk.
+    /// * `sink` - The channel to send the output of each chunk to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub async fn receive_random_correlated_chunked<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        chunk_size: usize,
+        mut sink: mpsc::Sender<RCOTReceiverOutput<bool, Block>>,
+    ) -> Result<(), OTError> {
+        for len in chunk_lens(count, chunk_size) {
+            let output = self.0.call(ctx, len, rcot).await;
+            sink.send(output)
+                .await
+                .map_err(|err| OTError::ReceiverError(Box::new(err)))?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt as _;
     use mpz_common::executor::test_st_executor;
     use mpz_ot_core::test::assert_cot;
     use rand::{Rng, SeedableRng};
@@ -220,4 +292,48 @@ mod tests {
         assert_eq!(count, choices.len());
         assert_cot(delta, &choices, &sender_msgs, &receiver_msgs);
     }
+
+    #[tokio::test]
+    async fn test_ideal_rcot_chunked() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut alice, mut bob) = ideal_rcot();
+
+        let delta = alice.0.get_mut().delta();
+
+        let (count, chunk_size) = (10, 4);
+        let (sink_a, stream_a) = mpsc::channel(0);
+        let (sink_b, stream_b) = mpsc::channel(0);
+
+        let (res_a, res_b, chunks_a, chunks_b) = tokio::join!(
+            alice.send_random_correlated_chunked(&mut ctx_a, count, chunk_size, sink_a),
+            bob.receive_random_correlated_chunked(&mut ctx_b, count, chunk_size, sink_b),
+            stream_a.collect::<Vec<_>>(),
+            stream_b.collect::<Vec<_>>(),
+        );
+        res_a.unwrap();
+        res_b.unwrap();
+
+        assert_eq!(
+            chunks_a
+                .iter()
+                .map(|chunk| chunk.msgs.len())
+                .collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        for (
+            RCOTSenderOutput {
+                id: id_a,
+                msgs: sender_msgs,
+            },
+            RCOTReceiverOutput {
+                id: id_b,
+                choices,
+                msgs: receiver_msgs,
+            },
+        ) in chunks_a.into_iter().zip(chunks_b)
+        {
+            assert_eq!(id_a, id_b);
+            assert_cot(delta, &choices, &sender_msgs, &receiver_msgs);
+        }
+    }
 }
@@ -3,3 +3,16 @@
 pub mod cot;
 pub mod ot;
 pub mod rot;
+
+/// Returns the lengths of the chunks of `count` messages, each with at most `chunk_size` messages.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+fn chunk_lens(count: usize, chunk_size: usize) -> impl Iterator<Item = usize> {
+    assert!(chunk_size > 0, "chunk size should be greater than 0");
+
+    (0..count)
+        .step_by(chunk_size)
+        .map(move |i| chunk_size.min(count - i))
+}
@@ -1,6 +1,7 @@
 //! Ideal functionality for random oblivious transfer.
 
 use async_trait::async_trait;
+use futures::{channel::mpsc, SinkExt as _};
 
 use mpz_common::{
     ideal::{ideal_f2p, Alice, Bob},
@@ -9,6 +10,7 @@ use mpz_common::{
 use mpz_ot_core::{ideal::rot::IdealROT, ROTReceiverOutput, ROTSenderOutput};
 use rand::distributions::{Distribution, Standard};
 
+use super::chunk_lens;
 use crate::{OTError, OTSetup, RandomOTReceiver, RandomOTSender};
 
 fn rot<T: Copy>(
@@ -74,6 +76,44 @@ where
     }
 }
 
+impl IdealROTSender {
+    /// Obliviously transfers random messages in chunks.
+    ///
+    /// The output of each chunk is sent to `sink` as soon as it is produced, and `sink` is closed
+    /// after the last chunk. The receiver must call [`IdealROTReceiver::receive_random_chunked`]
+    /// with the same `count` and `chunk_size`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of random messages to obliviously transfer.
+    /// * `chunk_size` - The maximum number of messages in a chunk.
+    /// * `sink` - The channel to send the output of each chunk to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub async fn send_random_chunked<T: Copy + Send + 'static, Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        chunk_size: usize,
+        mut sink: mpsc::Sender<ROTSenderOutput<[T; 2]>>,
+    ) -> Result<(), OTError>
+    where
+        Standard: Distribution<T>,
+    {
+        for len in chunk_lens(count, chunk_size) {
+            let output = self.0.call(ctx, len, rot::<T>).await;
+            sink.send(output)
+                .await
+                .map_err(|err| OTError::SenderError(Box::new(err)))?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Ideal ROT receiver.
 #[derive(Debug, Clone)]
 pub struct IdealROTReceiver(Bob<IdealROT>);
@@ -118,3 +158,82 @@ where
         Ok(self.0.call(ctx, count, rot).await)
     }
 }
+
+impl IdealROTReceiver {
+    /// Obliviously receives random messages in chunks.
+    ///
+    /// See [`IdealROTSender::send_random_chunked`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of random messages to obliviously receive.
+    /// * `chunk_size` - The maximum number of messages in a chunk.
+    /// * `sink` - The channel to send the output of each chunk to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub async fn receive_random_chunked<T: Copy + Send + 'static, Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        chunk_size: usize,
+        mut sink: mpsc::Sender<ROTReceiverOutput<bool, T>>,
+    ) -> Result<(), OTError>
+    where
+        Standard: Distribution<T>,
+    {
+        for len in chunk_lens(count, chunk_size) {
+            let output = self.0.call(ctx, len, rot::<T>).await;
+            sink.send(output)
+                .await
+                .map_err(|err| OTError::ReceiverError(Box::new(err)))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use mpz_common::executor::test_st_executor;
+    use mpz_core::Block;
+
+    #[tokio::test]
+    async fn test_ideal_rot_chunked() {
+        let (mut ctx_a, mut ctx_b) = test_st_executor(8);
+        let (mut alice, mut bob) = ideal_rot();
+
+        let (count, chunk_size) = (10, 3);
+        let (sink_a, stream_a) = mpsc::channel(0);
+        let (sink_b, stream_b) = mpsc::channel(0);
+
+        let (res_a, res_b, chunks_a, chunks_b) = tokio::join!(
+            alice.send_random_chunked::<Block, _>(&mut ctx_a, count, chunk_size, sink_a),
+            bob.receive_random_chunked::<Block, _>(&mut ctx_b, count, chunk_size, sink_b),
+            stream_a.collect::<Vec<_>>(),
+            stream_b.collect::<Vec<_>>(),
+        );
+        res_a.unwrap();
+        res_b.unwrap();
+
+        assert_eq!(
+            chunks_a
+                .iter()
+                .map(|chunk| chunk.msgs.len())
+                .collect::<Vec<_>>(),
+            vec![3, 3, 3, 1]
+        );
+        for (sender, receiver) in chunks_a.into_iter().zip(chunks_b) {
+            assert_eq!(sender.id, receiver.id);
+            assert!(sender
+                .msgs
+                .iter()
+                .zip(receiver.choices.iter().zip(receiver.msgs))
+                .all(|(msgs, (&choice, msg))| msgs[choice as usize] == msg));
+        }
+    }
+}
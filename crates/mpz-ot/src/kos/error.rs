@@ -1,4 +1,4 @@
-use crate::OTError;
+use crate::{cointoss_error_kind, ErrorKind, OTError};
 
 /// A KOS sender error.
 #[derive(Debug, thiserror::Error)]
@@ -12,6 +12,8 @@ pub enum SenderError {
     BaseOTError(#[from] crate::OTError),
     #[error("coin-toss error: {0}")]
     CointossError(#[from] mpz_cointoss::CointossError),
+    #[error(transparent)]
+    ConfigMismatch(#[from] mpz_ot_core::msgs::ConfigMismatch),
     #[error("{0}")]
     StateError(String),
     #[error("configuration error: {0}")]
@@ -20,6 +22,25 @@ pub enum SenderError {
     Other(String),
 }
 
+impl SenderError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SenderError::IOError(_) => ErrorKind::TransientIo,
+            SenderError::CoreError(err) => err.kind(),
+            SenderError::BaseOTError(err) => err.kind(),
+            SenderError::StateError(_) | SenderError::ConfigError(_) => ErrorKind::CallerBug,
+            SenderError::ConfigMismatch(_) | SenderError::Other(_) => ErrorKind::ProtocolAbort,
+            SenderError::CointossError(err) => cointoss_error_kind(err),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
 impl From<SenderError> for OTError {
     fn from(err: SenderError) -> Self {
         match err {
@@ -53,6 +74,8 @@ pub enum ReceiverError {
     BaseOTError(#[from] crate::OTError),
     #[error("coin-toss error: {0}")]
     CointossError(#[from] mpz_cointoss::CointossError),
+    #[error(transparent)]
+    ConfigMismatch(#[from] mpz_ot_core::msgs::ConfigMismatch),
     #[error("{0}")]
     StateError(String),
     #[error("configuration error: {0}")]
@@ -63,6 +86,26 @@ pub enum ReceiverError {
     Other(String),
 }
 
+impl ReceiverError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverError::IOError(_) => ErrorKind::TransientIo,
+            ReceiverError::CoreError(err) => err.kind(),
+            ReceiverError::BaseOTError(err) => err.kind(),
+            ReceiverError::StateError(_) | ReceiverError::ConfigError(_) => ErrorKind::CallerBug,
+            ReceiverError::ConfigMismatch(_) | ReceiverError::Other(_) => ErrorKind::ProtocolAbort,
+            ReceiverError::CointossError(err) => cointoss_error_kind(err),
+            ReceiverError::VerifyError(err) => err.kind(),
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
 impl From<ReceiverError> for OTError {
     fn from(err: ReceiverError) -> Self {
         match err {
@@ -90,3 +133,12 @@ pub enum ReceiverVerifyError {
     #[error("delta value is not inconsistent")]
     InconsistentDelta,
 }
+
+impl ReceiverVerifyError {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ReceiverVerifyError::InconsistentDelta => ErrorKind::PeerMisbehavior,
+        }
+    }
+}
@@ -18,6 +18,7 @@ pub(crate) use sender::StateError as SenderStateError;
 pub use mpz_ot_core::kos::{
     msgs, PayloadRecord, ReceiverConfig, ReceiverConfigBuilder, ReceiverConfigBuilderError,
     ReceiverKeys, SenderConfig, SenderConfigBuilder, SenderConfigBuilderError, SenderKeys,
+    DEFAULT_BATCH_SIZE,
 };
 
 // If we're testing we use a smaller chunk size to make sure the chunking code paths are tested.
@@ -30,6 +31,11 @@ cfg_if::cfg_if! {
     }
 }
 
+/// Returns the batch size rounded up to a multiple of 64, or `None` if it is zero.
+pub(crate) fn batch_size(batch_size: usize) -> Option<usize> {
+    (batch_size != 0).then(|| (batch_size + 63) & !63)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -37,7 +43,10 @@ mod tests {
 
     use futures::TryFutureExt;
     use itybity::ToBits;
-    use mpz_common::{executor::test_st_executor, Context};
+    use mpz_common::{
+        executor::{test_mt_executor, test_st_executor, test_tamper_executor, Deviation},
+        Context,
+    };
     use mpz_core::Block;
     use rand::Rng;
     use rand_chacha::ChaCha12Rng;
@@ -45,7 +54,7 @@ mod tests {
 
     use crate::{
         ideal::ot::{ideal_ot, IdealOTReceiver, IdealOTSender},
-        CommittedOTSender, OTError, OTReceiver, OTSender, OTSetup, RandomOTReceiver,
+        CommittedOTSender, ErrorKind, OTError, OTReceiver, OTSender, OTSetup, RandomOTReceiver,
         RandomOTSender, VerifiableOTReceiver,
     };
 
@@ -123,6 +132,223 @@ mod tests {
         assert_eq!(output_receiver.msgs, expected);
     }
 
+    #[rstest]
+    #[tokio::test]
+    async fn test_kos_send_iter(data: Vec<[Block; 2]>, choices: Vec<bool>) {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (mut sender, mut receiver) = setup(
+            SenderConfig::default(),
+            ReceiverConfig::default(),
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            data.len(),
+        )
+        .await;
+
+        // Gather the messages from two separate buffers.
+        let (first, second) = data.split_at(data.len() / 2);
+        let msgs = (0..data.len()).map(|i| {
+            if i < first.len() {
+                first[i]
+            } else {
+                second[i - first.len()]
+            }
+        });
+
+        let (output_sender, output_receiver) = tokio::try_join!(
+            OTSender::<_, [Block; 2]>::send_iter(&mut sender, &mut ctx_sender, msgs)
+                .map_err(OTError::from),
+            OTReceiver::<_, bool, Block>::receive(&mut receiver, &mut ctx_receiver, &choices)
+                .map_err(OTError::from)
+        )
+        .unwrap();
+
+        let expected = choose(data.iter().copied(), choices.iter_lsb0()).collect::<Vec<_>>();
+
+        assert_eq!(output_sender.id, output_receiver.id);
+        assert_eq!(output_receiver.msgs, expected);
+    }
+
+    #[rstest]
+    #[case::both(true, true)]
+    #[case::sender_only(true, false)]
+    #[case::receiver_only(false, true)]
+    #[tokio::test]
+    async fn test_kos_hashed_check(
+        data: Vec<[Block; 2]>,
+        choices: Vec<bool>,
+        #[case] sender_hashed: bool,
+        #[case] receiver_hashed: bool,
+    ) {
+        let mut sender_config = SenderConfig::builder();
+        if sender_hashed {
+            sender_config.hashed_check();
+        }
+        let mut receiver_config = ReceiverConfig::builder();
+        if receiver_hashed {
+            receiver_config.hashed_check();
+        }
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (mut sender, mut receiver) = setup(
+            sender_config.build().unwrap(),
+            receiver_config.build().unwrap(),
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            data.len(),
+        )
+        .await;
+
+        let (output_sender, output_receiver) = tokio::try_join!(
+            OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, &data)
+                .map_err(OTError::from),
+            OTReceiver::<_, bool, Block>::receive(&mut receiver, &mut ctx_receiver, &choices)
+                .map_err(OTError::from)
+        )
+        .unwrap();
+
+        let expected = choose(data.iter().copied(), choices.iter_lsb0()).collect::<Vec<_>>();
+
+        assert_eq!(output_sender.id, output_receiver.id);
+        assert_eq!(output_receiver.msgs, expected);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_kos_batched(data: Vec<[Block; 2]>, choices: Vec<bool>) {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        // Extend in batches smaller than the number of OTs.
+        let (mut sender, mut receiver) = setup(
+            SenderConfig::builder().batch_size(48).build().unwrap(),
+            ReceiverConfig::builder().batch_size(48).build().unwrap(),
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            data.len(),
+        )
+        .await;
+
+        let (output_sender, output_receiver) = tokio::try_join!(
+            OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, &data)
+                .map_err(OTError::from),
+            OTReceiver::<_, bool, Block>::receive(&mut receiver, &mut ctx_receiver, &choices)
+                .map_err(OTError::from)
+        )
+        .unwrap();
+
+        let expected = choose(data.iter().copied(), choices.iter_lsb0()).collect::<Vec<_>>();
+
+        assert_eq!(output_sender.id, output_receiver.id);
+        assert_eq!(output_receiver.msgs, expected);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_kos_rekey(data: Vec<[Block; 2]>, choices: Vec<bool>) {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (mut sender, mut receiver) = setup(
+            SenderConfig::default(),
+            ReceiverConfig::default(),
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            data.len(),
+        )
+        .await;
+
+        tokio::try_join!(
+            sender.rekey(&mut ctx_sender).map_err(OTError::from),
+            receiver.rekey(&mut ctx_receiver).map_err(OTError::from)
+        )
+        .unwrap();
+
+        // The OTs can be extended again, and the OTs of the previous extension are kept.
+        tokio::try_join!(
+            sender
+                .extend(&mut ctx_sender, data.len())
+                .map_err(OTError::from),
+            receiver
+                .extend(&mut ctx_receiver, data.len())
+                .map_err(OTError::from)
+        )
+        .unwrap();
+
+        let expected = choose(data.iter().copied(), choices.iter_lsb0()).collect::<Vec<_>>();
+        for _ in 0..2 {
+            let (output_sender, output_receiver) = tokio::try_join!(
+                OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, &data)
+                    .map_err(OTError::from),
+                OTReceiver::<_, bool, Block>::receive(&mut receiver, &mut ctx_receiver, &choices)
+                    .map_err(OTError::from)
+            )
+            .unwrap();
+
+            assert_eq!(output_sender.id, output_receiver.id);
+            assert_eq!(output_receiver.msgs, expected);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_kos_partition(data: Vec<[Block; 2]>, choices: Vec<bool>) {
+        let (mut exec_sender, mut exec_receiver) = test_mt_executor(8);
+        let (mut ctx_sender, mut ctx_receiver) =
+            tokio::try_join!(exec_sender.new_thread(), exec_receiver.new_thread()).unwrap();
+        let (mut sender, mut receiver) = setup(
+            SenderConfig::default(),
+            ReceiverConfig::default(),
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            data.len(),
+        )
+        .await;
+
+        let sender_shards = sender.partition(2).unwrap();
+        let receiver_shards = receiver.partition(2).unwrap();
+        let (sender_ctxs, receiver_ctxs) =
+            tokio::try_join!(ctx_sender.fork(2), ctx_receiver.fork(2)).unwrap();
+
+        // Drive the shards concurrently on the forked contexts.
+        let mut offset = 0;
+        let transfers = sender_shards
+            .into_iter()
+            .zip(receiver_shards)
+            .zip(sender_ctxs.into_iter().zip(receiver_ctxs))
+            .map(
+                |((mut sender, mut receiver), (mut ctx_sender, mut ctx_receiver))| {
+                    let count = receiver.remaining().unwrap();
+                    let data = data[offset..offset + count].to_vec();
+                    let choices = choices[offset..offset + count].to_vec();
+                    offset += count;
+
+                    async move {
+                        let (output_sender, output_receiver) = tokio::try_join!(
+                            OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, &data),
+                            OTReceiver::<_, bool, Block>::receive(
+                                &mut receiver,
+                                &mut ctx_receiver,
+                                &choices
+                            )
+                        )?;
+
+                        assert_eq!(output_sender.id, output_receiver.id);
+                        Ok::<_, OTError>((output_receiver.id, output_receiver.msgs))
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let outputs = futures::future::try_join_all(transfers).await.unwrap();
+
+        assert_ne!(outputs[0].0, outputs[1].0);
+
+        let received = outputs
+            .into_iter()
+            .flat_map(|(_, msgs)| msgs)
+            .collect::<Vec<_>>();
+        let expected = choose(data.iter().copied(), choices.iter_lsb0()).collect::<Vec<_>>();
+
+        assert_eq!(received, expected);
+    }
+
     #[tokio::test]
     async fn test_kos_random() {
         let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
@@ -226,6 +452,72 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_kos_malicious_receiver_check() {
+        // The receiver sends an inconsistent correlation check.
+        let (mut ctx_receiver, mut ctx_sender) =
+            test_tamper_executor(vec![Deviation::modify::<msgs::Check, _>(0, |check| {
+                check.t0 ^= Block::ONE
+            })]);
+        let (base_sender, base_receiver) = ideal_ot();
+
+        let mut sender = Sender::new(SenderConfig::default(), base_receiver);
+        let mut receiver = Receiver::new(ReceiverConfig::default(), base_sender);
+
+        tokio::try_join!(
+            sender.setup(&mut ctx_sender),
+            receiver.setup(&mut ctx_receiver)
+        )
+        .unwrap();
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.extend(&mut ctx_sender, 128),
+            receiver.extend(&mut ctx_receiver, 128)
+        );
+
+        receiver_result.unwrap();
+        assert!(matches!(
+            sender_result,
+            Err(SenderError::CoreError(
+                mpz_ot_core::kos::SenderError::ConsistencyCheckFailed
+            ))
+        ));
+        assert_eq!(ctx_receiver.io_mut().applied(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_kos_config_mismatch() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (base_sender, base_receiver) = ideal_ot();
+
+        let mut sender = Sender::new(
+            SenderConfig::builder().batch_size(1024).build().unwrap(),
+            base_receiver,
+        );
+        let mut receiver = Receiver::new(ReceiverConfig::default(), base_sender);
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.setup(&mut ctx_sender),
+            receiver.setup(&mut ctx_receiver)
+        );
+
+        let sender_err = sender_result.unwrap_err();
+        let receiver_err = receiver_result.unwrap_err();
+
+        assert_eq!(sender_err.kind(), ErrorKind::ProtocolAbort);
+        assert_eq!(receiver_err.kind(), ErrorKind::ProtocolAbort);
+        assert!(matches!(
+            sender_err,
+            OTError::SenderError(err)
+                if matches!(err.downcast_ref(), Some(SenderError::ConfigMismatch(_)))
+        ));
+        assert!(matches!(
+            receiver_err,
+            OTError::ReceiverError(err)
+                if matches!(err.downcast_ref(), Some(ReceiverError::ConfigMismatch(_)))
+        ));
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_shared_kos(data: Vec<[Block; 2]>, choices: Vec<bool>) {
@@ -255,4 +547,105 @@ mod tests {
         assert_eq!(output_sender.id, output_receiver.id);
         assert_eq!(output_receiver.msgs, expected);
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_shared_kos_coalescing(data: Vec<[Block; 2]>, choices: Vec<bool>) {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (base_sender, base_receiver) = ideal_ot();
+
+        let mut sender = Sender::new(SenderConfig::default(), base_receiver);
+        let mut receiver = Receiver::new(ReceiverConfig::default(), base_sender);
+
+        tokio::try_join!(
+            sender.setup(&mut ctx_sender),
+            receiver.setup(&mut ctx_receiver)
+        )
+        .unwrap();
+
+        // No OTs are preprocessed, the first request extends by the batch size.
+        let mut sender = SharedSender::new_coalescing(sender, data.len());
+        let mut receiver = SharedReceiver::new_coalescing(receiver, data.len());
+
+        for (data, choices) in data.chunks(16).zip(choices.chunks(16)) {
+            let (output_sender, output_receiver) = tokio::try_join!(
+                OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, data)
+                    .map_err(OTError::from),
+                OTReceiver::<_, bool, Block>::receive(&mut receiver, &mut ctx_receiver, choices)
+                    .map_err(OTError::from)
+            )
+            .unwrap();
+
+            let expected =
+                choose(data.iter().copied(), choices.iter().copied()).collect::<Vec<_>>();
+
+            assert_eq!(output_sender.id, output_receiver.id);
+            assert_eq!(output_receiver.msgs, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shared_kos_coalescing_concurrent() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let data: Vec<[Block; 2]> = (0..512)
+            .map(|_| [rng.gen::<[u8; 16]>().into(), rng.gen::<[u8; 16]>().into()])
+            .collect();
+        let choices: Vec<bool> = (0..512).map(|_| rng.gen()).collect();
+
+        let (mut exec_sender, mut exec_receiver) = test_mt_executor(8);
+        let (mut ctx_sender, mut ctx_receiver) =
+            tokio::try_join!(exec_sender.new_thread(), exec_receiver.new_thread()).unwrap();
+        let (base_sender, base_receiver) = ideal_ot();
+
+        let mut sender = Sender::new(SenderConfig::default(), base_receiver);
+        let mut receiver = Receiver::new(ReceiverConfig::default(), base_sender);
+
+        tokio::try_join!(
+            sender.setup(&mut ctx_sender),
+            receiver.setup(&mut ctx_receiver)
+        )
+        .unwrap();
+
+        // The tasks request more OTs than one batch holds, so the requests which are queued while
+        // the first batch is extended are served by a single extension after rekeying.
+        let sender = SharedSender::new_coalescing(sender, 16);
+        let receiver = SharedReceiver::new_coalescing(receiver, 16);
+
+        let (sender_ctxs, receiver_ctxs) =
+            tokio::try_join!(ctx_sender.fork(4), ctx_receiver.fork(4)).unwrap();
+
+        let tasks = sender_ctxs
+            .into_iter()
+            .zip(receiver_ctxs)
+            .zip(data.chunks(128).zip(choices.chunks(128)))
+            .map(|((mut ctx_sender, mut ctx_receiver), (data, choices))| {
+                let mut sender = sender.clone();
+                let mut receiver = receiver.clone();
+
+                async move {
+                    for (data, choices) in data.chunks(64).zip(choices.chunks(64)) {
+                        let (output_sender, output_receiver) = tokio::try_join!(
+                            OTSender::<_, [Block; 2]>::send(&mut sender, &mut ctx_sender, data)
+                                .map_err(OTError::from),
+                            OTReceiver::<_, bool, Block>::receive(
+                                &mut receiver,
+                                &mut ctx_receiver,
+                                choices
+                            )
+                            .map_err(OTError::from)
+                        )?;
+
+                        let expected = choose(data.iter().copied(), choices.iter().copied())
+                            .collect::<Vec<_>>();
+
+                        assert_eq!(output_sender.id, output_receiver.id);
+                        assert_eq!(output_receiver.msgs, expected);
+                    }
+
+                    Ok::<_, OTError>(())
+                }
+            });
+
+        futures::future::try_join_all(tasks).await.unwrap();
+    }
 }
@@ -2,9 +2,9 @@ use std::mem;
 
 use async_trait::async_trait;
 use futures::TryFutureExt as _;
-use itybity::{FromBitIterator, IntoBitIterator};
+use itybity::FromBitIterator;
 use mpz_cointoss as cointoss;
-use mpz_common::{try_join, Allocate, Context, Preprocess};
+use mpz_common::{cpu::CpuBackend, try_join, Allocate, Context, Preprocess};
 use mpz_core::{prg::Prg, Block};
 use mpz_ot_core::{
     kos::{
@@ -12,6 +12,7 @@ use mpz_ot_core::{
         pad_ot_count, receiver_state as state, Receiver as ReceiverCore, ReceiverConfig,
         ReceiverKeys, CSP,
     },
+    msgs::ConfigFingerprint,
     OTReceiverOutput, ROTReceiverOutput, TransferId,
 };
 
@@ -22,9 +23,8 @@ use rand::{
 };
 use rand_core::SeedableRng;
 use serio::{stream::IoStreamExt as _, SinkExt as _};
-use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
-use super::{ReceiverError, ReceiverVerifyError, EXTEND_CHUNK_SIZE};
+use super::{batch_size, ReceiverError, ReceiverVerifyError, EXTEND_CHUNK_SIZE};
 use crate::{
     OTError, OTReceiver, OTSender, OTSetup, RandomOTReceiver, VerifiableOTReceiver,
     VerifiableOTSender,
@@ -71,6 +71,33 @@ where
         Ok(self.state.try_as_extension()?.remaining())
     }
 
+    /// Partitions the remaining OTs into `k` shards.
+    ///
+    /// Each shard can be driven concurrently on a forked context, using transfer IDs which are
+    /// disjoint from the other shards. The sender must partition in the same order with the
+    /// same `k`.
+    ///
+    /// Shards do not have a base OT, so they can not be set up or extended. Transfers made with
+    /// a shard are verified using this receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The number of shards.
+    pub fn partition(&mut self, k: usize) -> Result<Vec<Receiver<()>>, ReceiverError> {
+        Ok(self
+            .state
+            .try_as_extension_mut()?
+            .partition(k)?
+            .into_iter()
+            .map(|shard| Receiver {
+                state: State::Extension(Box::new(shard)),
+                base: (),
+                alloc: 0,
+                cointoss_receiver: None,
+            })
+            .collect())
+    }
+
     pub(crate) fn state(&self) -> &State {
         &self.state
     }
@@ -85,11 +112,15 @@ where
 
     /// Performs OT extension.
     ///
+    /// The extension matrix is computed in batches of at most the configured batch size,
+    /// bounding memory usage, followed by a single consistency check.
+    ///
     /// # Arguments
     ///
     /// * `sink` - The sink to send messages to the sender
     /// * `stream` - The stream to receive messages from the sender
     /// * `count` - The number of OTs to extend
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count), skip_all, err))]
     pub async fn extend<Ctx: Context>(
         &mut self,
         ctx: &mut Ctx,
@@ -99,28 +130,41 @@ where
             std::mem::replace(&mut self.state, State::Error).try_into_extension()?;
 
         let count = pad_ot_count(count);
+        let batch_size = batch_size(ext_receiver.config().batch_size())
+            .ok_or_else(|| ReceiverError::ConfigError("batch size must be non-zero".to_string()))?;
 
-        // Extend the OTs.
-        let (mut ext_receiver, extend) = Backend::spawn(move || {
-            ext_receiver
-                .extend(count)
-                .map(|extend| (ext_receiver, extend))
-        })
-        .await?;
-
-        // Send the extend message and cointoss commitment.
         ctx.io_mut().feed(StartExtend { count }).await?;
-        for extend in extend.into_chunks(EXTEND_CHUNK_SIZE) {
-            ctx.io_mut().feed(extend).await?;
+
+        let mut extended = 0;
+        while extended < count {
+            let batch = (count - extended).min(batch_size);
+
+            // Extend the OTs.
+            let (ext, extend) = CpuBackend::blocking(move || {
+                ext_receiver
+                    .extend(batch)
+                    .map(|extend| (ext_receiver, extend))
+            })
+            .await?;
+            ext_receiver = ext;
+
+            // Send the batch of the extension matrix.
+            for extend in extend.into_chunks(EXTEND_CHUNK_SIZE) {
+                ctx.io_mut().feed(extend).await?;
+            }
+            ctx.io_mut().flush().await?;
+            extended += batch;
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(extended, "extended batch");
         }
-        ctx.io_mut().flush().await?;
 
         // Sample chi_seed with coin-toss.
         let seed = thread_rng().gen();
         let chi_seed = cointoss::cointoss_sender(ctx, vec![seed]).await?[0];
 
         // Compute consistency check.
-        let (ext_receiver, check) = Backend::spawn(move || {
+        let (ext_receiver, check) = CpuBackend::blocking(move || {
             ext_receiver
                 .check(chi_seed)
                 .map(|check| (ext_receiver, check))
@@ -128,18 +172,95 @@ where
         .await?;
 
         // Send correlation check value.
-        ctx.io_mut().send(check).await?;
+        if ext_receiver.config().hashed_check() {
+            ctx.io_mut().send(check.into_hashed()).await?;
+        } else {
+            ctx.io_mut().send(check).await?;
+        }
 
         self.state = State::Extension(ext_receiver);
 
         Ok(())
     }
+
+    /// Refreshes the base OTs, without restarting the extension.
+    ///
+    /// This runs a new base OT of [`CSP`] transfers, so that long-lived sessions can rotate the
+    /// correlation secrets periodically. The remaining OTs are kept, and the OTs can be extended
+    /// again. The sender must rekey at the same time, see [`Sender::rekey`](crate::kos::Sender::rekey).
+    ///
+    /// A receiver of a committed sender can not rekey, as the sender's delta is verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
+    pub async fn rekey<Ctx: Context>(&mut self, ctx: &mut Ctx) -> Result<(), ReceiverError>
+    where
+        BaseOT: OTSender<Ctx, [Block; 2]>,
+    {
+        let ext_receiver = self.state.try_as_extension()?;
+        if ext_receiver.config().sender_commit() {
+            return Err(ReceiverError::ConfigError(
+                "receiver of a committed sender can not rekey".to_string(),
+            ));
+        }
+        ext_receiver.check_rekey()?;
+
+        let seeds: [[Block; 2]; CSP] = std::array::from_fn(|_| thread_rng().gen());
+
+        self.base.send(ctx, &seeds).await?;
+
+        self.state
+            .try_as_extension_mut()?
+            .rekey(seeds)
+            .map_err(ReceiverError::from)
+    }
+
+    /// Ensures at least `count` OTs remain, extending if not.
+    ///
+    /// The extension serves all `queued` OTs at once, and at least `batch_size` OTs. If the OTs
+    /// were already extended, the base OTs are refreshed with [`Receiver::rekey`] first, as KOS
+    /// can only extend once per base OT.
+    ///
+    /// Both parties must reserve in the same order, which is guaranteed when the reservation is
+    /// made while holding a synchronized lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of OTs which must remain.
+    /// * `queued` - The number of OTs requested by all queued requests, including this one.
+    /// * `batch_size` - The minimum number of OTs to extend.
+    pub(crate) async fn reserve<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        queued: usize,
+        batch_size: usize,
+    ) -> Result<(), ReceiverError>
+    where
+        BaseOT: OTSender<Ctx, [Block; 2]>,
+    {
+        let remaining = self.remaining()?;
+        if remaining >= count {
+            return Ok(());
+        }
+
+        if self.state.try_as_extension()?.is_extended() {
+            self.rekey(ctx).await?;
+        }
+
+        self.extend(ctx, (queued.max(count) - remaining).max(batch_size))
+            .await
+    }
 }
 
 impl<BaseOT> Receiver<BaseOT>
 where
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     pub(crate) async fn verify_delta<Ctx: Context>(
         &mut self,
         ctx: &mut Ctx,
@@ -182,6 +303,7 @@ where
     Ctx: Context,
     BaseOT: OTSetup<Ctx> + OTSender<Ctx, [Block; 2]> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_extension() {
             return Ok(());
@@ -191,6 +313,16 @@ where
             .try_into_initialized()
             .map_err(ReceiverError::from)?;
 
+        // Negotiate the configuration with the sender before anything else is sent.
+        let config = ext_receiver.config();
+        ctx.io_mut().send(config.fingerprint()).await?;
+        let sender_fingerprint: ConfigFingerprint = ctx.io_mut().expect_next().await?;
+        let ext_receiver = ReceiverCore::new(
+            config
+                .negotiate(&sender_fingerprint)
+                .map_err(ReceiverError::from)?,
+        );
+
         // If the sender is committed, we run a coin toss
         if ext_receiver.config().sender_commit() {
             let cointoss_seed = thread_rng().gen();
@@ -234,6 +366,7 @@ where
 {
     type Error = OTError;
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn preprocess(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_initialized() {
             self.setup(ctx).await?;
@@ -254,6 +387,7 @@ where
     Ctx: Context,
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = choices.len(), id = tracing::field::Empty), skip_all, err))]
     async fn receive(
         &mut self,
         ctx: &mut Ctx,
@@ -266,9 +400,8 @@ where
 
         let mut receiver_keys = receiver.keys(choices.len()).map_err(ReceiverError::from)?;
 
-        let choices = choices.into_lsb0_vec();
         let derandomize = receiver_keys
-            .derandomize(&choices)
+            .derandomize(choices)
             .map_err(ReceiverError::from)?;
 
         // Send derandomize message
@@ -277,8 +410,10 @@ where
         // Receive payload
         let payload: SenderPayload = ctx.io_mut().expect_next().await?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
-        let received = Backend::spawn(move || {
+        let received = CpuBackend::blocking(move || {
             receiver_keys
                 .decrypt_blocks(payload)
                 .map_err(ReceiverError::from)
@@ -296,6 +431,7 @@ where
     Standard: Distribution<T>,
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %_ctx.id(), count, id = tracing::field::Empty), skip_all, err))]
     async fn receive_random(
         &mut self,
         _ctx: &mut Ctx,
@@ -308,6 +444,8 @@ where
 
         let keys = receiver.keys(count).map_err(ReceiverError::from)?;
         let id = keys.id();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
         let (choices, keys) = keys.take_choices_and_keys();
 
         let msgs = keys.into_iter().map(|k| Prg::from_seed(k).gen()).collect();
@@ -322,6 +460,7 @@ where
     Ctx: Context,
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = choices.len(), id = tracing::field::Empty), skip_all, err))]
     async fn receive(
         &mut self,
         ctx: &mut Ctx,
@@ -334,9 +473,8 @@ where
 
         let mut receiver_keys = receiver.keys(choices.len()).map_err(ReceiverError::from)?;
 
-        let choices = choices.into_lsb0_vec();
         let derandomize = receiver_keys
-            .derandomize(&choices)
+            .derandomize(choices)
             .map_err(ReceiverError::from)?;
 
         // Send derandomize message
@@ -345,8 +483,10 @@ where
         // Receive payload
         let payload: SenderPayload = ctx.io_mut().expect_next().await?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
-        let received = Backend::spawn(move || {
+        let received = CpuBackend::blocking(move || {
             receiver_keys
                 .decrypt_bytes(payload)
                 .map_err(ReceiverError::from)
@@ -363,10 +503,12 @@ where
     Ctx: Context,
     BaseOT: VerifiableOTSender<Ctx, bool, [Block; 2]> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn accept_reveal(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.verify_delta(ctx).await.map_err(OTError::from)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %_ctx.id(), %id, count = msgs.len()), skip_all, err))]
     async fn verify(
         &mut self,
         _ctx: &mut Ctx,
@@ -378,7 +520,7 @@ where
         let record = receiver.remove_record(id).map_err(ReceiverError::from)?;
 
         let msgs = msgs.to_vec();
-        Backend::spawn(move || record.verify(&msgs))
+        CpuBackend::blocking(move || record.verify(&msgs))
             .await
             .map_err(ReceiverError::from)?;
 
@@ -5,14 +5,15 @@ use enum_try_as_inner::EnumTryAsInner;
 use futures::TryFutureExt;
 use itybity::IntoBits;
 use mpz_cointoss as cointoss;
-use mpz_common::{try_join, Allocate, Context, Preprocess};
+use mpz_common::{cpu::CpuBackend, try_join, Allocate, Context, Preprocess};
 use mpz_core::{prg::Prg, Block};
 use mpz_ot_core::{
     kos::{
         extension_matrix_size,
-        msgs::{Extend, StartExtend},
+        msgs::{Check, Extend, HashedCheck, StartExtend},
         pad_ot_count, sender_state as state, Sender as SenderCore, SenderConfig, SenderKeys, CSP,
     },
+    msgs::ConfigFingerprint,
     OTSenderOutput, ROTSenderOutput,
 };
 use rand::{
@@ -21,11 +22,10 @@ use rand::{
 };
 use rand_core::SeedableRng;
 use serio::{stream::IoStreamExt as _, SinkExt as _};
-use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
 use crate::{
-    kos::SenderError, CommittedOTReceiver, CommittedOTSender, OTError, OTReceiver, OTSender,
-    OTSetup, RandomOTSender,
+    kos::{batch_size, SenderError},
+    CommittedOTReceiver, CommittedOTSender, OTError, OTReceiver, OTSender, OTSetup, RandomOTSender,
 };
 
 #[derive(Debug, EnumTryAsInner)]
@@ -66,6 +66,32 @@ impl<BaseOT: Send> Sender<BaseOT> {
         Ok(self.state.try_as_extension()?.remaining())
     }
 
+    /// Partitions the remaining OTs into `k` shards.
+    ///
+    /// Each shard can be driven concurrently on a forked context, using transfer IDs which are
+    /// disjoint from the other shards. The receiver must partition in the same order with the
+    /// same `k`.
+    ///
+    /// Shards do not have a base OT, so they can not be set up, extended or revealed.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The number of shards.
+    pub fn partition(&mut self, k: usize) -> Result<Vec<Sender<()>>, SenderError> {
+        Ok(self
+            .state
+            .try_as_extension_mut()?
+            .partition(k)?
+            .into_iter()
+            .map(|shard| Sender {
+                state: State::Extension(shard),
+                base: (),
+                alloc: 0,
+                cointoss_sender: None,
+            })
+            .collect())
+    }
+
     /// Returns the provided number of keys.
     pub(crate) fn take_keys(&mut self, count: usize) -> Result<SenderKeys, SenderError> {
         self.state
@@ -81,6 +107,7 @@ impl<BaseOT: Send> Sender<BaseOT> {
     /// * `sink` - The sink to send messages to the base OT sender
     /// * `stream` - The stream to receive messages from the base OT sender
     /// * `delta` - The delta value to use for the base OT setup.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     pub async fn setup_with_delta<Ctx: Context>(
         &mut self,
         ctx: &mut Ctx,
@@ -95,9 +122,22 @@ impl<BaseOT: Send> Sender<BaseOT> {
             ));
         }
 
+        self.check_config(ctx).await?;
         self._setup_with_delta(ctx, delta).await
     }
 
+    /// Negotiates the configuration with the receiver, before anything else is sent.
+    async fn check_config<Ctx: Context>(&mut self, ctx: &mut Ctx) -> Result<(), SenderError> {
+        let config = self.state.try_as_initialized()?.config().clone();
+        let receiver_fingerprint: ConfigFingerprint = ctx.io_mut().expect_next().await?;
+        ctx.io_mut().send(config.fingerprint()).await?;
+
+        let config = config.negotiate(&receiver_fingerprint)?;
+        self.state = State::Initialized(SenderCore::new(config));
+
+        Ok(())
+    }
+
     async fn _setup_with_delta<Ctx: Context>(
         &mut self,
         ctx: &mut Ctx,
@@ -125,17 +165,20 @@ impl<BaseOT: Send> Sender<BaseOT> {
 
     /// Performs OT extension.
     ///
+    /// The extension matrix is processed in batches of at most the configured batch size,
+    /// bounding memory usage, followed by a single consistency check.
+    ///
     /// # Arguments
     ///
     /// * `channel` - The channel to communicate with the receiver.
     /// * `count` - The number of OTs to extend.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count), skip_all, err))]
     pub async fn extend<Ctx: Context>(
         &mut self,
         ctx: &mut Ctx,
         count: usize,
     ) -> Result<(), SenderError> {
-        let mut ext_sender =
-            std::mem::replace(&mut self.state, State::Error).try_into_extension()?;
+        self.state.try_as_extension()?;
 
         let count = pad_ot_count(count);
 
@@ -144,49 +187,173 @@ impl<BaseOT: Send> Sender<BaseOT> {
         } = ctx.io_mut().expect_next().await?;
 
         if count != receiver_count {
+            self.state = State::Error;
             return Err(SenderError::ConfigError(
                 "sender and receiver count mismatch".to_string(),
             ));
         }
 
-        let expected_us = extension_matrix_size(count);
-        let mut extend = Extend {
-            us: Vec::with_capacity(expected_us),
-        };
+        self.extend_started(ctx, count).await
+    }
+
+    /// Performs OT extension after the receiver has started it.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of OTs to extend, including the padding.
+    async fn extend_started<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+    ) -> Result<(), SenderError> {
+        let mut ext_sender =
+            std::mem::replace(&mut self.state, State::Error).try_into_extension()?;
 
-        // Receive extension matrix from the receiver.
-        while extend.us.len() < expected_us {
-            let Extend { us: chunk } = ctx.io_mut().expect_next().await?;
+        let batch_size = batch_size(ext_sender.config().batch_size())
+            .ok_or_else(|| SenderError::ConfigError("batch size must be non-zero".to_string()))?;
 
-            extend.us.extend(chunk);
-        }
+        let mut extended = 0;
+        while extended < count {
+            let batch = (count - extended).min(batch_size);
+            let expected_us = extension_matrix_size(batch);
+            let mut extend = Extend {
+                us: Vec::with_capacity(expected_us),
+            };
 
-        // Extend the OTs.
-        let mut ext_sender =
-            Backend::spawn(move || ext_sender.extend(count, extend).map(|_| ext_sender)).await?;
+            // Receive the batch of the extension matrix from the receiver.
+            while extend.us.len() < expected_us {
+                let Extend { us: chunk } = ctx.io_mut().expect_next().await?;
+
+                extend.us.extend(chunk);
+            }
+
+            // Extend the OTs.
+            ext_sender =
+                CpuBackend::blocking(move || ext_sender.extend(batch, extend).map(|_| ext_sender))
+                    .await?;
+            extended += batch;
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(extended, "extended batch");
+        }
 
         // Sample chi_seed with coin-toss.
         let seed: Block = thread_rng().gen();
         let chi_seed = cointoss::cointoss_receiver(ctx, vec![seed]).await?[0];
 
-        // Receive the receiver's check.
-        let receiver_check = ctx.io_mut().expect_next().await?;
-
-        // Check consistency of extension.
-        let ext_sender = Backend::spawn(move || {
-            ext_sender
-                .check(chi_seed, receiver_check)
-                .map(|_| ext_sender)
-        })
-        .await?;
+        // Receive the receiver's check, and check consistency of extension.
+        let ext_sender = if ext_sender.config().hashed_check() {
+            let receiver_check: HashedCheck = ctx.io_mut().expect_next().await?;
+            CpuBackend::blocking(move || {
+                ext_sender
+                    .check_hashed(chi_seed, receiver_check)
+                    .map(|_| ext_sender)
+            })
+            .await?
+        } else {
+            let receiver_check: Check = ctx.io_mut().expect_next().await?;
+            CpuBackend::blocking(move || {
+                ext_sender
+                    .check(chi_seed, receiver_check)
+                    .map(|_| ext_sender)
+            })
+            .await?
+        };
 
         self.state = State::Extension(ext_sender);
 
         Ok(())
     }
+
+    /// Refreshes the base OTs with a new delta, without restarting the extension.
+    ///
+    /// This runs a new base OT of [`CSP`] transfers, so that long-lived sessions can rotate the
+    /// correlation secrets periodically. The remaining OTs are kept, and the OTs can be extended
+    /// again. The receiver must rekey at the same time, see [`Receiver::rekey`](crate::kos::Receiver::rekey).
+    ///
+    /// A committed sender can not rekey, as its delta is verified by the receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
+    pub async fn rekey<Ctx: Context>(&mut self, ctx: &mut Ctx) -> Result<(), SenderError>
+    where
+        BaseOT: OTReceiver<Ctx, bool, Block>,
+    {
+        let ext_sender = self.state.try_as_extension()?;
+        if ext_sender.config().sender_commit() {
+            return Err(SenderError::ConfigError(
+                "committed sender can not rekey".to_string(),
+            ));
+        }
+        ext_sender.check_rekey()?;
+
+        let delta = Block::random(&mut thread_rng());
+        let base_output = self.base.receive(ctx, &delta.into_lsb0_vec()).await?;
+
+        let seeds: [Block; CSP] = base_output
+            .msgs
+            .try_into()
+            .expect("seeds should be CSP length");
+
+        self.state
+            .try_as_extension_mut()?
+            .rekey(delta, seeds)
+            .map_err(SenderError::from)
+    }
+
+    /// Ensures at least `count` OTs remain, extending if not.
+    ///
+    /// The receiver decides how many OTs to extend, so that it can serve all of its queued
+    /// requests with a single extension, but it must extend at least `batch_size` OTs and enough
+    /// to serve this request. If the OTs were already extended, the base OTs are refreshed with
+    /// [`Sender::rekey`] first, as KOS can only extend once per base OT.
+    ///
+    /// Both parties must reserve in the same order, which is guaranteed when the reservation is
+    /// made while holding a synchronized lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of OTs which must remain.
+    /// * `batch_size` - The minimum number of OTs to extend.
+    pub(crate) async fn reserve<Ctx: Context>(
+        &mut self,
+        ctx: &mut Ctx,
+        count: usize,
+        batch_size: usize,
+    ) -> Result<(), SenderError>
+    where
+        BaseOT: OTReceiver<Ctx, bool, Block>,
+    {
+        let remaining = self.remaining()?;
+        if remaining >= count {
+            return Ok(());
+        }
+
+        if self.state.try_as_extension()?.is_extended() {
+            self.rekey(ctx).await?;
+        }
+
+        let StartExtend {
+            count: receiver_count,
+        } = ctx.io_mut().expect_next().await?;
+
+        if receiver_count < pad_ot_count((count - remaining).max(batch_size)) {
+            self.state = State::Error;
+            return Err(SenderError::ConfigError(format!(
+                "receiver extends {receiver_count} OTs, which does not serve {count} OTs"
+            )));
+        }
+
+        self.extend_started(ctx, receiver_count).await
+    }
 }
 
 impl<BaseOT: Send> Sender<BaseOT> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     pub(crate) async fn reveal<Ctx: Context>(&mut self, ctx: &mut Ctx) -> Result<(), SenderError>
     where
         BaseOT: CommittedOTReceiver<Ctx, bool, Block>,
@@ -218,11 +385,14 @@ where
     Ctx: Context,
     BaseOT: OTSetup<Ctx> + OTReceiver<Ctx, bool, Block> + Send + 'static,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_extension() {
             return Ok(());
         }
 
+        self.check_config(ctx).await?;
+
         let sender = std::mem::replace(&mut self.state, State::Error)
             .try_into_initialized()
             .map_err(SenderError::from)?;
@@ -276,6 +446,7 @@ where
 {
     type Error = OTError;
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn preprocess(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         if self.state.is_initialized() {
             self.setup(ctx).await?;
@@ -301,6 +472,16 @@ where
         ctx: &mut Ctx,
         msgs: &[[Block; 2]],
     ) -> Result<OTSenderOutput, OTError> {
+        OTSender::<Ctx, [Block; 2]>::send_iter(self, ctx, msgs.iter().copied()).await
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = msgs.len(), id = tracing::field::Empty), skip_all, err))]
+    async fn send_iter<I>(&mut self, ctx: &mut Ctx, msgs: I) -> Result<OTSenderOutput, OTError>
+    where
+        Ctx: Send,
+        [Block; 2]: Send + Sync,
+        I: ExactSizeIterator<Item = [Block; 2]> + Send,
+    {
         let sender = self
             .state
             .try_as_extension_mut()
@@ -313,9 +494,11 @@ where
             .derandomize(derandomize)
             .map_err(SenderError::from)?;
         let payload = sender_keys
-            .encrypt_blocks(msgs)
+            .encrypt_blocks_iter(msgs)
             .map_err(SenderError::from)?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         ctx.io_mut()
             .send(payload)
@@ -332,6 +515,7 @@ where
     Ctx: Context,
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = msgs.len(), id = tracing::field::Empty), skip_all, err))]
     async fn send(
         &mut self,
         ctx: &mut Ctx,
@@ -350,6 +534,8 @@ where
             .map_err(SenderError::from)?;
         let payload = sender_keys.encrypt_bytes(msgs).map_err(SenderError::from)?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         ctx.io_mut()
             .send(payload)
@@ -367,6 +553,7 @@ where
     Standard: Distribution<T>,
     BaseOT: Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %_ctx.id(), count, id = tracing::field::Empty), skip_all, err))]
     async fn send_random(
         &mut self,
         _ctx: &mut Ctx,
@@ -379,6 +566,8 @@ where
 
         let keys = sender.keys(count).map_err(SenderError::from)?;
         let id = keys.id();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         let msgs = keys
             .take_keys()
@@ -401,6 +590,7 @@ where
     Ctx: Context,
     BaseOT: CommittedOTReceiver<Ctx, bool, Block> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn reveal(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.reveal(ctx).await.map_err(OTError::from)
     }
@@ -1,16 +1,18 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use async_trait::async_trait;
 use itybity::IntoBitIterator;
-use mpz_common::{sync::AsyncMutex, Allocate, Context, Preprocess};
+use mpz_common::{cpu::CpuBackend, sync::AsyncMutex, Allocate, Context, Preprocess};
 use mpz_core::Block;
 use mpz_ot_core::{kos::msgs::SenderPayload, OTReceiverOutput, ROTReceiverOutput, TransferId};
 use rand::distributions::{Distribution, Standard};
 use serio::{stream::IoStreamExt, SinkExt};
-use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
 use crate::{
-    kos::{Receiver, ReceiverError},
+    kos::{Receiver, ReceiverError, ReceiverKeys},
     OTError, OTReceiver, OTSender, OTSetup, RandomOTReceiver, VerifiableOTReceiver,
     VerifiableOTSender,
 };
@@ -19,12 +21,17 @@ use crate::{
 #[derive(Debug)]
 pub struct SharedReceiver<BaseOT> {
     inner: Arc<AsyncMutex<Receiver<BaseOT>>>,
+    batch_size: Option<usize>,
+    /// The number of OTs requested by the requests which have not been served yet.
+    queued: Arc<AtomicUsize>,
 }
 
 impl<BaseOT> Clone for SharedReceiver<BaseOT> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            batch_size: self.batch_size,
+            queued: self.queued.clone(),
         }
     }
 }
@@ -35,8 +42,75 @@ impl<BaseOT> SharedReceiver<BaseOT> {
         Self {
             // KOS receiver is always the leader.
             inner: Arc::new(AsyncMutex::new_leader(receiver)),
+            batch_size: None,
+            queued: Arc::new(AtomicUsize::new(0)),
         }
     }
+
+    /// Creates a new shared receiver which coalesces requests into batches.
+    ///
+    /// Requests are queued while waiting for the lock. A request which can not be served from
+    /// the remaining OTs triggers a single extension which serves all queued requests, and at
+    /// least `batch_size` OTs, so many small requests, possibly from different threads, share an
+    /// extension instead of each requiring preprocessing. Once the OTs have been extended, the
+    /// base OTs are rekeyed before extending again, so a committed sender must preprocess all of
+    /// its OTs instead. The peer must be configured with the same batch size.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - The receiver.
+    /// * `batch_size` - The minimum number of OTs to extend at once.
+    pub fn new_coalescing(receiver: Receiver<BaseOT>, batch_size: usize) -> Self {
+        Self {
+            batch_size: Some(batch_size),
+            ..Self::new(receiver)
+        }
+    }
+
+    /// Returns the provided number of keys, extending if necessary when coalescing.
+    async fn take_keys<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        count: usize,
+    ) -> Result<ReceiverKeys, OTError>
+    where
+        BaseOT: OTSender<Ctx, [Block; 2]> + Send,
+    {
+        let request = QueuedRequest::new(&self.queued, count);
+        let mut inner = self.inner.lock(ctx).await?;
+        if let Some(batch_size) = self.batch_size {
+            inner
+                .reserve(ctx, count, request.queued(), batch_size)
+                .await?;
+        }
+
+        Ok(inner.take_keys(count)?)
+    }
+}
+
+/// A request which is queued until it is dropped.
+struct QueuedRequest<'a> {
+    queued: &'a AtomicUsize,
+    count: usize,
+}
+
+impl<'a> QueuedRequest<'a> {
+    fn new(queued: &'a AtomicUsize, count: usize) -> Self {
+        queued.fetch_add(count, Ordering::Relaxed);
+
+        Self { queued, count }
+    }
+
+    /// Returns the number of OTs requested by all queued requests.
+    fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for QueuedRequest<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(self.count, Ordering::Relaxed);
+    }
 }
 
 impl<BaseOT> Allocate for SharedReceiver<BaseOT> {
@@ -53,6 +127,7 @@ where
 {
     type Error = OTError;
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn preprocess(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.inner.lock(ctx).await?.preprocess(ctx).await
     }
@@ -62,14 +137,15 @@ where
 impl<Ctx, BaseOT> OTReceiver<Ctx, bool, Block> for SharedReceiver<BaseOT>
 where
     Ctx: Context,
-    BaseOT: Send,
+    BaseOT: OTSender<Ctx, [Block; 2]> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = choices.len(), id = tracing::field::Empty), skip_all, err))]
     async fn receive(
         &mut self,
         ctx: &mut Ctx,
         choices: &[bool],
     ) -> Result<OTReceiverOutput<Block>, OTError> {
-        let mut keys = self.inner.lock(ctx).await?.take_keys(choices.len())?;
+        let mut keys = self.take_keys(ctx, choices.len()).await?;
 
         let choices = choices.into_lsb0_vec();
         let derandomize = keys.derandomize(&choices).map_err(ReceiverError::from)?;
@@ -80,9 +156,11 @@ where
         // Receive payload
         let payload: SenderPayload = ctx.io_mut().expect_next().await?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         let msgs =
-            Backend::spawn(move || keys.decrypt_blocks(payload).map_err(ReceiverError::from))
+            CpuBackend::blocking(move || keys.decrypt_blocks(payload).map_err(ReceiverError::from))
                 .await?;
 
         Ok(OTReceiverOutput { id, msgs })
@@ -94,14 +172,23 @@ impl<Ctx, T, BaseOT> RandomOTReceiver<Ctx, bool, T> for SharedReceiver<BaseOT>
 where
     Ctx: Context,
     Standard: Distribution<T>,
-    BaseOT: Send,
+    BaseOT: OTSender<Ctx, [Block; 2]> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count), skip_all, err))]
     async fn receive_random(
         &mut self,
         ctx: &mut Ctx,
         count: usize,
     ) -> Result<ROTReceiverOutput<bool, T>, OTError> {
-        self.inner.lock(ctx).await?.receive_random(ctx, count).await
+        let request = QueuedRequest::new(&self.queued, count);
+        let mut inner = self.inner.lock(ctx).await?;
+        if let Some(batch_size) = self.batch_size {
+            inner
+                .reserve(ctx, count, request.queued(), batch_size)
+                .await?;
+        }
+
+        inner.receive_random(ctx, count).await
     }
 }
 
@@ -111,10 +198,12 @@ where
     Ctx: Context,
     BaseOT: VerifiableOTSender<Ctx, bool, [Block; 2]> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn accept_reveal(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.inner.lock(ctx).await?.accept_reveal(ctx).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %_ctx.id(), %id, count = msgs.len()), skip_all, err))]
     async fn verify(
         &mut self,
         _ctx: &mut Ctx,
@@ -130,7 +219,7 @@ where
         };
 
         let msgs = msgs.to_vec();
-        Backend::spawn(move || record.verify(&msgs))
+        CpuBackend::blocking(move || record.verify(&msgs))
             .await
             .map_err(ReceiverError::from)?;
 
@@ -8,7 +8,7 @@ use rand::distributions::{Distribution, Standard};
 use serio::{stream::IoStreamExt as _, SinkExt as _};
 
 use crate::{
-    kos::{Sender, SenderError},
+    kos::{Sender, SenderError, SenderKeys},
     CommittedOTReceiver, CommittedOTSender, OTError, OTReceiver, OTSender, OTSenderOutput, OTSetup,
     ROTSenderOutput, RandomOTSender,
 };
@@ -17,12 +17,14 @@ use crate::{
 #[derive(Debug)]
 pub struct SharedSender<BaseOT> {
     inner: Arc<AsyncMutex<Sender<BaseOT>>>,
+    batch_size: Option<usize>,
 }
 
 impl<BaseOT> Clone for SharedSender<BaseOT> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            batch_size: self.batch_size,
         }
     }
 }
@@ -33,8 +35,46 @@ impl<BaseOT> SharedSender<BaseOT> {
         Self {
             // KOS sender is always the follower.
             inner: Arc::new(AsyncMutex::new_follower(sender)),
+            batch_size: None,
         }
     }
+
+    /// Creates a new shared sender which coalesces requests into batches.
+    ///
+    /// A request which can not be served from the remaining OTs triggers a single extension of
+    /// at least `batch_size` OTs. The receiver sizes the extension to serve all of its queued
+    /// requests, so many small requests, possibly from different threads, share an extension
+    /// instead of each requiring preprocessing. Once the OTs have been extended, the base OTs are
+    /// rekeyed before extending again, so a committed sender must preprocess all of its OTs
+    /// instead. The peer must be configured with the same batch size.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender.
+    /// * `batch_size` - The minimum number of OTs to extend at once.
+    pub fn new_coalescing(sender: Sender<BaseOT>, batch_size: usize) -> Self {
+        Self {
+            batch_size: Some(batch_size),
+            ..Self::new(sender)
+        }
+    }
+
+    /// Returns the provided number of keys, extending if necessary when coalescing.
+    async fn take_keys<Ctx: Context>(
+        &self,
+        ctx: &mut Ctx,
+        count: usize,
+    ) -> Result<SenderKeys, OTError>
+    where
+        BaseOT: OTReceiver<Ctx, bool, Block> + Send,
+    {
+        let mut inner = self.inner.lock(ctx).await?;
+        if let Some(batch_size) = self.batch_size {
+            inner.reserve(ctx, count, batch_size).await?;
+        }
+
+        Ok(inner.take_keys(count)?)
+    }
 }
 
 impl<BaseOT> Allocate for SharedSender<BaseOT> {
@@ -51,6 +91,7 @@ where
 {
     type Error = OTError;
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn preprocess(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.inner.lock(ctx).await?.preprocess(ctx).await
     }
@@ -62,18 +103,21 @@ where
     Ctx: Context,
     BaseOT: OTReceiver<Ctx, bool, Block> + Send + 'static,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count = msgs.len(), id = tracing::field::Empty), skip_all, err))]
     async fn send(
         &mut self,
         ctx: &mut Ctx,
         msgs: &[[Block; 2]],
     ) -> Result<OTSenderOutput, OTError> {
-        let mut keys = self.inner.lock(ctx).await?.take_keys(msgs.len())?;
+        let mut keys = self.take_keys(ctx, msgs.len()).await?;
 
         let derandomize = ctx.io_mut().expect_next().await?;
 
         keys.derandomize(derandomize).map_err(SenderError::from)?;
         let payload = keys.encrypt_blocks(msgs).map_err(SenderError::from)?;
         let id = payload.id;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("id", tracing::field::display(id));
 
         ctx.io_mut()
             .send(payload)
@@ -89,14 +133,20 @@ impl<Ctx, T, BaseOT> RandomOTSender<Ctx, [T; 2]> for SharedSender<BaseOT>
 where
     Ctx: Context,
     Standard: Distribution<T>,
-    BaseOT: Send,
+    BaseOT: OTReceiver<Ctx, bool, Block> + Send,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id(), count), skip_all, err))]
     async fn send_random(
         &mut self,
         ctx: &mut Ctx,
         count: usize,
     ) -> Result<ROTSenderOutput<[T; 2]>, OTError> {
-        self.inner.lock(ctx).await?.send_random(ctx, count).await
+        let mut inner = self.inner.lock(ctx).await?;
+        if let Some(batch_size) = self.batch_size {
+            inner.reserve(ctx, count, batch_size).await?;
+        }
+
+        inner.send_random(ctx, count).await
     }
 }
 
@@ -106,6 +156,7 @@ where
     Ctx: Context,
     BaseOT: CommittedOTReceiver<Ctx, bool, Block> + Send + 'static,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(fields(thread = %ctx.id()), skip_all, err))]
     async fn reveal(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
         self.inner
             .lock(ctx)
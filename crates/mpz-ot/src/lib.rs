@@ -13,12 +13,15 @@ pub mod chou_orlandi;
 #[cfg(any(test, feature = "ideal"))]
 pub mod ideal;
 pub mod kos;
+pub mod oprf;
+#[cfg(feature = "swanky")]
+pub mod swanky;
 
 use async_trait::async_trait;
 
 pub use mpz_ot_core::{
-    COTReceiverOutput, COTSenderOutput, OTReceiverOutput, OTSenderOutput, RCOTReceiverOutput,
-    RCOTSenderOutput, ROTReceiverOutput, ROTSenderOutput, TransferId,
+    COTReceiverOutput, COTSenderOutput, ErrorKind, OTReceiverOutput, OTSenderOutput,
+    RCOTReceiverOutput, RCOTSenderOutput, ROTReceiverOutput, ROTSenderOutput, TransferId,
 };
 
 /// An oblivious transfer error.
@@ -37,6 +40,50 @@ pub enum OTError {
     ReceiverError(Box<dyn std::error::Error + Send + Sync>),
 }
 
+impl OTError {
+    /// Returns the kind of the error.
+    ///
+    /// Errors of protocols which are not known to this crate are classified as
+    /// [`ErrorKind::ProtocolAbort`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            OTError::IOError(_) | OTError::Context(_) => ErrorKind::TransientIo,
+            OTError::Mutex(_) => ErrorKind::ProtocolAbort,
+            OTError::SenderError(err) | OTError::ReceiverError(err) => {
+                if let Some(err) = err.downcast_ref::<chou_orlandi::SenderError>() {
+                    err.kind()
+                } else if let Some(err) = err.downcast_ref::<chou_orlandi::ReceiverError>() {
+                    err.kind()
+                } else if let Some(err) = err.downcast_ref::<kos::SenderError>() {
+                    err.kind()
+                } else if let Some(err) = err.downcast_ref::<kos::ReceiverError>() {
+                    err.kind()
+                } else if let Some(err) = err.downcast_ref::<mpz_ot_core::oprf::SenderError>() {
+                    err.kind()
+                } else if let Some(err) = err.downcast_ref::<mpz_ot_core::oprf::ReceiverError>() {
+                    err.kind()
+                } else {
+                    ErrorKind::ProtocolAbort
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if it is safe to retry, see [`ErrorKind`].
+    pub fn is_recoverable(&self) -> bool {
+        self.kind().is_recoverable()
+    }
+}
+
+/// Returns the kind of a coin-toss error.
+pub(crate) fn cointoss_error_kind(err: &mpz_cointoss::CointossError) -> ErrorKind {
+    match err {
+        mpz_cointoss::CointossError::Io(_) => ErrorKind::TransientIo,
+        mpz_cointoss::CointossError::Core(_) => ErrorKind::PeerMisbehavior,
+        _ => ErrorKind::ProtocolAbort,
+    }
+}
+
 /// An oblivious transfer protocol that needs to perform a one-time setup.
 #[async_trait]
 pub trait OTSetup<Ctx> {
@@ -58,6 +105,26 @@ pub trait OTSender<Ctx, T> {
     /// * `ctx` - The thread context.
     /// * `msgs` - The messages to obliviously transfer.
     async fn send(&mut self, ctx: &mut Ctx, msgs: &[T]) -> Result<OTSenderOutput, OTError>;
+
+    /// Obliviously transfers the messages provided by an iterator to the receiver.
+    ///
+    /// This avoids copying the messages into a contiguous buffer when they are gathered from
+    /// other buffers. The default implementation collects the messages and calls
+    /// [`send`](Self::send).
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `msgs` - The messages to obliviously transfer.
+    async fn send_iter<I>(&mut self, ctx: &mut Ctx, msgs: I) -> Result<OTSenderOutput, OTError>
+    where
+        Ctx: Send,
+        T: Send + Sync,
+        I: ExactSizeIterator<Item = T> + Send,
+    {
+        let msgs: Vec<T> = msgs.collect();
+        self.send(ctx, &msgs).await
+    }
 }
 
 /// A correlated oblivious transfer sender.
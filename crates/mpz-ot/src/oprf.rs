@@ -0,0 +1,194 @@
+//! A batched oblivious PRF built from random VOLE.
+//!
+//! See [`mpz_ot_core::oprf`] for a description of the protocol. Each OPRF instance consumes
+//! [`CSP`] random COTs from the underlying COT protocol, so the cost is amortized best with a
+//! silent OT extension.
+
+use async_trait::async_trait;
+use mpz_common::Context;
+use mpz_core::Block;
+use mpz_ot_core::{
+    oprf::{
+        cot_count, msgs::Masks, Receiver as ReceiverCore, ReceiverError, Sender as SenderCore,
+        SenderError,
+    },
+    RCOTReceiverOutput, RCOTSenderOutput,
+};
+use serio::{stream::IoStreamExt as _, SinkExt as _};
+
+use crate::{OTError, OTSetup, RandomCOTReceiver, RandomCOTSender};
+
+pub use mpz_ot_core::oprf::{SenderKeys, CSP};
+
+impl From<SenderError> for OTError {
+    fn from(err: SenderError) -> Self {
+        OTError::SenderError(Box::new(err))
+    }
+}
+
+impl From<ReceiverError> for OTError {
+    fn from(err: ReceiverError) -> Self {
+        OTError::ReceiverError(Box::new(err))
+    }
+}
+
+/// OPRF sender.
+#[derive(Debug)]
+pub struct Sender<OT> {
+    ot: OT,
+    core: SenderCore,
+}
+
+impl<OT> Sender<OT> {
+    /// Creates a new sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The random COT sender.
+    /// * `delta` - The correlation of the COT sender.
+    pub fn new(ot: OT, delta: Block) -> Self {
+        Self {
+            ot,
+            core: SenderCore::new(delta),
+        }
+    }
+
+    /// Returns the COT sender.
+    pub fn into_inner(self) -> OT {
+        self.ot
+    }
+
+    /// Computes the keys of a batch of OPRF instances.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `count` - The number of instances, which must match the number of the receiver's inputs.
+    pub async fn send<Ctx>(&mut self, ctx: &mut Ctx, count: usize) -> Result<SenderKeys, OTError>
+    where
+        Ctx: Context,
+        OT: RandomCOTSender<Ctx, Block> + Send,
+    {
+        let RCOTSenderOutput { msgs, .. } = self
+            .ot
+            .send_random_correlated(ctx, cot_count(count))
+            .await?;
+
+        let masks: Masks = ctx.io_mut().expect_next().await?;
+
+        Ok(self.core.send(&msgs, masks)?)
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Sender<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.ot.setup(ctx).await
+    }
+}
+
+/// OPRF receiver.
+#[derive(Debug)]
+pub struct Receiver<OT> {
+    ot: OT,
+    core: ReceiverCore,
+}
+
+impl<OT> Receiver<OT> {
+    /// Creates a new receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The random COT receiver.
+    pub fn new(ot: OT) -> Self {
+        Self {
+            ot,
+            core: ReceiverCore::new(),
+        }
+    }
+
+    /// Returns the COT receiver.
+    pub fn into_inner(self) -> OT {
+        self.ot
+    }
+
+    /// Evaluates a batch of OPRF instances, one for each input.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `inputs` - The inputs.
+    pub async fn receive<Ctx, T>(
+        &mut self,
+        ctx: &mut Ctx,
+        inputs: &[T],
+    ) -> Result<Vec<Block>, OTError>
+    where
+        Ctx: Context,
+        OT: RandomCOTReceiver<Ctx, bool, Block> + Send,
+        T: AsRef<[u8]> + Sync,
+    {
+        let RCOTReceiverOutput { choices, msgs, .. } = self
+            .ot
+            .receive_random_correlated(ctx, cot_count(inputs.len()))
+            .await?;
+
+        let (masks, outputs) = self.core.receive(inputs, &choices, &msgs)?;
+
+        ctx.io_mut().send(masks).await?;
+
+        Ok(outputs)
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Receiver<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.ot.setup(ctx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::executor::test_st_executor;
+    use mpz_ot_core::ideal::cot::IdealCOT;
+
+    use crate::ideal::cot::ideal_rcot;
+
+    #[tokio::test]
+    async fn test_oprf() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (cot_sender, cot_receiver) = ideal_rcot();
+
+        // The ideal COT uses the default correlation.
+        let mut sender = Sender::new(cot_sender, IdealCOT::default().delta());
+        let mut receiver = Receiver::new(cot_receiver);
+
+        let inputs = (0..100).map(|i: u32| i.to_be_bytes()).collect::<Vec<_>>();
+
+        let (keys, outputs) = tokio::try_join!(
+            sender.send(&mut ctx_sender, inputs.len()),
+            receiver.receive(&mut ctx_receiver, &inputs)
+        )
+        .unwrap();
+
+        for (i, (input, output)) in inputs.iter().zip(&outputs).enumerate() {
+            assert_eq!(keys.eval(i, input), *output);
+        }
+
+        // The sender's evaluations on inputs of other instances do not match.
+        let evals = keys.eval_batch(0, &inputs);
+        assert_eq!(evals[0], outputs[0]);
+        assert!(evals[1..].iter().all(|eval| !outputs.contains(eval)));
+    }
+}
@@ -0,0 +1,546 @@
+//! Interoperability with [swanky](https://github.com/GaloisInc/swanky).
+//!
+//! This module provides conversions between our types and their `scuttlebutt` equivalents, and
+//! adapters which implement our OT traits on top of an `ocelot` OT instance. This allows a
+//! project to migrate between the two ecosystems incrementally, eg. by using a swanky OT
+//! implementation beneath one of our higher level protocols.
+//!
+//! The swanky protocols are synchronous, so each call into an adapter runs the swanky protocol
+//! on a dedicated thread. The bytes it writes are forwarded to the peer over the I/O channel of
+//! the provided context, and both parties must use an adapter.
+
+use std::{
+    collections::VecDeque,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use async_trait::async_trait;
+use futures::{channel::oneshot, StreamExt as _};
+use mpz_common::Context;
+use mpz_core::Block;
+use ocelot::ot::{Receiver as SwankyReceiver, Sender as SwankySender};
+use rand::{rngs::StdRng, SeedableRng};
+use scuttlebutt::{AbstractChannel, Block as SwankyBlock};
+use serio::{stream::IoStreamExt, SinkExt as _};
+
+use crate::{OTError, OTReceiver, OTReceiverOutput, OTSender, OTSenderOutput, OTSetup, TransferId};
+
+/// Converts a value into its swanky equivalent.
+pub trait IntoSwanky {
+    /// The swanky type.
+    type Output;
+
+    /// Converts `self` into its swanky equivalent.
+    fn into_swanky(self) -> Self::Output;
+}
+
+/// Converts a value from its swanky equivalent.
+pub trait FromSwanky<T>: Sized {
+    /// Converts `value` from its swanky equivalent.
+    fn from_swanky(value: T) -> Self;
+}
+
+impl IntoSwanky for Block {
+    type Output = SwankyBlock;
+
+    fn into_swanky(self) -> SwankyBlock {
+        SwankyBlock::from(u128::from_le_bytes(self.to_bytes()))
+    }
+}
+
+impl FromSwanky<SwankyBlock> for Block {
+    fn from_swanky(value: SwankyBlock) -> Self {
+        Block::new(u128::from(value).to_le_bytes())
+    }
+}
+
+impl IntoSwanky for [Block; 2] {
+    type Output = (SwankyBlock, SwankyBlock);
+
+    fn into_swanky(self) -> Self::Output {
+        let [zero, one] = self;
+        (zero.into_swanky(), one.into_swanky())
+    }
+}
+
+impl FromSwanky<(SwankyBlock, SwankyBlock)> for [Block; 2] {
+    fn from_swanky((zero, one): (SwankyBlock, SwankyBlock)) -> Self {
+        [Block::from_swanky(zero), Block::from_swanky(one)]
+    }
+}
+
+impl<T: IntoSwanky> IntoSwanky for Vec<T> {
+    type Output = Vec<T::Output>;
+
+    fn into_swanky(self) -> Self::Output {
+        self.into_iter().map(IntoSwanky::into_swanky).collect()
+    }
+}
+
+impl<T, U: FromSwanky<T>> FromSwanky<Vec<T>> for Vec<U> {
+    fn from_swanky(value: Vec<T>) -> Self {
+        value.into_iter().map(U::from_swanky).collect()
+    }
+}
+
+/// Swanky OT does not assign transfer ids, so the receiver's output converts into the chosen
+/// messages only.
+impl<T: IntoSwanky> IntoSwanky for OTReceiverOutput<T> {
+    type Output = Vec<T::Output>;
+
+    fn into_swanky(self) -> Self::Output {
+        self.msgs.into_swanky()
+    }
+}
+
+/// Outputs converted from swanky carry the default transfer id.
+impl<T, U: FromSwanky<T>> FromSwanky<Vec<T>> for OTReceiverOutput<U> {
+    fn from_swanky(value: Vec<T>) -> Self {
+        OTReceiverOutput {
+            id: TransferId::default(),
+            msgs: Vec::from_swanky(value),
+        }
+    }
+}
+
+/// Swanky adapter error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum SwankyError {
+    #[error("swanky protocol error: {0}")]
+    Protocol(String),
+    #[error("adapter was not set up")]
+    NotSetup,
+    #[error("adapter was poisoned by a previous error")]
+    Poisoned,
+    #[error("swanky protocol thread panicked")]
+    Panicked,
+}
+
+/// A request from the protocol thread to the I/O loop.
+enum Request {
+    /// Send the bytes to the peer.
+    Send(Vec<u8>),
+    /// Receive the next message from the peer.
+    Recv,
+}
+
+#[derive(Debug)]
+struct ChannelState {
+    requests: futures::channel::mpsc::UnboundedSender<Request>,
+    incoming: mpsc::Receiver<Vec<u8>>,
+    read_buf: VecDeque<u8>,
+    write_buf: Vec<u8>,
+}
+
+impl std::fmt::Debug for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Request::Send(bytes) => write!(f, "Send({} bytes)", bytes.len()),
+            Request::Recv => write!(f, "Recv"),
+        }
+    }
+}
+
+fn disconnected() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::BrokenPipe, "I/O loop disconnected")
+}
+
+impl ChannelState {
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.write_buf.is_empty() {
+            return Ok(());
+        }
+
+        let bytes = std::mem::take(&mut self.write_buf);
+        self.requests
+            .unbounded_send(Request::Send(bytes))
+            .map_err(|_| disconnected())
+    }
+}
+
+/// A swanky channel which forwards bytes to and from the I/O loop.
+///
+/// Buffered writes are flushed before every read, so the peer always receives everything it
+/// needs to make progress.
+#[derive(Debug)]
+struct BridgeChannel(Arc<Mutex<ChannelState>>);
+
+impl AbstractChannel for BridgeChannel {
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> std::io::Result<()> {
+        let mut state = self.0.lock().unwrap();
+        state.flush()?;
+
+        while state.read_buf.len() < bytes.len() {
+            state
+                .requests
+                .unbounded_send(Request::Recv)
+                .map_err(|_| disconnected())?;
+            let msg = state.incoming.recv().map_err(|_| disconnected())?;
+            state.read_buf.extend(msg);
+        }
+
+        for (dst, src) in bytes.iter_mut().zip(state.read_buf.drain(..bytes.len())) {
+            *dst = src;
+        }
+
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.0.lock().unwrap().write_buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Runs `f` on a dedicated thread with a channel bridged to the I/O channel of `ctx`.
+async fn run<Ctx, F, T>(ctx: &mut Ctx, f: F) -> Result<T, OTError>
+where
+    Ctx: Context,
+    F: FnOnce(&mut BridgeChannel, &mut StdRng) -> Result<T, SwankyError> + Send + 'static,
+    T: Send + 'static,
+{
+    let (requests_tx, mut requests_rx) = futures::channel::mpsc::unbounded();
+    let (incoming_tx, incoming_rx) = mpsc::channel();
+    let (output_tx, output_rx) = oneshot::channel();
+
+    let mut channel = BridgeChannel(Arc::new(Mutex::new(ChannelState {
+        requests: requests_tx,
+        incoming: incoming_rx,
+        read_buf: VecDeque::new(),
+        write_buf: Vec::new(),
+    })));
+
+    thread::spawn(move || {
+        let mut rng = StdRng::from_entropy();
+        let output = f(&mut channel, &mut rng).and_then(|output| {
+            channel
+                .flush()
+                .map_err(|e| SwankyError::Protocol(e.to_string()))?;
+            Ok(output)
+        });
+        // Closes the request stream, which terminates the I/O loop.
+        drop(channel);
+        _ = output_tx.send(output);
+    });
+
+    let io = ctx.io_mut();
+    while let Some(request) = requests_rx.next().await {
+        match request {
+            Request::Send(bytes) => io.send(bytes).await?,
+            Request::Recv => {
+                let msg: Vec<u8> = io.expect_next().await?;
+                if incoming_tx.send(msg).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    output_rx
+        .await
+        .map_err(|_| SwankyError::Panicked)?
+        .map_err(OTError::from)
+}
+
+impl From<SwankyError> for OTError {
+    fn from(err: SwankyError) -> Self {
+        OTError::SenderError(Box::new(err))
+    }
+}
+
+#[derive(Debug)]
+enum State<OT> {
+    Initialized,
+    Setup(OT),
+    Error,
+}
+
+impl<OT> State<OT> {
+    fn take(&mut self) -> Result<OT, SwankyError> {
+        match std::mem::replace(self, State::Error) {
+            State::Setup(ot) => Ok(ot),
+            State::Initialized => {
+                *self = State::Initialized;
+                Err(SwankyError::NotSetup)
+            }
+            State::Error => Err(SwankyError::Poisoned),
+        }
+    }
+}
+
+/// An OT sender backed by a swanky OT sender.
+///
+/// The peer must use a [`SwankyOTReceiver`] backed by the corresponding swanky receiver.
+#[derive(Debug)]
+pub struct SwankyOTSender<OT> {
+    state: State<OT>,
+    id: u64,
+}
+
+impl<OT> SwankyOTSender<OT> {
+    /// Creates a new sender.
+    ///
+    /// The swanky sender is initialized during setup.
+    pub fn new() -> Self {
+        Self {
+            state: State::Initialized,
+            id: 0,
+        }
+    }
+
+    /// Creates a new sender from an initialized swanky sender.
+    pub fn from_swanky(ot: OT) -> Self {
+        Self {
+            state: State::Setup(ot),
+            id: 0,
+        }
+    }
+
+    /// Returns the swanky sender, if it is set up.
+    pub fn into_swanky(self) -> Option<OT> {
+        match self.state {
+            State::Setup(ot) => Some(ot),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of transfers which have been executed.
+    pub fn transfers(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<OT> Default for SwankyOTSender<OT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for SwankyOTSender<OT>
+where
+    Ctx: Context,
+    OT: SwankySender<Msg = SwankyBlock> + Send + 'static,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        if matches!(self.state, State::Setup(_)) {
+            return Ok(());
+        }
+
+        self.state = State::Error;
+        let ot = run(ctx, |channel, rng| {
+            OT::init(channel, rng).map_err(|e| SwankyError::Protocol(e.to_string()))
+        })
+        .await?;
+        self.state = State::Setup(ot);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSender<Ctx, [Block; 2]> for SwankyOTSender<OT>
+where
+    Ctx: Context,
+    OT: SwankySender<Msg = SwankyBlock> + Send + 'static,
+{
+    async fn send(
+        &mut self,
+        ctx: &mut Ctx,
+        msgs: &[[Block; 2]],
+    ) -> Result<OTSenderOutput, OTError> {
+        let mut ot = self.state.take()?;
+        let msgs = msgs.to_vec().into_swanky();
+
+        let ot = run(ctx, move |channel, rng| {
+            ot.send(channel, &msgs, rng)
+                .map_err(|e| SwankyError::Protocol(e.to_string()))?;
+            Ok(ot)
+        })
+        .await?;
+
+        self.state = State::Setup(ot);
+        self.id += 1;
+
+        Ok(OTSenderOutput {
+            id: TransferId::default(),
+        })
+    }
+}
+
+/// An OT receiver backed by a swanky OT receiver.
+///
+/// The peer must use a [`SwankyOTSender`] backed by the corresponding swanky sender.
+#[derive(Debug)]
+pub struct SwankyOTReceiver<OT> {
+    state: State<OT>,
+    id: u64,
+}
+
+impl<OT> SwankyOTReceiver<OT> {
+    /// Creates a new receiver.
+    ///
+    /// The swanky receiver is initialized during setup.
+    pub fn new() -> Self {
+        Self {
+            state: State::Initialized,
+            id: 0,
+        }
+    }
+
+    /// Creates a new receiver from an initialized swanky receiver.
+    pub fn from_swanky(ot: OT) -> Self {
+        Self {
+            state: State::Setup(ot),
+            id: 0,
+        }
+    }
+
+    /// Returns the swanky receiver, if it is set up.
+    pub fn into_swanky(self) -> Option<OT> {
+        match self.state {
+            State::Setup(ot) => Some(ot),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of transfers which have been executed.
+    pub fn transfers(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<OT> Default for SwankyOTReceiver<OT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for SwankyOTReceiver<OT>
+where
+    Ctx: Context,
+    OT: SwankyReceiver<Msg = SwankyBlock> + Send + 'static,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        if matches!(self.state, State::Setup(_)) {
+            return Ok(());
+        }
+
+        self.state = State::Error;
+        let ot = run(ctx, |channel, rng| {
+            OT::init(channel, rng).map_err(|e| SwankyError::Protocol(e.to_string()))
+        })
+        .await
+        .map_err(into_receiver_error)?;
+        self.state = State::Setup(ot);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTReceiver<Ctx, bool, Block> for SwankyOTReceiver<OT>
+where
+    Ctx: Context,
+    OT: SwankyReceiver<Msg = SwankyBlock> + Send + 'static,
+{
+    async fn receive(
+        &mut self,
+        ctx: &mut Ctx,
+        choices: &[bool],
+    ) -> Result<OTReceiverOutput<Block>, OTError> {
+        let mut ot = self
+            .state
+            .take()
+            .map_err(|e| OTError::ReceiverError(Box::new(e)))?;
+        let choices = choices.to_vec();
+
+        let (ot, msgs) = run(ctx, move |channel, rng| {
+            let msgs = ot
+                .receive(channel, &choices, rng)
+                .map_err(|e| SwankyError::Protocol(e.to_string()))?;
+            Ok((ot, msgs))
+        })
+        .await
+        .map_err(into_receiver_error)?;
+
+        self.state = State::Setup(ot);
+        self.id += 1;
+
+        Ok(OTReceiverOutput::from_swanky(msgs))
+    }
+}
+
+/// Reports adapter errors on the receiver side as receiver errors.
+fn into_receiver_error(err: OTError) -> OTError {
+    match err {
+        OTError::SenderError(err) => OTError::ReceiverError(err),
+        err => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::executor::test_st_executor;
+    use ocelot::ot::{ChouOrlandiReceiver, ChouOrlandiSender};
+    use rand::Rng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn test_block_roundtrip() {
+        let block: Block = ChaCha12Rng::seed_from_u64(0).gen();
+
+        assert_eq!(Block::from_swanky(block.into_swanky()), block);
+        assert_eq!(
+            block.into_swanky().as_ref(),
+            block.to_bytes().as_slice(),
+            "byte order must be preserved"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_swanky_ot() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let msgs: Vec<[Block; 2]> = (0..16).map(|_| [rng.gen(), rng.gen()]).collect();
+        let choices: Vec<bool> = (0..16).map(|_| rng.gen()).collect();
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let mut sender = SwankyOTSender::<ChouOrlandiSender>::new();
+        let mut receiver = SwankyOTReceiver::<ChouOrlandiReceiver>::new();
+
+        tokio::try_join!(
+            sender.setup(&mut ctx_sender),
+            receiver.setup(&mut ctx_receiver)
+        )
+        .unwrap();
+
+        let (_, output) = tokio::try_join!(
+            sender.send(&mut ctx_sender, &msgs),
+            receiver.receive(&mut ctx_receiver, &choices)
+        )
+        .unwrap();
+
+        let expected = msgs
+            .iter()
+            .zip(&choices)
+            .map(|(msgs, choice)| msgs[*choice as usize])
+            .collect::<Vec<_>>();
+
+        assert_eq!(output.msgs, expected);
+        assert_eq!(sender.transfers(), 1);
+        assert_eq!(receiver.transfers(), 1);
+    }
+}
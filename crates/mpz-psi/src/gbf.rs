@@ -0,0 +1,381 @@
+//! PSI from garbled Bloom filters.
+//!
+//! This is the protocol of [Dong, Chen and Wen](https://eprint.iacr.org/2017/769). The receiver
+//! inserts its items into a Bloom filter, and the sender inserts its items into a garbled Bloom
+//! filter, in which the entries at the positions of an item are random shares of a hash of the
+//! item. For each entry the receiver obtains the sender's garbled entry if its Bloom filter bit is
+//! set, and a random value otherwise, using one OT per entry. The receiver then recombines the
+//! shares at the positions of each of its items, which yields the hash of the item exactly when
+//! the item is in the sender's set, except with negligible probability.
+//!
+//! Both filters are sized for the larger of the two sets, with [`HASH_COUNT`] hash functions.
+
+use async_trait::async_trait;
+use mpz_common::Context;
+use mpz_core::{prg::Prg, Block};
+use mpz_ot::{OTError, OTReceiver, OTReceiverOutput, OTSender, OTSetup};
+use serio::{stream::IoStreamExt as _, SinkExt as _};
+
+use crate::{check_set_size, PsiError, PsiReceiver, PsiSender, DEFAULT_MAX_SET_SIZE};
+
+/// The number of hash functions, which is also the statistical security parameter.
+pub const HASH_COUNT: usize = 40;
+
+/// Context used to derive the positions of an item in the filter.
+const POSITIONS_CONTEXT: &str = "mpz-psi gbf positions";
+/// Context used to derive the value an item is shared into.
+const TARGET_CONTEXT: &str = "mpz-psi gbf target";
+
+/// Returns the number of entries of the filter for sets with at most `count` items.
+///
+/// This is the optimal size of a Bloom filter with a false positive rate of `2^-HASH_COUNT`, ie.
+/// `count * HASH_COUNT * log2(e)`.
+pub fn filter_len(count: usize) -> usize {
+    (count as f64 * HASH_COUNT as f64 * std::f64::consts::LOG2_E).ceil() as usize
+}
+
+/// Returns the distinct positions of an item in a filter with `len` entries.
+fn positions(item: &[u8], len: usize) -> Vec<usize> {
+    let mut reader = blake3::Hasher::new_derive_key(POSITIONS_CONTEXT)
+        .update(item)
+        .finalize_xof();
+
+    let mut positions = (0..HASH_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 8];
+            reader.fill(&mut bytes);
+            (u64::from_le_bytes(bytes) % len as u64) as usize
+        })
+        .collect::<Vec<_>>();
+
+    positions.sort_unstable();
+    positions.dedup();
+    positions
+}
+
+/// Returns the value an item is shared into.
+fn target(item: &[u8]) -> Block {
+    let hash = blake3::derive_key(TARGET_CONTEXT, item);
+    Block::try_from(&hash[..16]).expect("slice is 16 bytes")
+}
+
+/// Builds a garbled Bloom filter of a set.
+fn garbled_filter<T: AsRef<[u8]>>(
+    items: &[T],
+    len: usize,
+    prg: &mut Prg,
+) -> Result<Vec<Block>, PsiError> {
+    let mut filter: Vec<Option<Block>> = vec![None; len];
+
+    for item in items {
+        let item = item.as_ref();
+
+        // Every position but the first empty one is filled with a random share, and the
+        // remaining share is placed in the reserved position.
+        let mut share = target(item);
+        let mut reserved = None;
+        for pos in positions(item, len) {
+            match filter[pos] {
+                Some(value) => share ^= value,
+                None if reserved.is_none() => reserved = Some(pos),
+                None => {
+                    let value = prg.random_block();
+                    filter[pos] = Some(value);
+                    share ^= value;
+                }
+            }
+        }
+
+        match reserved {
+            Some(pos) => filter[pos] = Some(share),
+            // The item is a duplicate.
+            None if share == Block::ZERO => {}
+            None => return Err(PsiError::GbfInsertion),
+        }
+    }
+
+    Ok(filter
+        .into_iter()
+        .map(|value| value.unwrap_or_else(|| prg.random_block()))
+        .collect())
+}
+
+/// Exchanges the sizes of the sets, returning the length of the filters.
+///
+/// Returns an error if either set has more than `max_set_size` items.
+async fn exchange_len<Ctx: Context>(
+    ctx: &mut Ctx,
+    count: usize,
+    max_set_size: usize,
+) -> Result<usize, PsiError> {
+    ctx.io_mut().send(count as u64).await?;
+    let peer_count: u64 = ctx.io_mut().expect_next().await?;
+
+    let count = check_set_size(count as u64, max_set_size)?;
+    let peer_count = check_set_size(peer_count, max_set_size)?;
+
+    Ok(filter_len(count.max(peer_count)))
+}
+
+/// GBF PSI sender.
+#[derive(Debug)]
+pub struct Sender<OT> {
+    ot: OT,
+    max_set_size: usize,
+}
+
+impl<OT> Sender<OT> {
+    /// Creates a new sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The OT sender.
+    pub fn new(ot: OT) -> Self {
+        Self {
+            ot,
+            max_set_size: DEFAULT_MAX_SET_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of items of either set, [`DEFAULT_MAX_SET_SIZE`] by default.
+    ///
+    /// The peer must be configured with the same maximum.
+    pub fn with_max_set_size(mut self, max_set_size: usize) -> Self {
+        self.max_set_size = max_set_size;
+        self
+    }
+
+    /// Returns the OT sender.
+    pub fn into_inner(self) -> OT {
+        self.ot
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Sender<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.ot.setup(ctx).await
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> PsiSender<Ctx> for Sender<OT>
+where
+    Ctx: Context,
+    OT: OTSender<Ctx, [Block; 2]> + Send,
+{
+    async fn send<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<(), PsiError>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        let len = exchange_len(ctx, items.len(), self.max_set_size).await?;
+
+        let msgs = {
+            let mut prg = Prg::new();
+            garbled_filter(items, len, &mut prg)?
+                .into_iter()
+                .map(|value| [prg.random_block(), value])
+                .collect::<Vec<_>>()
+        };
+
+        self.ot.send(ctx, &msgs).await?;
+
+        Ok(())
+    }
+}
+
+/// GBF PSI receiver.
+#[derive(Debug)]
+pub struct Receiver<OT> {
+    ot: OT,
+    max_set_size: usize,
+}
+
+impl<OT> Receiver<OT> {
+    /// Creates a new receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The OT receiver.
+    pub fn new(ot: OT) -> Self {
+        Self {
+            ot,
+            max_set_size: DEFAULT_MAX_SET_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of items of either set, [`DEFAULT_MAX_SET_SIZE`] by default.
+    ///
+    /// The peer must be configured with the same maximum.
+    pub fn with_max_set_size(mut self, max_set_size: usize) -> Self {
+        self.max_set_size = max_set_size;
+        self
+    }
+
+    /// Returns the OT receiver.
+    pub fn into_inner(self) -> OT {
+        self.ot
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Receiver<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.ot.setup(ctx).await
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> PsiReceiver<Ctx> for Receiver<OT>
+where
+    Ctx: Context,
+    OT: OTReceiver<Ctx, bool, Block> + Send,
+{
+    async fn receive<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<Vec<usize>, PsiError>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        let len = exchange_len(ctx, items.len(), self.max_set_size).await?;
+
+        let positions = items
+            .iter()
+            .map(|item| positions(item.as_ref(), len))
+            .collect::<Vec<_>>();
+
+        let mut filter = vec![false; len];
+        for pos in positions.iter().flatten() {
+            filter[*pos] = true;
+        }
+
+        let OTReceiverOutput { msgs, .. } = self.ot.receive(ctx, &filter).await?;
+        if msgs.len() != len {
+            return Err(PsiError::CountMismatch {
+                expected: len,
+                actual: msgs.len(),
+            });
+        }
+
+        Ok(items
+            .iter()
+            .zip(positions)
+            .enumerate()
+            .filter(|(_, (item, positions))| {
+                let value = positions
+                    .iter()
+                    .fold(Block::ZERO, |acc, pos| acc ^ msgs[*pos]);
+
+                value == target(item.as_ref())
+            })
+            .map(|(i, _)| i)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::executor::test_st_executor;
+    use mpz_ot::ideal::ot::ideal_ot;
+
+    #[test]
+    fn test_garbled_filter() {
+        let items = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let len = filter_len(items.len());
+        let filter = garbled_filter(&items, len, &mut Prg::new()).unwrap();
+
+        let recombine = |item: &[u8]| {
+            positions(item, len)
+                .into_iter()
+                .fold(Block::ZERO, |acc, pos| acc ^ filter[pos])
+        };
+
+        assert!(items.iter().all(|item| recombine(item) == target(item)));
+        assert!((100..200u32)
+            .map(|i| i.to_be_bytes())
+            .all(|item| recombine(&item) != target(&item)));
+
+        // Duplicates are ignored.
+        let duplicates = [items[0], items[0]];
+        garbled_filter(&duplicates, len, &mut Prg::new()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gbf_psi() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (ot_sender, ot_receiver) = ideal_ot::<[Block; 2], Block>();
+        let mut sender = Sender::new(ot_sender);
+        let mut receiver = Receiver::new(ot_receiver);
+
+        let sender_items = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let receiver_items = [7u32, 1000, 42, 99, 100, 5000]
+            .map(|i| i.to_be_bytes())
+            .to_vec();
+
+        let (_, intersection) = tokio::try_join!(
+            sender.send(&mut ctx_sender, &sender_items),
+            receiver.receive(&mut ctx_receiver, &receiver_items)
+        )
+        .unwrap();
+
+        assert_eq!(intersection, vec![0, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_gbf_psi_empty() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (ot_sender, ot_receiver) = ideal_ot::<[Block; 2], Block>();
+        let mut sender = Sender::new(ot_sender);
+        let mut receiver = Receiver::new(ot_receiver);
+
+        let sender_items = Vec::<[u8; 4]>::new();
+        let receiver_items = [1u32, 2, 3].map(|i| i.to_be_bytes()).to_vec();
+
+        let (_, intersection) = tokio::try_join!(
+            sender.send(&mut ctx_sender, &sender_items),
+            receiver.receive(&mut ctx_receiver, &receiver_items)
+        )
+        .unwrap();
+
+        assert!(intersection.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gbf_psi_set_too_large() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (ot_sender, ot_receiver) = ideal_ot::<[Block; 2], Block>();
+        let mut sender = Sender::new(ot_sender).with_max_set_size(50);
+        let mut receiver = Receiver::new(ot_receiver).with_max_set_size(50);
+
+        let sender_items = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let receiver_items = [1u32, 2, 3].map(|i| i.to_be_bytes()).to_vec();
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.send(&mut ctx_sender, &sender_items),
+            receiver.receive(&mut ctx_receiver, &receiver_items)
+        );
+
+        assert!(matches!(
+            sender_result.unwrap_err(),
+            PsiError::SetTooLarge {
+                max: 50,
+                actual: 100
+            }
+        ));
+        assert!(matches!(
+            receiver_result.unwrap_err(),
+            PsiError::SetTooLarge {
+                max: 50,
+                actual: 100
+            }
+        ));
+    }
+}
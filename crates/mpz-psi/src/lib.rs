@@ -0,0 +1,145 @@
+//! Private set intersection.
+//!
+//! This crate provides two-party PSI protocols in which a receiver learns which of its items are
+//! also held by a sender, and the sender learns nothing but the size of the receiver's set.
+//!
+//! Protocols implement the common [`PsiSender`] and [`PsiReceiver`] traits so that applications
+//! can swap implementations:
+//!
+//! - [`oprf`] evaluates a batched OPRF on the receiver's items after cuckoo hashing them. It
+//!   requires little communication when used with a silent OT extension.
+//! - [`gbf`] obliviously transfers a garbled Bloom filter of the sender's set. It requires one OT
+//!   for each entry of the filter, but is simple and requires only a single round beyond the OTs.
+//!
+//! Both protocols are secure against semi-honest adversaries.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+pub mod gbf;
+pub mod oprf;
+
+use async_trait::async_trait;
+use mpz_ot::OTError;
+
+/// The default maximum number of items in a set, 1M.
+///
+/// The parties allocate memory in proportion to the size of the peer's set, so sets which are
+/// larger than the configured maximum are rejected.
+pub const DEFAULT_MAX_SET_SIZE: usize = 1 << 20;
+
+/// A PSI error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum PsiError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    OT(#[from] OTError),
+    #[error("failed to insert an item into the garbled Bloom filter")]
+    GbfInsertion,
+    #[error("peer sent {actual} values, expected {expected}")]
+    CountMismatch { expected: usize, actual: usize },
+    #[error("set of {actual} items exceeds the maximum of {max} items")]
+    SetTooLarge { max: usize, actual: u64 },
+    #[error("cuckoo hashing stashed {0} items, more than the stash size")]
+    StashOverflow(usize),
+}
+
+/// Checks that a set of `count` items does not exceed the maximum set size.
+fn check_set_size(count: u64, max: usize) -> Result<usize, PsiError> {
+    usize::try_from(count)
+        .ok()
+        .filter(|count| *count <= max)
+        .ok_or(PsiError::SetTooLarge { max, actual: count })
+}
+
+/// A PSI sender.
+#[async_trait]
+pub trait PsiSender<Ctx> {
+    /// Runs the protocol with the sender's set.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `items` - The items of the set.
+    async fn send<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<(), PsiError>
+    where
+        T: AsRef<[u8]> + Sync;
+}
+
+/// A PSI receiver.
+#[async_trait]
+pub trait PsiReceiver<Ctx> {
+    /// Runs the protocol with the receiver's set, returning the indices of the items which are
+    /// in the intersection in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `items` - The items of the set, which must be distinct.
+    async fn receive<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<Vec<usize>, PsiError>
+    where
+        T: AsRef<[u8]> + Sync;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::{executor::test_st_executor, Context};
+    use mpz_core::Block;
+    use mpz_ot::ideal::{cot::ideal_rcot, ot::ideal_ot};
+    use mpz_ot_core::ideal::cot::IdealCOT;
+
+    async fn run_psi<Ctx, S, R>(
+        ctx_sender: &mut Ctx,
+        ctx_receiver: &mut Ctx,
+        mut sender: S,
+        mut receiver: R,
+    ) -> Vec<usize>
+    where
+        Ctx: Context,
+        S: PsiSender<Ctx>,
+        R: PsiReceiver<Ctx>,
+    {
+        let sender_items = (0..200u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let receiver_items = (150..250u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+
+        let (_, intersection) = tokio::try_join!(
+            sender.send(ctx_sender, &sender_items),
+            receiver.receive(ctx_receiver, &receiver_items)
+        )
+        .unwrap();
+
+        intersection
+    }
+
+    #[tokio::test]
+    async fn test_psi_backends_agree() {
+        let expected = (0..50).collect::<Vec<_>>();
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (cot_sender, cot_receiver) = ideal_rcot();
+        let intersection = run_psi(
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            oprf::Sender::new(cot_sender, IdealCOT::default().delta()),
+            oprf::Receiver::new(cot_receiver),
+        )
+        .await;
+        assert_eq!(intersection, expected);
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (ot_sender, ot_receiver) = ideal_ot::<[Block; 2], Block>();
+        let intersection = run_psi(
+            &mut ctx_sender,
+            &mut ctx_receiver,
+            gbf::Sender::new(ot_sender),
+            gbf::Receiver::new(ot_receiver),
+        )
+        .await;
+        assert_eq!(intersection, expected);
+    }
+}
@@ -0,0 +1,414 @@
+//! PSI from a batched OPRF.
+//!
+//! This is the protocol of [Kolesnikov et al.](https://eprint.iacr.org/2016/799) instantiated with
+//! the VOLE-based OPRF of [`mpz_ot::oprf`]. The receiver inserts its items into a cuckoo hash
+//! table with [`CUCKOO_HASH_COUNT`] hash functions, placing items which cannot be inserted into a
+//! stash, and evaluates one OPRF instance on the contents of each bin and each stash entry.
+//!
+//! The stash is padded to [`STASH_SIZE`] entries, so that its size does not leak to the sender.
+//!
+//! For each hash function, the sender evaluates the instance of the bin its items hash to, and
+//! for each stash entry it evaluates the instance of the entry on all of its items. It sends the
+//! resulting sets of outputs to the receiver in random order, and the receiver checks which of its
+//! outputs are contained in them.
+//!
+//! Each input is tagged with the index of the hash function used to insert it, so that an item
+//! which hashes to the same bin more than once yields distinct outputs.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use mpz_common::Context;
+use mpz_core::Block;
+use mpz_ot::{
+    oprf::{Receiver as OprfReceiver, Sender as OprfSender},
+    OTError, OTSetup, RandomCOTReceiver, RandomCOTSender,
+};
+use rand::seq::SliceRandom;
+use serio::{stream::IoStreamExt as _, SinkExt as _};
+
+use crate::{check_set_size, PsiError, PsiReceiver, PsiSender, DEFAULT_MAX_SET_SIZE};
+
+/// The number of cuckoo hash functions.
+pub const CUCKOO_HASH_COUNT: usize = 3;
+/// The number of entries of the stash.
+///
+/// The receiver returns an error if more items are stashed, which happens with negligible
+/// probability.
+pub const STASH_SIZE: usize = 4;
+
+/// The maximum number of evictions when inserting an item before it is placed in the stash.
+const MAX_EVICTIONS: usize = 128;
+/// The tag of inputs evaluated by the stash instances.
+const STASH_TAG: u8 = CUCKOO_HASH_COUNT as u8;
+/// Context used to derive the bins of an item.
+const BINS_CONTEXT: &str = "mpz-psi oprf bins";
+
+/// Returns the number of bins of the cuckoo hash table for a set of `count` items.
+pub fn bin_count(count: usize) -> usize {
+    ((count as f64 * 1.27).ceil() as usize).max(1)
+}
+
+/// Returns the bins of an item for each hash function.
+fn bins(item: &[u8], bin_count: usize) -> [usize; CUCKOO_HASH_COUNT] {
+    let mut reader = blake3::Hasher::new_derive_key(BINS_CONTEXT)
+        .update(item)
+        .finalize_xof();
+
+    std::array::from_fn(|_| {
+        let mut bytes = [0u8; 8];
+        reader.fill(&mut bytes);
+        (u64::from_le_bytes(bytes) % bin_count as u64) as usize
+    })
+}
+
+/// Returns the input of an OPRF instance for an item with the given tag.
+fn tagged(item: &[u8], tag: u8) -> Vec<u8> {
+    let mut input = Vec::with_capacity(item.len() + 1);
+    input.extend_from_slice(item);
+    input.push(tag);
+    input
+}
+
+/// A cuckoo hash table of the indices of items.
+#[derive(Debug)]
+struct CuckooTable {
+    /// The index of the item in each bin, and the hash function used to insert it.
+    bins: Vec<Option<(usize, usize)>>,
+    /// The indices of the items which could not be inserted.
+    stash: Vec<usize>,
+}
+
+impl CuckooTable {
+    /// Inserts the items into a new table.
+    fn new<T: AsRef<[u8]>>(items: &[T]) -> Self {
+        let bin_count = bin_count(items.len());
+        let item_bins = items
+            .iter()
+            .map(|item| bins(item.as_ref(), bin_count))
+            .collect::<Vec<_>>();
+
+        let mut table = Self {
+            bins: vec![None; bin_count],
+            stash: Vec::new(),
+        };
+
+        for index in 0..items.len() {
+            let mut entry = (index, 0);
+            let mut evictions = 0;
+            loop {
+                let bin = item_bins[entry.0][entry.1];
+                match table.bins[bin].replace(entry) {
+                    None => break,
+                    Some((evicted, _)) if evictions == MAX_EVICTIONS => {
+                        table.stash.push(evicted);
+                        break;
+                    }
+                    Some((evicted, hash)) => {
+                        entry = (evicted, (hash + 1) % CUCKOO_HASH_COUNT);
+                        evictions += 1;
+                    }
+                }
+            }
+        }
+
+        table
+    }
+}
+
+/// OPRF PSI sender.
+#[derive(Debug)]
+pub struct Sender<OT> {
+    oprf: OprfSender<OT>,
+    max_set_size: usize,
+}
+
+impl<OT> Sender<OT> {
+    /// Creates a new sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The random COT sender.
+    /// * `delta` - The correlation of the COT sender.
+    pub fn new(ot: OT, delta: Block) -> Self {
+        Self {
+            oprf: OprfSender::new(ot, delta),
+            max_set_size: DEFAULT_MAX_SET_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of items of the receiver's set, [`DEFAULT_MAX_SET_SIZE`] by
+    /// default.
+    pub fn with_max_set_size(mut self, max_set_size: usize) -> Self {
+        self.max_set_size = max_set_size;
+        self
+    }
+
+    /// Returns the COT sender.
+    pub fn into_inner(self) -> OT {
+        self.oprf.into_inner()
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Sender<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.oprf.setup(ctx).await
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> PsiSender<Ctx> for Sender<OT>
+where
+    Ctx: Context,
+    OT: RandomCOTSender<Ctx, Block> + Send,
+{
+    async fn send<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<(), PsiError>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        let count: u64 = ctx.io_mut().expect_next().await?;
+        let bin_count = bin_count(check_set_size(count, self.max_set_size)?);
+
+        let keys = self.oprf.send(ctx, bin_count + STASH_SIZE).await?;
+
+        let item_bins = items
+            .iter()
+            .map(|item| bins(item.as_ref(), bin_count))
+            .collect::<Vec<_>>();
+
+        let mut sets = (0..CUCKOO_HASH_COUNT)
+            .map(|hash| {
+                items
+                    .iter()
+                    .zip(&item_bins)
+                    .map(|(item, bins)| keys.eval(bins[hash], &tagged(item.as_ref(), hash as u8)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        sets.extend((bin_count..keys.len()).map(|index| {
+            items
+                .iter()
+                .map(|item| keys.eval(index, &tagged(item.as_ref(), STASH_TAG)))
+                .collect::<Vec<_>>()
+        }));
+
+        for set in sets.iter_mut() {
+            set.shuffle(&mut rand::thread_rng());
+        }
+
+        ctx.io_mut().send(sets).await?;
+
+        Ok(())
+    }
+}
+
+/// OPRF PSI receiver.
+#[derive(Debug)]
+pub struct Receiver<OT> {
+    oprf: OprfReceiver<OT>,
+    max_set_size: usize,
+}
+
+impl<OT> Receiver<OT> {
+    /// Creates a new receiver.
+    ///
+    /// # Arguments
+    ///
+    /// * `ot` - The random COT receiver.
+    pub fn new(ot: OT) -> Self {
+        Self {
+            oprf: OprfReceiver::new(ot),
+            max_set_size: DEFAULT_MAX_SET_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of items of the receiver's set, [`DEFAULT_MAX_SET_SIZE`] by
+    /// default.
+    ///
+    /// The sender must be configured with the same maximum.
+    pub fn with_max_set_size(mut self, max_set_size: usize) -> Self {
+        self.max_set_size = max_set_size;
+        self
+    }
+
+    /// Returns the COT receiver.
+    pub fn into_inner(self) -> OT {
+        self.oprf.into_inner()
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> OTSetup<Ctx> for Receiver<OT>
+where
+    Ctx: Context,
+    OT: OTSetup<Ctx> + Send,
+{
+    async fn setup(&mut self, ctx: &mut Ctx) -> Result<(), OTError> {
+        self.oprf.setup(ctx).await
+    }
+}
+
+#[async_trait]
+impl<Ctx, OT> PsiReceiver<Ctx> for Receiver<OT>
+where
+    Ctx: Context,
+    OT: RandomCOTReceiver<Ctx, bool, Block> + Send,
+{
+    async fn receive<T>(&mut self, ctx: &mut Ctx, items: &[T]) -> Result<Vec<usize>, PsiError>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        ctx.io_mut().send(items.len() as u64).await?;
+        check_set_size(items.len() as u64, self.max_set_size)?;
+
+        let table = CuckooTable::new(items);
+        if table.stash.len() > STASH_SIZE {
+            return Err(PsiError::StashOverflow(table.stash.len()));
+        }
+
+        // Empty bins and stash entries are filled with an input which no tagged input is equal
+        // to.
+        let mut inputs = table
+            .bins
+            .iter()
+            .map(|entry| match entry {
+                Some((index, hash)) => tagged(items[*index].as_ref(), *hash as u8),
+                None => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        inputs.extend(
+            table
+                .stash
+                .iter()
+                .map(|index| tagged(items[*index].as_ref(), STASH_TAG)),
+        );
+        inputs.resize(table.bins.len() + STASH_SIZE, Vec::new());
+
+        let outputs = self.oprf.receive(ctx, &inputs).await?;
+
+        let sets: Vec<Vec<Block>> = ctx.io_mut().expect_next().await?;
+        let expected = CUCKOO_HASH_COUNT + STASH_SIZE;
+        if sets.len() != expected {
+            return Err(PsiError::CountMismatch {
+                expected,
+                actual: sets.len(),
+            });
+        }
+
+        let sets = sets
+            .into_iter()
+            .map(|set| {
+                set.into_iter()
+                    .map(|output| output.to_bytes())
+                    .collect::<HashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut intersection = table
+            .bins
+            .iter()
+            .zip(&outputs)
+            .filter_map(|(entry, output)| {
+                entry.filter(|(_, hash)| sets[*hash].contains(&output.to_bytes()))
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        intersection.extend(
+            table
+                .stash
+                .iter()
+                .zip(&outputs[table.bins.len()..])
+                .zip(&sets[CUCKOO_HASH_COUNT..])
+                .filter(|((_, output), set)| set.contains(&output.to_bytes()))
+                .map(|((index, _), _)| *index),
+        );
+
+        intersection.sort_unstable();
+
+        Ok(intersection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::executor::test_st_executor;
+    use mpz_ot::ideal::cot::ideal_rcot;
+    use mpz_ot_core::ideal::cot::IdealCOT;
+
+    #[test]
+    fn test_cuckoo_table() {
+        let items = (0..1000u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let table = CuckooTable::new(&items);
+
+        let mut inserted = table
+            .bins
+            .iter()
+            .enumerate()
+            .filter_map(|(bin, entry)| entry.map(|entry| (bin, entry)))
+            .inspect(|(bin, (index, hash))| {
+                assert_eq!(bins(&items[*index], table.bins.len())[*hash], *bin)
+            })
+            .map(|(_, (index, _))| index)
+            .chain(table.stash.iter().copied())
+            .collect::<Vec<_>>();
+        inserted.sort_unstable();
+
+        assert_eq!(inserted, (0..items.len()).collect::<Vec<_>>());
+        assert!(table.stash.len() <= STASH_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_oprf_psi() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (cot_sender, cot_receiver) = ideal_rcot();
+        let mut sender = Sender::new(cot_sender, IdealCOT::default().delta());
+        let mut receiver = Receiver::new(cot_receiver);
+
+        let sender_items = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let receiver_items = [7u32, 1000, 42, 99, 100, 5000]
+            .map(|i| i.to_be_bytes())
+            .to_vec();
+
+        let (_, intersection) = tokio::try_join!(
+            sender.send(&mut ctx_sender, &sender_items),
+            receiver.receive(&mut ctx_receiver, &receiver_items)
+        )
+        .unwrap();
+
+        assert_eq!(intersection, vec![0, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_oprf_psi_set_too_large() {
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(8);
+        let (cot_sender, cot_receiver) = ideal_rcot();
+        let mut sender = Sender::new(cot_sender, IdealCOT::default().delta()).with_max_set_size(4);
+        let mut receiver = Receiver::new(cot_receiver).with_max_set_size(4);
+
+        let sender_items = (0..100u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let receiver_items = (0..5u32).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+
+        let (sender_result, receiver_result) = tokio::join!(
+            sender.send(&mut ctx_sender, &sender_items),
+            receiver.receive(&mut ctx_receiver, &receiver_items)
+        );
+
+        assert!(matches!(
+            sender_result.unwrap_err(),
+            PsiError::SetTooLarge { max: 4, actual: 5 }
+        ));
+        assert!(matches!(
+            receiver_result.unwrap_err(),
+            PsiError::SetTooLarge { max: 4, actual: 5 }
+        ));
+    }
+}
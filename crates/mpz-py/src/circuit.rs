@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use itybity::IntoBits;
+use mpz_circuits::{
+    circuits::AES128,
+    types::{BinaryRepr, Bit, ToBinaryRepr, TypeError, ValueType},
+    Circuit, CircuitBuilder, Feed, Node, Tracer,
+};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+fn value_err(err: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Parses a value type, eg. `"u8"` or `"u8[16]"`.
+fn parse_type(ty: &str) -> PyResult<ValueType> {
+    let ty = ty.trim();
+    if let Some((elem, len)) = ty.strip_suffix(']').and_then(|ty| ty.split_once('[')) {
+        let len: usize = len
+            .trim()
+            .parse()
+            .map_err(|_| value_err(format!("invalid array length: {len}")))?;
+        return Ok(ValueType::Array(Box::new(parse_type(elem)?), len));
+    }
+
+    Ok(match ty {
+        "bit" | "bool" => ValueType::Bit,
+        "u8" => ValueType::U8,
+        "u16" => ValueType::U16,
+        "u32" => ValueType::U32,
+        "u64" => ValueType::U64,
+        "u128" => ValueType::U128,
+        "i8" => ValueType::I8,
+        "i16" => ValueType::I16,
+        "i32" => ValueType::I32,
+        "i64" => ValueType::I64,
+        "i128" => ValueType::I128,
+        _ => return Err(value_err(format!("unknown type: {ty}"))),
+    })
+}
+
+/// Formats a value type in the syntax accepted by [`parse_type`].
+fn format_type(ty: &ValueType) -> String {
+    match ty {
+        ValueType::Array(elem, len) => format!("{}[{len}]", format_type(elem)),
+        ValueType::Bit => "bit".to_string(),
+        ty => ty.to_string().to_lowercase(),
+    }
+}
+
+/// Returns the binary representation of a value of type `ty` over `nodes`.
+fn bin_repr(ty: &ValueType, nodes: &[Node<Feed>]) -> Result<BinaryRepr, TypeError> {
+    if nodes.len() != ty.len() {
+        return Err(TypeError::InvalidLength {
+            expected: ty.len(),
+            actual: nodes.len(),
+        });
+    }
+
+    Ok(match ty {
+        ValueType::Bit => bool::new_bin_repr(nodes)?.into(),
+        ValueType::U8 => u8::new_bin_repr(nodes)?.into(),
+        ValueType::U16 => u16::new_bin_repr(nodes)?.into(),
+        ValueType::U32 => u32::new_bin_repr(nodes)?.into(),
+        ValueType::U64 => u64::new_bin_repr(nodes)?.into(),
+        ValueType::U128 => u128::new_bin_repr(nodes)?.into(),
+        ValueType::I8 => i8::new_bin_repr(nodes)?.into(),
+        ValueType::I16 => i16::new_bin_repr(nodes)?.into(),
+        ValueType::I32 => i32::new_bin_repr(nodes)?.into(),
+        ValueType::I64 => i64::new_bin_repr(nodes)?.into(),
+        ValueType::I128 => i128::new_bin_repr(nodes)?.into(),
+        ValueType::Array(elem, _) => BinaryRepr::Array(
+            nodes
+                .chunks(elem.len())
+                .map(|nodes| bin_repr(elem, nodes))
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => unimplemented!("unimplemented value type: {:?}", ty),
+    })
+}
+
+/// A boolean circuit.
+#[pyclass(name = "Circuit", frozen)]
+pub struct PyCircuit(pub(crate) Arc<Circuit>);
+
+#[pymethods]
+impl PyCircuit {
+    /// Decodes a circuit from the stable binary encoding.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        Circuit::from_bytes(bytes)
+            .map(|circ| Self(Arc::new(circ)))
+            .map_err(value_err)
+    }
+
+    /// Parses a circuit in Bristol format.
+    ///
+    /// `inputs` and `outputs` are the types of the circuit inputs and outputs.
+    #[staticmethod]
+    fn parse(path: &str, inputs: Vec<String>, outputs: Vec<String>) -> PyResult<Self> {
+        let inputs = inputs
+            .iter()
+            .map(|ty| parse_type(ty))
+            .collect::<PyResult<Vec<_>>>()?;
+        let outputs = outputs
+            .iter()
+            .map(|ty| parse_type(ty))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Circuit::parse(path, &inputs, &outputs)
+            .map(|circ| Self(Arc::new(circ)))
+            .map_err(value_err)
+    }
+
+    /// Returns the AES-128 circuit, `fn(key: u8[16], msg: u8[16]) -> u8[16]`.
+    #[staticmethod]
+    fn aes128() -> Self {
+        Self(AES128.clone())
+    }
+
+    /// Encodes the circuit using the stable binary encoding.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    /// The types of the circuit inputs.
+    #[getter]
+    fn inputs(&self) -> Vec<String> {
+        self.0
+            .inputs()
+            .iter()
+            .map(|input| format_type(&input.value_type()))
+            .collect()
+    }
+
+    /// The types of the circuit outputs.
+    #[getter]
+    fn outputs(&self) -> Vec<String> {
+        self.0
+            .outputs()
+            .iter()
+            .map(|output| format_type(&output.value_type()))
+            .collect()
+    }
+
+    /// The number of AND gates in the circuit.
+    #[getter]
+    fn and_count(&self) -> usize {
+        self.0.and_count()
+    }
+
+    /// The number of XOR gates in the circuit.
+    #[getter]
+    fn xor_count(&self) -> usize {
+        self.0.xor_count()
+    }
+
+    /// Evaluates the circuit in plaintext.
+    ///
+    /// Each input and output is a list of bits in LSB0 order.
+    fn evaluate(&self, inputs: Vec<Vec<bool>>) -> PyResult<Vec<Vec<bool>>> {
+        if inputs.len() != self.0.inputs().len() {
+            return Err(value_err(format!(
+                "expected {} inputs, got {}",
+                self.0.inputs().len(),
+                inputs.len()
+            )));
+        }
+
+        let values = self
+            .0
+            .inputs()
+            .iter()
+            .zip(&inputs)
+            .map(|(input, bits)| input.from_bin_repr(bits))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(value_err)?;
+
+        let outputs = self.0.evaluate(&values).map_err(value_err)?;
+
+        Ok(outputs.into_iter().map(IntoBits::into_lsb0_vec).collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Circuit(inputs={:?}, outputs={:?}, and_count={})",
+            self.inputs(),
+            self.outputs(),
+            self.0.and_count()
+        )
+    }
+}
+
+/// Builds a boolean circuit gate by gate.
+///
+/// Wires are referred to by integer handles which are returned when adding inputs and gates.
+#[pyclass(name = "CircuitBuilder")]
+pub struct PyCircuitBuilder {
+    builder: Option<CircuitBuilder>,
+    wires: Vec<Node<Feed>>,
+}
+
+impl PyCircuitBuilder {
+    fn builder(&self) -> PyResult<&CircuitBuilder> {
+        self.builder
+            .as_ref()
+            .ok_or_else(|| value_err("circuit has already been built"))
+    }
+
+    fn node(&self, wire: usize) -> PyResult<Node<Feed>> {
+        self.wires
+            .get(wire)
+            .copied()
+            .ok_or_else(|| value_err(format!("unknown wire: {wire}")))
+    }
+
+    fn nodes(&self, wires: &[usize]) -> PyResult<Vec<Node<Feed>>> {
+        wires.iter().map(|wire| self.node(*wire)).collect()
+    }
+
+    fn add_wire(&mut self, node: Node<Feed>) -> usize {
+        self.wires.push(node);
+        self.wires.len() - 1
+    }
+
+    fn add_wires(&mut self, repr: &BinaryRepr) -> Vec<usize> {
+        repr.iter().map(|node| self.add_wire(*node)).collect()
+    }
+
+    /// Applies a gate to the provided wires.
+    fn gate(
+        &mut self,
+        wires: &[usize],
+        f: impl for<'a> FnOnce(Vec<Tracer<'a, Bit>>) -> Tracer<'a, Bit>,
+    ) -> PyResult<usize> {
+        let nodes = self.nodes(wires)?;
+        let builder = self.builder()?;
+        let bits = nodes
+            .into_iter()
+            .map(|node| bool::new_bin_repr(&[node]).map(|bit| Tracer::new(builder.state(), bit)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(value_err)?;
+
+        let out = f(bits).node();
+
+        Ok(self.add_wire(out))
+    }
+}
+
+#[pymethods]
+impl PyCircuitBuilder {
+    #[new]
+    fn new() -> Self {
+        Self {
+            builder: Some(CircuitBuilder::new()),
+            wires: Vec::new(),
+        }
+    }
+
+    /// Adds an input of the provided type, eg. `"u8[16]"`, and returns its wires in LSB0 order.
+    fn add_input(&mut self, ty: &str) -> PyResult<Vec<usize>> {
+        let ty = parse_type(ty)?;
+        let repr = self.builder()?.add_input_by_type(ty);
+
+        Ok(self.add_wires(&repr))
+    }
+
+    /// Returns a wire with a constant value.
+    fn constant(&mut self, value: bool) -> PyResult<usize> {
+        let node = self.builder()?.get_constant(value).node();
+
+        Ok(self.add_wire(node))
+    }
+
+    /// Adds an XOR gate.
+    fn xor(&mut self, x: usize, y: usize) -> PyResult<usize> {
+        self.gate(&[x, y], |bits| {
+            let [x, y]: [_; 2] = bits.try_into().expect("two wires");
+            x ^ y
+        })
+    }
+
+    /// Adds an AND gate.
+    fn and_(&mut self, x: usize, y: usize) -> PyResult<usize> {
+        self.gate(&[x, y], |bits| {
+            let [x, y]: [_; 2] = bits.try_into().expect("two wires");
+            x & y
+        })
+    }
+
+    /// Adds an OR gate.
+    fn or_(&mut self, x: usize, y: usize) -> PyResult<usize> {
+        self.gate(&[x, y], |bits| {
+            let [x, y]: [_; 2] = bits.try_into().expect("two wires");
+            x | y
+        })
+    }
+
+    /// Adds an inverter.
+    fn inv(&mut self, x: usize) -> PyResult<usize> {
+        self.gate(&[x], |bits| {
+            let [x]: [_; 1] = bits.try_into().expect("one wire");
+            !x
+        })
+    }
+
+    /// Appends an existing circuit and returns the wires of its outputs.
+    ///
+    /// `inputs` contains the wires of each input of the appended circuit.
+    fn append(
+        &mut self,
+        circuit: &PyCircuit,
+        inputs: Vec<Vec<usize>>,
+    ) -> PyResult<Vec<Vec<usize>>> {
+        if inputs.len() != circuit.0.inputs().len() {
+            return Err(value_err(format!(
+                "expected {} inputs, got {}",
+                circuit.0.inputs().len(),
+                inputs.len()
+            )));
+        }
+
+        let inputs = circuit
+            .0
+            .inputs()
+            .iter()
+            .zip(&inputs)
+            .map(|(input, wires)| {
+                bin_repr(&input.value_type(), &self.nodes(wires)?).map_err(value_err)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let outputs = self
+            .builder()?
+            .append(&circuit.0, &inputs)
+            .map_err(value_err)?;
+
+        Ok(outputs
+            .iter()
+            .map(|output| self.add_wires(output))
+            .collect())
+    }
+
+    /// Adds an output of the provided type over the wires, in LSB0 order.
+    fn add_output(&mut self, wires: Vec<usize>, ty: &str) -> PyResult<()> {
+        let ty = parse_type(ty)?;
+        let repr = bin_repr(&ty, &self.nodes(&wires)?).map_err(value_err)?;
+        self.builder()?.add_output(repr);
+
+        Ok(())
+    }
+
+    /// Builds the circuit.
+    ///
+    /// The builder can not be used afterwards.
+    fn build(&mut self) -> PyResult<PyCircuit> {
+        let builder = self
+            .builder
+            .take()
+            .ok_or_else(|| value_err("circuit has already been built"))?;
+
+        builder
+            .build()
+            .map(|circ| PyCircuit(Arc::new(circ)))
+            .map_err(value_err)
+    }
+}
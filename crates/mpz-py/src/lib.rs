@@ -0,0 +1,95 @@
+//! Python bindings for the mpz protocols.
+//!
+//! This crate builds the `mpz` Python extension module with [pyo3](https://pyo3.rs), aimed at
+//! prototyping protocols from Python before writing any Rust. It exposes:
+//!
+//! - `CircuitBuilder` and `Circuit` for building, loading and evaluating boolean circuits.
+//! - `Channel`, an in-memory or TCP transport between two parties.
+//! - `ot_send`/`ot_receive` for base OT and OT extension.
+//! - `garble`/`evaluate` for semi-honest two-party garbled circuit evaluation.
+//!
+//! The protocols are driven through the C ABI of `mpz-ffi`.
+//!
+//! # Building
+//!
+//! The module is built with [maturin](https://www.maturin.rs):
+//!
+//! ```sh
+//! cd crates/mpz-py
+//! maturin develop --release
+//! ```
+//!
+//! # Example
+//!
+//! ```python
+//! import threading
+//! import mpz
+//!
+//! circ = mpz.Circuit.aes128()
+//! alice, bob = mpz.Channel.memory_pair()
+//!
+//! key = mpz.to_bits(b"super secret key")
+//! msg = mpz.to_bits(b"super secret msg")
+//!
+//! gen = threading.Thread(target=mpz.garble, args=(alice, circ, [0, 1], key))
+//! gen.start()
+//! ciphertext = mpz.from_bits(mpz.evaluate(bob, circ, [0, 1], msg))
+//! gen.join()
+//! ```
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+
+mod circuit;
+mod protocol;
+mod transport;
+
+use pyo3::prelude::*;
+
+pub use circuit::{PyCircuit, PyCircuitBuilder};
+pub use transport::PyChannel;
+
+/// Converts bytes into bits in LSB0 order.
+#[pyfunction]
+fn to_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .collect()
+}
+
+/// Converts bits in LSB0 order into bytes.
+#[pyfunction]
+fn from_bits(bits: Vec<bool>) -> PyResult<Vec<u8>> {
+    if !bits.len().is_multiple_of(8) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "number of bits must be a multiple of 8",
+        ));
+    }
+
+    Ok(bits
+        .chunks(8)
+        .map(|bits| {
+            bits.iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, bit)| byte | (u8::from(*bit) << i))
+        })
+        .collect())
+}
+
+/// The `mpz` Python module.
+#[pymodule]
+fn mpz(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCircuit>()?;
+    m.add_class::<PyCircuitBuilder>()?;
+    m.add_class::<PyChannel>()?;
+
+    m.add_function(wrap_pyfunction!(to_bits, m)?)?;
+    m.add_function(wrap_pyfunction!(from_bits, m)?)?;
+    m.add_function(wrap_pyfunction!(protocol::ot_send, m)?)?;
+    m.add_function(wrap_pyfunction!(protocol::ot_receive, m)?)?;
+    m.add_function(wrap_pyfunction!(protocol::garble, m)?)?;
+    m.add_function(wrap_pyfunction!(protocol::evaluate, m)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use std::{ffi::CStr, ptr};
+
+use mpz_ffi::{
+    mpz_buffer_free, mpz_evaluate, mpz_garble, mpz_last_error, mpz_ot_ext_receive, mpz_ot_ext_send,
+    mpz_ot_receive, mpz_ot_send, MpzBuffer, MpzStatus, MPZ_BLOCK_LEN,
+};
+use pyo3::{
+    exceptions::{PyIOError, PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+
+use crate::{PyChannel, PyCircuit};
+
+/// Converts a status code into a Python exception.
+///
+/// Must be called on the thread which made the call.
+fn check(status: MpzStatus) -> Result<(), (MpzStatus, String)> {
+    if status == MpzStatus::Ok {
+        return Ok(());
+    }
+
+    let msg = mpz_last_error();
+    let msg = if msg.is_null() {
+        format!("{status:?}")
+    } else {
+        // SAFETY: the error message is a valid C string until the next call into `mpz-ffi`.
+        unsafe { CStr::from_ptr(msg) }
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    Err((status, msg))
+}
+
+fn into_py_err((status, msg): (MpzStatus, String)) -> PyErr {
+    match status {
+        MpzStatus::NullPointer | MpzStatus::InvalidInput => PyValueError::new_err(msg),
+        MpzStatus::Io => PyIOError::new_err(msg),
+        _ => PyRuntimeError::new_err(msg),
+    }
+}
+
+fn to_bytes(bits: &[bool]) -> Vec<u8> {
+    bits.iter().map(|bit| u8::from(*bit)).collect()
+}
+
+/// Sends pairs of 16 byte messages using oblivious transfer.
+///
+/// The peer must call `ot_receive` with the same number of choices and the same `extension`
+/// setting. OT extension (KOS15) is used by default, otherwise base OT (CO15).
+#[pyfunction]
+#[pyo3(signature = (channel, msgs, extension = true))]
+pub(crate) fn ot_send(
+    py: Python<'_>,
+    mut channel: PyRefMut<'_, PyChannel>,
+    msgs: Vec<(Vec<u8>, Vec<u8>)>,
+    extension: bool,
+) -> PyResult<()> {
+    let mut data = Vec::with_capacity(msgs.len() * 2 * MPZ_BLOCK_LEN);
+    for (zero, one) in &msgs {
+        if zero.len() != MPZ_BLOCK_LEN || one.len() != MPZ_BLOCK_LEN {
+            return Err(PyValueError::new_err("messages must be 16 bytes"));
+        }
+        data.extend_from_slice(zero);
+        data.extend_from_slice(one);
+    }
+
+    channel
+        .with_io(py, |io| {
+            check(unsafe {
+                if extension {
+                    mpz_ot_ext_send(io, data.as_ptr(), msgs.len())
+                } else {
+                    mpz_ot_send(io, data.as_ptr(), msgs.len())
+                }
+            })
+        })
+        .map_err(into_py_err)
+}
+
+/// Receives the chosen 16 byte messages using oblivious transfer.
+///
+/// See `ot_send`.
+#[pyfunction]
+#[pyo3(signature = (channel, choices, extension = true))]
+pub(crate) fn ot_receive<'py>(
+    py: Python<'py>,
+    mut channel: PyRefMut<'_, PyChannel>,
+    choices: Vec<bool>,
+    extension: bool,
+) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+    let choices = to_bytes(&choices);
+    let mut out = vec![0u8; choices.len() * MPZ_BLOCK_LEN];
+
+    channel
+        .with_io(py, |io| {
+            check(unsafe {
+                if extension {
+                    mpz_ot_ext_receive(io, choices.as_ptr(), choices.len(), out.as_mut_ptr())
+                } else {
+                    mpz_ot_receive(io, choices.as_ptr(), choices.len(), out.as_mut_ptr())
+                }
+            })
+        })
+        .map_err(into_py_err)?;
+
+    Ok(out
+        .chunks_exact(MPZ_BLOCK_LEN)
+        .map(|msg| PyBytes::new_bound(py, msg))
+        .collect())
+}
+
+type GarbleFn = unsafe extern "C" fn(
+    *const mpz_ffi::MpzIo,
+    *const u8,
+    usize,
+    *const u8,
+    *const u8,
+    usize,
+    *mut MpzBuffer,
+) -> MpzStatus;
+
+fn execute(
+    py: Python<'_>,
+    mut channel: PyRefMut<'_, PyChannel>,
+    circuit: &PyCircuit,
+    owners: Vec<u8>,
+    bits: Vec<bool>,
+    f: GarbleFn,
+) -> PyResult<Vec<bool>> {
+    if owners.len() != circuit.0.inputs().len() {
+        return Err(PyValueError::new_err(format!(
+            "expected {} input owners, got {}",
+            circuit.0.inputs().len(),
+            owners.len()
+        )));
+    }
+
+    let circuit = circuit.0.to_bytes();
+    let bits = to_bytes(&bits);
+
+    channel
+        .with_io(py, |io| {
+            let mut out = MpzBuffer {
+                ptr: ptr::null_mut(),
+                len: 0,
+            };
+
+            check(unsafe {
+                f(
+                    io,
+                    circuit.as_ptr(),
+                    circuit.len(),
+                    owners.as_ptr(),
+                    bits.as_ptr(),
+                    bits.len(),
+                    &mut out,
+                )
+            })?;
+
+            let outputs = if out.len == 0 {
+                Vec::new()
+            } else {
+                // SAFETY: the buffer was allocated by `mpz-ffi` and is released below.
+                unsafe { std::slice::from_raw_parts(out.ptr, out.len) }
+                    .iter()
+                    .map(|bit| *bit == 1)
+                    .collect()
+            };
+            unsafe { mpz_buffer_free(&mut out) };
+
+            Ok(outputs)
+        })
+        .map_err(into_py_err)
+}
+
+/// Garbles a circuit as the generator and returns the output bits.
+///
+/// `owners` assigns each circuit input to the generator (`0`) or the evaluator (`1`), and
+/// `bits` contains the generator's input bits in LSB0 order. The peer must call `evaluate`
+/// with the same circuit and owners.
+#[pyfunction]
+pub(crate) fn garble(
+    py: Python<'_>,
+    channel: PyRefMut<'_, PyChannel>,
+    circuit: PyRef<'_, PyCircuit>,
+    owners: Vec<u8>,
+    bits: Vec<bool>,
+) -> PyResult<Vec<bool>> {
+    execute(py, channel, &circuit, owners, bits, mpz_garble)
+}
+
+/// Evaluates a garbled circuit as the evaluator and returns the output bits.
+///
+/// See `garble`.
+#[pyfunction]
+pub(crate) fn evaluate(
+    py: Python<'_>,
+    channel: PyRefMut<'_, PyChannel>,
+    circuit: PyRef<'_, PyCircuit>,
+    owners: Vec<u8>,
+    bits: Vec<bool>,
+) -> PyResult<Vec<bool>> {
+    execute(py, channel, &circuit, owners, bits, mpz_evaluate)
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::VecDeque,
+    ffi::c_void,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use mpz_ffi::MpzIo;
+use pyo3::prelude::*;
+
+/// Transport used by a [`PyChannel`].
+enum Transport {
+    Memory {
+        tx: Sender<Vec<u8>>,
+        rx: Receiver<Vec<u8>>,
+        buf: VecDeque<u8>,
+    },
+    Tcp(TcpStream),
+}
+
+impl Transport {
+    fn send(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Transport::Memory { tx, .. } => tx.send(data.to_vec()).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "peer disconnected")
+            }),
+            Transport::Tcp(stream) => stream.write_all(data),
+        }
+    }
+
+    fn recv(&mut self, data: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            Transport::Memory { rx, buf, .. } => {
+                while buf.len() < data.len() {
+                    let bytes = rx.recv().map_err(|_| {
+                        std::io::Error::new(std::io::ErrorKind::BrokenPipe, "peer disconnected")
+                    })?;
+                    buf.extend(bytes);
+                }
+
+                let n = data.len();
+                for (dst, src) in data.iter_mut().zip(buf.drain(..n)) {
+                    *dst = src;
+                }
+
+                Ok(())
+            }
+            Transport::Tcp(stream) => stream.read_exact(data),
+        }
+    }
+}
+
+/// A channel to the peer.
+///
+/// Channels are either created in connected in-memory pairs, which is convenient for running
+/// both parties in one process, or over TCP.
+#[pyclass(name = "Channel")]
+pub struct PyChannel(Transport);
+
+#[pymethods]
+impl PyChannel {
+    /// Returns a pair of connected in-memory channels.
+    #[staticmethod]
+    fn memory_pair() -> (Self, Self) {
+        let (tx_a, rx_b) = channel();
+        let (tx_b, rx_a) = channel();
+
+        (
+            Self(Transport::Memory {
+                tx: tx_a,
+                rx: rx_a,
+                buf: VecDeque::new(),
+            }),
+            Self(Transport::Memory {
+                tx: tx_b,
+                rx: rx_b,
+                buf: VecDeque::new(),
+            }),
+        )
+    }
+
+    /// Connects to a peer listening on `addr`.
+    #[staticmethod]
+    fn connect(py: Python<'_>, addr: &str) -> PyResult<Self> {
+        let stream = py.allow_threads(|| TcpStream::connect(addr))?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self(Transport::Tcp(stream)))
+    }
+
+    /// Listens on `addr` and accepts a single connection from the peer.
+    #[staticmethod]
+    fn listen(py: Python<'_>, addr: &str) -> PyResult<Self> {
+        let (stream, _) = py.allow_threads(|| TcpListener::bind(addr)?.accept())?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self(Transport::Tcp(stream)))
+    }
+}
+
+impl PyChannel {
+    /// Runs `f` with I/O callbacks backed by the channel.
+    ///
+    /// The GIL is released while `f` runs.
+    pub(crate) fn with_io<T: Send>(
+        &mut self,
+        py: Python<'_>,
+        f: impl FnOnce(&MpzIo) -> T + Send,
+    ) -> T {
+        let transport = &mut self.0;
+        py.allow_threads(move || {
+            let io = MpzIo {
+                ctx: transport as *mut Transport as *mut c_void,
+                send: Some(send),
+                recv: Some(recv),
+            };
+
+            f(&io)
+        })
+    }
+}
+
+unsafe extern "C" fn send(ctx: *mut c_void, data: *const u8, len: usize) -> i32 {
+    let transport = &mut *(ctx as *mut Transport);
+    let data = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    };
+
+    match transport.send(data) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn recv(ctx: *mut c_void, data: *mut u8, len: usize) -> i32 {
+    let transport = &mut *(ctx as *mut Transport);
+    let data = if len == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(data, len)
+    };
+
+    match transport.recv(data) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
@@ -0,0 +1,72 @@
+"""Tests for the `mpz` Python module.
+
+Run with `maturin develop && pytest` from `crates/mpz-py`.
+"""
+
+import os
+import threading
+
+import mpz
+
+
+def run(gen, ev):
+    result = {}
+
+    def target():
+        result["gen"] = gen()
+
+    thread = threading.Thread(target=target)
+    thread.start()
+    result["ev"] = ev()
+    thread.join()
+
+    return result["gen"], result["ev"]
+
+
+def test_bits_roundtrip():
+    data = os.urandom(16)
+    assert mpz.from_bits(mpz.to_bits(data)) == data
+
+
+def test_builder():
+    builder = mpz.CircuitBuilder()
+    a = builder.add_input("u8")
+    b = builder.add_input("u8")
+    builder.add_output([builder.xor(x, y) for x, y in zip(a, b)], "u8")
+    circ = builder.build()
+
+    assert circ.inputs == ["u8", "u8"]
+    assert circ.outputs == ["u8"]
+
+    out = circ.evaluate([mpz.to_bits(b"\x0f"), mpz.to_bits(b"\xff")])
+    assert mpz.from_bits(out[0]) == b"\xf0"
+
+
+def test_ot():
+    for extension in (False, True):
+        msgs = [(os.urandom(16), os.urandom(16)) for _ in range(100)]
+        choices = [bool(byte & 1) for byte in os.urandom(100)]
+        sender, receiver = mpz.Channel.memory_pair()
+
+        _, received = run(
+            lambda: mpz.ot_send(sender, msgs, extension),
+            lambda: mpz.ot_receive(receiver, choices, extension),
+        )
+
+        assert received == [msg[choice] for msg, choice in zip(msgs, choices)]
+
+
+def test_garble():
+    circ = mpz.Circuit.aes128()
+    key = mpz.to_bits(b"super secret key")
+    msg = mpz.to_bits(b"super secret msg")
+    expected = circ.evaluate([key, msg])[0]
+
+    gen, ev = mpz.Channel.memory_pair()
+    gen_out, ev_out = run(
+        lambda: mpz.garble(gen, circ, [0, 1], key),
+        lambda: mpz.evaluate(ev, circ, [0, 1], msg),
+    )
+
+    assert gen_out == expected
+    assert ev_out == expected
@@ -9,7 +9,10 @@ use mpz_common::{
 use mpz_fields::Field;
 use mpz_share_conversion_core::ideal::{IdealA2M, IdealM2A};
 
-use crate::{AdditiveToMultiplicative, MultiplicativeToAdditive, ShareConversionError};
+use crate::{
+    AdditiveToMultiplicative, BatchShareConvert, Conversion, MultiplicativeToAdditive,
+    ShareConversionError,
+};
 
 #[derive(Debug, Default)]
 struct Inner {
@@ -85,6 +88,40 @@ impl<Ctx: Context, F: Field> MultiplicativeToAdditive<Ctx, F> for IdealShareConv
     }
 }
 
+fn convert_batch<F: Field>(
+    inner: &mut Inner,
+    alice: Vec<Conversion<F>>,
+    bob: Vec<Conversion<F>>,
+) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
+    assert_eq!(alice.len(), bob.len());
+
+    alice
+        .into_iter()
+        .zip(bob)
+        .map(|(a, b)| match (a, b) {
+            (Conversion::ToAdditive(a), Conversion::ToAdditive(b)) => inner.m2a.generate(a, b),
+            (Conversion::ToMultiplicative(a), Conversion::ToMultiplicative(b)) => {
+                inner.a2m.generate(a, b)
+            }
+            _ => panic!("conversions of the batch should match"),
+        })
+        .unzip()
+}
+
+#[async_trait]
+impl<Ctx: Context, F: Field> BatchShareConvert<Ctx, F> for IdealShareConverter {
+    async fn convert_batch(
+        &mut self,
+        ctx: &mut Ctx,
+        batch: Vec<Conversion<F>>,
+    ) -> Result<Vec<Vec<F>>, ShareConversionError> {
+        Ok(match &mut self.0 {
+            Role::Alice(alice) => alice.call(ctx, batch, convert_batch).await,
+            Role::Bob(bob) => bob.call(ctx, batch, convert_batch).await,
+        })
+    }
+}
+
 /// Creates a pair of ideal share converters.
 pub fn ideal_share_converter() -> (IdealShareConverter, IdealShareConverter) {
     let (alice, bob) = ideal_f2p(Inner::default());
@@ -7,6 +7,7 @@
 mod error;
 #[cfg(feature = "ideal")]
 pub mod ideal;
+pub mod point_addition;
 mod receiver;
 mod sender;
 
@@ -48,6 +49,49 @@ pub trait MultiplicativeToAdditive<Ctx, T> {
     ) -> Result<Vec<T>, ShareConversionError>;
 }
 
+/// A conversion of shares in a batch, see [`BatchShareConvert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion<T> {
+    /// Converts multiplicative shares into additive shares.
+    ToAdditive(Vec<T>),
+    /// Converts additive shares into multiplicative shares.
+    ToMultiplicative(Vec<T>),
+}
+
+impl<T> Conversion<T> {
+    /// Returns the shares to convert.
+    pub fn inputs(&self) -> &[T] {
+        match self {
+            Conversion::ToAdditive(inputs) | Conversion::ToMultiplicative(inputs) => inputs,
+        }
+    }
+
+    /// Returns `true` if the conversion is from additive to multiplicative shares.
+    pub fn is_to_multiplicative(&self) -> bool {
+        matches!(self, Conversion::ToMultiplicative(_))
+    }
+}
+
+/// A trait for converting a batch of shares in one protocol execution.
+///
+/// All conversions of a batch share the same OLE execution and message, rather than running
+/// the protocol for each of them. Both parties must provide batches with the same conversions
+/// of the same lengths.
+#[async_trait]
+pub trait BatchShareConvert<Ctx, T> {
+    /// Converts a batch of shares, returning the outputs of each conversion in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `batch` - The conversions to perform.
+    async fn convert_batch(
+        &mut self,
+        ctx: &mut Ctx,
+        batch: Vec<Conversion<T>>,
+    ) -> Result<Vec<Vec<T>>, ShareConversionError>;
+}
+
 /// A trait for converting between additive and multiplicative shares.
 pub trait ShareConvert<Ctx, T>:
     AdditiveToMultiplicative<Ctx, T> + MultiplicativeToAdditive<Ctx, T>
@@ -62,8 +106,8 @@ impl<Ctx, T, U> ShareConvert<Ctx, T> for U where
 #[cfg(test)]
 mod tests {
     use crate::{
-        AdditiveToMultiplicative, MultiplicativeToAdditive, ShareConversionReceiver,
-        ShareConversionSender,
+        AdditiveToMultiplicative, BatchShareConvert, Conversion, MultiplicativeToAdditive,
+        ShareConversionReceiver, ShareConversionSender,
     };
     use mpz_common::executor::test_st_executor;
     use mpz_core::{prg::Prg, Block};
@@ -128,4 +172,65 @@ mod tests {
             .zip(receiver_output)
             .for_each(|(((&si, ri), so), ro)| assert_eq!(si + ri, so * ro));
     }
+
+    #[tokio::test]
+    async fn test_convert_batch() {
+        let mut rng = Prg::from_seed(Block::ZERO);
+
+        let (ole_sender, ole_receiver) = ideal_ole();
+
+        let mut sender = ShareConversionSender::new(ole_sender);
+        let mut receiver = ShareConversionReceiver::new(ole_receiver);
+
+        // Whether each conversion is A2M, and its length.
+        let conversions = [(true, 3), (false, 5), (true, 0), (false, 4), (true, 2)];
+        let mut batch = || -> Vec<Conversion<P256>> {
+            conversions
+                .iter()
+                .map(|&(a2m, len)| {
+                    let inputs = (0..len).map(|_| P256::rand(&mut rng)).collect();
+                    if a2m {
+                        Conversion::ToMultiplicative(inputs)
+                    } else {
+                        Conversion::ToAdditive(inputs)
+                    }
+                })
+                .collect()
+        };
+        let sender_input = batch();
+        let receiver_input = batch();
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(10);
+
+        let (sender_output, receiver_output) = tokio::try_join!(
+            sender.convert_batch(&mut ctx_sender, sender_input.clone()),
+            receiver.convert_batch(&mut ctx_receiver, receiver_input.clone())
+        )
+        .unwrap();
+
+        assert_eq!(sender_output.len(), conversions.len());
+        for (((si, ri), so), ro) in sender_input
+            .iter()
+            .zip(receiver_input)
+            .zip(sender_output)
+            .zip(receiver_output)
+        {
+            assert_eq!(so.len(), si.inputs().len());
+            assert_eq!(ro.len(), ri.inputs().len());
+
+            let a2m = si.is_to_multiplicative();
+            si.inputs()
+                .iter()
+                .zip(ri.inputs())
+                .zip(so)
+                .zip(ro)
+                .for_each(|(((&si, &ri), so), ro)| {
+                    if a2m {
+                        assert_eq!(si + ri, so * ro)
+                    } else {
+                        assert_eq!(si * ri, so + ro)
+                    }
+                });
+        }
+    }
 }
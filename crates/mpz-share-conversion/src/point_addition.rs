@@ -0,0 +1,307 @@
+//! Two-party addition of P-256 points.
+//!
+//! Each party holds a point, and the parties compute additive shares of the coordinates of the
+//! sum of their points without revealing them. This is the step of a two-party ECDH key exchange
+//! in which the parties combine their shares of the shared secret point, eg. where a server's
+//! public key was multiplied by each party's share of a private key.
+//!
+//! For points `P1 = (x1, y1)` and `P2 = (x2, y2)`, the sum `P3 = (x3, y3)` is computed as
+//!
+//! ```text
+//! λ = (y2 - y1) / (x2 - x1)
+//! x3 = λ^2 - x1 - x2
+//! y3 = λ(x1 - x3) - y1
+//! ```
+//!
+//! The differences are converted into multiplicative shares, which makes computing the quotient
+//! local, and the results are converted back into additive shares.
+//!
+//! The points must be distinct and must not be inverses of one another, which for honestly
+//! generated key shares only happens with negligible probability.
+
+use mpz_common::Context;
+use mpz_fields::{p256::P256, Field};
+use p256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey};
+
+use crate::{ShareConversionError, ShareConvert};
+
+/// An error for point addition.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum PointAdditionError {
+    #[error(transparent)]
+    ShareConversion(#[from] ShareConversionError),
+    #[error("invalid point: {0}")]
+    InvalidPoint(String),
+    #[error("points can not be added, they are equal or inverses of one another")]
+    Degenerate,
+}
+
+/// The role of a party in point addition.
+///
+/// The role must match the role of the party's share converter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The party uses a share conversion sender.
+    Sender,
+    /// The party uses a share conversion receiver.
+    Receiver,
+}
+
+/// Additive shares of the coordinates of a point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointShare {
+    /// The share of the x-coordinate.
+    pub x: P256,
+    /// The share of the y-coordinate.
+    pub y: P256,
+}
+
+/// Two-party point addition.
+#[derive(Debug)]
+pub struct PointAddition<C> {
+    role: Role,
+    converter: C,
+}
+
+impl<C> PointAddition<C> {
+    /// Creates a new instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of this party.
+    /// * `converter` - The share converter used to compute the sum.
+    pub fn new(role: Role, converter: C) -> Self {
+        Self { role, converter }
+    }
+
+    /// Returns the role of this party.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Returns the share converter.
+    pub fn into_inner(self) -> C {
+        self.converter
+    }
+
+    /// Computes an additive share of the x-coordinate of the sum of both parties' points.
+    ///
+    /// This is all that is required for ECDH, where the shared secret is the x-coordinate.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `point` - This party's point.
+    pub async fn compute_x<Ctx>(
+        &mut self,
+        ctx: &mut Ctx,
+        point: PublicKey,
+    ) -> Result<P256, PointAdditionError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, P256> + Send,
+    {
+        let (x, y) = coordinates(&point)?;
+        let (x3, _) = self.compute_x_inner(ctx, x, y).await?;
+
+        Ok(x3)
+    }
+
+    /// Computes additive shares of the coordinates of the sum of both parties' points.
+    ///
+    /// This requires two more rounds of share conversion than [`compute_x`](Self::compute_x).
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The thread context.
+    /// * `point` - This party's point.
+    pub async fn compute_point<Ctx>(
+        &mut self,
+        ctx: &mut Ctx,
+        point: PublicKey,
+    ) -> Result<PointShare, PointAdditionError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, P256> + Send,
+    {
+        let (x, y) = coordinates(&point)?;
+        let (x3, lambda) = self.compute_x_inner(ctx, x, y).await?;
+
+        // Shares of x1 - x3, where x1 is the sender's x-coordinate.
+        let diff = match self.role {
+            Role::Sender => x + -x3,
+            Role::Receiver => -x3,
+        };
+
+        let [diff] = self.a2m(ctx, [diff]).await?;
+        let [product] = self.m2a(ctx, [lambda * diff]).await?;
+
+        let y3 = match self.role {
+            Role::Sender => product + -y,
+            Role::Receiver => product,
+        };
+
+        Ok(PointShare { x: x3, y: y3 })
+    }
+
+    /// Computes a share of the x-coordinate of the sum, and returns it along with this party's
+    /// multiplicative share of `λ`.
+    async fn compute_x_inner<Ctx>(
+        &mut self,
+        ctx: &mut Ctx,
+        x: P256,
+        y: P256,
+    ) -> Result<(P256, P256), PointAdditionError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, P256> + Send,
+    {
+        // Shares of x2 - x1 and y2 - y1, where the sender holds P1.
+        let inputs = match self.role {
+            Role::Sender => [-x, -y],
+            Role::Receiver => [x, y],
+        };
+
+        let [x_diff, y_diff] = self.a2m(ctx, inputs).await?;
+        if x_diff == P256::zero() {
+            return Err(PointAdditionError::Degenerate);
+        }
+
+        let lambda = y_diff * x_diff.inverse();
+        let [lambda_sq] = self.m2a(ctx, [lambda * lambda]).await?;
+
+        Ok((lambda_sq + -x, lambda))
+    }
+
+    async fn a2m<Ctx, const N: usize>(
+        &mut self,
+        ctx: &mut Ctx,
+        inputs: [P256; N],
+    ) -> Result<[P256; N], PointAdditionError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, P256> + Send,
+    {
+        let outputs = self
+            .converter
+            .to_multiplicative(ctx, inputs.to_vec())
+            .await?;
+
+        Ok(outputs
+            .try_into()
+            .expect("share conversion should preserve length"))
+    }
+
+    async fn m2a<Ctx, const N: usize>(
+        &mut self,
+        ctx: &mut Ctx,
+        inputs: [P256; N],
+    ) -> Result<[P256; N], PointAdditionError>
+    where
+        Ctx: Context,
+        C: ShareConvert<Ctx, P256> + Send,
+    {
+        let outputs = self.converter.to_additive(ctx, inputs.to_vec()).await?;
+
+        Ok(outputs
+            .try_into()
+            .expect("share conversion should preserve length"))
+    }
+}
+
+/// Returns the affine coordinates of a point as field elements.
+fn coordinates(point: &PublicKey) -> Result<(P256, P256), PointAdditionError> {
+    let point = point.to_encoded_point(false);
+    let (Some(x), Some(y)) = (point.x(), point.y()) else {
+        return Err(PointAdditionError::InvalidPoint(
+            "point is not in affine form".to_string(),
+        ));
+    };
+
+    Ok((from_be_bytes(x)?, from_be_bytes(y)?))
+}
+
+/// Converts big-endian bytes into a field element.
+fn from_be_bytes(bytes: &[u8]) -> Result<P256, PointAdditionError> {
+    let mut bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| PointAdditionError::InvalidPoint("expected 32 byte coordinate".to_string()))?;
+    bytes.reverse();
+
+    P256::try_from(bytes).map_err(|err| PointAdditionError::InvalidPoint(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_common::executor::test_st_executor;
+    use mpz_ole::ideal::ideal_ole;
+    use p256::{elliptic_curve::Field as _, NonZeroScalar, Scalar};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{ShareConversionReceiver, ShareConversionSender};
+
+    fn random_point(rng: &mut StdRng) -> PublicKey {
+        let scalar = NonZeroScalar::new(Scalar::random(rng)).unwrap();
+        PublicKey::from_secret_scalar(&scalar)
+    }
+
+    fn sum(a: &PublicKey, b: &PublicKey) -> PublicKey {
+        PublicKey::from_affine((a.to_projective() + b.to_projective()).to_affine()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_point_addition() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let p1 = random_point(&mut rng);
+        let p2 = random_point(&mut rng);
+        let (expected_x, expected_y) = coordinates(&sum(&p1, &p2)).unwrap();
+
+        let (ole_sender, ole_receiver) = ideal_ole();
+        let mut sender = PointAddition::new(Role::Sender, ShareConversionSender::new(ole_sender));
+        let mut receiver =
+            PointAddition::new(Role::Receiver, ShareConversionReceiver::new(ole_receiver));
+
+        let (mut ctx_sender, mut ctx_receiver) = test_st_executor(10);
+
+        let (sender_x, receiver_x) = tokio::try_join!(
+            sender.compute_x(&mut ctx_sender, p1),
+            receiver.compute_x(&mut ctx_receiver, p2)
+        )
+        .unwrap();
+
+        assert_eq!(sender_x + receiver_x, expected_x);
+
+        let (sender_share, receiver_share) = tokio::try_join!(
+            sender.compute_point(&mut ctx_sender, p1),
+            receiver.compute_point(&mut ctx_receiver, p2)
+        )
+        .unwrap();
+
+        assert_eq!(sender_share.x + receiver_share.x, expected_x);
+        assert_eq!(sender_share.y + receiver_share.y, expected_y);
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let point = PublicKey::from_secret_scalar(&NonZeroScalar::new(Scalar::one()).unwrap());
+        let (x, _) = coordinates(&point).unwrap();
+
+        // The x-coordinate of the P-256 generator.
+        let mut expected =
+            from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296");
+        expected.reverse();
+
+        assert_eq!(x, P256::try_from(expected).unwrap());
+    }
+
+    fn from_hex(hex: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{AdditiveToMultiplicative, MultiplicativeToAdditive, ShareConversionError};
+use crate::{
+    AdditiveToMultiplicative, BatchShareConvert, Conversion, MultiplicativeToAdditive,
+    ShareConversionError,
+};
 use async_trait::async_trait;
 use mpz_common::{Allocate, Context, Preprocess};
 use mpz_fields::Field;
@@ -99,3 +102,52 @@ where
         a2m_convert_receiver(masks, ole_output).map_err(ShareConversionError::from)
     }
 }
+
+#[async_trait]
+impl<Ctx, F, T> BatchShareConvert<Ctx, F> for ShareConversionReceiver<T, F>
+where
+    T: OLEReceiver<Ctx, F> + Send,
+    F: Field + Serialize + Deserialize,
+    Ctx: Context,
+{
+    async fn convert_batch(
+        &mut self,
+        ctx: &mut Ctx,
+        batch: Vec<Conversion<F>>,
+    ) -> Result<Vec<Vec<F>>, ShareConversionError> {
+        let ole_input: Vec<F> = batch
+            .iter()
+            .flat_map(|conversion| conversion.inputs())
+            .copied()
+            .collect();
+
+        let mut ole_output = self.ole_receiver.receive(ctx, ole_input).await?.into_iter();
+
+        // The masks of all A2M conversions are received in one message.
+        let mut masks = if batch.iter().any(Conversion::is_to_multiplicative) {
+            ctx.io_mut().expect_next::<Masks<F>>().await?.masks
+        } else {
+            Vec::new()
+        }
+        .into_iter();
+
+        let mut outputs = Vec::with_capacity(batch.len());
+        for conversion in batch {
+            let len = conversion.inputs().len();
+            let ole_output: Vec<F> = ole_output.by_ref().take(len).collect();
+            match conversion {
+                Conversion::ToAdditive(_) => outputs.push(ole_output),
+                Conversion::ToMultiplicative(_) => {
+                    let a2m_masks: A2MMasks<F> = Masks {
+                        masks: masks.by_ref().take(len).collect(),
+                    }
+                    .into();
+
+                    outputs.push(a2m_convert_receiver(a2m_masks, ole_output)?);
+                }
+            }
+        }
+
+        Ok(outputs)
+    }
+}
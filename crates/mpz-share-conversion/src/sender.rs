@@ -1,4 +1,7 @@
-use crate::{AdditiveToMultiplicative, MultiplicativeToAdditive, ShareConversionError};
+use crate::{
+    AdditiveToMultiplicative, BatchShareConvert, Conversion, MultiplicativeToAdditive,
+    ShareConversionError,
+};
 use async_trait::async_trait;
 use mpz_common::{Allocate, Context, Preprocess};
 use mpz_fields::Field;
@@ -90,17 +93,7 @@ where
         ctx: &mut Ctx,
         inputs: Vec<F>,
     ) -> Result<Vec<F>, ShareConversionError> {
-        let random: Vec<F> = {
-            let mut rng = thread_rng();
-            (0..inputs.len())
-                .map(|_| loop {
-                    let rand = F::rand(&mut rng);
-                    if rand != F::zero() {
-                        break rand;
-                    }
-                })
-                .collect()
-        };
+        let random: Vec<F> = random_nonzero(inputs.len());
 
         let ole_output = self.ole_sender.send(ctx, random.clone()).await?;
         let (output, masks) = a2m_convert_sender(inputs, random, ole_output)?;
@@ -113,3 +106,73 @@ where
         Ok(output)
     }
 }
+
+#[async_trait]
+impl<Ctx, F, T> BatchShareConvert<Ctx, F> for ShareConversionSender<T, F>
+where
+    T: OLESender<Ctx, F> + Send,
+    F: Field + Serialize + Deserialize,
+    Ctx: Context,
+{
+    async fn convert_batch(
+        &mut self,
+        ctx: &mut Ctx,
+        batch: Vec<Conversion<F>>,
+    ) -> Result<Vec<Vec<F>>, ShareConversionError> {
+        let has_a2m = batch.iter().any(Conversion::is_to_multiplicative);
+
+        // The OLE inputs of all conversions, which are random for A2M.
+        let mut randoms = Vec::new();
+        let mut ole_input = Vec::new();
+        for conversion in &batch {
+            match conversion {
+                Conversion::ToAdditive(inputs) => ole_input.extend_from_slice(inputs),
+                Conversion::ToMultiplicative(inputs) => {
+                    let random: Vec<F> = random_nonzero(inputs.len());
+                    ole_input.extend_from_slice(&random);
+                    randoms.push(random);
+                }
+            }
+        }
+
+        let mut ole_output = self.ole_sender.send(ctx, ole_input).await?.into_iter();
+        let mut randoms = randoms.into_iter();
+
+        let mut outputs = Vec::with_capacity(batch.len());
+        let mut masks = Vec::new();
+        for conversion in batch {
+            let len = conversion.inputs().len();
+            let ole_output: Vec<F> = ole_output.by_ref().take(len).collect();
+            match conversion {
+                Conversion::ToAdditive(_) => outputs.push(m2a_convert(ole_output)),
+                Conversion::ToMultiplicative(inputs) => {
+                    let random = randoms.next().expect("random should be set for each A2M");
+                    let (output, a2m_masks) = a2m_convert_sender(inputs, random, ole_output)?;
+
+                    masks.extend(Masks::from(a2m_masks).masks);
+                    outputs.push(output);
+                }
+            }
+        }
+
+        // The masks of all A2M conversions are sent in one message.
+        if has_a2m {
+            ctx.io_mut().send(Masks { masks }).await?;
+        }
+
+        Ok(outputs)
+    }
+}
+
+/// Returns random non-zero field elements.
+fn random_nonzero<F: Field>(count: usize) -> Vec<F> {
+    let mut rng = thread_rng();
+    (0..count)
+        .map(|_| loop {
+            let rand = F::rand(&mut rng);
+            if rand != F::zero() {
+                break rand;
+            }
+        })
+        .collect()
+}
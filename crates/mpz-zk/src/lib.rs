@@ -0,0 +1,28 @@
+//! Zero-knowledge proofs for binary circuits.
+//!
+//! The [`mpcith`] module provides non-interactive proofs using MPC-in-the-head. Unlike the
+//! zero-knowledge mode of the garbling protocols, a proof does not require interaction with the
+//! verifier, so it can be verified by anyone and at any time. The price is the size of the
+//! proof, which grows linearly with the number of AND gates in the circuit.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+pub mod mpcith;
+
+use mpz_circuits::{types::TypeError, CircuitError};
+
+/// A zero-knowledge proof error.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ZkError {
+    #[error(transparent)]
+    Circuit(#[from] CircuitError),
+    #[error(transparent)]
+    Type(#[from] TypeError),
+    #[error("expected {expected} repetitions, got {actual}")]
+    RepetitionMismatch { expected: usize, actual: usize },
+    #[error("invalid proof: {0}")]
+    InvalidProof(&'static str),
+}
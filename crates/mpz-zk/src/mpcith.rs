@@ -0,0 +1,604 @@
+//! Non-interactive MPC-in-the-head proofs for binary circuits.
+//!
+//! This is an implementation of [ZKB++](https://eprint.iacr.org/2017/279). The prover proves
+//! that it knows inputs for which a circuit produces some public outputs.
+//!
+//! The prover secret-shares its inputs between three simulated parties, and simulates a
+//! three-party protocol evaluating the circuit in which each party derives its randomness from a
+//! seed. It commits to the view of each party, and the Fiat-Shamir transform is used to select
+//! two of the parties whose views are opened. The verifier recomputes the views of the opened
+//! parties and checks that they are consistent with each other and with the commitments.
+//!
+//! A cheating prover is caught with probability `1/3` in each repetition, so
+//! [`DEFAULT_REPETITIONS`] repetitions provide 128 bits of security. The proof contains the
+//! output of each AND gate of one party in each repetition, and XOR and INV gates are free.
+
+use blake3::Hasher;
+use itybity::IntoBits;
+use mpz_circuits::{
+    types::{BinaryRepr, TypeError, Value},
+    Circuit, CircuitError, Gate,
+};
+use mpz_core::{prg::Prg, Block};
+use rand::{thread_rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::ZkError;
+
+/// The default number of repetitions, which provides 128 bits of security.
+pub const DEFAULT_REPETITIONS: usize = 219;
+
+const COMMIT_CONTEXT: &str = "mpz-zk mpcith commitment";
+const CHALLENGE_CONTEXT: &str = "mpz-zk mpcith challenge";
+
+/// A proof that the prover knows inputs for which a circuit produces some outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proof {
+    repetitions: Vec<Repetition>,
+}
+
+impl Proof {
+    /// Returns the number of repetitions of the proof.
+    pub fn repetitions(&self) -> usize {
+        self.repetitions.len()
+    }
+}
+
+/// The opening of a single repetition, where `e` is the challenge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Repetition {
+    challenge: u8,
+    /// The seeds of parties `e` and `e + 1`.
+    seeds: [Block; 2],
+    /// The input share of party 2, if it is opened.
+    input_share: Option<Vec<u8>>,
+    /// The AND gate outputs of party `e + 1`.
+    view: Vec<u8>,
+    /// The commitment to the view of party `e + 2`.
+    commitment: [u8; 32],
+}
+
+/// MPC-in-the-head prover.
+#[derive(Debug, Clone)]
+pub struct Prover {
+    repetitions: usize,
+}
+
+impl Default for Prover {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPETITIONS)
+    }
+}
+
+impl Prover {
+    /// Creates a new prover.
+    ///
+    /// # Arguments
+    ///
+    /// * `repetitions` - The number of repetitions.
+    pub fn new(repetitions: usize) -> Self {
+        Self { repetitions }
+    }
+
+    /// Evaluates a circuit and proves that the outputs were computed from inputs known to the
+    /// prover.
+    ///
+    /// Returns the outputs of the circuit and the proof.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit.
+    /// * `inputs` - The inputs to the circuit, which are not revealed by the proof.
+    pub fn prove(&self, circ: &Circuit, inputs: &[Value]) -> Result<(Vec<Value>, Proof), ZkError> {
+        let outputs = circ.evaluate(inputs)?;
+
+        let input_bits: Vec<bool> = inputs
+            .iter()
+            .cloned()
+            .flat_map(Value::into_iter_lsb0)
+            .collect();
+        let output_bits: Vec<bool> = outputs
+            .iter()
+            .cloned()
+            .flat_map(Value::into_iter_lsb0)
+            .collect();
+
+        let mut rng = thread_rng();
+        let mut transcript = Transcript::new(circ, self.repetitions, &output_bits);
+        let mut runs = Vec::with_capacity(self.repetitions);
+        for _ in 0..self.repetitions {
+            let seeds: [Block; 3] = std::array::from_fn(|_| Block::random(&mut rng));
+            let run = Run::simulate(circ, seeds, &input_bits);
+
+            transcript.absorb(&run.output_shares, &run.commitments);
+            runs.push(run);
+        }
+
+        let repetitions = runs
+            .into_iter()
+            .zip(transcript.challenges(self.repetitions))
+            .map(|(run, challenge)| run.open(challenge))
+            .collect();
+
+        Ok((outputs, Proof { repetitions }))
+    }
+}
+
+/// MPC-in-the-head verifier.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    repetitions: usize,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPETITIONS)
+    }
+}
+
+impl Verifier {
+    /// Creates a new verifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `repetitions` - The number of repetitions a proof must have.
+    pub fn new(repetitions: usize) -> Self {
+        Self { repetitions }
+    }
+
+    /// Verifies a proof that the prover knows inputs for which the circuit produces the
+    /// outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `circ` - The circuit.
+    /// * `outputs` - The outputs of the circuit.
+    /// * `proof` - The proof.
+    pub fn verify(&self, circ: &Circuit, outputs: &[Value], proof: &Proof) -> Result<(), ZkError> {
+        if proof.repetitions.len() != self.repetitions {
+            return Err(ZkError::RepetitionMismatch {
+                expected: self.repetitions,
+                actual: proof.repetitions.len(),
+            });
+        }
+
+        if outputs.len() != circ.outputs().len() {
+            return Err(
+                CircuitError::InvalidOutputCount(circ.outputs().len(), outputs.len()).into(),
+            );
+        }
+
+        for (output, value) in circ.outputs().iter().zip(outputs) {
+            if output.value_type() != value.value_type() {
+                return Err(TypeError::UnexpectedType {
+                    expected: output.value_type(),
+                    actual: value.value_type(),
+                }
+                .into());
+            }
+        }
+
+        let output_bits: Vec<bool> = outputs
+            .iter()
+            .cloned()
+            .flat_map(Value::into_iter_lsb0)
+            .collect();
+
+        let mut transcript = Transcript::new(circ, self.repetitions, &output_bits);
+        for repetition in &proof.repetitions {
+            let (output_shares, commitments) = replay(circ, repetition, &output_bits)?;
+            transcript.absorb(&output_shares, &commitments);
+        }
+
+        let challenges = transcript.challenges(self.repetitions);
+        if challenges
+            .into_iter()
+            .zip(&proof.repetitions)
+            .any(|(challenge, repetition)| challenge != repetition.challenge)
+        {
+            return Err(ZkError::InvalidProof("challenge mismatch"));
+        }
+
+        Ok(())
+    }
+}
+
+/// A simulation of the three-party protocol by the prover.
+struct Run {
+    seeds: [Block; 3],
+    /// The input share of party 2. The input shares of the other parties are derived from
+    /// their seeds.
+    input_share: Vec<bool>,
+    /// The AND gate outputs of each party.
+    views: [Vec<bool>; 3],
+    output_shares: [Vec<bool>; 3],
+    commitments: [[u8; 32]; 3],
+}
+
+impl Run {
+    fn simulate(circ: &Circuit, seeds: [Block; 3], input_bits: &[bool]) -> Self {
+        let mut tapes = seeds.map(Tape::new);
+        let mut wires: [Vec<bool>; 3] = std::array::from_fn(|_| vec![false; circ.feed_count()]);
+
+        let mut input_share = Vec::with_capacity(input_bits.len());
+        for (node, bit) in input_nodes(circ).zip(input_bits) {
+            let shares = [tapes[0].bit(), tapes[1].bit()];
+            let share = bit ^ shares[0] ^ shares[1];
+
+            wires[0][node] = shares[0];
+            wires[1][node] = shares[1];
+            wires[2][node] = share;
+            input_share.push(share);
+        }
+
+        let mut views: [Vec<bool>; 3] = Default::default();
+        for gate in circ.gates() {
+            match *gate {
+                Gate::Xor { x, y, z } => {
+                    for wires in wires.iter_mut() {
+                        wires[z.id()] = wires[x.id()] ^ wires[y.id()];
+                    }
+                }
+                Gate::Inv { x, z } => {
+                    for (party, wires) in wires.iter_mut().enumerate() {
+                        wires[z.id()] = wires[x.id()] ^ (party == 0);
+                    }
+                }
+                Gate::And { x, y, z } => {
+                    let r: [bool; 3] = std::array::from_fn(|party| tapes[party].bit());
+                    let xs: [bool; 3] = std::array::from_fn(|party| wires[party][x.id()]);
+                    let ys: [bool; 3] = std::array::from_fn(|party| wires[party][y.id()]);
+
+                    for party in 0..3 {
+                        let next = (party + 1) % 3;
+                        let out = and_share(
+                            [xs[party], ys[party], r[party]],
+                            [xs[next], ys[next], r[next]],
+                        );
+
+                        wires[party][z.id()] = out;
+                        views[party].push(out);
+                    }
+                }
+            }
+        }
+
+        let output_shares = std::array::from_fn(|party| {
+            output_nodes(circ).map(|node| wires[party][node]).collect()
+        });
+
+        let commitments = std::array::from_fn(|party| {
+            commit(
+                &seeds[party],
+                (party == 2).then_some(input_share.as_slice()),
+                &views[party],
+            )
+        });
+
+        Self {
+            seeds,
+            input_share,
+            views,
+            output_shares,
+            commitments,
+        }
+    }
+
+    /// Opens the views of parties `challenge` and `challenge + 1`.
+    fn open(self, challenge: u8) -> Repetition {
+        let e = challenge as usize;
+        let Self {
+            seeds,
+            input_share,
+            views,
+            commitments,
+            ..
+        } = self;
+
+        Repetition {
+            challenge,
+            seeds: [seeds[e], seeds[(e + 1) % 3]],
+            // Party 2 is opened unless the challenge is 0.
+            input_share: (e != 0).then(|| pack(&input_share)),
+            view: pack(&views[(e + 1) % 3]),
+            commitment: commitments[(e + 2) % 3],
+        }
+    }
+}
+
+/// The output shares and the commitments of the three parties of a repetition.
+type Replay = ([Vec<bool>; 3], [[u8; 32]; 3]);
+
+/// Recomputes the views of the opened parties of a repetition, returning the output shares
+/// and the commitments of all parties.
+fn replay(
+    circ: &Circuit,
+    repetition: &Repetition,
+    output_bits: &[bool],
+) -> Result<Replay, ZkError> {
+    let e = repetition.challenge as usize;
+    if e >= 3 {
+        return Err(ZkError::InvalidProof("invalid challenge"));
+    }
+    let parties = [e, (e + 1) % 3];
+
+    let input_len = circ.inputs().iter().map(BinaryRepr::len).sum();
+    let input_share = match (&repetition.input_share, e != 0) {
+        (Some(share), true) => Some(unpack(share, input_len)?),
+        (None, false) => None,
+        _ => return Err(ZkError::InvalidProof("unexpected input share")),
+    };
+    let opened_view = unpack(&repetition.view, circ.and_count())?;
+
+    let mut tapes = repetition.seeds.map(Tape::new);
+    let mut wires: [Vec<bool>; 2] = std::array::from_fn(|_| vec![false; circ.feed_count()]);
+
+    for (i, node) in input_nodes(circ).enumerate() {
+        for (k, &party) in parties.iter().enumerate() {
+            wires[k][node] = match &input_share {
+                Some(share) if party == 2 => share[i],
+                _ => tapes[k].bit(),
+            };
+        }
+    }
+
+    let mut view = Vec::with_capacity(circ.and_count());
+    for gate in circ.gates() {
+        match *gate {
+            Gate::Xor { x, y, z } => {
+                for wires in wires.iter_mut() {
+                    wires[z.id()] = wires[x.id()] ^ wires[y.id()];
+                }
+            }
+            Gate::Inv { x, z } => {
+                for (wires, &party) in wires.iter_mut().zip(&parties) {
+                    wires[z.id()] = wires[x.id()] ^ (party == 0);
+                }
+            }
+            Gate::And { x, y, z } => {
+                let r = [tapes[0].bit(), tapes[1].bit()];
+                let out = and_share(
+                    [wires[0][x.id()], wires[0][y.id()], r[0]],
+                    [wires[1][x.id()], wires[1][y.id()], r[1]],
+                );
+
+                wires[0][z.id()] = out;
+                wires[1][z.id()] = opened_view[view.len()];
+                view.push(out);
+            }
+        }
+    }
+
+    let shares: [Vec<bool>; 2] =
+        std::array::from_fn(|k| output_nodes(circ).map(|node| wires[k][node]).collect());
+
+    // The output shares of the unopened party are determined by the outputs.
+    let mut output_shares: [Vec<bool>; 3] = Default::default();
+    output_shares[(e + 2) % 3] = output_bits
+        .iter()
+        .zip(shares[0].iter().zip(&shares[1]))
+        .map(|(bit, (a, b))| bit ^ a ^ b)
+        .collect();
+    let [share_e, share_next] = shares;
+    output_shares[e] = share_e;
+    output_shares[(e + 1) % 3] = share_next;
+
+    let input_share_of = |party: usize| {
+        if party == 2 {
+            input_share.as_deref()
+        } else {
+            None
+        }
+    };
+
+    let mut commitments = [[0u8; 32]; 3];
+    commitments[e] = commit(&repetition.seeds[0], input_share_of(e), &view);
+    commitments[(e + 1) % 3] = commit(
+        &repetition.seeds[1],
+        input_share_of((e + 1) % 3),
+        &opened_view,
+    );
+    commitments[(e + 2) % 3] = repetition.commitment;
+
+    Ok((output_shares, commitments))
+}
+
+/// Computes a party's share of the output of an AND gate, from its shares `[x, y, r]` and the
+/// shares of the next party.
+fn and_share(own: [bool; 3], next: [bool; 3]) -> bool {
+    let [x, y, r] = own;
+    let [x_next, y_next, r_next] = next;
+
+    (x & y) ^ (x_next & y) ^ (x & y_next) ^ r ^ r_next
+}
+
+/// Returns the ids of the input nodes of a circuit.
+fn input_nodes(circ: &Circuit) -> impl Iterator<Item = usize> + '_ {
+    circ.inputs()
+        .iter()
+        .flat_map(|input| input.iter().map(|node| node.id()))
+}
+
+/// Returns the ids of the output nodes of a circuit.
+fn output_nodes(circ: &Circuit) -> impl Iterator<Item = usize> + '_ {
+    circ.outputs()
+        .iter()
+        .flat_map(|output| output.iter().map(|node| node.id()))
+}
+
+/// The random tape of a party.
+struct Tape(Prg);
+
+impl Tape {
+    fn new(seed: Block) -> Self {
+        Self(Prg::from_seed(seed))
+    }
+
+    fn bit(&mut self) -> bool {
+        self.0.random_bool()
+    }
+}
+
+/// Commits to the view of a party.
+fn commit(seed: &Block, input_share: Option<&[bool]>, view: &[bool]) -> [u8; 32] {
+    let mut hasher = Hasher::new_derive_key(COMMIT_CONTEXT);
+    hasher.update(&seed.to_bytes());
+    if let Some(input_share) = input_share {
+        hasher.update(&pack(input_share));
+    }
+    hasher.update(&pack(view));
+
+    hasher.finalize().into()
+}
+
+/// The Fiat-Shamir transcript.
+struct Transcript(Hasher);
+
+impl Transcript {
+    fn new(circ: &Circuit, repetitions: usize, output_bits: &[bool]) -> Self {
+        let mut hasher = Hasher::new_derive_key(CHALLENGE_CONTEXT);
+        hasher.update(circ.hash().as_bytes());
+        hasher.update(&(repetitions as u64).to_le_bytes());
+        hasher.update(&pack(output_bits));
+
+        Self(hasher)
+    }
+
+    fn absorb(&mut self, output_shares: &[Vec<bool>; 3], commitments: &[[u8; 32]; 3]) {
+        for (shares, commitment) in output_shares.iter().zip(commitments) {
+            self.0.update(&pack(shares));
+            self.0.update(commitment);
+        }
+    }
+
+    /// Returns uniformly random challenges in `0..3`.
+    fn challenges(self, count: usize) -> Vec<u8> {
+        let mut reader = self.0.finalize_xof();
+        let mut challenges = Vec::with_capacity(count);
+        let mut byte = [0u8];
+        while challenges.len() < count {
+            reader.fill(&mut byte);
+            for i in 0..4 {
+                let challenge = (byte[0] >> (2 * i)) & 3;
+                if challenge < 3 && challenges.len() < count {
+                    challenges.push(challenge);
+                }
+            }
+        }
+
+        challenges
+    }
+}
+
+fn pack(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, _) in bits.iter().enumerate().filter(|(_, &bit)| bit) {
+        bytes[i / 8] |= 1 << (i % 8);
+    }
+    bytes
+}
+
+fn unpack(bytes: &[u8], len: usize) -> Result<Vec<bool>, ZkError> {
+    if bytes.len() != len.div_ceil(8) {
+        return Err(ZkError::InvalidProof("invalid length"));
+    }
+
+    Ok((0..len)
+        .map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mpz_circuits::{circuits::AES128, ops::WrappingAdd, CircuitBuilder};
+
+    fn add_circuit() -> Circuit {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>();
+        let b = builder.add_input::<u32>();
+        let c = a.wrapping_add(b);
+        builder.add_output(c);
+        builder.add_output(!c);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_prove_verify() {
+        let circ = add_circuit();
+        let (outputs, proof) = Prover::default()
+            .prove(&circ, &[1234u32.into(), 5678u32.into()])
+            .unwrap();
+
+        assert_eq!(outputs, vec![Value::from(6912u32), Value::from(!6912u32)]);
+        assert_eq!(proof.repetitions(), DEFAULT_REPETITIONS);
+
+        Verifier::default().verify(&circ, &outputs, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_prove_verify_aes() {
+        let key = [42u8; 16];
+        let msg = [69u8; 16];
+
+        let (outputs, proof) = Prover::new(16)
+            .prove(&AES128, &[key.into(), msg.into()])
+            .unwrap();
+
+        Verifier::new(16).verify(&AES128, &outputs, &proof).unwrap();
+    }
+
+    #[test]
+    fn test_verify_wrong_outputs() {
+        let circ = add_circuit();
+        let (_, proof) = Prover::default()
+            .prove(&circ, &[1u32.into(), 2u32.into()])
+            .unwrap();
+
+        let err = Verifier::default()
+            .verify(&circ, &[4u32.into(), (!4u32).into()], &proof)
+            .unwrap_err();
+
+        assert!(matches!(err, ZkError::InvalidProof(_)));
+    }
+
+    #[test]
+    fn test_verify_tampered_proof() {
+        let circ = add_circuit();
+        let (outputs, proof) = Prover::default()
+            .prove(&circ, &[1u32.into(), 2u32.into()])
+            .unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.repetitions[0].view[0] ^= 1;
+        assert!(Verifier::default()
+            .verify(&circ, &outputs, &tampered)
+            .is_err());
+
+        let mut tampered = proof.clone();
+        tampered.repetitions[0].commitment[0] ^= 1;
+        assert!(Verifier::default()
+            .verify(&circ, &outputs, &tampered)
+            .is_err());
+
+        let mut tampered = proof;
+        tampered.repetitions[0].challenge = (tampered.repetitions[0].challenge + 1) % 3;
+        assert!(Verifier::default()
+            .verify(&circ, &outputs, &tampered)
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_repetition_mismatch() {
+        let circ = add_circuit();
+        let (outputs, proof) = Prover::new(10)
+            .prove(&circ, &[1u32.into(), 2u32.into()])
+            .unwrap();
+
+        assert!(matches!(
+            Verifier::default().verify(&circ, &outputs, &proof),
+            Err(ZkError::RepetitionMismatch { .. })
+        ));
+    }
+}
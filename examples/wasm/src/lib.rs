@@ -0,0 +1,89 @@
+//! Browser example for mpz.
+//!
+//! Runs both parties of a small two-party computation in the browser: the evaluator obtains
+//! the labels of its input from the generator using CO15 base OT, after which the generator
+//! garbles an 8-bit adder and the evaluator evaluates it. In practical situations the messages
+//! would be exchanged over a channel such as a WebSocket, for simplicity they are passed in
+//! memory here.
+
+use mpz_circuits::{ops::WrappingAdd, CircuitBuilder};
+use mpz_core::Block;
+use mpz_garble_core::{
+    encoding_state, ChaChaEncoder, EncodedValue, Encoder, Evaluator, Generator, Label,
+};
+use mpz_ot_core::chou_orlandi::{Receiver, Sender};
+use wasm_bindgen::prelude::*;
+
+/// Computes `a + b` securely, where `a` is the generator's input and `b` is the evaluator's
+/// input.
+#[wasm_bindgen]
+pub fn add(a: u8, b: u8) -> Result<u8, JsError> {
+    let builder = CircuitBuilder::new();
+    let x = builder.add_input::<u8>();
+    let y = builder.add_input::<u8>();
+    let z = x.wrapping_add(y);
+    builder.add_output(z);
+    let circ = builder.build()?;
+
+    // The generator encodes the inputs of the circuit.
+    let encoder = ChaChaEncoder::new(rand::random());
+    let full_inputs: Vec<EncodedValue<encoding_state::Full>> = circ
+        .inputs()
+        .iter()
+        .map(|input| encoder.encode_by_type(0, &input.value_type()))
+        .collect();
+
+    // The generator sends the active encoding of its own input directly.
+    let active_a = full_inputs[0].clone().select(a)?;
+
+    // The evaluator obtains the active encoding of its input using base OT, choosing with the
+    // bits of its input in LSB0 order.
+    let (sender_setup, mut sender) = Sender::default().setup();
+    let mut receiver = Receiver::default().setup(sender_setup);
+
+    let choices: Vec<bool> = (0..8).map(|i| (b >> i) & 1 == 1).collect();
+    let receiver_payload = receiver.receive_random(&choices);
+
+    let labels: Vec<[Block; 2]> = full_inputs[1].iter_blocks().collect();
+    let sender_payload = sender.send(&labels, receiver_payload)?;
+
+    let received: Vec<Label> = receiver
+        .receive(sender_payload)?
+        .into_iter()
+        .map(Label::new)
+        .collect();
+    let active_b = EncodedValue::<encoding_state::Active>::from_labels(
+        full_inputs[1].value_type(),
+        &received,
+    )?;
+
+    // The generator garbles the circuit and the evaluator evaluates it.
+    let mut gen = Generator::default();
+    let mut ev = Evaluator::default();
+
+    let mut gen_iter = gen.generate_batched(&circ, encoder.delta(), full_inputs)?;
+    let mut ev_consumer = ev.evaluate_batched(&circ, vec![active_a, active_b])?;
+
+    for batch in gen_iter.by_ref() {
+        ev_consumer.next(batch);
+    }
+
+    let full_outputs = gen_iter.finish()?.outputs;
+    let active_outputs = ev_consumer.finish()?.outputs;
+
+    // The generator sends the decoding information of the output to the evaluator.
+    let output = active_outputs[0].decode(&full_outputs[0].decoding())?;
+
+    Ok(output.try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        assert_eq!(add(3, 4).unwrap(), 7);
+        assert_eq!(add(255, 2).unwrap(), 1);
+    }
+}